@@ -1,4 +1,5 @@
 import fetch, { type RequestInit, type Response } from 'node-fetch'
+import * as http from 'http'
 import * as path from 'path'
 import * as os from 'os'
 import * as fs from 'fs'
@@ -25,6 +26,7 @@ export interface Session {
   ready_to_merge: boolean
   original_agent_type?: string
   original_skip_permissions?: boolean
+  agent_model?: string
   pending_name_generation: boolean
   was_auto_generated: boolean
 }
@@ -42,12 +44,62 @@ export interface SpecContent {
   content: string
   content_length: number
   updated_at: string
+  revision: number
 }
 
 interface SpecSummaryResponse {
   specs: SpecSummary[]
 }
 
+export interface SpecEdit {
+  find: string
+  replace: string
+}
+
+export interface SpecRevision {
+  revision: number
+  content: string
+  source: 'editor' | 'api'
+  summary?: string | null
+  created_at: string
+}
+
+export interface SpecRevisionProposal {
+  baseRevision?: number
+  edits?: SpecEdit[]
+  content?: string
+  summary?: string
+}
+
+interface SpecRevisionListResponse {
+  revisions: SpecRevision[]
+}
+
+export interface SessionQueryOptions {
+  fields: string[]
+  sessions?: string[]
+  state?: 'spec' | 'running' | 'reviewed'
+}
+
+export interface SessionQueryResult {
+  sessions: Array<Record<string, unknown> & { name: string }>
+  missing: string[]
+}
+
+export interface LoggedEvent {
+  seq: number
+  event: string
+  payload: unknown
+  created_at: string
+}
+
+export interface EventReplay {
+  events: LoggedEvent[]
+  latest_seq: number
+  has_more: boolean
+  missed_events: boolean
+}
+
 interface GitStatusResult {
   hasUncommittedChanges: boolean
   modifiedFiles: number
@@ -226,15 +278,18 @@ function createProjectContext(projectPath: string): ProjectContext {
 export class SchaltwerkBridge {
   private readonly projectContext: ProjectContext
   private readonly portCandidates: number[]
+  private readonly socketAgent: http.Agent | null
   private activePort: number | null = null
   private readonly host = '127.0.0.1'
   private hasLoggedPort = false
+  private hasLoggedSocket = false
 
   constructor() {
     // Detect and establish project context
     const projectPath = detectProjectPath()
     this.projectContext = createProjectContext(projectPath)
     this.portCandidates = this.resolveCandidatePorts()
+    this.socketAgent = this.createSocketAgent()
     
     console.error(`MCP Bridge initialized for project: ${this.projectContext.name}`)
     console.error(`Project path: ${this.projectContext.canonicalPath}`)
@@ -250,6 +305,14 @@ export class SchaltwerkBridge {
     }
   }
 
+  // Scoped to this project so the app routes the event to it even when another project is active
+  private webhookPath(route: string): string {
+    if (this.projectContext.hash === 'unknown') {
+      return `/webhook/${route}`
+    }
+    return `/projects/${this.projectContext.hash}/webhook/${route}`
+  }
+
   private calculateBasePort(): number {
     try {
       const digest = createHash('sha256')
@@ -293,6 +356,15 @@ export class SchaltwerkBridge {
     return ports
   }
 
+  // Schaltwerk also serves the API on a unix socket, which never collides with other projects' ports
+  private createSocketAgent(): http.Agent | null {
+    const socketPath = process.env.SCHALTWERK_MCP_SOCKET
+    if (!socketPath || process.platform === 'win32') {
+      return null
+    }
+    return new http.Agent({ socketPath } as http.AgentOptions)
+  }
+
   private getPortAttemptOrder(): number[] {
     if (this.activePort === null) {
       return [...this.portCandidates]
@@ -303,6 +375,11 @@ export class SchaltwerkBridge {
 
   private cloneInit(init: RequestInit): RequestInit {
     const headers = init.headers ? { ...(init.headers as Record<string, string>) } : undefined
+    // Schaltwerk rejects requests without the token it issued for this project
+    const token = process.env.SCHALTWERK_MCP_TOKEN
+    if (token) {
+      return { ...init, headers: { ...headers, Authorization: `Bearer ${token}` } }
+    }
     return { ...init, headers }
   }
 
@@ -312,11 +389,27 @@ export class SchaltwerkBridge {
     }
 
     const err = error as { code?: string }
-    const retryable = new Set(['ECONNREFUSED', 'ECONNRESET', 'EHOSTUNREACH', 'ENETUNREACH'])
+    const retryable = new Set(['ECONNREFUSED', 'ECONNRESET', 'EHOSTUNREACH', 'ENETUNREACH', 'ENOENT'])
     return !!err.code && retryable.has(err.code)
   }
 
   private async fetchWithAutoPort(path: string, init: RequestInit): Promise<Response> {
+    if (this.socketAgent) {
+      try {
+        const response = await fetch(`http://localhost${path}`, { ...this.cloneInit(init), agent: this.socketAgent })
+        if (!this.hasLoggedSocket) {
+          console.error(`Schaltwerk MCP bridge connected to socket ${process.env.SCHALTWERK_MCP_SOCKET}`)
+          this.hasLoggedSocket = true
+        }
+        return response
+      } catch (error) {
+        if (!this.isRetryableNetworkError(error)) {
+          throw error
+        }
+        // The app may run without the socket, e.g. when another instance holds it
+      }
+    }
+
     const attempts = this.getPortAttemptOrder()
     let lastError: unknown = null
 
@@ -476,7 +569,7 @@ export class SchaltwerkBridge {
     }
   }
 
-  async createSession(name: string, prompt?: string, baseBranch?: string, agentType?: string, skipPermissions?: boolean): Promise<Session> {
+  async createSession(name: string, prompt?: string, baseBranch?: string, agentType?: string, skipPermissions?: boolean, agentModel?: string): Promise<Session> {
     try {
       const response = await this.fetchWithAutoPort('/api/sessions', {
         method: 'POST',
@@ -490,20 +583,28 @@ export class SchaltwerkBridge {
           base_branch: baseBranch,
           agent_type: agentType,
           skip_permissions: skipPermissions,
+          agent_model: agentModel,
           user_edited_name: false
         })
       })
 
+      // Validation failures carry a machine readable code and the offending field
       if (!response.ok) {
-        throw new Error(`Failed to create session: ${response.statusText}`)
+        const body = await response.text()
+        let detail = this.extractErrorMessage(body) || response.statusText
+        try {
+          const parsed = JSON.parse(body) as { code?: string; field?: string }
+          if (parsed.code) {
+            detail = `${parsed.code}${parsed.field ? ` (${parsed.field})` : ''}: ${detail}`
+          }
+        } catch {
+          // Plain text errors are used as they are
+        }
+        throw new Error(`Failed to create session: ${detail}`)
       }
 
-      const session = await response.json() as Session
-
-      // Notify Schaltwerk UI about the new session
-      await this.notifySessionAdded(session)
-
-      return session
+      // Schaltwerk announces the new session to the UI itself
+      return await response.json() as Session
     } catch (error) {
       console.error('Failed to create session via API:', error)
       throw error
@@ -793,7 +894,7 @@ export class SchaltwerkBridge {
         parent_branch: session.parent_branch
       }
       
-      await this.fetchWithAutoPort('/webhook/session-added', {
+      await this.fetchWithAutoPort(this.webhookPath('session-added'), {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json'
@@ -814,7 +915,7 @@ export class SchaltwerkBridge {
         status: 'spec'
       }
       
-      await this.fetchWithAutoPort('/webhook/spec-created', {
+      await this.fetchWithAutoPort(this.webhookPath('spec-created'), {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json'
@@ -832,7 +933,7 @@ export class SchaltwerkBridge {
         session_name: sessionName
       }
       
-      await this.fetchWithAutoPort('/webhook/session-removed', {
+      await this.fetchWithAutoPort(this.webhookPath('session-removed'), {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json'
@@ -845,6 +946,7 @@ export class SchaltwerkBridge {
   }
 
   private async notifyFollowUpMessage(sessionName: string, message: string): Promise<void> {
+    let response: Response
     try {
       const payload = {
         session_name: sessionName,
@@ -852,7 +954,7 @@ export class SchaltwerkBridge {
         timestamp: Date.now()
       }
       
-      await this.fetchWithAutoPort('/webhook/follow-up-message', {
+      response = await this.fetchWithAutoPort(this.webhookPath('follow-up-message'), {
         method: 'POST',
         headers: {
           'Content-Type': 'application/json'
@@ -861,6 +963,13 @@ export class SchaltwerkBridge {
       })
     } catch (error) {
       console.warn('Failed to notify follow-up message:', error)
+      return
+    }
+
+    // Oversized messages (413) and request floods (429) are refused; tell the agent why
+    if (!response.ok) {
+      const reason = this.extractErrorMessage(await response.text())
+      throw new Error(`Follow-up message for '${sessionName}' was rejected: ${response.status} ${response.statusText}${reason ? ` - ${reason}` : ''}`)
     }
   }
 
@@ -1011,6 +1120,93 @@ export class SchaltwerkBridge {
     return this.parseJsonResponse<SpecContent>(response, 'spec content')
   }
 
+  async listSpecRevisions(sessionName: string): Promise<SpecRevision[]> {
+    const response = await this.fetchWithAutoPort(`/api/specs/${encodeURIComponent(sessionName)}/revisions`, {
+      method: 'GET',
+      headers: {
+        Accept: 'application/json',
+        ...this.getProjectHeaders()
+      }
+    })
+
+    const payload = await this.parseJsonResponse<SpecRevisionListResponse>(response, 'spec revisions')
+    return payload?.revisions ?? []
+  }
+
+  async proposeSpecRevision(sessionName: string, proposal: SpecRevisionProposal): Promise<SpecRevision> {
+    const requestBody: Record<string, unknown> = {}
+    if (proposal.baseRevision !== undefined) {
+      requestBody.base_revision = proposal.baseRevision
+    }
+    if (proposal.edits && proposal.edits.length > 0) {
+      requestBody.edits = proposal.edits
+    }
+    if (proposal.content !== undefined) {
+      requestBody.content = proposal.content
+    }
+    if (proposal.summary) {
+      requestBody.summary = proposal.summary
+    }
+
+    const response = await this.fetchWithAutoPort(`/api/specs/${encodeURIComponent(sessionName)}/revisions`, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        ...this.getProjectHeaders()
+      },
+      body: JSON.stringify(requestBody)
+    })
+
+    const responseBody = await response.text()
+    if (!response.ok) {
+      const message = this.extractErrorMessage(responseBody)
+      throw new Error(`Failed to revise spec '${sessionName}': ${response.status} ${response.statusText}${message ? ` - ${message}` : ''}`)
+    }
+
+    return JSON.parse(responseBody) as SpecRevision
+  }
+
+  async querySessions(options: SessionQueryOptions): Promise<SessionQueryResult> {
+    const requestBody: Record<string, unknown> = { fields: options.fields }
+    if (options.sessions && options.sessions.length > 0) {
+      requestBody.sessions = options.sessions
+    }
+    if (options.state) {
+      requestBody.state = options.state
+    }
+
+    const response = await this.fetchWithAutoPort('/api/query', {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        Accept: 'application/json',
+        ...this.getProjectHeaders()
+      },
+      body: JSON.stringify(requestBody)
+    })
+
+    const payload = await this.parseJsonResponse<SessionQueryResult>(response, 'session query')
+    return payload ?? { sessions: [], missing: [] }
+  }
+
+  async replayEvents(sinceSeq = 0, limit?: number): Promise<EventReplay> {
+    const params = new URLSearchParams({ since_seq: String(sinceSeq) })
+    if (limit !== undefined) {
+      params.set('limit', String(limit))
+    }
+
+    const response = await this.fetchWithAutoPort(`/api/events/replay?${params.toString()}`, {
+      method: 'GET',
+      headers: {
+        Accept: 'application/json',
+        ...this.getProjectHeaders()
+      }
+    })
+
+    const payload = await this.parseJsonResponse<EventReplay>(response, 'event replay')
+    return payload ?? { events: [], latest_seq: sinceSeq, has_more: false, missed_events: false }
+  }
+
   async listSessionsByState(filter?: 'all' | 'active' | 'spec' | 'reviewed'): Promise<Session[]> {
     try {
       if (filter === 'spec') {
@@ -1111,18 +1307,32 @@ export class SchaltwerkBridge {
     }
   }
 
-  async markSessionReviewed(sessionName: string): Promise<void> {
+  async markSessionReviewed(
+    sessionName: string,
+    options: { autoCommit?: boolean; commitMessage?: string } = {}
+  ): Promise<void> {
     try {
+      const requestBody: Record<string, unknown> = {}
+      if (options.autoCommit) {
+        requestBody.auto_commit = true
+        const commitMessage = options.commitMessage?.trim()
+        if (commitMessage) {
+          requestBody.commit_message = commitMessage
+        }
+      }
+
       const response = await this.fetchWithAutoPort(`/api/sessions/${encodeURIComponent(sessionName)}/mark-reviewed`, {
         method: 'POST',
         headers: { 
           'Content-Type': 'application/json',
           ...this.getProjectHeaders()
-        }
+        },
+        ...(Object.keys(requestBody).length > 0 ? { body: JSON.stringify(requestBody) } : {})
       })
 
       if (!response.ok) {
-        throw new Error(`Failed to mark session as reviewed: ${response.statusText}`)
+        const reason = await response.text().catch(() => '')
+        throw new Error(`Failed to mark session as reviewed: ${response.statusText}${reason ? ` - ${reason}` : ''}`)
       }
     } catch (error) {
       console.error('Failed to mark session as reviewed via API:', error)
@@ -1132,7 +1342,12 @@ export class SchaltwerkBridge {
 
   async mergeSession(
     sessionName: string,
-    options: { commitMessage?: string | null; mode?: MergeModeOption; cancelAfterMerge?: boolean }
+    options: {
+      commitMessage?: string | null
+      mode?: MergeModeOption
+      cancelAfterMerge?: boolean
+      confirmProtectedPaths?: boolean
+    }
   ): Promise<MergeSessionResult> {
     const mode: MergeModeOption = options.mode === 'reapply' ? 'reapply' : 'squash'
     const commitMessage = options.commitMessage?.trim()
@@ -1150,6 +1365,10 @@ export class SchaltwerkBridge {
       requestBody.commit_message = commitMessage
     }
 
+    if (options.confirmProtectedPaths) {
+      requestBody.confirm_protected_paths = true
+    }
+
     const response = await this.fetchWithAutoPort(`/api/sessions/${encodeURIComponent(sessionName)}/merge`, {
       method: 'POST',
       headers: {
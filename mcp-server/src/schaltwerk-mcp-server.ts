@@ -11,12 +11,15 @@ import {
   McpError,
   CallToolRequest,
 } from "@modelcontextprotocol/sdk/types.js"
-import { SchaltwerkBridge, Session, MergeModeOption } from "./schaltwerk-bridge.js"
+import { SchaltwerkBridge, Session, MergeModeOption, SessionQueryOptions, SpecEdit } from "./schaltwerk-bridge.js"
 
 interface SchaltwerkStartArgs {
   name?: string
   prompt?: string
-  agent_type?: 'claude' | 'opencode' | 'gemini' | 'codex' | 'qwen' | 'droid' | 'amp'
+  // A built-in agent or the id of a custom agent
+  agent_type?: string
+  // Model passed to the agent, e.g. opus or o3
+  agent_model?: string
   base_branch?: string
   skip_permissions?: boolean
   is_draft?: boolean
@@ -57,7 +60,8 @@ interface SchaltwerkCurrentSpecUpdateArgs {
 
 interface SchaltwerkDraftStartArgs {
   session_name: string
-  agent_type?: 'claude' | 'opencode' | 'gemini' | 'codex' | 'qwen' | 'droid' | 'amp'
+  // A built-in agent or the id of a custom agent
+  agent_type?: string
   skip_permissions?: boolean
   base_branch?: string
 }
@@ -72,6 +76,8 @@ interface SchaltwerkDraftDeleteArgs {
 
 interface SchaltwerkMarkReviewedArgs {
   session_name: string
+  auto_commit?: boolean
+  commit_message?: string
 }
 
 interface SchaltwerkConvertToSpecArgs {
@@ -83,6 +89,7 @@ interface SchaltwerkMergeArgs {
   commit_message?: string | null
   mode?: 'squash' | 'reapply'
   cancel_after_merge?: boolean
+  confirm_protected_paths?: boolean
 }
 
 interface SchaltwerkCreatePrArgs {
@@ -123,7 +130,7 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
     tools: [
       {
         name: "schaltwerk_create",
-        description: `Create a new Schaltwerk session and matching git worktree for an AI agent. Provide a unique session name plus a specific, implementation-focused prompt; that prompt seeds the agent. Optional fields let you select agent_type (claude, opencode, gemini, codex, qwen, droid), choose a base_branch, or bypass manual permission prompts when you understand the risk. Use this whenever you need a fresh, isolated development branch.`,
+        description: `Create a new Schaltwerk session and matching git worktree for an AI agent. Provide a unique session name plus a specific, implementation-focused prompt; that prompt seeds the agent. Optional fields let you select agent_type (claude, opencode, gemini, codex, qwen, droid, amp, aider, goose, or a custom agent id), choose a base_branch, or bypass manual permission prompts when you understand the risk. Use this whenever you need a fresh, isolated development branch.`,
         inputSchema: {
           type: "object",
           properties: {
@@ -137,8 +144,11 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
             },
             agent_type: {
             type: "string",
-            enum: ["claude", "opencode", "gemini", "codex", "qwen", "droid", "amp"],
-            description: "AI agent type to use (default: claude)"
+            description: "AI agent type to use (default: claude): claude, opencode, gemini, codex, qwen, droid, amp, aider, goose, or the id of a custom agent defined in settings or .schaltwerk/agents.toml"
+            },
+            agent_model: {
+              type: "string",
+              description: "Model the agent runs with, e.g. sonnet or opus for claude, o3 for codex (default: the agent's own default). Use different models across sessions with the same prompt to compare them"
             },
             base_branch: {
               type: "string",
@@ -173,6 +183,61 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
           additionalProperties: false
         }
       },
+      {
+        name: "schaltwerk_query",
+        description: `Fetch only the fields you need for many sessions in one call, e.g. branch, diff_stats, terminals and spec_content together, instead of listing sessions and reading each one. Omit sessions to get every session; names that match nothing are reported under missing.`,
+        inputSchema: {
+          type: "object",
+          properties: {
+            fields: {
+              type: "array",
+              items: {
+                type: "string",
+                enum: [
+                  "display_name", "branch", "base_branch", "worktree_path", "status", "session_state",
+                  "ready_to_merge", "created_at", "last_modified", "original_agent_type", "current_task",
+                  "has_uncommitted_changes", "has_conflicts", "diff_stats", "base_freshness", "remote_status",
+                  "worktree_size_bytes", "labels", "depends_on", "blocked_by", "spec_content", "terminals"
+                ]
+              },
+              description: "Fields to return per session; name is always included"
+            },
+            sessions: {
+              type: "array",
+              items: { type: "string" },
+              description: "Session names to return (default: all sessions)"
+            },
+            state: {
+              type: "string",
+              enum: ["spec", "running", "reviewed"],
+              description: "Only return sessions in this state"
+            }
+          },
+          required: ["fields"],
+          additionalProperties: false
+        }
+      },
+      {
+        name: "schaltwerk_events_replay",
+        description: `Catch up on session state changes (sessions added, removed, renamed, merged, crashed, follow-ups delivered) that happened while you were not listening. Pass the highest seq you have seen; events come back oldest first. Repeat with the last seq while has_more is true. If missed_events is true, older events were pruned and you should re-read state with schaltwerk_list or schaltwerk_query instead.`,
+        inputSchema: {
+          type: "object",
+          properties: {
+            since_seq: {
+              type: "integer",
+              minimum: 0,
+              description: "Last event seq you have seen (default: 0, the whole log)"
+            },
+            limit: {
+              type: "integer",
+              minimum: 1,
+              maximum: 2000,
+              description: "Maximum number of events to return (default: 500)"
+            }
+          },
+          additionalProperties: false
+        }
+      },
       {
         name: "schaltwerk_send_message",
         description: `Push a follow-up message into an existing session's agent terminal. The session must exist and be running; the server validates this before sending. Messages queue until the terminal is ready, so you can safely issue reminders or extra instructions.`,
@@ -297,6 +362,61 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
           additionalProperties: false
         }
       },
+      {
+        name: "schaltwerk_spec_propose_revision",
+        description: `Propose a change to a spec and store it as the spec's next revision. Read the spec with schaltwerk_spec_read first and pass its revision as base_revision; the proposal is refused when the spec changed in the meantime, so re-read and retry. Send either search-and-replace edits (each find must occur exactly once) or the full new content.`,
+        inputSchema: {
+          type: "object",
+          properties: {
+            session: {
+              type: "string",
+              description: "Spec session name"
+            },
+            base_revision: {
+              type: "number",
+              description: "Revision reported by schaltwerk_spec_read that the proposal is based on"
+            },
+            edits: {
+              type: "array",
+              description: "Search-and-replace edits applied in order",
+              items: {
+                type: "object",
+                properties: {
+                  find: { type: "string", description: "Exact text to replace; must occur once" },
+                  replace: { type: "string", description: "Replacement text" }
+                },
+                required: ["find", "replace"],
+                additionalProperties: false
+              }
+            },
+            content: {
+              type: "string",
+              description: "Full new Markdown content, instead of edits"
+            },
+            summary: {
+              type: "string",
+              description: "Short description of the change for the revision history"
+            }
+          },
+          required: ["session"],
+          additionalProperties: false
+        }
+      },
+      {
+        name: "schaltwerk_spec_revisions",
+        description: `List the stored revisions of a spec, oldest first, with who wrote each one (editor or api) and its summary.`,
+        inputSchema: {
+          type: "object",
+          properties: {
+            session: {
+              type: "string",
+              description: "Spec session name"
+            }
+          },
+          required: ["session"],
+          additionalProperties: false
+        }
+      },
       {
         name: "schaltwerk_diff_summary",
         description: `List changed files for a session (or orchestrator when session is omitted) using merge-base(HEAD, parent_branch) semantics. Supports pagination through cursor and page_size and mirrors the desktop diff summary.` ,
@@ -375,8 +495,7 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
             },
             agent_type: {
             type: "string",
-            enum: ["claude", "opencode", "gemini", "codex", "qwen", "droid", "amp"],
-            description: "AI agent type to use (default: claude)"
+            description: "AI agent type to use (default: claude): claude, opencode, gemini, codex, qwen, droid, amp, aider, goose, or the id of a custom agent defined in settings or .schaltwerk/agents.toml"
             },
             skip_permissions: {
               type: "boolean",
@@ -429,6 +548,14 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
             session_name: {
               type: "string",
               description: "Name of the running session to mark as reviewed"
+            },
+            auto_commit: {
+              type: "boolean",
+              description: "Commit pending worktree changes before marking the session reviewed. Without it, uncommitted changes are left out of a later merge."
+            },
+            commit_message: {
+              type: "string",
+              description: "Commit message for auto_commit. Defaults to 'Complete development work for <session>'."
             }
           },
           required: ["session_name"]
@@ -450,7 +577,7 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
       },
       {
         name: "schaltwerk_merge_session",
-        description: `Merge a reviewed session back onto its parent branch using the same pipeline as the desktop app. Run this only after the session is reviewed, clean, and tests are green. Optional parameters select the merge mode (squash or reapply), supply the squash commit_message, and request cancel_after_merge to queue worktree cleanup. The tool rejects spec sessions, unresolved conflicts, and empty merges, and it never runs tests for you.`,
+        description: `Merge a reviewed session back onto its parent branch using the same pipeline as the desktop app. Run this only after the session is reviewed, clean, and tests are green. Optional parameters select the merge mode (squash or reapply), supply the squash commit_message, and request cancel_after_merge to queue worktree cleanup. The tool rejects spec sessions, unresolved conflicts, and empty merges, and it never runs tests for you. Sessions that change the project's protected paths are refused unless confirm_protected_paths is set; only set it after the user has approved those changes.`,
         inputSchema: {
           type: "object",
           properties: {
@@ -470,6 +597,10 @@ server.setRequestHandler(ListToolsRequestSchema, async () => {
             cancel_after_merge: {
               type: "boolean",
               description: "Queue session cancellation after a successful merge (default false)."
+            },
+            confirm_protected_paths: {
+              type: "boolean",
+              description: "Acknowledge that the session changes protected paths configured for the project (default false)."
             }
           },
           required: ["session_name"]
@@ -571,6 +702,43 @@ server.setRequestHandler(CallToolRequestSchema, async (request: CallToolRequest)
         break
       }
 
+      case "schaltwerk_spec_propose_revision": {
+        const revisionArgs = args as {
+          session?: string
+          base_revision?: number
+          edits?: SpecEdit[]
+          content?: string
+          summary?: string
+        }
+        if (!revisionArgs.session || revisionArgs.session.trim().length === 0) {
+          throw new McpError(ErrorCode.InvalidParams, "'session' is required when invoking schaltwerk_spec_propose_revision.")
+        }
+        const hasEdits = Array.isArray(revisionArgs.edits) && revisionArgs.edits.length > 0
+        if (hasEdits === (revisionArgs.content !== undefined)) {
+          throw new McpError(ErrorCode.InvalidParams, "Pass either 'edits' or 'content' to schaltwerk_spec_propose_revision.")
+        }
+        const revision = await bridge.proposeSpecRevision(revisionArgs.session, {
+          baseRevision: revisionArgs.base_revision,
+          edits: revisionArgs.edits,
+          content: revisionArgs.content,
+          summary: revisionArgs.summary,
+        })
+        result = JSON.stringify(revision, null, 2)
+        resultMimeType = "application/json"
+        break
+      }
+
+      case "schaltwerk_spec_revisions": {
+        const specArgs = args as { session?: string }
+        if (!specArgs.session || specArgs.session.trim().length === 0) {
+          throw new McpError(ErrorCode.InvalidParams, "'session' is required when invoking schaltwerk_spec_revisions.")
+        }
+        const revisions = await bridge.listSpecRevisions(specArgs.session)
+        result = JSON.stringify({ revisions }, null, 2)
+        resultMimeType = "application/json"
+        break
+      }
+
       case "schaltwerk_diff_summary": {
         const diffArgs = args as { session?: string; cursor?: string; page_size?: number }
         const payload = await bridge.getDiffSummary({
@@ -637,20 +805,40 @@ server.setRequestHandler(CallToolRequestSchema, async (request: CallToolRequest)
             createArgs.prompt,
             createArgs.base_branch,
             createArgs.agent_type,
-            createArgs.skip_permissions
+            createArgs.skip_permissions,
+            createArgs.agent_model
           )
 
           result = `Session created successfully:
 - Name: ${session.name}
 - Branch: ${session.branch}
 - Worktree: ${session.worktree_path}
-- Agent: ${createArgs.agent_type || 'claude'}
+- Agent: ${createArgs.agent_type || 'claude'}${createArgs.agent_model ? ` (model: ${createArgs.agent_model})` : ''}
 - Base Branch: ${session.parent_branch}
 ${session.initial_prompt ? `- Initial Prompt: ${session.initial_prompt}` : ''}`
         }
         break
       }
 
+      case "schaltwerk_query": {
+        const queryArgs = args as SessionQueryOptions
+        if (!Array.isArray(queryArgs.fields) || queryArgs.fields.length === 0) {
+          throw new McpError(ErrorCode.InvalidParams, "'fields' must list at least one field when invoking schaltwerk_query.")
+        }
+        const payload = await bridge.querySessions(queryArgs)
+        result = JSON.stringify(payload, null, 2)
+        resultMimeType = "application/json"
+        break
+      }
+
+      case "schaltwerk_events_replay": {
+        const replayArgs = args as { since_seq?: number; limit?: number }
+        const payload = await bridge.replayEvents(replayArgs.since_seq ?? 0, replayArgs.limit)
+        result = JSON.stringify(payload, null, 2)
+        resultMimeType = "application/json"
+        break
+      }
+
       case "schaltwerk_list": {
         const listArgs = args as SchaltwerkListArgs
         
@@ -943,7 +1131,10 @@ ${session.initial_prompt ? `- Initial Prompt: ${session.initial_prompt}` : ''}`
        case "schaltwerk_mark_session_reviewed": {
          const markReviewedArgs = args as unknown as SchaltwerkMarkReviewedArgs
 
-         await bridge.markSessionReviewed(markReviewedArgs.session_name)
+         await bridge.markSessionReviewed(markReviewedArgs.session_name, {
+           autoCommit: markReviewedArgs.auto_commit,
+           commitMessage: markReviewedArgs.commit_message
+         })
 
        result = `Session '${markReviewedArgs.session_name}' has been marked as reviewed and is ready for merge`
         break
@@ -975,7 +1166,8 @@ ${session.initial_prompt ? `- Initial Prompt: ${session.initial_prompt}` : ''}`
         const mergeResult = await bridge.mergeSession(mergeArgs.session_name, {
           commitMessage: trimmedCommit.length > 0 ? trimmedCommit : undefined,
           mode: requestedMode,
-          cancelAfterMerge: mergeArgs.cancel_after_merge
+          cancelAfterMerge: mergeArgs.cancel_after_merge,
+          confirmProtectedPaths: mergeArgs.confirm_protected_paths
         })
 
         const cancelLine = mergeResult.cancelRequested
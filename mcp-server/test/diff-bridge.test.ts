@@ -249,3 +249,86 @@ describe('SchaltwerkBridge spec helpers', () => {
     expect(fetchMock).not.toHaveBeenCalled()
   })
 })
+
+describe('SchaltwerkBridge session query', () => {
+  beforeEach(() => {
+    fetchMock.mockReset()
+    process.env.SCHALTWERK_PROJECT_PATH = path.resolve(__dirname, '..', '..')
+  })
+
+  afterEach(() => {
+    delete process.env.SCHALTWERK_PROJECT_PATH
+  })
+
+  it('posts the requested fields and sessions in one request', async () => {
+    fetchMock.mockResolvedValue({
+      ok: true,
+      status: 200,
+      statusText: 'OK',
+      text: async () =>
+        JSON.stringify({
+          sessions: [{ name: 'alpha', branch: 'schaltwerk/alpha', terminals: ['session-alpha-top'] }],
+          missing: ['ghost'],
+        }),
+    })
+
+    const bridge = new SchaltwerkBridge()
+    const result = await bridge.querySessions({ fields: ['branch', 'terminals'], sessions: ['alpha', 'ghost'] })
+
+    const [url, init] = fetchMock.mock.calls[0]
+    expect(new URL(String(url)).pathname).toBe('/api/query')
+    expect(init?.method).toBe('POST')
+    expect(JSON.parse(String(init?.body))).toEqual({ fields: ['branch', 'terminals'], sessions: ['alpha', 'ghost'] })
+    expect(result.sessions[0].branch).toBe('schaltwerk/alpha')
+    expect(result.missing).toEqual(['ghost'])
+  })
+
+  it('replays logged events after the given sequence number', async () => {
+    fetchMock.mockResolvedValue({
+      ok: true,
+      status: 200,
+      statusText: 'OK',
+      text: async () =>
+        JSON.stringify({
+          events: [
+            { seq: 8, event: 'schaltwerk:session-removed', payload: { session_name: 'alpha' }, created_at: '2026-01-01T00:00:00Z' },
+          ],
+          latest_seq: 8,
+          has_more: false,
+          missed_events: false,
+        }),
+    })
+
+    const bridge = new SchaltwerkBridge()
+    const replay = await bridge.replayEvents(7, 50)
+
+    const [url, init] = fetchMock.mock.calls[0]
+    const requestUrl = new URL(String(url))
+    expect(requestUrl.pathname).toBe('/api/events/replay')
+    expect(requestUrl.searchParams.get('since_seq')).toBe('7')
+    expect(requestUrl.searchParams.get('limit')).toBe('50')
+    expect(init?.method).toBe('GET')
+    expect(replay.events.map(event => event.seq)).toEqual([8])
+    expect(replay.latest_seq).toBe(8)
+  })
+
+  it('reports structured session creation errors', async () => {
+    fetchMock.mockResolvedValueOnce({
+      ok: false,
+      status: 422,
+      statusText: 'Unprocessable Entity',
+      text: async () =>
+        JSON.stringify({
+          code: 'unknown_base_branch',
+          error: "Base branch 'nope' does not exist in the repository",
+          field: 'base_branch',
+        }),
+    })
+
+    const bridge = new SchaltwerkBridge()
+    await expect(bridge.createSession('fix-login', 'Fix it', 'nope')).rejects.toThrow(
+      "Failed to create session: unknown_base_branch (base_branch): Base branch 'nope' does not exist in the repository"
+    )
+    expect(fetchMock).toHaveBeenCalledTimes(1)
+  })
+})
@@ -160,4 +160,74 @@ describe('SchaltwerkBridge merge/pr helpers', () => {
       cancelError: 'session has active terminals'
     })
   })
+
+  it('sends auto-commit options when marking a session reviewed', async () => {
+    fetchMock.mockResolvedValue({
+      ok: true,
+      status: 200,
+      statusText: 'OK',
+      text: async () => ''
+    })
+
+    const bridge = new SchaltwerkBridge()
+    await bridge.markSessionReviewed('feature-login', {
+      autoCommit: true,
+      commitMessage: '  feat: add login screen  '
+    })
+    await bridge.markSessionReviewed('feature-login')
+
+    expect(fetchMock).toHaveBeenCalledTimes(2)
+    const [url, init] = fetchMock.mock.calls[0] as [string, Record<string, unknown>]
+    expect(url).toContain('/api/sessions/feature-login/mark-reviewed')
+    expect(init?.method).toBe('POST')
+    expect(JSON.parse(String(init?.body))).toEqual({
+      auto_commit: true,
+      commit_message: 'feat: add login screen'
+    })
+    const [, plainInit] = fetchMock.mock.calls[1] as [string, Record<string, unknown>]
+    expect(plainInit?.body).toBeUndefined()
+  })
+
+  it('posts follow-up webhooks to the project-scoped route', async () => {
+    fetchMock.mockResolvedValue({
+      ok: true,
+      status: 200,
+      statusText: 'OK',
+      json: async () => ({ name: 'feature-login' }),
+      text: async () => ''
+    })
+
+    const bridge = new SchaltwerkBridge()
+    await bridge.sendFollowUpMessage('feature-login', 'please rebase')
+
+    expect(fetchMock).toHaveBeenCalledTimes(2)
+    const [url, init] = fetchMock.mock.calls[1] as [string, Record<string, unknown>]
+    expect(url).toMatch(/\/projects\/[0-9a-f]{16}\/webhook\/follow-up-message$/)
+    expect(init?.method).toBe('POST')
+    expect(JSON.parse(String(init?.body))).toMatchObject({
+      session_name: 'feature-login',
+      message: 'please rebase'
+    })
+  })
+
+  it('reports follow-up messages the app refuses', async () => {
+    fetchMock
+      .mockResolvedValueOnce({
+        ok: true,
+        status: 200,
+        statusText: 'OK',
+        json: async () => ({ name: 'feature-login' }),
+        text: async () => ''
+      })
+      .mockResolvedValueOnce({
+        ok: false,
+        status: 413,
+        statusText: 'Payload Too Large',
+        text: async () => 'Follow-up message is 70000 bytes; the limit is 65536 bytes'
+      })
+
+    const bridge = new SchaltwerkBridge()
+    await expect(bridge.sendFollowUpMessage('feature-login', 'x'.repeat(70000)))
+      .rejects.toThrow('413 Payload Too Large - Follow-up message is 70000 bytes')
+  })
 })
@@ -45,6 +45,8 @@ describe('SchaltwerkBridge port discovery', () => {
   afterEach(() => {
     delete process.env.SCHALTWERK_PROJECT_PATH
     delete process.env.SCHALTWERK_MCP_PORT
+    delete process.env.SCHALTWERK_MCP_TOKEN
+    delete process.env.SCHALTWERK_MCP_SOCKET
     if (tempDir && fs.existsSync(tempDir)) {
       fs.rmSync(tempDir, { recursive: true, force: true })
     }
@@ -121,4 +123,35 @@ describe('SchaltwerkBridge port discovery', () => {
     expect(mockFetch).toHaveBeenCalledTimes(3)
     expect(mockFetch.mock.calls[2][0]).toEqual(mockFetch.mock.calls[1][0])
   })
+
+  it('sends the project token issued by Schaltwerk as a bearer token', async () => {
+    process.env.SCHALTWERK_MCP_TOKEN = 'test-token'
+    mockFetch.mockResolvedValueOnce(createResponse([]))
+
+    const bridge = new SchaltwerkBridge()
+    await bridge.listSessions()
+
+    const init = mockFetch.mock.calls[0][1] as { headers?: Record<string, string> }
+    expect(init.headers).toMatchObject({ Authorization: 'Bearer test-token' })
+  })
+
+  it('prefers the unix socket and falls back to ports when it is gone', async () => {
+    process.env.SCHALTWERK_MCP_SOCKET = path.join(tempDir, 'api.sock')
+    const missingSocket = new Error('connect ENOENT api.sock') as NodeJS.ErrnoException
+    missingSocket.code = 'ENOENT'
+    mockFetch
+      .mockResolvedValueOnce(createResponse([]))
+      .mockRejectedValueOnce(missingSocket)
+      .mockResolvedValueOnce(createResponse([]))
+
+    const bridge = new SchaltwerkBridge()
+    await bridge.listSessions()
+    const socketInit = mockFetch.mock.calls[0][1] as { agent?: unknown }
+    expect(String(mockFetch.mock.calls[0][0])).toBe('http://localhost/api/sessions')
+    expect(socketInit.agent).toBeDefined()
+
+    await bridge.listSessions()
+    expect(mockFetch).toHaveBeenCalledTimes(3)
+    expect(String(mockFetch.mock.calls[2][0])).toStartWith('http://127.0.0.1:')
+  })
 })
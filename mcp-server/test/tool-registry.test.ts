@@ -11,8 +11,12 @@ describe('MCP tool registry', () => {
     expect(content).toContain('name: "schaltwerk_create_pr"')
     expect(content).toContain('name: "schaltwerk_spec_list"')
     expect(content).toContain('name: "schaltwerk_spec_read"')
+    expect(content).toContain('name: "schaltwerk_spec_propose_revision"')
+    expect(content).toContain('name: "schaltwerk_spec_revisions"')
     expect(content).toContain('name: "schaltwerk_diff_summary"')
     expect(content).toContain('name: "schaltwerk_diff_chunk"')
     expect(content).toContain('name: "schaltwerk_session_spec"')
+    expect(content).toContain('name: "schaltwerk_query"')
+    expect(content).toContain('name: "schaltwerk_events_replay"')
   })
 })
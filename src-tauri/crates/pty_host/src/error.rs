@@ -6,6 +6,10 @@ pub enum PtyHostError {
     TerminalNotFound(String),
     #[error("terminal already exists: {0}")]
     TerminalExists(String),
+    #[error("terminal is already recording: {0}")]
+    RecordingActive(String),
+    #[error("no recording for terminal: {0}")]
+    NotRecording(String),
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("internal error: {0}")]
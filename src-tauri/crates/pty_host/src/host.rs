@@ -1,9 +1,10 @@
 use crate::error::{PtyHostError, Result};
+use crate::recording::AsciicastRecorder;
 use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use parking_lot::{Condvar, Mutex};
 use portable_pty::{Child, CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
@@ -28,6 +29,9 @@ pub struct SpawnOptions {
     pub rows: u16,
     pub cols: u16,
     pub env: Vec<(String, String)>,
+    /// Record the terminal to an asciicast file from the start
+    #[serde(default)]
+    pub record: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,16 +69,49 @@ pub struct AckRequest {
     pub bytes: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingRequest {
+    pub term_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRecordingRequest {
+    pub term_id: String,
+    pub destination: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingResponse {
+    pub term_id: String,
+    pub path: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubscribeRequest {
     pub term_id: String,
     pub last_seen_seq: Option<u64>,
 }
 
+/// `last_seen_seq` in a [`SubscribeRequest`] is the highest sequence number the client has
+/// acknowledged; when the host still holds everything after it, only those bytes are sent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SubscribeResponse {
     Snapshot(TerminalSnapshot),
-    DeltaReady { term_id: String, seq: u64 },
+    /// Output produced after `last_seen_seq`, up to and including `seq`
+    Delta(TerminalDelta),
+    /// The client is already up to date as of `seq`
+    DeltaReady {
+        term_id: String,
+        seq: u64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalDelta {
+    pub term_id: String,
+    pub from_seq: u64,
+    pub seq: u64,
+    pub base64: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,35 +123,73 @@ pub struct TerminalSnapshot {
 
 const MAX_TRANSCRIPT_BYTES: usize = 4 * 1024 * 1024;
 
+#[derive(Default)]
+struct TranscriptChunks {
+    chunks: VecDeque<(u64, Vec<u8>)>,
+    len: usize,
+    // Highest sequence number whose bytes were (partly) dropped; deltas must start at or after it
+    evicted_through: u64,
+}
+
 struct TranscriptWriter {
-    buffer: Mutex<Vec<u8>>,
+    buffer: Mutex<TranscriptChunks>,
 }
 
 impl TranscriptWriter {
     fn new(_root: &Path, _term_id: &str) -> Result<Self> {
         Ok(Self {
-            buffer: Mutex::new(Vec::new()),
+            buffer: Mutex::new(TranscriptChunks::default()),
         })
     }
 
-    fn append(&self, _seq: u64, bytes: &[u8]) -> Result<()> {
+    fn append(&self, seq: u64, bytes: &[u8]) -> Result<()> {
         let mut buffer = self.buffer.lock();
-        buffer.extend_from_slice(bytes);
-        if buffer.len() > MAX_TRANSCRIPT_BYTES {
-            let excess = buffer.len() - MAX_TRANSCRIPT_BYTES;
-            buffer.drain(..excess);
+        buffer.chunks.push_back((seq, bytes.to_vec()));
+        buffer.len += bytes.len();
+        while buffer.len > MAX_TRANSCRIPT_BYTES {
+            let excess = buffer.len - MAX_TRANSCRIPT_BYTES;
+            let Some((front_seq, front)) = buffer.chunks.front_mut() else {
+                break;
+            };
+            let front_seq = *front_seq;
+            if front.len() <= excess {
+                let dropped = front.len();
+                buffer.chunks.pop_front();
+                buffer.len -= dropped;
+            } else {
+                front.drain(..excess);
+                buffer.len -= excess;
+            }
+            buffer.evicted_through = front_seq;
         }
         Ok(())
     }
 
     fn load_snapshot(&self, limit_bytes: u64) -> Result<Vec<u8>> {
         let buffer = self.buffer.lock();
-        if buffer.is_empty() {
-            return Ok(Vec::new());
+        let bytes: Vec<u8> = buffer
+            .chunks
+            .iter()
+            .flat_map(|(_, chunk)| chunk.iter().copied())
+            .collect();
+        let start = bytes.len().saturating_sub(limit_bytes as usize);
+        Ok(bytes[start..].to_vec())
+    }
+
+    /// Bytes of every chunk after `after_seq` and the sequence number of the last one, or
+    /// `None` when part of that range was already dropped from memory.
+    fn load_since(&self, after_seq: u64) -> Option<(Vec<u8>, u64)> {
+        let buffer = self.buffer.lock();
+        if after_seq < buffer.evicted_through {
+            return None;
         }
-        let limit = limit_bytes as usize;
-        let start = buffer.len().saturating_sub(limit);
-        Ok(buffer[start..].to_vec())
+        let mut bytes = Vec::new();
+        let mut last_seq = after_seq;
+        for (seq, chunk) in buffer.chunks.iter().filter(|(seq, _)| *seq > after_seq) {
+            bytes.extend_from_slice(chunk);
+            last_seq = *seq;
+        }
+        Some((bytes, last_seq))
     }
 }
 
@@ -130,6 +205,7 @@ struct TerminalEntry {
     gate: Mutex<()>,
     gate_cv: Condvar,
     transcript: TranscriptWriter,
+    recording: Mutex<Option<AsciicastRecorder>>,
     reader_handle: Mutex<Option<JoinHandle<()>>>,
 }
 
@@ -153,6 +229,7 @@ impl TerminalEntry {
             gate: Mutex::new(()),
             gate_cv: Condvar::new(),
             transcript,
+            recording: Mutex::new(None),
             reader_handle: Mutex::new(None),
         })
     }
@@ -204,6 +281,11 @@ impl TerminalEntry {
                         entry.term_id
                     );
                 }
+                if let Some(recorder) = entry.recording.lock().as_mut() {
+                    if let Err(err) = recorder.record_output(chunk) {
+                        tracing::warn!("failed to record output for term {}: {err}", entry.term_id);
+                    }
+                }
 
                 entry.outstanding.fetch_add(read_bytes, Ordering::SeqCst);
 
@@ -240,6 +322,11 @@ impl TerminalEntry {
                 })
                 .map_err(|e| PtyHostError::Internal(format!("failed to resize pty: {e}")))?;
         }
+        if let Some(recorder) = self.recording.lock().as_mut() {
+            if let Err(err) = recorder.record_resize(cols, rows) {
+                tracing::warn!("failed to record resize for term {}: {err}", self.term_id);
+            }
+        }
 
         sleep(Duration::from_millis(RESIZE_DEBOUNCE_MS)).await;
         self.resizing.store(false, Ordering::SeqCst);
@@ -281,7 +368,41 @@ impl TerminalEntry {
         }
     }
 
+    fn start_recording(&self, path: &Path) -> Result<()> {
+        let mut recording = self.recording.lock();
+        if recording.is_some() {
+            return Err(PtyHostError::RecordingActive(self.term_id.clone()));
+        }
+        let size = self
+            .master
+            .lock()
+            .get_size()
+            .map_err(|e| PtyHostError::Internal(format!("failed to read pty size: {e}")))?;
+        *recording = Some(AsciicastRecorder::create(path, size.cols, size.rows)?);
+        Ok(())
+    }
+
+    fn stop_recording(&self) -> Result<Option<PathBuf>> {
+        match self.recording.lock().take() {
+            Some(recorder) => recorder.finish().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn flush_recording(&self) -> Result<()> {
+        match self.recording.lock().as_mut() {
+            Some(recorder) => recorder.flush(),
+            None => Ok(()),
+        }
+    }
+
     fn kill(&self) {
+        if let Err(err) = self.stop_recording() {
+            tracing::warn!(
+                "failed to finish recording for term {}: {err}",
+                self.term_id
+            );
+        }
         if let Some(mut child) = self.child.lock().take() {
             if let Err(err) = child.kill() {
                 tracing::debug!("failed to kill terminal process {}: {err}", self.term_id);
@@ -309,6 +430,8 @@ pub struct PtyHost {
     sink: Arc<dyn EventSink>,
     terminals: Mutex<HashMap<String, Arc<TerminalEntry>>>,
     transcript_root: PathBuf,
+    // Latest recording file per terminal, kept after the terminal exits so it can be exported
+    recordings: Mutex<HashMap<String, PathBuf>>,
 }
 
 impl PtyHost {
@@ -321,6 +444,7 @@ impl PtyHost {
             sink,
             terminals: Mutex::new(HashMap::new()),
             transcript_root,
+            recordings: Mutex::new(HashMap::new()),
         }
     }
 
@@ -385,6 +509,9 @@ impl PtyHost {
 
         let transcript = TranscriptWriter::new(&self.transcript_root, &opts.id)?;
         let entry = TerminalEntry::new(opts.id.clone(), pair.master, child, writer, transcript);
+        if opts.record {
+            self.begin_recording(&entry)?;
+        }
         entry.spawn_reader(Arc::clone(&self.sink));
         self.insert_terminal(entry);
 
@@ -416,9 +543,103 @@ impl PtyHost {
         Ok(())
     }
 
+    fn begin_recording(&self, entry: &TerminalEntry) -> Result<PathBuf> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let file_name: String = entry
+            .term_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = self
+            .transcript_root
+            .join("recordings")
+            .join(format!("{file_name}-{timestamp}.cast"));
+
+        entry.start_recording(&path)?;
+        self.recordings
+            .lock()
+            .insert(entry.term_id.clone(), path.clone());
+        Ok(path)
+    }
+
+    pub async fn start_recording(&self, request: RecordingRequest) -> Result<RecordingResponse> {
+        let entry = self.get_terminal(&request.term_id)?;
+        let path = self.begin_recording(&entry)?;
+        Ok(RecordingResponse {
+            term_id: request.term_id,
+            path: path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub async fn stop_recording(&self, request: RecordingRequest) -> Result<RecordingResponse> {
+        let entry = self.get_terminal(&request.term_id)?;
+        let path = entry
+            .stop_recording()?
+            .ok_or_else(|| PtyHostError::NotRecording(request.term_id.clone()))?;
+        Ok(RecordingResponse {
+            term_id: request.term_id,
+            path: path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Copy the latest recording of a terminal to `destination`; an active recording is
+    /// exported as far as it got.
+    pub async fn export_recording(
+        &self,
+        request: ExportRecordingRequest,
+    ) -> Result<RecordingResponse> {
+        if let Ok(entry) = self.get_terminal(&request.term_id) {
+            entry.flush_recording()?;
+        }
+        let source = self
+            .recordings
+            .lock()
+            .get(&request.term_id)
+            .cloned()
+            .ok_or_else(|| PtyHostError::NotRecording(request.term_id.clone()))?;
+
+        let destination = PathBuf::from(&request.destination);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &destination)?;
+        Ok(RecordingResponse {
+            term_id: request.term_id,
+            path: request.destination,
+        })
+    }
+
     pub async fn subscribe(&self, request: SubscribeRequest) -> Result<SubscribeResponse> {
         let entry = self.get_terminal(&request.term_id)?;
         let seq = entry.seq.load(Ordering::SeqCst);
+
+        // A client ahead of the host saw a previous terminal with this id and needs everything
+        if let Some(last_seen) = request.last_seen_seq.filter(|last_seen| *last_seen <= seq) {
+            if let Some((bytes, delta_seq)) = entry.transcript.load_since(last_seen) {
+                if bytes.is_empty() {
+                    return Ok(SubscribeResponse::DeltaReady {
+                        term_id: request.term_id,
+                        seq: delta_seq,
+                    });
+                }
+                return Ok(SubscribeResponse::Delta(TerminalDelta {
+                    term_id: request.term_id,
+                    from_seq: last_seen,
+                    seq: delta_seq,
+                    base64: STANDARD_NO_PAD.encode(&bytes),
+                }));
+            }
+        }
+
         let bytes = entry.transcript.load_snapshot(4 * 1024 * 1024)?;
         let base64 = STANDARD_NO_PAD.encode(&bytes);
         Ok(SubscribeResponse::Snapshot(TerminalSnapshot {
@@ -501,6 +722,7 @@ mod tests {
                     rows: 24,
                     cols: 80,
                     env: vec![],
+                    record: false,
                 },
             })
             .await?;
@@ -542,6 +764,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn transcript_writer_serves_deltas_until_evicted() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let writer = TranscriptWriter::new(temp_dir.path(), "delta-test")?;
+
+        writer.append(1, b"one ")?;
+        writer.append(2, b"two ")?;
+        writer.append(3, b"three")?;
+
+        assert_eq!(writer.load_since(1), Some((b"two three".to_vec(), 3)));
+        assert_eq!(writer.load_since(3), Some((Vec::new(), 3)));
+
+        // Dropping whole chunks keeps deltas after them intact
+        writer.append(4, &vec![b'x'; MAX_TRANSCRIPT_BYTES])?;
+        assert!(writer.load_since(2).is_none());
+        let (bytes, seq) = writer.load_since(3).expect("chunk 4 is complete");
+        assert_eq!((bytes.len(), seq), (MAX_TRANSCRIPT_BYTES, 4));
+
+        // A partly dropped chunk can no longer be resent
+        writer.append(5, b"y")?;
+        assert!(writer.load_since(3).is_none());
+        assert_eq!(writer.load_since(4), Some((b"y".to_vec(), 5)));
+
+        Ok(())
+    }
+
     #[test]
     fn transcript_writer_avoids_creating_files() -> Result<()> {
         let temp_dir = tempfile::tempdir()?;
@@ -684,6 +932,7 @@ mod tests {
                     rows: 24,
                     cols: 80,
                     env: vec![],
+                    record: false,
                 },
             })
             .await?;
@@ -720,6 +969,74 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn recording_captures_output_and_exports() -> Result<()> {
+        let sink = Arc::new(RecordingSink::new());
+        let temp_dir = tempfile::tempdir()?;
+        let host = make_host(&temp_dir, sink.clone());
+
+        let spawn = host
+            .spawn(SpawnRequest {
+                options: SpawnOptions {
+                    id: "record-term".to_string(),
+                    cwd: temp_dir.path().to_string_lossy().to_string(),
+                    rows: 24,
+                    cols: 80,
+                    env: vec![],
+                    record: false,
+                },
+            })
+            .await?;
+        let request = || RecordingRequest {
+            term_id: spawn.term_id.clone(),
+        };
+
+        assert!(matches!(
+            host.stop_recording(request()).await,
+            Err(PtyHostError::NotRecording(_))
+        ));
+        host.start_recording(request()).await?;
+        assert!(matches!(
+            host.start_recording(request()).await,
+            Err(PtyHostError::RecordingActive(_))
+        ));
+
+        host.write(WriteRequest {
+            term_id: spawn.term_id.clone(),
+            utf8: "printf 'recorded'\n".to_string(),
+        })
+        .await?;
+        let timeout_at = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !sink
+            .events
+            .lock()
+            .iter()
+            .any(|(_, _, bytes)| String::from_utf8_lossy(bytes).contains("recorded"))
+            && tokio::time::Instant::now() < timeout_at
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+
+        let stopped = host.stop_recording(request()).await?;
+        let cast = std::fs::read_to_string(&stopped.path)?;
+        assert!(cast.starts_with("{\"version\":2,\"width\":80,\"height\":24"));
+        assert!(cast.contains("recorded"));
+
+        host.kill(KillRequest {
+            term_id: spawn.term_id.clone(),
+        })
+        .await?;
+
+        let destination = temp_dir.path().join("export").join("run.cast");
+        host.export_recording(ExportRecordingRequest {
+            term_id: spawn.term_id.clone(),
+            destination: destination.to_string_lossy().to_string(),
+        })
+        .await?;
+        assert_eq!(std::fs::read_to_string(destination)?, cast);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn subscribe_returns_snapshot_after_history() -> Result<()> {
         let sink = Arc::new(RecordingSink::new());
@@ -734,6 +1051,7 @@ mod tests {
                     rows: 24,
                     cols: 80,
                     env: vec![],
+                    record: false,
                 },
             })
             .await?;
@@ -764,6 +1082,7 @@ mod tests {
                 assert!(text.contains("ready"));
             }
             SubscribeResponse::DeltaReady { .. } => panic!("expected snapshot response"),
+            SubscribeResponse::Delta(_) => anyhow::bail!("expected snapshot response"),
         }
 
         host.kill(KillRequest {
@@ -772,4 +1091,60 @@ mod tests {
         .await?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn subscribe_returns_delta_after_last_seen_seq() -> Result<()> {
+        let sink = Arc::new(RecordingSink::new());
+        let temp_dir = tempfile::tempdir()?;
+        let host = make_host(&temp_dir, sink.clone());
+
+        let spawn = host
+            .spawn(SpawnRequest {
+                options: SpawnOptions {
+                    id: "delta-term".to_string(),
+                    cwd: temp_dir.path().to_string_lossy().to_string(),
+                    rows: 24,
+                    cols: 80,
+                    env: vec![],
+                    record: false,
+                },
+            })
+            .await?;
+
+        host.write(WriteRequest {
+            term_id: spawn.term_id.clone(),
+            utf8: "echo ready && exit\n".to_string(),
+        })
+        .await?;
+
+        let events = sink.wait_for_events(1).await;
+        assert!(!events.is_empty());
+
+        let response = host
+            .subscribe(SubscribeRequest {
+                term_id: spawn.term_id.clone(),
+                last_seen_seq: Some(0),
+            })
+            .await?;
+        let SubscribeResponse::Delta(delta) = response else {
+            anyhow::bail!("expected delta response");
+        };
+        assert_eq!(delta.from_seq, 0);
+        let bytes = STANDARD_NO_PAD.decode(delta.base64).expect("delta base64");
+        assert!(String::from_utf8(bytes)?.contains("ready"));
+
+        let response = host
+            .subscribe(SubscribeRequest {
+                term_id: spawn.term_id.clone(),
+                last_seen_seq: Some(delta.seq),
+            })
+            .await?;
+        assert!(matches!(response, SubscribeResponse::DeltaReady { seq, .. } if seq == delta.seq));
+
+        host.kill(KillRequest {
+            term_id: spawn.term_id,
+        })
+        .await?;
+        Ok(())
+    }
 }
@@ -2,9 +2,11 @@
 
 pub mod error;
 pub mod host;
+pub mod recording;
 
 pub use crate::error::PtyHostError;
 pub use crate::host::{
-    AckRequest, EventSink, KillRequest, PtyHost, ResizeRequest, SpawnOptions, SpawnRequest,
-    SpawnResponse, SubscribeRequest, SubscribeResponse, TerminalSnapshot, WriteRequest,
+    AckRequest, EventSink, ExportRecordingRequest, KillRequest, PtyHost, RecordingRequest,
+    RecordingResponse, ResizeRequest, SpawnOptions, SpawnRequest, SpawnResponse, SubscribeRequest,
+    SubscribeResponse, TerminalDelta, TerminalSnapshot, WriteRequest,
 };
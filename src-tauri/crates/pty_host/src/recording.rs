@@ -0,0 +1,148 @@
+use crate::error::{PtyHostError, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize)]
+struct AsciicastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+}
+
+/// Writes terminal output as an asciicast v2 file that asciinema and compatible players replay.
+pub struct AsciicastRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+    // Trailing bytes of an incomplete UTF-8 sequence, completed by the next chunk
+    pending: Vec<u8>,
+}
+
+impl AsciicastRecorder {
+    pub fn create(path: &Path, cols: u16, rows: u16) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        let header = AsciicastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        write_line(&mut writer, &header)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+            started: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn record_output(&mut self, bytes: &[u8]) -> Result<()> {
+        self.pending.extend_from_slice(bytes);
+        let text = match std::str::from_utf8(&self.pending) {
+            Ok(text) => {
+                let text = text.to_string();
+                self.pending.clear();
+                text
+            }
+            Err(err) if err.error_len().is_none() => {
+                let valid = err.valid_up_to();
+                let text = String::from_utf8_lossy(&self.pending[..valid]).into_owned();
+                self.pending.drain(..valid);
+                text
+            }
+            Err(_) => {
+                let text = String::from_utf8_lossy(&self.pending).into_owned();
+                self.pending.clear();
+                text
+            }
+        };
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.write_event("o", text)
+    }
+
+    pub fn record_resize(&mut self, cols: u16, rows: u16) -> Result<()> {
+        self.write_event("r", format!("{cols}x{rows}"))
+    }
+
+    /// Flush buffered events so the file on disk is complete up to now.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(PtyHostError::IoError)
+    }
+
+    pub fn finish(mut self) -> Result<PathBuf> {
+        if !self.pending.is_empty() {
+            let text = String::from_utf8_lossy(&self.pending).into_owned();
+            self.pending.clear();
+            self.write_event("o", text)?;
+        }
+        self.flush()?;
+        Ok(self.path)
+    }
+
+    fn write_event(&mut self, kind: &str, data: String) -> Result<()> {
+        let elapsed = (self.started.elapsed().as_micros() as f64) / 1_000_000.0;
+        write_line(&mut self.writer, &(elapsed, kind, data))
+    }
+}
+
+fn write_line<T: Serialize>(writer: &mut BufWriter<File>, value: &T) -> Result<()> {
+    let line = serde_json::to_string(value)
+        .map_err(|e| PtyHostError::Internal(format!("failed to encode recording event: {e}")))?;
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn writes_header_and_events() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("nested").join("term.cast");
+
+        let mut recorder = AsciicastRecorder::create(&path, 80, 24)?;
+        recorder.record_output(b"hello\r\n")?;
+        // "é" split across two reads must come out as one character
+        recorder.record_output(&[b'c', b'a', b'f', 0xc3])?;
+        recorder.record_output(&[0xa9])?;
+        recorder.record_resize(120, 40)?;
+        let finished = recorder.finish()?;
+        assert_eq!(finished, path);
+
+        let contents = std::fs::read_to_string(&path)?;
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?;
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "hello\r\n");
+        assert_eq!(lines[2][2], "caf");
+        assert_eq!(lines[3][2], "é");
+        assert_eq!(lines[4][1], "r");
+        assert_eq!(lines[4][2], "120x40");
+        assert!(lines[4][0].as_f64().unwrap() >= lines[1][0].as_f64().unwrap());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+//! Bearer tokens for the local MCP/webhook HTTP server. Each project gets a fresh token
+//! per app run; it reaches the MCP bridge through `.mcp.json` and the agent environment,
+//! and requests without it are rejected.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+
+use super::socket::MCP_SOCKET_ENV;
+
+/// Environment variable the MCP bridge reads its token from
+pub const MCP_TOKEN_ENV: &str = "SCHALTWERK_MCP_TOKEN";
+
+/// Request header naming the project the bridge acts for
+pub const PROJECT_PATH_HEADER: &str = "X-Project-Path";
+
+static PROJECT_TOKENS: LazyLock<DashMap<PathBuf, String>> = LazyLock::new(DashMap::new);
+
+fn project_key(project_path: &Path) -> PathBuf {
+    std::fs::canonicalize(project_path).unwrap_or_else(|_| project_path.to_path_buf())
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    if let Err(e) = getrandom::fill(&mut bytes) {
+        log::warn!("Failed to get random bytes for MCP token: {e}, using fallback");
+        return format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+    }
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The project's token for this run, generated on first use.
+pub fn project_token(project_path: &Path) -> String {
+    PROJECT_TOKENS
+        .entry(project_key(project_path))
+        .or_insert_with(generate_token)
+        .clone()
+}
+
+/// The token from an `Authorization: Bearer <token>` header value.
+pub fn bearer_token(authorization: &str) -> Option<&str> {
+    let (scheme, token) = authorization.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Whether a request may drive Schaltwerk. With a project header the token has to be
+/// that project's; without one, any project's token is accepted.
+pub fn is_authorized(project_path: Option<&str>, authorization: Option<&str>) -> bool {
+    let Some(given) = authorization.and_then(bearer_token) else {
+        return false;
+    };
+    match project_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PROJECT_TOKENS
+            .get(&project_key(Path::new(path.trim())))
+            .is_some_and(|expected| tokens_match(expected.value(), given)),
+        None => PROJECT_TOKENS
+            .iter()
+            .any(|entry| tokens_match(entry.value(), given)),
+    }
+}
+
+/// Store `token`, and the API socket when the app serves one, in the `schaltwerk` server
+/// entry of the project's `.mcp.json`. Returns `false` when the project has no such entry,
+/// in which case nothing is written.
+pub fn write_connection_to_mcp_config(
+    project_path: &Path,
+    token: &str,
+    socket: Option<&Path>,
+) -> Result<bool> {
+    let config_path = project_path.join(".mcp.json");
+    if !config_path.exists() {
+        return Ok(false);
+    }
+    let content = std::fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let mut config: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+
+    let Some(server) = config
+        .get_mut("mcpServers")
+        .and_then(|servers| servers.get_mut("schaltwerk"))
+        .and_then(|server| server.as_object_mut())
+    else {
+        return Ok(false);
+    };
+    let Some(env) = server
+        .entry("env")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+    else {
+        return Ok(false);
+    };
+    let before = env.clone();
+    env.insert(
+        MCP_TOKEN_ENV.to_string(),
+        serde_json::Value::String(token.to_string()),
+    );
+    match socket {
+        Some(socket) => {
+            env.insert(
+                MCP_SOCKET_ENV.to_string(),
+                serde_json::Value::String(socket.to_string_lossy().to_string()),
+            );
+        }
+        None => {
+            env.remove(MCP_SOCKET_ENV);
+        }
+    }
+    if *env == before {
+        return Ok(true);
+    }
+
+    let updated = serde_json::to_string_pretty(&config)?;
+    std::fs::write(&config_path, format!("{updated}\n"))
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn requests_need_the_project_token() {
+        let project = TempDir::new().unwrap();
+        let other = TempDir::new().unwrap();
+        let token = project_token(project.path());
+        assert_eq!(token.len(), 64);
+        assert_eq!(project_token(project.path()), token);
+        let other_token = project_token(other.path());
+        assert_ne!(other_token, token);
+
+        let project_path = project.path().to_string_lossy().to_string();
+        let header = format!("Bearer {token}");
+        assert!(is_authorized(Some(&project_path), Some(&header)));
+        assert!(is_authorized(None, Some(&header)));
+        assert!(!is_authorized(
+            Some(&project_path),
+            Some(&format!("Bearer {other_token}"))
+        ));
+        assert!(!is_authorized(Some(&project_path), Some(&token)));
+        assert!(!is_authorized(Some(&project_path), None));
+        assert!(!is_authorized(None, Some("Bearer not-a-token")));
+    }
+
+    #[test]
+    fn token_is_written_into_the_schaltwerk_mcp_entry() {
+        let project = TempDir::new().unwrap();
+        assert!(!write_connection_to_mcp_config(project.path(), "abc", None).unwrap());
+
+        let config_path = project.path().join(".mcp.json");
+        std::fs::write(
+            &config_path,
+            r#"{"mcpServers":{"other":{"command":"x"},"schaltwerk":{"type":"stdio","command":"node","args":["server.js"]}}}"#,
+        )
+        .unwrap();
+        let socket = Path::new("/tmp/schaltwerk/api.sock");
+        assert!(write_connection_to_mcp_config(project.path(), "abc", Some(socket)).unwrap());
+
+        let read_config = || -> serde_json::Value {
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap()
+        };
+        let config = read_config();
+        let env = &config["mcpServers"]["schaltwerk"]["env"];
+        assert_eq!(env[MCP_TOKEN_ENV], "abc");
+        assert_eq!(env[MCP_SOCKET_ENV], "/tmp/schaltwerk/api.sock");
+        assert_eq!(config["mcpServers"]["schaltwerk"]["command"], "node");
+        assert!(config["mcpServers"]["other"].get("env").is_none());
+
+        // Without a socket the bridge falls back to TCP, so a stale path is dropped
+        assert!(write_connection_to_mcp_config(project.path(), "abc", None).unwrap());
+        let config = read_config();
+        assert!(config["mcpServers"]["schaltwerk"]["env"]
+            .get(MCP_SOCKET_ENV)
+            .is_none());
+    }
+}
@@ -0,0 +1,76 @@
+//! Unix domain socket for the local MCP/webhook API. It serves the same routes as the TCP
+//! port, but has a fixed path under the app data dir, so bridges of many concurrent
+//! projects find the app without probing ports that other tools may hold.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable the MCP bridge reads the socket path from
+pub const MCP_SOCKET_ENV: &str = "SCHALTWERK_MCP_SOCKET";
+
+static LISTENING_SOCKET: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn default_socket_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("schaltwerk")
+        .join("api.sock")
+}
+
+/// The socket this app instance serves the API on, once it is bound.
+pub fn listening_socket() -> Option<&'static Path> {
+    LISTENING_SOCKET.get().map(PathBuf::as_path)
+}
+
+/// Record that the API is served on `path`, so `.mcp.json` and agent environments point
+/// bridges at it.
+pub fn set_listening_socket(path: PathBuf) {
+    let _ = LISTENING_SOCKET.set(path);
+}
+
+/// Bind the API socket at `path`, replacing a socket file left behind by a crashed run.
+/// Returns `None` when another running instance already serves on it.
+#[cfg(unix)]
+pub fn bind_socket(path: &Path) -> std::io::Result<Option<tokio::net::UnixListener>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Ok(None);
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    // Only the current user may talk to the app
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(Some(listener))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn stale_sockets_are_replaced_and_live_ones_left_alone() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("api.sock");
+
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let listener = bind_socket(&path)
+            .unwrap()
+            .expect("stale socket is replaced");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        assert!(bind_socket(&path).unwrap().is_none());
+        drop(listener);
+    }
+}
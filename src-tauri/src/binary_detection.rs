@@ -17,6 +17,11 @@ static BINARY_EXTENSIONS: &[&str] = &[
     "pyc", "class", "jar", "war", "ear", "o", "obj", "lib", "a",
 ];
 
+/// Placeholder reason for files whose content lives in Git LFS
+pub const LFS_UNSUPPORTED_REASON: &str = "Binary/LFS file (stored in Git LFS)";
+
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/v1";
+
 static BINARY_EXTENSIONS_SET: LazyLock<HashSet<&'static str>> =
     LazyLock::new(|| BINARY_EXTENSIONS.iter().cloned().collect());
 
@@ -43,6 +48,11 @@ pub fn is_likely_binary_content(bytes: &[u8]) -> bool {
     sample.contains(&0)
 }
 
+/// Whether the bytes are a Git LFS pointer rather than the file's real content
+pub fn is_lfs_pointer(bytes: &[u8]) -> bool {
+    bytes.starts_with(LFS_POINTER_PREFIX)
+}
+
 pub fn get_unsupported_reason(file_path: &str, content_bytes: Option<&[u8]>) -> Option<String> {
     if is_binary_file_by_extension(file_path) {
         return Some(format!(
@@ -52,6 +62,10 @@ pub fn get_unsupported_reason(file_path: &str, content_bytes: Option<&[u8]>) ->
     }
 
     if let Some(bytes) = content_bytes {
+        if is_lfs_pointer(bytes) {
+            return Some(LFS_UNSUPPORTED_REASON.to_string());
+        }
+
         if bytes.len() > 10 * 1024 * 1024 {
             return Some("File is too large to diff (>10MB)".to_string());
         }
@@ -148,6 +162,11 @@ mod tests {
         let text_content = b"Hello, world!";
         let reason = get_unsupported_reason("test.txt", Some(text_content));
         assert!(reason.is_none());
+
+        // Test Git LFS pointer
+        let pointer = b"version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+        let reason = get_unsupported_reason("model.txt", Some(pointer));
+        assert_eq!(reason.as_deref(), Some(LFS_UNSUPPORTED_REASON));
     }
 
     #[test]
@@ -1,9 +1,108 @@
 use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub use crate::utils::binary_utils::{check_binary, DetectedBinary, InstallationMethod};
+pub use crate::utils::binary_utils::{
+    check_binary, detect_version, DetectedBinary, InstallationMethod,
+};
+
+/// A `major.minor.patch` version as found in an agent's `--version` output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AgentVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl AgentVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The first version number in the output, e.g. `1.0.42` in `1.0.42 (Claude Code)`
+    /// or `0.36.0` in `codex-cli 0.36.0`. A missing patch counts as zero.
+    pub fn parse(output: &str) -> Option<Self> {
+        output
+            .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .find_map(|candidate| {
+                let mut parts = candidate.split('.').filter(|part| !part.is_empty());
+                let major = parts.next()?.parse().ok()?;
+                let minor = parts.next()?.parse().ok()?;
+                let patch = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+                Some(Self::new(major, minor, patch))
+            })
+    }
+}
+
+impl fmt::Display for AgentVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Agent features Schaltwerk relies on that older CLI versions lack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentFeature {
+    /// Continuing the previous conversation when a session's agent restarts
+    Resume,
+}
+
+impl AgentFeature {
+    fn description(&self) -> &'static str {
+        match self {
+            AgentFeature::Resume => "resuming the previous conversation",
+        }
+    }
+}
+
+/// First version of each agent that supports a feature the way Schaltwerk invokes it
+const MINIMUM_VERSIONS: &[(&str, AgentFeature, AgentVersion)] = &[
+    // `codex resume` subcommand
+    ("codex", AgentFeature::Resume, AgentVersion::new(0, 36, 0)),
+    // `gemini --resume <session>`
+    ("gemini", AgentFeature::Resume, AgentVersion::new(0, 20, 0)),
+    // `goose session --resume --name`
+    ("goose", AgentFeature::Resume, AgentVersion::new(1, 0, 0)),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentVersionWarning {
+    pub agent_name: String,
+    pub version: String,
+    pub feature: AgentFeature,
+    pub minimum_version: String,
+    pub message: String,
+}
+
+/// Features the given version of an agent is too old for. Versions that can't be parsed
+/// produce no warnings.
+pub fn compatibility_warnings(agent_name: &str, version_output: &str) -> Vec<AgentVersionWarning> {
+    let Some(version) = AgentVersion::parse(version_output) else {
+        return Vec::new();
+    };
+    MINIMUM_VERSIONS
+        .iter()
+        .filter(|(agent, _, minimum)| *agent == agent_name && version < *minimum)
+        .map(|(_, feature, minimum)| AgentVersionWarning {
+            agent_name: agent_name.to_string(),
+            version: version.to_string(),
+            feature: *feature,
+            minimum_version: minimum.to_string(),
+            message: format!(
+                "{agent_name} {version} does not support {}; update to {minimum} or newer",
+                feature.description()
+            ),
+        })
+        .collect()
+}
 
 impl InstallationMethod {
     fn priority(&self) -> u8 {
@@ -160,6 +259,8 @@ impl BinaryDetector {
         let home = std::env::var("HOME").unwrap_or_default();
         let pip_paths = vec![
             format!("{}/.local/bin", home),
+            format!("{}/Library/Python/3.13/bin", home),
+            format!("{}/Library/Python/3.12/bin", home),
             format!("{}/Library/Python/3.11/bin", home),
             format!("{}/Library/Python/3.10/bin", home),
             format!("{}/Library/Python/3.9/bin", home),
@@ -315,6 +416,37 @@ mod tests {
         assert!(InstallationMethod::Manual.priority() < InstallationMethod::System.priority());
     }
 
+    #[test]
+    fn test_agent_version_parsing() {
+        assert_eq!(
+            AgentVersion::parse("1.0.42 (Claude Code)"),
+            Some(AgentVersion::new(1, 0, 42))
+        );
+        assert_eq!(
+            AgentVersion::parse("codex-cli 0.36.0"),
+            Some(AgentVersion::new(0, 36, 0))
+        );
+        assert_eq!(
+            AgentVersion::parse("goose v1.2"),
+            Some(AgentVersion::new(1, 2, 0))
+        );
+        assert_eq!(AgentVersion::parse("unknown build"), None);
+        assert!(AgentVersion::new(0, 9, 9) < AgentVersion::new(0, 10, 0));
+    }
+
+    #[test]
+    fn test_compatibility_warnings_for_old_versions() {
+        let warnings = compatibility_warnings("codex", "codex-cli 0.30.1");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].feature, AgentFeature::Resume);
+        assert_eq!(warnings[0].version, "0.30.1");
+        assert_eq!(warnings[0].minimum_version, "0.36.0");
+
+        assert!(compatibility_warnings("codex", "codex-cli 0.36.0").is_empty());
+        assert!(compatibility_warnings("claude", "0.1.0 (Claude Code)").is_empty());
+        assert!(compatibility_warnings("gemini", "dev build").is_empty());
+    }
+
     #[test]
     fn test_deduplication_and_ranking() {
         let binaries = vec![
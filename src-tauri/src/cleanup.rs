@@ -1,6 +1,6 @@
 use log::info;
 
-/// Cleanup all running terminals
+/// Cleanup all running terminals, including the processes their programs spawned
 pub async fn cleanup_all_terminals() {
     info!("Emergency cleanup (panic/unexpected exit)");
 
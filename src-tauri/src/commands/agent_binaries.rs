@@ -1,10 +1,54 @@
 use crate::SETTINGS_MANAGER;
 use log::{debug, info};
-use schaltwerk::binary_detector::{BinaryDetector, DetectedBinary};
+use schaltwerk::binary_detector::{
+    compatibility_warnings, detect_version, BinaryDetector, DetectedBinary,
+};
 use schaltwerk::domains::settings::AgentBinaryConfig;
+use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
+use std::path::Path;
+
+fn agent_binary_config(
+    agent_name: String,
+    custom_path: Option<String>,
+    auto_detect: bool,
+    detected_binaries: Vec<DetectedBinary>,
+) -> AgentBinaryConfig {
+    let detected_version = match &custom_path {
+        Some(path) => detect_version(Path::new(path)),
+        None => detected_binaries
+            .iter()
+            .find(|b| b.is_recommended)
+            .or_else(|| detected_binaries.first())
+            .and_then(|b| b.version.clone()),
+    };
+
+    AgentBinaryConfig {
+        agent_name,
+        custom_path,
+        auto_detect,
+        detected_binaries,
+        detected_version,
+    }
+}
+
+/// Warn the UI when the agent's binary is too old for features Schaltwerk uses with it.
+fn emit_version_warnings(app: &tauri::AppHandle, config: &AgentBinaryConfig) {
+    let Some(version) = &config.detected_version else {
+        return;
+    };
+    for warning in compatibility_warnings(&config.agent_name, version) {
+        log::warn!("{}", warning.message);
+        if let Err(e) = emit_event(app, SchaltEvent::AgentVersionWarning, &warning) {
+            log::error!("Failed to emit agent-version-warning event: {e}");
+        }
+    }
+}
 
 #[tauri::command]
-pub async fn detect_agent_binaries(agent_name: String) -> Result<Vec<DetectedBinary>, String> {
+pub async fn detect_agent_binaries(
+    app: tauri::AppHandle,
+    agent_name: String,
+) -> Result<Vec<DetectedBinary>, String> {
     info!("Detecting binaries for agent: {agent_name}");
 
     let detected_binaries = BinaryDetector::detect_agent_binaries(&agent_name);
@@ -15,14 +59,15 @@ pub async fn detect_agent_binaries(agent_name: String) -> Result<Vec<DetectedBin
         .ok_or_else(|| "Settings manager not initialized".to_string())?;
     let mut settings = settings_manager.lock().await;
 
-    let config = AgentBinaryConfig {
-        agent_name: agent_name.clone(),
-        custom_path: settings
+    let config = agent_binary_config(
+        agent_name.clone(),
+        settings
             .get_agent_binary_config(&agent_name)
             .and_then(|c| c.custom_path),
-        auto_detect: true,
-        detected_binaries: detected_binaries.clone(),
-    };
+        true,
+        detected_binaries.clone(),
+    );
+    emit_version_warnings(&app, &config);
 
     settings.set_agent_binary_config(config)?;
 
@@ -43,17 +88,21 @@ pub async fn get_agent_binary_config(agent_name: String) -> Result<AgentBinaryCo
     } else {
         // Create default config with detection
         let detected_binaries = BinaryDetector::detect_agent_binaries(&agent_name);
-        Ok(AgentBinaryConfig {
+        Ok(agent_binary_config(
             agent_name,
-            custom_path: None,
-            auto_detect: true,
+            None,
+            true,
             detected_binaries,
-        })
+        ))
     }
 }
 
 #[tauri::command]
-pub async fn set_agent_binary_path(agent_name: String, path: Option<String>) -> Result<(), String> {
+pub async fn set_agent_binary_path(
+    app: tauri::AppHandle,
+    agent_name: String,
+    path: Option<String>,
+) -> Result<(), String> {
     info!("Setting binary path for agent {agent_name}: {path:?}");
 
     // Validate and process the path
@@ -113,12 +162,13 @@ pub async fn set_agent_binary_path(agent_name: String, path: Option<String>) ->
         .map(|c| c.detected_binaries.clone())
         .unwrap_or_else(|| BinaryDetector::detect_agent_binaries(&agent_name));
 
-    let config = AgentBinaryConfig {
+    let config = agent_binary_config(
         agent_name,
-        custom_path: processed_path,
-        auto_detect: path.is_none(),
+        processed_path,
+        path.is_none(),
         detected_binaries,
-    };
+    );
+    emit_version_warnings(&app, &config);
 
     settings.set_agent_binary_config(config)
 }
@@ -177,12 +227,7 @@ pub async fn get_all_agent_binary_configs() -> Result<Vec<AgentBinaryConfig>, St
             configs.push(config);
         } else {
             let detected_binaries = BinaryDetector::detect_agent_binaries(&agent);
-            let config = AgentBinaryConfig {
-                agent_name: agent.to_string(),
-                custom_path: None,
-                auto_detect: true,
-                detected_binaries,
-            };
+            let config = agent_binary_config(agent.to_string(), None, true, detected_binaries);
 
             if let Err(e) = settings.set_agent_binary_config(config.clone()) {
                 log::warn!("Failed to save binary config for {agent}: {e}");
@@ -195,7 +240,9 @@ pub async fn get_all_agent_binary_configs() -> Result<Vec<AgentBinaryConfig>, St
 }
 
 #[tauri::command]
-pub async fn detect_all_agent_binaries() -> Result<Vec<AgentBinaryConfig>, String> {
+pub async fn detect_all_agent_binaries(
+    app: tauri::AppHandle,
+) -> Result<Vec<AgentBinaryConfig>, String> {
     info!("Running full detection for all agents");
 
     let settings_manager = SETTINGS_MANAGER
@@ -213,12 +260,14 @@ pub async fn detect_all_agent_binaries() -> Result<Vec<AgentBinaryConfig>, Strin
         let existing_config = settings.get_agent_binary_config(&agent);
         let custom_path = existing_config.and_then(|c| c.custom_path);
 
-        let config = AgentBinaryConfig {
-            agent_name: agent.to_string(),
-            custom_path: custom_path.clone(),
-            auto_detect: custom_path.is_none(),
+        let auto_detect = custom_path.is_none();
+        let config = agent_binary_config(
+            agent.to_string(),
+            custom_path,
+            auto_detect,
             detected_binaries,
-        };
+        );
+        emit_version_warnings(&app, &config);
 
         if let Err(e) = settings.set_agent_binary_config(config.clone()) {
             log::warn!("Failed to save binary config for {agent}: {e}");
@@ -231,6 +280,7 @@ pub async fn detect_all_agent_binaries() -> Result<Vec<AgentBinaryConfig>, Strin
 
 #[tauri::command]
 pub async fn refresh_agent_binary_detection(
+    app: tauri::AppHandle,
     agent_name: String,
 ) -> Result<AgentBinaryConfig, String> {
     info!("Refreshing binary detection for agent: {agent_name}");
@@ -245,12 +295,9 @@ pub async fn refresh_agent_binary_detection(
     let existing_config = settings.get_agent_binary_config(&agent_name);
     let custom_path = existing_config.and_then(|c| c.custom_path);
 
-    let config = AgentBinaryConfig {
-        agent_name,
-        custom_path: custom_path.clone(),
-        auto_detect: custom_path.is_none(),
-        detected_binaries,
-    };
+    let auto_detect = custom_path.is_none();
+    let config = agent_binary_config(agent_name, custom_path, auto_detect, detected_binaries);
+    emit_version_warnings(&app, &config);
 
     settings.set_agent_binary_config(config.clone())?;
 
@@ -0,0 +1,24 @@
+use schaltwerk::infrastructure::crash::{
+    crash_dir, export_crash_report_from, list_crash_reports_in, CrashReportSummary,
+};
+use std::path::PathBuf;
+
+/// Crash reports written by the panic hook, newest first.
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    tokio::task::spawn_blocking(|| list_crash_reports_in(&crash_dir()))
+        .await
+        .map_err(|e| format!("Failed to list crash reports: {e}"))
+}
+
+/// Copy a crash report to a file the user picked. Reports never leave the machine
+/// on their own; this is the opt-in way to share one.
+#[tauri::command]
+pub async fn export_crash_report(id: String, destination: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        export_crash_report_from(&crash_dir(), &id, &PathBuf::from(destination))
+    })
+    .await
+    .map_err(|e| format!("Failed to export crash report: {e}"))?
+    .map_err(|e| e.to_string())
+}
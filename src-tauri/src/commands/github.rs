@@ -1,6 +1,11 @@
-use crate::get_project_manager;
+use crate::commands::sessions_refresh::{request_sessions_refresh, SessionsRefreshReason};
+use crate::{get_core_read, get_core_write, get_project_manager};
 use log::{error, info};
-use schaltwerk::domains::git::github_cli::{CreatePrOptions, GitHubCli, GitHubCliError};
+use schaltwerk::domains::git::github_cli::{
+    session_pull_request_text, CreatePrOptions, GitHubCli, GitHubCliError, SessionPrOptions,
+};
+use schaltwerk::domains::git::{CommitSigning, GitHooks};
+use schaltwerk::domains::sessions::entity::{SessionRemoteStatus, SessionState};
 use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
 use schaltwerk::schaltwerk_core::db_project_config::{ProjectConfigMethods, ProjectGithubConfig};
 use serde::{Deserialize, Serialize};
@@ -119,15 +124,24 @@ pub async fn github_create_reviewed_pr(
         .map_err(|e| format!("No active project: {e}"))?;
     let project_path = project.path.clone();
 
-    let repository_config = {
+    let (repository_config, commit_signing, git_hooks) = {
         let core = project.schaltwerk_core.read().await;
         let db = core.database();
-        db.get_project_github_config(&project.path)
+        let repository_config = db
+            .get_project_github_config(&project.path)
             .map_err(|e| format!("Failed to load GitHub project config: {e}"))?
             .map(|cfg| GitHubRepositoryPayload {
                 name_with_owner: cfg.repository,
                 default_branch: cfg.default_branch,
-            })
+            });
+        let merge_preferences = db
+            .get_project_merge_preferences(&project.path)
+            .map_err(|e| format!("Failed to load project merge preferences: {e}"))?;
+        (
+            repository_config,
+            CommitSigning::from_preference(merge_preferences.sign_commits),
+            GitHooks::from_preference(merge_preferences.run_git_hooks),
+        )
     };
 
     let worktree_path = PathBuf::from(&args.worktree_path);
@@ -173,6 +187,8 @@ pub async fn github_create_reviewed_pr(
             default_branch: &default_branch,
             commit_message: args.commit_message.as_deref(),
             repository: repository.as_deref(),
+            commit_signing,
+            git_hooks,
         })
         .map_err(|err| {
             error!("GitHub PR creation failed: {err}");
@@ -189,6 +205,120 @@ pub async fn github_create_reviewed_pr(
     Ok(payload)
 }
 
+struct SessionPushTarget {
+    worktree_path: PathBuf,
+    branch: String,
+    parent_branch: String,
+    spec: Option<String>,
+}
+
+async fn session_push_target(session_name: &str) -> Result<SessionPushTarget, String> {
+    let core = get_core_read().await?;
+    let session = core
+        .session_manager()
+        .get_session(session_name)
+        .map_err(|e| format!("Failed to get session: {e}"))?;
+    if session.session_state == SessionState::Spec {
+        return Err(format!(
+            "Session '{session_name}' is a spec and has no branch to push"
+        ));
+    }
+    Ok(SessionPushTarget {
+        worktree_path: session.worktree_path,
+        branch: session.branch,
+        parent_branch: session.parent_branch,
+        spec: session.spec_content.or(session.initial_prompt),
+    })
+}
+
+async fn record_session_push(
+    app: &AppHandle,
+    session_name: &str,
+    remote: &str,
+    pull_request_url: Option<String>,
+) -> Result<SessionRemoteStatus, String> {
+    let status = {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .record_session_push(session_name, remote, pull_request_url)
+            .map_err(|e| format!("Failed to record push for session {session_name}: {e}"))?
+    };
+    request_sessions_refresh(app, SessionsRefreshReason::GitUpdate);
+    Ok(status)
+}
+
+/// Push the session branch to its remote and set it as upstream.
+#[tauri::command]
+pub async fn github_push_session_branch(
+    app: AppHandle,
+    session_name: String,
+) -> Result<SessionRemoteStatus, String> {
+    let target = session_push_target(&session_name).await?;
+    let cli = GitHubCli::new();
+    let remote = cli
+        .push_session_branch(&target.worktree_path, &target.branch)
+        .map_err(|err| {
+            error!("Pushing session '{session_name}' failed: {err}");
+            format_cli_error(err)
+        })?;
+    record_session_push(&app, &session_name, &remote, None).await
+}
+
+/// Push the session branch and open a pull request against its base branch, titled
+/// and described from the session spec. Reuses an already open pull request.
+#[tauri::command]
+pub async fn github_create_pull_request_for_session(
+    app: AppHandle,
+    session_name: String,
+) -> Result<SessionRemoteStatus, String> {
+    let cli = GitHubCli::new();
+    if let Err(err) = cli.ensure_installed() {
+        return Err(format_cli_error(err));
+    }
+
+    let target = session_push_target(&session_name).await?;
+    let repository = {
+        let project_manager = get_project_manager().await;
+        let project = project_manager
+            .current_project()
+            .await
+            .map_err(|e| format!("No active project: {e}"))?;
+        let core = project.schaltwerk_core.read().await;
+        core.database()
+            .get_project_github_config(&project.path)
+            .map_err(|e| format!("Failed to load GitHub project config: {e}"))?
+            .map(|cfg| cfg.repository)
+    };
+
+    let remote = cli
+        .push_session_branch(&target.worktree_path, &target.branch)
+        .map_err(|err| {
+            error!("Pushing session '{session_name}' failed: {err}");
+            format_cli_error(err)
+        })?;
+
+    let (title, body) = session_pull_request_text(&session_name, target.spec.as_deref());
+    info!(
+        "Opening pull request for session '{session_name}' from '{}' into '{}'",
+        target.branch, target.parent_branch
+    );
+    let url = cli
+        .create_session_pull_request(SessionPrOptions {
+            worktree_path: &target.worktree_path,
+            branch: &target.branch,
+            base_branch: &target.parent_branch,
+            title: &title,
+            body: &body,
+            repository: repository.as_deref(),
+        })
+        .map_err(|err| {
+            error!("GitHub PR creation for session '{session_name}' failed: {err}");
+            format_cli_error(err)
+        })?;
+
+    record_session_push(&app, &session_name, &remote, Some(url)).await
+}
+
 async fn build_status() -> Result<GitHubStatusPayload, String> {
     let project_manager = get_project_manager().await;
     let repository_payload = match project_manager.current_project().await {
@@ -0,0 +1,95 @@
+use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
+use schaltwerk::infrastructure::logging::current_log_file;
+use schaltwerk::infrastructure::logging::viewer::{
+    query_log_file, subscribe_log_lines, tail_log_file, LogFilter, LogPage, LogTail,
+};
+use std::sync::{LazyLock, Mutex};
+use tauri::async_runtime::JoinHandle;
+use tauri::AppHandle;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Records returned when the caller does not ask for a page size
+const DEFAULT_LOG_PAGE_SIZE: usize = 200;
+
+/// Forwards log records to the webview as `LogLine` events while a panel listens
+static LOG_STREAM_TASK: LazyLock<Mutex<Option<JoinHandle<()>>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// Matching records of the current log file, newest page first through `offset`.
+#[tauri::command]
+pub async fn query_logs(
+    filter: Option<LogFilter>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<LogPage, String> {
+    let filter = filter.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        query_log_file(
+            current_log_file().as_deref(),
+            &filter,
+            offset.unwrap_or(0),
+            limit.unwrap_or(DEFAULT_LOG_PAGE_SIZE),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to read logs: {e}"))?
+    .map_err(|e| format!("Failed to read logs: {e}"))
+}
+
+/// Matching records written after `after_line`, for polling the current log file.
+#[tauri::command]
+pub async fn tail_logs(
+    filter: Option<LogFilter>,
+    after_line: Option<usize>,
+    limit: Option<usize>,
+) -> Result<LogTail, String> {
+    let filter = filter.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        tail_log_file(
+            current_log_file().as_deref(),
+            &filter,
+            after_line,
+            limit.unwrap_or(DEFAULT_LOG_PAGE_SIZE),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to read logs: {e}"))?
+    .map_err(|e| format!("Failed to read logs: {e}"))
+}
+
+/// Emit every record logged from now on as a `LogLine` event, until `stop_log_stream`.
+#[tauri::command]
+pub async fn start_log_stream(app: AppHandle) -> Result<(), String> {
+    let mut task = LOG_STREAM_TASK
+        .lock()
+        .map_err(|_| "Log stream state is poisoned".to_string())?;
+    if task.is_some() {
+        return Ok(());
+    }
+
+    let mut lines = subscribe_log_lines();
+    *task = Some(tauri::async_runtime::spawn(async move {
+        loop {
+            match lines.recv().await {
+                // Failures are not logged: the warning would be streamed again
+                Ok(entry) => {
+                    let _ = emit_event(&app, SchaltEvent::LogLine, &entry);
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_log_stream() -> Result<(), String> {
+    let mut task = LOG_STREAM_TASK
+        .lock()
+        .map_err(|_| "Log stream state is poisoned".to_string())?;
+    if let Some(task) = task.take() {
+        task.abort();
+    }
+    Ok(())
+}
@@ -1,16 +1,113 @@
-use schaltwerk::services::{mcp::get_mcp_server_process as service_mcp_process, ServiceHandles};
-use std::sync::Arc;
-use tauri::State;
+use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
+use schaltwerk::services::mcp::{
+    get_mcp_server_process as service_mcp_process, mcp_process_status, McpHealth, McpProcessStatus,
+};
+use schaltwerk::services::{DynMcpService, ServiceHandles};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
 use tokio::sync::{Mutex, OnceCell};
 
+/// How often the supervisor checks that the MCP server process is alive
+const MCP_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+static MCP_HEALTH: LazyLock<std::sync::Mutex<McpHealth>> =
+    LazyLock::new(|| std::sync::Mutex::new(McpHealth::default()));
+static SUPERVISOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
 #[tauri::command]
 pub async fn start_mcp_server(
+    app: AppHandle,
     services: State<'_, ServiceHandles>,
     port: Option<u16>,
 ) -> Result<(), String> {
-    services.mcp.start_server(port).await
+    services.mcp.start_server(port).await?;
+    let pid = running_pid().await;
+    update_health(&app, |health| health.record_started(pid, Instant::now()));
+
+    if !SUPERVISOR_RUNNING.swap(true, Ordering::SeqCst) {
+        let mcp = Arc::clone(&services.mcp);
+        tauri::async_runtime::spawn(supervise_mcp_server(app, mcp, port));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_mcp_health() -> Result<McpHealth, String> {
+    Ok(current_health())
 }
 
 pub fn get_mcp_server_process() -> &'static OnceCell<Arc<Mutex<Option<std::process::Child>>>> {
     service_mcp_process()
 }
+
+fn current_health() -> McpHealth {
+    MCP_HEALTH
+        .lock()
+        .map(|health| health.clone())
+        .unwrap_or_default()
+}
+
+/// Apply `change` to the shared health and tell the UI when the visible state changed.
+fn update_health<R>(app: &AppHandle, change: impl FnOnce(&mut McpHealth) -> R) -> R {
+    let (result, before, after) = {
+        let mut health = MCP_HEALTH
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let before = health.clone();
+        let result = change(&mut health);
+        (result, before, health.clone())
+    };
+    if before != after {
+        if let Err(e) = emit_event(app, SchaltEvent::McpStatusChanged, &after) {
+            log::warn!("Failed to emit MCP status change: {e}");
+        }
+    }
+    result
+}
+
+async fn running_pid() -> Option<u32> {
+    match mcp_process_status().await {
+        McpProcessStatus::Running { pid } => Some(pid),
+        _ => None,
+    }
+}
+
+/// Watch the spawned MCP server and restart it with backoff when it exits. Gives up
+/// after repeated crashes; the next `start_mcp_server` call starts a new supervisor.
+async fn supervise_mcp_server(app: AppHandle, mcp: DynMcpService, port: Option<u16>) {
+    loop {
+        tokio::time::sleep(MCP_HEALTH_CHECK_INTERVAL).await;
+        let mut failure = match mcp_process_status().await {
+            McpProcessStatus::Running { .. } => continue,
+            // The process was taken away for shutdown
+            McpProcessStatus::NotStarted => break,
+            McpProcessStatus::Exited(reason) => reason,
+        };
+
+        loop {
+            log::warn!("MCP server is down: {failure}");
+            let retry = update_health(&app, |health| {
+                health.record_failure(failure.clone(), Instant::now())
+            });
+            let Some(delay) = retry else {
+                log::error!("MCP server keeps crashing; not restarting it again");
+                SUPERVISOR_RUNNING.store(false, Ordering::SeqCst);
+                return;
+            };
+
+            tokio::time::sleep(delay).await;
+            match mcp.start_server(port).await {
+                Ok(()) => {
+                    let pid = running_pid().await;
+                    log::info!("Restarted MCP server (pid {pid:?})");
+                    update_health(&app, |health| health.record_started(pid, Instant::now()));
+                    break;
+                }
+                Err(e) => failure = e,
+            }
+        }
+    }
+    SUPERVISOR_RUNNING.store(false, Ordering::SeqCst);
+}
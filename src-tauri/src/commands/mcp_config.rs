@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
+use schaltwerk::api::mcp::{auth, socket};
 use serde_json;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
 const MCP_SERVER_PATH: &str = "mcp-server/build/schaltwerk-mcp-server.js";
 
@@ -32,6 +32,14 @@ mod client {
     }
 
     impl McpClient {
+        pub const ALL: [McpClient; 5] = [
+            Self::Claude,
+            Self::Codex,
+            Self::OpenCode,
+            Self::Amp,
+            Self::Droid,
+        ];
+
         pub fn as_str(&self) -> &'static str {
             match self {
                 Self::Claude => "claude",
@@ -41,6 +49,248 @@ mod client {
                 Self::Droid => "droid",
             }
         }
+
+        /// Key of the table holding the MCP servers in the client's config file
+        pub fn servers_key(&self) -> &'static str {
+            match self {
+                Self::Claude | Self::Droid => "mcpServers",
+                Self::Codex => "mcp_servers",
+                Self::OpenCode => "mcp",
+                Self::Amp => "amp.mcpServers",
+            }
+        }
+
+        /// The Schaltwerk server entry in the client's config format. Config files and
+        /// manual setup instructions are all generated from this.
+        pub fn server_entry(&self, mcp_server_path: &str) -> serde_json::Value {
+            match self {
+                Self::Claude => serde_json::json!({
+                    "type": "stdio",
+                    "command": SERVER_COMMAND,
+                    "args": [mcp_server_path]
+                }),
+                Self::Codex | Self::Amp => serde_json::json!({
+                    "command": SERVER_COMMAND,
+                    "args": [mcp_server_path]
+                }),
+                Self::OpenCode => serde_json::json!({
+                    "type": "local",
+                    "command": [SERVER_COMMAND, mcp_server_path],
+                    "enabled": true
+                }),
+                Self::Droid => serde_json::json!({
+                    "type": "stdio",
+                    "command": SERVER_COMMAND,
+                    "args": [mcp_server_path],
+                    "disabled": false
+                }),
+            }
+        }
+    }
+
+    pub const SERVER_NAME: &str = "schaltwerk";
+    const SERVER_COMMAND: &str = "node";
+    const CODEX_SECTION_HEADER: &str = "[mcp_servers.schaltwerk]";
+
+    /// Merge a generated entry into the one in a config file: generated keys win, nested
+    /// tables such as `env` are merged key by key, and keys only the user set are kept.
+    pub fn merge_entry(existing: &mut serde_json::Value, generated: serde_json::Value) {
+        match (existing.as_object_mut(), generated) {
+            (Some(existing), serde_json::Value::Object(generated)) => {
+                for (key, value) in generated {
+                    match existing.get_mut(&key) {
+                        Some(current) if current.is_object() && value.is_object() => {
+                            merge_entry(current, value)
+                        }
+                        _ => {
+                            existing.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (_, generated) => *existing = generated,
+        }
+    }
+
+    /// Add or update the Schaltwerk server in a JSON config, keeping other servers and
+    /// settings.
+    pub fn upsert_json_server(
+        config: &mut serde_json::Value,
+        client: McpClient,
+        mcp_server_path: &str,
+    ) -> Result<(), String> {
+        let servers_key = client.servers_key();
+        let servers = config
+            .as_object_mut()
+            .ok_or("config is not a JSON object")?
+            .entry(servers_key)
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(|| format!("\"{servers_key}\" is not a JSON object"))?;
+        let entry = client.server_entry(mcp_server_path);
+        match servers.get_mut(SERVER_NAME) {
+            Some(existing) => merge_entry(existing, entry),
+            None => {
+                servers.insert(SERVER_NAME.to_string(), entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the Schaltwerk server from a JSON config, and the servers table with it when
+    /// no other server is left. Returns whether the server was configured.
+    pub fn remove_json_server(config: &mut serde_json::Value, client: McpClient) -> bool {
+        let servers_key = client.servers_key();
+        let Some(root) = config.as_object_mut() else {
+            return false;
+        };
+        let Some(servers) = root.get_mut(servers_key).and_then(|s| s.as_object_mut()) else {
+            return false;
+        };
+        let removed = servers.remove(SERVER_NAME).is_some();
+        if servers.is_empty() {
+            root.remove(servers_key);
+        }
+        removed
+    }
+
+    fn read_json_config(
+        config_path: &Path,
+        label: &str,
+        default: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        if !config_path.exists() {
+            return Ok(default);
+        }
+        let content = fs::read_to_string(config_path)
+            .map_err(|e| format!("Failed to read {label} config: {e}"))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {label} config JSON: {e}"))
+    }
+
+    fn write_json_config(
+        config_path: &Path,
+        label: &str,
+        config: &serde_json::Value,
+    ) -> Result<(), String> {
+        if let Some(dir) = config_path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {label} config dir: {e}"))?;
+        }
+        let content = serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Failed to serialize {label} config: {e}"))?;
+        fs::write(config_path, format!("{content}\n"))
+            .map_err(|e| format!("Failed to write {label} config: {e}"))
+    }
+
+    fn configure_json_client(
+        client: McpClient,
+        config_path: &Path,
+        label: &str,
+        default: serde_json::Value,
+        mcp_server_path: &str,
+    ) -> Result<(), String> {
+        let mut config = read_json_config(config_path, label, default)?;
+        let before = config.clone();
+        upsert_json_server(&mut config, client, mcp_server_path)
+            .map_err(|e| format!("Invalid {label} config: {e}"))?;
+        if config_path.exists() && config == before {
+            log::debug!(
+                "{label} MCP config at {} is up to date",
+                config_path.display()
+            );
+            return Ok(());
+        }
+        write_json_config(config_path, label, &config)?;
+        log::info!("Wrote {label} MCP config at {}", config_path.display());
+        Ok(())
+    }
+
+    /// Returns whether the config contained the Schaltwerk server
+    fn remove_json_client(
+        client: McpClient,
+        config_path: &Path,
+        label: &str,
+    ) -> Result<bool, String> {
+        let mut config = read_json_config(config_path, label, serde_json::json!({}))?;
+        if !remove_json_server(&mut config, client) {
+            return Ok(false);
+        }
+        write_json_config(config_path, label, &config)?;
+        Ok(true)
+    }
+
+    /// Byte range of the Schaltwerk section in a Codex config, including its subtables
+    /// such as `[mcp_servers.schaltwerk.env]`.
+    fn codex_section_range(content: &str) -> Option<std::ops::Range<usize>> {
+        let mut start = None;
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            let header = line.trim();
+            if header.starts_with('[') {
+                match start {
+                    None if header == CODEX_SECTION_HEADER => start = Some(offset),
+                    Some(start) if !header.starts_with("[mcp_servers.schaltwerk.") => {
+                        return Some(start..offset)
+                    }
+                    _ => {}
+                }
+            }
+            offset += line.len();
+        }
+        start.map(|start| start..content.len())
+    }
+
+    /// Add or update the Schaltwerk server in a Codex `config.toml`. The section is
+    /// rewritten in place, keeping keys the user added to it; the rest of the file,
+    /// comments included, is left as it is.
+    pub fn upsert_codex_server(content: &str, mcp_server_path: &str) -> Result<String, String> {
+        let range = codex_section_range(content);
+        let mut entry = match &range {
+            Some(range) => {
+                let section: toml::Table = toml::from_str(&content[range.clone()])
+                    .map_err(|e| format!("Failed to parse Codex config: {e}"))?;
+                let existing = section
+                    .get("mcp_servers")
+                    .and_then(|servers| servers.get(SERVER_NAME))
+                    .cloned()
+                    .unwrap_or_else(|| toml::Value::Table(toml::Table::new()));
+                serde_json::to_value(existing)
+                    .map_err(|e| format!("Failed to read Codex MCP entry: {e}"))?
+            }
+            None => serde_json::json!({}),
+        };
+        merge_entry(&mut entry, McpClient::Codex.server_entry(mcp_server_path));
+
+        let entry = toml::Value::try_from(entry)
+            .map_err(|e| format!("Failed to serialize Codex MCP entry: {e}"))?;
+        let mut servers = toml::Table::new();
+        servers.insert(SERVER_NAME.to_string(), entry);
+        let mut section_table = toml::Table::new();
+        section_table.insert("mcp_servers".to_string(), toml::Value::Table(servers));
+        let section = toml::to_string(&section_table)
+            .map_err(|e| format!("Failed to serialize Codex MCP entry: {e}"))?;
+
+        let mut content = content.to_string();
+        match range {
+            Some(range) => content.replace_range(range, &format!("{section}\n")),
+            None => {
+                if !content.is_empty() && !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push_str(&section);
+                content.push('\n');
+            }
+        }
+        Ok(content)
+    }
+
+    /// Remove the Schaltwerk section from a Codex `config.toml`; `None` when it has none.
+    pub fn remove_codex_server(content: &str) -> Option<String> {
+        let range = codex_section_range(content)?;
+        let mut content = content.to_string();
+        content.replace_range(range, "");
+        Some(content)
     }
 
     fn select_cli_path(
@@ -169,6 +419,7 @@ mod client {
                 custom_path: Some(custom.to_string_lossy().to_string()),
                 auto_detect: false,
                 detected_binaries: vec![],
+                detected_version: None,
             };
 
             let result = select_cli_path(Some(config), &[]).expect("cli path");
@@ -185,6 +436,7 @@ mod client {
                 custom_path: None,
                 auto_detect: true,
                 detected_binaries: vec![detected(&detected_path)],
+                detected_version: None,
             };
 
             let result = select_cli_path(Some(config), &[]).expect("cli path");
@@ -219,14 +471,7 @@ mod client {
         mcp_server_path: &str,
     ) -> Result<String, String> {
         match client {
-            McpClient::Claude => {
-                let cli_path = resolve_cli_path(McpClient::Claude)
-                    .await
-                    .ok_or_else(|| {
-                        "Claude CLI not found. Install the claude command or set a custom path in Settings → Agent Configuration.".to_string()
-                    })?;
-                configure_mcp_claude(&cli_path, project_path, mcp_server_path)
-            }
+            McpClient::Claude => configure_mcp_claude(project_path, mcp_server_path),
             McpClient::Codex => configure_mcp_codex(mcp_server_path),
             McpClient::OpenCode => configure_mcp_opencode(project_path, mcp_server_path),
             McpClient::Amp => configure_mcp_amp(mcp_server_path),
@@ -234,37 +479,19 @@ mod client {
         }
     }
 
-    fn configure_mcp_claude(
-        cli_path: &Path,
-        project_path: &str,
-        mcp_server_path: &str,
-    ) -> Result<String, String> {
-        log::info!("Configuring Claude MCP using CLI at {}", cli_path.display());
-
-        let output = Command::new(cli_path)
-            .args([
-                "mcp",
-                "add",
-                "--transport",
-                "stdio",
-                "--scope",
-                "project",
-                "schaltwerk",
-                "node",
-                mcp_server_path,
-            ])
-            .current_dir(project_path)
-            .output()
-            .map_err(|e| format!("Failed to run claude CLI at {}: {e}", cli_path.display()))?;
-
-        if !output.status.success() {
-            let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            stderr = strip_ansi(&stderr);
-            log::error!("claude CLI failed: {stderr}");
-            return Err(format!("claude CLI failed: {stderr}"));
-        }
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        log::info!("MCP configured successfully: {stdout}");
+    pub fn claude_config_path(project_path: &str) -> PathBuf {
+        PathBuf::from(project_path).join(".mcp.json")
+    }
+
+    fn configure_mcp_claude(project_path: &str, mcp_server_path: &str) -> Result<String, String> {
+        configure_json_client(
+            McpClient::Claude,
+            &claude_config_path(project_path),
+            "Claude",
+            serde_json::json!({}),
+            mcp_server_path,
+        )?;
+        issue_mcp_token(Path::new(project_path));
         Ok("MCP server configured successfully for this project".to_string())
     }
 
@@ -275,29 +502,21 @@ mod client {
                 log::info!("Created Codex config directory at {}", parent.display());
             }
         }
-        let mut content = if config_path.exists() {
+        let content = if config_path.exists() {
             fs::read_to_string(&config_path)
                 .map_err(|e| format!("Failed to read Codex config: {e}"))?
         } else {
             String::from("# Generated by Schaltwerk\n\n")
         };
-        let section_header = "[mcp_servers.schaltwerk]\n";
-        if let Some(start) = content.find(section_header) {
-            let mut end = content.len();
-            for (i, _) in content[start + section_header.len()..].match_indices('\n') {
-                let pos = start + section_header.len() + i + 1;
-                if content[pos..].starts_with('[') {
-                    end = pos;
-                    break;
-                }
-            }
-            content.replace_range(start..end, "");
-        }
-        let snippet = format!(
-            "[mcp_servers.schaltwerk]\ncommand = \"node\"\nargs = [\"{}\"]\n\n",
-            mcp_server_path.replace('"', "\\\"")
-        );
-        content.push_str(&snippet);
+        let updated = upsert_codex_server(&content, mcp_server_path)?;
+        if config_path.exists() && updated == content {
+            log::debug!(
+                "Codex MCP config at {} is up to date",
+                config_path.display()
+            );
+            return Ok("Codex MCP configured in ~/.codex/config.toml".to_string());
+        }
+        let content = updated;
         if let Some(dir) = config_path.parent() {
             fs::create_dir_all(dir)
                 .map_err(|e| format!("Failed to create Codex config dir: {e}"))?;
@@ -312,14 +531,7 @@ mod client {
 
     pub async fn remove_mcp(client: McpClient, project_path: &str) -> Result<String, String> {
         match client {
-            McpClient::Claude => {
-                let cli_path = resolve_cli_path(McpClient::Claude)
-                    .await
-                    .ok_or_else(|| {
-                        "Claude CLI not found. Install the claude command or set a custom path in Settings → Agent Configuration.".to_string()
-                    })?;
-                remove_mcp_claude(&cli_path, project_path)
-            }
+            McpClient::Claude => remove_mcp_claude(project_path),
             McpClient::Codex => remove_mcp_codex(),
             McpClient::OpenCode => remove_mcp_opencode(project_path),
             McpClient::Amp => remove_mcp_amp(),
@@ -327,17 +539,13 @@ mod client {
         }
     }
 
-    fn remove_mcp_claude(cli_path: &Path, project_path: &str) -> Result<String, String> {
-        let output = Command::new(cli_path)
-            .args(["mcp", "remove", "schaltwerk"])
-            .current_dir(project_path)
-            .output()
-            .map_err(|e| format!("Failed to run claude CLI at {}: {e}", cli_path.display()))?;
-        if !output.status.success() {
-            let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            stderr = strip_ansi(&stderr);
-            log::error!("Failed to remove MCP: {stderr}");
-            return Err(format!("Failed to remove MCP: {stderr}"));
+    fn remove_mcp_claude(project_path: &str) -> Result<String, String> {
+        if !remove_json_client(
+            McpClient::Claude,
+            &claude_config_path(project_path),
+            "Claude",
+        )? {
+            return Ok("schaltwerk MCP not present in .mcp.json".to_string());
         }
         log::info!("MCP configuration removed successfully");
         Ok("MCP server removed from project".to_string())
@@ -348,19 +556,9 @@ mod client {
         if !config_path.exists() {
             return Ok("Codex config not found".to_string());
         }
-        let mut content = fs::read_to_string(&config_path)
+        let content = fs::read_to_string(&config_path)
             .map_err(|e| format!("Failed to read Codex config: {e}"))?;
-        let section_header = "[mcp_servers.schaltwerk]\n";
-        if let Some(start) = content.find(section_header) {
-            let mut end = content.len();
-            for (i, _) in content[start + section_header.len()..].match_indices('\n') {
-                let pos = start + section_header.len() + i + 1;
-                if content[pos..].starts_with('[') {
-                    end = pos;
-                    break;
-                }
-            }
-            content.replace_range(start..end, "");
+        if let Some(content) = remove_codex_server(&content) {
             fs::write(&config_path, content)
                 .map_err(|e| format!("Failed to update Codex config: {e}"))?;
             Ok("Removed schaltwerk MCP from Codex config".to_string())
@@ -370,48 +568,23 @@ mod client {
     }
 
     pub fn generate_setup_command(client: McpClient, mcp_server_path: &str) -> String {
-        match client {
-            McpClient::Claude => format!("{} mcp add --transport stdio --scope project schaltwerk node \"{mcp_server_path}\"", client.as_str()),
-            McpClient::Codex => format!(
-                "Add to ~/.codex/config.toml:\n[mcp_servers.schaltwerk]\ncommand = \"node\"\nargs = [\"{}\"]",
-                mcp_server_path.replace('"', "\\\"")
-            ),
-            McpClient::OpenCode => format!(
-                "Add to opencode.json:\n{{\n  \"mcp\": {{\n    \"schaltwerk\": {{\n      \"type\": \"local\",\n      \"command\": [\"node\", \"{}\"],\n      \"enabled\": true\n    }}\n  }}\n}}",
-                mcp_server_path.replace('"', "\\\"")
-            ),
-            McpClient::Amp => format!(
-                "Add to ~/.config/amp/settings.json:\n{{\n  \"amp.mcpServers\": {{\n    \"schaltwerk\": {{\n      \"command\": \"node\",\n      \"args\": [\"{}\"]\n    }}\n  }}\n}}",
-                mcp_server_path.replace('"', "\\\"")
-            ),
-            McpClient::Droid => format!(
-                "Add to ~/.factory/mcp.json:\n{{\n  \"mcpServers\": {{\n    \"schaltwerk\": {{\n      \"type\": \"stdio\",\n      \"command\": \"node\",\n      \"args\": [\"{}\"]\n    }}\n  }}\n}}",
-                mcp_server_path.replace('"', "\\\"")
-            ),
-        }
-    }
-
-    fn strip_ansi(input: &str) -> String {
-        let mut out = String::with_capacity(input.len());
-        let bytes = input.as_bytes();
-        let mut i = 0usize;
-        while i < bytes.len() {
-            let ch = bytes[i];
-            if ch == 0x1B {
-                // ESC
-                i += 1;
-                while i < bytes.len() {
-                    if bytes[i] == b'm' {
-                        break;
-                    }
-                    i += 1;
-                }
-            } else {
-                out.push(ch as char);
+        let config_file = match client {
+            McpClient::Claude => {
+                return format!("{} mcp add --transport stdio --scope project schaltwerk node \"{mcp_server_path}\"", client.as_str())
             }
-            i += 1;
-        }
-        out
+            McpClient::Codex => {
+                let section = upsert_codex_server("", mcp_server_path).unwrap_or_default();
+                return format!("Add to ~/.codex/config.toml:\n{}", section.trim_end());
+            }
+            McpClient::OpenCode => "opencode.json",
+            McpClient::Amp => "~/.config/amp/settings.json",
+            McpClient::Droid => "~/.factory/mcp.json",
+        };
+        let mut config = serde_json::json!({});
+        config[client.servers_key()] =
+            serde_json::json!({ SERVER_NAME: client.server_entry(mcp_server_path) });
+        let snippet = serde_json::to_string_pretty(&config).unwrap_or_default();
+        format!("Add to {config_file}:\n{snippet}")
     }
 
     pub fn codex_config_path() -> Result<(PathBuf, bool), String> {
@@ -497,45 +670,13 @@ mod client {
             }
         }
 
-        // Read existing config or create new one
-        let config_content = if config_path.exists() {
-            std::fs::read_to_string(&config_path)
-                .map_err(|e| format!("Failed to read OpenCode config: {e}"))?
-        } else {
-            String::from("{\n  \"$schema\": \"https://opencode.ai/config.json\"\n}")
-        };
-
-        // Parse JSON to check if MCP section exists
-        let mut config: serde_json::Value = serde_json::from_str(&config_content)
-            .map_err(|e| format!("Failed to parse OpenCode config JSON: {e}"))?;
-
-        // Ensure MCP section exists
-        if config.get("mcp").is_none() {
-            config["mcp"] = serde_json::json!({});
-        }
-
-        // Add or update Schaltwerk MCP server
-        let mcp_section = config.get_mut("mcp").unwrap();
-        let schaltwerk_config = serde_json::json!({
-            "type": "local",
-            "command": ["node", mcp_server_path],
-            "enabled": true
-        });
-        mcp_section["schaltwerk"] = schaltwerk_config;
-
-        // Write updated config
-        if let Some(dir) = config_path.parent() {
-            std::fs::create_dir_all(dir)
-                .map_err(|e| format!("Failed to create OpenCode config dir: {e}"))?;
-        }
-
-        let updated_content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize OpenCode config: {e}"))?;
-
-        std::fs::write(&config_path, updated_content)
-            .map_err(|e| format!("Failed to write OpenCode config: {e}"))?;
-
-        log::info!("Wrote OpenCode MCP config at {}", config_path.display());
+        configure_json_client(
+            McpClient::OpenCode,
+            &config_path,
+            "OpenCode",
+            serde_json::json!({ "$schema": "https://opencode.ai/config.json" }),
+            mcp_server_path,
+        )?;
         Ok("OpenCode MCP configured successfully".to_string())
     }
 
@@ -546,70 +687,19 @@ mod client {
             return Ok("OpenCode config not found".to_string());
         }
 
-        let config_content = std::fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read OpenCode config: {e}"))?;
-
-        let mut config: serde_json::Value = serde_json::from_str(&config_content)
-            .map_err(|e| format!("Failed to parse OpenCode config JSON: {e}"))?;
-
-        // Remove Schaltwerk from MCP section
-        if let Some(mcp_section) = config.get_mut("mcp") {
-            if let Some(mcp_obj) = mcp_section.as_object_mut() {
-                mcp_obj.remove("schaltwerk");
-
-                // If MCP section is empty, remove it entirely
-                if mcp_obj.is_empty() {
-                    config.as_object_mut().unwrap().remove("mcp");
-                }
-            }
-        }
-
-        let updated_content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize OpenCode config: {e}"))?;
-
-        std::fs::write(&config_path, updated_content)
-            .map_err(|e| format!("Failed to update OpenCode config: {e}"))?;
-
+        remove_json_client(McpClient::OpenCode, &config_path, "OpenCode")?;
         Ok("Removed schaltwerk MCP from OpenCode config".to_string())
     }
 
     pub fn configure_mcp_amp(mcp_server_path: &str) -> Result<String, String> {
         let (config_path, _) = amp_config_path()?;
-
-        // Read existing config or create new one
-        let mut config: serde_json::Value = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .map_err(|e| format!("Failed to read Amp config: {e}"))?;
-            serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse Amp config JSON: {e}"))?
-        } else {
-            serde_json::json!({})
-        };
-
-        // Ensure amp.mcpServers object exists
-        if config.get("amp.mcpServers").is_none() {
-            config["amp.mcpServers"] = serde_json::json!({});
-        }
-
-        // Add or update schaltwerk server
-        config["amp.mcpServers"]["schaltwerk"] = serde_json::json!({
-            "command": "node",
-            "args": [mcp_server_path]
-        });
-
-        // Create parent directory if needed
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create Amp config dir: {e}"))?;
-        }
-
-        // Write back with pretty formatting
-        let content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize Amp config: {e}"))?;
-        fs::write(&config_path, content)
-            .map_err(|e| format!("Failed to write Amp config: {e}"))?;
-
-        log::info!("Wrote Amp MCP config at {}", config_path.display());
+        configure_json_client(
+            McpClient::Amp,
+            &config_path,
+            "Amp",
+            serde_json::json!({}),
+            mcp_server_path,
+        )?;
         Ok("Amp MCP configured in ~/.config/amp/settings.json".to_string())
     }
 
@@ -620,65 +710,19 @@ mod client {
             return Ok("Amp config not found".to_string());
         }
 
-        let content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read Amp config: {e}"))?;
-        let mut config: serde_json::Value = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse Amp config JSON: {e}"))?;
-
-        // Remove schaltwerk from amp.mcpServers
-        if let Some(mcp_servers) = config.get_mut("amp.mcpServers") {
-            if let Some(obj) = mcp_servers.as_object_mut() {
-                obj.remove("schaltwerk");
-
-                // If no MCP servers left, remove the section
-                if obj.is_empty() {
-                    config.as_object_mut().unwrap().remove("amp.mcpServers");
-                }
-            }
-        }
-
-        let updated_content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize Amp config: {e}"))?;
-        fs::write(&config_path, updated_content)
-            .map_err(|e| format!("Failed to update Amp config: {e}"))?;
-
+        remove_json_client(McpClient::Amp, &config_path, "Amp")?;
         Ok("Removed schaltwerk MCP from Amp config".to_string())
     }
 
     pub fn configure_mcp_droid(mcp_server_path: &str) -> Result<String, String> {
         let (config_path, _) = droid_config_path()?;
-
-        let mut config: serde_json::Value = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .map_err(|e| format!("Failed to read Factory Droid config: {e}"))?;
-            serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse Factory Droid config JSON: {e}"))?
-        } else {
-            serde_json::json!({})
-        };
-
-        if config.get("mcpServers").is_none() {
-            config["mcpServers"] = serde_json::json!({});
-        }
-
-        config["mcpServers"]["schaltwerk"] = serde_json::json!({
-            "type": "stdio",
-            "command": "node",
-            "args": [mcp_server_path],
-            "disabled": false
-        });
-
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create Factory Droid config dir: {e}"))?;
-        }
-
-        let content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize Factory Droid config: {e}"))?;
-        fs::write(&config_path, content)
-            .map_err(|e| format!("Failed to write Factory Droid config: {e}"))?;
-
-        log::info!("Wrote Factory Droid MCP config at {}", config_path.display());
+        configure_json_client(
+            McpClient::Droid,
+            &config_path,
+            "Factory Droid",
+            serde_json::json!({}),
+            mcp_server_path,
+        )?;
         Ok("Factory Droid MCP configured in ~/.factory/mcp.json".to_string())
     }
 
@@ -689,26 +733,7 @@ mod client {
             return Ok("Factory Droid config not found".to_string());
         }
 
-        let content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read Factory Droid config: {e}"))?;
-        let mut config: serde_json::Value = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse Factory Droid config JSON: {e}"))?;
-
-        if let Some(mcp_servers) = config.get_mut("mcpServers") {
-            if let Some(obj) = mcp_servers.as_object_mut() {
-                obj.remove("schaltwerk");
-
-                if obj.is_empty() {
-                    config.as_object_mut().unwrap().remove("mcpServers");
-                }
-            }
-        }
-
-        let updated_content = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize Factory Droid config: {e}"))?;
-        fs::write(&config_path, updated_content)
-            .map_err(|e| format!("Failed to update Factory Droid config: {e}"))?;
-
+        remove_json_client(McpClient::Droid, &config_path, "Factory Droid")?;
         Ok("Removed schaltwerk MCP from Factory Droid config".to_string())
     }
 }
@@ -972,6 +997,46 @@ pub async fn remove_mcp_for_project(
     client::remove_mcp(client, &project_path).await
 }
 
+/// Bring the Schaltwerk entry of every agent config that has one in line with the current
+/// MCP server, e.g. after the app was moved. Returns the agents whose config has the entry.
+#[tauri::command]
+pub async fn sync_mcp_configs(project_path: String) -> Result<Vec<String>, String> {
+    let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+    let (mcp_path, _) = detect_mcp_server_location(&exe_path)?;
+    let mcp_path = mcp_path.to_string_lossy();
+
+    let mut synced = Vec::new();
+    for client in client::McpClient::ALL {
+        if !check_mcp_configuration_status(&project_path, client) {
+            continue;
+        }
+        match client::configure_mcp(client, &project_path, &mcp_path).await {
+            Ok(_) => synced.push(client.as_str().to_string()),
+            Err(e) => log::warn!("Failed to sync {} MCP config: {e}", client.as_str()),
+        }
+    }
+    Ok(synced)
+}
+
+/// Issue the project's MCP token for this run and store it in the project's `.mcp.json`,
+/// together with the API socket when there is one, so the bridge started by the agent can
+/// reach the app and authenticate.
+pub fn issue_mcp_token(project_path: &Path) {
+    let token = auth::project_token(project_path);
+    let socket = socket::listening_socket();
+    match auth::write_connection_to_mcp_config(project_path, &token, socket) {
+        Ok(true) => log::info!("Updated MCP token in .mcp.json for {}", project_path.display()),
+        Ok(false) => log::debug!(
+            "No Schaltwerk entry in .mcp.json for {}, MCP token only passed through the agent environment",
+            project_path.display()
+        ),
+        Err(e) => log::warn!(
+            "Failed to write MCP token for {}: {e}",
+            project_path.display()
+        ),
+    }
+}
+
 #[tauri::command]
 pub async fn ensure_mcp_gitignored(project_path: String) -> Result<String, String> {
     log::info!("Ensuring .mcp.json is in gitignore for project: {project_path}");
@@ -1011,6 +1076,94 @@ pub async fn ensure_mcp_gitignored(project_path: String) -> Result<String, Strin
     Ok("Added to gitignore".to_string())
 }
 
+#[cfg(test)]
+mod tests_mcp_entries {
+    use super::client::*;
+
+    #[test]
+    fn json_entry_is_merged_into_user_config() {
+        let mut config = serde_json::json!({
+            "mcpServers": {
+                "schaltwerk": {
+                    "command": "node",
+                    "args": ["/old/server.js"],
+                    "env": { "DEBUG": "1", "SCHALTWERK_MCP_TOKEN": "token" }
+                },
+                "playwright": { "command": "npx", "args": ["@playwright/mcp@latest"] }
+            },
+            "theme": "dark"
+        });
+
+        upsert_json_server(&mut config, McpClient::Claude, "/new/server.js").unwrap();
+
+        let entry = &config["mcpServers"]["schaltwerk"];
+        assert_eq!(entry["type"], "stdio");
+        assert_eq!(entry["args"], serde_json::json!(["/new/server.js"]));
+        assert_eq!(entry["env"]["DEBUG"], "1");
+        assert_eq!(entry["env"]["SCHALTWERK_MCP_TOKEN"], "token");
+        assert!(config["mcpServers"]["playwright"].is_object());
+        assert_eq!(config["theme"], "dark");
+
+        assert!(remove_json_server(&mut config, McpClient::Claude));
+        assert!(config["mcpServers"]["schaltwerk"].is_null());
+        assert!(config["mcpServers"]["playwright"].is_object());
+        assert!(!remove_json_server(&mut config, McpClient::Claude));
+    }
+
+    #[test]
+    fn json_entry_rejects_non_object_servers_table() {
+        let mut config = serde_json::json!({ "mcp": ["not", "a", "table"] });
+        let err = upsert_json_server(&mut config, McpClient::OpenCode, "/server.js").unwrap_err();
+        assert!(err.contains("\"mcp\""));
+    }
+
+    #[test]
+    fn codex_section_is_updated_in_place() {
+        let content = r#"# my settings
+model = "o3"
+
+[mcp_servers.schaltwerk]
+command = "node"
+args = ["/old/server.js"]
+startup_timeout_sec = 30
+
+[mcp_servers.schaltwerk.env]
+DEBUG = "1"
+
+[mcp_servers.playwright]
+command = "npx"
+"#;
+        let updated = upsert_codex_server(content, "/new/server.js").unwrap();
+
+        assert!(updated.starts_with("# my settings\nmodel = \"o3\"\n\n[mcp_servers.schaltwerk]"));
+        assert!(updated.contains("args = [\"/new/server.js\"]"));
+        assert!(!updated.contains("/old/server.js"));
+        assert!(updated.contains("startup_timeout_sec = 30"));
+        assert!(updated.contains("[mcp_servers.schaltwerk.env]\nDEBUG = \"1\""));
+        assert!(updated.ends_with("[mcp_servers.playwright]\ncommand = \"npx\"\n"));
+        assert_eq!(
+            upsert_codex_server(&updated, "/new/server.js").unwrap(),
+            updated
+        );
+
+        let removed = remove_codex_server(&updated).unwrap();
+        assert!(!removed.contains("schaltwerk"));
+        assert!(removed.contains("[mcp_servers.playwright]"));
+        assert_eq!(remove_codex_server(&removed), None);
+    }
+
+    #[test]
+    fn codex_section_is_appended_when_missing() {
+        let updated = upsert_codex_server("model = \"o3\"", "/path/with\"quote.js").unwrap();
+        let config: toml::Table = toml::from_str(&updated).unwrap();
+        assert_eq!(config["model"].as_str(), Some("o3"));
+        assert_eq!(
+            config["mcp_servers"]["schaltwerk"]["args"][0].as_str(),
+            Some("/path/with\"quote.js")
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests_amp_mcp {
     use super::client::*;
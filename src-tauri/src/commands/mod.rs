@@ -1,7 +1,9 @@
 pub mod agent_binaries;
 pub mod clipboard;
+pub mod crash;
 pub mod git;
 pub mod github;
+pub mod logs;
 pub mod mcp;
 pub mod mcp_config;
 pub mod project;
@@ -19,39 +21,76 @@ mod tests;
 
 // Export schaltwerk_core commands individually to avoid unused import warnings
 pub use agent_binaries::*;
+pub use crash::*;
 pub use git::*;
 pub use github::*;
+pub use logs::*;
 pub use mcp::*;
 pub use mcp_config::*;
 pub use project::*;
 pub use pty::*;
+pub use schaltwerk_core::crash_restarts::{
+    schaltwerk_core_list_agent_crashes, start_agent_crash_supervisor,
+};
+pub use schaltwerk_core::database_backups::{
+    backup_database, check_database_integrity, list_database_backups, restore_database,
+    start_database_backups,
+};
+pub use schaltwerk_core::merge_queue::{
+    schaltwerk_core_enqueue_merge, schaltwerk_core_get_merge_queue,
+    schaltwerk_core_remove_from_merge_queue,
+};
+pub use schaltwerk_core::session_creation::schaltwerk_core_cancel_session_creation;
+pub use schaltwerk_core::session_setup::{
+    schaltwerk_core_cancel_session_setup, schaltwerk_core_get_session_setup_status,
+    schaltwerk_core_run_session_setup,
+};
+pub use schaltwerk_core::stale_sessions::{
+    schaltwerk_core_preview_stale_sessions, start_stale_session_cleanup,
+};
 pub use schaltwerk_core::{
-    schaltwerk_core_append_spec_content, schaltwerk_core_archive_spec_session,
-    schaltwerk_core_cancel_session, schaltwerk_core_cleanup_orphaned_worktrees,
+    add_review_comment, apply_prompt_template, delete_prompt_template, generate_commit_message,
+    get_agent_progress, get_session_cost_report, get_session_time_report, get_worktree_disk_usage,
+    list_pending_follow_ups, list_prompt_templates, list_review_comments, prune_worktrees,
+    render_prompt_template, request_review_changes, resolve_review_comment, save_prompt_template,
+    schaltwerk_core_add_session_label, schaltwerk_core_append_spec_content,
+    schaltwerk_core_archive_spec_session, schaltwerk_core_cancel_session,
+    schaltwerk_core_cherry_pick_session_commits, schaltwerk_core_cleanup_orphaned_worktrees,
     schaltwerk_core_convert_session_to_draft, schaltwerk_core_create_and_start_spec_session,
     schaltwerk_core_create_session, schaltwerk_core_create_spec_session,
     schaltwerk_core_delete_archived_spec, schaltwerk_core_discard_file_in_orchestrator,
-    schaltwerk_core_discard_file_in_session, schaltwerk_core_get_agent_type,
+    schaltwerk_core_discard_file_in_session, schaltwerk_core_discard_hunks_in_session,
+    schaltwerk_core_duplicate_session, schaltwerk_core_get_agent_type,
     schaltwerk_core_get_archive_max_entries, schaltwerk_core_get_font_sizes,
     schaltwerk_core_get_merge_preview, schaltwerk_core_get_orchestrator_agent_type,
     schaltwerk_core_get_orchestrator_skip_permissions, schaltwerk_core_get_session,
-    schaltwerk_core_get_session_agent_content, schaltwerk_core_get_skip_permissions,
+    schaltwerk_core_get_session_agent_content, schaltwerk_core_get_session_environment,
+    schaltwerk_core_get_session_notes, schaltwerk_core_get_skip_permissions,
     schaltwerk_core_has_uncommitted_changes, schaltwerk_core_list_archived_specs,
     schaltwerk_core_list_enriched_sessions, schaltwerk_core_list_enriched_sessions_sorted,
-    schaltwerk_core_list_project_files, schaltwerk_core_list_sessions,
-    schaltwerk_core_list_sessions_by_state, schaltwerk_core_mark_session_ready,
-    schaltwerk_core_merge_session_to_main, schaltwerk_core_rename_draft_session,
-    schaltwerk_core_rename_version_group, schaltwerk_core_reset_orchestrator,
-    schaltwerk_core_reset_session_worktree, schaltwerk_core_restore_archived_spec,
-    schaltwerk_core_set_agent_type, schaltwerk_core_set_archive_max_entries,
-    schaltwerk_core_set_font_sizes, schaltwerk_core_set_orchestrator_agent_type,
-    schaltwerk_core_set_orchestrator_skip_permissions, schaltwerk_core_set_session_agent_type,
-    schaltwerk_core_set_skip_permissions, schaltwerk_core_start_claude,
-    schaltwerk_core_start_claude_orchestrator, schaltwerk_core_start_claude_with_restart,
-    schaltwerk_core_start_fresh_orchestrator, schaltwerk_core_start_session_agent,
-    schaltwerk_core_start_session_agent_with_restart, schaltwerk_core_start_spec_session,
+    schaltwerk_core_list_project_files, schaltwerk_core_list_session_file_hunks,
+    schaltwerk_core_list_session_labels, schaltwerk_core_list_session_stashes,
+    schaltwerk_core_list_sessions, schaltwerk_core_list_sessions_by_state,
+    schaltwerk_core_mark_session_ready, schaltwerk_core_merge_session_dry_run,
+    schaltwerk_core_merge_session_to_main, schaltwerk_core_pop_session_stash,
+    schaltwerk_core_rebase_session_on_base, schaltwerk_core_relocate_session_worktree,
+    schaltwerk_core_remove_session_label, schaltwerk_core_rename_draft_session,
+    schaltwerk_core_rename_session, schaltwerk_core_rename_version_group,
+    schaltwerk_core_reset_orchestrator, schaltwerk_core_reset_session_worktree,
+    schaltwerk_core_restore_archived_spec, schaltwerk_core_set_agent_type,
+    schaltwerk_core_set_archive_max_entries, schaltwerk_core_set_font_sizes,
+    schaltwerk_core_set_orchestrator_agent_type, schaltwerk_core_set_orchestrator_skip_permissions,
+    schaltwerk_core_set_session_agent_type, schaltwerk_core_set_session_auto_nudge,
+    schaltwerk_core_set_session_codex_profile, schaltwerk_core_set_session_dependencies,
+    schaltwerk_core_set_session_environment, schaltwerk_core_set_session_labels,
+    schaltwerk_core_set_session_notes, schaltwerk_core_set_skip_permissions,
+    schaltwerk_core_start_claude, schaltwerk_core_start_claude_orchestrator,
+    schaltwerk_core_start_claude_with_restart, schaltwerk_core_start_fresh_orchestrator,
+    schaltwerk_core_start_session_agent, schaltwerk_core_start_session_agent_with_restart,
+    schaltwerk_core_start_spec_session, schaltwerk_core_stash_session_changes,
     schaltwerk_core_unmark_session_ready, schaltwerk_core_update_git_stats,
-    schaltwerk_core_update_session_state, schaltwerk_core_update_spec_content,
+    schaltwerk_core_update_session_state, schaltwerk_core_update_spec_content, summarize_spec,
+    switch_session_agent,
 };
 pub use settings::*;
 pub use terminal::*;
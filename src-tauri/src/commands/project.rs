@@ -65,7 +65,9 @@ pub async fn initialize_project(
     services: State<'_, ServiceHandles>,
     path: String,
 ) -> Result<(), String> {
-    services.projects.initialize_project(path).await
+    services.projects.initialize_project(path.clone()).await?;
+    super::issue_mcp_token(std::path::Path::new(&path));
+    Ok(())
 }
 
 #[tauri::command]
@@ -1,6 +1,7 @@
 use pty_host::{
-    AckRequest, KillRequest, ResizeRequest, SpawnOptions, SpawnRequest, SpawnResponse,
-    SubscribeRequest, SubscribeResponse, WriteRequest,
+    AckRequest, ExportRecordingRequest, KillRequest, RecordingRequest, RecordingResponse,
+    ResizeRequest, SpawnOptions, SpawnRequest, SpawnResponse, SubscribeRequest, SubscribeResponse,
+    WriteRequest,
 };
 use serde::Deserialize;
 use tauri::AppHandle;
@@ -64,6 +65,35 @@ pub async fn pty_ack(
         .await
 }
 
+/// Start writing an asciicast recording of the terminal's output.
+#[tauri::command]
+pub async fn start_terminal_recording(term_id: String) -> Result<RecordingResponse, String> {
+    get_pty_host()
+        .start_recording(RecordingRequest { term_id })
+        .await
+}
+
+#[tauri::command]
+pub async fn stop_terminal_recording(term_id: String) -> Result<RecordingResponse, String> {
+    get_pty_host()
+        .stop_recording(RecordingRequest { term_id })
+        .await
+}
+
+/// Copy the terminal's latest recording to `destination`, also after the terminal exited.
+#[tauri::command]
+pub async fn export_terminal_recording(
+    term_id: String,
+    destination: String,
+) -> Result<RecordingResponse, String> {
+    get_pty_host()
+        .export_recording(ExportRecordingRequest {
+            term_id,
+            destination,
+        })
+        .await
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SubscribeParams {
     pub term_id: String,
@@ -1,37 +1,64 @@
 use crate::{
     commands::session_lookup_cache::global_session_lookup_cache, get_core_read, get_core_write,
-    get_file_watcher_manager, get_terminal_manager, SETTINGS_MANAGER,
+    get_file_watcher_manager, get_schaltwerk_core, get_terminal_manager, SETTINGS_MANAGER,
 };
-use schaltwerk::domains::agents::{manifest::AgentManifest, naming, parse_agent_command};
-use schaltwerk::domains::git::repository;
+use schaltwerk::domains::agents::{
+    commit_message, manifest::AgentManifest, naming, parse_agent_command, spec_summary,
+};
+use schaltwerk::domains::git::{repository, HookFailure, SessionRebaseOutcome, SessionStash};
 use schaltwerk::domains::merge::types::MergeStateSnapshot;
-use schaltwerk::domains::merge::{MergeMode, MergeOutcome, MergePreview, MergeService};
+use schaltwerk::domains::merge::{
+    CherryPickOutcome, MergeDryRun, MergeMode, MergeOutcome, MergePreview, MergeService,
+};
 use schaltwerk::domains::sessions::cache::{cache_worktree_size, get_cached_worktree_size};
 use schaltwerk::domains::sessions::db_sessions::SessionMethods;
 use schaltwerk::domains::sessions::entity::{
-    EnrichedSession, FilterMode, Session, SessionState, SortMode,
+    EnrichedSession, FilterMode, NewReviewComment, PendingFollowUp, PromptTemplate,
+    PromptTemplateTarget, ReviewChecklistItem, ReviewComment, Session, SessionCostReport,
+    SessionNotes, SessionState, SessionTimeReport, SortMode, WorktreeDiskUsage,
+    WorktreePruneReport,
 };
+use schaltwerk::domains::sessions::follow_ups;
 use schaltwerk::domains::sessions::storage::compute_worktree_size_bytes;
-use schaltwerk::domains::terminal::{
-    build_login_shell_invocation_with_shell, get_effective_shell, sh_quote_string,
-    shell_invocation_to_posix,
-};
+use schaltwerk::domains::settings::SettingsChange;
+use schaltwerk::domains::terminal::run_status::run_terminal_id;
+use schaltwerk::domains::terminal::sh_quote_string;
+use schaltwerk::domains::terminal::TerminalManager;
 use schaltwerk::domains::workspace::get_project_files_with_status;
+use schaltwerk::infrastructure::events::outbound::{notify_outbound, OutboundEvent};
 use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
+use schaltwerk::infrastructure::logging::{in_current_log_context, with_log_context, LogContext};
 use schaltwerk::schaltwerk_core::db_app_config::AppConfigMethods;
 use schaltwerk::schaltwerk_core::db_project_config::{ProjectConfigMethods, DEFAULT_BRANCH_PREFIX};
-use schaltwerk::schaltwerk_core::SessionManager;
+use schaltwerk::schaltwerk_core::{Database, SchaltwerkCore, SessionManager};
 use schaltwerk::services::ServiceHandles;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration as StdDuration;
 use tauri::State;
+use tokio::sync::RwLock;
 mod agent_ctx;
 pub mod agent_launcher;
+pub mod crash_restarts;
+pub mod database_backups;
 pub mod events;
+pub mod merge_queue;
 mod schaltwerk_core_cli;
+pub mod session_creation;
+pub mod session_setup;
+pub mod stale_sessions;
 pub mod terminals;
 
+fn quote_command(program: &str, args: &[String]) -> String {
+    let mut command = sh_quote_string(program);
+    for arg in args {
+        command.push(' ');
+        command.push_str(&sh_quote_string(arg));
+    }
+    command
+}
+
 // Helper functions for session name parsing
 fn is_version_suffix(s: &str) -> bool {
     s.starts_with('v') && s.len() > 1 && s[1..].chars().all(|c| c.is_numeric())
@@ -73,6 +100,31 @@ fn summarize_error(message: &str) -> String {
         .to_string()
 }
 
+/// Print the output of git hooks that rejected a Schaltwerk commit into the session's
+/// run terminal, so it is readable in full rather than only as a one-line error.
+async fn surface_hook_failure(session_name: &str, err: &anyhow::Error) {
+    let Some(failure) = err.downcast_ref::<HookFailure>() else {
+        return;
+    };
+    let terminal_id = run_terminal_id(Some(session_name));
+    let message = format!(
+        "\r\n\x1b[33m[schaltwerk] git hooks ({}) rejected the commit:\x1b[0m\r\n{}\r\n",
+        failure.hooks.join(", "),
+        failure.output.replace('\n', "\r\n")
+    );
+    let result = match get_terminal_manager().await {
+        Ok(manager) => {
+            manager
+                .append_terminal_output(&terminal_id, message.as_bytes())
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        log::debug!("Hook output for '{session_name}' not shown in {terminal_id}: {e}");
+    }
+}
+
 fn emit_terminal_agent_started(
     app: &tauri::AppHandle,
     terminal_id: &str,
@@ -97,6 +149,15 @@ fn emit_terminal_agent_started(
     }
 }
 
+/// The agent a session without stored settings runs with: the project's default agent,
+/// then the global one.
+fn default_agent_type(db: &Database, repo_path: &Path) -> String {
+    SessionManager::new(db.clone(), repo_path.to_path_buf())
+        .default_agent_settings()
+        .map(|(agent_type, _)| agent_type)
+        .unwrap_or_else(|_| "claude".to_string())
+}
+
 fn get_agent_env_and_cli_args(agent_type: &str) -> (Vec<(String, String)>, String, Option<String>) {
     if let Some(settings_manager) = SETTINGS_MANAGER.get() {
         let manager = futures::executor::block_on(settings_manager.lock());
@@ -140,6 +201,7 @@ async fn session_manager_read() -> Result<SessionManager, String> {
 // (no local wrappers needed)
 
 #[tauri::command]
+#[tracing::instrument(level = "trace", skip_all)]
 pub async fn schaltwerk_core_list_enriched_sessions(
     services: State<'_, ServiceHandles>,
 ) -> Result<Vec<EnrichedSession>, String> {
@@ -154,7 +216,44 @@ pub async fn schaltwerk_core_get_merge_preview(name: String) -> Result<MergePrev
     };
 
     let service = MergeService::new(db, repo_path);
-    service.preview(&name).map_err(|e| e.to_string())
+    service.preview_with_overlaps(&name).map_err(|e| e.to_string())
+}
+
+/// Check whether a session merges cleanly and what it would change, without touching
+/// the parent branch.
+#[tauri::command]
+pub async fn schaltwerk_core_merge_session_dry_run(name: String) -> Result<MergeDryRun, String> {
+    let (db, repo_path) = {
+        let core = get_core_read().await?;
+        (core.db.clone(), core.repo_path.clone())
+    };
+
+    let service = MergeService::new(db, repo_path);
+    service
+        .merge_session_dry_run(&name)
+        .map_err(|e| format!("Failed to dry-run merge for session {name}: {e}"))
+}
+
+/// Apply selected commits from a session onto its base branch without merging the
+/// rest of the branch. Commits are picked oldest first whatever the selection order.
+#[tauri::command]
+pub async fn schaltwerk_core_cherry_pick_session_commits(
+    app: tauri::AppHandle,
+    session_name: String,
+    commits: Vec<String>,
+) -> Result<CherryPickOutcome, String> {
+    let (db, repo_path) = {
+        let core = get_core_read().await?;
+        (core.db.clone(), core.repo_path.clone())
+    };
+
+    let service = MergeService::new(db, repo_path);
+    let outcome = service
+        .cherry_pick_session_commits(&session_name, commits)
+        .await
+        .map_err(|e| format!("Failed to cherry-pick commits from session {session_name}: {e}"))?;
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::MergeWorkflow);
+    Ok(outcome)
 }
 
 #[derive(Debug, Clone)]
@@ -168,6 +267,7 @@ pub async fn merge_session_with_events(
     name: &str,
     mode: MergeMode,
     commit_message: Option<String>,
+    confirm_protected_paths: bool,
 ) -> Result<MergeOutcome, MergeCommandError> {
     let (db, repo_path) = match get_core_write().await {
         Ok(core) => (core.db.clone(), core.repo_path.clone()),
@@ -179,7 +279,30 @@ pub async fn merge_session_with_events(
         }
     };
 
-    let service = MergeService::new(db, repo_path);
+    merge_session_in_repo(
+        app,
+        db,
+        repo_path,
+        name,
+        mode,
+        commit_message,
+        confirm_protected_paths,
+    )
+    .await
+}
+
+/// The merge flow of `merge_session_with_events` for a given project rather than the
+/// current one, so merges from the queue land where they were queued.
+pub async fn merge_session_in_repo(
+    app: &tauri::AppHandle,
+    db: schaltwerk::schaltwerk_core::Database,
+    repo_path: PathBuf,
+    name: &str,
+    mode: MergeMode,
+    commit_message: Option<String>,
+    confirm_protected_paths: bool,
+) -> Result<MergeOutcome, MergeCommandError> {
+    let service = MergeService::new(db.clone(), repo_path.clone());
     let preview = service.preview(name).map_err(|e| MergeCommandError {
         message: e.to_string(),
         conflict: false,
@@ -193,7 +316,10 @@ pub async fn merge_session_with_events(
         mode.as_str(),
     );
 
-    match service.merge(name, mode, commit_message).await {
+    match service
+        .merge(name, mode, commit_message, confirm_protected_paths)
+        .await
+    {
         Ok(outcome) => {
             events::emit_git_operation_completed(
                 app,
@@ -203,10 +329,30 @@ pub async fn merge_session_with_events(
                 outcome.mode.as_str(),
                 &outcome.new_commit,
             );
+            notify_outbound(
+                &repo_path,
+                OutboundEvent::MergeCompleted,
+                name,
+                serde_json::json!({
+                    "mode": outcome.mode.as_str(),
+                    "session_branch": outcome.session_branch,
+                    "parent_branch": outcome.parent_branch,
+                    "commit": outcome.new_commit,
+                }),
+            );
+            match SessionManager::new(db, repo_path).record_dependency_merged(name) {
+                Ok(unblocked) => {
+                    for session_name in unblocked {
+                        events::emit_session_unblocked(app, &session_name, name);
+                    }
+                }
+                Err(e) => log::warn!("Failed to record merged dependency {name}: {e}"),
+            }
             events::request_sessions_refreshed(app, events::SessionsRefreshReason::MergeWorkflow);
             Ok(outcome)
         }
         Err(err) => {
+            surface_hook_failure(name, &err).await;
             let raw_message = err.to_string();
             let conflict = is_conflict_error(&raw_message);
             let summary = summarize_error(&raw_message);
@@ -233,17 +379,29 @@ pub async fn merge_session_with_events(
     }
 }
 
+/// Merge a session into its parent branch. `confirm_protected_paths` acknowledges
+/// changes to the project's protected paths, which are refused otherwise.
 #[tauri::command]
 pub async fn schaltwerk_core_merge_session_to_main(
     app: tauri::AppHandle,
     name: String,
     mode: MergeMode,
     commit_message: Option<String>,
+    confirm_protected_paths: Option<bool>,
 ) -> Result<(), String> {
-    merge_session_with_events(&app, &name, mode, commit_message)
-        .await
-        .map(|_| ())
-        .map_err(|err| err.message)
+    with_log_context(
+        LogContext::for_session(&name),
+        merge_session_with_events(
+            &app,
+            &name,
+            mode,
+            commit_message,
+            confirm_protected_paths.unwrap_or(false),
+        ),
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| err.message)
 }
 
 #[tauri::command]
@@ -402,6 +560,9 @@ pub struct CreateSessionParams {
     version_number: Option<i32>,
     agent_type: Option<String>,
     skip_permissions: Option<bool>,
+    agent_model: Option<String>,
+    force_setup: Option<bool>,
+    creation_id: Option<String>,
 }
 
 #[tauri::command]
@@ -417,7 +578,11 @@ pub async fn schaltwerk_core_create_session(
     version_number: Option<i32>,
     agent_type: Option<String>,
     skip_permissions: Option<bool>,
+    agent_model: Option<String>,
+    force_setup: Option<bool>,
+    creation_id: Option<String>,
 ) -> Result<Session, String> {
+    let context = LogContext::for_new_session(&name);
     // Wrap in params struct to avoid clippy warning about too many arguments
     let params = CreateSessionParams {
         name,
@@ -429,7 +594,17 @@ pub async fn schaltwerk_core_create_session(
         version_number,
         agent_type,
         skip_permissions,
+        agent_model,
+        force_setup,
+        creation_id,
     };
+    with_log_context(context, create_session_with_events(app, params)).await
+}
+
+async fn create_session_with_events(
+    app: tauri::AppHandle,
+    params: CreateSessionParams,
+) -> Result<Session, String> {
     let was_user_edited = params.user_edited_name.unwrap_or(false);
     // Consider it auto-generated if:
     // 1. It looks like a Docker-style name (adjective_noun format) AND wasn't user edited
@@ -446,39 +621,31 @@ pub async fn schaltwerk_core_create_session(
         version_number: params.version_number,
         agent_type: params.agent_type.as_deref(),
         skip_permissions: params.skip_permissions,
+        agent_model: params.agent_model.as_deref(),
     };
-    let session = {
+    // Registered before waiting for the core lock so a cancel issued meanwhile is honoured
+    let cancel = params
+        .creation_id
+        .as_deref()
+        .map(session_creation::register_creation)
+        .unwrap_or_default();
+    let created = {
         let core = get_core_write().await?;
         let manager = core.session_manager();
-        manager
-            .create_session_with_agent(creation_params)
-            .map_err(|e| format!("Failed to create session: {e}"))?
+        manager.create_session_cancellable(creation_params, &cancel)
     };
+    if let Some(creation_id) = params.creation_id.as_deref() {
+        session_creation::finish_creation(creation_id);
+    }
+    let session = created.map_err(|e| format!("Failed to create session: {e}"))?;
+    if params.force_setup.unwrap_or(false) {
+        session_setup::request_forced_setup(&session.name);
+    }
 
     let session_name_clone = session.name.clone();
     let app_handle = app.clone();
 
-    #[derive(serde::Serialize, Clone)]
-    struct SessionAddedPayload {
-        session_name: String,
-        branch: String,
-        worktree_path: String,
-        parent_branch: String,
-        created_at: String,
-        last_modified: Option<String>,
-    }
-    let _ = emit_event(
-        &app,
-        SchaltEvent::SessionAdded,
-        &SessionAddedPayload {
-            session_name: session.name.clone(),
-            branch: session.branch.clone(),
-            worktree_path: session.worktree_path.to_string_lossy().to_string(),
-            parent_branch: session.parent_branch.clone(),
-            created_at: session.created_at.to_rfc3339(),
-            last_modified: session.last_activity.map(|ts| ts.to_rfc3339()),
-        },
-    );
+    events::emit_session_added(&app, &session);
 
     // Only trigger auto-rename for non-versioned Docker-style names
     // Versioned names (ending with _v1, _v2, etc.) will be handled by group rename
@@ -487,7 +654,7 @@ pub async fn schaltwerk_core_create_session(
             "Session '{}' was auto-generated (non-versioned), spawning name generation agent",
             params.name
         );
-        tokio::spawn(async move {
+        tokio::spawn(in_current_log_context(async move {
             let (
                 (session_id, worktree_path, repo_path, current_branch, agent, initial_prompt),
                 db_clone,
@@ -520,11 +687,10 @@ pub async fn schaltwerk_core_create_session(
                     log::info!("Session '{session_name_clone}' does not have pending_name_generation flag, skipping");
                     return;
                 }
-                let agent = session.original_agent_type.clone().unwrap_or_else(|| {
-                    core.db
-                        .get_agent_type()
-                        .unwrap_or_else(|_| "claude".to_string())
-                });
+                let agent = session
+                    .original_agent_type
+                    .clone()
+                    .unwrap_or_else(|| default_agent_type(&core.db, &core.repo_path));
 
                 log::info!(
                     "Using agent '{agent}' for name generation of session '{session_name_clone}'"
@@ -629,7 +795,7 @@ pub async fn schaltwerk_core_create_session(
                     let _ = db_clone.set_pending_name_generation(&session_id, false);
                 }
             }
-        });
+        }));
     } else {
         log::info!(
             "Session '{}' was_auto_generated={}, has_prompt={}, skipping name generation",
@@ -642,6 +808,45 @@ pub async fn schaltwerk_core_create_session(
     Ok(session)
 }
 
+/// Fork a running session into a new worktree branched from its current HEAD.
+#[tauri::command]
+pub async fn schaltwerk_core_duplicate_session(
+    app: tauri::AppHandle,
+    session_name: String,
+    new_name: String,
+) -> Result<Session, String> {
+    let session = {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .duplicate_session(&session_name, &new_name)
+            .map_err(|e| format!("Failed to duplicate session {session_name}: {e}"))?
+    };
+    events::emit_session_added(&app, &session);
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(session)
+}
+
+/// Move a session's worktree into the project's configured worktree root.
+/// Its terminals are closed first since their working directory moves away; refused
+/// while the session's agent is running.
+#[tauri::command]
+pub async fn schaltwerk_core_relocate_session_worktree(
+    app: tauri::AppHandle,
+    session_name: String,
+) -> Result<String, String> {
+    let terminal_manager = get_terminal_manager().await.ok();
+    terminals::ensure_agent_stopped(terminal_manager.as_deref(), &session_name).await?;
+    terminals::close_session_terminals_if_any(&session_name).await;
+    let new_path = {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .relocate_session_worktree(&session_name)
+            .map_err(|e| format!("Failed to relocate worktree of session {session_name}: {e}"))?
+    };
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(new_path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 pub async fn schaltwerk_core_rename_version_group(
     app: tauri::AppHandle,
@@ -709,7 +914,7 @@ pub async fn schaltwerk_core_rename_version_group(
     let agent_type = first_session
         .original_agent_type
         .clone()
-        .unwrap_or_else(|| db.get_agent_type().unwrap_or_else(|_| "claude".to_string()));
+        .unwrap_or_else(|| default_agent_type(&db, &repo_path));
 
     // Get environment variables for the agent
     let (mut env_vars, cli_args, binary_path) = get_agent_env_and_cli_args(&agent_type);
@@ -852,18 +1057,32 @@ pub async fn schaltwerk_core_get_session_agent_content(
 pub async fn schaltwerk_core_cancel_session(
     app: tauri::AppHandle,
     name: String,
+) -> Result<(), String> {
+    let core = get_schaltwerk_core().await?;
+    let terminal_manager = get_terminal_manager().await.ok();
+    cancel_session_in_project(app, core, terminal_manager, name).await
+}
+
+/// Cancel a session of the project owning `core` and `terminal_manager`, which need not be
+/// the project currently open in the UI.
+pub async fn cancel_session_in_project(
+    app: tauri::AppHandle,
+    core: Arc<RwLock<SchaltwerkCore>>,
+    terminal_manager: Option<Arc<TerminalManager>>,
+    name: String,
 ) -> Result<(), String> {
     log::info!("Starting cancel session: {name}");
 
     // Determine session state first to handle Spec vs non-Spec behavior
-    let (is_spec, repo_path_str, archive_count_after_opt) = {
-        let core = get_core_write().await?;
+    let (is_spec, repo_path_str, archive_count_after_opt, worktree_path) = {
+        let core = core.write().await;
         let manager = core.session_manager();
 
         let session = manager.get_session(&name).map_err(|e| {
             log::error!("Cancel {name}: Session not found: {e}");
             format!("Session not found: {e}")
         })?;
+        let worktree_path = session.worktree_path.clone();
 
         if session.session_state == schaltwerk::domains::sessions::entity::SessionState::Spec {
             // Archive spec sessions instead of deleting
@@ -872,13 +1091,13 @@ pub async fn schaltwerk_core_cancel_session(
                 .map_err(|e| format!("Failed to archive spec: {e}"))?;
             let repo = core.repo_path.to_string_lossy().to_string();
             let count = manager.list_archived_specs().map(|v| v.len()).unwrap_or(0);
-            (true, repo, Some(count))
+            (true, repo, Some(count), worktree_path)
         } else {
             // For non-spec, archive prompt first, then continue with cancellation flow
             if let Err(e) = manager.archive_prompt_for_session(&name) {
                 log::warn!("Cancel {name}: Failed to archive prompt before cancel: {e}");
             }
-            (false, core.repo_path.to_string_lossy().to_string(), None)
+            (false, core.repo_path.to_string_lossy().to_string(), None, worktree_path)
         }
     };
 
@@ -890,7 +1109,9 @@ pub async fn schaltwerk_core_cancel_session(
         evict_session_cache_entry_for_repo(&repo_path_str, &name).await;
         events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
 
-        terminals::close_session_terminals_if_any(&name).await;
+        if let Some(terminal_manager) = &terminal_manager {
+            terminals::close_session_terminals_in(terminal_manager, &name).await;
+        }
         return Ok(());
     }
 
@@ -905,7 +1126,7 @@ pub async fn schaltwerk_core_cancel_session(
 
         // Always close terminals BEFORE removing the worktree to avoid leaving
         // shells in deleted directories (which causes getcwd errors in tools like `just`).
-        if let Ok(terminal_manager) = get_terminal_manager().await {
+        if let Some(terminal_manager) = terminal_manager {
             let mut ids: HashSet<String> = HashSet::new();
             ids.insert(terminals::terminal_id_for_session_top(&name_for_bg));
             ids.insert(terminals::terminal_id_for_session_bottom(&name_for_bg));
@@ -920,20 +1141,27 @@ pub async fn schaltwerk_core_cancel_session(
                 &name_for_bg,
             ));
 
-            for id in ids {
+            for id in &ids {
                 if let Err(e) = terminal_manager.close_terminal(id.clone()).await {
                     log::debug!("Terminal {id} cleanup (pre-cancel): {e}");
                 }
             }
-        }
 
-        let cancel_result = match get_core_write().await {
-            Ok(core) => {
-                let manager = core.session_manager();
-                // Use fast async cancellation
-                manager.fast_cancel_session(&name_for_bg).await
+            let ids: Vec<String> = ids.into_iter().collect();
+            terminal_manager
+                .kill_persistent_terminals(&ids, &worktree_path)
+                .await;
+
+            if let Err(e) = terminal_manager.remove_session_container(&name_for_bg).await {
+                log::warn!("Cancel {name_for_bg}: Failed to remove session container: {e}");
             }
-            Err(e) => Err(anyhow::anyhow!(e)),
+        }
+
+        let cancel_result = {
+            let core = core.write().await;
+            let manager = core.session_manager();
+            // Use fast async cancellation
+            manager.fast_cancel_session(&name_for_bg).await
         };
 
         match cancel_result {
@@ -1056,6 +1284,27 @@ pub async fn schaltwerk_core_cleanup_orphaned_worktrees() -> Result<(), String>
         .map_err(|e| format!("Failed to cleanup orphaned worktrees: {e}"))
 }
 
+#[tauri::command]
+pub async fn get_worktree_disk_usage() -> Result<Vec<WorktreeDiskUsage>, String> {
+    let manager = session_manager_read().await?;
+    tokio::task::spawn_blocking(move || manager.worktree_disk_usage())
+        .await
+        .map_err(|e| format!("Worktree disk usage task failed: {e}"))?
+        .map_err(|e| format!("Failed to measure worktree disk usage: {e}"))
+}
+
+/// Remove orphaned worktrees and build artifacts of cancelled sessions; with `dry_run`
+/// only report what would be removed.
+#[tauri::command]
+pub async fn prune_worktrees(dry_run: bool) -> Result<WorktreePruneReport, String> {
+    let core = get_core_write().await?;
+    let manager = core.session_manager();
+    tokio::task::spawn_blocking(move || manager.prune_worktrees(dry_run))
+        .await
+        .map_err(|e| format!("Worktree prune task failed: {e}"))?
+        .map_err(|e| format!("Failed to prune worktrees: {e}"))
+}
+
 #[tauri::command]
 pub async fn schaltwerk_core_start_claude(
     app: tauri::AppHandle,
@@ -1063,7 +1312,12 @@ pub async fn schaltwerk_core_start_claude(
     cols: Option<u16>,
     rows: Option<u16>,
 ) -> Result<String, String> {
-    schaltwerk_core_start_claude_with_restart(app, session_name, false, cols, rows).await
+    let context = LogContext::continuing_session(&session_name);
+    with_log_context(
+        context,
+        schaltwerk_core_start_claude_with_restart(app, session_name, false, cols, rows),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -1073,7 +1327,12 @@ pub async fn schaltwerk_core_start_session_agent(
     cols: Option<u16>,
     rows: Option<u16>,
 ) -> Result<String, String> {
-    schaltwerk_core_start_session_agent_with_restart(app, session_name, false, cols, rows).await
+    let context = LogContext::continuing_session(&session_name);
+    with_log_context(
+        context,
+        schaltwerk_core_start_session_agent_with_restart(app, session_name, false, cols, rows),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -1083,6 +1342,23 @@ pub async fn schaltwerk_core_start_claude_with_restart(
     force_restart: bool,
     cols: Option<u16>,
     rows: Option<u16>,
+) -> Result<String, String> {
+    start_session_agent(app, session_name, force_restart, cols, rows, None).await
+}
+
+/// Agent a session is handed to, started with `prompt` before the switch is stored
+struct AgentHandoff<'a> {
+    agent_type: &'a str,
+    prompt: &'a str,
+}
+
+async fn start_session_agent(
+    app: tauri::AppHandle,
+    session_name: String,
+    force_restart: bool,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    handoff: Option<AgentHandoff<'_>>,
 ) -> Result<String, String> {
     log::info!("Starting Claude for session: {session_name}");
 
@@ -1092,11 +1368,16 @@ pub async fn schaltwerk_core_start_claude_with_restart(
     let session = manager
         .get_session(&session_name)
         .map_err(|e| format!("Failed to get session: {e}"))?;
-    let agent_type = session.original_agent_type.clone().unwrap_or(
-        core.db
-            .get_agent_type()
-            .map_err(|e| format!("Failed to get agent type: {e}"))?,
-    );
+    let agent_type = match (&handoff, session.original_agent_type.clone()) {
+        (Some(handoff), _) => handoff.agent_type.to_string(),
+        (None, Some(agent_type)) => agent_type,
+        (None, None) => {
+            manager
+                .default_agent_settings()
+                .map_err(|e| format!("Failed to get agent type: {e}"))?
+                .0
+        }
+    };
 
     if agent_type == "terminal" {
         log::info!("Skipping agent startup for terminal-only session: {session_name}");
@@ -1109,11 +1390,11 @@ pub async fn schaltwerk_core_start_claude_with_restart(
         let mut paths = std::collections::HashMap::new();
 
         // Get resolved binary paths for all agents
-        for agent in ["claude", "codex", "opencode", "gemini", "droid", "qwen", "amp"] {
-            match settings.get_effective_binary_path(agent) {
+        for agent in AgentManifest::launchable_agents() {
+            match settings.get_effective_binary_path(&agent) {
                 Ok(path) => {
                     log::debug!("Cached binary path for {agent}: {path}");
-                    paths.insert(agent.to_string(), path);
+                    paths.insert(agent, path);
                 }
                 Err(e) => log::warn!("Failed to get cached binary path for {agent}: {e}"),
             }
@@ -1135,17 +1416,32 @@ pub async fn schaltwerk_core_start_claude_with_restart(
         None
     };
 
-    let spec = manager
-        .start_claude_in_session_with_restart_and_binary(
+    let spec = match &handoff {
+        Some(handoff) => manager.start_agent_handoff_with_binary(
+            &session_name,
+            handoff.agent_type,
+            handoff.prompt,
+            &binary_paths,
+            amp_mcp_servers.as_ref(),
+        ),
+        None => manager.start_claude_in_session_with_restart_and_binary(
             &session_name,
             force_restart,
             &binary_paths,
             amp_mcp_servers.as_ref(),
-        )
-        .map_err(|e| {
-            log::error!("Failed to build {agent_type} command for session {session_name}: {e}");
-            format!("Failed to start {agent_type} in session: {e}")
-        })?;
+        ),
+    }
+    .map_err(|e| {
+        log::error!("Failed to build {agent_type} command for session {session_name}: {e}");
+        format!("Failed to start {agent_type} in session: {e}")
+    })?;
+
+    // Record the Amp thread this start creates, e.g. after a restart dropped the old one
+    if agent_type == "amp" {
+        if let Err(e) = manager.spawn_amp_thread_watcher(&session_name) {
+            log::warn!("Failed to spawn amp thread watcher for session '{session_name}': {e}");
+        }
+    }
 
     let command = spec.shell_command.clone();
     let initial_command = spec.initial_command.clone();
@@ -1163,6 +1459,27 @@ pub async fn schaltwerk_core_start_claude_with_restart(
     terminals::ensure_cwd_access(&cwd)?;
     log::info!("Working directory access confirmed: {cwd}");
 
+    // A deferred start would launch the stored agent, not the one the session is handed to
+    if handoff.is_some() && session_setup::is_session_setup_running(&session_name).await {
+        return Err(format!(
+            "Setup of session '{session_name}' is still running; switch agents once it finished"
+        ));
+    }
+
+    // The project setup step runs in its own terminal; the agent starts once it passed
+    if session_setup::is_session_setup_running(&session_name).await {
+        log::info!("Setup still running for {session_name}; agent starts when it finishes");
+        return Ok("Setup is running - agent starts when it finishes".to_string());
+    }
+    if let Some(request) =
+        session_setup::pending_setup_request(&manager, &core.repo_path, &session_name)?
+    {
+        log::info!("Running setup for {session_name} before starting {agent_name}");
+        let force_setup = session_setup::take_forced_setup(&session_name);
+        session_setup::launch_session_setup(app, &manager, request, true, force_setup).await?;
+        return Ok("Setup started - agent starts when it finishes".to_string());
+    }
+
     // Sanitize session name to match frontend's terminal ID generation
     let terminal_id = terminals::terminal_id_for_session_top(&session_name);
     let terminal_manager = get_terminal_manager().await?;
@@ -1179,97 +1496,59 @@ pub async fn schaltwerk_core_start_claude_with_restart(
         }
     }
 
-    let (env_vars, cli_args) =
-        agent_ctx::collect_agent_env_and_cli(&agent_kind, &core.repo_path, &core.db).await;
+    let (env_vars, cli_args) = agent_ctx::collect_agent_env_and_cli(
+        &agent_kind,
+        &core.repo_path,
+        Path::new(&cwd),
+        &core.db,
+    )
+    .await;
+    let env_vars = agent_launcher::merge_env_vars(env_vars, &spec.env_vars);
+    agent_ctx::warn_missing_env_requirements(&agent_kind, &env_vars);
     log::info!("Creating terminal with {agent_name} directly: {terminal_id} with {} env vars and CLI args: '{cli_args}'", env_vars.len());
 
-    // If a project setup script exists, run it ONCE inside this terminal before exec'ing the agent.
-    // This streams all setup output to the agent terminal and avoids blocking session creation.
-    // We gate with a marker file in the worktree: .schaltwerk/setup.done
     let mut use_shell_chain = false;
     let mut shell_cmd: Option<String> = None;
-    let marker_rel = ".schaltwerk/setup.done";
-    
+
     // For Amp commands with pipes (containing " | amp"), use shell chain to preserve the pipe
     let has_pipe = command.contains(" | amp") || (command.contains(" | ") && agent_name.ends_with("/amp"));
     if has_pipe {
         log::info!("Detected Amp command with pipe, using shell chain to preserve it: {command}");
         // Extract the actual command part (after " && ")
         if let Some(cmd_part) = command.split(" && ").nth(1) {
-            shell_cmd = Some(cmd_part.to_string());
+            shell_cmd = Some(agent_ctx::append_cli_args_to_shell_command(
+                cmd_part, &cli_args,
+            ));
             use_shell_chain = true;
         }
     }
-    if let Ok(Some(setup)) = core.db.get_project_setup_script(&core.repo_path) {
-        if !setup.trim().is_empty() {
-            // Persist setup script to a temp file for reliable execution
-            let temp_dir = std::env::temp_dir();
-            let ts = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_nanos())
-                .unwrap_or(0);
-            let script_path = temp_dir.join(format!("schalt_setup_{session_name}_{ts}.sh"));
-            if let Err(e) = std::fs::write(&script_path, setup) {
-                log::warn!("Failed to write setup script to temp file: {e}");
-            } else {
-                let marker_q = sh_quote_string(marker_rel);
-                let script_q = sh_quote_string(&script_path.display().to_string());
-                let script_command = format!("sh {script_q}");
-
-                let (user_shell, default_args) = get_effective_shell();
-                let login_invocation = build_login_shell_invocation_with_shell(
-                    &user_shell,
-                    &default_args,
-                    &script_command,
-                );
-                let run_setup_command = shell_invocation_to_posix(&login_invocation);
-
-                // If we already have a shell_cmd (e.g., from Amp with pipe), wrap it with setup
-                let is_piped_cmd = use_shell_chain && shell_cmd.is_some();
-                let exec_cmd = if is_piped_cmd {
-                    // Amp with pipe: wrap the piped command with setup (no exec prefix needed)
-                    shell_cmd.take().unwrap()
-                } else {
-                    // Regular agent: build exec command from agent_name and args
-                    let mut exec_cmd = String::new();
-                    exec_cmd.push_str(&sh_quote_string(&agent_name));
-                    for a in &agent_args {
-                        exec_cmd.push(' ');
-                        exec_cmd.push_str(&sh_quote_string(a));
-                    }
-                    exec_cmd
-                };
-
-                // For piped commands, exec is already in the command (or not needed)
-                // For regular agents, use exec to replace the shell
-                let exec_prefix = if is_piped_cmd {
-                    ""
-                } else {
-                    "exec "
-                };
-                let chained = format!(
-                        "set -e; if [ ! -f {marker_q} ]; then {run_setup_command}; rm -f {script_q}; mkdir -p .schaltwerk; : > {marker_q}; fi; {exec_prefix}{exec_cmd}"
-                    );
-                shell_cmd = Some(chained);
-                use_shell_chain = true;
-            }
-        }
-    }
-
     // Build final args using centralized logic (handles Codex ordering/normalization)
-    let final_args = agent_ctx::build_final_args(&agent_kind, agent_args.clone(), &cli_args);
+    let final_args = agent_ctx::build_final_args(&agent_kind, agent_args, &cli_args);
+
+    // Enter the project's dev environment when one is configured
+    if let Some(kind) = agent_launcher::project_dev_environment(&core.db, &core.repo_path) {
+        shell_cmd = Some(match shell_cmd.take() {
+            // Amp with pipe: run the piped command through sh inside the environment
+            Some(piped) => agent_launcher::environment_shell_command(
+                kind,
+                &cwd,
+                "sh".to_string(),
+                vec!["-c".to_string(), piped],
+            ),
+            None => agent_launcher::environment_shell_command(
+                kind,
+                &cwd,
+                agent_name.clone(),
+                final_args.clone(),
+            ),
+        });
+        use_shell_chain = true;
+    }
 
     // Codex prompt ordering is now handled in the CLI args section above
 
     // Log the exact command that will be executed
-    let kind_str = match agent_kind {
-        agent_ctx::AgentKind::Claude => "claude",
-        agent_ctx::AgentKind::Codex => "codex",
-        agent_ctx::AgentKind::OpenCode => "opencode",
-        agent_ctx::AgentKind::Gemini => "gemini",
-        agent_ctx::AgentKind::Droid => "droid",
-        agent_ctx::AgentKind::Fallback => "claude",
-    };
+    let kind_str = agent_kind.manifest_key();
     log::info!(
         "FINAL COMMAND CONSTRUCTION for {kind_str}: command='{agent_name}', args={final_args:?}"
     );
@@ -1394,11 +1673,11 @@ pub async fn schaltwerk_core_start_claude_orchestrator(
         let mut paths = std::collections::HashMap::new();
 
         // Get resolved binary paths for all agents
-        for agent in ["claude", "codex", "opencode", "gemini", "droid", "qwen", "amp"] {
-            match settings.get_effective_binary_path(agent) {
+        for agent in AgentManifest::launchable_agents() {
+            match settings.get_effective_binary_path(&agent) {
                 Ok(path) => {
                     log::debug!("Cached binary path for {agent}: {path}");
-                    paths.insert(agent.to_string(), path);
+                    paths.insert(agent, path);
                 }
                 Err(e) => log::warn!("Failed to get cached binary path for {agent}: {e}"),
             }
@@ -1519,10 +1798,10 @@ pub async fn schaltwerk_core_set_session_agent_type(
         .get_session_by_name(&core.repo_path, &session_name)
         .map_err(|e| format!("Failed to find session {session_name}: {e}"))?;
 
-    // Get current skip permissions setting
-    let skip_permissions = core
-        .db
-        .get_skip_permissions()
+    // Get the project's skip permissions default
+    let (_, skip_permissions) = core
+        .session_manager()
+        .default_agent_settings()
         .map_err(|e| format!("Failed to get skip permissions: {e}"))?;
 
     // Update session's original settings to use the new agent type
@@ -1540,60 +1819,573 @@ pub async fn schaltwerk_core_set_session_agent_type(
     Ok(())
 }
 
+/// Hand a running session to a different agent type, e.g. a stuck Gemini session to
+/// Claude: stops the session's agent and starts `agent_type` in the same worktree with a
+/// prompt carrying the spec and the changes made so far.
 #[tauri::command]
-pub async fn schaltwerk_core_get_agent_type() -> Result<String, String> {
-    let core = get_core_read().await?;
-    core.db
-        .get_agent_type()
-        .map_err(|e| format!("Failed to get agent type: {e}"))
+pub async fn switch_session_agent(
+    app: tauri::AppHandle,
+    session_name: String,
+    agent_type: String,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, String> {
+    if !AgentManifest::launchable_agents().contains(&agent_type) {
+        return Err(format!(
+            "Cannot switch to unknown agent type '{agent_type}'"
+        ));
+    }
+
+    let prompt = get_core_read()
+        .await?
+        .session_manager()
+        .prepare_agent_handoff(&session_name, &agent_type)
+        .map_err(|e| format!("Failed to hand session {session_name} to {agent_type}: {e}"))?;
+
+    // Starting the new agent replaces the old one's terminal; the switch is stored only
+    // once that worked, so a failed start leaves the session on its previous agent
+    let handoff = AgentHandoff {
+        agent_type: &agent_type,
+        prompt: &prompt,
+    };
+    let result = start_session_agent(
+        app.clone(),
+        session_name.clone(),
+        true,
+        cols,
+        rows,
+        Some(handoff),
+    )
+    .await?;
+
+    get_core_write()
+        .await?
+        .session_manager()
+        .commit_agent_handoff(&session_name, &agent_type, &prompt)
+        .map_err(|e| format!("Failed to hand session {session_name} to {agent_type}: {e}"))?;
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(result)
 }
 
 #[tauri::command]
-pub async fn schaltwerk_core_set_orchestrator_agent_type(agent_type: String) -> Result<(), String> {
+pub async fn schaltwerk_core_set_session_codex_profile(
+    session_name: String,
+    profile: Option<String>,
+) -> Result<(), String> {
     let core = get_core_write().await?;
-    core.db
-        .set_orchestrator_agent_type(&agent_type)
-        .map_err(|e| format!("Failed to set orchestrator agent type: {e}"))
-}
 
-#[tauri::command]
-pub async fn schaltwerk_core_get_orchestrator_agent_type() -> Result<String, String> {
-    let core = get_core_read().await?;
+    let session = core
+        .db
+        .get_session_by_name(&core.repo_path, &session_name)
+        .map_err(|e| format!("Failed to find session {session_name}: {e}"))?;
+
     core.db
-        .get_orchestrator_agent_type()
-        .map_err(|e| format!("Failed to get orchestrator agent type: {e}"))
+        .set_session_codex_profile(&session.id, profile.as_deref())
+        .map_err(|e| format!("Failed to set Codex profile for session {session_name}: {e}"))
 }
 
 #[tauri::command]
-pub async fn schaltwerk_core_get_font_sizes() -> Result<(i32, i32), String> {
-    let core = get_core_read().await?;
-    core.db
-        .get_font_sizes()
-        .map_err(|e| format!("Failed to get font sizes: {e}"))
+pub async fn schaltwerk_core_get_session_environment(
+    session_name: String,
+) -> Result<std::collections::HashMap<String, String>, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .get_session_environment(&session_name)
+        .map_err(|e| format!("Failed to get environment for session {session_name}: {e}"))
 }
 
+/// Replace the session's extra environment variables; applies to terminals started afterwards.
 #[tauri::command]
-pub async fn schaltwerk_core_set_font_sizes(
-    terminal_font_size: i32,
-    ui_font_size: i32,
+pub async fn schaltwerk_core_set_session_environment(
+    session_name: String,
+    env_vars: std::collections::HashMap<String, String>,
 ) -> Result<(), String> {
     let core = get_core_write().await?;
-    core.db
-        .set_font_sizes(terminal_font_size, ui_font_size)
-        .map_err(|e| format!("Failed to set font sizes: {e}"))
+    core.session_manager()
+        .set_session_environment(&session_name, env_vars)
+        .map_err(|e| format!("Failed to set environment for session {session_name}: {e}"))
 }
 
+/// Replace the sessions `session_name` waits on; it cannot start until they merged.
 #[tauri::command]
-pub async fn schaltwerk_core_mark_session_ready(
+pub async fn schaltwerk_core_set_session_dependencies(
     app: tauri::AppHandle,
-    name: String,
-    auto_commit: bool,
-    commit_message: Option<String>,
-) -> Result<bool, String> {
-    log::info!("Marking session {name} as reviewed (auto_commit: {auto_commit})");
+    session_name: String,
+    depends_on: Vec<String>,
+) -> Result<(), String> {
+    {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .set_session_dependencies(&session_name, depends_on)
+            .map_err(|e| format!("Failed to set dependencies for session {session_name}: {e}"))?;
+    }
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(())
+}
 
-    let effective_auto_commit = if auto_commit {
-        true
+#[tauri::command]
+pub async fn get_session_time_report() -> Result<SessionTimeReport, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .get_session_time_report()
+        .map_err(|e| format!("Failed to build session time report: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_session_cost_report() -> Result<SessionCostReport, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .get_session_cost_report()
+        .map_err(|e| format!("Failed to build session cost report: {e}"))
+}
+
+/// Follow-up messages that did not reach their agent terminal yet, oldest first.
+#[tauri::command]
+pub async fn list_pending_follow_ups() -> Result<Vec<PendingFollowUp>, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .list_pending_follow_ups()
+        .map_err(|e| format!("Failed to list pending follow-up messages: {e}"))
+}
+
+#[tauri::command]
+pub async fn list_prompt_templates() -> Result<Vec<PromptTemplate>, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .list_prompt_templates()
+        .map_err(|e| format!("Failed to list prompt templates: {e}"))
+}
+
+/// Create a prompt template, or update the one with `id`.
+#[tauri::command]
+pub async fn save_prompt_template(
+    id: Option<String>,
+    name: String,
+    content: String,
+    project_only: bool,
+) -> Result<PromptTemplate, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .save_prompt_template(id.as_deref(), &name, &content, project_only)
+        .map_err(|e| format!("Failed to save prompt template: {e}"))
+}
+
+#[tauri::command]
+pub async fn delete_prompt_template(id: String) -> Result<(), String> {
+    let manager = session_manager_read().await?;
+    manager
+        .delete_prompt_template(&id)
+        .map_err(|e| format!("Failed to delete prompt template: {e}"))
+}
+
+/// Preview a template filled in for a session.
+#[tauri::command]
+pub async fn render_prompt_template(
+    template_id: String,
+    session_name: String,
+) -> Result<String, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .render_prompt_template(&template_id, &session_name)
+        .map_err(|e| format!("Failed to render prompt template: {e}"))
+}
+
+/// Render a template for a session and use it as the session's initial prompt, or send
+/// it to the session's agent as a follow-up message. Returns the rendered text.
+#[tauri::command]
+pub async fn apply_prompt_template(
+    app: tauri::AppHandle,
+    template_id: String,
+    session_name: String,
+    target: PromptTemplateTarget,
+) -> Result<String, String> {
+    let (prompt, db, repo_path) = {
+        let core = get_core_write().await?;
+        let manager = core.session_manager();
+        let prompt = manager
+            .render_prompt_template(&template_id, &session_name)
+            .map_err(|e| format!("Failed to render prompt template: {e}"))?;
+        match target {
+            PromptTemplateTarget::InitialPrompt => manager
+                .set_session_prompt(&session_name, &prompt)
+                .map_err(|e| format!("Failed to update prompt of session {session_name}: {e}"))?,
+            PromptTemplateTarget::FollowUp => {
+                let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+                manager
+                    .queue_follow_up(&session_name, &prompt, timestamp)
+                    .map_err(|e| {
+                        format!("Failed to queue follow-up message for {session_name}: {e}")
+                    })?;
+            }
+        }
+        (prompt, core.db.clone(), core.repo_path.clone())
+    };
+
+    match target {
+        PromptTemplateTarget::InitialPrompt => {
+            events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SpecSync);
+        }
+        PromptTemplateTarget::FollowUp => {
+            // Stays queued for background delivery when the agent terminal is not up
+            let terminals = get_terminal_manager().await?;
+            let delivered = follow_ups::deliver_pending_follow_ups(
+                &db,
+                &repo_path,
+                &terminals,
+                Some(&session_name),
+            )
+            .await
+            .map_err(|e| format!("Failed to deliver follow-up message: {e}"))?;
+            follow_ups::emit_delivered_follow_ups(&app, &delivered);
+        }
+    }
+    Ok(prompt)
+}
+
+/// Opt a session in or out of the automatic nudge sent when its agent stalls.
+#[tauri::command]
+pub async fn schaltwerk_core_set_session_auto_nudge(
+    app: tauri::AppHandle,
+    session_name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .set_session_auto_nudge(&session_name, enabled)
+            .map_err(|e| format!("Failed to update auto-nudge for session {session_name}: {e}"))?;
+    }
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_list_session_labels() -> Result<Vec<String>, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .list_session_labels()
+        .map_err(|e| format!("Failed to list session labels: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_get_session_notes(
+    session_name: String,
+) -> Result<SessionNotes, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .get_session_notes(&session_name)
+        .map_err(|e| format!("Failed to get notes for session {session_name}: {e}"))
+}
+
+/// Replace the review notes and checklist a reviewer keeps for the session.
+#[tauri::command]
+pub async fn schaltwerk_core_set_session_notes(
+    session_name: String,
+    notes: String,
+    checklist: Vec<ReviewChecklistItem>,
+) -> Result<SessionNotes, String> {
+    let core = get_core_write().await?;
+    core.session_manager()
+        .set_session_notes(&session_name, notes, checklist)
+        .map_err(|e| format!("Failed to set notes for session {session_name}: {e}"))
+}
+
+/// Comment on a line range of the session's diff.
+#[tauri::command]
+pub async fn add_review_comment(
+    session_name: String,
+    comment: NewReviewComment,
+) -> Result<ReviewComment, String> {
+    let core = get_core_write().await?;
+    core.session_manager()
+        .add_review_comment(&session_name, comment)
+        .map_err(|e| format!("Failed to add review comment to session {session_name}: {e}"))
+}
+
+#[tauri::command]
+pub async fn list_review_comments(
+    session_name: String,
+    include_resolved: Option<bool>,
+) -> Result<Vec<ReviewComment>, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .list_review_comments(&session_name, include_resolved.unwrap_or(false))
+        .map_err(|e| format!("Failed to list review comments of session {session_name}: {e}"))
+}
+
+#[tauri::command]
+pub async fn resolve_review_comment(
+    session_name: String,
+    comment_id: String,
+) -> Result<(), String> {
+    let core = get_core_write().await?;
+    core.session_manager()
+        .resolve_review_comment(&session_name, &comment_id)
+        .map_err(|e| format!("Failed to resolve review comment: {e}"))
+}
+
+/// Send the unresolved review comments of a session to its agent as one prompt. Once the
+/// prompt is submitted the comments are resolved and a reviewed session goes back to
+/// running; when it cannot be delivered nothing changes.
+#[tauri::command]
+pub async fn request_review_changes(
+    app: tauri::AppHandle,
+    services: State<'_, ServiceHandles>,
+    session_name: String,
+) -> Result<usize, String> {
+    let core = get_core_write().await?;
+    let manager = core.session_manager();
+    let (prompt, comment_ids) = manager
+        .review_changes_prompt(&session_name)
+        .map_err(|e| format!("Failed to request changes for session {session_name}: {e}"))?;
+    let session = manager
+        .get_session(&session_name)
+        .map_err(|e| format!("Failed to find session {session_name}: {e}"))?;
+
+    // Claude Code treats a bracketed paste as an attachment
+    let bracketed = session.original_agent_type.as_deref() != Some("claude");
+    services
+        .terminals
+        .paste_and_submit_terminal(
+            terminals::terminal_id_for_session_top(&session_name),
+            prompt.into_bytes(),
+            bracketed,
+        )
+        .await?;
+
+    let reopened = manager
+        .complete_review_changes_request(&session_name, &comment_ids)
+        .map_err(|e| format!("Failed to reopen session {session_name}: {e}"))?;
+    if reopened {
+        log::info!("Sent review comments to '{session_name}' and moved it back to running");
+    }
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::MergeWorkflow);
+    Ok(comment_ids.len())
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_set_session_labels(
+    app: tauri::AppHandle,
+    session_name: String,
+    labels: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let labels = {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .set_session_labels(&session_name, labels)
+            .map_err(|e| format!("Failed to set labels for session {session_name}: {e}"))?
+    };
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(labels)
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_add_session_label(
+    app: tauri::AppHandle,
+    session_name: String,
+    label: String,
+) -> Result<Vec<String>, String> {
+    let labels = {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .add_session_label(&session_name, &label)
+            .map_err(|e| format!("Failed to add label to session {session_name}: {e}"))?
+    };
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(labels)
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_remove_session_label(
+    app: tauri::AppHandle,
+    session_name: String,
+    label: String,
+) -> Result<Vec<String>, String> {
+    let labels = {
+        let core = get_core_write().await?;
+        core.session_manager()
+            .remove_session_label(&session_name, &label)
+            .map_err(|e| format!("Failed to remove label from session {session_name}: {e}"))?
+    };
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(labels)
+}
+
+#[tauri::command]
+pub async fn get_agent_progress(
+    session_name: String,
+) -> Result<Option<schaltwerk::domains::agents::progress::AgentProgress>, String> {
+    let core = get_core_read().await?;
+
+    let session = core
+        .db
+        .get_session_by_name(&core.repo_path, &session_name)
+        .map_err(|e| format!("Failed to find session {session_name}: {e}"))?;
+    drop(core);
+
+    // Only Claude exposes a structured TODO list; other agents report no progress
+    if session.original_agent_type.as_deref().unwrap_or("claude") != "claude" {
+        return Ok(None);
+    }
+
+    let worktree_path = session.worktree_path.clone();
+    tokio::task::spawn_blocking(move || {
+        schaltwerk::domains::agents::progress::read_claude_progress(&worktree_path)
+    })
+    .await
+    .map_err(|e| format!("Failed to read agent progress: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_get_agent_type() -> Result<String, String> {
+    let core = get_core_read().await?;
+    core.db
+        .get_agent_type()
+        .map_err(|e| format!("Failed to get agent type: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_set_orchestrator_agent_type(agent_type: String) -> Result<(), String> {
+    let core = get_core_write().await?;
+    core.db
+        .set_orchestrator_agent_type(&agent_type)
+        .map_err(|e| format!("Failed to set orchestrator agent type: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_get_orchestrator_agent_type() -> Result<String, String> {
+    let core = get_core_read().await?;
+    core.db
+        .get_orchestrator_agent_type()
+        .map_err(|e| format!("Failed to get orchestrator agent type: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_get_font_sizes() -> Result<(i32, i32), String> {
+    let core = get_core_read().await?;
+    core.db
+        .get_font_sizes()
+        .map_err(|e| format!("Failed to get font sizes: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_set_font_sizes(
+    app: tauri::AppHandle,
+    terminal_font_size: i32,
+    ui_font_size: i32,
+) -> Result<(), String> {
+    {
+        let core = get_core_write().await?;
+        core.db
+            .set_font_sizes(terminal_font_size, ui_font_size)
+            .map_err(|e| format!("Failed to set font sizes: {e}"))?;
+    }
+    let change = SettingsChange::FontSizes {
+        terminal_font_size,
+        ui_font_size,
+    };
+    if let Err(e) = emit_event(&app, SchaltEvent::SettingsChanged, &change) {
+        log::warn!("Failed to emit font size change: {e}");
+    }
+    Ok(())
+}
+
+/// Ask the session's agent for a conventional-commit message describing the changes
+/// auto-commit on review would commit.
+#[tauri::command]
+pub async fn generate_commit_message(session_name: String) -> Result<String, String> {
+    let (db, repo_path) = {
+        let core = get_core_read().await?;
+        (core.db.clone(), core.repo_path.clone())
+    };
+    let session = SessionManager::new(db.clone(), repo_path.clone())
+        .get_session(&session_name)
+        .map_err(|e| format!("Failed to find session {session_name}: {e}"))?;
+
+    let diff_summary = schaltwerk::domains::git::operations::uncommitted_diff_summary(
+        &session.worktree_path,
+        commit_message::COMMIT_MESSAGE_DIFF_LIMIT,
+    )
+    .map_err(|e| format!("Failed to read changes for session {session_name}: {e}"))?;
+    if diff_summary.is_empty() {
+        return Err(format!(
+            "Session '{session_name}' has no uncommitted changes to describe"
+        ));
+    }
+
+    let agent_type = session
+        .original_agent_type
+        .clone()
+        .unwrap_or_else(|| default_agent_type(&db, &repo_path));
+    let (mut env_vars, cli_args, binary_path) = get_agent_env_and_cli_args(&agent_type);
+    if let Ok(project_env_vars) = db.get_project_environment_variables(&repo_path) {
+        env_vars.extend(project_env_vars);
+    }
+
+    commit_message::generate_commit_message(commit_message::CommitMessageContext {
+        session_id: &session.id,
+        agent_type: &agent_type,
+        diff_summary: &diff_summary,
+        cli_args: Some(cli_args).filter(|args| !args.is_empty()),
+        env_vars,
+        binary_path,
+    })
+    .await
+    .map_err(|e| format!("Failed to generate commit message: {e}"))?
+    .ok_or_else(|| format!("The {agent_type} agent did not return a commit message"))
+}
+
+/// Ask the session's agent for a short summary of the session's spec or initial prompt.
+#[tauri::command]
+pub async fn summarize_spec(session_name: String) -> Result<String, String> {
+    let (db, repo_path) = {
+        let core = get_core_read().await?;
+        (core.db.clone(), core.repo_path.clone())
+    };
+    let session = SessionManager::new(db.clone(), repo_path.clone())
+        .get_session(&session_name)
+        .map_err(|e| format!("Failed to find session {session_name}: {e}"))?;
+
+    let spec = session
+        .spec_content
+        .clone()
+        .or_else(|| session.initial_prompt.clone())
+        .filter(|spec| !spec.trim().is_empty())
+        .ok_or_else(|| format!("Session '{session_name}' has no spec to summarize"))?;
+
+    let agent_type = session
+        .original_agent_type
+        .clone()
+        .unwrap_or_else(|| default_agent_type(&db, &repo_path));
+    let (mut env_vars, cli_args, binary_path) = get_agent_env_and_cli_args(&agent_type);
+    if let Ok(project_env_vars) = db.get_project_environment_variables(&repo_path) {
+        env_vars.extend(project_env_vars);
+    }
+
+    spec_summary::summarize_spec(spec_summary::SpecSummaryContext {
+        session_id: &session.id,
+        agent_type: &agent_type,
+        spec: &spec,
+        cli_args: Some(cli_args).filter(|args| !args.is_empty()),
+        env_vars,
+        binary_path,
+    })
+    .await
+    .map_err(|e| format!("Failed to summarize spec: {e}"))?
+    .ok_or_else(|| format!("The {agent_type} agent did not return a summary"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_mark_session_ready(
+    app: tauri::AppHandle,
+    name: String,
+    auto_commit: bool,
+    commit_message: Option<String>,
+) -> Result<bool, String> {
+    log::info!("Marking session {name} as reviewed (auto_commit: {auto_commit})");
+
+    let effective_auto_commit = if auto_commit {
+        true
     } else {
         let settings_manager = crate::SETTINGS_MANAGER
             .get()
@@ -1607,9 +2399,24 @@ pub async fn schaltwerk_core_mark_session_ready(
     let core = get_core_write().await?;
     let manager = core.session_manager();
 
-    let result = manager
-        .mark_session_ready_with_message(&name, effective_auto_commit, commit_message.as_deref())
-        .map_err(|e| format!("Failed to mark session as reviewed: {e}"))?;
+    let result = match manager.mark_session_ready_with_message(
+        &name,
+        effective_auto_commit,
+        commit_message.as_deref(),
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            surface_hook_failure(&name, &e).await;
+            return Err(format!("Failed to mark session as reviewed: {e}"));
+        }
+    };
+
+    notify_outbound(
+        &core.repo_path,
+        OutboundEvent::SessionReviewed,
+        &name,
+        serde_json::json!({ "auto_commit": effective_auto_commit }),
+    );
 
     if let Ok(session) = manager.get_session(&name) {
         if session.worktree_path.exists() {
@@ -1649,6 +2456,7 @@ pub async fn schaltwerk_core_mark_session_ready(
                     merge_conflicting_paths: merge_snapshot.merge_conflicting_paths,
                     merge_is_up_to_date: merge_snapshot.merge_is_up_to_date,
                     worktree_size_bytes,
+                    base_freshness: None,
                 };
 
                 if let Err(err) = emit_event(&app, SchaltEvent::SessionGitStats, &payload) {
@@ -1682,6 +2490,85 @@ pub async fn schaltwerk_core_has_uncommitted_changes(name: String) -> Result<boo
         .map_err(|e| format!("Failed to check uncommitted changes: {e}"))
 }
 
+/// Fetch the session's base branch and rebase the session onto it. Conflicts abort
+/// the rebase and come back as a `conflicts` outcome listing the paths.
+#[tauri::command]
+pub async fn schaltwerk_core_rebase_session_on_base(
+    app: tauri::AppHandle,
+    session_name: String,
+) -> Result<SessionRebaseOutcome, String> {
+    log::info!("Rebasing session '{session_name}' onto its base branch");
+    let manager = session_manager_read().await?;
+    let outcome = manager
+        .rebase_session_on_base(&session_name)
+        .map_err(|e| format!("Failed to rebase session {session_name}: {e}"))?;
+    if matches!(outcome, SessionRebaseOutcome::Rebased { .. }) {
+        events::request_sessions_refreshed(&app, events::SessionsRefreshReason::GitUpdate);
+    }
+    Ok(outcome)
+}
+
+async fn session_worktree_for_stash(session_name: &str) -> Result<PathBuf, String> {
+    let manager = session_manager_read().await?;
+    let session = manager
+        .get_session(session_name)
+        .map_err(|e| format!("Failed to get session: {e}"))?;
+    if session.session_state == SessionState::Spec {
+        return Err(format!(
+            "Session '{session_name}' is a spec and has no worktree"
+        ));
+    }
+    Ok(session.worktree_path)
+}
+
+/// Park the session's uncommitted changes, e.g. before resetting its worktree.
+/// Returns `None` when the worktree was already clean.
+#[tauri::command]
+pub async fn schaltwerk_core_stash_session_changes(
+    app: tauri::AppHandle,
+    session_name: String,
+    message: Option<String>,
+) -> Result<Option<SessionStash>, String> {
+    let worktree_path = session_worktree_for_stash(&session_name).await?;
+    let stash = schaltwerk::domains::git::stash_session_changes(
+        &worktree_path,
+        &session_name,
+        message.as_deref(),
+    )
+    .map_err(|e| format!("Failed to stash changes for session {session_name}: {e}"))?;
+    if stash.is_some() {
+        events::request_sessions_refreshed(&app, events::SessionsRefreshReason::GitUpdate);
+    }
+    Ok(stash)
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_list_session_stashes(
+    session_name: String,
+) -> Result<Vec<SessionStash>, String> {
+    let worktree_path = session_worktree_for_stash(&session_name).await?;
+    schaltwerk::domains::git::list_session_stashes(&worktree_path, &session_name)
+        .map_err(|e| format!("Failed to list stashes for session {session_name}: {e}"))
+}
+
+/// Restore a stash into the session worktree; the newest one unless `stash_id` is given.
+#[tauri::command]
+pub async fn schaltwerk_core_pop_session_stash(
+    app: tauri::AppHandle,
+    session_name: String,
+    stash_id: Option<String>,
+) -> Result<SessionStash, String> {
+    let worktree_path = session_worktree_for_stash(&session_name).await?;
+    let stash = schaltwerk::domains::git::pop_session_stash(
+        &worktree_path,
+        &session_name,
+        stash_id.as_deref(),
+    )
+    .map_err(|e| format!("Failed to pop stash for session {session_name}: {e}"))?;
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::GitUpdate);
+    Ok(stash)
+}
+
 #[tauri::command]
 pub async fn schaltwerk_core_unmark_session_ready(
     app: tauri::AppHandle,
@@ -2006,11 +2893,10 @@ pub async fn schaltwerk_core_start_spec_session(
                     log::info!("Session '{session_name_clone}' does not have pending_name_generation flag, skipping");
                     return;
                 }
-                let agent = session.original_agent_type.clone().unwrap_or_else(|| {
-                    core.db
-                        .get_agent_type()
-                        .unwrap_or_else(|_| "claude".to_string())
-                });
+                let agent = session
+                    .original_agent_type
+                    .clone()
+                    .unwrap_or_else(|| default_agent_type(&core.db, &core.repo_path));
 
                 log::info!("Using agent '{agent}' for name generation of spec-started session '{session_name_clone}'");
 
@@ -2189,6 +3075,45 @@ pub async fn schaltwerk_core_update_spec_content(
     Ok(())
 }
 
+/// Rename a session including its branch and worktree. Terminals are keyed by session
+/// name, so the old ones are closed and the UI reopens them under the new name. Refused
+/// while the session's agent is running.
+#[tauri::command]
+pub async fn schaltwerk_core_rename_session(
+    app: tauri::AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<Session, String> {
+    log::info!("Renaming session from '{old_name}' to '{new_name}'");
+
+    let terminal_manager = get_terminal_manager().await.ok();
+    let session = {
+        let core = get_core_write().await?;
+        let manager = core.session_manager();
+        terminals::rename_session_with_terminals(
+            terminal_manager.as_deref(),
+            &old_name,
+            &new_name,
+            || {
+                let old_worktree = manager
+                    .get_session(&old_name)
+                    .map_err(|e| format!("Failed to rename session {old_name}: {e}"))?
+                    .worktree_path;
+                let session = manager
+                    .rename_session(&old_name, &new_name)
+                    .map_err(|e| format!("Failed to rename session {old_name}: {e}"))?;
+                let new_worktree = session.worktree_path.clone();
+                Ok((session, old_worktree, new_worktree))
+            },
+        )
+        .await?
+    };
+
+    events::emit_session_renamed(&app, &old_name, &session);
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(session)
+}
+
 #[tauri::command]
 pub async fn schaltwerk_core_rename_draft_session(
     app: tauri::AppHandle,
@@ -2294,11 +3219,11 @@ pub async fn schaltwerk_core_start_fresh_orchestrator(
         let mut paths = std::collections::HashMap::new();
 
         // Get resolved binary paths for all agents
-        for agent in ["claude", "codex", "opencode", "gemini", "droid", "qwen", "amp"] {
-            match settings.get_effective_binary_path(agent) {
+        for agent in AgentManifest::launchable_agents() {
+            match settings.get_effective_binary_path(&agent) {
                 Ok(path) => {
                     log::debug!("Cached binary path for {agent}: {path}");
-                    paths.insert(agent.to_string(), path);
+                    paths.insert(agent, path);
                 }
                 Err(e) => log::warn!("Failed to get cached binary path for {agent}: {e}"),
             }
@@ -2446,6 +3371,34 @@ pub async fn schaltwerk_core_discard_file_in_session(
         .map_err(|e| format!("Failed to discard file changes: {e}"))
 }
 
+#[tauri::command]
+pub async fn schaltwerk_core_list_session_file_hunks(
+    session_name: String,
+    file_path: String,
+) -> Result<Vec<schaltwerk::domains::git::service::WorktreeHunk>, String> {
+    let manager = session_manager_read().await?;
+    manager
+        .list_session_file_hunks(&session_name, &file_path)
+        .map_err(|e| format!("Failed to list hunks: {e}"))
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_discard_hunks_in_session(
+    session_name: String,
+    file_path: String,
+    hunk_ids: Vec<String>,
+) -> Result<usize, String> {
+    log::info!(
+        "Discarding {} hunk(s) in session '{session_name}' for path: {file_path}",
+        hunk_ids.len()
+    );
+    let core = get_core_write().await?;
+    let manager = core.session_manager();
+    manager
+        .discard_hunks_in_session(&session_name, &file_path, &hunk_ids)
+        .map_err(|e| format!("Failed to discard hunks: {e}"))
+}
+
 #[tauri::command]
 pub async fn schaltwerk_core_discard_file_in_orchestrator(file_path: String) -> Result<(), String> {
     log::info!("Discarding file changes in orchestrator for path: {file_path}");
@@ -1,9 +1,15 @@
 use crate::commands::schaltwerk_core::schaltwerk_core_cli::extract_codex_prompt_if_present;
 use crate::commands::schaltwerk_core::schaltwerk_core_cli::{
-    fix_codex_single_dash_long_flags, normalize_cli_text, reorder_codex_model_after_profile,
+    drop_model_args, fix_codex_single_dash_long_flags, normalize_cli_text,
+    reorder_codex_model_after_profile,
 };
 use crate::SETTINGS_MANAGER;
+use schaltwerk::api::mcp::{auth, socket};
+use schaltwerk::domains::agents::manifest::AgentManifest;
+use schaltwerk::domains::sessions::db_sessions::SessionMethods;
+use schaltwerk::domains::settings::{SettingsLayerValues, SettingsOverrides};
 use schaltwerk::schaltwerk_core::db_project_config::ProjectConfigMethods;
+use std::collections::HashMap;
 use std::path::Path;
 
 pub enum AgentKind {
@@ -12,6 +18,8 @@ pub enum AgentKind {
     OpenCode,
     Gemini,
     Droid,
+    /// Any other agent declared in the manifest, keyed by its manifest id
+    Other(&'static str),
     Fallback,
 }
 
@@ -26,6 +34,8 @@ pub fn infer_agent_kind(agent_name: &str) -> AgentKind {
         AgentKind::Gemini
     } else if agent_name.ends_with("/droid") || agent_name == "droid" {
         AgentKind::Droid
+    } else if let Some(def) = AgentManifest::find_by_binary(agent_name) {
+        AgentKind::Other(def.id.as_str())
     } else {
         AgentKind::Fallback
     }
@@ -39,35 +49,92 @@ impl AgentKind {
             AgentKind::OpenCode => "opencode",
             AgentKind::Gemini => "gemini",
             AgentKind::Droid => "droid",
+            AgentKind::Other(id) => *id,
             AgentKind::Fallback => "claude",
         }
     }
 }
 
+/// Warn about manifest-declared environment requirements that are not satisfied
+pub fn warn_missing_env_requirements(agent_kind: &AgentKind, env_vars: &[(String, String)]) {
+    if let Some(def) = AgentManifest::get(agent_kind.manifest_key()) {
+        let missing = def.missing_env(env_vars);
+        if !missing.is_empty() {
+            log::warn!(
+                "{} requires environment variables that are not set: {}",
+                def.display_name,
+                missing.join(", ")
+            );
+        }
+    }
+}
+
+/// Agent env vars and CLI args resolved across the global settings, the project and the
+/// session owning `worktree_path`; later layers win. The project's MCP token is always
+/// added so the agent's MCP bridge can reach Schaltwerk.
 pub async fn collect_agent_env_and_cli(
     agent_kind: &AgentKind,
     repo_path: &Path,
+    worktree_path: &Path,
     db: &schaltwerk::schaltwerk_core::Database,
 ) -> (Vec<(String, String)>, String) {
-    let agent_str = match agent_kind {
-        AgentKind::Claude => "claude",
-        AgentKind::Codex => "codex",
-        AgentKind::OpenCode => "opencode",
-        AgentKind::Gemini => "gemini",
-        AgentKind::Droid => "droid",
-        AgentKind::Fallback => "claude",
-    };
+    let agent_str = agent_kind.manifest_key();
 
     let (env_vars, cli_args) = if let Some(settings_manager) = SETTINGS_MANAGER.get() {
         let mgr = settings_manager.lock().await;
-        let mut env = mgr
-            .get_agent_env_vars(agent_str)
+        let project = SettingsLayerValues {
+            overrides: db
+                .get_project_settings_overrides(repo_path)
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to load project settings overrides: {e}");
+                    SettingsOverrides::default()
+                }),
+            env_vars: {
+                // The project's own variables win over those of its env sets
+                let env_sets = db.get_project_env_sets(repo_path).unwrap_or_else(|e| {
+                    log::warn!("Failed to load project env sets: {e}");
+                    Vec::new()
+                });
+                let mut env_vars = mgr.resolve_env_sets(&env_sets);
+                env_vars.extend(
+                    db.get_project_environment_variables(repo_path)
+                        .unwrap_or_default(),
+                );
+                env_vars
+            },
+        };
+        let session = SettingsLayerValues {
+            overrides: db
+                .get_session_settings_overrides_by_worktree(worktree_path)
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to load session settings overrides: {e}");
+                    SettingsOverrides::default()
+                }),
+            env_vars: db
+                .get_session_environment_variables_by_worktree(worktree_path)
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to load session environment variables: {e}");
+                    HashMap::new()
+                }),
+        };
+        let effective = mgr.resolve_effective_settings(agent_str, &project, Some(&session));
+
+        let mut env = effective
+            .env_vars
             .into_iter()
+            .map(|(key, effective)| (key, effective.value))
             .collect::<Vec<_>>();
-        if let Ok(project_env) = db.get_project_environment_variables(repo_path) {
-            env.extend(project_env.into_iter());
+        env.push((
+            auth::MCP_TOKEN_ENV.to_string(),
+            auth::project_token(repo_path),
+        ));
+        if let Some(socket) = socket::listening_socket() {
+            env.push((
+                socket::MCP_SOCKET_ENV.to_string(),
+                socket.to_string_lossy().to_string(),
+            ));
         }
-        (env, mgr.get_agent_cli_args(agent_str))
+        (env, effective.agent_cli_args.value)
     } else {
         (vec![], String::new())
     };
@@ -88,11 +155,17 @@ pub fn build_final_args(
     let mut additional =
         shell_words::split(&normalized).unwrap_or_else(|_| vec![cli_args_text.to_string()]);
 
+    // The session's own model, inserted by the launch spec, wins over one in the CLI args
+    let session_picked_model = parsed_agent_args.iter().any(|arg| arg == "--model");
+
     match agent_kind {
         AgentKind::Codex => {
             // Preserve any trailing prompt from parsed args, then enforce flag normalization and order
             let extracted_prompt = extract_codex_prompt_if_present(&mut parsed_agent_args);
             fix_codex_single_dash_long_flags(&mut additional);
+            if session_picked_model {
+                drop_model_args(&mut additional);
+            }
             reorder_codex_model_after_profile(&mut additional);
             parsed_agent_args.extend(additional);
             if let Some(p) = extracted_prompt {
@@ -101,16 +174,46 @@ pub fn build_final_args(
             parsed_agent_args
         }
         _ => {
+            if session_picked_model {
+                drop_model_args(&mut additional);
+            }
             parsed_agent_args.extend(additional);
             parsed_agent_args
         }
     }
 }
 
+/// Append the user's CLI args to a shell command line, e.g. the `echo ... | amp` pipe that
+/// runs through `sh -c` and so never passes through [`build_final_args`].
+pub fn append_cli_args_to_shell_command(command: &str, cli_args_text: &str) -> String {
+    if cli_args_text.trim().is_empty() {
+        return command.to_string();
+    }
+    let normalized = normalize_cli_text(cli_args_text);
+    let additional =
+        shell_words::split(&normalized).unwrap_or_else(|_| vec![cli_args_text.to_string()]);
+    format!("{command} {}", shell_words::join(additional))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn cli_args_are_quoted_onto_piped_commands() {
+        assert_eq!(
+            append_cli_args_to_shell_command(r#"echo "hi" | amp"#, ""),
+            r#"echo "hi" | amp"#
+        );
+        assert_eq!(
+            append_cli_args_to_shell_command(
+                r#"echo "hi" | amp --dangerously-allow-all"#,
+                "--mode free --notes 'two words'"
+            ),
+            r#"echo "hi" | amp --dangerously-allow-all --mode free --notes 'two words'"#
+        );
+    }
+
     #[test]
     fn test_infer_agent_kind() {
         assert!(matches!(infer_agent_kind("claude"), AgentKind::Claude));
@@ -132,6 +235,10 @@ mod tests {
             infer_agent_kind("/Users/test/.local/bin/droid"),
             AgentKind::Droid
         ));
+        assert!(matches!(
+            infer_agent_kind("/opt/bin/qwen"),
+            AgentKind::Other("qwen")
+        ));
         assert!(matches!(infer_agent_kind("unknown"), AgentKind::Fallback));
     }
 
@@ -162,6 +269,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_final_args_session_model_wins() {
+        let args = build_final_args(
+            &AgentKind::Codex,
+            vec![
+                "--model".into(),
+                "o3".into(),
+                "--sandbox".into(),
+                "workspace-write".into(),
+            ],
+            "-model gpt-4 -profile work",
+        );
+        assert_eq!(
+            args,
+            vec![
+                "--model",
+                "o3",
+                "--sandbox",
+                "workspace-write",
+                "--profile",
+                "work"
+            ]
+        );
+    }
+
     #[test]
     fn test_manifest_key_mapping() {
         assert_eq!(AgentKind::Claude.manifest_key(), "claude");
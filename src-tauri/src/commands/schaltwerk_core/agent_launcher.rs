@@ -1,8 +1,12 @@
-use super::{agent_ctx, terminals};
+use super::{agent_ctx, quote_command, terminals};
 use crate::get_terminal_manager;
 use schaltwerk::domains::agents::{parse_agent_command, AgentLaunchSpec};
 use schaltwerk::domains::terminal::manager::CreateTerminalWithAppAndSizeParams;
+use schaltwerk::domains::workspace::dev_environment::wrap_in_environment;
+use schaltwerk::schaltwerk_core::db_project_config::{DevEnvironmentKind, ProjectConfigMethods};
+use schaltwerk::schaltwerk_core::Database;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, LazyLock};
 use tokio::sync::Mutex as AsyncMutex;
 
@@ -12,8 +16,8 @@ static START_LOCKS: LazyLock<AsyncMutex<HashMap<String, Arc<AsyncMutex<()>>>>> =
 pub async fn launch_in_terminal(
     terminal_id: String,
     launch_spec: AgentLaunchSpec,
-    db: &schaltwerk::schaltwerk_core::Database,
-    repo_path: &std::path::Path,
+    db: &Database,
+    repo_path: &Path,
     cols: Option<u16>,
     rows: Option<u16>,
 ) -> Result<String, String> {
@@ -39,9 +43,21 @@ pub async fn launch_in_terminal(
 
     let agent_kind = agent_ctx::infer_agent_kind(&agent_name);
     let (env_vars, cli_text) =
-        agent_ctx::collect_agent_env_and_cli(&agent_kind, repo_path, db).await;
+        agent_ctx::collect_agent_env_and_cli(&agent_kind, repo_path, Path::new(&cwd), db).await;
     let merged_env = merge_env_vars(env_vars, &launch_spec.env_vars);
+    agent_ctx::warn_missing_env_requirements(&agent_kind, &merged_env);
     let final_args = agent_ctx::build_final_args(&agent_kind, agent_args, &cli_text);
+    // Enter the project's dev environment when one is configured
+    let (command, final_args) = match project_dev_environment(db, repo_path) {
+        Some(kind) => (
+            "sh".to_string(),
+            vec![
+                "-lc".to_string(),
+                environment_shell_command(kind, &cwd, agent_name, final_args),
+            ],
+        ),
+        None => (agent_name, final_args),
+    };
 
     let manager = get_terminal_manager().await?;
     if manager.terminal_exists(&terminal_id).await? {
@@ -52,30 +68,49 @@ pub async fn launch_in_terminal(
         manager
             .create_terminal_with_app_and_size(CreateTerminalWithAppAndSizeParams {
                 id: terminal_id.clone(),
-                cwd: cwd.clone(),
-                command: agent_name.clone(),
-                args: final_args.clone(),
-                env: merged_env.clone(),
+                cwd,
+                command,
+                args: final_args,
+                env: merged_env,
                 cols: c,
                 rows: r,
             })
             .await?;
     } else {
         manager
-            .create_terminal_with_app(
-                terminal_id.clone(),
-                cwd.clone(),
-                agent_name.clone(),
-                final_args.clone(),
-                merged_env.clone(),
-            )
+            .create_terminal_with_app(terminal_id.clone(), cwd, command, final_args, merged_env)
             .await?;
     }
 
     Ok(launch_spec.shell_command)
 }
 
-fn merge_env_vars(
+/// The dev environment configured for the project, if any.
+pub(super) fn project_dev_environment(
+    db: &Database,
+    repo_path: &Path,
+) -> Option<DevEnvironmentKind> {
+    db.get_project_dev_environment(repo_path)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load project dev environment: {e}");
+            None
+        })
+}
+
+/// Shell command that runs `program` with its final arguments inside the dev environment
+/// of `cwd`.
+pub(super) fn environment_shell_command(
+    kind: DevEnvironmentKind,
+    cwd: &str,
+    program: String,
+    args: Vec<String>,
+) -> String {
+    let (launcher, args) = wrap_in_environment(kind, Path::new(cwd), program, args);
+    format!("exec {}", quote_command(&launcher, &args))
+}
+
+/// `base` with the launch spec's own variables added; the spec wins on duplicates.
+pub(super) fn merge_env_vars(
     base: Vec<(String, String)>,
     extra: &HashMap<String, String>,
 ) -> Vec<(String, String)> {
@@ -93,9 +128,32 @@ fn merge_env_vars(
 
 #[cfg(test)]
 mod tests {
-    use super::merge_env_vars;
+    use super::{agent_ctx, environment_shell_command, merge_env_vars};
+    use schaltwerk::schaltwerk_core::db_project_config::DevEnvironmentKind;
     use std::collections::HashMap;
 
+    #[test]
+    fn environment_command_keeps_the_agent_cli_args() {
+        let final_args = agent_ctx::build_final_args(
+            &agent_ctx::AgentKind::Claude,
+            vec!["--dangerously-skip-permissions".into()],
+            "--model opus --add-dir '/tmp/two words'",
+        );
+
+        let command = environment_shell_command(
+            DevEnvironmentKind::Nix,
+            "/wt/demo",
+            "claude".to_string(),
+            final_args,
+        );
+
+        assert_eq!(
+            command,
+            "exec 'nix' 'develop' '/wt/demo' '--command' 'claude' \
+             '--dangerously-skip-permissions' '--model' 'opus' '--add-dir' '/tmp/two words'"
+        );
+    }
+
     #[test]
     fn merge_env_vars_overrides_duplicates() {
         let base = vec![
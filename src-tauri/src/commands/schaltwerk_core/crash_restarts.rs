@@ -0,0 +1,112 @@
+use crate::{get_core_read, get_terminal_manager};
+use schaltwerk::domains::sessions::entity::{AgentCrash, SessionState};
+use schaltwerk::infrastructure::events::SchaltEvent;
+use schaltwerk::shared::terminal_id::terminal_id_for_session_top;
+use std::path::PathBuf;
+use tauri::{AppHandle, Listener};
+
+/// The parts of `SchaltEvent::AgentCrashed` the supervisor needs
+#[derive(Debug, serde::Deserialize)]
+struct AgentCrashedPayload {
+    terminal_id: String,
+    exit_code: Option<i32>,
+    project_path: Option<PathBuf>,
+}
+
+/// Crashes of a session's agent, oldest first.
+#[tauri::command]
+pub async fn schaltwerk_core_list_agent_crashes(
+    session_name: String,
+) -> Result<Vec<AgentCrash>, String> {
+    let core = get_core_read().await?;
+    core.session_manager()
+        .list_agent_crashes(&session_name)
+        .map_err(|e| format!("Failed to list agent crashes: {e}"))
+}
+
+/// Record every crashed session agent and, as the agent restart settings allow, start
+/// it again in its terminal after a backoff.
+pub fn start_agent_crash_supervisor(app: AppHandle) {
+    let handle = app.clone();
+    app.listen_any(SchaltEvent::AgentCrashed.as_str(), move |event| {
+        let payload = match serde_json::from_str::<AgentCrashedPayload>(event.payload()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::warn!("Ignoring unreadable agent crash event: {e}");
+                return;
+            }
+        };
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = supervise_crash(app, payload).await {
+                log::warn!("Agent crash supervision failed: {e}");
+            }
+        });
+    });
+}
+
+async fn supervise_crash(app: AppHandle, crash: AgentCrashedPayload) -> Result<(), String> {
+    // A clean exit is the user quitting the agent
+    if crash.exit_code.is_none_or(|code| code == 0) {
+        return Ok(());
+    }
+
+    let (session_name, plan) = {
+        let core = get_core_read().await?;
+        // Restarts go through the current project's session manager
+        if crash.project_path.as_deref() != Some(core.repo_path.as_path()) {
+            log::info!(
+                "Not supervising crash in terminal {} outside the current project",
+                crash.terminal_id
+            );
+            return Ok(());
+        }
+        let manager = core.session_manager();
+        let sessions = manager
+            .list_sessions()
+            .map_err(|e| format!("Failed to list sessions: {e}"))?;
+        let Some(session) = sessions.into_iter().find(|session| {
+            session.session_state == SessionState::Running
+                && terminal_id_for_session_top(&session.name) == crash.terminal_id
+        }) else {
+            return Ok(());
+        };
+        let agent_type = session
+            .original_agent_type
+            .clone()
+            .unwrap_or_else(|| "claude".to_string());
+        let plan = manager
+            .record_agent_crash(&session.name, &agent_type, crash.exit_code)
+            .map_err(|e| format!("Failed to record agent crash: {e}"))?;
+        (session.name, plan)
+    };
+
+    let Some(plan) = plan else {
+        log::info!("Agent of session '{session_name}' crashed; not restarting it");
+        return Ok(());
+    };
+    log::info!(
+        "Restarting crashed agent of session '{session_name}' in {:?} (attempt {})",
+        plan.delay,
+        plan.attempt
+    );
+    tokio::time::sleep(plan.delay).await;
+
+    // The user may have restarted the agent or removed the session in the meantime
+    let manager = get_terminal_manager().await?;
+    if manager.terminal_exists(&crash.terminal_id).await? {
+        return Ok(());
+    }
+    let still_running = get_core_read()
+        .await?
+        .session_manager()
+        .get_session(&session_name)
+        .is_ok_and(|session| session.session_state == SessionState::Running);
+    if !still_running {
+        return Ok(());
+    }
+
+    super::schaltwerk_core_start_claude_with_restart(app, session_name, false, None, None)
+        .await
+        .map(|_| ())
+}
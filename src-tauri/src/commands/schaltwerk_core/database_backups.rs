@@ -0,0 +1,104 @@
+use crate::{get_core_read, get_core_write};
+use chrono::Utc;
+use schaltwerk::infrastructure::database::db_backup::{
+    BackupMethods, DatabaseBackup, IntegrityReport,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::events;
+
+const DATABASE_BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Age of the newest backup after which the periodic task takes another one
+const DATABASE_BACKUP_INTERVAL_HOURS: i64 = 24;
+
+/// Backups kept in the backups directory; older ones are deleted
+const DATABASE_BACKUPS_KEPT: usize = 7;
+
+/// Back up the current project's database to `destination`, or into its backups
+/// directory when no destination is given. Returns where the backup was written.
+#[tauri::command]
+pub async fn backup_database(destination: Option<String>) -> Result<String, String> {
+    let db = get_core_read().await?.db.clone();
+    tokio::task::spawn_blocking(move || match destination {
+        Some(destination) => {
+            let destination = PathBuf::from(destination);
+            db.backup_database(&destination).map(|_| destination)
+        }
+        None => db.create_automatic_backup(DATABASE_BACKUPS_KEPT),
+    })
+    .await
+    .map_err(|e| format!("Failed to back up database: {e}"))?
+    .map(|path| path.display().to_string())
+    .map_err(|e| format!("Failed to back up database: {e:#}"))
+}
+
+/// Replace the current project's database with a backup. The write lock on the core
+/// keeps every other command off the database until the restore is done.
+#[tauri::command]
+pub async fn restore_database(app: AppHandle, source: String) -> Result<(), String> {
+    {
+        let core = get_core_write().await?;
+        let db = core.db.clone();
+        tokio::task::spawn_blocking(move || db.restore_database(&PathBuf::from(source)))
+            .await
+            .map_err(|e| format!("Failed to restore database: {e}"))?
+            .map_err(|e| format!("Failed to restore database: {e:#}"))?;
+    }
+    events::request_sessions_refreshed(&app, events::SessionsRefreshReason::SessionLifecycle);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn check_database_integrity() -> Result<IntegrityReport, String> {
+    let db = get_core_read().await?.db.clone();
+    tokio::task::spawn_blocking(move || db.check_database_integrity())
+        .await
+        .map_err(|e| format!("Failed to check database integrity: {e}"))?
+        .map_err(|e| format!("Failed to check database integrity: {e}"))
+}
+
+#[tauri::command]
+pub async fn list_database_backups() -> Result<Vec<DatabaseBackup>, String> {
+    get_core_read()
+        .await?
+        .db
+        .list_database_backups()
+        .map_err(|e| format!("Failed to list database backups: {e}"))
+}
+
+/// Back up the current project's database once a day.
+pub fn start_database_backups() {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(DATABASE_BACKUP_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = back_up_if_due().await {
+                log::warn!("Automatic database backup failed: {e}");
+            }
+        }
+    });
+}
+
+async fn back_up_if_due() -> Result<(), String> {
+    let db = get_core_read().await?.db.clone();
+    let newest = db
+        .list_database_backups()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next();
+    if newest.is_some_and(|backup| {
+        Utc::now() - backup.created_at < chrono::Duration::hours(DATABASE_BACKUP_INTERVAL_HOURS)
+    }) {
+        return Ok(());
+    }
+    let path =
+        tokio::task::spawn_blocking(move || db.create_automatic_backup(DATABASE_BACKUPS_KEPT))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{e:#}"))?;
+    log::info!("Backed up the database to {}", path.display());
+    Ok(())
+}
@@ -1,18 +1,44 @@
 use crate::commands::sessions_refresh::request_sessions_refresh;
 pub use crate::commands::sessions_refresh::SessionsRefreshReason;
+use schaltwerk::domains::merge::queue::MergeQueueSnapshot;
+use schaltwerk::domains::sessions::entity::Session;
 use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
 use tauri::AppHandle;
 
+#[derive(serde::Serialize, Clone)]
+pub struct SessionAddedPayload {
+    pub session_name: String,
+    pub branch: String,
+    pub worktree_path: String,
+    pub parent_branch: String,
+    pub created_at: String,
+    pub last_modified: Option<String>,
+}
+
 #[derive(serde::Serialize, Clone)]
 pub struct SessionRemovedPayload {
     pub session_name: String,
 }
 
+#[derive(serde::Serialize, Clone)]
+pub struct SessionRenamedPayload {
+    pub old_session_name: String,
+    pub session_name: String,
+    pub branch: String,
+    pub worktree_path: String,
+}
+
 #[derive(serde::Serialize, Clone)]
 pub struct SessionCancellingPayload {
     pub session_name: String,
 }
 
+#[derive(serde::Serialize, Clone)]
+pub struct SessionUnblockedPayload {
+    pub session_name: String,
+    pub dependency: String,
+}
+
 #[derive(serde::Serialize, Clone)]
 pub struct SelectionPayload {
     pub kind: &'static str,
@@ -38,6 +64,21 @@ pub struct GitOperationFailedPayload {
     pub error: String,
 }
 
+pub fn emit_session_added(app: &AppHandle, session: &Session) {
+    let _ = emit_event(
+        app,
+        SchaltEvent::SessionAdded,
+        &SessionAddedPayload {
+            session_name: session.name.clone(),
+            branch: session.branch.clone(),
+            worktree_path: session.worktree_path.to_string_lossy().to_string(),
+            parent_branch: session.parent_branch.clone(),
+            created_at: session.created_at.to_rfc3339(),
+            last_modified: session.last_activity.map(|ts| ts.to_rfc3339()),
+        },
+    );
+}
+
 pub fn emit_session_removed(app: &AppHandle, name: &str) {
     let _ = emit_event(
         app,
@@ -48,6 +89,30 @@ pub fn emit_session_removed(app: &AppHandle, name: &str) {
     );
 }
 
+pub fn emit_session_renamed(app: &AppHandle, old_name: &str, session: &Session) {
+    let _ = emit_event(
+        app,
+        SchaltEvent::SessionRenamed,
+        &SessionRenamedPayload {
+            old_session_name: old_name.to_string(),
+            session_name: session.name.clone(),
+            branch: session.branch.clone(),
+            worktree_path: session.worktree_path.to_string_lossy().to_string(),
+        },
+    );
+}
+
+pub fn emit_session_unblocked(app: &AppHandle, name: &str, dependency: &str) {
+    let _ = emit_event(
+        app,
+        SchaltEvent::SessionUnblocked,
+        &SessionUnblockedPayload {
+            session_name: name.to_string(),
+            dependency: dependency.to_string(),
+        },
+    );
+}
+
 pub fn emit_session_cancelling(app: &AppHandle, name: &str) {
     let _ = emit_event(
         app,
@@ -146,3 +211,7 @@ pub fn emit_git_operation_failed(
     };
     let _ = emit_event(app, SchaltEvent::GitOperationFailed, &payload);
 }
+
+pub fn emit_merge_queue_updated(app: &AppHandle, snapshot: &MergeQueueSnapshot) {
+    let _ = emit_event(app, SchaltEvent::MergeQueueUpdated, snapshot);
+}
@@ -0,0 +1,183 @@
+use crate::get_core_read;
+use schaltwerk::domains::merge::queue::{
+    self, MergeQueueEntry, MergeQueueSnapshot, MergeQueueStage,
+};
+use schaltwerk::domains::merge::{MergeMode, MergeService};
+use schaltwerk::schaltwerk_core::db_project_config::ProjectConfigMethods;
+use schaltwerk::schaltwerk_core::{Database, SessionManager};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::{events, merge_session_in_repo};
+
+/// Queue a reviewed session for merging. Queued sessions merge one at a time, each
+/// optionally rebased onto the updated base and checked with the project's test
+/// script first.
+#[tauri::command]
+pub async fn schaltwerk_core_enqueue_merge(
+    app: AppHandle,
+    name: String,
+    mode: MergeMode,
+    commit_message: Option<String>,
+    auto_rebase: bool,
+    confirm_protected_paths: Option<bool>,
+) -> Result<MergeQueueSnapshot, String> {
+    let (db, repo_path) = {
+        let core = get_core_read().await?;
+        (core.db.clone(), core.repo_path.clone())
+    };
+
+    let session = SessionManager::new(db.clone(), repo_path.clone())
+        .get_session(&name)
+        .map_err(|e| format!("Failed to find session {name}: {e}"))?;
+    if !session.ready_to_merge {
+        return Err(format!(
+            "Session '{name}' must be marked as reviewed before it can be queued for merge"
+        ));
+    }
+
+    let commit_message = commit_message.filter(|m| !m.trim().is_empty());
+    let commit_message = match (mode, commit_message) {
+        (MergeMode::Squash, None) => Some(
+            MergeService::new(db.clone(), repo_path.clone())
+                .preview(&name)
+                .map_err(|e| format!("Failed to prepare merge for session {name}: {e}"))?
+                .default_commit_message,
+        ),
+        (_, message) => message,
+    };
+
+    let (snapshot, start_worker) = queue::enqueue(
+        &repo_path,
+        &name,
+        mode,
+        commit_message,
+        auto_rebase,
+        confirm_protected_paths.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())?;
+    events::emit_merge_queue_updated(&app, &snapshot);
+    if start_worker {
+        tokio::spawn(run_merge_queue(app, db, repo_path));
+    }
+    Ok(snapshot)
+}
+
+/// Take a session out of the merge queue. The session being processed is cancelled
+/// instead, unless it is already merging.
+#[tauri::command]
+pub async fn schaltwerk_core_remove_from_merge_queue(
+    app: AppHandle,
+    name: String,
+) -> Result<MergeQueueSnapshot, String> {
+    let repo_path = get_core_read().await?.repo_path.clone();
+    let snapshot = queue::remove(&repo_path, &name).map_err(|e| e.to_string())?;
+    events::emit_merge_queue_updated(&app, &snapshot);
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_get_merge_queue() -> Result<MergeQueueSnapshot, String> {
+    let repo_path = get_core_read().await?.repo_path.clone();
+    Ok(queue::snapshot(&repo_path))
+}
+
+/// Drain the queue of one project. A failed entry is dropped and reported; the
+/// entries behind it still get their turn.
+async fn run_merge_queue(app: AppHandle, db: Database, repo_path: PathBuf) {
+    while let Some(entry) = queue::begin_next(&repo_path) {
+        let name = entry.session_name.clone();
+        let failure = process_entry(&app, &db, &repo_path, entry)
+            .await
+            .err()
+            .filter(|_| !queue::is_cancelled(&repo_path, &name));
+        if let Some((stage, message)) = &failure {
+            log::warn!("Merge queue dropped session '{name}' while {stage:?}: {message}");
+        }
+        let snapshot = queue::finish(&repo_path, &name, failure);
+        events::emit_merge_queue_updated(&app, &snapshot);
+    }
+    events::emit_merge_queue_updated(&app, &queue::snapshot(&repo_path));
+}
+
+async fn process_entry(
+    app: &AppHandle,
+    db: &Database,
+    repo_path: &Path,
+    entry: MergeQueueEntry,
+) -> Result<(), (MergeQueueStage, String)> {
+    let name = entry.session_name.as_str();
+    let session = SessionManager::new(db.clone(), repo_path.to_path_buf())
+        .get_session(name)
+        .map_err(|e| (MergeQueueStage::Queued, e.to_string()))?;
+    if !session.ready_to_merge {
+        return Err((
+            MergeQueueStage::Queued,
+            format!("Session '{name}' is no longer marked as reviewed"),
+        ));
+    }
+
+    if entry.auto_rebase {
+        set_stage(app, repo_path, name, MergeQueueStage::Rebasing);
+        let (db, repo, session_name) = (db.clone(), repo_path.to_path_buf(), name.to_string());
+        tokio::task::spawn_blocking(move || {
+            queue::rebase_onto_base(&SessionManager::new(db, repo), &session_name)
+        })
+        .await
+        .map_err(|e| (MergeQueueStage::Rebasing, e.to_string()))?
+        .map_err(|e| (MergeQueueStage::Rebasing, e.to_string()))?;
+        events::request_sessions_refreshed(app, events::SessionsRefreshReason::MergeWorkflow);
+    }
+
+    let test_script = db
+        .get_project_test_script(repo_path)
+        .map_err(|e| (MergeQueueStage::Testing, e.to_string()))?;
+    if let Some(script) = test_script {
+        let timeout = db
+            .get_project_test_timeout_minutes(repo_path)
+            .map_err(|e| (MergeQueueStage::Testing, e.to_string()))?
+            .map_or(queue::DEFAULT_TEST_TIMEOUT, |minutes| {
+                Duration::from_secs(u64::from(minutes) * 60)
+            });
+        set_stage(app, repo_path, name, MergeQueueStage::Testing);
+        let (worktree_path, repo, session_name) = (
+            session.worktree_path.clone(),
+            repo_path.to_path_buf(),
+            name.to_string(),
+        );
+        tokio::task::spawn_blocking(move || {
+            queue::run_test_script(&worktree_path, &script, timeout, || {
+                queue::is_cancelled(&repo, &session_name)
+            })
+        })
+        .await
+        .map_err(|e| (MergeQueueStage::Testing, e.to_string()))?
+        .map_err(|e| (MergeQueueStage::Testing, e.to_string()))?;
+    }
+
+    if queue::is_cancelled(repo_path, name) {
+        return Err((
+            MergeQueueStage::Merging,
+            format!("Session '{name}' was removed from the merge queue"),
+        ));
+    }
+    set_stage(app, repo_path, name, MergeQueueStage::Merging);
+    merge_session_in_repo(
+        app,
+        db.clone(),
+        repo_path.to_path_buf(),
+        name,
+        entry.mode,
+        entry.commit_message,
+        entry.confirm_protected_paths,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|e| (MergeQueueStage::Merging, e.message))
+}
+
+fn set_stage(app: &AppHandle, repo_path: &Path, name: &str, stage: MergeQueueStage) {
+    let snapshot = queue::set_stage(repo_path, name, stage);
+    events::emit_merge_queue_updated(app, &snapshot);
+}
@@ -126,6 +126,23 @@ pub fn reorder_codex_model_after_profile(args: &mut Vec<String>) {
     *args = without_model;
 }
 
+// Drop `--model <id>` / `--model=<id>` from user args when the session already picked a
+// model, so the agent does not receive two models. Short `-m` means other things to
+// some agents (e.g. Aider's `--message`), so it is left alone.
+pub fn drop_model_args(args: &mut Vec<String>) {
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--model" {
+            let end = (i + 2).min(args.len());
+            args.drain(i..end);
+        } else if args[i].starts_with("--model=") {
+            args.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,6 +274,20 @@ mod tests {
         assert_eq!(args, vec!["--search"]);
     }
 
+    #[test]
+    fn test_drop_model_args_keeps_other_flags() {
+        let mut args = vec![
+            "--model".to_string(),
+            "sonnet".to_string(),
+            "-m".to_string(),
+            "hello".to_string(),
+            "--model=opus".to_string(),
+            "--verbose".to_string(),
+        ];
+        drop_model_args(&mut args);
+        assert_eq!(args, vec!["-m", "hello", "--verbose"]);
+    }
+
     #[test]
     fn test_reorder_codex_model_after_profile() {
         let mut args = vec![
@@ -0,0 +1,45 @@
+use schaltwerk::domains::sessions::cancellation::CancellationToken;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+// Session creations that can still be cancelled, keyed by the id the frontend passed in
+static PENDING_CREATIONS: LazyLock<Mutex<HashMap<String, CancellationToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn register_creation(creation_id: &str) -> CancellationToken {
+    let token = CancellationToken::new();
+    if let Ok(mut pending) = PENDING_CREATIONS.lock() {
+        pending.insert(creation_id.to_string(), token.clone());
+    }
+    token
+}
+
+pub fn finish_creation(creation_id: &str) {
+    if let Ok(mut pending) = PENDING_CREATIONS.lock() {
+        pending.remove(creation_id);
+    }
+}
+
+/// Cancel an in-flight `schaltwerk_core_create_session`. Returns false when it already
+/// finished, or got too far to be cancelled; the session then exists.
+#[tauri::command]
+pub async fn schaltwerk_core_cancel_session_creation(creation_id: String) -> Result<bool, String> {
+    let token = PENDING_CREATIONS
+        .lock()
+        .map_err(|e| format!("Failed to access pending session creations: {e}"))?
+        .get(&creation_id)
+        .cloned();
+
+    match token {
+        Some(token) => {
+            let cancelled = token.cancel();
+            if cancelled {
+                log::info!("Cancelling session creation {creation_id}");
+            } else {
+                log::info!("Session creation {creation_id} completed before it could be cancelled");
+            }
+            Ok(cancelled)
+        }
+        None => Ok(false),
+    }
+}
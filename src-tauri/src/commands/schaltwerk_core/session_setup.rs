@@ -0,0 +1,411 @@
+use crate::{get_core_read, get_project_manager, get_terminal_manager};
+use futures::future::BoxFuture;
+use schaltwerk::domains::sessions::entity::SetupStatus;
+use schaltwerk::domains::sessions::setup_cache::{setup_fingerprint, SetupCache};
+use schaltwerk::domains::terminal::setup_runner::{
+    SetupFinished, SetupFinishedHandler, SetupRequest, SetupRunner,
+};
+use schaltwerk::domains::terminal::TerminalManager;
+use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
+use schaltwerk::schaltwerk_core::SessionManager;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Instant;
+use tauri::AppHandle;
+
+use super::terminals::terminal_id_for_session_setup;
+
+static SETUP_RUNNER: LazyLock<SetupRunner> = LazyLock::new(SetupRunner::new);
+// Sessions whose setup is being restored from the cache instead of running in a terminal
+static RESTORING_SESSIONS: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+// Sessions created with `force_setup`; their first setup skips the cache
+static FORCED_SETUP_SESSIONS: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// Written by the previous in-terminal setup chain; worktrees carrying it were already set up
+const LEGACY_SETUP_MARKER: &str = ".schaltwerk/setup.done";
+
+#[derive(serde::Serialize, Clone)]
+pub struct SetupStatusPayload {
+    pub session_name: String,
+    pub terminal_id: String,
+    pub status: SetupStatus,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl From<&SetupFinished> for SetupStatusPayload {
+    fn from(finished: &SetupFinished) -> Self {
+        Self {
+            session_name: finished.session_name.clone(),
+            terminal_id: finished.terminal_id.clone(),
+            status: finished.status,
+            exit_code: finished.exit_code,
+            duration_ms: Some(finished.duration_ms),
+            error: (finished.status == SetupStatus::Failed).then(|| {
+                match (finished.timed_out, finished.exit_code) {
+                    (true, _) => format!(
+                        "Setup script timed out after {}s",
+                        finished.duration_ms / 1000
+                    ),
+                    (false, Some(code)) => format!("Setup script exited with code {code}"),
+                    (false, None) => "Setup script failed".to_string(),
+                }
+            }),
+        }
+    }
+}
+
+/// Setup run for a session; `None` when the project has no setup step.
+pub fn setup_request(
+    manager: &SessionManager,
+    repo_path: &Path,
+    session_name: &str,
+) -> Result<Option<SetupRequest>, String> {
+    let Some(script) = manager
+        .session_setup_script()
+        .map_err(|e| format!("Failed to load setup script: {e}"))?
+        .filter(|script| !script.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let session = manager
+        .get_session(session_name)
+        .map_err(|e| format!("Failed to get session: {e}"))?;
+    Ok(Some(SetupRequest {
+        terminal_id: terminal_id_for_session_setup(&session.name),
+        session_name: session.name,
+        worktree_path: session.worktree_path,
+        repo_path: repo_path.to_path_buf(),
+        branch_name: session.branch,
+        script,
+        timeout: manager.session_setup_timeout(),
+    }))
+}
+
+/// Setup run for a session whose setup has not passed yet; `None` when the agent may start.
+pub fn pending_setup_request(
+    manager: &SessionManager,
+    repo_path: &Path,
+    session_name: &str,
+) -> Result<Option<SetupRequest>, String> {
+    let status = manager
+        .get_session_setup_status(session_name)
+        .map_err(|e| format!("Failed to get setup status: {e}"))?;
+    if status == Some(SetupStatus::Succeeded) {
+        return Ok(None);
+    }
+    let Some(request) = setup_request(manager, repo_path, session_name)? else {
+        return Ok(None);
+    };
+    if status.is_none() && request.worktree_path.join(LEGACY_SETUP_MARKER).exists() {
+        manager
+            .set_session_setup_status(session_name, Some(SetupStatus::Succeeded))
+            .map_err(|e| format!("Failed to record setup status: {e}"))?;
+        return Ok(None);
+    }
+    Ok(Some(request))
+}
+
+fn is_restoring(session_name: &str) -> bool {
+    RESTORING_SESSIONS
+        .lock()
+        .map(|restoring| restoring.contains(session_name))
+        .unwrap_or(false)
+}
+
+fn set_restoring(session_name: &str, restoring: bool) {
+    if let Ok(mut sessions) = RESTORING_SESSIONS.lock() {
+        if restoring {
+            sessions.insert(session_name.to_string());
+        } else {
+            sessions.remove(session_name);
+        }
+    }
+}
+
+/// Make the next setup of a session run the script even when a cached result exists.
+pub fn request_forced_setup(session_name: &str) {
+    if let Ok(mut sessions) = FORCED_SETUP_SESSIONS.lock() {
+        sessions.insert(session_name.to_string());
+    }
+}
+
+pub fn take_forced_setup(session_name: &str) -> bool {
+    FORCED_SETUP_SESSIONS
+        .lock()
+        .map(|mut sessions| sessions.remove(session_name))
+        .unwrap_or(false)
+}
+
+pub async fn is_session_setup_running(session_name: &str) -> bool {
+    is_restoring(session_name) || SETUP_RUNNER.is_running(session_name).await
+}
+
+async fn record_setup_status(repo_path: &Path, session_name: &str, status: SetupStatus) {
+    let core = match get_project_manager()
+        .await
+        .get_schaltwerk_core_for_path(&repo_path.to_path_buf())
+        .await
+    {
+        Ok(core) => core,
+        Err(e) => {
+            log::warn!("Failed to record setup status for {session_name}: {e}");
+            return;
+        }
+    };
+    let core = core.read().await;
+    if let Err(e) = core
+        .session_manager()
+        .set_session_setup_status(session_name, Some(status))
+    {
+        log::warn!("Failed to record setup status for {session_name}: {e}");
+    }
+}
+
+fn emit_setup_error(app: &AppHandle, request: &SetupRequest, error: String) {
+    let _ = emit_event(
+        app,
+        SchaltEvent::SetupError,
+        &SetupStatusPayload {
+            session_name: request.session_name.clone(),
+            terminal_id: request.terminal_id.clone(),
+            status: SetupStatus::Failed,
+            exit_code: None,
+            duration_ms: None,
+            error: Some(error),
+        },
+    );
+}
+
+// Boxed so the agent start, which itself launches setup, is not a recursive opaque future
+fn start_agent(app: AppHandle, session_name: String) -> BoxFuture<'static, Result<String, String>> {
+    Box::pin(super::schaltwerk_core_start_claude_with_restart(
+        app,
+        session_name,
+        false,
+        None,
+        None,
+    ))
+}
+
+/// Records and emits the outcome, caches the artifacts of a successful script run under
+/// `cache_fingerprint` and optionally starts the agent.
+fn setup_finished_handler(
+    app: AppHandle,
+    request: &SetupRequest,
+    start_agent_after: bool,
+    cache_fingerprint: Option<String>,
+) -> SetupFinishedHandler {
+    let repo_path = request.repo_path.clone();
+    let worktree_path = request.worktree_path.clone();
+    Box::new(move |finished: SetupFinished| {
+        Box::pin(async move {
+            record_setup_status(&repo_path, &finished.session_name, finished.status).await;
+
+            let event = if finished.status == SetupStatus::Failed {
+                SchaltEvent::SetupError
+            } else {
+                SchaltEvent::SetupFinished
+            };
+            let _ = emit_event(&app, event, &SetupStatusPayload::from(&finished));
+
+            if finished.status != SetupStatus::Succeeded {
+                return;
+            }
+            if let Some(fingerprint) = cache_fingerprint {
+                let cache = SetupCache::new(&repo_path);
+                let stored =
+                    tokio::task::spawn_blocking(move || cache.store(&fingerprint, &worktree_path))
+                        .await;
+                if let Ok(Err(e)) = stored {
+                    log::warn!(
+                        "Failed to cache setup artifacts for {}: {e}",
+                        finished.session_name
+                    );
+                }
+            }
+
+            if !start_agent_after {
+                return;
+            }
+            let current_project = get_project_manager().await.current_project_path().await;
+            if current_project.as_ref() != Some(&repo_path) {
+                log::info!(
+                    "Not starting agent for {} after setup: project is no longer active",
+                    finished.session_name
+                );
+                return;
+            }
+            if let Err(e) = start_agent(app, finished.session_name.clone()).await {
+                log::error!(
+                    "Failed to start agent for {} after setup: {e}",
+                    finished.session_name
+                );
+            }
+        }) as BoxFuture<'static, ()>
+    })
+}
+
+/// Restore cached setup artifacts, falling back to running the script when restoring fails.
+async fn restore_cached_setup(
+    app: AppHandle,
+    request: SetupRequest,
+    fingerprint: String,
+    start_agent_after: bool,
+    terminal_manager: Arc<TerminalManager>,
+) {
+    let started = Instant::now();
+    let cache = SetupCache::new(&request.repo_path);
+    let worktree_path = request.worktree_path.clone();
+    let restore_fingerprint = fingerprint.clone();
+    let restored =
+        tokio::task::spawn_blocking(move || cache.restore(&restore_fingerprint, &worktree_path))
+            .await;
+    set_restoring(&request.session_name, false);
+
+    if let Ok(Ok(true)) = restored {
+        let on_finish = setup_finished_handler(app, &request, start_agent_after, None);
+        on_finish(SetupFinished {
+            session_name: request.session_name,
+            terminal_id: request.terminal_id,
+            status: SetupStatus::Succeeded,
+            exit_code: None,
+            duration_ms: started.elapsed().as_millis() as u64,
+            timed_out: false,
+        })
+        .await;
+        return;
+    }
+
+    log::warn!(
+        "Could not restore cached setup for {}, running the script instead: {restored:?}",
+        request.session_name
+    );
+    let on_finish =
+        setup_finished_handler(app.clone(), &request, start_agent_after, Some(fingerprint));
+    if let Err(e) = SETUP_RUNNER
+        .start(request.clone(), terminal_manager, on_finish)
+        .await
+    {
+        record_setup_status(
+            &request.repo_path,
+            &request.session_name,
+            SetupStatus::Failed,
+        )
+        .await;
+        emit_setup_error(&app, &request, e);
+    }
+}
+
+/// Run the setup step for a session; when `start_agent_after` is set the session's agent is
+/// started once setup succeeded. Unless `force_setup` is set, an identical earlier setup is
+/// restored from the cache instead of running the script again.
+pub async fn launch_session_setup(
+    app: AppHandle,
+    manager: &SessionManager,
+    request: SetupRequest,
+    start_agent_after: bool,
+    force_setup: bool,
+) -> Result<(), String> {
+    if is_session_setup_running(&request.session_name).await {
+        return Err(format!(
+            "Setup is already running for session '{}'",
+            request.session_name
+        ));
+    }
+    let terminal_manager = get_terminal_manager().await?;
+    let fingerprint = setup_fingerprint(&request.script, &request.worktree_path);
+
+    // Marked running before the work starts so a fast finish is not overwritten
+    manager
+        .set_session_setup_status(&request.session_name, Some(SetupStatus::Running))
+        .map_err(|e| format!("Failed to record setup status: {e}"))?;
+    let _ = emit_event(
+        &app,
+        SchaltEvent::SetupStarted,
+        &SetupStatusPayload {
+            session_name: request.session_name.clone(),
+            terminal_id: request.terminal_id.clone(),
+            status: SetupStatus::Running,
+            exit_code: None,
+            duration_ms: None,
+            error: None,
+        },
+    );
+
+    if !force_setup && SetupCache::new(&request.repo_path).has_entry(&fingerprint) {
+        log::info!(
+            "Restoring cached setup {fingerprint} for session {}",
+            request.session_name
+        );
+        set_restoring(&request.session_name, true);
+        tokio::spawn(restore_cached_setup(
+            app,
+            request,
+            fingerprint,
+            start_agent_after,
+            terminal_manager,
+        ));
+        return Ok(());
+    }
+
+    let on_finish =
+        setup_finished_handler(app.clone(), &request, start_agent_after, Some(fingerprint));
+    if let Err(e) = SETUP_RUNNER
+        .start(request.clone(), terminal_manager, on_finish)
+        .await
+    {
+        manager
+            .set_session_setup_status(&request.session_name, Some(SetupStatus::Failed))
+            .map_err(|e| format!("Failed to record setup status: {e}"))?;
+        emit_setup_error(&app, &request, e.clone());
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_run_session_setup(
+    app: AppHandle,
+    session_name: String,
+    start_agent: Option<bool>,
+    force_setup: Option<bool>,
+) -> Result<String, String> {
+    let core = get_core_read().await?;
+    let manager = core.session_manager();
+    // An explicit run repeats setup even when it passed before
+    let Some(request) = setup_request(&manager, &core.repo_path, &session_name)? else {
+        return Err("No setup script is configured for this project".to_string());
+    };
+    let terminal_id = request.terminal_id.clone();
+    launch_session_setup(
+        app,
+        &manager,
+        request,
+        start_agent.unwrap_or(false),
+        force_setup.unwrap_or(false),
+    )
+    .await?;
+    Ok(terminal_id)
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_cancel_session_setup(session_name: String) -> Result<bool, String> {
+    let terminal_manager = get_terminal_manager().await?;
+    SETUP_RUNNER.cancel(&session_name, &terminal_manager).await
+}
+
+#[tauri::command]
+pub async fn schaltwerk_core_get_session_setup_status(
+    session_name: String,
+) -> Result<Option<SetupStatus>, String> {
+    let core = get_core_read().await?;
+    core.session_manager()
+        .get_session_setup_status(&session_name)
+        .map_err(|e| format!("Failed to get setup status: {e}"))
+}
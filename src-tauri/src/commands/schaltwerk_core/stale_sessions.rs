@@ -0,0 +1,186 @@
+use crate::{get_core_read, get_core_write};
+use chrono::Utc;
+use schaltwerk::domains::sessions::entity::StaleSession;
+use schaltwerk::domains::terminal::TerminalManager;
+use schaltwerk::schaltwerk_core::db_project_config::{
+    ProjectConfigMethods, StaleSessionAction, STALE_SESSION_LABEL,
+};
+use schaltwerk::schaltwerk_core::SchaltwerkCore;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use super::events;
+
+const STALE_SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Dry run of the project's stale session policy: the sessions it would label or
+/// cancel right now. Works while the policy is disabled so it can be tuned first.
+#[tauri::command]
+pub async fn schaltwerk_core_preview_stale_sessions() -> Result<Vec<StaleSession>, String> {
+    let core = get_core_read().await?;
+    let policy = core
+        .db
+        .get_project_stale_session_policy(&core.repo_path)
+        .map_err(|e| format!("Failed to get stale session policy: {e}"))?;
+    core.session_manager()
+        .find_stale_sessions(policy.idle_days, Utc::now())
+        .map_err(|e| format!("Failed to find stale sessions: {e}"))
+}
+
+/// The sessions to label and the sessions to cancel this round. Activity is only recorded
+/// while the app runs, so the first round after a project opens labels instead of cancelling:
+/// a session that only looks idle because the app was closed gets an hour's warning. Sessions
+/// holding unmerged work are only ever labelled.
+fn plan_round(
+    action: StaleSessionAction,
+    first_round: bool,
+    stale: Vec<StaleSession>,
+) -> (Vec<StaleSession>, Vec<StaleSession>) {
+    if first_round || action == StaleSessionAction::Label {
+        return (stale, Vec::new());
+    }
+    stale
+        .into_iter()
+        .partition(|session| session.has_unmerged_work)
+}
+
+/// Apply a project's stale session policy once an hour, starting an hour after the project
+/// opens so the activity tracker has caught up first.
+pub fn start_stale_session_cleanup(
+    app: AppHandle,
+    core: Arc<RwLock<SchaltwerkCore>>,
+    terminals: Arc<TerminalManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(
+            tokio::time::Instant::now() + STALE_SESSION_CHECK_INTERVAL,
+            STALE_SESSION_CHECK_INTERVAL,
+        );
+        let mut first_round = true;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = apply_stale_session_policy(&app, &core, &terminals, first_round).await {
+                log::warn!("Stale session cleanup failed: {e}");
+            }
+            first_round = false;
+        }
+    })
+}
+
+async fn apply_stale_session_policy(
+    app: &AppHandle,
+    core: &Arc<RwLock<SchaltwerkCore>>,
+    terminals: &Arc<TerminalManager>,
+    first_round: bool,
+) -> Result<(), String> {
+    let (action, stale) = {
+        let core = core.read().await;
+        let policy = core
+            .db
+            .get_project_stale_session_policy(&core.repo_path)
+            .map_err(|e| format!("Failed to get stale session policy: {e}"))?;
+        if !policy.enabled {
+            return Ok(());
+        }
+        let stale = core
+            .session_manager()
+            .find_stale_sessions(policy.idle_days, Utc::now())
+            .map_err(|e| format!("Failed to find stale sessions: {e}"))?;
+        (policy.action, stale)
+    };
+    let (label, cancel) = plan_round(action, first_round, stale);
+
+    if !label.is_empty() {
+        {
+            let core = core.write().await;
+            let manager = core.session_manager();
+            for session in &label {
+                if let Err(e) =
+                    manager.add_session_label(&session.session_name, STALE_SESSION_LABEL)
+                {
+                    log::warn!(
+                        "Failed to label stale session '{}': {e}",
+                        session.session_name
+                    );
+                }
+            }
+        }
+        events::request_sessions_refreshed(app, events::SessionsRefreshReason::SessionLifecycle);
+    }
+
+    for session in cancel {
+        log::info!(
+            "Cancelling session '{}' after {} idle days",
+            session.session_name,
+            session.idle_days
+        );
+        if let Err(e) = super::cancel_session_in_project(
+            app.clone(),
+            core.clone(),
+            Some(terminals.clone()),
+            session.session_name.clone(),
+        )
+        .await
+        {
+            log::warn!(
+                "Failed to cancel stale session '{}': {e}",
+                session.session_name
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stale(name: &str, has_unmerged_work: bool) -> StaleSession {
+        StaleSession {
+            session_name: name.to_string(),
+            last_activity: Utc::now(),
+            idle_days: 30,
+            has_unmerged_work,
+        }
+    }
+
+    fn names(sessions: &[StaleSession]) -> Vec<&str> {
+        sessions
+            .iter()
+            .map(|session| session.session_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn first_round_only_labels() {
+        let (label, cancel) = plan_round(
+            StaleSessionAction::Cancel,
+            true,
+            vec![stale("abandoned", false)],
+        );
+        assert_eq!(names(&label), vec!["abandoned"]);
+        assert!(cancel.is_empty());
+
+        let (label, cancel) = plan_round(
+            StaleSessionAction::Cancel,
+            false,
+            vec![stale("abandoned", false)],
+        );
+        assert!(label.is_empty());
+        assert_eq!(names(&cancel), vec!["abandoned"]);
+    }
+
+    #[test]
+    fn unmerged_work_is_labelled_instead_of_cancelled() {
+        let (label, cancel) = plan_round(
+            StaleSessionAction::Cancel,
+            false,
+            vec![stale("abandoned", false), stale("committed", true)],
+        );
+        assert_eq!(names(&label), vec!["committed"]);
+        assert_eq!(names(&cancel), vec!["abandoned"]);
+    }
+}
@@ -1,10 +1,12 @@
+use schaltwerk::domains::terminal::TerminalManager;
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub use schaltwerk::shared::terminal_id::{
     legacy_terminal_id_for_session_bottom, legacy_terminal_id_for_session_top,
     previous_hashed_terminal_id_for_session_bottom, previous_hashed_terminal_id_for_session_top,
-    terminal_id_for_session_bottom, terminal_id_for_session_top,
+    session_terminal_base, terminal_id_for_session_bottom, terminal_id_for_session_setup,
+    terminal_id_for_session_top,
 };
 
 pub fn ensure_cwd_access<P: AsRef<Path>>(cwd: P) -> Result<(), String> {
@@ -20,22 +22,76 @@ pub fn ensure_cwd_access<P: AsRef<Path>>(cwd: P) -> Result<(), String> {
 
 pub async fn close_session_terminals_if_any(session_name: &str) {
     if let Ok(manager) = crate::get_terminal_manager().await {
-        let mut ids: HashSet<String> = HashSet::new();
-        ids.insert(terminal_id_for_session_top(session_name));
-        ids.insert(terminal_id_for_session_bottom(session_name));
-        ids.insert(previous_hashed_terminal_id_for_session_top(session_name));
-        ids.insert(previous_hashed_terminal_id_for_session_bottom(session_name));
-        ids.insert(legacy_terminal_id_for_session_top(session_name));
-        ids.insert(legacy_terminal_id_for_session_bottom(session_name));
-
-        for id in ids {
-            if let Ok(true) = manager.terminal_exists(&id).await {
-                let _ = manager.close_terminal(id).await;
-            }
+        close_session_terminals_in(&manager, session_name).await;
+    }
+}
+
+/// Close the session's terminals that are open in `manager`.
+pub async fn close_session_terminals_in(manager: &TerminalManager, session_name: &str) {
+    let mut ids: HashSet<String> = HashSet::new();
+    ids.insert(terminal_id_for_session_top(session_name));
+    ids.insert(terminal_id_for_session_bottom(session_name));
+    ids.insert(terminal_id_for_session_setup(session_name));
+    ids.insert(previous_hashed_terminal_id_for_session_top(session_name));
+    ids.insert(previous_hashed_terminal_id_for_session_bottom(session_name));
+    ids.insert(legacy_terminal_id_for_session_top(session_name));
+    ids.insert(legacy_terminal_id_for_session_bottom(session_name));
+
+    for id in ids {
+        if let Ok(true) = manager.terminal_exists(&id).await {
+            let _ = manager.close_terminal(id).await;
         }
     }
 }
 
+/// Refuse to move the worktree of a session whose agent is running: the agent would keep
+/// working in a directory that no longer exists.
+pub async fn ensure_agent_stopped(
+    manager: Option<&TerminalManager>,
+    session_name: &str,
+) -> Result<(), String> {
+    let Some(manager) = manager else {
+        return Ok(());
+    };
+    if manager
+        .terminal_exists(&terminal_id_for_session_top(session_name))
+        .await?
+    {
+        return Err(format!(
+            "Stop the agent of session '{session_name}' before moving its worktree"
+        ));
+    }
+    Ok(())
+}
+
+/// Rename a session with `rename`, which returns the renamed session with its old and new
+/// worktree, then carry the session's live terminals over to the ids of `new_name`. The
+/// terminals are left alone when the rename fails, e.g. because the name is invalid or taken.
+/// A session whose agent is running is not renamed.
+pub async fn rename_session_with_terminals<T>(
+    manager: Option<&TerminalManager>,
+    old_name: &str,
+    new_name: &str,
+    rename: impl FnOnce() -> Result<(T, PathBuf, PathBuf), String>,
+) -> Result<T, String> {
+    if old_name != new_name {
+        ensure_agent_stopped(manager, old_name).await?;
+    }
+    let (renamed, old_worktree, new_worktree) = rename()?;
+    if let Some(manager) = manager.filter(|_| old_name != new_name) {
+        manager
+            .rename_session_terminals(
+                &session_terminal_base(old_name),
+                &session_terminal_base(new_name),
+                new_name,
+                &old_worktree,
+                &new_worktree,
+            )
+            .await;
+    }
+    Ok(renamed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +115,60 @@ mod tests {
         assert_ne!(id_a, id_b);
     }
 
+    #[tokio::test]
+    async fn failed_rename_keeps_session_terminals() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = TerminalManager::new();
+        let old_id = terminal_id_for_session_bottom("rename-src");
+        let new_id = terminal_id_for_session_bottom("rename-dst");
+        manager
+            .create_terminal(old_id.clone(), tmp.path().display().to_string())
+            .await
+            .unwrap();
+
+        let result: Result<(), String> =
+            rename_session_with_terminals(Some(&manager), "rename-src", "bad name", || {
+                Err("Invalid session name".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(manager.terminal_exists(&old_id).await.unwrap());
+
+        rename_session_with_terminals(Some(&manager), "rename-src", "rename-dst", || {
+            Ok(((), tmp.path().to_path_buf(), tmp.path().to_path_buf()))
+        })
+        .await
+        .unwrap();
+        assert!(!manager.terminal_exists(&old_id).await.unwrap());
+        assert!(manager.terminal_exists(&new_id).await.unwrap());
+
+        manager.close_terminal(new_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn session_with_running_agent_is_not_renamed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = TerminalManager::new();
+        let agent_id = terminal_id_for_session_top("busy-src");
+        manager
+            .create_terminal(agent_id.clone(), tmp.path().display().to_string())
+            .await
+            .unwrap();
+
+        let mut renamed = false;
+        let result = rename_session_with_terminals(Some(&manager), "busy-src", "busy-dst", || {
+            renamed = true;
+            Ok(((), tmp.path().to_path_buf(), tmp.path().to_path_buf()))
+        })
+        .await;
+
+        assert!(result.unwrap_err().contains("Stop the agent"));
+        assert!(!renamed);
+        assert!(manager.terminal_exists(&agent_id).await.unwrap());
+
+        manager.close_terminal(agent_id).await.unwrap();
+    }
+
     #[test]
     fn test_ensure_cwd_access_ok_and_notfound() {
         let tmp = tempfile::tempdir().unwrap();
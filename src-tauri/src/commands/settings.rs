@@ -1,22 +1,31 @@
 use std::collections::HashMap;
 
 use crate::{get_core_read, get_core_write, PROJECT_MANAGER, SETTINGS_MANAGER};
+use schaltwerk::domains::agents::manifest::AgentManifest;
+use schaltwerk::domains::sessions::entity::BranchPrefixMigration;
+use schaltwerk::domains::settings::fonts::{
+    has_ligatures, validate_font, FontValidation, InstalledFont,
+};
+use schaltwerk::domains::settings::shortcuts::{validate_shortcuts, ShortcutValidation};
+use schaltwerk::domains::settings::validation::validate_settings_overrides;
 use schaltwerk::domains::settings::{
-    DiffViewPreferences, McpServerConfig, SessionPreferences, TerminalSettings, TerminalUIPreferences,
+    DiffViewPreferences, EffectiveSettings, EnvVarSet, McpServerConfig, SessionPreferences,
+    SettingsOverrides, TerminalSettings, TerminalTheme, TerminalUIPreferences,
+};
+use schaltwerk::domains::workspace::dev_environment::{
+    detect_dev_environments, DetectedDevEnvironment,
 };
+use schaltwerk::infrastructure::events::outbound::configure_outbound_webhooks;
+use schaltwerk::infrastructure::secrets::mask_secrets;
+use schaltwerk::project_manager::container_session_resolver;
 use schaltwerk::schaltwerk_core::db_app_config::AppConfigMethods;
 use schaltwerk::schaltwerk_core::db_project_config::{
-    default_action_buttons, HeaderActionConfig, ProjectConfigMethods, ProjectMergePreferences,
-    ProjectSessionsSettings, RunScript,
+    default_action_buttons, project_worktrees_root, DevEnvironmentKind, HeaderActionConfig,
+    ProjectAgentDefaults, ProjectCodexSettings, ProjectConfigMethods, ProjectContainerConfig,
+    ProjectDependencyCacheConfig, ProjectMergePreferences, ProjectOutboundWebhookConfig,
+    ProjectSessionsSettings, ProjectSetupScripts, ProjectStaleSessionPolicy, RunScript,
 };
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
-pub struct InstalledFont {
-    pub family: String,
-    pub monospace: bool,
-}
-
 fn normalize_and_sort_fonts(mut entries: Vec<InstalledFont>) -> Vec<InstalledFont> {
     use std::collections::BTreeMap;
     let mut map: BTreeMap<String, (String, bool)> = BTreeMap::new();
@@ -46,10 +55,7 @@ fn normalize_and_sort_fonts(mut entries: Vec<InstalledFont>) -> Vec<InstalledFon
     list
 }
 
-#[tauri::command]
-pub async fn list_installed_fonts() -> Result<Vec<InstalledFont>, String> {
-    let mut db = fontdb::Database::new();
-    db.load_system_fonts();
+fn installed_fonts(db: &fontdb::Database) -> Vec<InstalledFont> {
     let mut entries: Vec<InstalledFont> = Vec::new();
     for info in db.faces() {
         for fam in &info.families {
@@ -57,7 +63,8 @@ pub async fn list_installed_fonts() -> Result<Vec<InstalledFont>, String> {
                 s if !s.is_empty() => s.to_string(),
                 _ => continue,
             };
-            let inferred = name.to_lowercase().contains("mono")
+            let inferred = info.monospaced
+                || name.to_lowercase().contains("mono")
                 || name.to_lowercase().contains("code")
                 || name.to_lowercase().contains("console")
                 || name.to_lowercase().contains("monospace");
@@ -67,7 +74,66 @@ pub async fn list_installed_fonts() -> Result<Vec<InstalledFont>, String> {
             });
         }
     }
-    Ok(normalize_and_sort_fonts(entries))
+    normalize_and_sort_fonts(entries)
+}
+
+#[tauri::command]
+pub async fn list_installed_fonts() -> Result<Vec<InstalledFont>, String> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    Ok(installed_fonts(&db))
+}
+
+/// Whether `family` is installed, monospaced and draws ligatures, with the closest
+/// installed monospace font to fall back to.
+#[tauri::command]
+pub async fn validate_terminal_font(family: String) -> Result<FontValidation, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        let installed = installed_fonts(&db);
+        validate_font(&family, &installed, |matched| {
+            let query = fontdb::Query {
+                families: &[fontdb::Family::Name(matched)],
+                ..Default::default()
+            };
+            db.query(&query)
+                .and_then(|id| db.with_face_data(id, has_ligatures))
+                .unwrap_or(false)
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to validate font: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_terminal_themes() -> Result<Vec<TerminalTheme>, String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let manager = settings_manager.lock().await;
+    Ok(manager.get_terminal_themes())
+}
+
+#[tauri::command]
+pub async fn set_terminal_theme(theme: TerminalTheme) -> Result<(), String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let mut manager = settings_manager.lock().await;
+    manager.set_terminal_theme(theme)
+}
+
+#[tauri::command]
+pub async fn delete_terminal_theme(name: String) -> Result<(), String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let mut manager = settings_manager.lock().await;
+    manager.delete_terminal_theme(&name)
 }
 
 #[tauri::command]
@@ -219,6 +285,12 @@ pub async fn set_project_default_base_branch(branch: Option<String>) -> Result<(
 pub struct ProjectSettings {
     pub setup_script: String,
     pub branch_prefix: String,
+    /// Run in a session worktree before the merge queue merges it; empty to skip
+    #[serde(default)]
+    pub test_script: String,
+    /// Minutes the test script may run before it is killed; `None` for the default
+    #[serde(default)]
+    pub test_timeout_minutes: Option<u32>,
 }
 
 #[tauri::command]
@@ -242,14 +314,27 @@ pub async fn get_project_settings() -> Result<ProjectSettings, String> {
         .get_project_branch_prefix(&project.path)
         .map_err(|e| format!("Failed to get project branch prefix: {e}"))?;
 
+    let test_script = db
+        .get_project_test_script(&project.path)
+        .map_err(|e| format!("Failed to get project test script: {e}"))?
+        .unwrap_or_default();
+
+    let test_timeout_minutes = db
+        .get_project_test_timeout_minutes(&project.path)
+        .map_err(|e| format!("Failed to get project test timeout: {e}"))?;
+
     Ok(ProjectSettings {
         setup_script,
         branch_prefix,
+        test_script,
+        test_timeout_minutes,
     })
 }
 
 #[tauri::command]
-pub async fn set_project_settings(settings: ProjectSettings) -> Result<(), String> {
+pub async fn set_project_settings(
+    settings: ProjectSettings,
+) -> Result<BranchPrefixMigration, String> {
     let project = PROJECT_MANAGER
         .get()
         .ok_or_else(|| "Project manager not initialized".to_string())?
@@ -262,10 +347,13 @@ pub async fn set_project_settings(settings: ProjectSettings) -> Result<(), Strin
 
     db.set_project_setup_script(&project.path, &settings.setup_script)
         .map_err(|e| format!("Failed to set project setup script: {e}"))?;
-    db.set_project_branch_prefix(&project.path, &settings.branch_prefix)
-        .map_err(|e| format!("Failed to set project branch prefix: {e}"))?;
-
-    Ok(())
+    db.set_project_test_script(&project.path, &settings.test_script)
+        .map_err(|e| format!("Failed to set project test script: {e}"))?;
+    db.set_project_test_timeout_minutes(&project.path, settings.test_timeout_minutes)
+        .map_err(|e| format!("Failed to set project test timeout: {e}"))?;
+    core.session_manager()
+        .set_branch_prefix(&settings.branch_prefix)
+        .map_err(|e| format!("Failed to set project branch prefix: {e}"))
 }
 
 #[tauri::command]
@@ -336,6 +424,131 @@ pub async fn set_project_environment_variables(
         .map_err(|e| format!("Failed to set project environment variables: {e}"))
 }
 
+/// The settings `agent_type` launches with in the project, or in `session_name`, and the
+/// layer each value comes from.
+#[tauri::command]
+pub async fn get_effective_settings(
+    agent_type: String,
+    session_name: Option<String>,
+) -> Result<EffectiveSettings, String> {
+    if AgentManifest::get(&agent_type).is_none() {
+        return Err(format!("Unknown agent type: {agent_type}"));
+    }
+
+    let (project, session) = {
+        let core = get_core_read().await?;
+        core.session_manager()
+            .settings_layers(session_name.as_deref())
+            .map_err(|e| format!("Failed to load settings layers: {e}"))?
+    };
+
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+    let manager = settings_manager.lock().await;
+    let mut effective = manager.resolve_effective_settings(&agent_type, &project, session.as_ref());
+    // Secret env set values are shown masked, like everywhere else outside the agent
+    for entry in effective.env_vars.values_mut() {
+        entry.value = mask_secrets(&entry.value).into_owned();
+    }
+    Ok(effective)
+}
+
+#[tauri::command]
+pub async fn get_project_settings_overrides() -> Result<SettingsOverrides, String> {
+    let core = get_core_read().await?;
+    core.db
+        .get_project_settings_overrides(&core.repo_path)
+        .map_err(|e| format!("Failed to get project settings overrides: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_project_settings_overrides(overrides: SettingsOverrides) -> Result<(), String> {
+    validate_settings_overrides(&overrides).map_err(|e| e.to_string())?;
+    let core = get_core_write().await?;
+    core.db
+        .set_project_settings_overrides(&core.repo_path, &overrides)
+        .map_err(|e| format!("Failed to set project settings overrides: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_env_sets() -> Result<Vec<EnvVarSet>, String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let manager = settings_manager.lock().await;
+    Ok(manager.get_env_sets())
+}
+
+#[tauri::command]
+pub async fn set_env_set(env_set: EnvVarSet) -> Result<(), String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let mut manager = settings_manager.lock().await;
+    manager.set_env_set(env_set)
+}
+
+#[tauri::command]
+pub async fn delete_env_set(name: String) -> Result<(), String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let mut manager = settings_manager.lock().await;
+    manager.delete_env_set(&name)
+}
+
+#[tauri::command]
+pub async fn get_project_env_sets() -> Result<Vec<String>, String> {
+    let core = get_core_read().await?;
+    core.db
+        .get_project_env_sets(&core.repo_path)
+        .map_err(|e| format!("Failed to get project env sets: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_project_env_sets(env_sets: Vec<String>) -> Result<(), String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+    let known = settings_manager.lock().await.get_env_sets();
+    if let Some(unknown) = env_sets
+        .iter()
+        .find(|name| !known.iter().any(|set| &set.name == *name))
+    {
+        return Err(format!("Env set '{unknown}' does not exist"));
+    }
+
+    let core = get_core_write().await?;
+    core.db
+        .set_project_env_sets(&core.repo_path, &env_sets)
+        .map_err(|e| format!("Failed to set project env sets: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_session_settings_overrides(
+    session_name: String,
+) -> Result<SettingsOverrides, String> {
+    let core = get_core_read().await?;
+    core.session_manager()
+        .get_session_settings_overrides(&session_name)
+        .map_err(|e| format!("Failed to get settings overrides of session {session_name}: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_session_settings_overrides(
+    session_name: String,
+    overrides: SettingsOverrides,
+) -> Result<(), String> {
+    let core = get_core_write().await?;
+    core.session_manager()
+        .set_session_settings_overrides(&session_name, &overrides)
+        .map_err(|e| format!("Failed to set settings overrides of session {session_name}: {e}"))
+}
+
 #[tauri::command]
 pub async fn get_project_merge_preferences() -> Result<ProjectMergePreferences, String> {
     let project = PROJECT_MANAGER
@@ -387,14 +600,7 @@ pub async fn set_terminal_settings(terminal: TerminalSettings) -> Result<(), Str
         .ok_or_else(|| "Settings manager not initialized".to_string())?;
 
     let mut manager = settings_manager.lock().await;
-    // Persist first
-    manager.set_terminal_settings(terminal.clone()).map(|_| {
-        // Propagate new shell to terminal domain for immediate effect
-        let shell = terminal
-            .shell
-            .unwrap_or_else(|| std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string()));
-        schaltwerk::domains::terminal::put_terminal_shell_override(shell, terminal.shell_args);
-    })
+    manager.set_terminal_settings(terminal)
 }
 
 #[tauri::command]
@@ -497,6 +703,13 @@ pub async fn set_keyboard_shortcuts(shortcuts: HashMap<String, Vec<String>>) ->
     manager.set_keyboard_shortcuts(shortcuts)
 }
 
+/// Normalized bindings together with the ones that are invalid or conflict, so the
+/// shortcut editor can point at them before saving.
+#[tauri::command]
+pub fn validate_keyboard_shortcuts(shortcuts: HashMap<String, Vec<String>>) -> ShortcutValidation {
+    validate_shortcuts(&shortcuts)
+}
+
 #[tauri::command]
 pub async fn get_project_action_buttons() -> Result<Vec<HeaderActionConfig>, String> {
     let project = PROJECT_MANAGER
@@ -598,6 +811,28 @@ pub async fn set_tutorial_completed(completed: bool) -> Result<(), String> {
         .map_err(|e| format!("Failed to set tutorial completion status: {e}"))
 }
 
+#[tauri::command]
+pub async fn get_json_logs() -> Result<bool, String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let manager = settings_manager.lock().await;
+    Ok(manager.get_json_logs())
+}
+
+#[tauri::command]
+pub async fn set_json_logs(enabled: bool) -> Result<(), String> {
+    let settings_manager = SETTINGS_MANAGER
+        .get()
+        .ok_or_else(|| "Settings manager not initialized".to_string())?;
+
+    let mut manager = settings_manager.lock().await;
+    manager
+        .set_json_logs(enabled)
+        .map_err(|e| format!("Failed to set log format: {e}"))
+}
+
 #[tauri::command]
 pub async fn get_project_run_script() -> Result<Option<RunScript>, String> {
     let project = PROJECT_MANAGER
@@ -630,6 +865,373 @@ pub async fn set_project_run_script(run_script: RunScript) -> Result<(), String>
         .map_err(|e| format!("Failed to set project run script: {e}"))
 }
 
+#[tauri::command]
+pub async fn get_project_codex_settings() -> Result<ProjectCodexSettings, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_codex_settings(&project.path)
+        .map_err(|e| format!("Failed to get project Codex settings: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_project_codex_settings(settings: ProjectCodexSettings) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.write().await;
+    let db = core.database();
+
+    db.set_project_codex_settings(&project.path, &settings)
+        .map_err(|e| format!("Failed to set project Codex settings: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_project_agent_defaults() -> Result<ProjectAgentDefaults, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_agent_defaults(&project.path)
+        .map_err(|e| format!("Failed to get project agent defaults: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_project_agent_defaults(defaults: ProjectAgentDefaults) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.write().await;
+    let db = core.database();
+
+    db.set_project_agent_defaults(&project.path, &defaults)
+        .map_err(|e| format!("Failed to set project agent defaults: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_project_setup_scripts() -> Result<ProjectSetupScripts, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_setup_scripts(&project.path)
+        .map_err(|e| format!("Failed to get project setup scripts: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_project_setup_scripts(scripts: ProjectSetupScripts) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.write().await;
+    let db = core.database();
+
+    db.set_project_setup_scripts(&project.path, &scripts)
+        .map_err(|e| format!("Failed to set project setup scripts: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_project_container_config() -> Result<Option<ProjectContainerConfig>, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_container_config(&project.path)
+        .map_err(|e| format!("Failed to get project container config: {e}"))
+}
+
+/// Store the project's container backend; applies to terminals created afterwards.
+#[tauri::command]
+pub async fn set_project_container_config(config: ProjectContainerConfig) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let sessions = {
+        let core = project.schaltwerk_core.write().await;
+        let db = core.database();
+
+        db.set_project_container_config(&project.path, &config)
+            .map_err(|e| format!("Failed to set project container config: {e}"))?;
+        container_session_resolver(db, &project.path)
+    };
+
+    project
+        .terminal_manager
+        .configure_containers(Some(config), sessions);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_project_dependency_cache_config() -> Result<ProjectDependencyCacheConfig, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_dependency_cache_config(&project.path)
+        .map_err(|e| format!("Failed to get project dependency cache config: {e}"))
+}
+
+/// Store which package caches the worktrees share; applies to terminals created afterwards.
+#[tauri::command]
+pub async fn set_project_dependency_cache_config(
+    config: ProjectDependencyCacheConfig,
+) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let worktrees_root = {
+        let core = project.schaltwerk_core.write().await;
+        let db = core.database();
+
+        db.set_project_dependency_cache_config(&project.path, &config)
+            .map_err(|e| format!("Failed to set project dependency cache config: {e}"))?;
+        project_worktrees_root(db, &project.path)
+    };
+
+    project
+        .terminal_manager
+        .configure_dependency_cache(&project.path, &worktrees_root, config);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_project_worktree_root() -> Result<Option<String>, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_worktree_root(&project.path)
+        .map_err(|e| format!("Failed to get project worktree root: {e}"))
+}
+
+/// Store where new session worktrees are created; `None` restores `.schaltwerk/worktrees`.
+/// Existing worktrees stay put until they are relocated.
+#[tauri::command]
+pub async fn set_project_worktree_root(worktree_root: Option<String>) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let (worktrees_root, dependency_cache_config) = {
+        let core = project.schaltwerk_core.write().await;
+        let db = core.database();
+
+        db.set_project_worktree_root(&project.path, worktree_root.as_deref())
+            .map_err(|e| format!("Failed to set project worktree root: {e}"))?;
+        (
+            project_worktrees_root(db, &project.path),
+            db.get_project_dependency_cache_config(&project.path)
+                .map_err(|e| format!("Failed to get project dependency cache config: {e}"))?,
+        )
+    };
+
+    project.terminal_manager.set_worktrees_root(&worktrees_root);
+    project.terminal_manager.configure_dependency_cache(
+        &project.path,
+        &worktrees_root,
+        dependency_cache_config,
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_project_stale_session_policy() -> Result<ProjectStaleSessionPolicy, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_stale_session_policy(&project.path)
+        .map_err(|e| format!("Failed to get project stale session policy: {e}"))
+}
+
+/// Store when idle sessions count as stale and whether they get labelled or cancelled;
+/// the background check picks it up on its next hourly run.
+#[tauri::command]
+pub async fn set_project_stale_session_policy(
+    policy: ProjectStaleSessionPolicy,
+) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.write().await;
+    let db = core.database();
+
+    db.set_project_stale_session_policy(&project.path, &policy)
+        .map_err(|e| format!("Failed to set project stale session policy: {e}"))
+}
+
+#[tauri::command]
+pub async fn get_project_outbound_webhook_config() -> Result<ProjectOutboundWebhookConfig, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_outbound_webhook_config(&project.path)
+        .map_err(|e| format!("Failed to get project outbound webhook config: {e}"))
+}
+
+/// Store which events are POSTed to which URLs; takes effect for the next event.
+#[tauri::command]
+pub async fn set_project_outbound_webhook_config(
+    config: ProjectOutboundWebhookConfig,
+) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let stored = {
+        let core = project.schaltwerk_core.write().await;
+        let db = core.database();
+
+        db.set_project_outbound_webhook_config(&project.path, &config)
+            .map_err(|e| format!("Failed to set project outbound webhook config: {e}"))?;
+        db.get_project_outbound_webhook_config(&project.path)
+            .map_err(|e| format!("Failed to get project outbound webhook config: {e}"))?
+    };
+
+    configure_outbound_webhooks(&project.path, stored);
+    Ok(())
+}
+
+/// Devcontainer or Nix configurations found in the current project.
+#[tauri::command]
+pub async fn detect_project_dev_environments() -> Result<Vec<DetectedDevEnvironment>, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    Ok(detect_dev_environments(&project.path))
+}
+
+#[tauri::command]
+pub async fn get_project_dev_environment() -> Result<Option<DevEnvironmentKind>, String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    let core = project.schaltwerk_core.read().await;
+    let db = core.database();
+
+    db.get_project_dev_environment(&project.path)
+        .map_err(|e| format!("Failed to get project dev environment: {e}"))
+}
+
+/// Use a detected dev environment as the session setup step, or clear it with `None`.
+#[tauri::command]
+pub async fn set_project_dev_environment(
+    environment: Option<DevEnvironmentKind>,
+) -> Result<(), String> {
+    let project = PROJECT_MANAGER
+        .get()
+        .ok_or_else(|| "Project manager not initialized".to_string())?
+        .current_project()
+        .await
+        .map_err(|e| format!("Failed to get current project: {e}"))?;
+
+    if let Some(kind) = environment {
+        if !detect_dev_environments(&project.path)
+            .iter()
+            .any(|detected| detected.kind == kind)
+        {
+            return Err(format!(
+                "No {kind:?} configuration found in {}",
+                project.path.display()
+            ));
+        }
+    }
+
+    let core = project.schaltwerk_core.write().await;
+    let db = core.database();
+
+    db.set_project_dev_environment(&project.path, environment)
+        .map_err(|e| format!("Failed to set project dev environment: {e}"))
+}
+
 #[tauri::command]
 pub async fn get_amp_mcp_servers() -> Result<HashMap<String, McpServerConfig>, String> {
     let settings_manager = SETTINGS_MANAGER
@@ -660,6 +1262,8 @@ mod tests {
         let settings = ProjectSettings {
             setup_script: "#!/bin/bash\necho test".to_string(),
             branch_prefix: "team".to_string(),
+            test_script: String::new(),
+            test_timeout_minutes: None,
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -1024,6 +1628,8 @@ mod tests {
         let settings = ProjectSettings {
             setup_script: "#!/bin/bash\necho test".to_string(),
             branch_prefix: "team".to_string(),
+            test_script: String::new(),
+            test_timeout_minutes: None,
         };
         let result = set_project_settings(settings).await;
         assert!(result.is_err());
@@ -1049,6 +1655,9 @@ mod tests {
     async fn test_set_project_merge_preferences_uninitialized_manager() {
         let preferences = ProjectMergePreferences {
             auto_cancel_after_merge: true,
+            sign_commits: true,
+            run_git_hooks: true,
+            protected_paths: Vec::new(),
         };
         let result = set_project_merge_preferences(preferences).await;
         assert!(result.is_err());
@@ -1175,6 +1784,8 @@ mod tests {
         let settings = ProjectSettings {
             setup_script: setup_script.to_string(),
             branch_prefix: "team".to_string(),
+            test_script: String::new(),
+            test_timeout_minutes: None,
         };
 
         assert_eq!(settings.setup_script, setup_script);
@@ -1217,6 +1828,8 @@ mod tests {
             setup_script: "#!/bin/bash\necho 'test script'\nexport PATH=/usr/local/bin:$PATH"
                 .to_string(),
             branch_prefix: "team".to_string(),
+            test_script: String::new(),
+            test_timeout_minutes: None,
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -1291,6 +1904,8 @@ mod tests {
         let settings = ProjectSettings {
             setup_script: "#!/bin/bash\necho 'special chars: @#$%^&*()'\nexport PATH=/usr/local/bin:$PATH\ncd /some/path".to_string(),
             branch_prefix: "team".to_string(),
+            test_script: String::new(),
+            test_timeout_minutes: None,
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -1319,6 +1934,8 @@ mod tests {
         let settings = ProjectSettings {
             setup_script: String::new(),
             branch_prefix: "schaltwerk".to_string(),
+            test_script: String::new(),
+            test_timeout_minutes: None,
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -1,9 +1,29 @@
+use crate::{get_core_read, get_file_watcher_manager, get_terminal_manager};
+use schaltwerk::domains::agents::status::SessionAgentStatePayload;
+use schaltwerk::domains::terminal::buffer_search::{BufferSearchQuery, BufferSearchResult};
+use schaltwerk::domains::terminal::control_sequences::CommandRecord;
+use schaltwerk::domains::terminal::idle_detection::SessionIdlePayload;
+use schaltwerk::domains::terminal::paste::{is_multiline, PasteOutcome};
+use schaltwerk::domains::terminal::port_detection::DetectedPort;
+use schaltwerk::domains::terminal::preview_proxy::{
+    PreviewPortResolver, PreviewProxy, DEFAULT_PREVIEW_PROXY_PORT,
+};
+use schaltwerk::domains::terminal::resource_usage::TerminalResourceUsage;
+use schaltwerk::domains::terminal::run_status::{run_terminal_id, run_terminal_session_name};
+use schaltwerk::domains::terminal::run_watch::RunWatchManager;
+use schaltwerk::infrastructure::secrets::mask_secrets;
+use schaltwerk::schaltwerk_core::db_project_config::ProjectConfigMethods;
 use schaltwerk::services::terminals::{
     CreateRunTerminalRequest, CreateTerminalRequest, CreateTerminalWithSizeRequest,
 };
 use schaltwerk::services::ServiceHandles;
 use serde::Serialize;
+use std::sync::{Arc, LazyLock};
 use tauri::State;
+use tokio::sync::Mutex;
+
+static RUN_WATCH_MANAGER: LazyLock<RunWatchManager> = LazyLock::new(RunWatchManager::new);
+static PREVIEW_PROXY: LazyLock<Mutex<Option<PreviewProxy>>> = LazyLock::new(|| Mutex::new(None));
 
 #[tauri::command]
 pub async fn create_terminal(
@@ -83,10 +103,43 @@ pub async fn paste_and_submit_terminal(
     id: String,
     data: String,
     use_bracketed_paste: Option<bool>,
-) -> Result<(), String> {
+    require_confirmation: Option<bool>,
+) -> Result<PasteOutcome, String> {
+    let use_bracketed_paste = use_bracketed_paste.unwrap_or(false);
+    if require_confirmation.unwrap_or(false) && is_multiline(data.as_bytes()) {
+        return Ok(get_terminal_manager()
+            .await?
+            .hold_paste_for_confirmation(id, data.into_bytes(), use_bracketed_paste)
+            .await);
+    }
+
     services
         .terminals
-        .paste_and_submit_terminal(id, data.into_bytes(), use_bracketed_paste.unwrap_or(false))
+        .paste_and_submit_terminal(id, data.into_bytes(), use_bracketed_paste)
+        .await?;
+    Ok(PasteOutcome::Submitted)
+}
+
+/// Send the same input to several terminals at once, e.g. one prompt to parallel agent sessions.
+/// Nothing is written when any of the terminals does not exist.
+#[tauri::command]
+pub async fn broadcast_to_terminals(
+    ids: Vec<String>,
+    data: String,
+    submit: Option<bool>,
+) -> Result<(), String> {
+    get_terminal_manager()
+        .await?
+        .broadcast_to_terminals(ids, data.into_bytes(), submit.unwrap_or(true))
+        .await
+}
+
+/// Accept or reject a paste held for confirmation. Returns true when it was submitted.
+#[tauri::command]
+pub async fn confirm_terminal_paste(paste_id: String, accept: bool) -> Result<bool, String> {
+    get_terminal_manager()
+        .await?
+        .confirm_paste(&paste_id, accept)
         .await
 }
 
@@ -102,6 +155,7 @@ pub async fn resize_terminal(
 
 #[tauri::command]
 pub async fn close_terminal(services: State<'_, ServiceHandles>, id: String) -> Result<(), String> {
+    RUN_WATCH_MANAGER.stop(&id).await;
     services.terminals.close_terminal(id).await
 }
 
@@ -126,6 +180,7 @@ pub async fn terminals_exist_bulk(
 pub struct TerminalBufferResponse {
     pub seq: u64,
     pub start_seq: u64,
+    pub from_seq: u64,
     pub data: String,
 }
 
@@ -136,14 +191,24 @@ pub async fn get_terminal_buffer(
     from_seq: Option<u64>,
 ) -> Result<TerminalBufferResponse, String> {
     let snapshot = services.terminals.get_terminal_buffer(id, from_seq).await?;
-    let data = String::from_utf8_lossy(&snapshot.data).to_string();
+    let data = mask_secrets(&String::from_utf8_lossy(&snapshot.data)).into_owned();
     Ok(TerminalBufferResponse {
         seq: snapshot.seq,
         start_seq: snapshot.start_seq,
+        from_seq: snapshot.from_seq,
         data,
     })
 }
 
+#[tauri::command]
+pub async fn search_terminal_buffer(
+    services: State<'_, ServiceHandles>,
+    id: String,
+    query: BufferSearchQuery,
+) -> Result<BufferSearchResult, String> {
+    services.terminals.search_terminal_buffer(id, query).await
+}
+
 #[tauri::command]
 pub async fn get_terminal_activity_status(
     services: State<'_, ServiceHandles>,
@@ -195,3 +260,193 @@ pub async fn resume_session_terminals(
         .resume_session_terminals(project_id, session_id)
         .await
 }
+
+/// Restart the project run script in `terminal_id` whenever its worktree changes.
+/// Run terminal ids are `run-terminal-<session>` or `run-terminal-orchestrator`.
+#[tauri::command]
+pub async fn start_run_watch(terminal_id: String) -> Result<(), String> {
+    let session_name = run_terminal_session_name(&terminal_id)
+        .ok_or_else(|| format!("Terminal {terminal_id} is not a run terminal"))?
+        .to_string();
+
+    let run_script = {
+        let core = get_core_read().await?;
+        core.db
+            .get_project_run_script(&core.repo_path)
+            .map_err(|e| format!("Failed to get project run script: {e}"))?
+    }
+    .filter(|script| !script.command.trim().is_empty())
+    .ok_or_else(|| "No run script configured".to_string())?;
+
+    crate::start_file_watcher(session_name.clone()).await?;
+    let changes = get_file_watcher_manager().await?.subscribe_changes();
+    let terminal_manager = get_terminal_manager().await?;
+
+    RUN_WATCH_MANAGER
+        .start(
+            terminal_id,
+            session_name,
+            run_script.command,
+            changes,
+            terminal_manager,
+        )
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_run_watch(terminal_id: String) -> Result<bool, String> {
+    Ok(RUN_WATCH_MANAGER.stop(&terminal_id).await)
+}
+
+#[tauri::command]
+pub async fn is_run_watch_active(terminal_id: String) -> Result<bool, String> {
+    Ok(RUN_WATCH_MANAGER.is_watching(&terminal_id).await)
+}
+
+#[tauri::command]
+pub async fn get_terminal_resource_usage() -> Result<Vec<TerminalResourceUsage>, String> {
+    get_terminal_manager()
+        .await?
+        .get_terminal_resource_usage()
+        .await
+}
+
+/// Resume a terminal paused by the output throttle. Returns the number of bytes that were skipped.
+#[tauri::command]
+pub async fn resume_terminal_output(id: String) -> Result<Option<u64>, String> {
+    Ok(get_terminal_manager()
+        .await?
+        .resume_terminal_output(&id)
+        .await)
+}
+
+/// Idle state of every session agent terminal, so the sidebar can seed its indicators.
+#[tauri::command]
+pub async fn get_session_idle_states() -> Result<Vec<SessionIdlePayload>, String> {
+    Ok(get_terminal_manager()
+        .await?
+        .get_session_idle_states()
+        .await)
+}
+
+/// What each session's agent is doing, for the sidebar's initial state.
+#[tauri::command]
+pub async fn get_session_agent_states() -> Result<Vec<SessionAgentStatePayload>, String> {
+    Ok(get_terminal_manager()
+        .await?
+        .get_session_agent_states()
+        .await)
+}
+
+/// Commands run in a terminal, delimited by the shell's OSC 133 prompt markers.
+#[tauri::command]
+pub async fn get_terminal_command_history(id: String) -> Result<Vec<CommandRecord>, String> {
+    Ok(get_terminal_manager().await?.get_command_history(&id).await)
+}
+
+/// Dev-server URLs announced in the run terminal of `session_name` (orchestrator when omitted).
+#[tauri::command]
+pub async fn get_detected_ports(session_name: Option<String>) -> Result<Vec<DetectedPort>, String> {
+    let terminal_manager = get_terminal_manager().await?;
+    Ok(terminal_manager
+        .get_detected_ports(&run_terminal_id(session_name.as_deref()))
+        .await)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewUrl {
+    pub session_name: Option<String>,
+    pub url: String,
+    pub target_port: u16,
+}
+
+/// Session whose preview host label is `label`; `Some(None)` is the orchestrator.
+async fn resolve_preview_session(label: &str) -> Option<Option<String>> {
+    if label.is_empty() {
+        return Some(None);
+    }
+    let core = get_core_read().await.ok()?;
+    let sessions = core.session_manager().list_sessions().ok()?;
+    sessions
+        .into_iter()
+        .find(|session| session.name.to_lowercase() == label)
+        .map(|session| Some(session.name))
+}
+
+async fn preview_target_port(session_name: Option<&str>) -> Option<u16> {
+    let terminal_manager = get_terminal_manager().await.ok()?;
+    terminal_manager
+        .get_detected_ports(&run_terminal_id(session_name))
+        .await
+        .first()
+        .map(|detected| detected.port)
+}
+
+/// Start the local preview proxy serving `http://<session>.localhost:<port>`. Returns the bound port.
+#[tauri::command]
+pub async fn start_preview_proxy(port: Option<u16>) -> Result<u16, String> {
+    let mut proxy = PREVIEW_PROXY.lock().await;
+    if let Some(existing) = proxy.as_ref() {
+        if port.is_none_or(|requested| requested == existing.port()) {
+            return Ok(existing.port());
+        }
+    }
+    // Release the current port before binding a new one
+    proxy.take();
+
+    let resolver: PreviewPortResolver = Arc::new(|label: String| {
+        Box::pin(async move {
+            let session_name = resolve_preview_session(&label).await?;
+            preview_target_port(session_name.as_deref()).await
+        })
+    });
+    let started = PreviewProxy::start(port.unwrap_or(DEFAULT_PREVIEW_PROXY_PORT), resolver).await?;
+    let bound = started.port();
+    *proxy = Some(started);
+    Ok(bound)
+}
+
+#[tauri::command]
+pub async fn stop_preview_proxy() -> Result<bool, String> {
+    Ok(PREVIEW_PROXY.lock().await.take().is_some())
+}
+
+/// Preview URLs for every session (and the orchestrator) with a detected dev server.
+/// Empty while the preview proxy is not running.
+#[tauri::command]
+pub async fn get_preview_urls() -> Result<Vec<PreviewUrl>, String> {
+    let proxy = PREVIEW_PROXY.lock().await;
+    let Some(proxy) = proxy.as_ref() else {
+        return Ok(Vec::new());
+    };
+
+    let session_names = {
+        let core = get_core_read().await?;
+        core.session_manager()
+            .list_sessions()
+            .map_err(|e| format!("Failed to list sessions: {e}"))?
+            .into_iter()
+            .map(|session| session.name)
+            .collect::<Vec<_>>()
+    };
+
+    let mut urls = Vec::new();
+    if let Some(target_port) = preview_target_port(None).await {
+        urls.push(PreviewUrl {
+            session_name: None,
+            url: proxy.preview_url(None),
+            target_port,
+        });
+    }
+    for name in session_names {
+        if let Some(target_port) = preview_target_port(Some(&name)).await {
+            urls.push(PreviewUrl {
+                url: proxy.preview_url(Some(&name)),
+                session_name: Some(name),
+                target_port,
+            });
+        }
+    }
+    Ok(urls)
+}
@@ -55,6 +55,36 @@ pub async fn open_in_vscode(worktree_path: String) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+pub async fn open_in_browser(url: String) -> Result<(), String> {
+    let trimmed = url.trim();
+    if !(trimmed.starts_with("http://") || trimmed.starts_with("https://")) {
+        return Err(format!("Refusing to open non-http URL: {trimmed}"));
+    }
+    log::info!("Opening browser at {trimmed}");
+
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    let status = command
+        .arg(trimmed)
+        .status()
+        .map_err(|e| format!("Failed to open browser: {e}"))?;
+
+    if !status.success() {
+        return Err(format!("Browser command exited with status: {status}"));
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
@@ -8,7 +8,9 @@ use crate::diff_engine::{
 use crate::file_utils;
 use crate::get_core_read;
 use git2::{Delta, DiffFindOptions, DiffOptions, ObjectType, Oid, Repository, Sort, Status, Tree};
-use schaltwerk::binary_detection::{get_unsupported_reason, is_binary_file_by_extension};
+use schaltwerk::binary_detection::{
+    get_unsupported_reason, is_binary_file_by_extension, LFS_UNSUPPORTED_REASON,
+};
 use schaltwerk::domains::git;
 use schaltwerk::domains::sessions::entity::ChangedFile;
 use serde::Serialize;
@@ -276,6 +278,7 @@ mod tests {
                     version_number: None,
                     agent_type: None,
                     skip_permissions: None,
+                    agent_model: None,
                 };
                 let session = session_manager.create_session_with_agent(params).unwrap();
                 (
@@ -450,6 +453,10 @@ pub async fn get_file_diff_from_main(
 ) -> Result<(String, String), String> {
     let repo_path = get_repo_path(session_name.clone()).await?;
 
+    if git::is_lfs_tracked_path(Path::new(&repo_path), &file_path) {
+        return Err(format!("Cannot diff file: {LFS_UNSUPPORTED_REASON}"));
+    }
+
     // Check if the worktree file is diffable
     let worktree_path = Path::new(&repo_path).join(&file_path);
     if worktree_path.exists() {
@@ -482,6 +489,15 @@ pub async fn get_file_diff_from_main(
     Ok((base_text, worktree_text))
 }
 
+async fn lfs_unsupported_reason(
+    session_name: Option<String>,
+    file_path: &str,
+) -> Result<Option<String>, String> {
+    let repo_path = get_repo_path(session_name).await?;
+    Ok(git::is_lfs_tracked_path(Path::new(&repo_path), file_path)
+        .then(|| LFS_UNSUPPORTED_REASON.to_string()))
+}
+
 fn is_likely_binary(bytes: &[u8]) -> bool {
     // Use Git's standard algorithm: check for null bytes in first 8000 bytes
     // This matches Git's buffer_is_binary() function
@@ -893,6 +909,7 @@ async fn resolve_session_info(session_name: &str) -> Result<(String, String), St
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "trace", skip(repo_path, old_file_path))]
 pub async fn compute_commit_unified_diff(
     repo_path: Option<String>,
     commit_hash: String,
@@ -1002,15 +1019,15 @@ pub async fn compute_commit_unified_diff(
 
     if total_duration.as_millis() > 100 || is_large_file {
         let commit_short = short_id_str(&repo, commit.id());
-        log::info!(
-            "Commit diff performance for {file_path}@{commit_short}: total={}ms (load={}ms, diff={}ms, collapse={}ms, stats={}ms), size={}KB, lines={}",
-            total_duration.as_millis(),
-            load_duration.as_millis(),
-            diff_duration.as_millis(),
-            collapse_duration.as_millis(),
-            stats_duration.as_millis(),
-            size_bytes / 1024,
-            lines_with_collapsible.len()
+        tracing::info!(
+            total_ms = total_duration.as_millis() as u64,
+            load_ms = load_duration.as_millis() as u64,
+            diff_ms = diff_duration.as_millis() as u64,
+            collapse_ms = collapse_duration.as_millis() as u64,
+            stats_ms = stats_duration.as_millis() as u64,
+            size_kb = size_bytes / 1024,
+            lines = lines_with_collapsible.len(),
+            "Commit diff performance for {file_path}@{commit_short}"
         );
     }
 
@@ -1030,6 +1047,7 @@ pub async fn compute_commit_unified_diff(
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "trace")]
 pub async fn compute_unified_diff_backend(
     session_name: Option<String>,
     file_path: String,
@@ -1053,6 +1071,21 @@ pub async fn compute_unified_diff_backend(
         });
     }
 
+    // LFS content can be huge; skip it before anything is read
+    if let Some(reason) = lfs_unsupported_reason(session_name.clone(), &file_path).await? {
+        return Ok(DiffResponse {
+            lines: vec![],
+            stats: calculate_diff_stats(&[]),
+            file_info: FileInfo {
+                language: None,
+                size_bytes: 0,
+            },
+            is_large_file: false,
+            is_binary: Some(true),
+            unsupported_reason: Some(reason),
+        });
+    }
+
     // Profile file content loading
     let start_load = Instant::now();
     let (old_content, new_content) =
@@ -1100,16 +1133,15 @@ pub async fn compute_unified_diff_backend(
 
     // Log performance metrics
     if total_duration.as_millis() > 100 || is_large_file {
-        log::info!(
-            "Diff performance for {}: total={}ms (load={}ms, diff={}ms, collapse={}ms, stats={}ms), size={}KB, lines={}",
-            file_path,
-            total_duration.as_millis(),
-            load_duration.as_millis(),
-            diff_duration.as_millis(),
-            collapse_duration.as_millis(),
-            stats_duration.as_millis(),
-            new_content.len() / 1024,
-            lines_with_collapsible.len()
+        tracing::info!(
+            total_ms = total_duration.as_millis() as u64,
+            load_ms = load_duration.as_millis() as u64,
+            diff_ms = diff_duration.as_millis() as u64,
+            collapse_ms = collapse_duration.as_millis() as u64,
+            stats_ms = stats_duration.as_millis() as u64,
+            size_kb = new_content.len() / 1024,
+            lines = lines_with_collapsible.len(),
+            "Diff performance for {file_path}"
         );
     }
 
@@ -1124,6 +1156,7 @@ pub async fn compute_unified_diff_backend(
 }
 
 #[tauri::command]
+#[tracing::instrument(level = "trace")]
 pub async fn compute_split_diff_backend(
     session_name: Option<String>,
     file_path: String,
@@ -1147,6 +1180,21 @@ pub async fn compute_split_diff_backend(
         });
     }
 
+    // LFS content can be huge; skip it before anything is read
+    if let Some(reason) = lfs_unsupported_reason(session_name.clone(), &file_path).await? {
+        return Ok(SplitDiffResponse {
+            split_result: compute_split_diff("", ""),
+            stats: calculate_split_diff_stats(&compute_split_diff("", "")),
+            file_info: FileInfo {
+                language: None,
+                size_bytes: 0,
+            },
+            is_large_file: false,
+            is_binary: Some(true),
+            unsupported_reason: Some(reason),
+        });
+    }
+
     // Profile file content loading
     let start_load = Instant::now();
     let (old_content, new_content) =
@@ -1189,16 +1237,15 @@ pub async fn compute_split_diff_backend(
 
     // Log performance metrics
     if total_duration.as_millis() > 100 || is_large_file {
-        log::info!(
-            "Split diff performance for {}: total={}ms (load={}ms, diff={}ms, stats={}ms), size={}KB, lines={}+{}",
-            file_path,
-            total_duration.as_millis(),
-            load_duration.as_millis(),
-            diff_duration.as_millis(),
-            stats_duration.as_millis(),
-            new_content.len() / 1024,
-            split_result.left_lines.len(),
-            split_result.right_lines.len()
+        tracing::info!(
+            total_ms = total_duration.as_millis() as u64,
+            load_ms = load_duration.as_millis() as u64,
+            diff_ms = diff_duration.as_millis() as u64,
+            stats_ms = stats_duration.as_millis() as u64,
+            size_kb = new_content.len() / 1024,
+            left_lines = split_result.left_lines.len(),
+            right_lines = split_result.right_lines.len(),
+            "Split diff performance for {file_path}"
         );
     }
 
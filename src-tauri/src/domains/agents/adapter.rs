@@ -1,6 +1,6 @@
 use super::format_binary_invocation;
 use super::launch_spec::AgentLaunchSpec;
-use super::manifest::AgentDefinition;
+use super::manifest::{AgentDefinition, PromptMechanism};
 use std::path::Path;
 
 pub struct AgentLaunchContext<'a> {
@@ -12,6 +12,16 @@ pub struct AgentLaunchContext<'a> {
     pub manifest: &'a AgentDefinition,
 }
 
+impl<'a> AgentLaunchContext<'a> {
+    /// The binary to launch: the configured override, or the manifest's default
+    pub fn binary(&self) -> &'a str {
+        self.binary_override
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .unwrap_or(self.manifest.default_binary_path.as_str())
+    }
+}
+
 pub trait AgentAdapter: Send + Sync {
     fn find_session(&self, path: &Path) -> Option<String> {
         let _ = path;
@@ -30,25 +40,51 @@ impl DefaultAdapter {
 }
 
 impl AgentAdapter for DefaultAdapter {
+    /// Builds the command purely from the agent's manifest capabilities
     fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
-        let binary = ctx
-            .binary_override
-            .unwrap_or(ctx.manifest.default_binary_path.as_str());
-
-        let binary_invocation = format_binary_invocation(binary);
+        let manifest = ctx.manifest;
+        let binary_invocation = format_binary_invocation(ctx.binary());
         let cwd_quoted = format_binary_invocation(&ctx.worktree_path.display().to_string());
-        let mut command = format!("cd {cwd_quoted} && {binary_invocation}");
+        let prompt = ctx.initial_prompt.filter(|p| !p.trim().is_empty());
+
+        let mut command = format!("cd {cwd_quoted} && ");
+        if let (PromptMechanism::Stdin, Some(prompt)) = (manifest.prompt_mechanism, prompt) {
+            let escaped = super::escape_prompt_for_shell(prompt);
+            command.push_str(&format!(r#"echo "{escaped}" | "#));
+        }
+        command.push_str(&binary_invocation);
 
         if ctx.skip_permissions {
-            command.push_str(" -d");
+            if let Some(flag) = manifest.permission_flag.as_deref() {
+                command.push(' ');
+                command.push_str(flag);
+            }
         }
 
-        if let Some(prompt) = ctx.initial_prompt {
-            let escaped = prompt.replace('"', r#"\""#);
-            command.push_str(&format!(r#" "{escaped}""#));
+        let resume = match (manifest.resume_flag.as_deref(), ctx.session_id) {
+            (Some(flag), Some(id)) if !id.trim().is_empty() => {
+                command.push_str(&format!(" {flag} {}", format_binary_invocation(id)));
+                true
+            }
+            _ => false,
+        };
+
+        let mut spec_initial_command = None;
+        if let Some(prompt) = prompt.filter(|_| !resume) {
+            let escaped = super::escape_prompt_for_shell(prompt);
+            match manifest.prompt_mechanism {
+                PromptMechanism::Positional => command.push_str(&format!(r#" "{escaped}""#)),
+                PromptMechanism::Flag => match manifest.prompt_flag.as_deref() {
+                    Some(flag) => command.push_str(&format!(r#" {flag} "{escaped}""#)),
+                    None => command.push_str(&format!(r#" "{escaped}""#)),
+                },
+                PromptMechanism::Terminal => spec_initial_command = Some(prompt.to_string()),
+                PromptMechanism::Stdin | PromptMechanism::None => {}
+            }
         }
 
         AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf())
+            .with_initial_command(spec_initial_command)
     }
 }
 
@@ -109,7 +145,58 @@ mod tests {
         };
 
         let spec = adapter.build_launch_spec(ctx);
-        assert!(spec.shell_command.contains(" -d"));
+        assert!(spec
+            .shell_command
+            .contains(" --dangerously-skip-permissions"));
+    }
+
+    #[test]
+    fn test_default_adapter_uses_prompt_flag_and_resume_flag() {
+        let adapter = DefaultAdapter::new("test".to_string());
+        let manifest = AgentManifest::get("gemini").unwrap();
+
+        let fresh = adapter.build_launch_spec(AgentLaunchContext {
+            worktree_path: Path::new("/test/path"),
+            session_id: None,
+            initial_prompt: Some("do it"),
+            skip_permissions: true,
+            binary_override: None,
+            manifest,
+        });
+        assert_eq!(
+            fresh.shell_command,
+            r#"cd /test/path && gemini --yolo --prompt-interactive "do it""#
+        );
+
+        let resumed = adapter.build_launch_spec(AgentLaunchContext {
+            worktree_path: Path::new("/test/path"),
+            session_id: Some("latest"),
+            initial_prompt: Some("do it"),
+            skip_permissions: false,
+            binary_override: None,
+            manifest,
+        });
+        assert_eq!(
+            resumed.shell_command,
+            "cd /test/path && gemini --resume latest"
+        );
+    }
+
+    #[test]
+    fn test_default_adapter_terminal_prompt_becomes_initial_command() {
+        let adapter = DefaultAdapter::new("test".to_string());
+        let manifest = AgentManifest::get("aider").unwrap();
+
+        let spec = adapter.build_launch_spec(AgentLaunchContext {
+            worktree_path: Path::new("/test/path"),
+            session_id: None,
+            initial_prompt: Some("do it"),
+            skip_permissions: false,
+            binary_override: Some("aider"),
+            manifest,
+        });
+        assert_eq!(spec.shell_command, "cd /test/path && aider");
+        assert_eq!(spec.initial_command.as_deref(), Some("do it"));
     }
 
     #[test]
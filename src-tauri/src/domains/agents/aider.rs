@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::Path;
+
+/// Chat log Aider writes to the root of the repository it runs in
+pub const AIDER_CHAT_HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Aider has no conversation ids; resuming reloads the worktree's chat log, so the
+/// "session id" of a resumable Aider session is this marker
+pub const AIDER_RESUME_SENTINEL: &str = "chat-history";
+
+/// A worktree is resumable once Aider has logged a conversation in it.
+pub fn find_aider_session(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path.join(AIDER_CHAT_HISTORY_FILE)).ok()?;
+    if metadata.is_file() && metadata.len() > 0 {
+        Some(AIDER_RESUME_SENTINEL.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_session_requires_chat_history() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(find_aider_session(dir.path()), None);
+
+        let history = dir.path().join(AIDER_CHAT_HISTORY_FILE);
+        fs::write(&history, "").unwrap();
+        assert_eq!(find_aider_session(dir.path()), None);
+
+        fs::write(&history, "# aider chat started at 2026-01-01 10:00:00\n").unwrap();
+        assert_eq!(
+            find_aider_session(dir.path()).as_deref(),
+            Some(AIDER_RESUME_SENTINEL)
+        );
+    }
+}
@@ -1,15 +1,9 @@
-use super::format_binary_invocation;
 use std::fs;
 use std::path::Path;
 use std::time::SystemTime;
 use std::time::Duration;
 use tokio::time::sleep;
 
-#[derive(Debug, Clone, Default)]
-pub struct AmpConfig {
-    pub binary_path: Option<String>,
-}
-
 // Simple function to return binary name for external callers
 pub fn resolve_amp_binary() -> String {
     "amp".to_string()
@@ -162,201 +156,10 @@ pub async fn watch_amp_thread_creation(timeout_secs: u64) -> Option<String> {
     }
 }
 
-pub fn build_amp_command_with_config(
-    worktree_path: &Path,
-    session_id: Option<&str>,
-    initial_prompt: Option<&str>,
-    skip_permissions: bool,
-    config: Option<&AmpConfig>,
-) -> String {
-    let binary_name = if let Some(cfg) = config {
-        if let Some(ref path) = cfg.binary_path {
-            let trimmed = path.trim();
-            if !trimmed.is_empty() {
-                trimmed
-            } else {
-                "amp"
-            }
-        } else {
-            "amp"
-        }
-    } else {
-        "amp"
-    };
-    let binary_invocation = format_binary_invocation(binary_name);
-    let cwd_quoted = format_binary_invocation(&worktree_path.display().to_string());
-
-    let mut cmd = format!("cd {cwd_quoted}");
-    cmd.push_str(" && ");
-
-    // Amp supports stdin input, so we can pipe the prompt if provided
-    if let Some(prompt) = initial_prompt {
-        if !prompt.trim().is_empty() {
-            let escaped = super::escape_prompt_for_shell(prompt);
-            cmd.push_str("echo \"");
-            cmd.push_str(&escaped);
-            cmd.push_str("\" | ");
-        }
-    }
-
-    cmd.push_str(&binary_invocation);
-
-    // Resume existing thread if session_id is provided
-    if let Some(thread_id) = session_id {
-        if !thread_id.is_empty() {
-            cmd.push_str(" threads continue ");
-            cmd.push_str(thread_id);
-        }
-    }
-
-    if skip_permissions {
-        cmd.push_str(" --dangerously-allow-all");
-    }
-
-    cmd
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use serial_test::serial;
-    use std::path::Path;
-
-    #[test]
-    fn test_new_session_with_prompt() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some("implement feature X"),
-            true,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            "cd /path/to/worktree && echo \"implement feature X\" | amp --dangerously-allow-all"
-        );
-    }
-
-    #[test]
-    fn test_command_with_spaces_in_cwd() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/with spaces"),
-            None,
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(cmd, "cd \"/path/with spaces\" && amp");
-    }
-
-    #[test]
-    fn test_resume_with_thread_id() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"),
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            "cd /path/to/worktree && amp threads continue T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"
-        );
-    }
-
-    #[test]
-    fn test_new_session_no_prompt_no_permissions() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(cmd, "cd /path/to/worktree && amp");
-    }
-
-    #[test]
-    fn test_prompt_with_quotes() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some(r#"implement "feature" with quotes"#),
-            false,
-            Some(&config),
-        );
-        assert!(cmd.contains("implement"));
-        assert!(cmd.contains("feature"));
-        assert!(cmd.contains("quotes"));
-        assert!(cmd.contains("echo"));
-        assert!(cmd.contains("| amp"));
-    }
-
-    #[test]
-    fn test_resume_with_thread_id_and_permissions() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"),
-            None,
-            true,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            "cd /path/to/worktree && amp threads continue T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc --dangerously-allow-all"
-        );
-    }
-
-    #[test]
-    fn test_resume_with_thread_id_and_prompt() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"),
-            Some("continue with feature X"),
-            false,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            "cd /path/to/worktree && echo \"continue with feature X\" | amp threads continue T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"
-        );
-    }
-
-    #[test]
-    fn test_empty_thread_id_ignored() {
-        let config = AmpConfig {
-            binary_path: Some("amp".to_string()),
-        };
-        let cmd = build_amp_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some(""),
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(cmd, "cd /path/to/worktree && amp");
-    }
 
     #[tokio::test]
     #[serial]
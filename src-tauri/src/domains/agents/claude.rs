@@ -1,4 +1,3 @@
-use super::format_binary_invocation;
 use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
@@ -7,11 +6,6 @@ use std::time::SystemTime;
 
 const CLAUDE_SESSION_SCAN_LIMIT: usize = 64;
 
-#[derive(Debug, Clone, Default)]
-pub struct ClaudeConfig {
-    pub binary_path: Option<String>,
-}
-
 /// Fast-path session detection: scans for Claude JSONL transcripts in the project directory
 /// Returns the most recently modified session ID so callers can resume deterministically
 /// Falls back to `None` when no usable conversation files are present
@@ -160,11 +154,11 @@ pub fn find_resumable_claude_session_fast(path: &Path) -> Option<String> {
     }
 }
 
-fn sanitize_path_for_claude(path: &Path) -> String {
+pub(super) fn sanitize_path_for_claude(path: &Path) -> String {
     path.to_string_lossy().replace(['/', '.', '_'], "-")
 }
 
-fn claude_home_directory() -> Option<PathBuf> {
+pub(super) fn claude_home_directory() -> Option<PathBuf> {
     if let Ok(override_path) = std::env::var("SCHALTWERK_CLAUDE_HOME_OVERRIDE") {
         let trimmed = override_path.trim();
         if !trimmed.is_empty() {
@@ -231,70 +225,6 @@ fn session_file_contains_session_metadata(path: &Path, expected_session_id: &str
     false
 }
 
-pub fn build_claude_command_with_config(
-    worktree_path: &Path,
-    session_id: Option<&str>,
-    initial_prompt: Option<&str>,
-    skip_permissions: bool,
-    config: Option<&ClaudeConfig>,
-) -> String {
-    // Use simple binary name and let system PATH handle resolution
-    let binary_name = if let Some(cfg) = config {
-        if let Some(ref path) = cfg.binary_path {
-            let trimmed = path.trim();
-            if !trimmed.is_empty() {
-                trimmed
-            } else {
-                "claude"
-            }
-        } else {
-            "claude"
-        }
-    } else {
-        "claude"
-    };
-    let binary_invocation = format_binary_invocation(binary_name);
-    let cwd_quoted = format_binary_invocation(&worktree_path.display().to_string());
-    let mut cmd = format!("cd {cwd_quoted} && {binary_invocation}");
-
-    if skip_permissions {
-        cmd.push_str(" --dangerously-skip-permissions");
-    }
-
-    // Use specific session resumption, continue most recent, or start fresh
-    if let Some(session) = session_id {
-        if session == "__continue__" {
-            // Special value to indicate using --continue flag for most recent conversation
-            log::info!(
-                "Claude command builder: Using --continue flag to resume most recent session"
-            );
-            cmd.push_str(" --continue");
-        } else {
-            // Resume specific session with conversation history
-            log::info!(
-                "Claude command builder: Resuming specific session '{session}' using -r flag"
-            );
-            cmd.push_str(&format!(" -r {session}"));
-        }
-    } else if let Some(prompt) = initial_prompt {
-        // Start fresh with initial prompt
-        log::info!(
-            "Claude command builder: Starting fresh session with initial prompt: '{prompt}'"
-        );
-        let escaped = super::escape_prompt_for_shell(prompt);
-        cmd.push_str(&format!(r#" "{escaped}""#));
-    } else {
-        // Start fresh without prompt
-        log::info!(
-            "Claude command builder: Starting fresh session without prompt or session resumption"
-        );
-        // Claude will start a new session by default with no additional flags
-    }
-
-    log::info!("Claude command builder: Final command: '{cmd}'");
-    cmd
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -303,72 +233,6 @@ mod tests {
     use std::io::Write as _;
     use std::path::Path;
 
-    #[test]
-    fn test_new_session_with_prompt() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some("implement feature X"),
-            true,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            r#"cd /path/to/worktree && claude --dangerously-skip-permissions "implement feature X""#
-        );
-    }
-
-    #[test]
-    fn test_resume_with_session_id() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("session123"),
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(cmd, r#"cd /path/to/worktree && claude -r session123"#);
-    }
-
-    #[test]
-    fn test_binary_with_spaces_is_quoted() {
-        let config = ClaudeConfig {
-            binary_path: Some("/Applications/Claude Latest/bin/claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            r#"cd /path/to/worktree && "/Applications/Claude Latest/bin/claude""#
-        );
-    }
-
-    #[test]
-    fn test_command_with_spaces_in_cwd() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/with spaces"),
-            None,
-            None,
-            false,
-            Some(&config),
-        );
-        assert!(cmd.starts_with(r#"cd "/path/with spaces" && "#));
-    }
-
     #[test]
     fn test_sanitize_path_for_claude() {
         let path = Path::new("/Users/john.doe/my-project");
@@ -393,27 +257,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_prompt_with_trailing_backslash_round_trips() {
-        use crate::domains::agents::command_parser::parse_agent_command;
-
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let prompt = "Check Windows path: C:\\Users\\tester\\Projects\\";
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some(prompt),
-            false,
-            Some(&config),
-        );
-
-        let (_, _, args) =
-            parse_agent_command(&cmd).expect("command with trailing backslash prompt should parse");
-        assert_eq!(args.last().unwrap(), prompt);
-    }
-
     #[test]
     #[serial_test::serial]
     fn test_find_resumable_claude_session_fast_with_temp_home() {
@@ -568,57 +411,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_new_session_no_prompt_no_permissions() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(cmd, "cd /path/to/worktree && claude");
-    }
-
-    #[test]
-    fn test_resume_with_permissions() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("session123"),
-            None,
-            true,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            r#"cd /path/to/worktree && claude --dangerously-skip-permissions -r session123"#
-        );
-    }
-
-    #[test]
-    fn test_prompt_with_quotes() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some(r#"implement "feature" with quotes"#),
-            false,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            r#"cd /path/to/worktree && claude "implement \"feature\" with quotes""#
-        );
-    }
-
     #[test]
     fn test_sanitize_schaltwerk_main_repo_path() {
         // Matches observed ~/.claude/projects folder: -Users-marius-wichtner-Documents-git-schaltwerk
@@ -638,37 +430,4 @@ mod tests {
             "-Users-marius-wichtner-Documents-git-schaltwerk--schaltwerk-worktrees-auto-submit-functionality"
         );
     }
-
-    #[test]
-    fn test_build_claude_command_with_continue_special_session_id() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("__continue__"),
-            None,
-            false,
-            Some(&config),
-        );
-        assert_eq!(cmd, "cd /path/to/worktree && claude --continue");
-    }
-
-    #[test]
-    fn test_build_claude_command_with_continue_and_permissions() {
-        let config = ClaudeConfig {
-            binary_path: Some("claude".to_string()),
-        };
-        let cmd = build_claude_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("__continue__"),
-            None,
-            true,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            "cd /path/to/worktree && claude --dangerously-skip-permissions --continue"
-        );
-    }
 }
@@ -452,6 +452,10 @@ fn persist_disk_cache(sessions_dir: &Path, cache: &HashMap<PathBuf, CachedFileEn
 #[derive(Debug, Clone, Default)]
 pub struct CodexConfig {
     pub binary_path: Option<String>,
+    /// Named profile from `~/.codex/config.toml`, passed as `--profile <name>`.
+    pub profile: Option<String>,
+    /// Ad-hoc `-c key=value` overrides, applied in order after the profile.
+    pub config_overrides: Vec<(String, String)>,
 }
 
 pub fn find_codex_session_fast(path: &Path) -> Option<String> {
@@ -900,6 +904,24 @@ pub fn build_codex_command_with_config(
     // Add sandbox mode first (this is an option)
     cmd.push_str(&format!(" --sandbox {sandbox_mode}"));
 
+    // Profile and config overrides must precede the resume subcommand / prompt
+    if let Some(cfg) = config {
+        if let Some(profile) = cfg.profile.as_deref().map(str::trim) {
+            if !profile.is_empty() {
+                cmd.push_str(" --profile ");
+                cmd.push_str(&shell_words::quote(profile));
+            }
+        }
+        for (key, value) in &cfg.config_overrides {
+            let key = key.trim();
+            if key.is_empty() {
+                continue;
+            }
+            cmd.push_str(" -c ");
+            cmd.push_str(&shell_words::quote(&format!("{key}={}", value.trim())));
+        }
+    }
+
     // Handle session resumption
     log::debug!(
         "🛠️ Codex command builder: Configuring session for worktree: {}",
@@ -973,6 +995,7 @@ mod tests {
     fn test_new_session_with_prompt() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -987,10 +1010,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_profile_and_config_overrides() {
+        let config = CodexConfig {
+            binary_path: Some("codex".to_string()),
+            profile: Some("work".to_string()),
+            config_overrides: vec![
+                ("model".to_string(), "o3".to_string()),
+                ("model_reasoning_effort".to_string(), "high".to_string()),
+            ],
+        };
+        let cmd = build_codex_command_with_config(
+            Path::new("/path/to/worktree"),
+            None,
+            Some("implement feature X"),
+            "workspace-write",
+            Some(&config),
+        );
+        assert_eq!(
+            cmd,
+            r#"cd /path/to/worktree && codex --sandbox workspace-write --profile work -c 'model=o3' -c 'model_reasoning_effort=high' "implement feature X""#
+        );
+    }
+
+    #[test]
+    fn test_profile_precedes_resume_and_quotes_values() {
+        let config = CodexConfig {
+            binary_path: Some("codex".to_string()),
+            profile: Some("  ".to_string()),
+            config_overrides: vec![
+                ("".to_string(), "ignored".to_string()),
+                (
+                    "sandbox_workspace_write.writable_roots".to_string(),
+                    "[\"/tmp/a b\"]".to_string(),
+                ),
+            ],
+        };
+        let cmd = build_codex_command_with_config(
+            Path::new("/path/to/worktree"),
+            Some("__continue__"),
+            None,
+            "workspace-write",
+            Some(&config),
+        );
+        assert_eq!(
+            cmd,
+            r#"cd /path/to/worktree && codex --sandbox workspace-write -c 'sandbox_workspace_write.writable_roots=["/tmp/a b"]' resume --last"#
+        );
+    }
+
+    #[test]
+    fn test_config_override_values_survive_shell_splitting() {
+        let config = CodexConfig {
+            binary_path: Some("codex".to_string()),
+            profile: Some("team's profile".to_string()),
+            config_overrides: vec![(
+                "instructions".to_string(),
+                "don't touch $HOME or \"quoted\" paths".to_string(),
+            )],
+        };
+        let cmd = build_codex_command_with_config(
+            Path::new("/path/to/worktree"),
+            None,
+            None,
+            "workspace-write",
+            Some(&config),
+        );
+        let (_, agent) = cmd.split_once(" && ").unwrap();
+        assert_eq!(
+            shell_words::split(agent).unwrap(),
+            vec![
+                "codex",
+                "--sandbox",
+                "workspace-write",
+                "--profile",
+                "team's profile",
+                "-c",
+                "instructions=don't touch $HOME or \"quoted\" paths",
+            ]
+        );
+    }
+
     #[test]
     fn test_command_with_spaces_in_cwd() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/with spaces"),
@@ -1006,6 +1111,7 @@ mod tests {
     fn test_new_session_no_prompt() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -1021,6 +1127,7 @@ mod tests {
     fn test_resume_picker_mode() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -1039,6 +1146,7 @@ mod tests {
     fn test_continue_most_recent_session() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -1057,6 +1165,7 @@ mod tests {
     fn test_resume_by_session_id() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/repo/worktree-a"),
@@ -1072,6 +1181,7 @@ mod tests {
     fn test_resume_by_session_id_with_danger_mode() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/repo/worktree-a"),
@@ -1094,6 +1204,7 @@ mod tests {
         let legacy_uri = format!("file://{}", temp_file.path().display());
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/repo/worktree-a"),
@@ -1123,6 +1234,7 @@ mod tests {
         let legacy_uri = format!("file://{}", temp_file.path().display());
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/repo/worktree-a"),
@@ -1140,6 +1252,7 @@ mod tests {
     fn test_danger_mode() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -1158,6 +1271,7 @@ mod tests {
     fn test_prompt_with_quotes() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -1178,6 +1292,7 @@ mod tests {
 
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let prompt = "Send Windows path: C:\\Users\\codex\\Sandbox\\";
         let cmd = build_codex_command_with_config(
@@ -1330,6 +1445,7 @@ mod tests {
     fn test_continue_with_danger_mode() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
@@ -1348,6 +1464,7 @@ mod tests {
     fn test_resume_picker_with_danger_mode() {
         let config = CodexConfig {
             binary_path: Some("codex".to_string()),
+            ..Default::default()
         };
         let cmd = build_codex_command_with_config(
             Path::new("/path/to/worktree"),
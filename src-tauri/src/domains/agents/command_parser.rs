@@ -93,9 +93,12 @@ pub fn parse_agent_command(command: &str) -> Result<(String, String, Vec<String>
 
     // For Amp, we need to check both the binary name and path
     let is_amp = agent_token == "amp" || agent_token.ends_with("/amp") || agent_token.ends_with("\\amp");
+    // Custom agents name their binary freely, so it need not match the agent id
     let is_supported = supported_agents
         .iter()
-        .any(|agent| stem == *agent || agent_token == *agent) || is_amp;
+        .any(|agent| stem == *agent || agent_token == *agent)
+        || is_amp
+        || AgentManifest::find_by_binary(&normalized_token).is_some();
 
     if !is_supported {
         let agent_list = supported_agents.join(", ");
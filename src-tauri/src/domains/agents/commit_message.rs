@@ -0,0 +1,134 @@
+use anyhow::Result;
+
+use super::headless::{run_headless_task, HeadlessTask};
+use super::naming::ansi_strip;
+
+/// Bytes of patch passed to the agent; the changed-file list is always sent in full
+pub const COMMIT_MESSAGE_DIFF_LIMIT: usize = 12_000;
+
+pub struct CommitMessageContext<'a> {
+    pub session_id: &'a str,
+    pub agent_type: &'a str,
+    /// Changed files and patch, as produced by `git::uncommitted_diff_summary`
+    pub diff_summary: &'a str,
+    pub cli_args: Option<String>,
+    pub env_vars: Vec<(String, String)>,
+    pub binary_path: Option<String>,
+}
+
+pub fn build_commit_message_prompt(diff_summary: &str) -> String {
+    format!(
+        r#"IMPORTANT: Do not use any tools. Answer this message directly without searching or reading files.
+
+Write a git commit message for the changes below.
+
+Rules:
+- Use the Conventional Commits format: <type>(<optional scope>): <summary>
+- Types: feat, fix, refactor, perf, test, docs, style, build, ci, chore
+- Summary in the imperative mood, 72 characters or less, no trailing period
+- Optionally add a blank line and a short body explaining why
+- Return ONLY the commit message, no quotes, code fences or explanation
+
+Changes:
+{diff_summary}
+
+Respond with just the commit message:"#
+    )
+}
+
+/// The commit message from an agent's reply: Claude's JSON `result` when present,
+/// without code fences, surrounding quotes or preamble before the conventional header.
+pub fn parse_commit_message(raw: &str) -> Option<String> {
+    let stripped = ansi_strip(raw);
+    let text = serde_json::from_str::<serde_json::Value>(stripped.trim())
+        .ok()
+        .and_then(|v| {
+            v.as_str()
+                .or_else(|| v.get("result").and_then(|x| x.as_str()))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or(stripped);
+
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+    let start = lines
+        .iter()
+        .position(|line| is_conventional_header(line.trim()))
+        .unwrap_or(0);
+    let message = lines[start..].join("\n");
+    let message = message
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+        .trim();
+
+    if message.is_empty() {
+        None
+    } else {
+        Some(message.to_string())
+    }
+}
+
+fn is_conventional_header(line: &str) -> bool {
+    let Some((prefix, summary)) = line.split_once(": ") else {
+        return false;
+    };
+    let kind = prefix.trim_end_matches('!');
+    let kind = kind.split_once('(').map_or(kind, |(kind, _)| kind);
+    !summary.trim().is_empty() && !kind.is_empty() && kind.chars().all(|c| c.is_ascii_lowercase())
+}
+
+/// Ask the session's agent for a commit message describing `ctx.diff_summary`.
+pub async fn generate_commit_message(ctx: CommitMessageContext<'_>) -> Result<Option<String>> {
+    let prompt = build_commit_message_prompt(ctx.diff_summary);
+    run_headless_task(
+        HeadlessTask {
+            task: "commit message",
+            session_id: ctx.session_id,
+            agent_type: ctx.agent_type,
+            prompt: &prompt,
+            cli_args: ctx.cli_args.as_deref(),
+            env_vars: &ctx.env_vars,
+            binary_path: ctx.binary_path.as_deref(),
+        },
+        parse_commit_message,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_includes_the_diff_summary() {
+        let prompt = build_commit_message_prompt("M src/lib.rs\n\n+fn added() {}\n");
+        assert!(prompt.contains("Conventional Commits"));
+        assert!(prompt.contains("M src/lib.rs"));
+        assert!(prompt.contains("+fn added() {}"));
+    }
+
+    #[test]
+    fn parse_commit_message_reads_claude_json_result() {
+        let raw = r#"{"type":"result","result":"feat(auth): add token refresh\n\nTokens expired mid-session."}"#;
+        assert_eq!(
+            parse_commit_message(raw).as_deref(),
+            Some("feat(auth): add token refresh\n\nTokens expired mid-session.")
+        );
+    }
+
+    #[test]
+    fn parse_commit_message_drops_fences_quotes_and_preamble() {
+        let raw = "Here is the commit message:\n```\nfix: handle empty diff\n```\n";
+        assert_eq!(
+            parse_commit_message(raw).as_deref(),
+            Some("fix: handle empty diff")
+        );
+        assert_eq!(
+            parse_commit_message("\"refactor!: drop legacy config\"").as_deref(),
+            Some("refactor!: drop legacy config")
+        );
+        assert_eq!(parse_commit_message("  \n```\n```"), None);
+    }
+}
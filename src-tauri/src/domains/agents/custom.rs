@@ -0,0 +1,528 @@
+//! User-defined agents. Besides the agents in `agents_manifest.toml`, users can describe
+//! their own CLIs in the `custom_agents` settings entry or in a project's
+//! `.schaltwerk/agents.toml`. They are registered with the manifest at runtime and
+//! launched by [`CustomAgentAdapter`].
+
+use super::adapter::{AgentAdapter, AgentLaunchContext};
+use super::format_binary_invocation;
+use super::launch_spec::AgentLaunchSpec;
+use super::manifest::{AgentDefinition, AgentManifest, PromptMechanism, StatusMarkers};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, RwLock};
+
+/// Agent definitions of a project, relative to the repository root
+pub const PROJECT_AGENTS_FILE: &str = ".schaltwerk/agents.toml";
+
+/// Custom agents have no conversation ids; a resumable session is marked with this
+const CUSTOM_RESUME_SENTINEL: &str = "session-file";
+
+const WORKTREE_PLACEHOLDER: &str = "{worktree}";
+const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct CustomAgentConfig {
+    /// Name shown for the agent; its id when omitted
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Executable name or path
+    pub binary: String,
+    /// Arguments following the binary. `{worktree}` is replaced with the worktree path,
+    /// and an argument containing `{prompt}` carries the initial prompt.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Flag passing the prompt when no argument uses `{prompt}`; positional otherwise
+    #[serde(default)]
+    pub prompt_flag: Option<String>,
+    /// Flag continuing the previous conversation, used once `session_file` exists
+    #[serde(default)]
+    pub resume_flag: Option<String>,
+    /// File or directory, relative to the worktree, the agent keeps its conversation in
+    #[serde(default)]
+    pub session_file: Option<String>,
+    /// Flag appended when the user opts into skipping permission prompts
+    #[serde(default)]
+    pub permission_flag: Option<String>,
+    /// Flag selecting the model when a session picks one, placed right after the binary
+    #[serde(default)]
+    pub model_flag: Option<String>,
+    /// Output fragments shown while the agent waits for input
+    #[serde(default)]
+    pub idle_markers: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl CustomAgentConfig {
+    fn validate(&self, id: &str) -> Result<(), String> {
+        let valid_id = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err("ids may only contain letters, numbers, hyphens and underscores".into());
+        }
+        if AgentManifest::builtin(id).is_some() {
+            return Err("the id belongs to a built-in agent".into());
+        }
+        if self.binary.trim().is_empty() {
+            return Err("binary must not be empty".into());
+        }
+        Ok(())
+    }
+
+    fn to_definition(&self, id: &str) -> AgentDefinition {
+        let binary = self.binary.trim();
+        let binary_name = Path::new(binary)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(binary);
+        AgentDefinition {
+            id: id.to_string(),
+            display_name: self
+                .display_name
+                .clone()
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| id.to_string()),
+            binary_name: binary_name.to_string(),
+            default_binary_path: binary.to_string(),
+            auto_send_initial_command: false,
+            supports_resume: self.resume_flag.is_some() && self.session_file.is_some(),
+            ready_marker: None,
+            prompt_mechanism: if self.prompt_flag.is_some() {
+                PromptMechanism::Flag
+            } else {
+                PromptMechanism::Positional
+            },
+            prompt_flag: self.prompt_flag.clone(),
+            permission_flag: self.permission_flag.clone(),
+            resume_flag: self.resume_flag.clone(),
+            model_flag: self.model_flag.clone(),
+            model_env: None,
+            required_env: Vec::new(),
+            idle_markers: self.idle_markers.clone(),
+            status_markers: StatusMarkers::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectAgentsFile {
+    #[serde(default)]
+    agents: HashMap<String, CustomAgentConfig>,
+}
+
+/// Where a set of custom agents is defined. The current project's definition wins over
+/// the settings when both use the same id.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CustomAgentSource {
+    Project(PathBuf),
+    Settings,
+}
+
+struct RegisteredAgent {
+    definition: &'static AgentDefinition,
+    config: CustomAgentConfig,
+}
+
+type AgentsBySource = BTreeMap<CustomAgentSource, HashMap<String, RegisteredAgent>>;
+
+static CUSTOM_AGENTS: LazyLock<RwLock<AgentsBySource>> =
+    LazyLock::new(|| RwLock::new(BTreeMap::new()));
+
+/// Project whose agents are looked up before the settings
+static CURRENT_PROJECT: LazyLock<RwLock<Option<PathBuf>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Every definition handed out so far. Definitions are `&'static` like the built-in
+/// ones, so each distinct definition is leaked once and reused by later reloads.
+static INTERNED_DEFINITIONS: LazyLock<Mutex<Vec<&'static AgentDefinition>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+fn intern_definition(definition: AgentDefinition) -> &'static AgentDefinition {
+    let mut interned = INTERNED_DEFINITIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(existing) = interned.iter().find(|existing| ***existing == definition) {
+        return existing;
+    }
+    let leaked: &'static AgentDefinition = Box::leak(Box::new(definition));
+    interned.push(leaked);
+    leaked
+}
+
+/// Set the project whose agents take precedence, i.e. the one open in the UI.
+pub fn set_current_custom_agent_project(repo_path: Option<&Path>) {
+    *CURRENT_PROJECT
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = repo_path.map(Path::to_path_buf);
+}
+
+/// Look up `agent_id` in the current project, then the settings, then any other open
+/// project whose sessions may still use it.
+fn find_registered<T>(agent_id: &str, map: impl Fn(&RegisteredAgent) -> T) -> Option<T> {
+    let agents = CUSTOM_AGENTS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let current = CURRENT_PROJECT
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+        .map(CustomAgentSource::Project);
+    current
+        .iter()
+        .chain(std::iter::once(&CustomAgentSource::Settings))
+        .filter_map(|source| agents.get(source))
+        .chain(agents.values())
+        .find_map(|by_id| by_id.get(agent_id))
+        .map(map)
+}
+
+/// Replace the agents defined by `source`; invalid definitions are skipped with a warning.
+pub fn register_custom_agents(
+    source: CustomAgentSource,
+    configs: HashMap<String, CustomAgentConfig>,
+) {
+    let mut agents = CUSTOM_AGENTS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    agents.remove(&source);
+
+    let mut registered = HashMap::new();
+    for (id, config) in configs {
+        if let Err(reason) = config.validate(&id) {
+            log::warn!("Ignoring custom agent '{id}' from {source:?}: {reason}");
+            continue;
+        }
+        let definition = intern_definition(config.to_definition(&id));
+        log::info!("Registered custom agent '{id}' from {source:?}");
+        registered.insert(id, RegisteredAgent { definition, config });
+    }
+
+    if !registered.is_empty() {
+        agents.insert(source, registered);
+    }
+}
+
+/// Read the project's agent definitions; a project without the file defines none.
+pub fn load_project_custom_agents(repo_path: &Path) -> Result<HashMap<String, CustomAgentConfig>> {
+    let path = repo_path.join(PROJECT_AGENTS_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let file: ProjectAgentsFile =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(file.agents)
+}
+
+pub fn custom_agent_definition(agent_id: &str) -> Option<&'static AgentDefinition> {
+    find_registered(agent_id, |agent| agent.definition)
+}
+
+/// Every registered custom agent, one definition per id as [`custom_agent_definition`]
+/// resolves it
+pub fn custom_agent_definitions() -> Vec<&'static AgentDefinition> {
+    let ids: Vec<String> = {
+        let agents = CUSTOM_AGENTS
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        agents
+            .values()
+            .flat_map(|by_id| by_id.keys().cloned())
+            .collect()
+    };
+    let mut definitions: Vec<&'static AgentDefinition> = Vec::new();
+    for id in ids {
+        if definitions.iter().any(|def| def.id == id) {
+            continue;
+        }
+        if let Some(definition) = custom_agent_definition(&id) {
+            definitions.push(definition);
+        }
+    }
+    definitions
+}
+
+pub fn custom_agent_config(agent_id: &str) -> Option<CustomAgentConfig> {
+    find_registered(agent_id, |agent| agent.config.clone())
+}
+
+/// Build the launch command of a custom agent. When resuming, `resume_flag` is added and
+/// the prompt is left out, as the conversation already has it.
+pub fn build_custom_agent_command(
+    config: &CustomAgentConfig,
+    worktree_path: &Path,
+    resume: bool,
+    initial_prompt: Option<&str>,
+    skip_permissions: bool,
+    binary_override: Option<&str>,
+) -> String {
+    let binary = binary_override
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .unwrap_or(config.binary.trim());
+    let worktree = worktree_path.display().to_string();
+    let cwd_quoted = format_binary_invocation(&worktree);
+    let mut cmd = format!("cd {cwd_quoted} && {}", format_binary_invocation(binary));
+
+    let prompt = initial_prompt.filter(|prompt| !prompt.trim().is_empty() && !resume);
+    let template_takes_prompt = config
+        .args
+        .iter()
+        .any(|arg| arg.contains(PROMPT_PLACEHOLDER));
+    for arg in &config.args {
+        let arg = arg.replace(WORKTREE_PLACEHOLDER, &worktree);
+        let arg = if arg.contains(PROMPT_PLACEHOLDER) {
+            // Arguments carrying the prompt are left out when there is none to send
+            let Some(prompt) = prompt else {
+                continue;
+            };
+            arg.replace(PROMPT_PLACEHOLDER, prompt)
+        } else {
+            arg
+        };
+        cmd.push(' ');
+        cmd.push_str(&shell_words::quote(&arg));
+    }
+
+    if skip_permissions {
+        if let Some(flag) = config.permission_flag.as_deref() {
+            cmd.push(' ');
+            cmd.push_str(flag);
+        }
+    }
+
+    if resume {
+        if let Some(flag) = config.resume_flag.as_deref() {
+            cmd.push(' ');
+            cmd.push_str(flag);
+        }
+    }
+
+    if let Some(prompt) = prompt.filter(|_| !template_takes_prompt) {
+        let escaped = super::escape_prompt_for_shell(prompt);
+        match config.prompt_flag.as_deref() {
+            Some(flag) => cmd.push_str(&format!(r#" {flag} "{escaped}""#)),
+            None => cmd.push_str(&format!(r#" "{escaped}""#)),
+        }
+    }
+
+    cmd
+}
+
+pub struct CustomAgentAdapter {
+    config: CustomAgentConfig,
+}
+
+impl CustomAgentAdapter {
+    pub fn new(config: CustomAgentConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl AgentAdapter for CustomAgentAdapter {
+    /// Resumable once the agent's `session_file` exists in the worktree
+    fn find_session(&self, path: &Path) -> Option<String> {
+        self.config.resume_flag.as_ref()?;
+        let session_file = self.config.session_file.as_deref()?;
+        let metadata = fs::metadata(path.join(session_file)).ok()?;
+        if metadata.is_dir() || metadata.len() > 0 {
+            Some(CUSTOM_RESUME_SENTINEL.to_string())
+        } else {
+            None
+        }
+    }
+
+    fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
+        let command = build_custom_agent_command(
+            &self.config,
+            ctx.worktree_path,
+            ctx.session_id.is_some_and(|id| !id.trim().is_empty()),
+            ctx.initial_prompt,
+            ctx.skip_permissions,
+            ctx.binary_override,
+        );
+        AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf())
+            .with_env_vars(self.config.env.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn acme_config() -> CustomAgentConfig {
+        CustomAgentConfig {
+            display_name: Some("Acme".to_string()),
+            binary: "/opt/acme/bin/acme-cli".to_string(),
+            args: vec!["chat".to_string(), "--workspace={worktree}".to_string()],
+            prompt_flag: Some("--message".to_string()),
+            resume_flag: Some("--continue".to_string()),
+            session_file: Some(".acme/history.json".to_string()),
+            permission_flag: Some("--auto-approve".to_string()),
+            model_flag: None,
+            idle_markers: vec!["acme>".to_string()],
+            env: HashMap::from([("ACME_PROFILE".to_string(), "dev".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_command_from_template_and_flags() {
+        let config = acme_config();
+        let worktree = Path::new("/repo/.schaltwerk/worktrees/my session");
+        let cmd =
+            build_custom_agent_command(&config, worktree, false, Some(r#"fix "it""#), true, None);
+        assert_eq!(
+            cmd,
+            r#"cd "/repo/.schaltwerk/worktrees/my session" && /opt/acme/bin/acme-cli chat '--workspace=/repo/.schaltwerk/worktrees/my session' --auto-approve --message "fix \"it\"""#
+        );
+
+        let cmd = build_custom_agent_command(
+            &config,
+            worktree,
+            true,
+            Some("ignored while resuming"),
+            false,
+            Some("acme-dev"),
+        );
+        assert!(cmd.ends_with(
+            "&& acme-dev chat '--workspace=/repo/.schaltwerk/worktrees/my session' --continue"
+        ));
+    }
+
+    #[test]
+    fn test_prompt_placeholder_replaces_prompt_flag() {
+        let config = CustomAgentConfig {
+            binary: "acme".to_string(),
+            args: vec!["--task".to_string(), "{prompt}".to_string()],
+            prompt_flag: Some("--message".to_string()),
+            ..Default::default()
+        };
+        let worktree = Path::new("/w");
+        assert_eq!(
+            build_custom_agent_command(&config, worktree, false, Some("add tests"), false, None),
+            "cd /w && acme --task 'add tests'"
+        );
+        assert_eq!(
+            build_custom_agent_command(&config, worktree, false, None, false, None),
+            "cd /w && acme --task"
+        );
+    }
+
+    #[test]
+    fn test_registered_agents_resolve_through_manifest() {
+        let project = TempDir::new().unwrap();
+        fs::create_dir_all(project.path().join(".schaltwerk")).unwrap();
+        fs::write(
+            project.path().join(PROJECT_AGENTS_FILE),
+            r#"
+[agents.acme-manifest-test]
+display_name = "Acme"
+binary = "/opt/acme/bin/acme-manifest-cli"
+resume_flag = "--continue"
+session_file = ".acme/history.json"
+idle_markers = ["acme>"]
+
+[agents.claude]
+binary = "not-claude"
+"#,
+        )
+        .unwrap();
+
+        let agents = load_project_custom_agents(project.path()).unwrap();
+        let source = CustomAgentSource::Project(project.path().to_path_buf());
+        register_custom_agents(source.clone(), agents);
+
+        let def = AgentManifest::get("acme-manifest-test").expect("custom agent registered");
+        assert_eq!(def.display_name, "Acme");
+        assert!(def.supports_resume);
+        assert_eq!(def.idle_markers, vec!["acme>".to_string()]);
+        assert_eq!(
+            AgentManifest::find_by_binary("acme-manifest-cli").map(|d| d.id.as_str()),
+            Some("acme-manifest-test")
+        );
+        assert!(AgentManifest::supported_agents().contains(&"acme-manifest-test".to_string()));
+        assert_eq!(AgentManifest::get("claude").unwrap().binary_name, "claude");
+
+        register_custom_agents(source, HashMap::new());
+        assert!(AgentManifest::get("acme-manifest-test").is_none());
+    }
+
+    #[test]
+    fn test_current_project_wins_over_settings_and_other_projects() {
+        let current = PathBuf::from("/custom-agents-test/current");
+        let other = PathBuf::from("/custom-agents-test/a-other");
+        let config = |binary: &str| {
+            HashMap::from([(
+                "acme-precedence-test".to_string(),
+                CustomAgentConfig {
+                    binary: binary.to_string(),
+                    ..Default::default()
+                },
+            )])
+        };
+        register_custom_agents(CustomAgentSource::Project(other.clone()), config("other"));
+        register_custom_agents(CustomAgentSource::Settings, config("settings"));
+        register_custom_agents(
+            CustomAgentSource::Project(current.clone()),
+            config("current"),
+        );
+
+        set_current_custom_agent_project(Some(&current));
+        let binary = |id| custom_agent_config(id).map(|config| config.binary);
+        assert_eq!(binary("acme-precedence-test").as_deref(), Some("current"));
+
+        register_custom_agents(CustomAgentSource::Project(current), HashMap::new());
+        assert_eq!(binary("acme-precedence-test").as_deref(), Some("settings"));
+
+        register_custom_agents(CustomAgentSource::Settings, HashMap::new());
+        assert_eq!(binary("acme-precedence-test").as_deref(), Some("other"));
+
+        register_custom_agents(CustomAgentSource::Project(other), HashMap::new());
+        set_current_custom_agent_project(None);
+    }
+
+    #[test]
+    fn test_reloading_reuses_interned_definitions() {
+        let source = CustomAgentSource::Project(PathBuf::from("/custom-agents-test/reload"));
+        let configs = || {
+            HashMap::from([(
+                "acme-reload-test".to_string(),
+                CustomAgentConfig {
+                    binary: "acme-reload".to_string(),
+                    ..Default::default()
+                },
+            )])
+        };
+        register_custom_agents(source.clone(), configs());
+        let first = custom_agent_definition("acme-reload-test").unwrap();
+        register_custom_agents(source.clone(), HashMap::new());
+        register_custom_agents(source.clone(), configs());
+        let second = custom_agent_definition("acme-reload-test").unwrap();
+        assert!(std::ptr::eq(first, second));
+
+        register_custom_agents(source, HashMap::new());
+    }
+
+    #[test]
+    fn test_find_session_needs_session_file() {
+        let worktree = TempDir::new().unwrap();
+        let adapter = CustomAgentAdapter::new(acme_config());
+        assert_eq!(adapter.find_session(worktree.path()), None);
+
+        fs::create_dir_all(worktree.path().join(".acme")).unwrap();
+        fs::write(worktree.path().join(".acme/history.json"), "[]").unwrap();
+        assert_eq!(
+            adapter.find_session(worktree.path()).as_deref(),
+            Some(CUSTOM_RESUME_SENTINEL)
+        );
+        assert!(load_project_custom_agents(worktree.path())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,181 +1,206 @@
-use super::format_binary_invocation;
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::Path;
-
-#[derive(Debug, Clone, Default)]
-pub struct GeminiConfig {
-    pub binary_path: Option<String>,
-}
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 // Simple function to return binary name for external callers
 pub fn resolve_gemini_binary() -> String {
     "gemini".to_string()
 }
 
-pub fn find_gemini_session(path: &Path) -> Option<String> {
-    let session_file = path.join(".gemini-session");
-
-    if session_file.exists() {
-        fs::read_to_string(&session_file).ok().and_then(|content| {
-            let trimmed = content.trim();
-            if !trimmed.is_empty() {
-                Some(trimmed.to_string())
-            } else {
-                None
-            }
-        })
-    } else {
-        None
+fn gemini_home_directory() -> Option<PathBuf> {
+    if let Ok(override_path) = std::env::var("SCHALTWERK_GEMINI_HOME_OVERRIDE") {
+        let trimmed = override_path.trim();
+        if !trimmed.is_empty() {
+            return Some(PathBuf::from(trimmed));
+        }
     }
+
+    std::env::var("HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(dirs::home_dir)
 }
 
-pub fn build_gemini_command_with_config(
-    worktree_path: &Path,
-    _session_id: Option<&str>,
-    _initial_prompt: Option<&str>,
-    skip_permissions: bool,
-    config: Option<&GeminiConfig>,
-) -> String {
-    // Use simple binary name and let system PATH handle resolution
-    let binary_name = if let Some(cfg) = config {
-        if let Some(ref path) = cfg.binary_path {
-            let trimmed = path.trim();
-            if !trimmed.is_empty() {
-                trimmed
-            } else {
-                "gemini"
-            }
-        } else {
-            "gemini"
-        }
-    } else {
-        "gemini"
+/// Gemini keys its per-project state by the SHA-256 of the project root path:
+/// `~/.gemini/tmp/<hash>/{chats,checkpoints}`
+fn gemini_project_hash(path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn gemini_project_dirs(path: &Path) -> Vec<PathBuf> {
+    let Some(home) = gemini_home_directory() else {
+        return Vec::new();
     };
-    let binary_invocation = format_binary_invocation(binary_name);
-    let cwd_quoted = format_binary_invocation(&worktree_path.display().to_string());
-    let mut cmd = format!("cd {cwd_quoted} && {binary_invocation}");
+    let tmp_root = home.join(".gemini").join("tmp");
 
-    if skip_permissions {
-        cmd.push_str(" --yolo");
+    let mut dirs = vec![tmp_root.join(gemini_project_hash(path))];
+    if let Ok(canonical) = path.canonicalize() {
+        let alt = tmp_root.join(gemini_project_hash(&canonical));
+        if !dirs.contains(&alt) {
+            dirs.push(alt);
+        }
     }
+    dirs
+}
 
-    // Prefer using real CLI interactive prompt flag when available.
-    // Fallback: launch TUI and inject prompt via terminal manager.
-    if let Some(prompt) = _initial_prompt {
-        if !prompt.trim().is_empty() {
-            let escaped = super::escape_prompt_for_shell(prompt);
-            cmd.push_str(&format!(r#" --prompt-interactive "{escaped}""#));
-        }
+/// A checkpoint is usable only if it parses and actually carries conversation history.
+/// Returns the session id recorded in the file (if any) on success.
+fn verify_gemini_checkpoint(path: &Path) -> Result<Option<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("unreadable: {e}"))?;
+    if content.trim().is_empty() {
+        return Err("file is empty".to_string());
     }
 
-    cmd
-}
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("invalid JSON: {e}"))?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::Path;
+    let messages = match &value {
+        serde_json::Value::Array(items) => Some(items),
+        serde_json::Value::Object(map) => map.get("messages").and_then(|m| m.as_array()),
+        _ => None,
+    };
 
-    #[test]
-    fn test_new_session_with_prompt() {
-        let config = GeminiConfig {
-            binary_path: Some("gemini".to_string()),
-        };
-        let cmd = build_gemini_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some("implement feature X"),
-            true,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            r#"cd /path/to/worktree && gemini --yolo --prompt-interactive "implement feature X""#
-        );
+    match messages {
+        Some(items) if !items.is_empty() => Ok(value
+            .get("sessionId")
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())),
+        Some(_) => Err("no conversation history".to_string()),
+        None => Err("missing message list".to_string()),
     }
+}
 
-    #[test]
-    fn test_command_with_spaces_in_cwd() {
-        let config = GeminiConfig {
-            binary_path: Some("gemini".to_string()),
+fn collect_checkpoint_candidates(project_dir: &Path) -> Vec<(SystemTime, PathBuf)> {
+    let mut candidates = Vec::new();
+    for sub in ["chats", "checkpoints"] {
+        let Ok(entries) = fs::read_dir(project_dir.join(sub)) else {
+            continue;
         };
-        let cmd = build_gemini_command_with_config(
-            Path::new("/path/with spaces"),
-            None,
-            None,
-            false,
-            Some(&config),
-        );
-        assert!(cmd.starts_with(r#"cd "/path/with spaces" && "#));
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map(|ext| ext != "json").unwrap_or(true) {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            candidates.push((modified, path));
+        }
     }
+    candidates
+}
 
-    #[test]
-    fn test_resume_with_session_id() {
-        let config = GeminiConfig {
-            binary_path: Some("gemini".to_string()),
-        };
-        let cmd = build_gemini_command_with_config(
-            Path::new("/path/to/worktree"),
-            Some("12345678-1234-1234-1234-123456789012"),
-            None,
-            false,
-            Some(&config),
-        );
-        // Gemini doesn't support resume, so we just start interactive mode
-        assert_eq!(cmd, "cd /path/to/worktree && gemini");
+pub fn find_gemini_session(path: &Path) -> Option<String> {
+    let mut candidates: Vec<(SystemTime, PathBuf)> = gemini_project_dirs(path)
+        .iter()
+        .flat_map(|dir| collect_checkpoint_candidates(dir))
+        .collect();
+    // Newest first
+    candidates.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let mut rejected = 0usize;
+    for (_, candidate) in &candidates {
+        match verify_gemini_checkpoint(candidate) {
+            Ok(Some(session_id)) => {
+                log::info!(
+                    "Gemini session detection: Resuming session '{session_id}' from {}",
+                    candidate.display()
+                );
+                return Some(session_id);
+            }
+            // `--resume latest` is resolved by Gemini itself and can land on another
+            // project's conversation, so only an id read from this worktree's state is used
+            Ok(None) => {
+                log::debug!(
+                    "Gemini session detection: Skipping checkpoint {} without a session id",
+                    candidate.display()
+                );
+            }
+            Err(reason) => {
+                rejected += 1;
+                log::warn!(
+                    "Gemini session detection: Skipping corrupt checkpoint {}: {reason}",
+                    candidate.display()
+                );
+            }
+        }
     }
 
-    #[test]
-    fn test_new_session_no_prompt_no_permissions() {
-        let config = GeminiConfig {
-            binary_path: Some("gemini".to_string()),
-        };
-        let cmd = build_gemini_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            None,
-            false,
-            Some(&config),
+    if rejected > 0 {
+        log::warn!(
+            "Gemini session detection: All {rejected} checkpoint(s) for {} are corrupt; starting fresh",
+            path.display()
         );
-        assert_eq!(cmd, "cd /path/to/worktree && gemini");
+        return None;
     }
 
-    #[test]
-    fn test_prompt_with_quotes() {
-        let config = GeminiConfig {
-            binary_path: Some("gemini".to_string()),
-        };
-        let cmd = build_gemini_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some(r#"implement "feature" with quotes"#),
-            false,
-            Some(&config),
-        );
-        assert_eq!(
-            cmd,
-            r#"cd /path/to/worktree && gemini --prompt-interactive "implement \"feature\" with quotes""#
+    // Legacy marker written by older integrations
+    let session_file = path.join(".gemini-session");
+    let legacy = fs::read_to_string(&session_file)
+        .ok()
+        .map(|content| content.trim().to_string())
+        .filter(|id| !id.is_empty());
+    if legacy.is_none() {
+        log::debug!(
+            "Gemini session detection: No checkpoints found for {}",
+            path.display()
         );
     }
+    legacy
+}
 
-    #[test]
-    fn test_prompt_with_trailing_backslash_round_trips() {
-        use crate::domains::agents::command_parser::parse_agent_command;
-
-        let config = GeminiConfig {
-            binary_path: Some("gemini".to_string()),
-        };
-        let prompt = "Inspect path: C:\\Users\\gemini\\Workspace\\";
-        let cmd = build_gemini_command_with_config(
-            Path::new("/path/to/worktree"),
-            None,
-            Some(prompt),
-            false,
-            Some(&config),
-        );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
 
-        let (_, _, args) =
-            parse_agent_command(&cmd).expect("gemini prompt ending with backslash should parse");
-        assert_eq!(args.last().unwrap(), prompt);
+    #[test]
+    #[serial_test::serial]
+    fn test_find_gemini_session_skips_corrupt_checkpoints() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let prev = std::env::var("SCHALTWERK_GEMINI_HOME_OVERRIDE").ok();
+        std::env::set_var("SCHALTWERK_GEMINI_HOME_OVERRIDE", tempdir.path());
+
+        let worktree = Path::new("/tmp/schaltwerk-gemini-worktree");
+        let chats = tempdir
+            .path()
+            .join(".gemini")
+            .join("tmp")
+            .join(gemini_project_hash(worktree))
+            .join("chats");
+        fs::create_dir_all(&chats).unwrap();
+
+        let good = chats.join("session-good.json");
+        let corrupt = chats.join("session-corrupt.json");
+        fs::write(
+            &good,
+            r#"{"sessionId":"abc-123","messages":[{"type":"user","content":"hi"}]}"#,
+        )
+        .unwrap();
+        fs::write(&corrupt, r#"{"sessionId":"broken","messages":["#).unwrap();
+        filetime::set_file_mtime(&good, filetime::FileTime::from_unix_time(100, 0)).unwrap();
+        filetime::set_file_mtime(&corrupt, filetime::FileTime::from_unix_time(200, 0)).unwrap();
+
+        // Newest checkpoint is corrupt, so the older valid one is used
+        assert_eq!(find_gemini_session(worktree).as_deref(), Some("abc-123"));
+
+        // Once every checkpoint is corrupt we start fresh
+        fs::write(&good, r#"{"sessionId":"abc-123","messages":[]}"#).unwrap();
+        assert_eq!(find_gemini_session(worktree), None);
+
+        // A checkpoint without a session id never resumes whatever Gemini ran last
+        fs::remove_file(&corrupt).unwrap();
+        fs::write(&good, r#"{"messages":[{"type":"user","content":"hi"}]}"#).unwrap();
+        assert_eq!(find_gemini_session(worktree), None);
+
+        match prev {
+            Some(v) => std::env::set_var("SCHALTWERK_GEMINI_HOME_OVERRIDE", v),
+            None => std::env::remove_var("SCHALTWERK_GEMINI_HOME_OVERRIDE"),
+        }
     }
 }
@@ -0,0 +1,178 @@
+use super::format_binary_invocation;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Environment variable selecting Goose's approval mode
+pub const GOOSE_MODE_ENV: &str = "GOOSE_MODE";
+
+fn goose_sessions_dir() -> Option<PathBuf> {
+    let data_home = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))?;
+    Some(data_home.join("goose").join("sessions"))
+}
+
+/// Goose keeps conversations by name rather than by directory. Sessions Schaltwerk starts
+/// in a worktree share this prefix, so the worktree's latest conversation can be found again.
+pub fn goose_session_prefix(worktree_path: &Path) -> String {
+    let dir_name = worktree_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let sanitized: String = dir_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut hasher = Sha256::new();
+    hasher.update(worktree_path.to_string_lossy().as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    format!("schaltwerk-{sanitized}-{}", &hash[..8])
+}
+
+/// Name for a new conversation in the worktree; a restart starts a new one instead of
+/// reusing an existing name.
+pub fn new_goose_session_name(worktree_path: &Path) -> String {
+    let started = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!("{}-{started}", goose_session_prefix(worktree_path))
+}
+
+fn find_goose_session_in(sessions_dir: &Path, worktree_path: &Path) -> Option<String> {
+    let prefix = format!("{}-", goose_session_prefix(worktree_path));
+    let entries = fs::read_dir(sessions_dir).ok()?;
+
+    let mut newest: Option<(SystemTime, String)> = None;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|ext| ext != "jsonl").unwrap_or(true) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        // A session file without messages has nothing to resume
+        if !name.starts_with(&prefix) || metadata.len() == 0 {
+            continue;
+        }
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, name.to_string()));
+        }
+    }
+    newest.map(|(_, name)| name)
+}
+
+/// The latest Goose conversation Schaltwerk started in `worktree_path`, by session name.
+pub fn find_goose_session(worktree_path: &Path) -> Option<String> {
+    let sessions_dir = goose_sessions_dir()?;
+    let found = find_goose_session_in(&sessions_dir, worktree_path);
+    log::debug!(
+        "Goose session detection in {}: {found:?}",
+        sessions_dir.display()
+    );
+    found
+}
+
+/// Build the Goose launch command. A session name resumes that conversation with
+/// `goose session --resume`; otherwise a new named conversation starts, through
+/// `goose run --interactive` when there is a prompt to send first.
+pub fn build_goose_command(
+    worktree_path: &Path,
+    resume_session: Option<&str>,
+    initial_prompt: Option<&str>,
+    binary: &str,
+) -> String {
+    let binary_invocation = format_binary_invocation(binary);
+    let cwd_quoted = format_binary_invocation(&worktree_path.display().to_string());
+    let mut cmd = format!("cd {cwd_quoted} && {binary_invocation}");
+
+    if let Some(name) = resume_session.filter(|name| !name.trim().is_empty()) {
+        cmd.push_str(&format!(
+            " session --resume --name {}",
+            format_binary_invocation(name)
+        ));
+        return cmd;
+    }
+
+    let name = new_goose_session_name(worktree_path);
+    match initial_prompt.filter(|prompt| !prompt.trim().is_empty()) {
+        Some(prompt) => {
+            let escaped = super::escape_prompt_for_shell(prompt);
+            cmd.push_str(&format!(
+                r#" run --interactive --name {name} --text "{escaped}""#
+            ));
+        }
+        None => cmd.push_str(&format!(" session --name {name}")),
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new_session_with_prompt_stays_interactive() {
+        let worktree = Path::new("/repo/.schaltwerk/worktrees/fix-login");
+        let cmd = build_goose_command(worktree, None, Some(r#"fix the "login" flow"#), "goose");
+        let prefix = goose_session_prefix(worktree);
+        assert!(prefix.starts_with("schaltwerk-fix-login-"));
+        assert!(cmd.starts_with(&format!(
+            "cd /repo/.schaltwerk/worktrees/fix-login && goose run --interactive --name {prefix}-"
+        )));
+        assert!(cmd.ends_with(r#" --text "fix the \"login\" flow""#));
+    }
+
+    #[test]
+    fn test_resume_and_plain_start() {
+        let worktree = Path::new("/path/with spaces");
+        let cmd = build_goose_command(
+            worktree,
+            Some("schaltwerk-with_spaces-0123abcd-1700000000"),
+            Some("ignored while resuming"),
+            "/opt/bin/goose",
+        );
+        assert_eq!(
+            cmd,
+            r#"cd "/path/with spaces" && /opt/bin/goose session --resume --name schaltwerk-with_spaces-0123abcd-1700000000"#
+        );
+
+        let cmd = build_goose_command(worktree, None, None, "/opt/bin/goose");
+        assert!(cmd.contains("/opt/bin/goose session --name schaltwerk-with_spaces-"));
+    }
+
+    #[test]
+    fn test_find_session_picks_worktree_conversation_with_messages() {
+        let sessions = TempDir::new().unwrap();
+        let worktree = Path::new("/repo/.schaltwerk/worktrees/alpha");
+        let prefix = goose_session_prefix(worktree);
+        assert_eq!(find_goose_session_in(sessions.path(), worktree), None);
+
+        fs::write(sessions.path().join(format!("{prefix}-1.jsonl")), "").unwrap();
+        let other = goose_session_prefix(Path::new("/other/.schaltwerk/worktrees/alpha"));
+        fs::write(sessions.path().join(format!("{other}-2.jsonl")), "{}\n").unwrap();
+        assert_eq!(find_goose_session_in(sessions.path(), worktree), None);
+
+        fs::write(sessions.path().join(format!("{prefix}-3.jsonl")), "{}\n").unwrap();
+        assert_eq!(
+            find_goose_session_in(sessions.path(), worktree),
+            Some(format!("{prefix}-3"))
+        );
+    }
+}
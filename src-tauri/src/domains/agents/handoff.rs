@@ -0,0 +1,130 @@
+//! The prompt that lets a different agent take over a session's work: what the session
+//! is for and what the previous agent already changed.
+
+use crate::domains::sessions::entity::ChangedFile;
+
+/// Bytes of uncommitted patch included in the handoff prompt
+pub const HANDOFF_PATCH_LIMIT: usize = 12_000;
+
+const HANDOFF_INTRO: &str = "You are taking over this task from another coding agent";
+const TASK_HEADING: &str = "\n## Task\n\n";
+const FILES_HEADING: &str = "\n## Files changed against ";
+
+pub struct HandoffContext<'a> {
+    pub previous_agent: &'a str,
+    pub spec: Option<&'a str>,
+    pub base_branch: &'a str,
+    /// Committed and uncommitted changes against the base branch
+    pub changed_files: &'a [ChangedFile],
+    /// Uncommitted changes as produced by `git::uncommitted_diff_summary`
+    pub uncommitted_summary: &'a str,
+}
+
+pub fn build_handoff_prompt(ctx: &HandoffContext<'_>) -> String {
+    let mut prompt = format!(
+        "{HANDOFF_INTRO} ({}). The work so far is in this worktree; continue from there instead of starting over.\n",
+        ctx.previous_agent
+    );
+
+    let spec = ctx.spec.and_then(original_task);
+    if let Some(spec) = spec.map(str::trim).filter(|spec| !spec.is_empty()) {
+        prompt.push_str(TASK_HEADING);
+        prompt.push_str(spec);
+        prompt.push('\n');
+    }
+
+    prompt.push_str(&format!("{FILES_HEADING}{}\n\n", ctx.base_branch));
+    if ctx.changed_files.is_empty() {
+        prompt.push_str("None yet.\n");
+    }
+    for file in ctx.changed_files {
+        prompt.push_str(&format!("- {} ({})\n", file.path, file.change_type));
+    }
+
+    let uncommitted = ctx.uncommitted_summary.trim();
+    if !uncommitted.is_empty() {
+        prompt.push_str("\n## Uncommitted changes\n\n```diff\n");
+        prompt.push_str(uncommitted);
+        prompt.push_str("\n```\n");
+    }
+
+    prompt.push_str(
+        "\nReview these changes, then carry on with the task. Ask if something is unclear.\n",
+    );
+    prompt
+}
+
+/// The task a prompt describes. A handoff prompt becomes the session's initial prompt,
+/// so when the session is handed on again only its task section is carried over.
+pub fn original_task(prompt: &str) -> Option<&str> {
+    if !prompt.starts_with(HANDOFF_INTRO) {
+        return Some(prompt);
+    }
+    let (_, task) = prompt.split_once(TASK_HEADING)?;
+    Some(
+        task.split_once(FILES_HEADING)
+            .map_or(task, |(task, _)| task),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handoff_prompt_carries_spec_and_changes() {
+        let changed_files = vec![
+            ChangedFile {
+                path: "src/auth.rs".to_string(),
+                change_type: "modified".to_string(),
+            },
+            ChangedFile {
+                path: "src/token.rs".to_string(),
+                change_type: "added".to_string(),
+            },
+        ];
+        let prompt = build_handoff_prompt(&HandoffContext {
+            previous_agent: "gemini",
+            spec: Some("Add token refresh to the login flow."),
+            base_branch: "main",
+            changed_files: &changed_files,
+            uncommitted_summary: "A src/token.rs\n\n+pub fn refresh() {}\n",
+        });
+
+        assert!(prompt.contains("another coding agent (gemini)"));
+        assert!(prompt.contains("## Task\n\nAdd token refresh to the login flow."));
+        assert!(prompt.contains("## Files changed against main"));
+        assert!(prompt.contains("- src/auth.rs (modified)\n- src/token.rs (added)"));
+        assert!(prompt.contains("```diff\nA src/token.rs\n\n+pub fn refresh() {}\n```"));
+
+        // Handing the session on again carries over only the task, not the old handoff
+        assert_eq!(
+            original_task(&prompt).map(str::trim),
+            Some("Add token refresh to the login flow.")
+        );
+        let again = build_handoff_prompt(&HandoffContext {
+            previous_agent: "claude",
+            spec: Some(&prompt),
+            base_branch: "main",
+            changed_files: &changed_files,
+            uncommitted_summary: "",
+        });
+        assert_eq!(again.matches("## Task").count(), 1);
+        assert!(again.contains("another coding agent (claude)"));
+    }
+
+    #[test]
+    fn handoff_prompt_without_spec_or_changes() {
+        let prompt = build_handoff_prompt(&HandoffContext {
+            previous_agent: "codex",
+            spec: Some("  "),
+            base_branch: "develop",
+            changed_files: &[],
+            uncommitted_summary: "",
+        });
+
+        assert!(!prompt.contains("## Task"));
+        assert!(prompt.contains("## Files changed against develop\n\nNone yet."));
+        assert!(!prompt.contains("Uncommitted"));
+    }
+}
@@ -0,0 +1,360 @@
+//! One-shot agent runs without a terminal: the agent gets a prompt, answers and exits.
+//! Schaltwerk uses them for small tasks it hands to a session's agent, such as naming
+//! the session, writing a commit message or summarizing a spec.
+
+use super::naming::{
+    ansi_strip, build_claude_namegen_args, build_namegen_env, fix_codex_single_dash_long_flags,
+    reorder_codex_model_after_profile,
+};
+use super::usage::AgentUsage;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long a headless run may take before it is killed
+pub const HEADLESS_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Agents with a non-interactive mode
+pub const HEADLESS_AGENTS: &[&str] = &["claude", "codex", "gemini", "opencode"];
+
+pub fn supports_headless(agent_type: &str) -> bool {
+    HEADLESS_AGENTS.contains(&agent_type)
+}
+
+pub struct HeadlessRun<'a> {
+    pub agent_type: &'a str,
+    pub prompt: &'a str,
+    /// Directory the agent runs in: a session's worktree, or a scratch directory for
+    /// tasks that need no files
+    pub working_dir: &'a Path,
+    pub cli_args: Option<&'a str>,
+    pub env_vars: &'a [(String, String)],
+    pub binary_path: Option<&'a str>,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HeadlessOutput {
+    /// `None` when the agent was killed by a signal
+    pub exit_code: Option<i32>,
+    /// The agent's final answer: Claude's JSON `result`, Codex's last message, or the
+    /// plain output of other agents, without terminal escapes
+    pub text: String,
+    pub stdout: String,
+    pub stderr: String,
+    /// Reported by agents with structured output (Claude)
+    pub usage: Option<AgentUsage>,
+}
+
+impl HeadlessOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Answer and usage from the JSON Claude prints with `--output-format json`.
+pub fn parse_claude_json(stdout: &str) -> Option<(String, Option<AgentUsage>)> {
+    let value: serde_json::Value = serde_json::from_str(stdout.trim()).ok()?;
+    if let Some(text) = value.as_str() {
+        return Some((text.to_string(), None));
+    }
+    let text = value.get("result")?.as_str()?.to_string();
+
+    let count = |key: &str| {
+        value
+            .get("usage")
+            .and_then(|usage| usage.get(key))
+            .and_then(|count| count.as_u64())
+            .unwrap_or_default()
+    };
+    let cost_usd = value.get("total_cost_usd").and_then(|cost| cost.as_f64());
+    let usage = (value.get("usage").is_some() || cost_usd.is_some()).then(|| AgentUsage {
+        input_tokens: count("input_tokens"),
+        output_tokens: count("output_tokens"),
+        cache_read_tokens: count("cache_read_input_tokens"),
+        cache_write_tokens: count("cache_creation_input_tokens"),
+        cost_usd: cost_usd.unwrap_or_default(),
+    });
+    Some((text, usage))
+}
+
+fn build_command(run: &HeadlessRun<'_>, last_message_file: &Path) -> Option<Command> {
+    let command = match run.agent_type {
+        "codex" => {
+            let mut args: Vec<String> = vec![
+                "exec".into(),
+                "--sandbox".into(),
+                "workspace-write".into(),
+                "--skip-git-repo-check".into(),
+                "--json".into(),
+            ];
+            if let Some(cli) = run.cli_args {
+                let mut extra = shell_words::split(cli).unwrap_or_else(|_| vec![cli.to_string()]);
+                fix_codex_single_dash_long_flags(&mut extra);
+                reorder_codex_model_after_profile(&mut extra);
+                // `exec` rejects the interactive-only `--search` flag
+                extra.retain(|a| a != "--search" && a != "-search");
+                args.extend(extra);
+            }
+            args.push("--output-last-message".into());
+            args.push(last_message_file.to_string_lossy().to_string());
+            args.push(run.prompt.to_string());
+            let mut command = Command::new(run.binary_path.unwrap_or("codex"));
+            command.args(args);
+            command
+        }
+        "opencode" => {
+            let mut command = Command::new(super::opencode::resolve_opencode_binary());
+            command.args(["run", run.prompt]);
+            command
+        }
+        "gemini" => {
+            let mut command = Command::new(super::gemini::resolve_gemini_binary());
+            command.args(["--prompt", run.prompt]);
+            command
+        }
+        "claude" => {
+            let mut command = Command::new(run.binary_path.unwrap_or("claude"));
+            command.args(build_claude_namegen_args(run.prompt, run.cli_args));
+            command
+        }
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Run the agent non-interactively with `run.prompt` in `run.working_dir`. `None` when
+/// the agent has no headless mode; an error when it could not be started or timed out.
+/// A run that exits unsuccessfully still returns its output.
+pub async fn run_agent_headless(run: HeadlessRun<'_>) -> Result<Option<HeadlessOutput>> {
+    let agent_type = run.agent_type;
+    let last_message_file = std::env::temp_dir().join(format!(
+        "schaltwerk_codex_headless_{}.txt",
+        uuid::Uuid::new_v4()
+    ));
+    let Some(mut command) = build_command(&run, &last_message_file) else {
+        log::info!("Agent type '{agent_type}' has no headless mode");
+        return Ok(None);
+    };
+
+    command.current_dir(run.working_dir);
+    command.stdin(std::process::Stdio::null());
+    command.kill_on_drop(true);
+    for (key, value) in build_namegen_env(run.env_vars) {
+        command.env(key, value);
+    }
+
+    log::info!(
+        "Running {agent_type} headless in {}",
+        run.working_dir.display()
+    );
+    let output = tokio::time::timeout(run.timeout, command.output()).await;
+    // Codex writes its final answer to the last-message file; stdout is its event stream
+    let last_message = std::fs::read_to_string(&last_message_file)
+        .ok()
+        .filter(|s| !s.trim().is_empty());
+    let _ = std::fs::remove_file(&last_message_file);
+
+    let output = output
+        .map_err(|_| anyhow!("{agent_type} did not finish within {:?}", run.timeout))?
+        .map_err(|e| anyhow!("Failed to execute {agent_type}: {e}"))?;
+
+    let stdout = ansi_strip(&String::from_utf8_lossy(&output.stdout));
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let (text, usage) = match agent_type {
+        "claude" => parse_claude_json(&stdout).unwrap_or_else(|| (stdout.clone(), None)),
+        "codex" => (
+            last_message.map_or_else(|| stdout.clone(), |message| ansi_strip(&message)),
+            None,
+        ),
+        _ => (stdout.clone(), None),
+    };
+
+    let output = HeadlessOutput {
+        exit_code: output.status.code(),
+        text: text.trim().to_string(),
+        stdout,
+        stderr,
+        usage,
+    };
+    if !output.success() {
+        log::warn!(
+            "{agent_type} returned non-zero exit status in headless run: code={}, stderr='{}'",
+            output.exit_code.unwrap_or(-1),
+            output.stderr.trim()
+        );
+    }
+    Ok(Some(output))
+}
+
+/// A one-shot task for a session's agent that needs no files, like writing a commit message.
+pub struct HeadlessTask<'a> {
+    /// What the task produces, for logs, e.g. "commit message"
+    pub task: &'a str,
+    pub session_id: &'a str,
+    pub agent_type: &'a str,
+    pub prompt: &'a str,
+    pub cli_args: Option<&'a str>,
+    pub env_vars: &'a [(String, String)],
+    pub binary_path: Option<&'a str>,
+}
+
+/// Run `task` headless in a scratch directory and read the agent's answer with `parse`.
+/// `None` when the agent has no headless mode, the run failed or `parse` found nothing
+/// usable.
+pub async fn run_headless_task<T>(
+    task: HeadlessTask<'_>,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Result<Option<T>> {
+    let HeadlessTask {
+        task,
+        session_id,
+        agent_type,
+        prompt,
+        cli_args,
+        env_vars,
+        binary_path,
+    } = task;
+
+    let run_dir =
+        std::env::temp_dir().join(format!("schaltwerk_headless_{}", uuid::Uuid::new_v4()));
+    if let Err(e) = std::fs::create_dir_all(&run_dir) {
+        log::warn!("Failed to create temp directory for the {task}: {e}");
+    }
+
+    log::info!("Generating {task} with {agent_type} for session_id '{session_id}'");
+    let output = run_agent_headless(HeadlessRun {
+        agent_type,
+        prompt,
+        working_dir: &run_dir,
+        cli_args,
+        env_vars,
+        binary_path,
+        timeout: HEADLESS_TIMEOUT,
+    })
+    .await;
+    let _ = std::fs::remove_dir_all(&run_dir);
+
+    let Some(output) = output?.filter(|output| output.success()) else {
+        return Ok(None);
+    };
+    let parsed = parse(&output.text);
+    if parsed.is_none() {
+        log::warn!("{agent_type} produced no usable {task}");
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_claude_result_and_usage() {
+        let stdout = r#"{"type":"result","subtype":"success","is_error":false,"result":"fix-login","total_cost_usd":0.0123,"usage":{"input_tokens":12,"cache_creation_input_tokens":300,"cache_read_input_tokens":4000,"output_tokens":7}}"#;
+        let (text, usage) = parse_claude_json(stdout).unwrap();
+        assert_eq!(text, "fix-login");
+        assert_eq!(
+            usage,
+            Some(AgentUsage {
+                input_tokens: 12,
+                output_tokens: 7,
+                cache_read_tokens: 4000,
+                cache_write_tokens: 300,
+                cost_usd: 0.0123,
+            })
+        );
+
+        assert_eq!(
+            parse_claude_json(r#""just text""#),
+            Some(("just text".to_string(), None))
+        );
+        assert_eq!(parse_claude_json("plain answer"), None);
+    }
+
+    #[tokio::test]
+    async fn agents_without_headless_mode_are_skipped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = run_agent_headless(HeadlessRun {
+            agent_type: "aider",
+            prompt: "hello",
+            working_dir: dir.path(),
+            cli_args: None,
+            env_vars: &[],
+            binary_path: None,
+            timeout: HEADLESS_TIMEOUT,
+        })
+        .await
+        .unwrap();
+        assert!(output.is_none());
+        assert!(!supports_headless("aider"));
+        assert!(supports_headless("codex"));
+    }
+
+    #[cfg(unix)]
+    fn fake_claude(dir: &Path, script: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let binary = dir.join("claude");
+        std::fs::write(&binary, format!("#!/bin/sh\n{script}")).unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        binary.to_string_lossy().to_string()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn captures_output_of_a_failed_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let binary = fake_claude(
+            dir.path(),
+            "echo '{\"result\":\"partial\"}'\necho 'rate limited' >&2\nexit 3\n",
+        );
+
+        let output = run_agent_headless(HeadlessRun {
+            agent_type: "claude",
+            prompt: "hello",
+            working_dir: dir.path(),
+            cli_args: None,
+            env_vars: &[],
+            binary_path: Some(&binary),
+            timeout: HEADLESS_TIMEOUT,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!output.success());
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.text, "partial");
+        assert_eq!(output.stderr.trim(), "rate limited");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn headless_tasks_only_parse_successful_answers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let task = |binary_path| HeadlessTask {
+            task: "answer",
+            session_id: "demo",
+            agent_type: "claude",
+            prompt: "hello",
+            cli_args: None,
+            env_vars: &[],
+            binary_path,
+        };
+        let parse = |text: &str| (!text.is_empty()).then(|| text.to_uppercase());
+
+        let binary = fake_claude(dir.path(), "echo '{\"result\":\"done\"}'\n");
+        let answer = run_headless_task(task(Some(&binary)), parse).await.unwrap();
+        assert_eq!(answer.as_deref(), Some("DONE"));
+
+        let binary = fake_claude(dir.path(), "echo '{\"result\":\"done\"}'\nexit 1\n");
+        let answer = run_headless_task(task(Some(&binary)), parse).await.unwrap();
+        assert_eq!(answer, None);
+
+        let binary = fake_claude(dir.path(), "echo '{\"result\":\"\"}'\n");
+        let answer = run_headless_task(task(Some(&binary)), parse).await.unwrap();
+        assert_eq!(answer, None);
+    }
+}
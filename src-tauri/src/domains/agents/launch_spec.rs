@@ -1,3 +1,5 @@
+use super::format_binary_invocation;
+use super::manifest::AgentDefinition;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -29,14 +31,66 @@ impl AgentLaunchSpec {
         self
     }
 
+    /// Run the agent with `model` through the manifest's `model_flag`, placed right after
+    /// the binary so it precedes prompts and subcommands, or its `model_env`.
+    pub fn with_model(mut self, manifest: &AgentDefinition, model: Option<&str>) -> Self {
+        let Some(model) = model.map(str::trim).filter(|model| !model.is_empty()) else {
+            return self;
+        };
+        if let Some(flag) = manifest.model_flag.as_deref() {
+            match binary_end(&self.shell_command) {
+                Some(end) => self
+                    .shell_command
+                    .insert_str(end, &format!(" {flag} {}", format_binary_invocation(model))),
+                None => log::warn!(
+                    "Could not locate the {} binary to pass model '{model}'",
+                    manifest.id
+                ),
+            }
+        } else if let Some(env) = manifest.model_env.as_deref() {
+            self.env_vars.insert(env.to_string(), model.to_string());
+        }
+        self
+    }
+
     pub fn format_for_shell(&self) -> String {
         self.shell_command.clone()
     }
 }
 
+/// Byte offset just past the binary in `cd <dir> && [echo "<prompt>" | ]<binary> ...`,
+/// i.e. the end of the first word after the last unquoted `&&` or `|`.
+fn binary_end(command: &str) -> Option<usize> {
+    let bytes = command.as_bytes();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut segment_start = None;
+    for (i, &byte) in bytes.iter().enumerate() {
+        match quote {
+            _ if escaped => escaped = false,
+            Some(b'"') if byte == b'\\' => escaped = true,
+            Some(q) if byte == q => quote = None,
+            Some(_) => {}
+            None if byte == b'"' || byte == b'\'' => quote = Some(byte),
+            None if bytes[i..].starts_with(b" && ") => segment_start = Some(i + 4),
+            None if bytes[i..].starts_with(b" | ") => segment_start = Some(i + 3),
+            None => {}
+        }
+    }
+
+    let start = segment_start?;
+    let rest = &command[start..];
+    let len = match rest.chars().next()? {
+        q @ ('"' | '\'') => rest[1..].find(q)? + 2,
+        _ => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+    };
+    Some(start + len)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domains::agents::manifest::AgentManifest;
     use std::path::Path;
 
     #[test]
@@ -85,4 +139,48 @@ mod tests {
 
         assert_eq!(spec.format_for_shell(), "cd /test && claude --flag");
     }
+
+    #[test]
+    fn test_with_model_places_flag_after_binary() {
+        let claude = AgentManifest::get("claude").unwrap();
+        let spec = AgentLaunchSpec::new(
+            r#"cd "/path/a && b" && "/opt/my tools/claude" --dangerously-skip-permissions "fix | it""#
+                .to_string(),
+            Path::new("/test").to_path_buf(),
+        )
+        .with_model(claude, Some("opus"));
+        assert_eq!(
+            spec.shell_command,
+            r#"cd "/path/a && b" && "/opt/my tools/claude" --model opus --dangerously-skip-permissions "fix | it""#
+        );
+
+        let codex = AgentManifest::get("codex").unwrap();
+        let spec = AgentLaunchSpec::new(
+            "cd /w && codex --sandbox workspace-write resume abc".to_string(),
+            Path::new("/w").to_path_buf(),
+        )
+        .with_model(codex, Some("o3"));
+        assert_eq!(
+            spec.shell_command,
+            "cd /w && codex --model o3 --sandbox workspace-write resume abc"
+        );
+
+        let piped = r#"cd /w && echo "a && b" | amp --dangerously-allow-all"#;
+        assert_eq!(binary_end(piped), piped.find(" --dangerously"));
+    }
+
+    #[test]
+    fn test_with_model_uses_env_without_flag() {
+        let goose = AgentManifest::get("goose").unwrap();
+        let spec = AgentLaunchSpec::new(
+            "cd /w && goose session --name s".to_string(),
+            Path::new("/w").to_path_buf(),
+        )
+        .with_model(goose, Some("gpt-4o"));
+        assert_eq!(spec.shell_command, "cd /w && goose session --name s");
+        assert_eq!(
+            spec.env_vars.get("GOOSE_MODEL").map(String::as_str),
+            Some("gpt-4o")
+        );
+    }
 }
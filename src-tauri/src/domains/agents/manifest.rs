@@ -1,7 +1,47 @@
+use super::custom;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::LazyLock;
 
+/// How an agent receives its initial prompt on launch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptMechanism {
+    /// Quoted trailing positional argument: `agent "prompt"`
+    #[default]
+    Positional,
+    /// Passed through `prompt_flag`: `agent --flag "prompt"`
+    Flag,
+    /// Piped through stdin: `echo "prompt" | agent`
+    Stdin,
+    /// Typed into the terminal once the agent is ready (see `ready_marker`)
+    Terminal,
+    /// Agent does not accept an initial prompt
+    None,
+}
+
+/// Output fragments telling what an agent is doing, see [`super::status`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusMarkers {
+    #[serde(default)]
+    pub thinking: Vec<String>,
+    #[serde(default)]
+    pub editing: Vec<String>,
+    #[serde(default)]
+    pub approval: Vec<String>,
+    #[serde(default)]
+    pub done: Vec<String>,
+}
+
+impl StatusMarkers {
+    pub fn is_empty(&self) -> bool {
+        self.thinking.is_empty()
+            && self.editing.is_empty()
+            && self.approval.is_empty()
+            && self.done.is_empty()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AgentDefinition {
     pub id: String,
@@ -12,6 +52,53 @@ pub struct AgentDefinition {
     pub supports_resume: bool,
     #[serde(default)]
     pub ready_marker: Option<String>,
+    #[serde(default)]
+    pub prompt_mechanism: PromptMechanism,
+    #[serde(default)]
+    pub prompt_flag: Option<String>,
+    /// Flag appended when the user opts into skipping permission prompts
+    #[serde(default)]
+    pub permission_flag: Option<String>,
+    /// Flag used to resume a previous conversation, followed by the session id
+    #[serde(default)]
+    pub resume_flag: Option<String>,
+    /// Flag selecting the model, followed by the model id
+    #[serde(default)]
+    pub model_flag: Option<String>,
+    /// Environment variable selecting the model, for agents without a model flag
+    #[serde(default)]
+    pub model_env: Option<String>,
+    /// Environment variables that must be present for the agent to start
+    #[serde(default)]
+    pub required_env: Vec<String>,
+    /// Output fragments indicating the agent is idle and waiting for input
+    #[serde(default)]
+    pub idle_markers: Vec<String>,
+    #[serde(default)]
+    pub status_markers: StatusMarkers,
+}
+
+impl AgentDefinition {
+    pub fn supports_model(&self) -> bool {
+        self.model_flag.is_some() || self.model_env.is_some()
+    }
+
+    /// Required environment variables missing from both `env` and the process environment
+    pub fn missing_env<'a>(&'a self, env: &[(String, String)]) -> Vec<&'a str> {
+        self.required_env
+            .iter()
+            .filter(|name| {
+                let provided = env
+                    .iter()
+                    .any(|(key, value)| key == *name && !value.trim().is_empty());
+                !provided
+                    && std::env::var(name.as_str())
+                        .map(|v| v.trim().is_empty())
+                        .unwrap_or(true)
+            })
+            .map(|name| name.as_str())
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,19 +116,61 @@ static AGENT_MANIFEST: LazyLock<HashMap<String, AgentDefinition>> = LazyLock::ne
 pub struct AgentManifest;
 
 impl AgentManifest {
+    /// Built-in agent or, failing that, a registered custom agent
     pub fn get(agent_id: &str) -> Option<&'static AgentDefinition> {
-        AGENT_MANIFEST.get(agent_id)
+        AGENT_MANIFEST
+            .get(agent_id)
+            .or_else(|| custom::custom_agent_definition(agent_id))
     }
 
+    /// Agents from `agents_manifest.toml`, without custom agents
     pub fn all() -> &'static HashMap<String, AgentDefinition> {
         &AGENT_MANIFEST
     }
 
+    pub(super) fn builtin(agent_id: &str) -> Option<&'static AgentDefinition> {
+        AGENT_MANIFEST.get(agent_id)
+    }
+
     pub fn supported_agents() -> Vec<String> {
-        let mut agents: Vec<_> = AGENT_MANIFEST.keys().cloned().collect();
+        let mut agents: Vec<_> = AGENT_MANIFEST
+            .keys()
+            .cloned()
+            .chain(
+                custom::custom_agent_definitions()
+                    .into_iter()
+                    .map(|def| def.id.clone()),
+            )
+            .collect();
         agents.sort();
+        agents.dedup();
         agents
     }
+
+    /// Agents that launch a CLI binary (everything except the plain terminal)
+    pub fn launchable_agents() -> Vec<String> {
+        Self::supported_agents()
+            .into_iter()
+            .filter(|id| id != "terminal")
+            .collect()
+    }
+
+    /// Resolve a definition from an executable name or path (`/usr/local/bin/qwen` -> qwen)
+    pub fn find_by_binary(binary: &str) -> Option<&'static AgentDefinition> {
+        let file_name = std::path::Path::new(binary.trim())
+            .file_name()
+            .and_then(|n| n.to_str())?;
+        let mut matches: Vec<_> = AGENT_MANIFEST
+            .values()
+            .filter(|def| def.binary_name == file_name)
+            .collect();
+        matches.sort_by(|a, b| a.id.cmp(&b.id));
+        matches.into_iter().next().or_else(|| {
+            custom::custom_agent_definitions()
+                .into_iter()
+                .find(|def| def.binary_name == file_name)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -103,6 +232,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_aider_definition() {
+        let aider = AgentManifest::get("aider").expect("Aider manifest entry missing");
+        assert_eq!(aider.binary_name, "aider");
+        assert!(aider.auto_send_initial_command);
+        assert!(aider.supports_resume);
+        assert_eq!(aider.prompt_mechanism, PromptMechanism::Terminal);
+        assert_eq!(aider.permission_flag.as_deref(), Some("--yes-always"));
+        assert!(aider.resume_flag.is_none());
+    }
+
     #[test]
     fn test_terminal_definition() {
         let terminal = AgentManifest::get("terminal").expect("Terminal manifest entry missing");
@@ -114,6 +254,62 @@ mod tests {
         assert!(!terminal.supports_resume);
     }
 
+    #[test]
+    fn test_capability_fields() {
+        let claude = AgentManifest::get("claude").unwrap();
+        assert_eq!(
+            claude.permission_flag.as_deref(),
+            Some("--dangerously-skip-permissions")
+        );
+        assert_eq!(claude.resume_flag.as_deref(), Some("-r"));
+        assert_eq!(claude.prompt_mechanism, PromptMechanism::Positional);
+
+        let gemini = AgentManifest::get("gemini").unwrap();
+        assert_eq!(gemini.prompt_mechanism, PromptMechanism::Flag);
+        assert_eq!(gemini.prompt_flag.as_deref(), Some("--prompt-interactive"));
+
+        let droid = AgentManifest::get("droid").unwrap();
+        assert_eq!(droid.prompt_mechanism, PromptMechanism::Positional);
+
+        let goose = AgentManifest::get("goose").unwrap();
+        assert_eq!(goose.prompt_flag.as_deref(), Some("--text"));
+        assert!(goose.supports_resume);
+        assert!(goose.permission_flag.is_none());
+
+        let terminal = AgentManifest::get("terminal").unwrap();
+        assert_eq!(terminal.prompt_mechanism, PromptMechanism::None);
+    }
+
+    #[test]
+    fn test_find_by_binary() {
+        assert_eq!(
+            AgentManifest::find_by_binary("/usr/local/bin/qwen").map(|d| d.id.as_str()),
+            Some("qwen")
+        );
+        assert_eq!(
+            AgentManifest::find_by_binary("amp").map(|d| d.id.as_str()),
+            Some("amp")
+        );
+        assert!(AgentManifest::find_by_binary("/usr/bin/unknown-agent").is_none());
+        assert!(!AgentManifest::launchable_agents().contains(&"terminal".to_string()));
+    }
+
+    #[test]
+    fn test_missing_env() {
+        let mut def = AgentManifest::get("claude").unwrap().clone();
+        def.required_env = vec!["SCHALTWERK_TEST_REQUIRED_ENV_UNSET".to_string()];
+        assert_eq!(
+            def.missing_env(&[]),
+            vec!["SCHALTWERK_TEST_REQUIRED_ENV_UNSET"]
+        );
+
+        let provided = vec![(
+            "SCHALTWERK_TEST_REQUIRED_ENV_UNSET".to_string(),
+            "value".to_string(),
+        )];
+        assert!(def.missing_env(&provided).is_empty());
+    }
+
     #[test]
     fn test_nonexistent_agent() {
         assert!(AgentManifest::get("nonexistent").is_none());
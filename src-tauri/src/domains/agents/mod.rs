@@ -1,16 +1,26 @@
 pub mod adapter;
+pub mod aider;
 pub mod amp;
 pub mod claude;
 pub mod codex;
 pub mod command_parser;
+pub mod commit_message;
+pub mod custom;
 pub mod droid;
 pub mod gemini;
+pub mod goose;
+pub mod handoff;
+pub mod headless;
 pub mod launch_spec;
 pub mod manifest;
 pub mod naming;
 pub mod opencode;
+pub mod progress;
 pub mod qwen;
+pub mod spec_summary;
+pub mod status;
 pub mod unified;
+pub mod usage;
 
 pub use adapter::{AgentAdapter, AgentLaunchContext};
 pub use command_parser::parse_agent_command;
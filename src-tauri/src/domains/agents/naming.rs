@@ -7,9 +7,9 @@ use crate::{
     },
 };
 
-use anyhow::{anyhow, Result};
+use super::headless::{run_agent_headless, HeadlessRun, HEADLESS_TIMEOUT};
+use anyhow::Result;
 use std::path::Path;
-use tokio::process::Command;
 
 pub struct SessionRenameContext<'a> {
     pub db: &'a Database,
@@ -60,7 +60,7 @@ pub fn sanitize_name(input: &str) -> String {
     trimmed.chars().take(30).collect()
 }
 
-fn ansi_strip(input: &str) -> String {
+pub(super) fn ansi_strip(input: &str) -> String {
     let mut out = String::with_capacity(input.len());
     let mut chars = input.chars().peekable();
     while let Some(ch) = chars.next() {
@@ -286,346 +286,89 @@ Respond with JSON: {{"name": "short-kebab-case-name"}}"#
         run_dir.display()
     );
 
-    // Handle Codex name generation
-    if agent_type == "codex" {
-        log::info!("Attempting to generate name with codex");
-        let mut args: Vec<String> = vec![
-            "exec".into(),
-            "--sandbox".into(),
-            "workspace-write".into(),
-            "--skip-git-repo-check".into(),
-            "--json".into(),
-        ];
-        if let Some(cli) = cli_args {
-            let mut extra = shell_words::split(cli).unwrap_or_else(|_| vec![cli.to_string()]);
-            fix_codex_single_dash_long_flags(&mut extra);
-            reorder_codex_model_after_profile(&mut extra);
-            // Codex `exec` subcommand does not accept the interactive-only `--search` flag.
-            // Filter it out to avoid exit code 2 during name generation.
-            extra.retain(|a| a != "--search" && a != "-search");
-            args.extend(extra);
-        }
-        // Capture only the last assistant message to a temp file for reliable parsing
-        let tmp_file = std::env::temp_dir().join(format!("schaltwerk_codex_name_{session_id}.txt"));
-        args.push("--output-last-message".into());
-        args.push(tmp_file.to_string_lossy().to_string());
-        args.push(prompt_plain.clone());
-
-        log::info!("codex exec args for namegen: {args:?}");
-        let output = Command::new("codex")
-            .args(&args)
-            .current_dir(&run_dir)
-            .env("NO_COLOR", "1")
-            .env("CLICOLOR", "0")
-            .env("TERM", "dumb")
-            .env("CI", "1")
-            .env("NONINTERACTIVE", "1")
-            .stdin(std::process::Stdio::null())
-            .envs(env_vars.iter().cloned())
-            .output()
-            .await;
-
-        let output = match output {
-            Ok(output) => output,
-            Err(e) => {
-                log::warn!("Failed to execute codex for name generation: {e}");
-                return Ok(None);
-            }
-        };
+    log::info!("Attempting to generate name with {agent_type}");
+    // OpenCode answers more reliably in JSON
+    let prompt = if agent_type == "opencode" {
+        &prompt_json
+    } else {
+        &prompt_plain
+    };
+    let output = run_agent_headless(HeadlessRun {
+        agent_type,
+        prompt,
+        working_dir: &run_dir,
+        cli_args,
+        env_vars,
+        binary_path,
+        timeout: HEADLESS_TIMEOUT,
+    })
+    .await;
+    // Clean up temp directory
+    let _ = std::fs::remove_dir_all(&unique_temp_dir);
 
-        if output.status.success() {
-            // Prefer the last message file if present
-            let candidate = std::fs::read_to_string(&tmp_file)
-                .ok()
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .or_else(|| {
-                    let stdout = ansi_strip(&String::from_utf8_lossy(&output.stdout));
-                    log::debug!("codex stdout: {stdout}");
-                    stdout
-                        .lines()
-                        .map(|l| l.trim())
-                        .find(|line| {
-                            !line.is_empty()
-                                && !line.contains(' ')
-                                && line.chars().all(|c| {
-                                    c.is_ascii_lowercase() || c == '-' || c.is_ascii_digit()
-                                })
-                                && line.len() <= 30
-                        })
-                        .map(|s| s.to_string())
-                        .or_else(|| {
-                            let raw = stdout.trim();
-                            if !raw.is_empty() {
-                                Some(raw.to_string())
-                            } else {
-                                None
-                            }
-                        })
-                });
-
-            if let Some(result) = candidate {
-                log::info!("codex returned name candidate: {result}");
-                let name = sanitize_name(&result);
-                log::info!("Sanitized name: {name}");
-                if !name.is_empty() {
-                    db.update_session_display_name(session_id, &name)?;
-                    log::info!(
-                        "Updated database with display_name '{name}' for session_id '{session_id}'"
-                    );
-                    return Ok(Some(name));
-                }
-            } else {
-                log::warn!("codex produced no usable output for naming");
-            }
-        } else {
-            let code = output.status.code().unwrap_or(-1);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            log::warn!(
-                "codex returned non-zero exit status: code={code}, stderr='{}', stdout='{}'",
-                stderr.trim(),
-                stdout.trim()
-            );
+    let output = match output {
+        Ok(Some(output)) => output,
+        Ok(None) => {
+            log::info!("Agent type '{agent_type}' has no headless mode, not generating a name");
+            return Ok(None);
         }
-        // Clean up temp directory
-        let _ = std::fs::remove_dir_all(&unique_temp_dir);
-        return Ok(None);
-    }
-
-    // Handle OpenCode name generation
-    if agent_type == "opencode" {
-        log::info!("Attempting to generate name with opencode");
-
-        // OpenCode uses the run command for non-interactive usage
-        let binary = super::opencode::resolve_opencode_binary();
-        let mut command = Command::new(&binary);
-        command.args(["run", &prompt_json]);
-        command.current_dir(&run_dir);
-        command.stdin(std::process::Stdio::null());
-        for (key, value) in build_namegen_env(env_vars) {
-            command.env(key, value);
+        // Claude is the default agent, so failing to run it is an error rather than a skip
+        Err(e) if agent_type == "claude" => {
+            log::error!("Name generation with claude failed: {e}");
+            return Err(e);
         }
-
-        let output = command.output().await;
-
-        let output = match output {
-            Ok(output) => {
-                log::debug!("opencode executed successfully");
-                output
-            }
-            Err(e) => {
-                log::warn!("Failed to execute opencode: {e}");
-                return Ok(None);
-            }
-        };
-
-        if output.status.success() {
-            let stdout = ansi_strip(&String::from_utf8_lossy(&output.stdout));
-            log::debug!("opencode stdout: {stdout}");
-
-            let candidate = parse_opencode_output(&stdout);
-
-            if let Some(result) = candidate {
-                log::info!("opencode returned name candidate: {result}");
-                let name = sanitize_name(&result);
-                log::info!("Sanitized name: {name}");
-
-                if !name.is_empty() {
-                    db.update_session_display_name(session_id, &name)?;
-                    log::info!(
-                        "Updated database with display_name '{name}' for session_id '{session_id}'"
-                    );
-                    return Ok(Some(name));
-                }
-            } else {
-                log::warn!("opencode produced no usable output for naming");
-            }
-        } else {
-            let code = output.status.code().unwrap_or(-1);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            log::warn!(
-                "opencode returned non-zero exit status: code={code}, stderr='{}', stdout='{}'",
-                stderr.trim(),
-                stdout.trim()
-            );
+        Err(e) => {
+            log::warn!("Name generation with {agent_type} failed: {e}");
+            return Ok(None);
         }
-
-        // Clean up temp directory
-        let _ = std::fs::remove_dir_all(&unique_temp_dir);
+    };
+    if !output.success() {
         return Ok(None);
     }
+    log::debug!("{agent_type} answer: {}", output.text);
+
+    let candidate = match agent_type {
+        "opencode" => parse_opencode_output(&output.text),
+        "gemini" => find_name_line(&output.text, |line| {
+            // Has hyphens or is very short
+            line.contains('-') || line.len() <= 10
+        }),
+        "codex" => find_name_line(&output.text, |_| true)
+            .or_else(|| Some(output.text.clone()).filter(|text| !text.is_empty())),
+        _ => Some(output.text.clone()).filter(|text| !text.is_empty()),
+    };
 
-    // Handle Gemini name generation
-    if agent_type == "gemini" {
-        log::info!("Attempting to generate name with gemini");
-
-        let binary = super::gemini::resolve_gemini_binary();
-        let mut command = Command::new(&binary);
-        command.args(["--prompt", prompt_plain.as_str()]);
-        command.current_dir(&run_dir);
-        command.stdin(std::process::Stdio::null());
-        for (key, value) in build_namegen_env(env_vars) {
-            command.env(key, value);
-        }
-
-        let output = command.output().await;
-
-        let output = match output {
-            Ok(output) => {
-                log::debug!("gemini executed successfully");
-                output
-            }
-            Err(e) => {
-                log::warn!("Failed to execute gemini: {e}");
-                return Ok(None);
-            }
-        };
-
-        if output.status.success() {
-            let stdout = ansi_strip(&String::from_utf8_lossy(&output.stdout));
-            log::debug!("gemini stdout: {stdout}");
-
-            // Gemini returns plain text, so we look for a kebab-case name in the output
-            // Split by newlines and find the first line that looks like a kebab-case name
-            let candidate = stdout
-                .lines()
-                .map(|line| line.trim())
-                .filter(|line| !line.is_empty())
-                .filter(|line| {
-                    line.chars()
-                        .all(|c| c.is_ascii_lowercase() || c == '-' || c.is_ascii_digit())
-                })
-                .filter(|line| line.contains('-') || line.len() <= 10) // Has hyphens or very short
-                .filter(|line| line.len() <= 30) // Reasonable length
-                .find(|_| true) // Get first match
-                .map(|s| s.to_string());
-
-            if let Some(result) = candidate {
-                log::info!("gemini returned name candidate: {result}");
-                let name = sanitize_name(&result);
-                log::info!("Sanitized name: {name}");
-
-                if !name.is_empty() {
-                    db.update_session_display_name(session_id, &name)?;
-                    log::info!(
-                        "Updated database with display_name '{name}' for session_id '{session_id}'"
-                    );
-                    return Ok(Some(name));
-                }
-            } else {
-                log::warn!("gemini produced no usable output for naming");
-            }
-        } else {
-            let code = output.status.code().unwrap_or(-1);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            log::warn!(
-                "gemini returned non-zero exit status: code={code}, stderr='{}', stdout='{}'",
-                stderr.trim(),
-                stdout.trim()
-            );
-        }
-
-        // Clean up temp directory
-        let _ = std::fs::remove_dir_all(&unique_temp_dir);
+    let Some(result) = candidate else {
+        log::warn!("{agent_type} produced no usable output for naming");
         return Ok(None);
-    }
-
-    // Use Claude only if claude was selected (not as a fallback)
-    if agent_type != "claude" {
-        log::info!("Agent type is '{agent_type}', not generating name with claude");
-        // Clean up temp directory
-        let _ = std::fs::remove_dir_all(&unique_temp_dir);
+    };
+    log::info!("{agent_type} returned name candidate: {result}");
+    let name = sanitize_name(&result);
+    log::info!("Sanitized name: {name}");
+    if name.is_empty() {
+        log::warn!("No name could be generated for session_id '{session_id}'");
         return Ok(None);
     }
 
-    log::info!("Attempting to generate name with claude");
-    let claude_args = build_claude_namegen_args(&prompt_plain, cli_args);
-    let binary = binary_path.unwrap_or("claude");
-    log::debug!("Claude namegen using binary: {binary}");
-    let mut command = Command::new(binary);
-    command.args(&claude_args);
-    command.current_dir(&run_dir);
-    command.stdin(std::process::Stdio::null());
-
-    for (key, value) in build_namegen_env(env_vars) {
-        command.env(key, value);
-    }
-
-    let output = command.output().await;
-
-    let output = match output {
-        Ok(output) => {
-            log::debug!("claude executed successfully");
-            output
-        }
-        Err(e) => {
-            log::error!("Failed to execute claude: {e}");
-            return Err(anyhow!("Failed to execute claude: {e}"));
-        }
-    };
-
-    if output.status.success() {
-        let stdout = ansi_strip(&String::from_utf8_lossy(&output.stdout));
-        log::debug!("claude stdout: {stdout}");
-
-        // Try JSON first, then fallback to raw text
-        let parsed_json: Result<serde_json::Value, _> = serde_json::from_str(&stdout);
-        let candidate = if let Ok(v) = parsed_json {
-            v.as_str()
-                .or_else(|| v.get("result").and_then(|x| x.as_str()))
-                .map(|s| s.to_string())
-        } else {
-            None
-        }
-        .or_else(|| {
-            let raw = stdout.trim();
-            if !raw.is_empty() {
-                Some(raw.to_string())
-            } else {
-                None
-            }
-        });
-
-        if let Some(result) = candidate {
-            log::info!("claude returned name candidate: {result}");
-            let name = sanitize_name(&result);
-            log::info!("Sanitized name: {name}");
-
-            if !name.is_empty() {
-                db.update_session_display_name(session_id, &name)?;
-                log::info!(
-                    "Updated database with display_name '{name}' for session_id '{session_id}'"
-                );
-                // Clean up temp directory
-                let _ = std::fs::remove_dir_all(&unique_temp_dir);
-                return Ok(Some(name));
-            } else {
-                log::warn!("Sanitized name is empty");
-            }
-        } else {
-            log::warn!("Claude produced no usable output for naming");
-        }
-    } else {
-        let code = output.status.code().unwrap_or(-1);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        log::warn!(
-            "claude returned non-zero exit status: code={code}, stderr='{}', stdout='{}'",
-            stderr.trim(),
-            stdout.trim()
-        );
-    }
+    db.update_session_display_name(session_id, &name)?;
+    log::info!("Updated database with display_name '{name}' for session_id '{session_id}'");
+    Ok(Some(name))
+}
 
-    log::warn!("No name could be generated for session_id '{session_id}'");
-    // Clean up temp directory
-    let _ = std::fs::remove_dir_all(&unique_temp_dir);
-    Ok(None)
+/// First line of the answer that is a plain kebab-case name of reasonable length.
+fn find_name_line(text: &str, accept: impl Fn(&str) -> bool) -> Option<String> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && line.len() <= 30)
+        .filter(|line| {
+            line.chars()
+                .all(|c| c.is_ascii_lowercase() || c == '-' || c.is_ascii_digit())
+        })
+        .find(|line| accept(line))
+        .map(|line| line.to_string())
 }
 
-fn build_claude_namegen_args(prompt_plain: &str, cli_args: Option<&str>) -> Vec<String> {
+pub(super) fn build_claude_namegen_args(prompt_plain: &str, cli_args: Option<&str>) -> Vec<String> {
     let mut user_args: Vec<String> = Vec::new();
 
     if let Some(raw) = cli_args {
@@ -694,7 +437,7 @@ pub fn parse_opencode_output(stdout: &str) -> Option<String> {
     candidate
 }
 
-fn build_namegen_env(env_vars: &[(String, String)]) -> Vec<(String, String)> {
+pub(super) fn build_namegen_env(env_vars: &[(String, String)]) -> Vec<(String, String)> {
     let mut combined = vec![
         ("NO_COLOR".to_string(), "1".to_string()),
         ("CLICOLOR".to_string(), "0".to_string()),
@@ -712,7 +455,7 @@ fn build_namegen_env(env_vars: &[(String, String)]) -> Vec<(String, String)> {
 // NOTE: These functions are duplicated here because the naming module needs to apply
 // the same Codex-specific flag normalization when invoking Codex for name generation.
 // Any changes to these functions should be synchronized with the versions in schaltwerk_core.rs
-fn fix_codex_single_dash_long_flags(args: &mut [String]) {
+pub(super) fn fix_codex_single_dash_long_flags(args: &mut [String]) {
     for a in args.iter_mut() {
         if a.starts_with("--") {
             continue;
@@ -736,7 +479,7 @@ fn fix_codex_single_dash_long_flags(args: &mut [String]) {
     }
 }
 
-fn reorder_codex_model_after_profile(args: &mut Vec<String>) {
+pub(super) fn reorder_codex_model_after_profile(args: &mut Vec<String>) {
     let mut without_model = Vec::with_capacity(args.len());
     let mut model_flags = Vec::new();
     let mut i = 0;
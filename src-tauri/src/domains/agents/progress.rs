@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentTodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentTodo {
+    pub content: String,
+    pub status: AgentTodoStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_form: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentProgress {
+    pub agent_session_id: String,
+    pub todos: Vec<AgentTodo>,
+    pub completed: usize,
+    pub total: usize,
+    pub updated_at: i64,
+}
+
+impl AgentProgress {
+    fn from_todos(agent_session_id: String, todos: Vec<AgentTodo>, updated_at: i64) -> Self {
+        let completed = todos
+            .iter()
+            .filter(|t| t.status == AgentTodoStatus::Completed)
+            .count();
+        let total = todos.len();
+        Self {
+            agent_session_id,
+            todos,
+            completed,
+            total,
+            updated_at,
+        }
+    }
+
+    pub fn percentage(&self) -> Option<f64> {
+        if self.total == 0 {
+            None
+        } else {
+            Some(self.completed as f64 * 100.0 / self.total as f64)
+        }
+    }
+
+    pub fn current_task(&self) -> Option<&str> {
+        self.todos
+            .iter()
+            .find(|t| t.status == AgentTodoStatus::InProgress)
+            .map(|t| t.active_form.as_deref().unwrap_or(&t.content))
+    }
+}
+
+/// Raw todo entry as written by Claude's TodoWrite tool (camelCase keys)
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawClaudeTodo {
+    content: String,
+    status: String,
+    #[serde(default)]
+    active_form: Option<String>,
+}
+
+fn convert_todos(raw: Vec<RawClaudeTodo>) -> Vec<AgentTodo> {
+    raw.into_iter()
+        .filter_map(|todo| {
+            let status = match todo.status.as_str() {
+                "pending" => AgentTodoStatus::Pending,
+                "in_progress" => AgentTodoStatus::InProgress,
+                "completed" => AgentTodoStatus::Completed,
+                other => {
+                    log::debug!("Claude progress: ignoring todo with unknown status '{other}'");
+                    return None;
+                }
+            };
+            Some(AgentTodo {
+                content: todo.content,
+                status,
+                active_form: todo.active_form.filter(|s| !s.trim().is_empty()),
+            })
+        })
+        .collect()
+}
+
+struct ProgressCacheEntry {
+    /// Worktree of the session the file belongs to
+    worktree: PathBuf,
+    modified: SystemTime,
+    todos: Option<Vec<AgentTodo>>,
+}
+
+// Parsed todos keyed by source file; re-parsed only when the file's mtime changes
+static PROGRESS_CACHE: LazyLock<Mutex<HashMap<PathBuf, ProgressCacheEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn cached_parse(
+    worktree: &Path,
+    path: &Path,
+    parse: fn(&Path) -> Option<Vec<AgentTodo>>,
+) -> Option<(Vec<AgentTodo>, SystemTime)> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+
+    if let Ok(cache) = PROGRESS_CACHE.lock() {
+        if let Some(entry) = cache.get(path) {
+            if entry.modified == modified {
+                return entry.todos.clone().map(|t| (t, modified));
+            }
+        }
+    }
+
+    let todos = parse(path);
+    if let Ok(mut cache) = PROGRESS_CACHE.lock() {
+        cache.insert(
+            path.to_path_buf(),
+            ProgressCacheEntry {
+                worktree: worktree.to_path_buf(),
+                modified,
+                todos: todos.clone(),
+            },
+        );
+    }
+    todos.map(|t| (t, modified))
+}
+
+/// Drop cached checklists of sessions whose worktree is not in `worktrees`.
+pub fn retain_progress_cache(worktrees: &HashSet<&Path>) {
+    if let Ok(mut cache) = PROGRESS_CACHE.lock() {
+        cache.retain(|_, entry| worktrees.contains(entry.worktree.as_path()));
+    }
+}
+
+fn parse_todo_file(path: &Path) -> Option<Vec<AgentTodo>> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<Vec<RawClaudeTodo>>(&content) {
+        Ok(raw) => Some(convert_todos(raw)),
+        Err(err) => {
+            log::debug!(
+                "Claude progress: failed to parse todo file {}: {err}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Scan a Claude JSONL transcript and return the todos from the last TodoWrite tool call
+fn parse_transcript_todos(path: &Path) -> Option<Vec<AgentTodo>> {
+    let file = File::open(path).ok()?;
+    let reader = BufReader::new(file);
+    let mut latest: Option<Vec<AgentTodo>> = None;
+
+    for line in reader.lines().map_while(Result::ok) {
+        // Cheap pre-filter so we only deserialize lines carrying a TodoWrite call
+        if !line.contains("\"TodoWrite\"") {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(items) = value
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_array())
+        else {
+            continue;
+        };
+        for item in items {
+            let is_todo_write = item.get("type").and_then(|t| t.as_str()) == Some("tool_use")
+                && item.get("name").and_then(|n| n.as_str()) == Some("TodoWrite");
+            if !is_todo_write {
+                continue;
+            }
+            if let Some(todos) = item.get("input").and_then(|i| i.get("todos")) {
+                if let Ok(raw) = serde_json::from_value::<Vec<RawClaudeTodo>>(todos.clone()) {
+                    latest = Some(convert_todos(raw));
+                }
+            }
+        }
+    }
+
+    latest
+}
+
+fn to_unix(ts: SystemTime) -> i64 {
+    ts.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Read the current TODO checklist of the Claude session running in `worktree_path`.
+///
+/// Prefers Claude's dedicated todo store (`~/.claude/todos/<id>-agent-<id>.json`) and
+/// falls back to the last TodoWrite call in the session transcript.
+pub fn read_claude_progress(worktree_path: &Path) -> Option<AgentProgress> {
+    let session_id = super::claude::find_resumable_claude_session_fast(worktree_path)?;
+    let claude_dir = super::claude::claude_home_directory()?.join(".claude");
+
+    let todo_file = claude_dir
+        .join("todos")
+        .join(format!("{session_id}-agent-{session_id}.json"));
+    if let Some((todos, modified)) = cached_parse(worktree_path, &todo_file, parse_todo_file) {
+        return Some(AgentProgress::from_todos(
+            session_id,
+            todos,
+            to_unix(modified),
+        ));
+    }
+
+    let transcript = claude_dir
+        .join("projects")
+        .join(super::claude::sanitize_path_for_claude(worktree_path))
+        .join(format!("{session_id}.jsonl"));
+    cached_parse(worktree_path, &transcript, parse_transcript_todos)
+        .map(|(todos, modified)| AgentProgress::from_todos(session_id, todos, to_unix(modified)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn transcript_uses_last_todo_write() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("ses.jsonl");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, r#"{{"sessionId":"ses","type":"user"}}"#).unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"content":[{{"type":"tool_use","name":"TodoWrite","input":{{"todos":[{{"content":"A","status":"pending","activeForm":"Doing A"}}]}}}}]}}}}"#
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"content":[{{"type":"tool_use","name":"TodoWrite","input":{{"todos":[{{"content":"A","status":"completed","activeForm":"Doing A"}},{{"content":"B","status":"in_progress","activeForm":"Doing B"}}]}}}}]}}}}"#
+        )
+        .unwrap();
+
+        let todos = parse_transcript_todos(&path).expect("todos");
+        let progress = AgentProgress::from_todos("ses".into(), todos, 0);
+        assert_eq!(progress.total, 2);
+        assert_eq!(progress.completed, 1);
+        assert_eq!(progress.percentage(), Some(50.0));
+        assert_eq!(progress.current_task(), Some("Doing B"));
+    }
+
+    #[test]
+    fn progress_cache_forgets_removed_sessions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("todos.json");
+        fs::write(&path, r#"[{"content":"A","status":"pending"}]"#).unwrap();
+        let kept = tmp.path().join("kept");
+        let removed = tmp.path().join("removed");
+
+        assert!(cached_parse(&removed, &path, parse_todo_file).is_some());
+        retain_progress_cache(&HashSet::from([kept.as_path()]));
+        assert!(!PROGRESS_CACHE.lock().unwrap().contains_key(&path));
+    }
+
+    #[test]
+    fn todo_file_skips_unknown_statuses() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("todos.json");
+        fs::write(
+            &path,
+            r#"[{"content":"A","status":"completed"},{"content":"B","status":"weird"}]"#,
+        )
+        .unwrap();
+
+        let todos = parse_todo_file(&path).expect("todos");
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].status, AgentTodoStatus::Completed);
+        assert_eq!(todos[0].active_form, None);
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+
+use super::headless::{run_headless_task, HeadlessTask};
+
+/// Characters of the spec passed to the agent
+pub const SPEC_SUMMARY_INPUT_LIMIT: usize = 16_000;
+
+pub struct SpecSummaryContext<'a> {
+    pub session_id: &'a str,
+    pub agent_type: &'a str,
+    pub spec: &'a str,
+    pub cli_args: Option<String>,
+    pub env_vars: Vec<(String, String)>,
+    pub binary_path: Option<String>,
+}
+
+pub fn build_spec_summary_prompt(spec: &str) -> String {
+    let spec: String = spec.chars().take(SPEC_SUMMARY_INPUT_LIMIT).collect();
+    format!(
+        r#"IMPORTANT: Do not use any tools. Answer this message directly without searching or reading files.
+
+Summarize the task described below for a list of work items.
+
+Rules:
+- At most 3 sentences, plain text
+- State what will be built or changed and why, skip implementation details
+- Return ONLY the summary, no heading, quotes or explanation
+
+Task:
+{spec}
+
+Respond with just the summary:"#
+    )
+}
+
+/// The summary from an agent's answer, without a leading label or code fences.
+pub fn parse_spec_summary(text: &str) -> Option<String> {
+    let text: String = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim();
+    let text = text
+        .strip_prefix("Summary:")
+        .or_else(|| text.strip_prefix("**Summary:**"))
+        .unwrap_or(text);
+    let summary = text.trim().trim_matches('"').trim();
+    (!summary.is_empty()).then(|| summary.to_string())
+}
+
+/// Ask the session's agent for a short summary of `ctx.spec`.
+pub async fn summarize_spec(ctx: SpecSummaryContext<'_>) -> Result<Option<String>> {
+    let prompt = build_spec_summary_prompt(ctx.spec);
+    run_headless_task(
+        HeadlessTask {
+            task: "spec summary",
+            session_id: ctx.session_id,
+            agent_type: ctx.agent_type,
+            prompt: &prompt,
+            cli_args: ctx.cli_args.as_deref(),
+            env_vars: &ctx.env_vars,
+            binary_path: ctx.binary_path.as_deref(),
+        },
+        parse_spec_summary,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_includes_the_spec() {
+        let prompt = build_spec_summary_prompt("# Login\n\nAdd OAuth login.");
+        assert!(prompt.contains("Add OAuth login."));
+        assert!(prompt.contains("At most 3 sentences"));
+    }
+
+    #[test]
+    fn parse_spec_summary_drops_labels_and_fences() {
+        assert_eq!(
+            parse_spec_summary("Summary: Adds OAuth login so users skip passwords.").as_deref(),
+            Some("Adds OAuth login so users skip passwords.")
+        );
+        assert_eq!(
+            parse_spec_summary("```\n\"Moves config to TOML.\"\n```").as_deref(),
+            Some("Moves config to TOML.")
+        );
+        assert_eq!(parse_spec_summary("  \n"), None);
+    }
+}
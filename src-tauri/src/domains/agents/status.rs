@@ -0,0 +1,211 @@
+//! What an agent is doing, read from its terminal output. Each agent prints its own
+//! spinners, tool headers and confirmation prompts; the latest one seen decides the state.
+
+use super::manifest::{AgentManifest, StatusMarkers};
+use crate::domains::terminal::ansi::strip_ansi;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+// Output kept from the previous chunk so a marker split across reads is still found
+const CARRY_OVER_CHARS: usize = 96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentActivityState {
+    Thinking,
+    EditingFiles,
+    WaitingForApproval,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SessionAgentStatePayload {
+    pub session_id: String,
+    pub terminal_id: String,
+    pub state: AgentActivityState,
+}
+
+/// Used for agents whose manifest entry has no status markers; prompts most CLIs share
+static GENERIC_MARKERS: LazyLock<StatusMarkers> = LazyLock::new(|| StatusMarkers {
+    thinking: vec!["esc to interrupt".into(), "esc to cancel".into()],
+    editing: vec!["Applied edit to".into(), "Wrote ".into()],
+    approval: vec![
+        "(y/n)".into(),
+        "[y/N]".into(),
+        "[Y/n]".into(),
+        "Do you want to".into(),
+    ],
+    done: Vec::new(),
+});
+
+pub fn markers_for_agent(agent_type: &str) -> &'static StatusMarkers {
+    AgentManifest::get(agent_type)
+        .map(|definition| &definition.status_markers)
+        .filter(|markers| !markers.is_empty())
+        .unwrap_or(&GENERIC_MARKERS)
+}
+
+/// The manifest id of the agent a terminal command runs, by the name of its binary.
+pub fn agent_type_from_command(command: &str) -> Option<&'static str> {
+    AgentManifest::find_by_binary(command).map(|definition| definition.id.as_str())
+}
+
+/// Follows one agent terminal and classifies its output.
+#[derive(Debug)]
+pub struct AgentStateParser {
+    markers: &'static StatusMarkers,
+    carry_over: String,
+    state: Option<AgentActivityState>,
+}
+
+impl AgentStateParser {
+    pub fn for_agent(agent_type: Option<&str>) -> Self {
+        Self {
+            markers: markers_for_agent(agent_type.unwrap_or_default()),
+            carry_over: String::new(),
+            state: None,
+        }
+    }
+
+    pub fn state(&self) -> Option<AgentActivityState> {
+        self.state
+    }
+
+    /// Classify a chunk of output. Returns the new state when it changed.
+    pub fn observe_output(&mut self, data: &[u8]) -> Option<AgentActivityState> {
+        let mut text = std::mem::take(&mut self.carry_over);
+        // Markers entirely within the carried-over output were classified already
+        let seen = text.len();
+        text.push_str(&strip_ansi(&String::from_utf8_lossy(data)));
+
+        let latest = [
+            (&self.markers.thinking, AgentActivityState::Thinking),
+            (&self.markers.editing, AgentActivityState::EditingFiles),
+            (
+                &self.markers.approval,
+                AgentActivityState::WaitingForApproval,
+            ),
+            (&self.markers.done, AgentActivityState::Done),
+        ]
+        .into_iter()
+        .flat_map(|(markers, state)| {
+            markers
+                .iter()
+                .filter_map(|marker| text.rfind(marker).map(|at| (at + marker.len(), state)))
+                .filter(|(end, _)| *end > seen)
+                .collect::<Vec<_>>()
+        })
+        .max_by_key(|(end, _)| *end)
+        .map(|(_, state)| state);
+
+        let keep_from = text
+            .char_indices()
+            .rev()
+            .nth(CARRY_OVER_CHARS - 1)
+            .map(|(at, _)| at)
+            .unwrap_or(0);
+        self.carry_over = text[keep_from..].to_string();
+
+        self.set(latest?)
+    }
+
+    /// The agent went quiet: it finished its turn unless it is waiting on a confirmation.
+    pub fn observe_idle(&mut self) -> Option<AgentActivityState> {
+        if self.state == Some(AgentActivityState::WaitingForApproval) {
+            return None;
+        }
+        self.set(AgentActivityState::Done)
+    }
+
+    fn set(&mut self, state: AgentActivityState) -> Option<AgentActivityState> {
+        if self.state == Some(state) {
+            return None;
+        }
+        self.state = Some(state);
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claude_output_walks_through_states() {
+        let mut parser = AgentStateParser::for_agent(Some("claude"));
+        assert_eq!(
+            parser
+                .observe_output(b"\x1b[2m\xc2\xb7 Pondering\xe2\x80\xa6 (esc to interrupt)\x1b[0m"),
+            Some(AgentActivityState::Thinking)
+        );
+        assert_eq!(parser.observe_output(b"still pondering"), None);
+        assert_eq!(
+            parser.observe_output(b"\xe2\x8f\xba Update(src/main.rs)\r\n"),
+            Some(AgentActivityState::EditingFiles)
+        );
+        assert_eq!(
+            parser.observe_output(
+                b" Do you want to make this edit to main.rs?\r\n \xe2\x9d\xaf 1. Yes"
+            ),
+            Some(AgentActivityState::WaitingForApproval)
+        );
+        // Waiting on the user is not the end of the turn
+        assert_eq!(parser.observe_idle(), None);
+
+        assert_eq!(
+            parser.observe_output(b"(esc to interrupt)"),
+            Some(AgentActivityState::Thinking)
+        );
+        assert_eq!(parser.observe_idle(), Some(AgentActivityState::Done));
+        // A redraw after going idle does not bring back the old spinner
+        assert_eq!(parser.observe_output(b"\x1b[?25h"), None);
+        assert_eq!(parser.state(), Some(AgentActivityState::Done));
+    }
+
+    #[test]
+    fn finds_markers_split_across_chunks() {
+        let mut parser = AgentStateParser::for_agent(Some("codex"));
+        assert_eq!(
+            parser.observe_output(b"Would you like to run the fol"),
+            None
+        );
+        assert_eq!(
+            parser.observe_output(b"lowing command?\n  cargo test"),
+            Some(AgentActivityState::WaitingForApproval)
+        );
+    }
+
+    #[test]
+    fn markers_come_from_the_manifest() {
+        assert_eq!(
+            markers_for_agent("claude").done,
+            vec!["Total cost:".to_string()]
+        );
+        assert_eq!(
+            markers_for_agent("gemini"),
+            &AgentManifest::get("gemini").unwrap().status_markers
+        );
+        // Agents without markers of their own share the generic ones
+        assert!(markers_for_agent("qwen")
+            .approval
+            .contains(&"[y/N]".to_string()));
+        assert!(markers_for_agent("unknown").thinking.len() > 1);
+    }
+
+    #[test]
+    fn agent_type_comes_from_the_binary_name() {
+        assert_eq!(
+            agent_type_from_command("/opt/homebrew/bin/claude"),
+            Some("claude")
+        );
+        assert_eq!(agent_type_from_command("codex"), Some("codex"));
+        assert_eq!(agent_type_from_command("/usr/local/bin/qwen"), Some("qwen"));
+        assert_eq!(agent_type_from_command("/bin/zsh"), None);
+
+        let mut parser = AgentStateParser::for_agent(agent_type_from_command("aider"));
+        assert_eq!(
+            parser.observe_output(b"Create new file? (Y)es/(N)o [Yes]: [y/N]"),
+            Some(AgentActivityState::WaitingForApproval)
+        );
+    }
+}
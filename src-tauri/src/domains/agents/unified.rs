@@ -1,7 +1,9 @@
 use super::adapter::{AgentAdapter, AgentLaunchContext, DefaultAdapter};
+use super::aider;
 use super::amp;
+use super::custom::{self, CustomAgentAdapter};
 use super::droid;
-use super::format_binary_invocation;
+use super::goose;
 use super::launch_spec::AgentLaunchSpec;
 use super::manifest::AgentManifest;
 use super::qwen;
@@ -17,21 +19,18 @@ impl AgentAdapter for ClaudeAdapter {
     }
 
     fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
-        let config = super::claude::ClaudeConfig {
-            binary_path: Some(
-                ctx.binary_override
-                    .unwrap_or(&ctx.manifest.default_binary_path)
-                    .to_string(),
-            ),
-        };
-        let command = super::claude::build_claude_command_with_config(
-            ctx.worktree_path,
-            ctx.session_id,
-            ctx.initial_prompt,
-            ctx.skip_permissions,
-            Some(&config),
-        );
-        AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf())
+        // `__continue__` asks for the most recent conversation instead of a specific one
+        if ctx.session_id != Some("__continue__") {
+            return DefaultAdapter::new("claude".to_string()).build_launch_spec(ctx);
+        }
+        let mut spec =
+            DefaultAdapter::new("claude".to_string()).build_launch_spec(AgentLaunchContext {
+                session_id: None,
+                initial_prompt: None,
+                ..ctx
+            });
+        spec.shell_command.push_str(" --continue");
+        spec
     }
 }
 
@@ -55,11 +54,8 @@ impl AgentAdapter for CodexAdapter {
         };
 
         let config = super::codex::CodexConfig {
-            binary_path: Some(
-                ctx.binary_override
-                    .unwrap_or(&ctx.manifest.default_binary_path)
-                    .to_string(),
-            ),
+            binary_path: Some(ctx.binary().to_string()),
+            ..Default::default()
         };
         let command = super::codex::build_codex_command_with_config(
             ctx.worktree_path,
@@ -80,54 +76,29 @@ impl AgentAdapter for GeminiAdapter {
     }
 
     fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
-        let config = super::gemini::GeminiConfig {
-            binary_path: Some(
-                ctx.binary_override
-                    .unwrap_or(&ctx.manifest.default_binary_path)
-                    .to_string(),
-            ),
-        };
-        let command = super::gemini::build_gemini_command_with_config(
-            ctx.worktree_path,
-            None,
-            ctx.initial_prompt,
-            ctx.skip_permissions,
-            Some(&config),
-        );
-        AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf())
+        DefaultAdapter::new("gemini".to_string()).build_launch_spec(ctx)
     }
 }
 
 pub struct OpenCodeAdapter;
 
-fn build_droid_prompt_arg(prompt: &str) -> String {
-    let normalized = prompt.replace("\r\n", "\n").replace('\r', "\n");
-    let escaped = super::escape_prompt_for_shell(&normalized);
-    format!("\"{escaped}\"")
-}
-
 pub struct DroidAdapter;
 
 impl AgentAdapter for DroidAdapter {
     fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
-        let binary = ctx
-            .binary_override
-            .unwrap_or(&ctx.manifest.default_binary_path);
-        let binary_invocation = format_binary_invocation(binary);
-        let cwd_quoted = format_binary_invocation(&ctx.worktree_path.display().to_string());
-
-        let mut command = format!("cd {cwd_quoted} && {binary_invocation}");
-
-        if let Some(prompt) = ctx.initial_prompt {
-            let prompt_arg = build_droid_prompt_arg(prompt);
-            command.push(' ');
-            command.push_str(&prompt_arg);
-        }
-
-        let mut spec = AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf());
+        // Droid reads a lone carriage return as the end of its prompt
+        let prompt = ctx
+            .initial_prompt
+            .map(|prompt| prompt.replace("\r\n", "\n").replace('\r', "\n"));
+        let worktree_path = ctx.worktree_path;
+        let mut spec =
+            DefaultAdapter::new("droid".to_string()).build_launch_spec(AgentLaunchContext {
+                initial_prompt: prompt.as_deref(),
+                ..ctx
+            });
 
         let system_path = std::env::var("PATH").unwrap_or_default();
-        match droid::ensure_vscode_cli_shim(ctx.worktree_path, &system_path) {
+        match droid::ensure_vscode_cli_shim(worktree_path, &system_path) {
             Ok(Some(updated_path)) => {
                 let mut env = HashMap::new();
                 env.insert("PATH".to_string(), updated_path);
@@ -137,7 +108,7 @@ impl AgentAdapter for DroidAdapter {
             Err(err) => {
                 warn!(
                     "Failed to prepare VSCode shim for droid session at {}: {err}",
-                    ctx.worktree_path.display()
+                    worktree_path.display()
                 );
             }
         }
@@ -160,11 +131,7 @@ impl AgentAdapter for OpenCodeAdapter {
             });
 
         let config = super::opencode::OpenCodeConfig {
-            binary_path: Some(
-                ctx.binary_override
-                    .unwrap_or(&ctx.manifest.default_binary_path)
-                    .to_string(),
-            ),
+            binary_path: Some(ctx.binary().to_string()),
         };
         let command = super::opencode::build_opencode_command_with_config(
             ctx.worktree_path,
@@ -185,21 +152,7 @@ impl AgentAdapter for QwenAdapter {
     }
 
     fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
-        let config = qwen::QwenConfig {
-            binary_path: Some(
-                ctx.binary_override
-                    .unwrap_or(&ctx.manifest.default_binary_path)
-                    .to_string(),
-            ),
-        };
-        let command = qwen::build_qwen_command_with_config(
-            ctx.worktree_path,
-            ctx.session_id,
-            ctx.initial_prompt,
-            ctx.skip_permissions,
-            Some(&config),
-        );
-        AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf())
+        DefaultAdapter::new("qwen".to_string()).build_launch_spec(ctx)
     }
 }
 
@@ -211,21 +164,52 @@ impl AgentAdapter for AmpAdapter {
     }
 
     fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
-        let config = amp::AmpConfig {
-            binary_path: Some(
-                ctx.binary_override
-                    .unwrap_or(&ctx.manifest.default_binary_path)
-                    .to_string(),
-            ),
-        };
-        let command = amp::build_amp_command_with_config(
+        DefaultAdapter::new("amp".to_string()).build_launch_spec(ctx)
+    }
+}
+
+pub struct AiderAdapter;
+
+impl AgentAdapter for AiderAdapter {
+    fn find_session(&self, path: &Path) -> Option<String> {
+        aider::find_aider_session(path)
+    }
+
+    /// The initial prompt is typed into the terminal once Aider is ready: `--message`
+    /// would exit after one reply
+    fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
+        let resume = ctx.session_id.is_some_and(|id| !id.trim().is_empty());
+        let mut spec = DefaultAdapter::new("aider".to_string()).build_launch_spec(ctx);
+        if resume {
+            spec.shell_command.push_str(" --restore-chat-history");
+        }
+        spec
+    }
+}
+
+pub struct GooseAdapter;
+
+impl AgentAdapter for GooseAdapter {
+    fn find_session(&self, path: &Path) -> Option<String> {
+        goose::find_goose_session(path)
+    }
+
+    fn build_launch_spec(&self, ctx: AgentLaunchContext) -> AgentLaunchSpec {
+        let command = goose::build_goose_command(
             ctx.worktree_path,
             ctx.session_id,
             ctx.initial_prompt,
-            ctx.skip_permissions,
-            Some(&config),
+            ctx.binary(),
         );
-        AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf())
+        let mut spec = AgentLaunchSpec::new(command, ctx.worktree_path.to_path_buf());
+
+        // Goose has no permission flag; its approval mode comes from the environment
+        if ctx.skip_permissions {
+            let mut env = HashMap::new();
+            env.insert(goose::GOOSE_MODE_ENV.to_string(), "auto".to_string());
+            spec = spec.with_env_vars(env);
+        }
+        spec
     }
 }
 
@@ -239,6 +223,7 @@ impl AgentAdapter for TerminalAdapter {
 
 pub struct AgentRegistry {
     adapters: HashMap<String, Box<dyn AgentAdapter>>,
+    model: Option<String>,
 }
 
 impl AgentRegistry {
@@ -252,15 +237,30 @@ impl AgentRegistry {
         adapters.insert("droid".to_string(), Box::new(DroidAdapter));
         adapters.insert("qwen".to_string(), Box::new(QwenAdapter));
         adapters.insert("amp".to_string(), Box::new(AmpAdapter));
+        adapters.insert("aider".to_string(), Box::new(AiderAdapter));
+        adapters.insert("goose".to_string(), Box::new(GooseAdapter));
         adapters.insert("terminal".to_string(), Box::new(TerminalAdapter));
 
         for agent_id in AgentManifest::supported_agents() {
             if !adapters.contains_key(&agent_id) {
-                adapters.insert(agent_id.clone(), Box::new(DefaultAdapter::new(agent_id)));
+                let adapter: Box<dyn AgentAdapter> = match custom::custom_agent_config(&agent_id) {
+                    Some(config) => Box::new(CustomAgentAdapter::new(config)),
+                    None => Box::new(DefaultAdapter::new(agent_id.clone())),
+                };
+                adapters.insert(agent_id, adapter);
             }
         }
 
-        Self { adapters }
+        Self {
+            adapters,
+            model: None,
+        }
+    }
+
+    /// Launch agents with `model` instead of their default, for agents that support it
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
     }
 
     pub fn get(&self, agent_type: &str) -> Option<&dyn AgentAdapter> {
@@ -294,7 +294,11 @@ impl AgentRegistry {
             manifest,
         };
 
-        Some(adapter.build_launch_spec(ctx))
+        Some(
+            adapter
+                .build_launch_spec(ctx)
+                .with_model(manifest, self.model.as_deref()),
+        )
     }
 }
 
@@ -308,31 +312,53 @@ impl Default for AgentRegistry {
 mod tests {
     use super::*;
 
+    fn launch(
+        adapter: &dyn AgentAdapter,
+        agent: &str,
+        worktree: &str,
+        session_id: Option<&str>,
+        initial_prompt: Option<&str>,
+        skip_permissions: bool,
+    ) -> AgentLaunchSpec {
+        adapter.build_launch_spec(AgentLaunchContext {
+            worktree_path: Path::new(worktree),
+            session_id,
+            initial_prompt,
+            skip_permissions,
+            binary_override: None,
+            manifest: AgentManifest::get(agent).unwrap(),
+        })
+    }
+
     #[test]
     fn test_registry_has_all_agents() {
-    let registry = AgentRegistry::new();
-    assert!(registry.get("claude").is_some());
-    assert!(registry.get("codex").is_some());
-    assert!(registry.get("gemini").is_some());
-    assert!(registry.get("opencode").is_some());
-    assert!(registry.get("droid").is_some());
-    assert!(registry.get("qwen").is_some());
-    assert!(registry.get("amp").is_some());
+        let registry = AgentRegistry::new();
+        assert!(registry.get("claude").is_some());
+        assert!(registry.get("codex").is_some());
+        assert!(registry.get("gemini").is_some());
+        assert!(registry.get("opencode").is_some());
+        assert!(registry.get("droid").is_some());
+        assert!(registry.get("qwen").is_some());
+        assert!(registry.get("amp").is_some());
+        assert!(registry.get("aider").is_some());
+        assert!(registry.get("goose").is_some());
         assert!(registry.get("terminal").is_some());
     }
 
     #[test]
     fn test_registry_supported_agents() {
-    let registry = AgentRegistry::new();
-    let supported = registry.supported_agents();
-    assert!(supported.len() >= 8);
-    assert!(supported.contains(&"claude".to_string()));
-    assert!(supported.contains(&"codex".to_string()));
-    assert!(supported.contains(&"droid".to_string()));
-    assert!(supported.contains(&"gemini".to_string()));
-    assert!(supported.contains(&"opencode".to_string()));
-    assert!(supported.contains(&"qwen".to_string()));
-    assert!(supported.contains(&"amp".to_string()));
+        let registry = AgentRegistry::new();
+        let supported = registry.supported_agents();
+        assert!(supported.len() >= 8);
+        assert!(supported.contains(&"claude".to_string()));
+        assert!(supported.contains(&"codex".to_string()));
+        assert!(supported.contains(&"droid".to_string()));
+        assert!(supported.contains(&"gemini".to_string()));
+        assert!(supported.contains(&"opencode".to_string()));
+        assert!(supported.contains(&"qwen".to_string()));
+        assert!(supported.contains(&"amp".to_string()));
+        assert!(supported.contains(&"aider".to_string()));
+        assert!(supported.contains(&"goose".to_string()));
         assert!(supported.contains(&"terminal".to_string()));
     }
 
@@ -374,6 +400,162 @@ mod tests {
             let spec = adapter.build_launch_spec(ctx);
             assert!(spec.shell_command.contains("claude"));
         }
+
+        #[test]
+        fn test_new_session_with_prompt() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                None,
+                Some("implement feature X"),
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && claude --dangerously-skip-permissions "implement feature X""#
+            );
+        }
+
+        #[test]
+        fn test_resume_with_session_id() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                Some("session123"),
+                Some("ignored while resuming"),
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && claude -r session123"
+            );
+        }
+
+        #[test]
+        fn test_resume_with_permissions() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                Some("session123"),
+                None,
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && claude --dangerously-skip-permissions -r session123"
+            );
+        }
+
+        #[test]
+        fn test_continue_special_session_id() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                Some("__continue__"),
+                None,
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && claude --continue"
+            );
+
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                Some("__continue__"),
+                Some("ignored while resuming"),
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && claude --dangerously-skip-permissions --continue"
+            );
+        }
+
+        #[test]
+        fn test_new_session_no_prompt_no_permissions() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                None,
+                None,
+                false,
+            );
+            assert_eq!(spec.shell_command, "cd /path/to/worktree && claude");
+        }
+
+        #[test]
+        fn test_binary_with_spaces_is_quoted() {
+            let spec = ClaudeAdapter.build_launch_spec(AgentLaunchContext {
+                worktree_path: Path::new("/path/to/worktree"),
+                session_id: None,
+                initial_prompt: None,
+                skip_permissions: false,
+                binary_override: Some("/Applications/Claude Latest/bin/claude"),
+                manifest: AgentManifest::get("claude").unwrap(),
+            });
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && "/Applications/Claude Latest/bin/claude""#
+            );
+        }
+
+        #[test]
+        fn test_command_with_spaces_in_cwd() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/with spaces",
+                None,
+                None,
+                false,
+            );
+            assert!(spec
+                .shell_command
+                .starts_with(r#"cd "/path/with spaces" && "#));
+        }
+
+        #[test]
+        fn test_prompt_with_quotes() {
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                None,
+                Some(r#"implement "feature" with quotes"#),
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && claude "implement \"feature\" with quotes""#
+            );
+        }
+
+        #[test]
+        fn test_prompt_with_trailing_backslash_round_trips() {
+            use crate::domains::agents::command_parser::parse_agent_command;
+
+            let prompt = "Check Windows path: C:\\Users\\tester\\Projects\\";
+            let spec = launch(
+                &ClaudeAdapter,
+                "claude",
+                "/path/to/worktree",
+                None,
+                Some(prompt),
+                false,
+            );
+
+            let (_, _, args) = parse_agent_command(&spec.shell_command)
+                .expect("command with trailing backslash prompt should parse");
+            assert_eq!(args.last().unwrap(), prompt);
+        }
     }
 
     mod codex_tests {
@@ -418,6 +600,117 @@ mod tests {
             let spec = adapter.build_launch_spec(ctx);
             assert!(spec.shell_command.contains("gemini"));
         }
+
+        #[test]
+        fn test_new_session_with_prompt() {
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/to/worktree",
+                None,
+                Some("implement feature X"),
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && gemini --yolo --prompt-interactive "implement feature X""#
+            );
+        }
+
+        #[test]
+        fn test_resume_with_session_id() {
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/to/worktree",
+                Some("12345678-1234-1234-1234-123456789012"),
+                None,
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && gemini --resume 12345678-1234-1234-1234-123456789012"
+            );
+        }
+
+        #[test]
+        fn test_resume_ignores_initial_prompt() {
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/to/worktree",
+                Some("latest"),
+                Some("implement feature X"),
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && gemini --yolo --resume latest"
+            );
+        }
+
+        #[test]
+        fn test_new_session_no_prompt_no_permissions() {
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/to/worktree",
+                None,
+                None,
+                false,
+            );
+            assert_eq!(spec.shell_command, "cd /path/to/worktree && gemini");
+        }
+
+        #[test]
+        fn test_command_with_spaces_in_cwd() {
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/with spaces",
+                None,
+                None,
+                false,
+            );
+            assert!(spec
+                .shell_command
+                .starts_with(r#"cd "/path/with spaces" && "#));
+        }
+
+        #[test]
+        fn test_prompt_with_quotes() {
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/to/worktree",
+                None,
+                Some(r#"implement "feature" with quotes"#),
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && gemini --prompt-interactive "implement \"feature\" with quotes""#
+            );
+        }
+
+        #[test]
+        fn test_prompt_with_trailing_backslash_round_trips() {
+            use crate::domains::agents::command_parser::parse_agent_command;
+
+            let prompt = "Inspect path: C:\\Users\\gemini\\Workspace\\";
+            let spec = launch(
+                &GeminiAdapter,
+                "gemini",
+                "/path/to/worktree",
+                None,
+                Some(prompt),
+                false,
+            );
+
+            let (_, _, args) = parse_agent_command(&spec.shell_command)
+                .expect("gemini prompt ending with backslash should parse");
+            assert_eq!(args.last().unwrap(), prompt);
+        }
     }
 
     mod opencode_tests {
@@ -583,28 +876,131 @@ mod tests {
     }
 
     mod qwen_tests {
-    use super::*;
+        use super::*;
 
-    #[test]
-    fn test_qwen_adapter_basic() {
-    let adapter = QwenAdapter;
-    let manifest = AgentManifest::get("qwen").unwrap();
+        #[test]
+        fn test_qwen_adapter_basic() {
+            let adapter = QwenAdapter;
+            let manifest = AgentManifest::get("qwen").unwrap();
 
-    let ctx = AgentLaunchContext {
-    worktree_path: Path::new("/test/path"),
-    session_id: None,
-    initial_prompt: Some("test prompt"),
-    skip_permissions: true,
-    binary_override: Some("qwen"),
-    manifest,
-    };
+            let ctx = AgentLaunchContext {
+                worktree_path: Path::new("/test/path"),
+                session_id: None,
+                initial_prompt: Some("test prompt"),
+                skip_permissions: true,
+                binary_override: Some("qwen"),
+                manifest,
+            };
 
-    let spec = adapter.build_launch_spec(ctx);
-    assert!(spec.shell_command.contains("qwen"));
-    assert!(spec.shell_command.contains("--yolo"));
-    assert!(spec.shell_command.contains("--prompt-interactive"));
-    assert!(spec.shell_command.contains("test prompt"));
-    }
+            let spec = adapter.build_launch_spec(ctx);
+            assert!(spec.shell_command.contains("qwen"));
+            assert!(spec.shell_command.contains("--yolo"));
+            assert!(spec.shell_command.contains("--prompt-interactive"));
+            assert!(spec.shell_command.contains("test prompt"));
+        }
+
+        #[test]
+        fn test_qwen_ignores_session_id_and_escapes_prompt() {
+            use crate::domains::agents::command_parser::parse_agent_command;
+
+            let manifest = AgentManifest::get("qwen").unwrap();
+            let prompt = "Inspect \"path\": C:\\\\Users\\\\qwen\\\\";
+            let ctx = AgentLaunchContext {
+                worktree_path: Path::new("/path/to/worktree"),
+                session_id: Some("12345678-1234-1234-1234-123456789012"),
+                initial_prompt: Some(prompt),
+                skip_permissions: false,
+                binary_override: None,
+                manifest,
+            };
+
+            let spec = QwenAdapter.build_launch_spec(ctx);
+            assert!(spec
+                .shell_command
+                .starts_with("cd /path/to/worktree && qwen --prompt-interactive "));
+            let (_, _, args) = parse_agent_command(&spec.shell_command)
+                .expect("qwen prompt ending with backslash should parse");
+            assert_eq!(args.last().unwrap(), prompt);
+        }
+
+        #[test]
+        fn test_new_session_with_prompt() {
+            let spec = launch(
+                &QwenAdapter,
+                "qwen",
+                "/path/to/worktree",
+                None,
+                Some("implement feature X"),
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && qwen --yolo --prompt-interactive "implement feature X""#
+            );
+        }
+
+        #[test]
+        fn test_command_with_spaces_in_cwd() {
+            let spec = launch(&QwenAdapter, "qwen", "/path/with spaces", None, None, false);
+            assert!(spec
+                .shell_command
+                .starts_with(r#"cd "/path/with spaces" && "#));
+        }
+
+        #[test]
+        fn test_resume_with_session_id() {
+            let spec = launch(
+                &QwenAdapter,
+                "qwen",
+                "/path/to/worktree",
+                Some("12345678-1234-1234-1234-123456789012"),
+                None,
+                false,
+            );
+            // Qwen doesn't support resume, so we just start interactive mode
+            assert_eq!(spec.shell_command, "cd /path/to/worktree && qwen");
+        }
+
+        #[test]
+        fn test_new_session_no_prompt_no_permissions() {
+            let spec = launch(&QwenAdapter, "qwen", "/path/to/worktree", None, None, false);
+            assert_eq!(spec.shell_command, "cd /path/to/worktree && qwen");
+        }
+
+        #[test]
+        fn test_prompt_with_quotes() {
+            let spec = launch(
+                &QwenAdapter,
+                "qwen",
+                "/path/to/worktree",
+                None,
+                Some(r#"implement "feature" with quotes"#),
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && qwen --prompt-interactive "implement \"feature\" with quotes""#
+            );
+        }
+
+        #[test]
+        fn test_prompt_with_trailing_backslash_round_trips() {
+            use crate::domains::agents::command_parser::parse_agent_command;
+
+            let prompt = "Inspect path: C:\\Users\\qwen\\Workspace\\";
+            let spec = launch(
+                &QwenAdapter,
+                "qwen",
+                "/path/to/worktree",
+                None,
+                Some(prompt),
+                false,
+            );
+
+            let (_, _, args) = parse_agent_command(&spec.shell_command)
+                .expect("qwen prompt ending with backslash should parse");
+            assert_eq!(args.last().unwrap(), prompt);
+        }
     }
 
     mod amp_tests {
@@ -629,5 +1025,179 @@ mod tests {
             assert!(spec.shell_command.contains("--dangerously-allow-all"));
             assert!(spec.shell_command.contains("test prompt"));
         }
+
+        #[test]
+        fn test_new_session_with_prompt() {
+            let spec = launch(
+                &AmpAdapter,
+                "amp",
+                "/path/to/worktree",
+                None,
+                Some("implement feature X"),
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && echo \"implement feature X\" | amp --dangerously-allow-all"
+            );
+        }
+
+        #[test]
+        fn test_command_with_spaces_in_cwd() {
+            let spec = launch(&AmpAdapter, "amp", "/path/with spaces", None, None, false);
+            assert_eq!(spec.shell_command, "cd \"/path/with spaces\" && amp");
+        }
+
+        #[test]
+        fn test_resume_with_thread_id() {
+            let spec = launch(
+                &AmpAdapter,
+                "amp",
+                "/path/to/worktree",
+                Some("T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"),
+                None,
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && amp threads continue T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"
+            );
+        }
+
+        #[test]
+        fn test_new_session_no_prompt_no_permissions() {
+            let spec = launch(&AmpAdapter, "amp", "/path/to/worktree", None, None, false);
+            assert_eq!(spec.shell_command, "cd /path/to/worktree && amp");
+        }
+
+        #[test]
+        fn test_prompt_with_quotes() {
+            let spec = launch(
+                &AmpAdapter,
+                "amp",
+                "/path/to/worktree",
+                None,
+                Some(r#"implement "feature" with quotes"#),
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd /path/to/worktree && echo "implement \"feature\" with quotes" | amp"#
+            );
+        }
+
+        #[test]
+        fn test_resume_with_thread_id_and_permissions() {
+            let spec = launch(
+                &AmpAdapter,
+                "amp",
+                "/path/to/worktree",
+                Some("T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"),
+                None,
+                true,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && amp --dangerously-allow-all threads continue T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"
+            );
+        }
+
+        #[test]
+        fn test_resume_with_thread_id_and_prompt() {
+            let spec = launch(
+                &AmpAdapter,
+                "amp",
+                "/path/to/worktree",
+                Some("T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"),
+                Some("continue with feature X"),
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                "cd /path/to/worktree && echo \"continue with feature X\" | amp threads continue T-7bb2c785-d6f5-44a1-80e0-28f11fd997bc"
+            );
+        }
+
+        #[test]
+        fn test_empty_thread_id_ignored() {
+            let spec = launch(
+                &AmpAdapter,
+                "amp",
+                "/path/to/worktree",
+                Some(""),
+                None,
+                false,
+            );
+            assert_eq!(spec.shell_command, "cd /path/to/worktree && amp");
+        }
+    }
+
+    mod aider_tests {
+        use super::*;
+
+        #[test]
+        fn test_aider_adapter_types_prompt_into_terminal() {
+            let adapter = AiderAdapter;
+            let manifest = AgentManifest::get("aider").unwrap();
+
+            let ctx = AgentLaunchContext {
+                worktree_path: Path::new("/test/path"),
+                session_id: None,
+                initial_prompt: Some("test prompt"),
+                skip_permissions: true,
+                binary_override: Some("aider"),
+                manifest,
+            };
+
+            let spec = adapter.build_launch_spec(ctx);
+            assert_eq!(spec.shell_command, "cd /test/path && aider --yes-always");
+            assert_eq!(spec.initial_command.as_deref(), Some("test prompt"));
+            assert!(manifest.auto_send_initial_command);
+        }
+
+        #[test]
+        fn test_resume_restores_chat_history() {
+            let spec = launch(
+                &AiderAdapter,
+                "aider",
+                "/path/with spaces",
+                Some(aider::AIDER_RESUME_SENTINEL),
+                None,
+                false,
+            );
+            assert_eq!(
+                spec.shell_command,
+                r#"cd "/path/with spaces" && aider --restore-chat-history"#
+            );
+        }
+    }
+
+    mod goose_tests {
+        use super::*;
+
+        #[test]
+        fn test_goose_adapter_maps_permissions_to_goose_mode() {
+            let adapter = GooseAdapter;
+            let manifest = AgentManifest::get("goose").unwrap();
+
+            let ctx = AgentLaunchContext {
+                worktree_path: Path::new("/test/path"),
+                session_id: Some("schaltwerk-path-0123abcd-1"),
+                initial_prompt: None,
+                skip_permissions: true,
+                binary_override: Some("goose"),
+                manifest,
+            };
+
+            let spec = adapter.build_launch_spec(ctx);
+            assert_eq!(
+                spec.shell_command,
+                "cd /test/path && goose session --resume --name schaltwerk-path-0123abcd-1"
+            );
+            assert_eq!(
+                spec.env_vars.get(goose::GOOSE_MODE_ENV).map(String::as_str),
+                Some("auto")
+            );
+        }
     }
 }
@@ -0,0 +1,325 @@
+//! Token usage and cost summaries printed by agents. Claude prints its summary on exit
+//! and for `/cost`; Codex prints a `Token usage:` line when a conversation ends. Each
+//! summary covers the whole agent run so far, so only the latest one of a run counts.
+
+use crate::domains::terminal::ansi::strip_ansi;
+use crate::domains::terminal::line_buffer::LineBuffer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct AgentUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Zero when the agent does not report a cost
+    pub cost_usd: f64,
+}
+
+impl AgentUsage {
+    pub fn add(&mut self, other: &AgentUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+}
+
+/// Token counts as agents print them: `1,234`, `3.1k` or `1.2m`.
+pub fn parse_token_count(text: &str) -> Option<u64> {
+    let text = text.trim().replace(',', "");
+    let (number, factor) = match text.chars().last()? {
+        'k' | 'K' => (&text[..text.len() - 1], 1_000.0),
+        'm' | 'M' => (&text[..text.len() - 1], 1_000_000.0),
+        _ => (text.as_str(), 1.0),
+    };
+    let value: f64 = number.parse().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    Some((value * factor).round() as u64)
+}
+
+fn parse_cost(text: &str) -> Option<f64> {
+    let amount = text.trim().strip_prefix('$')?;
+    let amount: String = amount
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+    amount.parse().ok()
+}
+
+/// `claude-sonnet:  25 input, 3.1k output, 120.5k cache read, 20.1k cache write`
+fn parse_claude_model_line(line: &str) -> Option<AgentUsage> {
+    let (_, counts) = line.split_once(':')?;
+    let mut usage = AgentUsage::default();
+    let (mut has_input, mut has_output) = (false, false);
+    for part in counts.split(',') {
+        let (count, label) = part.trim().split_once(' ')?;
+        let count = parse_token_count(count)?;
+        match label.trim() {
+            "input" => {
+                usage.input_tokens = count;
+                has_input = true;
+            }
+            "output" => {
+                usage.output_tokens = count;
+                has_output = true;
+            }
+            "cache read" => usage.cache_read_tokens = count,
+            "cache write" => usage.cache_write_tokens = count,
+            _ => return None,
+        }
+    }
+    (has_input && has_output).then_some(usage)
+}
+
+/// `Token usage: total=1,234 input=1,000 (+ 5,000 cached) output=234 (reasoning 100)`
+fn parse_codex_usage_line(line: &str) -> Option<AgentUsage> {
+    let (_, rest) = line.split_once("Token usage:")?;
+    let mut usage = AgentUsage::default();
+    let mut has_output = false;
+    let mut words = rest.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        if let Some(count) = word.strip_prefix("input=") {
+            usage.input_tokens = parse_token_count(count)?;
+        } else if let Some(count) = word.strip_prefix("output=") {
+            usage.output_tokens = parse_token_count(count)?;
+            has_output = true;
+        } else if word == "(+" {
+            let count = words.next().and_then(parse_token_count)?;
+            if words.peek().is_some_and(|next| next.starts_with("cached")) {
+                usage.cache_read_tokens = count;
+            }
+        }
+    }
+    has_output.then_some(usage)
+}
+
+/// Follows one agent's output line by line and keeps its latest usage summary.
+#[derive(Debug, Default)]
+pub struct UsageParser {
+    latest: Option<AgentUsage>,
+    in_model_breakdown: bool,
+}
+
+impl UsageParser {
+    /// Feed one line of output. Returns true when the usage summary changed.
+    pub fn feed_line(&mut self, line: &str) -> bool {
+        let line = strip_ansi(line);
+        let line = line.trim();
+
+        if let Some(cost) = line.strip_prefix("Total cost:") {
+            // A new summary replaces the previous one; its model lines follow
+            self.latest = Some(AgentUsage {
+                cost_usd: parse_cost(cost).unwrap_or_default(),
+                ..Default::default()
+            });
+            self.in_model_breakdown = false;
+            return true;
+        }
+        if line.starts_with("Usage by model:") {
+            self.in_model_breakdown = self.latest.is_some();
+            return false;
+        }
+        if self.in_model_breakdown {
+            if let Some(model_usage) = parse_claude_model_line(line) {
+                if let Some(latest) = self.latest.as_mut() {
+                    latest.add(&model_usage);
+                }
+                return true;
+            }
+            self.in_model_breakdown = false;
+        }
+        if let Some(usage) = parse_codex_usage_line(line) {
+            self.latest = Some(usage);
+            return true;
+        }
+        false
+    }
+
+    pub fn latest(&self) -> Option<AgentUsage> {
+        self.latest
+    }
+}
+
+/// Latest usage of one agent run; a run lasts as long as its terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageUpdate {
+    pub terminal_id: String,
+    pub run_id: String,
+    pub usage: AgentUsage,
+}
+
+struct TerminalUsage {
+    run_id: String,
+    parser: UsageParser,
+    pending: LineBuffer,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    terminals: HashMap<String, TerminalUsage>,
+    /// Changed runs not yet taken, by run id
+    updates: HashMap<String, UsageUpdate>,
+}
+
+/// Collects usage summaries from agent terminal output until they are stored.
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan output for usage summaries. Returns true when the terminal's usage changed.
+    pub async fn observe_output(&self, id: &str, data: &[u8]) -> bool {
+        let mut guard = self.state.lock().await;
+        let state = &mut *guard;
+        let terminal = state
+            .terminals
+            .entry(id.to_string())
+            .or_insert_with(|| TerminalUsage {
+                run_id: uuid::Uuid::new_v4().to_string(),
+                parser: UsageParser::default(),
+                pending: LineBuffer::default(),
+            });
+        let Some(complete) = terminal.pending.push(data) else {
+            return false;
+        };
+
+        let mut changed = false;
+        for line in complete.split(['\n', '\r']) {
+            changed |= terminal.parser.feed_line(line);
+        }
+        let Some(usage) = terminal.parser.latest().filter(|_| changed) else {
+            return false;
+        };
+        state.updates.insert(
+            terminal.run_id.clone(),
+            UsageUpdate {
+                terminal_id: id.to_string(),
+                run_id: terminal.run_id.clone(),
+                usage,
+            },
+        );
+        true
+    }
+
+    /// Usage of every run that changed since the last call.
+    pub async fn take_updates(&self) -> Vec<UsageUpdate> {
+        self.state
+            .lock()
+            .await
+            .updates
+            .drain()
+            .map(|(_, update)| update)
+            .collect()
+    }
+
+    /// Forget the terminal's run; usage it reported stays available to `take_updates`.
+    pub async fn clear(&self, id: &str) {
+        self.state.lock().await.terminals.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUDE_SUMMARY: &str = "Total cost:            $0.0420\n\
+        Total duration (API):  1m 2.3s\n\
+        Total code changes:    10 lines added, 2 lines removed\n\
+        Usage by model:\n\
+        \x20   claude-3-5-haiku:  1.2k input, 85 output, 0 cache read, 0 cache write\n\
+        \x20      claude-sonnet:  25 input, 3.1k output, 120.5k cache read, 20.1k cache write\n\
+        \n";
+
+    #[test]
+    fn parses_token_counts() {
+        assert_eq!(parse_token_count("1,234"), Some(1234));
+        assert_eq!(parse_token_count("3.1k"), Some(3100));
+        assert_eq!(parse_token_count("1.2m"), Some(1_200_000));
+        assert_eq!(parse_token_count("lots"), None);
+        assert_eq!(parse_token_count(""), None);
+    }
+
+    #[test]
+    fn sums_claude_model_breakdown() {
+        let mut parser = UsageParser::default();
+        for line in CLAUDE_SUMMARY.lines() {
+            parser.feed_line(line);
+        }
+        assert_eq!(
+            parser.latest(),
+            Some(AgentUsage {
+                input_tokens: 1225,
+                output_tokens: 3185,
+                cache_read_tokens: 120_500,
+                cache_write_tokens: 20_100,
+                cost_usd: 0.042,
+            })
+        );
+
+        // Lines that merely look like usage after the breakdown ended are ignored
+        assert!(!parser.feed_line("note: 5 input, 5 output"));
+        assert_eq!(parser.latest().unwrap().input_tokens, 1225);
+    }
+
+    #[test]
+    fn parses_codex_token_usage() {
+        let mut parser = UsageParser::default();
+        assert!(parser.feed_line(
+            "\x1b[2mToken usage: total=6,234 input=1,000 (+ 5,000 cached) output=234 (reasoning 100)\x1b[0m"
+        ));
+        assert_eq!(
+            parser.latest(),
+            Some(AgentUsage {
+                input_tokens: 1000,
+                output_tokens: 234,
+                cache_read_tokens: 5000,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn repeated_summaries_replace_each_other_within_a_run() {
+        let tracker = UsageTracker::new();
+        assert!(!tracker.observe_output("t1", b"Total cost: $0.01").await);
+        assert!(tracker.observe_output("t1", b"\r\n").await);
+        assert!(
+            tracker
+                .observe_output("t1", CLAUDE_SUMMARY.as_bytes())
+                .await
+        );
+
+        let updates = tracker.take_updates().await;
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].terminal_id, "t1");
+        assert_eq!(updates[0].usage.cost_usd, 0.042);
+        assert!(tracker.take_updates().await.is_empty());
+
+        // A new terminal run reports separately, even after the old one was cleared
+        tracker.clear("t1").await;
+        tracker
+            .observe_output("t1", b"Token usage: total=10 input=4 output=6\n")
+            .await;
+        let updates = tracker.take_updates().await;
+        assert_eq!(updates.len(), 1);
+        assert_ne!(updates[0].run_id, "");
+        assert_eq!(updates[0].usage.total_tokens(), 10);
+    }
+}
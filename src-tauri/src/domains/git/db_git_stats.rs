@@ -1,4 +1,4 @@
-use crate::domains::sessions::entity::GitStats;
+use crate::domains::sessions::entity::{BaseFreshness, GitStats};
 use crate::schaltwerk_core::database::Database;
 use anyhow::Result;
 use chrono::{TimeZone, Utc};
@@ -10,6 +10,34 @@ pub trait GitStatsMethods {
     fn get_all_git_stats(&self) -> Result<Vec<GitStats>>;
     fn get_git_stats_bulk(&self, session_ids: &[String]) -> Result<Vec<GitStats>>;
     fn should_update_stats(&self, session_id: &str) -> Result<bool>;
+    fn save_base_freshness(&self, session_id: &str, freshness: &BaseFreshness) -> Result<()>;
+}
+
+const GIT_STATS_COLUMNS: &str = "session_id, files_changed, lines_added, lines_removed, \
+     has_uncommitted, calculated_at, base_ahead, base_behind, base_checked_at";
+
+fn git_stats_from_row(row: &rusqlite::Row) -> rusqlite::Result<GitStats> {
+    let base_ahead: Option<u32> = row.get(6)?;
+    let base_behind: Option<u32> = row.get(7)?;
+    let base_checked_at: Option<i64> = row.get(8)?;
+    let base_freshness = match (base_ahead, base_behind, base_checked_at) {
+        (Some(ahead), Some(behind), Some(checked_at)) => Some(BaseFreshness {
+            ahead,
+            behind,
+            checked_at: Utc.timestamp_opt(checked_at, 0).unwrap(),
+        }),
+        _ => None,
+    };
+    Ok(GitStats {
+        session_id: row.get(0)?,
+        files_changed: row.get(1)?,
+        lines_added: row.get(2)?,
+        lines_removed: row.get(3)?,
+        has_uncommitted: row.get(4)?,
+        calculated_at: Utc.timestamp_opt(row.get(5)?, 0).unwrap(),
+        last_diff_change_ts: None,
+        base_freshness,
+    })
 }
 
 impl GitStatsMethods for Database {
@@ -17,9 +45,15 @@ impl GitStatsMethods for Database {
         let conn = self.get_conn()?;
 
         conn.execute(
-            "INSERT OR REPLACE INTO git_stats
+            "INSERT INTO git_stats
              (session_id, files_changed, lines_added, lines_removed, has_uncommitted, calculated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(session_id) DO UPDATE SET
+                files_changed = excluded.files_changed,
+                lines_added = excluded.lines_added,
+                lines_removed = excluded.lines_removed,
+                has_uncommitted = excluded.has_uncommitted,
+                calculated_at = excluded.calculated_at",
             params![
                 stats.session_id,
                 stats.files_changed,
@@ -35,21 +69,11 @@ impl GitStatsMethods for Database {
 
     fn get_git_stats(&self, session_id: &str) -> Result<Option<GitStats>> {
         let conn = self.get_conn()?;
-        let mut stmt = conn.prepare(
-            "SELECT session_id, files_changed, lines_added, lines_removed, has_uncommitted, calculated_at
-             FROM git_stats WHERE session_id = ?1",
-        )?;
-        let result: rusqlite::Result<GitStats> = stmt.query_row(params![session_id], |row| {
-            Ok(GitStats {
-                session_id: row.get(0)?,
-                files_changed: row.get(1)?,
-                lines_added: row.get(2)?,
-                lines_removed: row.get(3)?,
-                has_uncommitted: row.get(4)?,
-                calculated_at: Utc.timestamp_opt(row.get(5)?, 0).unwrap(),
-                last_diff_change_ts: None,
-            })
-        });
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {GIT_STATS_COLUMNS} FROM git_stats WHERE session_id = ?1"
+        ))?;
+        let result: rusqlite::Result<GitStats> =
+            stmt.query_row(params![session_id], git_stats_from_row);
         match result {
             Ok(stats) => Ok(Some(stats)),
             Err(_) => Ok(None),
@@ -58,21 +82,8 @@ impl GitStatsMethods for Database {
 
     fn get_all_git_stats(&self) -> Result<Vec<GitStats>> {
         let conn = self.get_conn()?;
-        let mut stmt = conn.prepare(
-            "SELECT session_id, files_changed, lines_added, lines_removed, has_uncommitted, calculated_at
-             FROM git_stats",
-        )?;
-        let stats_iter = stmt.query_map([], |row| {
-            Ok(GitStats {
-                session_id: row.get(0)?,
-                files_changed: row.get(1)?,
-                lines_added: row.get(2)?,
-                lines_removed: row.get(3)?,
-                has_uncommitted: row.get(4)?,
-                calculated_at: Utc.timestamp_opt(row.get(5)?, 0).unwrap(),
-                last_diff_change_ts: None,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!("SELECT {GIT_STATS_COLUMNS} FROM git_stats"))?;
+        let stats_iter = stmt.query_map([], git_stats_from_row)?;
 
         let mut results = Vec::new();
         for stat in stats_iter {
@@ -93,8 +104,7 @@ impl GitStatsMethods for Database {
             .collect::<Vec<_>>()
             .join(",");
         let query = format!(
-            "SELECT session_id, files_changed, lines_added, lines_removed, has_uncommitted, calculated_at
-             FROM git_stats WHERE session_id IN ({placeholders})"
+            "SELECT {GIT_STATS_COLUMNS} FROM git_stats WHERE session_id IN ({placeholders})"
         );
 
         let mut stmt = conn.prepare(&query)?;
@@ -103,17 +113,7 @@ impl GitStatsMethods for Database {
             .map(|id| id as &dyn rusqlite::ToSql)
             .collect();
 
-        let stats_iter = stmt.query_map(params.as_slice(), |row| {
-            Ok(GitStats {
-                session_id: row.get(0)?,
-                files_changed: row.get(1)?,
-                lines_added: row.get(2)?,
-                lines_removed: row.get(3)?,
-                has_uncommitted: row.get(4)?,
-                calculated_at: Utc.timestamp_opt(row.get(5)?, 0).unwrap(),
-                last_diff_change_ts: None,
-            })
-        })?;
+        let stats_iter = stmt.query_map(params.as_slice(), git_stats_from_row)?;
 
         let mut results = Vec::new();
         for stat in stats_iter {
@@ -139,4 +139,19 @@ impl GitStatsMethods for Database {
             Err(_) => Ok(true),
         }
     }
+    fn save_base_freshness(&self, session_id: &str, freshness: &BaseFreshness) -> Result<()> {
+        let conn = self.get_conn()?;
+        // Stats rows are created with the session; without one there is nothing to attach to
+        conn.execute(
+            "UPDATE git_stats SET base_ahead = ?1, base_behind = ?2, base_checked_at = ?3
+             WHERE session_id = ?4",
+            params![
+                freshness.ahead,
+                freshness.behind,
+                freshness.checked_at.timestamp(),
+                session_id,
+            ],
+        )?;
+        Ok(())
+    }
 }
@@ -9,8 +9,10 @@ use log::{debug, info};
 use serde::Deserialize;
 
 use super::branches::branch_exists;
+use super::hooks::GitHooks;
 use super::operations::{commit_all_changes, has_uncommitted_changes};
 use super::repository::get_current_branch;
+use super::signing::CommitSigning;
 use super::worktrees::update_worktree_branch;
 
 #[derive(Debug, Clone)]
@@ -398,7 +400,13 @@ impl<R: CommandRunner> GitHubCli<R> {
                 "Staging and committing changes in '{}' before PR",
                 opts.worktree_path.display()
             );
-            commit_all_changes(opts.worktree_path, &commit_message).map_err(GitHubCliError::Git)?;
+            commit_all_changes(
+                opts.worktree_path,
+                &commit_message,
+                opts.commit_signing,
+                opts.git_hooks,
+            )
+            .map_err(GitHubCliError::Git)?;
         } else {
             debug!("No uncommitted changes detected prior to PR creation");
         }
@@ -547,6 +555,85 @@ impl<R: CommandRunner> GitHubCli<R> {
         Ok(Some(response.url))
     }
 
+    /// Push a session branch to the remote it tracks, falling back to `origin` or
+    /// the only configured remote, and set it as upstream. Returns the remote name.
+    pub fn push_session_branch(
+        &self,
+        worktree_path: &Path,
+        branch_name: &str,
+    ) -> Result<String, GitHubCliError> {
+        let remote = detect_push_remote(worktree_path, branch_name)?;
+        let env = [("GIT_TERMINAL_PROMPT", "0")];
+        let args_vec = vec![
+            "push".to_string(),
+            "--set-upstream".to_string(),
+            remote.clone(),
+            branch_name.to_string(),
+        ];
+        let arg_refs: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
+
+        let output = self
+            .runner
+            .run("git", &arg_refs, Some(worktree_path), &env)
+            .map_err(map_runner_error)?;
+
+        if !output.success() {
+            return Err(command_failure("git", &args_vec, output));
+        }
+
+        info!("Pushed session branch '{branch_name}' to '{remote}'");
+        Ok(remote)
+    }
+
+    /// Open a pull request for an already pushed session branch, or return the URL
+    /// of the one that is already open.
+    pub fn create_session_pull_request(
+        &self,
+        opts: SessionPrOptions<'_>,
+    ) -> Result<String, GitHubCliError> {
+        let env = [("GH_PROMPT_DISABLED", "1"), ("NO_COLOR", "1")];
+        let mut args_vec = vec![
+            "pr".to_string(),
+            "create".to_string(),
+            "--head".to_string(),
+            opts.branch.to_string(),
+            "--base".to_string(),
+            opts.base_branch.to_string(),
+            "--title".to_string(),
+            opts.title.to_string(),
+            "--body".to_string(),
+            opts.body.to_string(),
+        ];
+
+        if let Some(repo) = opts.repository {
+            args_vec.push("--repo".to_string());
+            args_vec.push(repo.to_string());
+        }
+
+        let arg_refs: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
+        let output = self
+            .runner
+            .run(&self.program, &arg_refs, Some(opts.worktree_path), &env)
+            .map_err(map_runner_error)?;
+
+        if !output.success() {
+            if let Some(existing_url) =
+                self.view_existing_pr(opts.branch, opts.repository, opts.worktree_path)?
+            {
+                info!(
+                    "Reusing existing PR for branch '{}': {existing_url}",
+                    opts.branch
+                );
+                return Ok(existing_url);
+            }
+            return Err(command_failure(&self.program, &args_vec, output));
+        }
+
+        extract_pr_url(&combine_output(&output)).ok_or_else(|| {
+            GitHubCliError::InvalidOutput("GitHub CLI did not return a pull request URL.".into())
+        })
+    }
+
     pub fn authenticate(&self) -> Result<(), GitHubCliError> {
         Err(GitHubCliError::CommandFailed {
             program: "gh".to_string(),
@@ -571,6 +658,68 @@ pub struct CreatePrOptions<'a> {
     pub default_branch: &'a str,
     pub commit_message: Option<&'a str>,
     pub repository: Option<&'a str>,
+    pub commit_signing: CommitSigning,
+    pub git_hooks: GitHooks,
+}
+
+pub struct SessionPrOptions<'a> {
+    pub worktree_path: &'a Path,
+    pub branch: &'a str,
+    pub base_branch: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+    pub repository: Option<&'a str>,
+}
+
+/// Pull request title and body for a session: the first line of its spec becomes
+/// the title and the rest the body. Sessions without a spec use their name.
+pub fn session_pull_request_text(session_name: &str, spec: Option<&str>) -> (String, String) {
+    let spec = spec.unwrap_or_default().trim();
+    let (title, body) = spec.split_once('\n').unwrap_or((spec, ""));
+    let title = title.trim_start_matches('#').trim();
+    let title = if title.is_empty() {
+        session_name.to_string()
+    } else {
+        title.to_string()
+    };
+    let body = body.trim();
+    let footer = format!("Created from Schaltwerk session `{session_name}`.");
+    let body = if body.is_empty() {
+        footer
+    } else {
+        format!("{body}\n\n{footer}")
+    };
+    (title, body)
+}
+
+/// The remote a branch tracks, else `origin`, else the only remote there is.
+fn detect_push_remote(worktree_path: &Path, branch_name: &str) -> Result<String, GitHubCliError> {
+    let repo = Repository::open(worktree_path)
+        .map_err(|err| GitHubCliError::Git(AnyhowError::new(err)))?;
+
+    let tracked = repo
+        .config()
+        .and_then(|config| config.get_string(&format!("branch.{branch_name}.remote")))
+        .ok()
+        .filter(|remote| !remote.trim().is_empty() && remote != ".");
+    if let Some(remote) = tracked {
+        return Ok(remote);
+    }
+
+    let remotes = repo
+        .remotes()
+        .map_err(|err| GitHubCliError::Git(AnyhowError::new(err)))?;
+    let names: Vec<&str> = remotes.iter().flatten().collect();
+    if names.contains(&"origin") {
+        return Ok("origin".to_string());
+    }
+    match names.as_slice() {
+        [only] => Ok(only.to_string()),
+        [] => Err(GitHubCliError::NoGitRemote),
+        _ => Err(GitHubCliError::Git(AnyhowError::msg(format!(
+            "Branch '{branch_name}' has no upstream and there is no 'origin' remote. Set an upstream for it first."
+        )))),
+    }
 }
 
 fn map_runner_error(err: io::Error) -> GitHubCliError {
@@ -922,6 +1071,8 @@ mod tests {
             default_branch: "main",
             commit_message: Some("feat: demo"),
             repository: Some("owner/repo"),
+            commit_signing: CommitSigning::default(),
+            git_hooks: GitHooks::default(),
         };
 
         let result = cli.create_pr_from_worktree(opts).expect("pr result");
@@ -993,6 +1144,8 @@ mod tests {
             default_branch: "main",
             commit_message: Some("feat: demo"),
             repository: Some("owner/repo"),
+            commit_signing: CommitSigning::default(),
+            git_hooks: GitHooks::default(),
         };
 
         let result = cli.create_pr_from_worktree(opts).expect("pr result");
@@ -1064,6 +1217,8 @@ mod tests {
             default_branch: "main",
             commit_message: Some("feat: demo"),
             repository: Some("owner/repo"),
+            commit_signing: CommitSigning::default(),
+            git_hooks: GitHooks::default(),
         };
 
         let result = cli.create_pr_from_worktree(opts).expect("pr result");
@@ -1090,6 +1245,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn push_session_branch_prefers_origin_and_sets_upstream() {
+        let runner = MockRunner::default();
+        runner.push_response(Ok(CommandOutput {
+            status: Some(0),
+            stdout: String::new(),
+            stderr: String::new(),
+        }));
+        let cli = GitHubCli::with_runner(runner.clone());
+
+        let temp = TempDir::new().unwrap();
+        let repo = git2::Repository::init(temp.path()).unwrap();
+        repo.remote("upstream", "https://github.com/other/repo.git")
+            .unwrap();
+        repo.remote("origin", "https://github.com/owner/repo.git")
+            .unwrap();
+
+        let remote = cli
+            .push_session_branch(temp.path(), "schaltwerk/demo")
+            .expect("push");
+        assert_eq!(remote, "origin");
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].program, "git");
+        assert_eq!(
+            calls[0].args,
+            vec!["push", "--set-upstream", "origin", "schaltwerk/demo"]
+        );
+    }
+
+    #[test]
+    fn push_session_branch_requires_a_remote() {
+        let cli = GitHubCli::with_runner(MockRunner::default());
+        let temp = TempDir::new().unwrap();
+        git2::Repository::init(temp.path()).unwrap();
+
+        let err = cli
+            .push_session_branch(temp.path(), "schaltwerk/demo")
+            .unwrap_err();
+        assert!(matches!(err, GitHubCliError::NoGitRemote));
+    }
+
+    #[test]
+    fn create_session_pull_request_sends_title_and_body() {
+        let runner = MockRunner::default();
+        runner.push_response(Ok(CommandOutput {
+            status: Some(0),
+            stdout: "https://github.com/owner/repo/pull/7\n".to_string(),
+            stderr: String::new(),
+        }));
+        let cli = GitHubCli::with_runner(runner.clone());
+        let temp = TempDir::new().unwrap();
+
+        let (title, body) = session_pull_request_text("demo", Some("# Add demo\n\nExpose `demo`."));
+        let url = cli
+            .create_session_pull_request(SessionPrOptions {
+                worktree_path: temp.path(),
+                branch: "schaltwerk/demo",
+                base_branch: "main",
+                title: &title,
+                body: &body,
+                repository: Some("owner/repo"),
+            })
+            .expect("pr url");
+
+        assert_eq!(url, "https://github.com/owner/repo/pull/7");
+        let args = &runner.calls()[0].args;
+        assert!(args.windows(2).any(|w| w == ["--title", "Add demo"]));
+        assert!(args.windows(2).any(|w| w == ["--base", "main"]));
+        assert_eq!(
+            body,
+            "Expose `demo`.\n\nCreated from Schaltwerk session `demo`."
+        );
+    }
+
+    #[test]
+    fn session_pull_request_text_falls_back_to_session_name() {
+        let (title, body) = session_pull_request_text("demo", None);
+        assert_eq!(title, "demo");
+        assert_eq!(body, "Created from Schaltwerk session `demo`.");
+    }
+
     #[test]
     fn sanitize_branch_component_squashes_invalid_chars() {
         assert_eq!(sanitize_branch_component("My Session #1"), "my-session-1");
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+/// Hooks `git commit` runs before the commit is recorded.
+const COMMIT_HOOKS: [&str; 3] = ["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// Whether git hooks run for commits Schaltwerk creates (auto-commit on review, merges).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GitHooks {
+    #[default]
+    Run,
+    /// Commit with `--no-verify`
+    Skip,
+}
+
+impl GitHooks {
+    pub fn from_preference(run_git_hooks: bool) -> Self {
+        if run_git_hooks {
+            Self::Run
+        } else {
+            Self::Skip
+        }
+    }
+
+    /// Extra arguments for `git commit` so the CLI matches this setting.
+    pub fn git_args(self) -> &'static [&'static str] {
+        match self {
+            Self::Run => &[],
+            Self::Skip => &["--no-verify"],
+        }
+    }
+
+    /// Commit hooks installed for `worktree_path` that a commit would run; empty when
+    /// hooks are skipped.
+    pub fn active_hooks(self, worktree_path: &Path) -> Vec<String> {
+        if self == Self::Skip {
+            return Vec::new();
+        }
+        let Some(hooks_dir) = hooks_dir(worktree_path) else {
+            return Vec::new();
+        };
+        COMMIT_HOOKS
+            .iter()
+            .filter(|hook| is_executable(&hooks_dir.join(hook)))
+            .map(|hook| hook.to_string())
+            .collect()
+    }
+}
+
+/// A commit git refused while running hooks; `output` is what the hooks and git printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    pub hooks: Vec<String>,
+    pub output: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "git commit failed while running hooks ({}): {}",
+            self.hooks.join(", "),
+            self.output
+        )
+    }
+}
+
+impl std::error::Error for HookFailure {}
+
+/// `core.hooksPath` when set (relative to the worktree root), else the shared `hooks`
+/// directory of the repository, which linked worktrees use as well.
+fn hooks_dir(worktree_path: &Path) -> Option<PathBuf> {
+    let repo = Repository::open(worktree_path).ok()?;
+    let configured = repo
+        .config()
+        .and_then(|config| config.get_path("core.hooksPath"))
+        .ok();
+    match configured {
+        Some(path) if path.is_absolute() => Some(path),
+        Some(path) => Some(repo.workdir().unwrap_or(worktree_path).join(path)),
+        None => Some(repo.commondir().join("hooks")),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
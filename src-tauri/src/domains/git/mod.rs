@@ -2,9 +2,12 @@ pub mod branches;
 pub mod db_git_stats;
 pub mod github_cli;
 pub mod history;
+pub mod hooks;
 pub mod operations;
+pub mod rebase;
 pub mod repository;
 pub mod service;
+pub mod signing;
 pub mod stats;
 pub mod worktrees;
 
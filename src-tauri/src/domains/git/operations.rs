@@ -1,6 +1,15 @@
-use anyhow::{anyhow, Result};
-use git2::{IndexAddOption, Repository, Status, StatusOptions};
+use anyhow::{anyhow, Context, Result};
+use git2::{
+    Delta, DiffFormat, DiffOptions, IndexAddOption, Repository, StashApplyOptions, StashFlags,
+    Status, StatusOptions,
+};
+use log::debug;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::process::Command;
+
+use super::hooks::{GitHooks, HookFailure};
+use super::signing::{is_signing_failure, signing_error, CommitSigning};
 
 #[inline]
 fn is_internal_tooling_path(path: &str) -> bool {
@@ -88,7 +97,81 @@ pub fn uncommitted_sample_paths(worktree_path: &Path, limit: usize) -> Result<Ve
     Ok(out)
 }
 
-pub fn commit_all_changes(worktree_path: &Path, message: &str) -> Result<()> {
+/// Summary of what `commit_all_changes` would commit: one `<status> <path>` line per
+/// changed file followed by the patch, cut off after `max_patch_bytes`. Empty when the
+/// worktree has nothing to commit.
+pub fn uncommitted_diff_summary(worktree_path: &Path, max_patch_bytes: usize) -> Result<String> {
+    let repo = Repository::open(worktree_path)?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))?;
+
+    let delta_path = |delta: &git2::DiffDelta<'_>| {
+        delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut files = String::new();
+    for delta in diff.deltas() {
+        let path = delta_path(&delta);
+        if is_internal_tooling_path(&path) {
+            continue;
+        }
+        let status = match delta.status() {
+            Delta::Added | Delta::Untracked => 'A',
+            Delta::Deleted => 'D',
+            Delta::Renamed => 'R',
+            _ => 'M',
+        };
+        files.push_str(&format!("{status} {path}\n"));
+    }
+    if files.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut patch = String::new();
+    let mut truncated = false;
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        if truncated || is_internal_tooling_path(&delta_path(&delta)) {
+            return true;
+        }
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        if patch.len() > max_patch_bytes {
+            let mut end = max_patch_bytes;
+            while !patch.is_char_boundary(end) {
+                end -= 1;
+            }
+            patch.truncate(end);
+            truncated = true;
+        }
+        true
+    })?;
+    if truncated {
+        patch.push_str("\n[diff truncated]\n");
+    }
+
+    Ok(format!("{files}\n{patch}"))
+}
+
+/// Stage and commit everything in the worktree. When `signing` allows it and git is
+/// configured to sign commits, or when `hooks` allows installed commit hooks to run,
+/// the commit goes through the git CLI.
+pub fn commit_all_changes(
+    worktree_path: &Path,
+    message: &str,
+    signing: CommitSigning,
+    hooks: GitHooks,
+) -> Result<()> {
     let repo = Repository::open(worktree_path)?;
 
     // Get the index
@@ -122,6 +205,18 @@ pub fn commit_all_changes(worktree_path: &Path, message: &str) -> Result<()> {
         }
     }
 
+    // libgit2 can neither sign nor run hooks, so either one goes through the CLI
+    let signing_format = signing.active_format(worktree_path);
+    let active_hooks = hooks.active_hooks(worktree_path);
+    if signing_format.is_some() || !active_hooks.is_empty() {
+        return commit_index_with_git(
+            worktree_path,
+            message,
+            signing_format.as_deref(),
+            &active_hooks,
+        );
+    }
+
     // Get the signature from git config
     let signature = repo.signature()
         .map_err(|e| anyhow!("Failed to get signature from git config: {e}. Please configure git user.name and user.email"))?;
@@ -145,6 +240,155 @@ pub fn commit_all_changes(worktree_path: &Path, message: &str) -> Result<()> {
     Ok(())
 }
 
+/// Commit the already written index through the git CLI. Signs when `signing_format` is
+/// set and runs the commit hooks only when some are installed, so an empty `hooks`
+/// matches a commit made through libgit2.
+fn commit_index_with_git(
+    worktree_path: &Path,
+    message: &str,
+    signing_format: Option<&str>,
+    hooks: &[String],
+) -> Result<()> {
+    let mut args = vec!["commit", "--quiet"];
+    if hooks.is_empty() {
+        args.push("--no-verify");
+    }
+    args.push(if signing_format.is_some() {
+        "-S"
+    } else {
+        "--no-gpg-sign"
+    });
+    args.extend(["-m", message]);
+    debug!(
+        "Committing in {} through git (signing: {signing_format:?}, hooks: {hooks:?})",
+        worktree_path.display()
+    );
+
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(worktree_path)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .context("Failed to execute git commit")?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if output.status.success() {
+        if !stdout.is_empty() || !stderr.is_empty() {
+            debug!("git commit hook output: {stdout}\n{stderr}");
+        }
+        return Ok(());
+    }
+
+    if let Some(format) = signing_format.filter(|_| is_signing_failure(&stderr)) {
+        return Err(signing_error(format, &stderr));
+    }
+    if !hooks.is_empty() {
+        let output = [stdout, stderr]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(HookFailure {
+            hooks: hooks.to_vec(),
+            output,
+        }
+        .into());
+    }
+    Err(anyhow!("git commit failed: {stderr}"))
+}
+
+/// A stash entry created for a session worktree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStash {
+    /// Position in the repository's stash list, newest first
+    pub index: usize,
+    pub id: String,
+    pub message: String,
+}
+
+// Stashes live in the shared `refs/stash`, so every worktree sees every entry.
+// Tagging the message keeps each session to its own.
+fn session_stash_tag(session_name: &str) -> String {
+    format!("[session:{session_name}]")
+}
+
+/// Park all uncommitted changes, untracked files included. Returns `None` when
+/// there is nothing to stash.
+pub fn stash_session_changes(
+    worktree_path: &Path,
+    session_name: &str,
+    message: Option<&str>,
+) -> Result<Option<SessionStash>> {
+    if !has_uncommitted_changes(worktree_path)? {
+        return Ok(None);
+    }
+
+    let mut repo = Repository::open(worktree_path)?;
+    let signature = repo.signature()
+        .map_err(|e| anyhow!("Failed to get signature from git config: {e}. Please configure git user.name and user.email"))?;
+    let label = message
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .unwrap_or("Parked changes");
+    let stash_message = format!("{label} {}", session_stash_tag(session_name));
+    repo.stash_save(
+        &signature,
+        &stash_message,
+        Some(StashFlags::INCLUDE_UNTRACKED),
+    )?;
+
+    Ok(list_session_stashes(worktree_path, session_name)?
+        .into_iter()
+        .next())
+}
+
+/// The session's stashes, newest first.
+pub fn list_session_stashes(worktree_path: &Path, session_name: &str) -> Result<Vec<SessionStash>> {
+    let mut repo = Repository::open(worktree_path)?;
+    let tag = session_stash_tag(session_name);
+
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        if message.contains(&tag) {
+            stashes.push(SessionStash {
+                index,
+                id: oid.to_string(),
+                message: message.replace(&tag, "").trim_end().to_string(),
+            });
+        }
+        true
+    })?;
+    Ok(stashes)
+}
+
+/// Restore a session stash into the worktree and drop it. Pops the newest one
+/// unless `stash_id` picks another. Fails without touching the stash when the
+/// restore would overwrite local changes.
+pub fn pop_session_stash(
+    worktree_path: &Path,
+    session_name: &str,
+    stash_id: Option<&str>,
+) -> Result<SessionStash> {
+    let stashes = list_session_stashes(worktree_path, session_name)?;
+    let stash = match stash_id {
+        Some(id) => stashes
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| anyhow!("Stash {id} does not belong to session '{session_name}'"))?,
+        None => stashes
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Session '{session_name}' has no stashed changes"))?,
+    };
+
+    let mut repo = Repository::open(worktree_path)?;
+    let mut options = StashApplyOptions::new();
+    options.reinstantiate_index();
+    repo.stash_pop(stash.index, Some(&mut options))
+        .map_err(|e| anyhow!("Failed to restore stash for session '{session_name}': {e}"))?;
+    Ok(stash)
+}
+
 pub fn is_valid_session_name(name: &str) -> bool {
     if name.is_empty() || name.len() > 100 {
         return false;
@@ -432,8 +676,13 @@ mod tests {
         fs::write(temp_dir.path().join("file2.txt"), "content2").expect("Failed to write file");
 
         // Commit all changes
-        commit_all_changes(temp_dir.path(), "Test commit message")
-            .expect("Should commit all changes");
+        commit_all_changes(
+            temp_dir.path(),
+            "Test commit message",
+            CommitSigning::default(),
+            GitHooks::default(),
+        )
+        .expect("Should commit all changes");
 
         // Verify no uncommitted changes remain
         let has_changes = has_uncommitted_changes(temp_dir.path()).expect("Should check status");
@@ -478,8 +727,13 @@ mod tests {
             .expect("Failed to create initial commit");
 
         // Try to commit when there are no changes
-        commit_all_changes(temp_dir.path(), "Should not create this commit")
-            .expect("Should handle no-changes case gracefully");
+        commit_all_changes(
+            temp_dir.path(),
+            "Should not create this commit",
+            CommitSigning::default(),
+            GitHooks::default(),
+        )
+        .expect("Should handle no-changes case gracefully");
 
         // Verify HEAD hasn't moved
         let repo = Repository::open(temp_dir.path()).expect("Failed to open repo");
@@ -511,7 +765,13 @@ mod tests {
             .expect("Failed to write file");
 
         // Commit - should handle first commit case
-        commit_all_changes(temp_dir.path(), "First commit").expect("Should create first commit");
+        commit_all_changes(
+            temp_dir.path(),
+            "First commit",
+            CommitSigning::default(),
+            GitHooks::default(),
+        )
+        .expect("Should create first commit");
 
         // Verify commit was created
         let repo = Repository::open(temp_dir.path()).expect("Failed to open repo");
@@ -526,6 +786,126 @@ mod tests {
         );
     }
 
+    fn init_signing_repo(signing_program: &str) -> TempDir {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        run_git(temp_dir.path(), &["init"]);
+        run_git(
+            temp_dir.path(),
+            &["config", "user.email", "test@example.com"],
+        );
+        run_git(temp_dir.path(), &["config", "user.name", "Test User"]);
+        run_git(temp_dir.path(), &["config", "commit.gpgsign", "true"]);
+        run_git(temp_dir.path(), &["config", "gpg.program", signing_program]);
+        fs::write(temp_dir.path().join("README.md"), "# Signed\n").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_commit_all_changes_surfaces_signing_failure() {
+        let temp_dir = init_signing_repo("false");
+
+        let err = commit_all_changes(
+            temp_dir.path(),
+            "Signed",
+            CommitSigning::default(),
+            GitHooks::default(),
+        )
+        .expect_err("signing with a failing gpg program should fail");
+        let message = err.to_string();
+        assert!(
+            message.contains("Commit signing (openpgp) failed"),
+            "{message}"
+        );
+        assert!(message.contains("Settings"), "{message}");
+
+        commit_all_changes(
+            temp_dir.path(),
+            "Unsigned",
+            CommitSigning::Disabled,
+            GitHooks::default(),
+        )
+        .expect("disabled signing should commit without a key");
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        assert_eq!(repo.find_commit(head).unwrap().message(), Some("Unsigned"));
+        assert!(repo.extract_signature(&head, None).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_all_changes_signs_when_configured() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tools = TempDir::new().unwrap();
+        let fake_gpg = tools.path().join("fake-gpg");
+        fs::write(
+            &fake_gpg,
+            "#!/bin/sh\ncat >/dev/null\necho >&2\necho '[GNUPG:] SIG_CREATED D 1 8 00 0 FAKE' >&2\nprintf '%s\\n' '-----BEGIN PGP SIGNATURE-----' 'ZmFrZQ==' '-----END PGP SIGNATURE-----'\n",
+        )
+        .unwrap();
+        fs::set_permissions(&fake_gpg, fs::Permissions::from_mode(0o755)).unwrap();
+        let temp_dir = init_signing_repo(fake_gpg.to_str().unwrap());
+
+        commit_all_changes(
+            temp_dir.path(),
+            "Signed",
+            CommitSigning::default(),
+            GitHooks::default(),
+        )
+        .expect("signing should succeed");
+
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        assert_eq!(repo.find_commit(head).unwrap().message(), Some("Signed\n"));
+        let (signature, _) = repo.extract_signature(&head, None).unwrap();
+        assert!(signature.as_str().unwrap().contains("BEGIN PGP SIGNATURE"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_all_changes_runs_or_skips_hooks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        run_git(temp_dir.path(), &["init"]);
+        run_git(
+            temp_dir.path(),
+            &["config", "user.email", "test@example.com"],
+        );
+        run_git(temp_dir.path(), &["config", "user.name", "Test User"]);
+        let hook = temp_dir.path().join(".git/hooks/pre-commit");
+        fs::write(
+            &hook,
+            "#!/bin/sh\necho 'lint: trailing whitespace'\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(temp_dir.path().join("README.md"), "# Hooks \n").unwrap();
+
+        let err = commit_all_changes(
+            temp_dir.path(),
+            "Checked",
+            CommitSigning::default(),
+            GitHooks::Run,
+        )
+        .expect_err("a failing pre-commit hook should reject the commit");
+        let failure = err.downcast_ref::<HookFailure>().expect("hook failure");
+        assert_eq!(failure.hooks, vec!["pre-commit".to_string()]);
+        assert!(failure.output.contains("lint: trailing whitespace"));
+        assert!(Repository::open(temp_dir.path()).unwrap().head().is_err());
+
+        commit_all_changes(
+            temp_dir.path(),
+            "Unchecked",
+            CommitSigning::default(),
+            GitHooks::Skip,
+        )
+        .expect("skipped hooks should not block the commit");
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let head = repo.head().unwrap().target().unwrap();
+        assert_eq!(repo.find_commit(head).unwrap().message(), Some("Unchecked"));
+    }
+
     #[test]
     fn test_has_conflicts_detects_merge_conflict() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -559,4 +939,88 @@ mod tests {
         let detected = has_conflicts(temp_dir.path()).expect("Conflict detection should succeed");
         assert!(detected, "Conflict must be reported");
     }
+
+    #[test]
+    fn test_session_stash_round_trip() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        run_git(temp_dir.path(), &["init"]);
+        run_git(
+            temp_dir.path(),
+            &["config", "user.email", "test@example.com"],
+        );
+        run_git(temp_dir.path(), &["config", "user.name", "Test User"]);
+        fs::write(temp_dir.path().join("file.txt"), "base\n").unwrap();
+        run_git(temp_dir.path(), &["add", "file.txt"]);
+        run_git(temp_dir.path(), &["commit", "-m", "initial"]);
+
+        assert!(stash_session_changes(temp_dir.path(), "alpha", None)
+            .unwrap()
+            .is_none());
+
+        fs::write(temp_dir.path().join("file.txt"), "agent edit\n").unwrap();
+        fs::write(temp_dir.path().join("new.txt"), "untracked\n").unwrap();
+        let stash = stash_session_changes(temp_dir.path(), "alpha", Some("before reset"))
+            .unwrap()
+            .expect("changes should be stashed");
+        assert!(stash.message.ends_with("before reset"));
+        assert!(!has_uncommitted_changes(temp_dir.path()).unwrap());
+        assert!(!temp_dir.path().join("new.txt").exists());
+
+        // Another session's stash stays out of alpha's list
+        fs::write(temp_dir.path().join("file.txt"), "other session\n").unwrap();
+        stash_session_changes(temp_dir.path(), "beta", None)
+            .unwrap()
+            .unwrap();
+        let stashes = list_session_stashes(temp_dir.path(), "alpha").unwrap();
+        assert_eq!(stashes.len(), 1);
+        assert_eq!(stashes[0].id, stash.id);
+        assert_eq!(stashes[0].index, 1);
+
+        assert!(pop_session_stash(temp_dir.path(), "beta", Some(&stash.id)).is_err());
+        pop_session_stash(temp_dir.path(), "alpha", None).unwrap();
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("file.txt")).unwrap(),
+            "agent edit\n"
+        );
+        assert!(temp_dir.path().join("new.txt").exists());
+        assert!(list_session_stashes(temp_dir.path(), "alpha")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            list_session_stashes(temp_dir.path(), "beta").unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_uncommitted_diff_summary_lists_changes_and_truncates_patch() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        run_git(temp_dir.path(), &["init"]);
+        run_git(
+            temp_dir.path(),
+            &["config", "user.email", "test@example.com"],
+        );
+        run_git(temp_dir.path(), &["config", "user.name", "Test User"]);
+        fs::write(temp_dir.path().join("file.txt"), "base\n").unwrap();
+        run_git(temp_dir.path(), &["add", "file.txt"]);
+        run_git(temp_dir.path(), &["commit", "-m", "initial"]);
+
+        assert!(uncommitted_diff_summary(temp_dir.path(), 1000)
+            .unwrap()
+            .is_empty());
+
+        fs::write(temp_dir.path().join("file.txt"), "agent edit\n").unwrap();
+        fs::write(temp_dir.path().join("new.txt"), "untracked\n").unwrap();
+        fs::create_dir_all(temp_dir.path().join(".schaltwerk")).unwrap();
+        fs::write(temp_dir.path().join(".schaltwerk/temp.txt"), "internal\n").unwrap();
+
+        let summary = uncommitted_diff_summary(temp_dir.path(), 1000).unwrap();
+        assert!(summary.starts_with("M file.txt\nA new.txt\n"), "{summary}");
+        assert!(summary.contains("+agent edit"), "{summary}");
+        assert!(summary.contains("+untracked"), "{summary}");
+        assert!(!summary.contains(".schaltwerk"), "{summary}");
+
+        let truncated = uncommitted_diff_summary(temp_dir.path(), 10).unwrap();
+        assert!(truncated.ends_with("[diff truncated]\n"), "{truncated}");
+    }
 }
@@ -0,0 +1,381 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use git2::{BranchType, Repository, Status, StatusOptions};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::operations::{has_uncommitted_changes, uncommitted_sample_paths};
+use crate::domains::sessions::entity::BaseFreshness;
+
+/// What happened when a session was rebased onto its base branch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SessionRebaseOutcome {
+    /// The session already contains the tip of the base branch
+    UpToDate { base_commit: String },
+    Rebased {
+        base_commit: String,
+        head_commit: String,
+    },
+    /// The rebase stopped on conflicts and was aborted; the worktree is unchanged
+    Conflicts {
+        base_commit: String,
+        conflicting_paths: Vec<String>,
+    },
+}
+
+/// Bring the local base branch up to date with its remote, then rebase the session
+/// worktree onto it. Conflicts abort the rebase so the session is left as it was.
+/// Repositories without a remote skip the fetch and rebase onto the local branch.
+pub fn rebase_session_on_base(
+    repo_path: &Path,
+    worktree_path: &Path,
+    base_branch: &str,
+) -> Result<SessionRebaseOutcome> {
+    if has_uncommitted_changes(worktree_path)? {
+        let sample = uncommitted_sample_paths(worktree_path, 3)
+            .unwrap_or_default()
+            .join(", ");
+        return Err(anyhow!(
+            "Commit or stash the session's changes before rebasing. Uncommitted: {sample}"
+        ));
+    }
+
+    if let Some(remote) = base_remote(repo_path, base_branch)? {
+        if let Err(e) = update_base_from_remote(repo_path, &remote, base_branch) {
+            warn!("Could not update '{base_branch}' from '{remote}', rebasing onto the local branch: {e}");
+        }
+    }
+
+    let repo = Repository::open(worktree_path)?;
+    let base_oid = repo
+        .find_branch(base_branch, BranchType::Local)
+        .with_context(|| format!("Base branch '{base_branch}' not found"))?
+        .get()
+        .target()
+        .ok_or_else(|| anyhow!("Base branch '{base_branch}' has no target"))?;
+    let head_oid = repo
+        .head()?
+        .target()
+        .ok_or_else(|| anyhow!("Session HEAD has no target"))?;
+    let base_commit = base_oid.to_string();
+
+    if repo.merge_base(head_oid, base_oid)? == base_oid {
+        debug!(
+            "Session at {} already contains '{base_branch}'",
+            worktree_path.display()
+        );
+        return Ok(SessionRebaseOutcome::UpToDate { base_commit });
+    }
+
+    if let Err(e) = run_git(worktree_path, &["rebase", base_branch]) {
+        let conflicting_paths = conflicting_paths(&repo)?;
+        if let Err(abort_err) = run_git(worktree_path, &["rebase", "--abort"]) {
+            warn!(
+                "Failed to abort rebase in {}: {abort_err}",
+                worktree_path.display()
+            );
+        }
+        if conflicting_paths.is_empty() {
+            return Err(e);
+        }
+        info!(
+            "Rebase of {} onto '{base_branch}' hit conflicts in {conflicting_paths:?}",
+            worktree_path.display()
+        );
+        return Ok(SessionRebaseOutcome::Conflicts {
+            base_commit,
+            conflicting_paths,
+        });
+    }
+
+    let head_commit = Repository::open(worktree_path)?
+        .head()?
+        .target()
+        .ok_or_else(|| anyhow!("Session HEAD has no target"))?
+        .to_string();
+    info!(
+        "Rebased {} onto '{base_branch}' at {base_commit}",
+        worktree_path.display()
+    );
+    Ok(SessionRebaseOutcome::Rebased {
+        base_commit,
+        head_commit,
+    })
+}
+
+/// Update the remote-tracking ref of the base branch. Local branches are left alone.
+pub fn fetch_base_branch(repo_path: &Path, remote: &str, base_branch: &str) -> Result<()> {
+    run_git(
+        repo_path,
+        &["fetch", "--quiet", "--no-tags", remote, base_branch],
+    )
+}
+
+/// Compare the session HEAD with its base branch. The fetched remote tip is used when it
+/// is ahead of the local branch, so upstream progress shows up before anyone pulls.
+pub fn base_freshness(
+    worktree_path: &Path,
+    base_branch: &str,
+    remote: Option<&str>,
+) -> Result<BaseFreshness> {
+    let repo = Repository::open(worktree_path)?;
+    let branch_tip = |name: &str, kind: BranchType| {
+        repo.find_branch(name, kind)
+            .ok()
+            .and_then(|branch| branch.get().target())
+    };
+    let local_oid = branch_tip(base_branch, BranchType::Local);
+    let remote_oid = remote
+        .and_then(|remote| branch_tip(&format!("{remote}/{base_branch}"), BranchType::Remote));
+    let base_oid = match (local_oid, remote_oid) {
+        (Some(local), Some(remote)) if repo.graph_descendant_of(remote, local)? => remote,
+        (Some(local), _) => local,
+        (None, Some(remote)) => remote,
+        (None, None) => return Err(anyhow!("Base branch '{base_branch}' not found")),
+    };
+    let head_oid = repo
+        .head()?
+        .target()
+        .ok_or_else(|| anyhow!("Session HEAD has no target"))?;
+
+    let (ahead, behind) = repo.graph_ahead_behind(head_oid, base_oid)?;
+    Ok(BaseFreshness {
+        ahead: ahead as u32,
+        behind: behind as u32,
+        checked_at: Utc::now(),
+    })
+}
+
+/// The remote the base branch tracks, else `origin` when it exists.
+pub fn base_remote(repo_path: &Path, base_branch: &str) -> Result<Option<String>> {
+    let repo = Repository::open(repo_path)?;
+    let tracked = repo
+        .config()
+        .and_then(|config| config.get_string(&format!("branch.{base_branch}.remote")))
+        .ok()
+        .filter(|remote| !remote.trim().is_empty() && remote != ".");
+    if tracked.is_some() {
+        return Ok(tracked);
+    }
+    let remotes = repo.remotes()?;
+    Ok(remotes
+        .iter()
+        .flatten()
+        .any(|name| name == "origin")
+        .then(|| "origin".to_string()))
+}
+
+/// Fast-forward the local base branch to the fetched remote one. Never moves the
+/// branch when it has diverged or when that would touch uncommitted work.
+fn update_base_from_remote(repo_path: &Path, remote: &str, base_branch: &str) -> Result<()> {
+    run_git(repo_path, &["fetch", remote, base_branch])?;
+
+    let repo = Repository::open(repo_path)?;
+    let remote_ref = format!("{remote}/{base_branch}");
+    let remote_oid = match repo.find_branch(&remote_ref, BranchType::Remote) {
+        Ok(branch) => branch
+            .get()
+            .target()
+            .ok_or_else(|| anyhow!("Remote branch '{remote_ref}' has no target"))?,
+        Err(_) => {
+            debug!("No remote-tracking branch '{remote_ref}' after fetch");
+            return Ok(());
+        }
+    };
+    let local_oid = repo
+        .find_branch(base_branch, BranchType::Local)?
+        .get()
+        .target()
+        .ok_or_else(|| anyhow!("Base branch '{base_branch}' has no target"))?;
+    if local_oid == remote_oid || !repo.graph_descendant_of(remote_oid, local_oid)? {
+        return Ok(());
+    }
+
+    let checked_out_here = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string))
+        .is_some_and(|name| name == base_branch);
+    if checked_out_here {
+        // Updates the checkout too; git refuses if local edits would be overwritten
+        run_git(repo_path, &["merge", "--ff-only", &remote_ref])
+    } else {
+        // A local fetch only fast-forwards and refuses branches checked out elsewhere
+        let refspec = format!("refs/remotes/{remote_ref}:refs/heads/{base_branch}");
+        run_git(repo_path, &["fetch", ".", &refspec])
+    }
+}
+
+fn conflicting_paths(repo: &Repository) -> Result<Vec<String>> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status().contains(Status::CONFLICTED))
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect())
+}
+
+fn run_git(current_dir: &Path, args: &[&str]) -> Result<()> {
+    debug!("Running git {args:?} in {}", current_dir.display());
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(current_dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_EDITOR", "true")
+        .output()
+        .with_context(|| format!("Failed to execute git {args:?}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Err(anyhow!(
+        "git {} failed: {}",
+        args.join(" "),
+        if stderr.is_empty() { stdout } else { stderr }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        run_git(dir, args).unwrap();
+    }
+
+    fn commit_file(dir: &Path, name: &str, contents: &str, message: &str) {
+        fs::write(dir.join(name), contents).unwrap();
+        git(dir, &["add", name]);
+        git(dir, &["commit", "-m", message]);
+    }
+
+    /// A clone of a bare "remote" with a session worktree on its own branch.
+    fn setup() -> (
+        TempDir,
+        std::path::PathBuf,
+        std::path::PathBuf,
+        std::path::PathBuf,
+    ) {
+        let temp = TempDir::new().unwrap();
+        let origin = temp.path().join("origin.git");
+        let seed = temp.path().join("seed");
+        let repo = temp.path().join("repo");
+        let worktree = temp.path().join("worktree");
+
+        fs::create_dir_all(&seed).unwrap();
+        git(&seed, &["init", "-b", "main"]);
+        git(&seed, &["config", "user.email", "test@example.com"]);
+        git(&seed, &["config", "user.name", "Test User"]);
+        commit_file(&seed, "shared.txt", "base\n", "initial");
+        git(temp.path(), &["clone", "--bare", "seed", "origin.git"]);
+        git(temp.path(), &["clone", "origin.git", "repo"]);
+        git(&repo, &["config", "user.email", "test@example.com"]);
+        git(&repo, &["config", "user.name", "Test User"]);
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "schaltwerk/session",
+                worktree.to_str().unwrap(),
+                "main",
+            ],
+        );
+        git(
+            &seed,
+            &["remote", "add", "origin", origin.to_str().unwrap()],
+        );
+
+        (temp, seed, repo, worktree)
+    }
+
+    #[test]
+    fn rebases_onto_fetched_base() {
+        let (_temp, seed, repo, worktree) = setup();
+        commit_file(&worktree, "session.txt", "work\n", "session work");
+        commit_file(&seed, "upstream.txt", "upstream\n", "upstream work");
+        git(&seed, &["push", "origin", "main"]);
+
+        let outcome = rebase_session_on_base(&repo, &worktree, "main").unwrap();
+
+        let SessionRebaseOutcome::Rebased { base_commit, .. } = outcome else {
+            panic!("expected a rebase, got {outcome:?}");
+        };
+        let seed_head = Repository::open(&seed)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+        assert_eq!(base_commit, seed_head.to_string());
+        assert!(worktree.join("upstream.txt").exists());
+        assert!(worktree.join("session.txt").exists());
+        // The clone's checkout of main moved along with the branch
+        assert!(repo.join("upstream.txt").exists());
+
+        let again = rebase_session_on_base(&repo, &worktree, "main").unwrap();
+        assert!(matches!(again, SessionRebaseOutcome::UpToDate { .. }));
+    }
+
+    #[test]
+    fn conflicts_abort_and_leave_the_session_untouched() {
+        let (_temp, seed, repo, worktree) = setup();
+        commit_file(&worktree, "shared.txt", "session\n", "session edit");
+        commit_file(&seed, "shared.txt", "upstream\n", "upstream edit");
+        git(&seed, &["push", "origin", "main"]);
+        let before = Repository::open(&worktree)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+
+        let outcome = rebase_session_on_base(&repo, &worktree, "main").unwrap();
+
+        let SessionRebaseOutcome::Conflicts {
+            conflicting_paths, ..
+        } = outcome
+        else {
+            panic!("expected conflicts, got {outcome:?}");
+        };
+        assert_eq!(conflicting_paths, vec!["shared.txt".to_string()]);
+        let after = Repository::open(&worktree)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+        assert_eq!(before, after);
+        assert!(!has_uncommitted_changes(&worktree).unwrap());
+    }
+
+    #[test]
+    fn freshness_counts_fetched_upstream_commits() {
+        let (_temp, seed, repo, worktree) = setup();
+        commit_file(&worktree, "session.txt", "work\n", "session work");
+        commit_file(&seed, "one.txt", "1\n", "upstream one");
+        commit_file(&seed, "two.txt", "2\n", "upstream two");
+        git(&seed, &["push", "origin", "main"]);
+
+        let stale = base_freshness(&worktree, "main", Some("origin")).unwrap();
+        assert_eq!((stale.ahead, stale.behind), (1, 0));
+
+        let remote = base_remote(&repo, "main").unwrap().unwrap();
+        fetch_base_branch(&repo, &remote, "main").unwrap();
+        let fresh = base_freshness(&worktree, "main", Some(&remote)).unwrap();
+        assert_eq!((fresh.ahead, fresh.behind), (1, 2));
+        // Only the remote-tracking ref moved
+        assert!(!repo.join("one.txt").exists());
+    }
+}
@@ -1,3 +1,12 @@
+use super::db_git_stats::GitStatsMethods;
+use crate::domains::sessions::activity::{EventEmitter, SessionGitStatsUpdated};
+use crate::domains::sessions::db_sessions::SessionMethods;
+use crate::schaltwerk_core::database::Database;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
 // Re-export all the functions from the git domain modules
 pub use super::repository::{
     create_initial_commit, discover_repository, get_default_branch, init_repository,
@@ -8,21 +17,128 @@ pub use super::branches::{branch_exists, delete_branch, list_branches, rename_br
 #[cfg(test)]
 pub use super::repository::{get_commit_hash, get_current_branch};
 pub use super::worktrees::{
-    create_worktree_from_base, list_worktrees, prune_worktrees, remove_worktree,
-    update_worktree_branch,
+    create_worktree_from_base, discard_hunks_in_worktree, is_lfs_tracked_path, list_worktree_hunks,
+    list_worktrees, move_worktree, prune_worktrees, remove_worktree, update_worktree_branch,
+    WorktreeHunk,
 };
 
 pub use super::history::{
     get_commit_file_changes, get_git_history, CommitFileChange, HistoryProviderSnapshot,
 };
+pub use super::hooks::{GitHooks, HookFailure};
 pub use super::operations::{
     commit_all_changes, has_conflicts, has_uncommitted_changes, is_valid_branch_name,
-    is_valid_session_name,
+    is_valid_session_name, list_session_stashes, pop_session_stash, stash_session_changes,
+    uncommitted_diff_summary, SessionStash,
+};
+pub use super::rebase::{
+    base_freshness, base_remote, fetch_base_branch, rebase_session_on_base, SessionRebaseOutcome,
 };
+pub use super::signing::CommitSigning;
 pub use super::stats::{calculate_git_stats_fast, get_changed_files};
 #[cfg(test)]
 pub use super::worktrees::is_worktree_registered;
 
+/// How often session base branches are fetched for the ahead/behind counts
+const BASE_FRESHNESS_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Periodically fetch the base branch of every running session and record how far the
+/// session and its base have diverged, emitting `SessionGitStats` when the counts change.
+pub fn start_base_freshness_tracking<E: EventEmitter + 'static>(
+    db: Arc<Database>,
+    emitter: E,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(BASE_FRESHNESS_INTERVAL);
+        loop {
+            interval.tick().await;
+            let db = db.clone();
+            match tokio::task::spawn_blocking(move || refresh_base_freshness(&db)).await {
+                Ok(Ok(updates)) => {
+                    for payload in updates {
+                        let _ = emitter.emit_session_git_stats(payload);
+                    }
+                }
+                Ok(Err(e)) => log::warn!("Failed to refresh base branch freshness: {e}"),
+                Err(e) => log::warn!("Base branch freshness task panicked: {e}"),
+            }
+        }
+    })
+}
+
+fn refresh_base_freshness(db: &Database) -> anyhow::Result<Vec<SessionGitStatsUpdated>> {
+    // Sessions sharing a repository and base branch share a single fetch
+    let mut remotes: HashMap<(std::path::PathBuf, String), Option<String>> = HashMap::new();
+    let mut updates = Vec::new();
+
+    for session in db.list_all_active_sessions()? {
+        if !session.worktree_path.exists() {
+            continue;
+        }
+        let remote = remotes
+            .entry((
+                session.repository_path.clone(),
+                session.parent_branch.clone(),
+            ))
+            .or_insert_with(|| {
+                let remote = base_remote(&session.repository_path, &session.parent_branch)
+                    .ok()
+                    .flatten()?;
+                if let Err(e) =
+                    fetch_base_branch(&session.repository_path, &remote, &session.parent_branch)
+                {
+                    log::debug!(
+                        "Could not fetch '{}' from '{remote}', using the last fetched tip: {e}",
+                        session.parent_branch
+                    );
+                }
+                Some(remote)
+            })
+            .clone();
+
+        let freshness = match base_freshness(
+            &session.worktree_path,
+            &session.parent_branch,
+            remote.as_deref(),
+        ) {
+            Ok(freshness) => freshness,
+            Err(e) => {
+                log::debug!("Skipping base freshness for '{}': {e}", session.name);
+                continue;
+            }
+        };
+        let Some(stats) = db.get_git_stats(&session.id)? else {
+            continue;
+        };
+        db.save_base_freshness(&session.id, &freshness)?;
+
+        let unchanged = stats
+            .base_freshness
+            .as_ref()
+            .is_some_and(|prev| (prev.ahead, prev.behind) == (freshness.ahead, freshness.behind));
+        if unchanged {
+            continue;
+        }
+        updates.push(SessionGitStatsUpdated {
+            session_id: session.id.clone(),
+            session_name: session.name.clone(),
+            files_changed: stats.files_changed,
+            lines_added: stats.lines_added,
+            lines_removed: stats.lines_removed,
+            has_uncommitted: stats.has_uncommitted,
+            has_conflicts: has_conflicts(&session.worktree_path).unwrap_or(false),
+            top_uncommitted_paths: None,
+            merge_has_conflicts: None,
+            merge_conflicting_paths: None,
+            merge_is_up_to_date: None,
+            worktree_size_bytes: None,
+            base_freshness: Some(freshness),
+        });
+    }
+
+    Ok(updates)
+}
+
 #[cfg(test)]
 mod performance_tests {
     use super::*;
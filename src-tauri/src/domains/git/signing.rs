@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use git2::Repository;
+
+/// Whether commits created by Schaltwerk follow the repository's signing config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommitSigning {
+    /// Sign when git is configured to (`commit.gpgsign`), with `gpg.format` and
+    /// `user.signingkey` picking GPG, SSH or X.509
+    #[default]
+    FromGitConfig,
+    /// Create unsigned commits even when git would sign them
+    Disabled,
+}
+
+impl CommitSigning {
+    pub fn from_preference(sign_commits: bool) -> Self {
+        if sign_commits {
+            Self::FromGitConfig
+        } else {
+            Self::Disabled
+        }
+    }
+
+    /// The signing format git would use for commits in `repo_path`, or `None` when
+    /// commits go unsigned.
+    pub fn active_format(self, repo_path: &Path) -> Option<String> {
+        if self == Self::Disabled {
+            return None;
+        }
+        let config = Repository::open(repo_path)
+            .and_then(|repo| repo.config())
+            .ok()?;
+        if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return None;
+        }
+        Some(
+            config
+                .get_string("gpg.format")
+                .unwrap_or_else(|_| "openpgp".to_string()),
+        )
+    }
+
+    /// Extra arguments for `git commit` and `git rebase` so the CLI matches this setting.
+    pub fn git_args(self) -> &'static [&'static str] {
+        match self {
+            Self::FromGitConfig => &[],
+            Self::Disabled => &["--no-gpg-sign"],
+        }
+    }
+}
+
+/// Whether git's error output points at the signing step rather than the commit itself.
+pub fn is_signing_failure(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    ["failed to sign", "failed to write commit object", "signing"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
+
+/// An actionable error for a commit that could not be signed.
+pub fn signing_error(format: &str, detail: &str) -> anyhow::Error {
+    let hint = match format {
+        "ssh" => {
+            "Check user.signingkey and that the key is available to ssh-keygen or your SSH agent"
+        }
+        "x509" => "Check user.signingkey and gpg.x509.program",
+        _ => "Check user.signingkey and that gpg-agent can unlock the key",
+    };
+    anyhow!(
+        "Commit signing ({format}) failed: {detail}. {hint}, or turn off commit signing for this project in Settings."
+    )
+}
@@ -63,6 +63,7 @@ fn is_internal_tooling_path(path: &str) -> bool {
     path == ".schaltwerk" || path.starts_with(".schaltwerk/")
 }
 
+#[tracing::instrument(level = "trace", skip_all, fields(worktree = %worktree_path.display()))]
 pub fn calculate_git_stats_fast(worktree_path: &Path, parent_branch: &str) -> Result<GitStats> {
     #[cfg(test)]
     increment_git_stats_call_count();
@@ -280,6 +281,7 @@ pub fn calculate_git_stats_fast(worktree_path: &Path, parent_branch: &str) -> Re
                     has_uncommitted: has_uncommitted_filtered,
                     calculated_at: Utc::now(),
                     last_diff_change_ts,
+                    base_freshness: None,
                 });
             }
         }
@@ -395,6 +397,7 @@ pub fn calculate_git_stats_fast(worktree_path: &Path, parent_branch: &str) -> Re
         has_uncommitted: has_uncommitted_filtered,
         calculated_at: Utc::now(),
         last_diff_change_ts,
+        base_freshness: None,
     };
 
     let map = STATS_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
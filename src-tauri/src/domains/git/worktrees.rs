@@ -2,10 +2,16 @@ use super::repository::get_commit_hash;
 use anyhow::{anyhow, Result};
 use git2::ResetType;
 use git2::{
-    build::CheckoutBuilder, BranchType, Repository, WorktreeAddOptions, WorktreePruneOptions,
+    build::CheckoutBuilder, ApplyLocation, ApplyOptions, AttrCheckFlags, BranchType, Diff,
+    DiffOptions, Patch, Repository, WorktreeAddOptions, WorktreeLockStatus, WorktreePruneOptions,
 };
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 /// Discard changes for a single path inside a worktree.
 ///
@@ -100,6 +106,165 @@ pub fn discard_path_in_worktree(worktree_path: &Path, file_path: &Path) -> Resul
     Ok(())
 }
 
+/// One hunk of uncommitted changes in a worktree file, numbered against HEAD.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorktreeHunk {
+    /// Identifies the hunk by position and content, so a stale id never matches
+    pub id: String,
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+}
+
+/// Hunks of uncommitted changes (staged or not) for a tracked file. Untracked
+/// files have no hunks; discard them as a whole file instead.
+pub fn list_worktree_hunks(worktree_path: &Path, file_path: &str) -> Result<Vec<WorktreeHunk>> {
+    let repo = Repository::open(worktree_path)?;
+    let diff = worktree_file_diff(&repo, file_path, false)?;
+    let Some(patch) = Patch::from_diff(&diff, 0)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut hunks = Vec::with_capacity(patch.num_hunks());
+    for (hunk, (removed, added)) in patch_hunks(&patch)? {
+        let (old_start, old_lines, new_start, new_lines) = (
+            hunk.old_start(),
+            hunk.old_lines(),
+            hunk.new_start(),
+            hunk.new_lines(),
+        );
+        hunks.push(WorktreeHunk {
+            id: hunk_id(old_start, old_lines, new_start, new_lines, &removed, &added),
+            header: String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string(),
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+        });
+    }
+    Ok(hunks)
+}
+
+/// Revert only the selected hunks of a file in the working tree by applying the
+/// reverse patch for them. The index is left alone. Returns how many hunks were
+/// discarded; ids that no longer match the file fail the whole call.
+pub fn discard_hunks_in_worktree(
+    worktree_path: &Path,
+    file_path: &str,
+    hunk_ids: &[String],
+) -> Result<usize> {
+    if hunk_ids.is_empty() {
+        return Ok(0);
+    }
+    let repo = Repository::open(worktree_path)?;
+    let reverse = worktree_file_diff(&repo, file_path, true)?;
+    let patch = Patch::from_diff(&reverse, 0)?
+        .ok_or_else(|| anyhow!("No textual changes to discard in {file_path}"))?;
+
+    // Reverse hunks swap old and new, so ids are computed in the forward direction
+    let wanted: HashSet<&str> = hunk_ids.iter().map(String::as_str).collect();
+    let mut selected = HashMap::new();
+    for (hunk, (removed, added)) in patch_hunks(&patch)? {
+        let id = hunk_id(
+            hunk.new_start(),
+            hunk.new_lines(),
+            hunk.old_start(),
+            hunk.old_lines(),
+            &added,
+            &removed,
+        );
+        if wanted.contains(id.as_str()) {
+            selected.insert(
+                (
+                    hunk.old_start(),
+                    hunk.old_lines(),
+                    hunk.new_start(),
+                    hunk.new_lines(),
+                ),
+                id,
+            );
+        }
+    }
+    if selected.len() != wanted.len() {
+        return Err(anyhow!(
+            "Some hunks in {file_path} changed since they were listed; refresh the diff and try again"
+        ));
+    }
+
+    let mut opts = ApplyOptions::new();
+    opts.hunk_callback(|hunk| {
+        hunk.is_some_and(|hunk| {
+            selected.contains_key(&(
+                hunk.old_start(),
+                hunk.old_lines(),
+                hunk.new_start(),
+                hunk.new_lines(),
+            ))
+        })
+    });
+    repo.apply(&reverse, ApplyLocation::WorkDir, Some(&mut opts))?;
+    Ok(wanted.len())
+}
+
+fn worktree_file_diff<'repo>(
+    repo: &'repo Repository,
+    file_path: &str,
+    reverse: bool,
+) -> Result<Diff<'repo>> {
+    if file_path.starts_with(".schaltwerk/") || file_path.split('/').any(|part| part == "..") {
+        return Err(anyhow!("Refusing to diff path {file_path}"));
+    }
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path)
+        .disable_pathspec_match(true)
+        .reverse(reverse);
+    Ok(repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))?)
+}
+
+type HunkLines = (Vec<Vec<u8>>, Vec<Vec<u8>>);
+
+/// Each hunk of a single-file patch with its removed and added lines.
+fn patch_hunks<'a>(patch: &'a Patch<'_>) -> Result<Vec<(git2::DiffHunk<'a>, HunkLines)>> {
+    let mut hunks = Vec::with_capacity(patch.num_hunks());
+    for index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(index)?;
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(index, line_index)?;
+            match line.origin() {
+                '-' => removed.push(line.content().to_vec()),
+                '+' => added.push(line.content().to_vec()),
+                _ => {}
+            }
+        }
+        hunks.push((hunk, (removed, added)));
+    }
+    Ok(hunks)
+}
+
+fn hunk_id(
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    removed: &[Vec<u8>],
+    added: &[Vec<u8>],
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    removed.hash(&mut hasher);
+    added.hash(&mut hasher);
+    format!(
+        "{old_start},{old_lines},{new_start},{new_lines}:{:016x}",
+        hasher.finish()
+    )
+}
+
 pub fn create_worktree_from_base(
     repo_path: &Path,
     branch_name: &str,
@@ -150,9 +315,82 @@ pub fn create_worktree_from_base(
         "Successfully created worktree at: {}",
         worktree_path.display()
     );
+
+    // libgit2 checks out LFS pointers as-is, so fetch the real content afterwards
+    if repository_uses_lfs(worktree_path) {
+        pull_lfs_objects(worktree_path);
+    }
     Ok(())
 }
 
+/// Whether the checkout's root `.gitattributes` routes any paths through Git LFS.
+pub fn repository_uses_lfs(worktree_path: &Path) -> bool {
+    fs::read_to_string(worktree_path.join(".gitattributes"))
+        .map(|attributes| attributes.contains("filter=lfs"))
+        .unwrap_or(false)
+}
+
+/// Whether `file_path` (relative to the worktree) is stored in Git LFS.
+pub fn is_lfs_tracked_path(worktree_path: &Path, file_path: &str) -> bool {
+    let Ok(repo) = Repository::open(worktree_path) else {
+        return false;
+    };
+    matches!(
+        repo.get_attr(Path::new(file_path), "filter", AttrCheckFlags::default()),
+        Ok(Some("lfs"))
+    )
+}
+
+/// Install the LFS hooks for the repository and replace the worktree's pointer
+/// files with their content. Missing `git-lfs` or a failed download only warns,
+/// the session still works with pointer files.
+fn pull_lfs_objects(worktree_path: &Path) {
+    let run = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(worktree_path)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .output()
+    };
+
+    match run(&["lfs", "version"]) {
+        Ok(output) if output.status.success() => {}
+        _ => {
+            log::warn!(
+                "Repository uses Git LFS but git-lfs is not installed; {} keeps pointer files",
+                worktree_path.display()
+            );
+            return;
+        }
+    }
+
+    let steps: [&[&str]; 2] = [&["lfs", "install", "--local"], &["lfs", "pull"]];
+    for args in steps {
+        match run(args) {
+            Ok(output) if output.status.success() => {
+                log::info!(
+                    "git {} succeeded in {}",
+                    args.join(" "),
+                    worktree_path.display()
+                );
+            }
+            Ok(output) => {
+                log::warn!(
+                    "git {} failed in {}: {}",
+                    args.join(" "),
+                    worktree_path.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                return;
+            }
+            Err(e) => {
+                log::warn!("Failed to run git {}: {e}", args.join(" "));
+                return;
+            }
+        }
+    }
+}
+
 pub fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<()> {
     let repo = Repository::open(repo_path)?;
 
@@ -230,6 +468,129 @@ pub fn prune_worktrees(repo_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Move a registered worktree to `new_path` and point git's bookkeeping at it.
+///
+/// When a plain rename fails (e.g. the destination is on another disk) the worktree
+/// is copied and the original is only removed once git metadata has been updated.
+/// Refuses locked worktrees and destinations that already exist.
+pub fn move_worktree(repo_path: &Path, worktree_path: &Path, new_path: &Path) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+
+    let canonical_source = worktree_path
+        .canonicalize()
+        .unwrap_or_else(|_| worktree_path.to_path_buf());
+
+    let mut worktree = None;
+    for wt_name in repo.worktrees()?.iter().flatten() {
+        if let Ok(wt) = repo.find_worktree(wt_name) {
+            let wt_path = wt.path();
+            let canonical_wt_path = wt_path
+                .canonicalize()
+                .unwrap_or_else(|_| wt_path.to_path_buf());
+            if canonical_wt_path == canonical_source {
+                worktree = Some((wt_name.to_string(), wt));
+                break;
+            }
+        }
+    }
+    let (wt_name, wt) = worktree.ok_or_else(|| anyhow!("Worktree not found: {worktree_path:?}"))?;
+
+    if let WorktreeLockStatus::Locked(reason) = wt.is_locked()? {
+        let reason = reason.unwrap_or_default();
+        return Err(anyhow!("Worktree is locked: {reason}"));
+    }
+    if new_path.exists() {
+        return Err(anyhow!(
+            "Destination already exists: {}",
+            new_path.display()
+        ));
+    }
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let canonical_parent = new_path
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok());
+    if canonical_parent.is_some_and(|parent| parent.starts_with(&canonical_source)) {
+        return Err(anyhow!("Cannot move a worktree into itself"));
+    }
+
+    let copied = match fs::rename(worktree_path, new_path) {
+        Ok(()) => false,
+        Err(rename_err) => {
+            log::info!(
+                "Renaming worktree to {} failed ({rename_err}), copying instead",
+                new_path.display()
+            );
+            if let Err(e) = copy_dir_all(worktree_path, new_path) {
+                let _ = fs::remove_dir_all(new_path);
+                return Err(anyhow!(
+                    "Failed to copy worktree to {}: {e}",
+                    new_path.display()
+                ));
+            }
+            true
+        }
+    };
+
+    let admin_dir = repo.commondir().join("worktrees").join(&wt_name);
+    let canonical_dest = new_path
+        .canonicalize()
+        .unwrap_or_else(|_| new_path.to_path_buf());
+    fs::write(
+        admin_dir.join("gitdir"),
+        format!("{}\n", canonical_dest.join(".git").display()),
+    )?;
+    fs::write(
+        canonical_dest.join(".git"),
+        format!("gitdir: {}\n", admin_dir.display()),
+    )?;
+
+    if copied {
+        if let Err(e) = fs::remove_dir_all(worktree_path) {
+            log::warn!(
+                "Worktree copied to {} but the original at {} could not be removed: {e}",
+                new_path.display(),
+                worktree_path.display()
+            );
+        }
+    }
+
+    log::info!(
+        "Moved worktree {} to {}",
+        worktree_path.display(),
+        new_path.display()
+    );
+    Ok(())
+}
+
+fn copy_dir_all(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
+
 #[cfg(test)]
 pub fn is_worktree_registered(repo_path: &Path, worktree_path: &Path) -> Result<bool> {
     let repo = Repository::open(repo_path)?;
@@ -530,6 +891,67 @@ mod discard_path_tests {
     }
 }
 
+#[cfg(test)]
+mod move_worktree_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_repo_with_worktree(dir: &Path) -> (PathBuf, PathBuf) {
+        let repo_path = dir.join("repo");
+        let repo = Repository::init(&repo_path).unwrap();
+        {
+            let mut cfg = repo.config().unwrap();
+            cfg.set_str("user.name", "Test").unwrap();
+            cfg.set_str("user.email", "test@example.com").unwrap();
+        }
+        {
+            let mut index = repo.index().unwrap();
+            let tree_id = index.write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+            let sig = repo.signature().unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                .unwrap();
+        }
+        let head = repo.head().unwrap().shorthand().unwrap().to_string();
+        let worktree_path = repo_path.join(".schaltwerk/worktrees/feature");
+        create_worktree_from_base(&repo_path, "schaltwerk/feature", &worktree_path, &head).unwrap();
+        (repo_path, worktree_path)
+    }
+
+    #[test]
+    fn move_updates_git_metadata_and_keeps_changes() {
+        let tmp = TempDir::new().unwrap();
+        let (repo_path, worktree_path) = init_repo_with_worktree(tmp.path());
+        std::fs::write(worktree_path.join("wip.txt"), "uncommitted").unwrap();
+
+        let new_path = tmp.path().join("external/trees/feature");
+        move_worktree(&repo_path, &worktree_path, &new_path).unwrap();
+
+        assert!(!worktree_path.exists());
+        assert_eq!(
+            std::fs::read_to_string(new_path.join("wip.txt")).unwrap(),
+            "uncommitted"
+        );
+        assert!(is_worktree_registered(&repo_path, &new_path).unwrap());
+        let moved = Repository::open(&new_path).unwrap();
+        assert_eq!(
+            moved.head().unwrap().shorthand(),
+            Some("schaltwerk/feature")
+        );
+    }
+
+    #[test]
+    fn move_refuses_existing_destination() {
+        let tmp = TempDir::new().unwrap();
+        let (repo_path, worktree_path) = init_repo_with_worktree(tmp.path());
+        let new_path = tmp.path().join("taken");
+        std::fs::create_dir_all(&new_path).unwrap();
+
+        assert!(move_worktree(&repo_path, &worktree_path, &new_path).is_err());
+        assert!(worktree_path.exists());
+    }
+}
+
 fn validate_branch_name(name: &str) -> Result<()> {
     if name.is_empty() {
         return Err(anyhow!("Branch name cannot be empty"));
@@ -544,3 +966,148 @@ fn validate_branch_name(name: &str) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod lfs_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const POINTER: &str = "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+
+    fn init_lfs_repo(dir: &Path) {
+        let repo = Repository::init(dir).unwrap();
+        {
+            let mut cfg = repo.config().unwrap();
+            cfg.set_str("user.name", "Test").unwrap();
+            cfg.set_str("user.email", "test@example.com").unwrap();
+        }
+        fs::write(
+            dir.join(".gitattributes"),
+            "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.join("models")).unwrap();
+        fs::write(dir.join("models/weights.bin"), POINTER).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = repo.signature().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn detects_lfs_tracked_paths() {
+        let tmp = TempDir::new().unwrap();
+        init_lfs_repo(tmp.path());
+
+        assert!(repository_uses_lfs(tmp.path()));
+        assert!(is_lfs_tracked_path(tmp.path(), "models/weights.bin"));
+        assert!(!is_lfs_tracked_path(tmp.path(), "src/main.rs"));
+    }
+
+    #[test]
+    fn worktree_creation_succeeds_for_lfs_repositories() {
+        let tmp = TempDir::new().unwrap();
+        let repo_path = tmp.path().join("repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        init_lfs_repo(&repo_path);
+        let head = Repository::open(&repo_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .shorthand()
+            .unwrap()
+            .to_string();
+
+        let worktree_path = tmp.path().join("worktrees/lfs-session");
+        create_worktree_from_base(&repo_path, "lfs-session", &worktree_path, &head).unwrap();
+
+        assert!(worktree_path.join("models/weights.bin").exists());
+        assert!(is_lfs_tracked_path(&worktree_path, "models/weights.bin"));
+    }
+}
+
+#[cfg(test)]
+mod hunk_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_repo_with_file(dir: &Path, contents: &str) -> Repository {
+        let repo = Repository::init(dir).unwrap();
+        {
+            let mut cfg = repo.config().unwrap();
+            cfg.set_str("user.name", "Test").unwrap();
+            cfg.set_str("user.email", "test@example.com").unwrap();
+        }
+        fs::write(dir.join("lib.rs"), contents).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        index.write().unwrap();
+        let tree_id = index.write_tree().unwrap();
+        {
+            let tree = repo.find_tree(tree_id).unwrap();
+            let sig = repo.signature().unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                .unwrap();
+        }
+        repo
+    }
+
+    fn numbered_lines(count: usize) -> Vec<String> {
+        (1..=count).map(|n| format!("line {n}")).collect()
+    }
+
+    #[test]
+    fn discards_only_selected_hunks() {
+        let tmp = TempDir::new().unwrap();
+        let original = numbered_lines(30);
+        let _repo = init_repo_with_file(tmp.path(), &(original.join("\n") + "\n"));
+
+        let mut edited = original.clone();
+        edited[1] = "first change".to_string();
+        edited[25] = "second change".to_string();
+        fs::write(tmp.path().join("lib.rs"), edited.join("\n") + "\n").unwrap();
+
+        let hunks = list_worktree_hunks(tmp.path(), "lib.rs").unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks[0].header.starts_with("@@ -1,"));
+
+        let discarded =
+            discard_hunks_in_worktree(tmp.path(), "lib.rs", &[hunks[1].id.clone()]).unwrap();
+        assert_eq!(discarded, 1);
+
+        let contents = fs::read_to_string(tmp.path().join("lib.rs")).unwrap();
+        assert!(contents.contains("first change"));
+        assert!(!contents.contains("second change"));
+        assert!(contents.contains("line 26"));
+
+        let remaining = list_worktree_hunks(tmp.path(), "lib.rs").unwrap();
+        assert_eq!(remaining, vec![hunks[0].clone()]);
+    }
+
+    #[test]
+    fn rejects_stale_hunk_ids() {
+        let tmp = TempDir::new().unwrap();
+        let original = numbered_lines(10);
+        let _repo = init_repo_with_file(tmp.path(), &(original.join("\n") + "\n"));
+
+        let mut edited = original.clone();
+        edited[4] = "changed".to_string();
+        fs::write(tmp.path().join("lib.rs"), edited.join("\n") + "\n").unwrap();
+        let hunks = list_worktree_hunks(tmp.path(), "lib.rs").unwrap();
+
+        edited[4] = "changed again".to_string();
+        fs::write(tmp.path().join("lib.rs"), edited.join("\n") + "\n").unwrap();
+
+        let err =
+            discard_hunks_in_worktree(tmp.path(), "lib.rs", &[hunks[0].id.clone()]).unwrap_err();
+        assert!(err.to_string().contains("changed since they were listed"));
+        let contents = fs::read_to_string(tmp.path().join("lib.rs")).unwrap();
+        assert!(contents.contains("changed again"));
+    }
+}
@@ -1,6 +1,9 @@
 pub mod lock;
+pub mod queue;
 pub mod service;
 pub mod types;
 
 pub use service::MergeService;
-pub use types::{MergeMode, MergeOutcome, MergePreview, MergeState};
+pub use types::{
+    CherryPickOutcome, MergeDryRun, MergeMode, MergeOutcome, MergePreview, MergeState,
+};
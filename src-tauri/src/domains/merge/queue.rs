@@ -0,0 +1,426 @@
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::domains::git::service::SessionRebaseOutcome;
+use crate::domains::merge::types::MergeMode;
+use crate::domains::sessions::service::SessionManager;
+use crate::domains::terminal::build_login_shell_invocation;
+
+/// Lines of test script output kept in the failure message
+const TEST_OUTPUT_TAIL_LINES: usize = 20;
+/// How long the test script may run when the project sets no timeout
+pub const DEFAULT_TEST_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const TEST_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static MERGE_QUEUES: LazyLock<DashMap<PathBuf, QueueState>> = LazyLock::new(DashMap::new);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeQueueStage {
+    Queued,
+    Rebasing,
+    Testing,
+    Merging,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueueEntry {
+    pub session_name: String,
+    pub mode: MergeMode,
+    pub commit_message: Option<String>,
+    /// Rebase onto the base branch, as earlier entries left it, before merging
+    pub auto_rebase: bool,
+    /// Changes to the project's protected paths were acknowledged when queueing
+    pub confirm_protected_paths: bool,
+    pub stage: MergeQueueStage,
+    pub enqueued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueueFailure {
+    pub session_name: String,
+    pub stage: MergeQueueStage,
+    pub message: String,
+}
+
+/// Queue state for the UI. While `running`, the first entry is the one being merged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueueSnapshot {
+    pub repo_path: String,
+    pub entries: Vec<MergeQueueEntry>,
+    pub running: bool,
+    pub last_failure: Option<MergeQueueFailure>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    entries: VecDeque<MergeQueueEntry>,
+    running: bool,
+    active: Option<String>,
+    /// The active entry was removed; the worker stops it at the next check
+    cancel_requested: bool,
+    last_failure: Option<MergeQueueFailure>,
+}
+
+impl QueueState {
+    fn snapshot(&self, repo_path: &Path) -> MergeQueueSnapshot {
+        MergeQueueSnapshot {
+            repo_path: repo_path.to_string_lossy().to_string(),
+            entries: self.entries.iter().cloned().collect(),
+            running: self.running,
+            last_failure: self.last_failure.clone(),
+        }
+    }
+}
+
+pub fn snapshot(repo_path: &Path) -> MergeQueueSnapshot {
+    match MERGE_QUEUES.get(repo_path) {
+        Some(state) => state.snapshot(repo_path),
+        None => QueueState::default().snapshot(repo_path),
+    }
+}
+
+/// Append a session to the repository's queue. The returned flag is `true` when no
+/// worker is draining the queue yet and the caller has to start one.
+pub fn enqueue(
+    repo_path: &Path,
+    session_name: &str,
+    mode: MergeMode,
+    commit_message: Option<String>,
+    auto_rebase: bool,
+    confirm_protected_paths: bool,
+) -> Result<(MergeQueueSnapshot, bool)> {
+    let mut state = MERGE_QUEUES.entry(repo_path.to_path_buf()).or_default();
+    if state.entries.iter().any(|e| e.session_name == session_name) {
+        return Err(anyhow!(
+            "Session '{session_name}' is already in the merge queue"
+        ));
+    }
+
+    state.entries.push_back(MergeQueueEntry {
+        session_name: session_name.to_string(),
+        mode,
+        commit_message,
+        auto_rebase,
+        confirm_protected_paths,
+        stage: MergeQueueStage::Queued,
+        enqueued_at: Utc::now(),
+    });
+    let start_worker = !state.running;
+    state.running = true;
+    Ok((state.snapshot(repo_path), start_worker))
+}
+
+/// Take a session out of the queue. Removing the entry being processed cancels it: the
+/// worker kills its test script and drops it before merging.
+pub fn remove(repo_path: &Path, session_name: &str) -> Result<MergeQueueSnapshot> {
+    let mut state = MERGE_QUEUES
+        .get_mut(repo_path)
+        .ok_or_else(|| anyhow!("Session '{session_name}' is not in the merge queue"))?;
+    if state.active.as_deref() == Some(session_name) {
+        if state
+            .entries
+            .front()
+            .is_some_and(|e| e.stage == MergeQueueStage::Merging)
+        {
+            return Err(anyhow!("Session '{session_name}' is already being merged"));
+        }
+        state.cancel_requested = true;
+        return Ok(state.snapshot(repo_path));
+    }
+    let index = state
+        .entries
+        .iter()
+        .position(|e| e.session_name == session_name)
+        .ok_or_else(|| anyhow!("Session '{session_name}' is not in the merge queue"))?;
+    state.entries.remove(index);
+    Ok(state.snapshot(repo_path))
+}
+
+/// The entry the worker processes next, or `None` once the queue is drained. Draining
+/// marks the queue idle so the next `enqueue` starts a new worker.
+pub fn begin_next(repo_path: &Path) -> Option<MergeQueueEntry> {
+    let mut state = MERGE_QUEUES.get_mut(repo_path)?;
+    match state.entries.front().cloned() {
+        Some(entry) => {
+            state.active = Some(entry.session_name.clone());
+            state.cancel_requested = false;
+            Some(entry)
+        }
+        None => {
+            state.running = false;
+            state.active = None;
+            None
+        }
+    }
+}
+
+/// Whether the entry being processed was removed from the queue.
+pub fn is_cancelled(repo_path: &Path, session_name: &str) -> bool {
+    MERGE_QUEUES.get(repo_path).is_some_and(|state| {
+        state.cancel_requested && state.active.as_deref() == Some(session_name)
+    })
+}
+
+pub fn set_stage(
+    repo_path: &Path,
+    session_name: &str,
+    stage: MergeQueueStage,
+) -> MergeQueueSnapshot {
+    if let Some(mut state) = MERGE_QUEUES.get_mut(repo_path) {
+        if let Some(entry) = state
+            .entries
+            .iter_mut()
+            .find(|e| e.session_name == session_name)
+        {
+            entry.stage = stage;
+        }
+        return state.snapshot(repo_path);
+    }
+    snapshot(repo_path)
+}
+
+/// Drop the processed entry; a failure is kept for the UI until that session is
+/// merged or another entry fails.
+pub fn finish(
+    repo_path: &Path,
+    session_name: &str,
+    failure: Option<(MergeQueueStage, String)>,
+) -> MergeQueueSnapshot {
+    let Some(mut state) = MERGE_QUEUES.get_mut(repo_path) else {
+        return snapshot(repo_path);
+    };
+    state.entries.retain(|e| e.session_name != session_name);
+    if state.active.as_deref() == Some(session_name) {
+        state.active = None;
+        state.cancel_requested = false;
+    }
+    match failure {
+        Some((stage, message)) => {
+            state.last_failure = Some(MergeQueueFailure {
+                session_name: session_name.to_string(),
+                stage,
+                message,
+            });
+        }
+        None => {
+            if state
+                .last_failure
+                .as_ref()
+                .is_some_and(|f| f.session_name == session_name)
+            {
+                state.last_failure = None;
+            }
+        }
+    }
+    state.snapshot(repo_path)
+}
+
+/// Rebase a queued session onto its base branch so it is tested and merged against
+/// what the entries ahead of it left behind.
+pub fn rebase_onto_base(manager: &SessionManager, session_name: &str) -> Result<()> {
+    match manager.rebase_session_on_base(session_name)? {
+        SessionRebaseOutcome::UpToDate { .. } | SessionRebaseOutcome::Rebased { .. } => Ok(()),
+        SessionRebaseOutcome::Conflicts {
+            conflicting_paths, ..
+        } => Err(anyhow!(
+            "Rebasing '{session_name}' onto its base branch hit conflicts in: {}",
+            conflicting_paths.join(", ")
+        )),
+    }
+}
+
+/// Run the project's test script in a session worktree; a failing run reports the
+/// tail of its output. The script runs in its own process group, which is killed once
+/// `timeout` passes or `cancelled` returns `true`.
+pub fn run_test_script(
+    worktree_path: &Path,
+    script: &str,
+    timeout: Duration,
+    cancelled: impl Fn() -> bool,
+) -> Result<()> {
+    let invocation = build_login_shell_invocation(script);
+    let mut command = Command::new(&invocation.program);
+    command
+        .args(&invocation.args)
+        .current_dir(worktree_path)
+        .env("WORKTREE_PATH", worktree_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command.spawn()?;
+
+    let stdout = child.stdout.take().map(spawn_output_reader);
+    let stderr = child.stderr.take().map(spawn_output_reader);
+    let started = Instant::now();
+    let (status, stopped) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, None);
+        }
+        let stopped = if cancelled() {
+            Some("was cancelled".to_string())
+        } else if started.elapsed() >= timeout {
+            Some(format!("timed out after {}s", timeout.as_secs()))
+        } else {
+            std::thread::sleep(TEST_POLL_INTERVAL);
+            continue;
+        };
+        kill_process_group(&mut child);
+        break (child.wait()?, stopped);
+    };
+
+    let mut combined = String::new();
+    for reader in [stdout, stderr].into_iter().flatten() {
+        combined.push_str(&String::from_utf8_lossy(&reader.join().unwrap_or_default()));
+    }
+    let lines: Vec<&str> = combined.lines().collect();
+    let tail = lines[lines.len().saturating_sub(TEST_OUTPUT_TAIL_LINES)..].join("\n");
+    match stopped {
+        Some(reason) => Err(anyhow!("Test script {reason}:\n{tail}")),
+        None if status.success() => Ok(()),
+        None => Err(anyhow!("Test script failed ({status}):\n{tail}")),
+    }
+}
+
+fn spawn_output_reader(mut pipe: impl Read + Send + 'static) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+fn kill_process_group(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn queue_serializes_entries_and_tracks_the_worker() {
+        let repo = TempDir::new().unwrap();
+        let repo_path = repo.path();
+
+        let (_, start) = enqueue(repo_path, "first", MergeMode::Squash, None, true, false).unwrap();
+        assert!(start);
+        let (state, start) =
+            enqueue(repo_path, "second", MergeMode::Reapply, None, false, false).unwrap();
+        assert!(!start);
+        assert_eq!(state.entries.len(), 2);
+        assert!(enqueue(repo_path, "first", MergeMode::Squash, None, false, false).is_err());
+
+        let entry = begin_next(repo_path).unwrap();
+        assert_eq!(entry.session_name, "first");
+        let state = set_stage(repo_path, "first", MergeQueueStage::Merging);
+        assert_eq!(state.entries[0].stage, MergeQueueStage::Merging);
+        assert!(remove(repo_path, "first").is_err());
+        let state = set_stage(repo_path, "first", MergeQueueStage::Testing);
+        assert_eq!(state.entries[0].stage, MergeQueueStage::Testing);
+
+        let state = finish(
+            repo_path,
+            "first",
+            Some((MergeQueueStage::Testing, "tests failed".to_string())),
+        );
+        assert_eq!(state.entries.len(), 1);
+        assert_eq!(
+            state.last_failure.as_ref().map(|f| f.session_name.as_str()),
+            Some("first")
+        );
+
+        let state = remove(repo_path, "second").unwrap();
+        assert!(state.entries.is_empty());
+        assert!(state.running);
+        assert!(begin_next(repo_path).is_none());
+        assert!(!snapshot(repo_path).running);
+
+        let (_, start) =
+            enqueue(repo_path, "third", MergeMode::Squash, None, false, false).unwrap();
+        assert!(start);
+    }
+
+    #[test]
+    fn removing_the_active_entry_cancels_it() {
+        let repo = TempDir::new().unwrap();
+        let repo_path = repo.path();
+
+        enqueue(repo_path, "active", MergeMode::Squash, None, false, false).unwrap();
+        begin_next(repo_path).unwrap();
+        set_stage(repo_path, "active", MergeQueueStage::Testing);
+        assert!(!is_cancelled(repo_path, "active"));
+
+        let state = remove(repo_path, "active").unwrap();
+        assert_eq!(state.entries.len(), 1);
+        assert!(is_cancelled(repo_path, "active"));
+
+        finish(repo_path, "active", None);
+        assert!(!is_cancelled(repo_path, "active"));
+    }
+
+    #[test]
+    fn test_script_failure_reports_output_tail() {
+        let worktree = TempDir::new().unwrap();
+
+        assert!(
+            run_test_script(worktree.path(), "test -d .", DEFAULT_TEST_TIMEOUT, || false).is_ok()
+        );
+
+        let err = run_test_script(
+            worktree.path(),
+            "echo 'assertion failed'; exit 3",
+            DEFAULT_TEST_TIMEOUT,
+            || false,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("assertion failed"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_is_killed_on_timeout_and_cancel() {
+        let worktree = TempDir::new().unwrap();
+
+        let started = Instant::now();
+        let err = run_test_script(
+            worktree.path(),
+            "echo started; sleep 30 & wait",
+            Duration::from_millis(300),
+            || false,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("timed out"), "{err}");
+        assert!(err.contains("started"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let err = run_test_script(worktree.path(), "sleep 30", DEFAULT_TEST_TIMEOUT, || true)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cancelled"), "{err}");
+    }
+}
@@ -1,24 +1,33 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 use std::ffi::OsString;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
-use git2::{build::CheckoutBuilder, BranchType, MergeOptions, Oid, Repository};
+use git2::{build::CheckoutBuilder, BranchType, Commit, MergeOptions, Oid, Repository, Sort};
 use log::{debug, error, info, warn};
 use tokio::task;
 use tokio::time::timeout;
 
+use crate::domains::git::hooks::{GitHooks, HookFailure};
 use crate::domains::git::operations::{has_uncommitted_changes, uncommitted_sample_paths};
+use crate::domains::git::signing::{is_signing_failure, signing_error, CommitSigning};
 use crate::domains::merge::lock;
-use crate::domains::merge::types::{MergeMode, MergeOutcome, MergePreview, MergeState};
-use crate::domains::sessions::entity::SessionState;
+use crate::domains::merge::types::{
+    CherryPickOutcome, MergeDryRun, MergeMode, MergeOutcome, MergePreview, MergeState,
+    SessionOverlap,
+};
+use crate::domains::sessions::entity::{SessionState, SessionStatus};
 use crate::domains::sessions::service::SessionManager;
 use crate::schaltwerk_core::database::Database;
 
 const MERGE_TIMEOUT: Duration = Duration::from_secs(180);
 const OPERATION_LABEL: &str = "merge_session";
 const CONFLICT_SAMPLE_LIMIT: usize = 5;
+const SPEC_COMMIT_MESSAGE_TEMPLATE: &str = "{spec_title}\n\n{spec_body}";
+const FALLBACK_COMMIT_MESSAGE_TEMPLATE: &str = "Merge session {session} into {parent}";
 
 #[derive(Clone)]
 struct SessionMergeContext {
@@ -30,6 +39,42 @@ struct SessionMergeContext {
     parent_branch: String,
     session_oid: Oid,
     parent_oid: Oid,
+    spec_content: Option<String>,
+    commit_signing: CommitSigning,
+    git_hooks: GitHooks,
+    protected_paths: Vec<String>,
+}
+
+impl SessionMergeContext {
+    /// Expand `{session}`, `{branch}`, `{parent}`, `{spec_title}` (first line of the
+    /// spec, without heading markers) and `{spec_body}` (the rest of the spec).
+    fn render_commit_message(&self, template: &str) -> String {
+        let (spec_title, spec_body) = split_spec(self.spec_content.as_deref());
+        // Spec text is substituted last so session placeholders inside it stay literal
+        template
+            .replace("{session}", &self.session_name)
+            .replace("{branch}", &self.session_branch)
+            .replace("{parent}", &self.parent_branch)
+            .replace("{spec_title}", spec_title)
+            .replace("{spec_body}", spec_body)
+            .trim()
+            .to_string()
+    }
+
+    fn default_commit_message(&self) -> String {
+        let (spec_title, _) = split_spec(self.spec_content.as_deref());
+        if spec_title.is_empty() {
+            self.render_commit_message(FALLBACK_COMMIT_MESSAGE_TEMPLATE)
+        } else {
+            self.render_commit_message(SPEC_COMMIT_MESSAGE_TEMPLATE)
+        }
+    }
+}
+
+fn split_spec(spec: Option<&str>) -> (&str, &str) {
+    let spec = spec.unwrap_or_default().trim();
+    let (title, body) = spec.split_once('\n').unwrap_or((spec, ""));
+    (title.trim_start_matches('#').trim(), body.trim())
 }
 
 pub struct MergeService {
@@ -65,10 +110,23 @@ impl MergeService {
 
     pub fn preview(&self, session_name: &str) -> Result<MergePreview> {
         let context = self.prepare_context(session_name)?;
-        let default_message = format!(
-            "Merge session {} into {}",
-            context.session_name, context.parent_branch
-        );
+        self.preview_context(context)
+    }
+
+    /// `preview` plus the other reviewed sessions that edit the same files. Each pair is
+    /// merged in memory so conflicts between parallel sessions show up before either of
+    /// them lands on its parent branch.
+    pub fn preview_with_overlaps(&self, session_name: &str) -> Result<MergePreview> {
+        let context = self.prepare_context(session_name)?;
+        let session_overlaps = self.session_overlaps(&context)?;
+        Ok(MergePreview {
+            session_overlaps,
+            ..self.preview_context(context)?
+        })
+    }
+
+    fn preview_context(&self, context: SessionMergeContext) -> Result<MergePreview> {
+        let default_message = context.default_commit_message();
 
         // Compose human-readable commands for the UI preview only. The merge implementation
         // uses libgit2 directly; these commands are never executed by the backend.
@@ -86,29 +144,157 @@ impl MergeService {
             ),
         ];
 
+        let fast_forward_commands = vec![
+            format!(
+                "git merge-base --is-ancestor {} {}",
+                context.parent_branch, context.session_branch
+            ),
+            format!(
+                "git update-ref refs/heads/{} refs/heads/{}",
+                context.parent_branch, context.session_branch
+            ),
+        ];
+
         let assessment = self.assess_context(&context)?;
+        let can_fast_forward = can_fast_forward(&context)?;
+        let protected_paths = touched_protected_paths(&context)?;
 
         Ok(MergePreview {
             session_branch: context.session_branch,
             parent_branch: context.parent_branch,
             squash_commands,
             reapply_commands,
+            fast_forward_commands,
             default_commit_message: default_message,
             has_conflicts: assessment.has_conflicts,
             conflicting_paths: assessment.conflicting_paths,
             is_up_to_date: assessment.is_up_to_date,
+            can_fast_forward,
+            session_overlaps: Vec::new(),
+            protected_paths,
+        })
+    }
+
+    /// Merge the session into its parent branch in an in-memory index and report the
+    /// conflicts and diff stats. Nothing is written to the repository or any worktree.
+    pub fn merge_session_dry_run(&self, session_name: &str) -> Result<MergeDryRun> {
+        let context = self.prepare_context(session_name)?;
+        let repo = Repository::open(&context.repo_path).with_context(|| {
+            format!(
+                "Failed to open git repository at {}",
+                context.repo_path.display()
+            )
+        })?;
+        let state = compute_merge_state(
+            &repo,
+            context.session_oid,
+            context.parent_oid,
+            &context.session_branch,
+            &context.parent_branch,
+        )?;
+        let can_fast_forward = can_fast_forward(&context)?;
+
+        let (files_changed, insertions, deletions) = if state.has_conflicts || state.is_up_to_date {
+            (0, 0, 0)
+        } else {
+            let parent_commit = repo.find_commit(context.parent_oid)?;
+            let session_commit = repo.find_commit(context.session_oid)?;
+            let index = repo.merge_commits(&parent_commit, &session_commit, None)?;
+            let diff = repo.diff_tree_to_index(Some(&parent_commit.tree()?), Some(&index), None)?;
+            let stats = diff.stats()?;
+            (stats.files_changed(), stats.insertions(), stats.deletions())
+        };
+
+        Ok(MergeDryRun {
+            session_branch: context.session_branch,
+            parent_branch: context.parent_branch,
+            has_conflicts: state.has_conflicts,
+            conflicting_paths: state.conflicting_paths,
+            is_up_to_date: state.is_up_to_date,
+            can_fast_forward,
+            files_changed,
+            insertions,
+            deletions,
         })
     }
 
+    fn session_overlaps(&self, context: &SessionMergeContext) -> Result<Vec<SessionOverlap>> {
+        let repo = Repository::open(&context.repo_path).with_context(|| {
+            format!(
+                "Failed to open git repository at {}",
+                context.repo_path.display()
+            )
+        })?;
+        let changed = changed_paths(&repo, context.session_oid, context.parent_oid)?;
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut overlaps = Vec::new();
+        for other in self.session_manager().list_sessions()? {
+            if other.name == context.session_name
+                || !other.ready_to_merge
+                || other.session_state == SessionState::Spec
+                || other.status == SessionStatus::Cancelled
+            {
+                continue;
+            }
+            let (Ok(other_oid), Ok(other_parent_oid)) = (
+                branch_target(&repo, &other.branch),
+                branch_target(&repo, other.parent_branch.trim()),
+            ) else {
+                debug!(
+                    "{OPERATION_LABEL}: skipping overlap check with '{}' because its branches are missing",
+                    other.name
+                );
+                continue;
+            };
+
+            let other_changed = changed_paths(&repo, other_oid, other_parent_oid)?;
+            let overlapping_paths: Vec<String> =
+                changed.intersection(&other_changed).cloned().collect();
+            if overlapping_paths.is_empty() {
+                continue;
+            }
+
+            let state = compute_merge_state(
+                &repo,
+                context.session_oid,
+                other_oid,
+                &context.session_branch,
+                &other.branch,
+            )?;
+            overlaps.push(SessionOverlap {
+                session_name: other.name,
+                session_branch: other.branch,
+                overlapping_paths,
+                conflicting_paths: state.conflicting_paths,
+            });
+        }
+
+        Ok(overlaps)
+    }
+
+    /// Merge the session into its parent branch. A session changing any of the project's
+    /// protected paths is refused unless `confirm_protected_paths` is set.
     pub async fn merge(
         &self,
         session_name: &str,
         mode: MergeMode,
         commit_message: Option<String>,
+        confirm_protected_paths: bool,
     ) -> Result<MergeOutcome> {
         let context = self.prepare_context(session_name)?;
         let assessment = self.assess_context(&context)?;
 
+        if mode == MergeMode::FastForward && !can_fast_forward(&context)? {
+            return Err(anyhow!(
+                "Cannot fast-forward '{}' to session '{}' because the parent branch has moved on. Use a squash or reapply merge instead.",
+                context.parent_branch,
+                context.session_name
+            ));
+        }
+
         if assessment.has_conflicts {
             let hint = if assessment.conflicting_paths.is_empty() {
                 String::new()
@@ -135,6 +321,17 @@ impl MergeService {
             ));
         }
 
+        if !confirm_protected_paths {
+            let protected = touched_protected_paths(&context)?;
+            if !protected.is_empty() {
+                return Err(anyhow!(
+                    "Session '{}' changes protected paths: {}. Confirm the merge to include them.",
+                    context.session_name,
+                    protected.join(", ")
+                ));
+            }
+        }
+
         let commit_message = match mode {
             MergeMode::Squash => {
                 let message = commit_message
@@ -147,11 +344,12 @@ impl MergeService {
                         }
                     })
                     .ok_or_else(|| anyhow!("Commit message is required for squash merges"))?;
-                Some(message)
+                Some(context.render_commit_message(&message))
             }
             MergeMode::Reapply => commit_message
                 .map(|m| m.trim().to_string())
                 .filter(|m| !m.is_empty()),
+            MergeMode::FastForward => None,
         };
 
         let lock_guard = lock::try_acquire(&context.session_name).ok_or_else(|| {
@@ -188,6 +386,62 @@ impl MergeService {
         Ok(outcome)
     }
 
+    /// Apply the selected session commits on top of the parent branch, oldest first,
+    /// without merging the rest of the branch. The parent branch only moves when
+    /// every selected commit applies cleanly.
+    pub async fn cherry_pick_session_commits(
+        &self,
+        session_name: &str,
+        commits: Vec<String>,
+    ) -> Result<CherryPickOutcome> {
+        if commits.is_empty() {
+            return Err(anyhow!("Select at least one commit to cherry-pick"));
+        }
+
+        let manager = self.session_manager();
+        let session = manager
+            .get_session(session_name)
+            .with_context(|| format!("Session '{session_name}' not found"))?;
+        if session.session_state == SessionState::Spec {
+            return Err(anyhow!(
+                "Session '{session_name}' is still a spec and has no commits"
+            ));
+        }
+        let parent_branch = session.parent_branch.trim().to_string();
+        if parent_branch.is_empty() {
+            return Err(anyhow!(
+                "Session '{session_name}' has no recorded parent branch"
+            ));
+        }
+
+        let lock_guard = lock::try_acquire(session_name)
+            .ok_or_else(|| anyhow!("Merge already running for session '{session_name}'"))?;
+
+        let repo_path = session.repository_path.clone();
+        let session_branch = session.branch.clone();
+        let commit_signing = manager.commit_signing();
+        let result = task::spawn_blocking(move || {
+            perform_cherry_pick(
+                &repo_path,
+                session_branch,
+                parent_branch,
+                &commits,
+                commit_signing,
+            )
+        })
+        .await
+        .map_err(|e| anyhow!("Cherry-pick task panicked: {e}"));
+
+        drop(lock_guard);
+        let outcome = result??;
+
+        if let Err(err) = manager.update_git_stats(&session.id) {
+            warn!("{OPERATION_LABEL}: failed to refresh git stats for '{session_name}': {err}");
+        }
+
+        Ok(outcome)
+    }
+
     fn after_success(&self, context: &SessionMergeContext) -> Result<()> {
         info!(
             "{OPERATION_LABEL}: refreshing session '{session_name}' state after successful merge",
@@ -245,6 +499,12 @@ impl MergeService {
             ));
         }
 
+        let spec_content = session
+            .spec_content
+            .clone()
+            .or_else(|| session.initial_prompt.clone())
+            .filter(|content| !content.trim().is_empty());
+
         let parent_branch = session.parent_branch.trim();
         if parent_branch.is_empty() {
             return Err(anyhow!(
@@ -286,6 +546,10 @@ impl MergeService {
             parent_branch: parent_branch.to_string(),
             session_oid,
             parent_oid,
+            spec_content,
+            commit_signing: manager.commit_signing(),
+            git_hooks: manager.git_hooks(),
+            protected_paths: manager.protected_paths(),
         })
     }
 
@@ -306,6 +570,7 @@ impl MergeService {
                 perform_squash(context_for_task, message)
             }
             MergeMode::Reapply => perform_reapply(context_for_task),
+            MergeMode::FastForward => perform_fast_forward(context_for_task),
         })
         .await
         .map_err(|e| anyhow!("Merge task panicked: {e}"))
@@ -332,6 +597,9 @@ fn perform_squash(context: SessionMergeContext, commit_message: String) -> Resul
         );
     }
 
+    let repo = Repository::open(&context.repo_path)?;
+    let rebased_oid = resolve_branch_oid(&repo, &context.session_branch)?;
+
     run_git(
         &context.worktree_path,
         vec![
@@ -341,16 +609,23 @@ fn perform_squash(context: SessionMergeContext, commit_message: String) -> Resul
         ],
     )?;
 
-    run_git(
-        &context.worktree_path,
-        vec![
-            OsString::from("commit"),
-            OsString::from("-m"),
-            OsString::from(commit_message),
-        ],
-    )?;
+    let mut commit_args = vec![OsString::from("commit")];
+    commit_args.extend(context.commit_signing.git_args().iter().map(OsString::from));
+    commit_args.extend(context.git_hooks.git_args().iter().map(OsString::from));
+    commit_args.extend([OsString::from("-m"), OsString::from(commit_message)]);
+    if let Err(err) = run_git(&context.worktree_path, commit_args) {
+        // Put the session's commits back so the merge can be retried
+        let _ = run_git(
+            &context.worktree_path,
+            vec![
+                OsString::from("reset"),
+                OsString::from("--soft"),
+                OsString::from(rebased_oid.to_string()),
+            ],
+        );
+        return Err(explain_commit_failure(&context, err));
+    }
 
-    let repo = Repository::open(&context.repo_path)?;
     let head_oid = resolve_branch_oid(&repo, &context.session_branch)?;
     fast_forward_branch(&repo, &context.parent_branch, head_oid)?;
 
@@ -394,6 +669,203 @@ fn perform_reapply(context: SessionMergeContext) -> Result<MergeOutcome> {
     })
 }
 
+fn perform_fast_forward(context: SessionMergeContext) -> Result<MergeOutcome> {
+    info!(
+        "{OPERATION_LABEL}: fast-forwarding '{parent}' to branch '{branch}'",
+        branch = context.session_branch.as_str(),
+        parent = context.parent_branch.as_str()
+    );
+
+    let repo = Repository::open(&context.repo_path)?;
+    let head_oid = resolve_branch_oid(&repo, &context.session_branch)?;
+    fast_forward_branch(&repo, &context.parent_branch, head_oid)?;
+
+    Ok(MergeOutcome {
+        session_branch: context.session_branch,
+        parent_branch: context.parent_branch,
+        new_commit: head_oid.to_string(),
+        mode: MergeMode::FastForward,
+    })
+}
+
+fn perform_cherry_pick(
+    repo_path: &Path,
+    session_branch: String,
+    parent_branch: String,
+    selected: &[String],
+    commit_signing: CommitSigning,
+) -> Result<CherryPickOutcome> {
+    info!(
+        "{OPERATION_LABEL}: cherry-picking {count} commit(s) from '{session_branch}' onto '{parent_branch}'",
+        count = selected.len()
+    );
+
+    let repo = Repository::open(repo_path)?;
+    let session_oid = resolve_branch_oid(&repo, &session_branch)?;
+    let parent_oid = resolve_branch_oid(&repo, &parent_branch)?;
+
+    let mut wanted = HashSet::new();
+    for rev in selected {
+        let commit = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("Commit '{rev}' not found"))?;
+        wanted.insert(commit.id());
+    }
+
+    // Walk the session's own commits oldest first so picks keep their history order
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.push(session_oid)?;
+    revwalk.hide(parent_oid)?;
+    let mut ordered = Vec::new();
+    for oid in revwalk {
+        let oid = oid?;
+        if wanted.remove(&oid) {
+            ordered.push(oid);
+        }
+    }
+    if let Some(stray) = wanted.into_iter().next() {
+        return Err(anyhow!(
+            "Commit {stray} is not a session commit of '{session_branch}' ahead of '{parent_branch}'"
+        ));
+    }
+
+    let signing_format = commit_signing.active_format(repo_path);
+    let mut tip = repo.find_commit(parent_oid)?;
+    let mut new_commits = Vec::new();
+    for oid in ordered {
+        let commit = repo.find_commit(oid)?;
+        let short = short_oid(&commit);
+        if commit.parent_count() > 1 {
+            return Err(anyhow!(
+                "Commit {short} is a merge commit and cannot be cherry-picked"
+            ));
+        }
+
+        let mut index = repo.cherrypick_commit(&commit, &tip, 0, None)?;
+        if index.has_conflicts() {
+            let paths = collect_conflicting_paths(&index)?;
+            return Err(anyhow!(
+                "Cherry-picking {short} onto '{parent_branch}' conflicts in: {}",
+                paths.join(", ")
+            ));
+        }
+        let tree_oid = index.write_tree_to(&repo)?;
+        if tree_oid == tip.tree_id() {
+            debug!(
+                "{OPERATION_LABEL}: skipping {short}, its changes are already on '{parent_branch}'"
+            );
+            continue;
+        }
+
+        let new_oid = create_picked_commit(
+            &repo,
+            repo_path,
+            &commit,
+            tree_oid,
+            &tip,
+            signing_format.as_deref(),
+        )?;
+        tip = repo.find_commit(new_oid)?;
+        new_commits.push(new_oid.to_string());
+    }
+
+    if new_commits.is_empty() {
+        return Err(anyhow!(
+            "The selected commits are already applied to '{parent_branch}'"
+        ));
+    }
+    fast_forward_branch(&repo, &parent_branch, tip.id())?;
+
+    Ok(CherryPickOutcome {
+        session_branch,
+        parent_branch,
+        new_commits,
+    })
+}
+
+/// Record a cherry-picked commit with its original author and message. Signed
+/// commits go through `git commit-tree` so git applies the configured key.
+fn create_picked_commit(
+    repo: &Repository,
+    repo_path: &Path,
+    source: &Commit,
+    tree_oid: Oid,
+    parent: &Commit,
+    signing_format: Option<&str>,
+) -> Result<Oid> {
+    let message = source.message_raw().unwrap_or_default();
+    let author = source.author();
+
+    let Some(format) = signing_format else {
+        let committer = repo.signature().map_err(|e| {
+            anyhow!("Failed to get signature from git config: {e}. Please configure git user.name and user.email")
+        })?;
+        let tree = repo.find_tree(tree_oid)?;
+        return Ok(repo.commit(None, &author, &committer, message, &tree, &[parent])?);
+    };
+
+    let offset = author.when().offset_minutes();
+    let author_date = format!(
+        "@{} {}{:02}{:02}",
+        author.when().seconds(),
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    );
+    let mut child = Command::new("git")
+        .args([
+            "commit-tree",
+            &tree_oid.to_string(),
+            "-p",
+            &parent.id().to_string(),
+            "-S",
+            "-F",
+            "-",
+        ])
+        .current_dir(repo_path)
+        .env("GIT_AUTHOR_NAME", author.name().unwrap_or_default())
+        .env("GIT_AUTHOR_EMAIL", author.email().unwrap_or_default())
+        .env("GIT_AUTHOR_DATE", author_date)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git commit-tree")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if is_signing_failure(&stderr) {
+            return Err(signing_error(format, &stderr));
+        }
+        return Err(anyhow!("git commit-tree failed: {stderr}"));
+    }
+    Ok(Oid::from_str(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    )?)
+}
+
+fn short_oid(commit: &Commit) -> String {
+    commit
+        .as_object()
+        .short_id()
+        .ok()
+        .and_then(|buf| buf.as_str().map(str::to_string))
+        .unwrap_or_else(|| commit.id().to_string())
+}
+
+fn can_fast_forward(context: &SessionMergeContext) -> Result<bool> {
+    if context.session_oid == context.parent_oid {
+        return Ok(true);
+    }
+    let repo = Repository::open(&context.repo_path)?;
+    Ok(repo.graph_descendant_of(context.session_oid, context.parent_oid)?)
+}
+
 fn needs_rebase(context: &SessionMergeContext) -> Result<bool> {
     let repo = Repository::open(&context.repo_path)?;
     let latest_parent_oid = resolve_branch_oid(&repo, &context.parent_branch)?;
@@ -403,13 +875,39 @@ fn needs_rebase(context: &SessionMergeContext) -> Result<bool> {
 }
 
 fn run_rebase(context: &SessionMergeContext) -> Result<()> {
-    run_git(
-        &context.worktree_path,
-        vec![
-            OsString::from("rebase"),
-            OsString::from(&context.parent_branch),
-        ],
-    )
+    let mut args = vec![OsString::from("rebase")];
+    args.extend(context.commit_signing.git_args().iter().map(OsString::from));
+    args.push(OsString::from(&context.parent_branch));
+    run_git(&context.worktree_path, args).map_err(|err| explain_signing_failure(context, err))
+}
+
+/// Turn git's raw output into a signing error when the commit could not be signed.
+fn explain_signing_failure(context: &SessionMergeContext, err: anyhow::Error) -> anyhow::Error {
+    match context.commit_signing.active_format(&context.worktree_path) {
+        Some(format) if is_signing_failure(&err.to_string()) => {
+            signing_error(&format, &err.to_string())
+        }
+        _ => err,
+    }
+}
+
+/// Like `explain_signing_failure`, but also attributes the failure to the commit hooks
+/// when any ran, so their output can be shown to the user.
+fn explain_commit_failure(context: &SessionMergeContext, err: anyhow::Error) -> anyhow::Error {
+    let hooks = context.git_hooks.active_hooks(&context.worktree_path);
+    let signing_failed = context
+        .commit_signing
+        .active_format(&context.worktree_path)
+        .is_some()
+        && is_signing_failure(&err.to_string());
+    if hooks.is_empty() || signing_failed {
+        return explain_signing_failure(context, err);
+    }
+    HookFailure {
+        hooks,
+        output: err.to_string(),
+    }
+    .into()
 }
 
 fn abort_rebase(context: &SessionMergeContext) -> Result<()> {
@@ -498,6 +996,50 @@ fn run_git(current_dir: &Path, args: Vec<OsString>) -> Result<()> {
     Err(anyhow!(combined))
 }
 
+/// Files the branch at `head` changes since it forked from `base`.
+fn changed_paths(repo: &Repository, head: Oid, base: Oid) -> Result<BTreeSet<String>> {
+    let fork_point = repo
+        .merge_base(head, base)
+        .with_context(|| format!("Failed to find merge base of {head} and {base}"))?;
+    let base_tree = repo.find_commit(fork_point)?.tree()?;
+    let head_tree = repo.find_commit(head)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)?;
+
+    Ok(diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Files the session changes that fall under one of the project's protected paths.
+fn touched_protected_paths(context: &SessionMergeContext) -> Result<Vec<String>> {
+    if context.protected_paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let repo = Repository::open(&context.repo_path)?;
+    let changed = changed_paths(&repo, context.session_oid, context.parent_oid)?;
+    Ok(changed
+        .into_iter()
+        .filter(|path| {
+            context.protected_paths.iter().any(|protected| {
+                path == protected
+                    || path
+                        .strip_prefix(protected.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+        .collect())
+}
+
+fn branch_target(repo: &Repository, branch: &str) -> Result<Oid> {
+    find_branch(repo, branch)?
+        .get()
+        .target()
+        .ok_or_else(|| anyhow!("Branch '{branch}' has no target"))
+}
+
 fn commits_ahead(repo: &Repository, session_oid: Oid, parent_oid: Oid) -> Result<bool> {
     if session_oid == parent_oid {
         return Ok(false);
@@ -698,6 +1240,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -758,6 +1301,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -825,6 +1369,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -864,6 +1409,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -893,6 +1439,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -923,6 +1470,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -952,6 +1500,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -964,6 +1513,7 @@ mod tests {
                 &session.name,
                 MergeMode::Squash,
                 Some("Squash merge".into()),
+                false,
             )
             .await
             .unwrap();
@@ -996,6 +1546,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -1031,6 +1582,7 @@ mod tests {
                 &session.name,
                 MergeMode::Squash,
                 Some("Squash merge".into()),
+                false,
             )
             .await
             .unwrap();
@@ -1074,6 +1626,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -1126,6 +1679,10 @@ mod tests {
             parent_branch: session_after.parent_branch.clone(),
             session_oid: resolve_branch_oid(&repo, &session_after.branch).unwrap(),
             parent_oid: resolve_branch_oid(&repo, &session_after.parent_branch).unwrap(),
+            spec_content: None,
+            commit_signing: CommitSigning::default(),
+            git_hooks: GitHooks::default(),
+            protected_paths: Vec::new(),
         };
 
         assert!(
@@ -1139,6 +1696,7 @@ mod tests {
                 &session_after.name,
                 MergeMode::Squash,
                 Some("Squash merge".into()),
+                false,
             )
             .await
             .unwrap();
@@ -1167,6 +1725,7 @@ mod tests {
             version_number: None,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
 
         let session = manager.create_session_with_agent(params).unwrap();
@@ -1193,7 +1752,7 @@ mod tests {
 
         let service = MergeService::new(db.clone(), repo_path.clone());
         let outcome = service
-            .merge(&session.name, MergeMode::Reapply, None)
+            .merge(&session.name, MergeMode::Reapply, None, false)
             .await
             .unwrap();
 
@@ -1206,4 +1765,470 @@ mod tests {
         assert!(session_after.ready_to_merge);
         assert_eq!(session_after.session_state, SessionState::Reviewed);
     }
+
+    #[tokio::test]
+    async fn squash_merge_renders_spec_placeholders() {
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let params = SessionCreationParams {
+            name: "templated",
+            prompt: Some("# Add demo function\n\nExpose `demo` from the library."),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(&session.worktree_path, "src/lib.rs", "pub fn demo() {}\n");
+        manager.mark_session_ready(&session.name, false).unwrap();
+
+        let service = MergeService::new(db.clone(), repo_path.clone());
+        let preview = service.preview(&session.name).unwrap();
+        assert_eq!(
+            preview.default_commit_message,
+            "Add demo function\n\nExpose `demo` from the library."
+        );
+
+        let outcome = service
+            .merge(
+                &session.name,
+                MergeMode::Squash,
+                Some("{spec_title} ({session})".into()),
+                false,
+            )
+            .await
+            .unwrap();
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let parent_oid = resolve_branch_oid(&repo, &outcome.parent_branch).unwrap();
+        let parent_commit = repo.find_commit(parent_oid).unwrap();
+        assert_eq!(
+            parent_commit.summary(),
+            Some("Add demo function (templated)")
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_touching_protected_paths_requires_confirmation() {
+        use crate::schaltwerk_core::db_project_config::{
+            ProjectConfigMethods, ProjectMergePreferences,
+        };
+
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+        db.set_project_merge_preferences(
+            &repo_path,
+            &ProjectMergePreferences {
+                auto_cancel_after_merge: false,
+                sign_commits: true,
+                run_git_hooks: true,
+                protected_paths: vec![".github/workflows".to_string(), "infra".to_string()],
+            },
+        )
+        .unwrap();
+
+        let params = SessionCreationParams {
+            name: "protected",
+            prompt: Some("do work"),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(
+            &session.worktree_path,
+            ".github/workflows/ci.yml",
+            "name: ci\n",
+        );
+        write_session_file(&session.worktree_path, "infrastructure.md", "notes\n");
+        manager.mark_session_ready(&session.name, false).unwrap();
+
+        let service = MergeService::new(db.clone(), repo_path.clone());
+        let preview = service.preview(&session.name).unwrap();
+        assert_eq!(
+            preview.protected_paths,
+            vec![".github/workflows/ci.yml".to_string()]
+        );
+
+        let err = service
+            .merge(&session.name, MergeMode::Squash, Some("CI".into()), false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("protected paths"), "{err}");
+        let repo = Repository::open(&repo_path).unwrap();
+        let main_before = resolve_branch_oid(&repo, "main").unwrap();
+
+        let outcome = service
+            .merge(&session.name, MergeMode::Squash, Some("CI".into()), true)
+            .await
+            .unwrap();
+        assert_ne!(
+            resolve_branch_oid(&repo, &outcome.parent_branch).unwrap(),
+            main_before
+        );
+    }
+
+    #[tokio::test]
+    async fn squash_merge_reports_signing_failures_and_honors_opt_out() {
+        use crate::schaltwerk_core::db_project_config::{
+            ProjectConfigMethods, ProjectMergePreferences,
+        };
+
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let params = SessionCreationParams {
+            name: "signed",
+            prompt: Some("do work"),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(&session.worktree_path, "src/lib.rs", "pub fn demo() {}\n");
+        manager.mark_session_ready(&session.name, false).unwrap();
+        for (key, value) in [("commit.gpgsign", "true"), ("gpg.program", "false")] {
+            run_git(
+                &repo_path,
+                vec![
+                    OsString::from("config"),
+                    OsString::from(key),
+                    OsString::from(value),
+                ],
+            )
+            .unwrap();
+        }
+
+        let service = MergeService::new(db.clone(), repo_path.clone());
+        let err = service
+            .merge(
+                &session.name,
+                MergeMode::Squash,
+                Some("Signed".into()),
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Commit signing (openpgp) failed"),
+            "{err}"
+        );
+        let repo = Repository::open(&repo_path).unwrap();
+        assert!(!has_uncommitted_changes(&session.worktree_path).unwrap());
+        assert_ne!(
+            resolve_branch_oid(&repo, "main").unwrap(),
+            resolve_branch_oid(&repo, &session.branch).unwrap()
+        );
+
+        db.set_project_merge_preferences(
+            &repo_path,
+            &ProjectMergePreferences {
+                auto_cancel_after_merge: false,
+                sign_commits: false,
+                run_git_hooks: true,
+                protected_paths: Vec::new(),
+            },
+        )
+        .unwrap();
+        let outcome = service
+            .merge(
+                &session.name,
+                MergeMode::Squash,
+                Some("Unsigned".into()),
+                false,
+            )
+            .await
+            .unwrap();
+        let parent_oid = resolve_branch_oid(&repo, &outcome.parent_branch).unwrap();
+        assert_eq!(
+            repo.find_commit(parent_oid).unwrap().summary(),
+            Some("Unsigned")
+        );
+        assert!(repo.extract_signature(&parent_oid, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn cherry_pick_applies_only_selected_commits() {
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let params = SessionCreationParams {
+            name: "picky",
+            prompt: Some("do work"),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(&session.worktree_path, "src/skip.rs", "pub fn skip() {}\n");
+        write_session_file(&session.worktree_path, "src/keep.rs", "pub fn keep() {}\n");
+        let repo = Repository::open(&repo_path).unwrap();
+        let keep_oid = resolve_branch_oid(&repo, &session.branch).unwrap();
+        let main_before = resolve_branch_oid(&repo, "main").unwrap();
+
+        let service = MergeService::new(db.clone(), repo_path.clone());
+        let err = service
+            .cherry_pick_session_commits(&session.name, vec![main_before.to_string()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not a session commit"), "{err}");
+
+        let outcome = service
+            .cherry_pick_session_commits(&session.name, vec![keep_oid.to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.new_commits.len(), 1);
+        let main_after = resolve_branch_oid(&repo, "main").unwrap();
+        assert_eq!(main_after.to_string(), outcome.new_commits[0]);
+        let picked = repo.find_commit(main_after).unwrap();
+        assert_eq!(picked.parent_id(0).unwrap(), main_before);
+        assert_eq!(picked.summary(), Some("session work"));
+        let tree = picked.tree().unwrap();
+        assert!(tree.get_path(Path::new("src/keep.rs")).is_ok());
+        assert!(tree.get_path(Path::new("src/skip.rs")).is_err());
+        // The session branch itself is left alone
+        assert_eq!(
+            resolve_branch_oid(&repo, &session.branch).unwrap(),
+            keep_oid
+        );
+    }
+
+    #[tokio::test]
+    async fn fast_forward_merge_keeps_session_commits() {
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let params = SessionCreationParams {
+            name: "ff-session",
+            prompt: Some("do work"),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(&session.worktree_path, "src/lib.rs", "pub fn demo() {}\n");
+        write_session_file(
+            &session.worktree_path,
+            "src/other.rs",
+            "pub fn other() {}\n",
+        );
+        manager.mark_session_ready(&session.name, false).unwrap();
+
+        let service = MergeService::new(db.clone(), repo_path.clone());
+        assert!(service.preview(&session.name).unwrap().can_fast_forward);
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let session_head = resolve_branch_oid(&repo, &session.branch).unwrap();
+
+        let outcome = service
+            .merge(&session.name, MergeMode::FastForward, None, false)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.mode, MergeMode::FastForward);
+        assert_eq!(outcome.new_commit, session_head.to_string());
+        assert_eq!(
+            resolve_branch_oid(&repo, &outcome.parent_branch).unwrap(),
+            session_head
+        );
+        let session_after = manager.get_session(&session.name).unwrap();
+        assert_eq!(session_after.session_state, SessionState::Reviewed);
+    }
+
+    #[tokio::test]
+    async fn fast_forward_merge_rejects_moved_parent() {
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let params = SessionCreationParams {
+            name: "ff-diverged",
+            prompt: Some("do work"),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(&session.worktree_path, "src/lib.rs", "pub fn demo() {}\n");
+        manager.mark_session_ready(&session.name, false).unwrap();
+
+        std::fs::write(repo_path.join("README.md"), "updated").unwrap();
+        run_git(
+            &repo_path,
+            vec![
+                OsString::from("commit"),
+                OsString::from("-am"),
+                OsString::from("main update"),
+            ],
+        )
+        .unwrap();
+
+        let service = MergeService::new(db.clone(), repo_path.clone());
+        assert!(!service.preview(&session.name).unwrap().can_fast_forward);
+
+        let err = service
+            .merge(&session.name, MergeMode::FastForward, None, false)
+            .await
+            .expect_err("diverged parent must not fast-forward");
+        assert!(err.to_string().contains("Cannot fast-forward"));
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let session_head = resolve_branch_oid(&repo, &session.branch).unwrap();
+        assert_ne!(resolve_branch_oid(&repo, "main").unwrap(), session_head);
+    }
+
+    #[test]
+    fn preview_with_overlaps_flags_reviewed_sessions_editing_the_same_files() {
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let mut sessions = Vec::new();
+        for (name, file, contents) in [
+            ("overlap-a", "src/lib.rs", "pub fn a() {}\n"),
+            ("overlap-b", "src/lib.rs", "pub fn b() {}\n"),
+            ("overlap-c", "src/other.rs", "pub fn c() {}\n"),
+        ] {
+            let params = SessionCreationParams {
+                name,
+                prompt: Some("do work"),
+                base_branch: Some("main"),
+                custom_branch: None,
+                was_auto_generated: false,
+                version_group_id: None,
+                version_number: None,
+                agent_type: None,
+                skip_permissions: None,
+                agent_model: None,
+            };
+            let session = manager.create_session_with_agent(params).unwrap();
+            write_session_file(&session.worktree_path, file, contents);
+            manager.mark_session_ready(&session.name, false).unwrap();
+            sessions.push(session);
+        }
+
+        let service = MergeService::new(db, repo_path);
+        assert!(service
+            .preview(&sessions[0].name)
+            .unwrap()
+            .session_overlaps
+            .is_empty());
+
+        let preview = service.preview_with_overlaps(&sessions[0].name).unwrap();
+        assert_eq!(
+            preview.session_overlaps,
+            vec![SessionOverlap {
+                session_name: sessions[1].name.clone(),
+                session_branch: sessions[1].branch.clone(),
+                overlapping_paths: vec!["src/lib.rs".to_string()],
+                conflicting_paths: vec!["src/lib.rs".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn merge_session_dry_run_reports_stats_without_moving_the_parent() {
+        let temp = TempDir::new().unwrap();
+        let (manager, db, repo_path) = create_session_manager(&temp);
+
+        let params = SessionCreationParams {
+            name: "dry-run",
+            prompt: Some("do work"),
+            base_branch: Some("main"),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
+        };
+        let session = manager.create_session_with_agent(params).unwrap();
+        write_session_file(
+            &session.worktree_path,
+            "src/lib.rs",
+            "pub fn demo() {}\npub fn more() {}\n",
+        );
+        manager.mark_session_ready(&session.name, false).unwrap();
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let main_before = resolve_branch_oid(&repo, "main").unwrap();
+
+        let service = MergeService::new(db, repo_path.clone());
+        let dry_run = service.merge_session_dry_run(&session.name).unwrap();
+        assert!(!dry_run.has_conflicts);
+        assert!(!dry_run.is_up_to_date);
+        assert!(dry_run.can_fast_forward);
+        assert_eq!(
+            (dry_run.files_changed, dry_run.insertions, dry_run.deletions),
+            (1, 2, 0)
+        );
+
+        std::fs::write(session.worktree_path.join("README.md"), "session").unwrap();
+        run_git(
+            &session.worktree_path,
+            vec![
+                OsString::from("commit"),
+                OsString::from("-am"),
+                OsString::from("session readme"),
+            ],
+        )
+        .unwrap();
+        std::fs::write(repo_path.join("README.md"), "main").unwrap();
+        run_git(
+            &repo_path,
+            vec![
+                OsString::from("commit"),
+                OsString::from("-am"),
+                OsString::from("main readme"),
+            ],
+        )
+        .unwrap();
+        let main_after_commit = resolve_branch_oid(&repo, "main").unwrap();
+        assert_ne!(main_before, main_after_commit);
+
+        let dry_run = service.merge_session_dry_run(&session.name).unwrap();
+        assert!(dry_run.has_conflicts);
+        assert_eq!(dry_run.conflicting_paths, vec!["README.md".to_string()]);
+        assert_eq!(dry_run.files_changed, 0);
+        assert_eq!(
+            resolve_branch_oid(&repo, "main").unwrap(),
+            main_after_commit
+        );
+    }
 }
@@ -1,10 +1,13 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MergeMode {
     Squash,
     Reapply,
+    /// Move the parent branch to the session head without rewriting any commits
+    FastForward,
 }
 
 impl MergeMode {
@@ -12,6 +15,7 @@ impl MergeMode {
         match self {
             MergeMode::Squash => "squash",
             MergeMode::Reapply => "reapply",
+            MergeMode::FastForward => "fast_forward",
         }
     }
 }
@@ -23,10 +27,30 @@ pub struct MergePreview {
     pub parent_branch: String,
     pub squash_commands: Vec<String>,
     pub reapply_commands: Vec<String>,
+    pub fast_forward_commands: Vec<String>,
     pub default_commit_message: String,
     pub has_conflicts: bool,
     pub conflicting_paths: Vec<String>,
     pub is_up_to_date: bool,
+    /// The parent branch has not moved since the session branched off
+    pub can_fast_forward: bool,
+    /// Other reviewed sessions editing the same files; only filled in by
+    /// `MergeService::preview_with_overlaps`
+    pub session_overlaps: Vec<SessionOverlap>,
+    /// Changed files under the project's protected paths; merging them needs confirmation
+    pub protected_paths: Vec<String>,
+}
+
+/// A reviewed session whose branch touches files the previewed session also changes
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOverlap {
+    pub session_name: String,
+    pub session_branch: String,
+    /// Files both branches change since they forked from their parent branches
+    pub overlapping_paths: Vec<String>,
+    /// Files that conflict when the two session branches are merged with each other
+    pub conflicting_paths: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -85,6 +109,32 @@ pub struct MergeOutcome {
     pub mode: MergeMode,
 }
 
+/// What merging a session would do to its parent branch, computed without touching
+/// either branch or any worktree
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeDryRun {
+    pub session_branch: String,
+    pub parent_branch: String,
+    pub has_conflicts: bool,
+    pub conflicting_paths: Vec<String>,
+    pub is_up_to_date: bool,
+    pub can_fast_forward: bool,
+    /// Changes the merge would bring to the parent branch; zero when it conflicts
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CherryPickOutcome {
+    pub session_branch: String,
+    pub parent_branch: String,
+    /// Commits created on the parent branch, oldest first
+    pub new_commits: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,10 +178,14 @@ mod tests {
             parent_branch: "main".into(),
             squash_commands: vec![],
             reapply_commands: vec![],
+            fast_forward_commands: vec![],
             default_commit_message: String::new(),
             has_conflicts: false,
             conflicting_paths: vec!["conflict.txt".into()],
             is_up_to_date: true,
+            can_fast_forward: true,
+            session_overlaps: vec![],
+            protected_paths: vec![],
         };
         let snapshot = MergeStateSnapshot::from_preview(Some(&preview));
         assert_eq!(snapshot.merge_has_conflicts, Some(false));
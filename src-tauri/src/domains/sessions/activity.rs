@@ -1,11 +1,19 @@
+use crate::domains::agents::progress::{
+    read_claude_progress, retain_progress_cache, AgentProgress,
+};
+use crate::domains::agents::usage::UsageUpdate;
 use crate::domains::merge::service::{compute_merge_state, resolve_branch_oid};
 use crate::domains::merge::types::MergeStateSnapshot;
+use crate::domains::terminal::idle_detection::SessionIdlePayload;
+use crate::domains::terminal::TerminalManager;
 use crate::infrastructure::events::{emit_event, SchaltEvent};
+use crate::shared::terminal_id::terminal_id_for_session_top;
 use crate::{
     domains::git::db_git_stats::GitStatsMethods,
     domains::git::service as git,
     domains::sessions::cache::{cache_worktree_size, get_cached_worktree_size},
     domains::sessions::db_sessions::SessionMethods,
+    domains::sessions::entity::BaseFreshness,
     domains::sessions::storage::compute_worktree_size_bytes,
     schaltwerk_core::database::Database,
 };
@@ -14,14 +22,16 @@ use anyhow::Result;
 use chrono::DateTime;
 use chrono::{TimeZone, Utc};
 use git2::Repository;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 #[cfg(test)]
 use std::path::Path;
-use std::sync::Arc;
-use std::time::Duration as StdDuration;
+use std::sync::{Arc, Mutex, RwLock};
 #[cfg(test)]
 use std::time::UNIX_EPOCH;
+use std::time::{Duration as StdDuration, Instant};
 use tauri::AppHandle;
+use tokio::task::JoinHandle;
 use tokio::time::{interval, Duration};
 #[cfg(test)]
 use walkdir::WalkDir;
@@ -29,6 +39,7 @@ use walkdir::WalkDir;
 pub trait EventEmitter: Send + Sync {
     fn emit_session_activity(&self, payload: SessionActivityUpdated) -> Result<()>;
     fn emit_session_git_stats(&self, payload: SessionGitStatsUpdated) -> Result<()>;
+    fn emit_agent_progress(&self, payload: SessionAgentProgressUpdated) -> Result<()>;
 }
 
 impl EventEmitter for AppHandle {
@@ -41,27 +52,115 @@ impl EventEmitter for AppHandle {
         emit_event(self, SchaltEvent::SessionGitStats, &payload)
             .map_err(|e| anyhow::anyhow!("Failed to emit git stats: {e}"))
     }
+
+    fn emit_agent_progress(&self, payload: SessionAgentProgressUpdated) -> Result<()> {
+        emit_event(self, SchaltEvent::AgentProgressUpdated, &payload)
+            .map_err(|e| anyhow::anyhow!("Failed to emit agent progress: {e}"))
+    }
 }
 
 pub struct ActivityTracker<E: EventEmitter> {
     db: Arc<Database>,
     emitter: E,
+    // Last emitted progress per session, so unchanged checklists are not re-broadcast
+    last_progress: Mutex<HashMap<String, AgentProgress>>,
 }
 
 impl<E: EventEmitter> ActivityTracker<E> {
     pub fn new(db: Arc<Database>, emitter: E) -> Self {
-        Self { db, emitter }
+        Self {
+            db,
+            emitter,
+            last_progress: Mutex::new(HashMap::new()),
+        }
     }
 
-    pub async fn start_polling(self) {
+    pub async fn start_polling(self)
+    where
+        E: 'static,
+    {
+        let tracker = Arc::new(self);
+        // Progress is polled more often than git stats: checklists change within a single turn
+        let mut progress_interval = interval(Duration::from_secs(15));
         let mut interval = interval(Duration::from_secs(60));
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = tracker.update_all_activities().await {
+                        log::error!("Failed to update activities: {e}");
+                    }
+                }
+                _ = progress_interval.tick() => {
+                    // Reads todo files and scans transcripts, so it stays off the runtime threads
+                    let progress_tracker = Arc::clone(&tracker);
+                    match tokio::task::spawn_blocking(move || {
+                        progress_tracker.update_all_agent_progress()
+                    })
+                    .await
+                    {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => log::error!("Failed to update agent progress: {e}"),
+                        Err(e) => log::error!("Agent progress update panicked: {e}"),
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_all_agent_progress(&self) -> Result<()> {
+        let active_sessions = self.db.list_all_active_sessions()?;
 
-            if let Err(e) = self.update_all_activities().await {
-                log::error!("Failed to update activities: {e}");
+        // Forget sessions that were removed or cancelled since the last pass
+        let active_ids: HashSet<&str> = active_sessions.iter().map(|s| s.id.as_str()).collect();
+        if let Ok(mut last) = self.last_progress.lock() {
+            last.retain(|id, _| active_ids.contains(id.as_str()));
+        }
+        let active_worktrees: HashSet<&std::path::Path> = active_sessions
+            .iter()
+            .map(|s| s.worktree_path.as_path())
+            .collect();
+        retain_progress_cache(&active_worktrees);
+
+        for session in active_sessions.iter() {
+            self.refresh_agent_progress_for_session(session);
+        }
+
+        Ok(())
+    }
+
+    fn refresh_agent_progress_for_session(
+        &self,
+        session: &crate::domains::sessions::entity::Session,
+    ) {
+        // Only Claude exposes a structured TODO list today
+        let agent = session.original_agent_type.as_deref().unwrap_or("claude");
+        if agent != "claude" || !session.worktree_path.exists() {
+            return;
+        }
+
+        let Some(progress) = read_claude_progress(&session.worktree_path) else {
+            return;
+        };
+
+        let changed = match self.last_progress.lock() {
+            Ok(mut last) => {
+                let changed = last.get(&session.id) != Some(&progress);
+                if changed {
+                    last.insert(session.id.clone(), progress.clone());
+                }
+                changed
             }
+            Err(_) => true,
+        };
+
+        if changed {
+            let payload = SessionAgentProgressUpdated {
+                session_id: session.id.clone(),
+                session_name: session.name.clone(),
+                progress,
+            };
+            let _ = self.emitter.emit_agent_progress(payload);
         }
     }
 
@@ -156,6 +255,7 @@ impl<E: EventEmitter> ActivityTracker<E> {
                                 merge_conflicting_paths: merge_snapshot.merge_conflicting_paths,
                                 merge_is_up_to_date: merge_snapshot.merge_is_up_to_date,
                                 worktree_size_bytes,
+                                base_freshness: None,
                             };
                             let _ = self.emitter.emit_session_git_stats(payload);
                         }
@@ -172,13 +272,21 @@ impl<E: EventEmitter> ActivityTracker<E> {
                             // Use strict set to ensure UI reflects the new diff-aware time (even if earlier/later)
                             self.db.set_session_activity(&session.id, dt)?;
                             let session_info = self.db.get_session_by_id(&session.id)?;
+                            let progress = self
+                                .last_progress
+                                .lock()
+                                .ok()
+                                .and_then(|last| last.get(&session.id).cloned());
                             let payload = SessionActivityUpdated {
                                 session_id: session.id.clone(),
                                 session_name: session.name.clone(),
                                 last_activity_ts: dt.timestamp(),
-                                current_task: session_info.initial_prompt.clone(),
-                                todo_percentage: None, // Not available in Session
-                                is_blocked: None,      // Not available in Session
+                                current_task: progress
+                                    .as_ref()
+                                    .and_then(|p| p.current_task().map(str::to_string))
+                                    .or_else(|| session_info.initial_prompt.clone()),
+                                todo_percentage: progress.as_ref().and_then(|p| p.percentage()),
+                                is_blocked: None, // Not available in Session
                             };
                             let _ = self.emitter.emit_session_activity(payload);
                             emitted_activity = true;
@@ -267,6 +375,15 @@ pub struct SessionGitStatsUpdated {
     pub merge_is_up_to_date: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub worktree_size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_freshness: Option<BaseFreshness>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SessionAgentProgressUpdated {
+    pub session_id: String,
+    pub session_name: String,
+    pub progress: AgentProgress,
 }
 
 pub fn start_activity_tracking_with_app(db: Arc<Database>, app: AppHandle) {
@@ -276,6 +393,217 @@ pub fn start_activity_tracking_with_app(db: Arc<Database>, app: AppHandle) {
     });
 }
 
+pub const DEFAULT_AUTO_NUDGE_IDLE_MINUTES: u64 = 15;
+pub const DEFAULT_AUTO_NUDGE_MESSAGE: &str =
+    "Are you still making progress? If you are stuck, summarize what is blocking you and continue with the next step.";
+const AUTO_NUDGE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Message pasted into an agent terminal that has been idle for `idle_minutes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoNudgeConfig {
+    pub enabled: bool,
+    pub idle_minutes: u64,
+    pub message: String,
+}
+
+impl Default for AutoNudgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: DEFAULT_AUTO_NUDGE_IDLE_MINUTES,
+            message: DEFAULT_AUTO_NUDGE_MESSAGE.to_string(),
+        }
+    }
+}
+
+static AUTO_NUDGE_CONFIG: RwLock<Option<AutoNudgeConfig>> = RwLock::new(None);
+
+pub fn put_auto_nudge_config(config: AutoNudgeConfig) {
+    if let Ok(mut guard) = AUTO_NUDGE_CONFIG.write() {
+        *guard = Some(config);
+    }
+}
+
+fn auto_nudge_config() -> AutoNudgeConfig {
+    AUTO_NUDGE_CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_default()
+}
+
+/// Tracks how long each session agent terminal has been idle and picks the ones to nudge.
+/// A terminal is nudged once per idle stretch; the pasted nudge itself ends the stretch, so
+/// an agent that stays stalled is nudged again only after another full idle period.
+#[derive(Default)]
+pub struct NudgeScheduler {
+    idle_since: HashMap<String, Instant>,
+    nudged: HashSet<String>,
+}
+
+impl NudgeScheduler {
+    pub fn due(
+        &mut self,
+        states: &[SessionIdlePayload],
+        opted_out: &HashSet<String>,
+        idle_for: StdDuration,
+        now: Instant,
+    ) -> Vec<SessionIdlePayload> {
+        self.idle_since.retain(|terminal_id, _| {
+            states
+                .iter()
+                .any(|state| state.idle && &state.terminal_id == terminal_id)
+        });
+        self.nudged
+            .retain(|terminal_id| self.idle_since.contains_key(terminal_id));
+
+        let mut due = Vec::new();
+        for state in states.iter().filter(|state| state.idle) {
+            let since = *self
+                .idle_since
+                .entry(state.terminal_id.clone())
+                .or_insert(now);
+            if opted_out.contains(&state.session_id) || self.nudged.contains(&state.terminal_id) {
+                continue;
+            }
+            if now.duration_since(since) >= idle_for {
+                self.nudged.insert(state.terminal_id.clone());
+                due.push(state.clone());
+            }
+        }
+        due
+    }
+}
+
+/// Periodically pastes the configured nudge into stalled session agents, skipping
+/// sessions that opted out. Does nothing while the nudge is disabled in the settings.
+pub fn start_auto_nudge(db: Arc<Database>, terminals: Arc<TerminalManager>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut scheduler = NudgeScheduler::default();
+        let mut ticker = interval(AUTO_NUDGE_POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let config = auto_nudge_config();
+            if !config.enabled || config.message.trim().is_empty() {
+                scheduler = NudgeScheduler::default();
+                continue;
+            }
+
+            let opted_out = match opted_out_session_names(&db) {
+                Ok(names) => names,
+                Err(e) => {
+                    log::warn!("Skipping auto-nudge round: {e}");
+                    continue;
+                }
+            };
+            let states = terminals.get_session_idle_states().await;
+            let idle_for = StdDuration::from_secs(config.idle_minutes.max(1) * 60);
+            for state in scheduler.due(&states, &opted_out, idle_for, Instant::now()) {
+                log::info!(
+                    "Nudging stalled session '{}' in terminal {}",
+                    state.session_id,
+                    state.terminal_id
+                );
+                if let Err(e) = terminals
+                    .paste_and_submit_terminal(
+                        state.terminal_id.clone(),
+                        config.message.clone().into_bytes(),
+                        false,
+                    )
+                    .await
+                {
+                    log::warn!("Failed to nudge terminal {}: {e}", state.terminal_id);
+                }
+            }
+        }
+    })
+}
+
+const RUNTIME_SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Samples agent terminals and books the time they were busy (not idle) on the
+/// session and the current local day, for the session time report.
+pub fn start_runtime_tracking(
+    db: Arc<Database>,
+    terminals: Arc<TerminalManager>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(RUNTIME_SAMPLE_INTERVAL);
+        // The first tick fires immediately and covers no elapsed time
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let busy: HashSet<String> = terminals
+                .get_session_idle_states()
+                .await
+                .into_iter()
+                .filter(|state| !state.idle)
+                .map(|state| state.session_id)
+                .collect();
+            if busy.is_empty() {
+                continue;
+            }
+            if let Err(e) = record_busy_sessions(&db, &busy, RUNTIME_SAMPLE_INTERVAL.as_secs()) {
+                log::warn!("Failed to record session runtime: {e}");
+            }
+        }
+    })
+}
+
+fn record_busy_sessions(db: &Database, busy_names: &HashSet<String>, seconds: u64) -> Result<()> {
+    let day = chrono::Local::now().format("%Y-%m-%d").to_string();
+    for session in db.list_all_active_sessions()? {
+        if busy_names.contains(&session.name) {
+            db.add_session_active_seconds(&session.id, &day, seconds)?;
+        }
+    }
+    Ok(())
+}
+
+const USAGE_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Stores the token usage and cost session agents report, for the session cost report.
+pub fn start_usage_tracking(db: Arc<Database>, terminals: Arc<TerminalManager>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(USAGE_SAMPLE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let updates = terminals.take_agent_usage_updates().await;
+            if updates.is_empty() {
+                continue;
+            }
+            if let Err(e) = record_usage_updates(&db, &updates) {
+                log::warn!("Failed to record agent usage: {e}");
+            }
+        }
+    })
+}
+
+fn record_usage_updates(db: &Database, updates: &[UsageUpdate]) -> Result<()> {
+    let sessions: HashMap<String, String> = db
+        .list_all_active_sessions()?
+        .into_iter()
+        .map(|session| (terminal_id_for_session_top(&session.name), session.id))
+        .collect();
+    for update in updates {
+        if let Some(session_id) = sessions.get(&update.terminal_id) {
+            db.set_agent_run_usage(session_id, &update.run_id, &update.usage)?;
+        }
+    }
+    Ok(())
+}
+
+fn opted_out_session_names(db: &Database) -> Result<HashSet<String>> {
+    let opted_out_ids = db.list_auto_nudge_opt_outs()?;
+    Ok(db
+        .list_all_active_sessions()?
+        .into_iter()
+        .filter(|session| opted_out_ids.contains(&session.id))
+        .map(|session| session.name)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +650,51 @@ mod tests {
             self.git_stats_events.lock().unwrap().push(payload);
             Ok(())
         }
+
+        fn emit_agent_progress(&self, _payload: SessionAgentProgressUpdated) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn nudges_each_idle_stretch_once_and_respects_opt_outs() {
+        let idle = |session: &str, idle: bool| SessionIdlePayload {
+            session_id: session.to_string(),
+            terminal_id: format!("session-{session}-top"),
+            idle,
+            waiting_for_input: false,
+        };
+        let threshold = StdDuration::from_secs(600);
+        let opted_out: HashSet<String> = ["quiet".to_string()].into();
+        let start = Instant::now();
+        let mut scheduler = NudgeScheduler::default();
+
+        let states = vec![
+            idle("stalled", true),
+            idle("quiet", true),
+            idle("busy", false),
+        ];
+        assert!(scheduler
+            .due(&states, &opted_out, threshold, start)
+            .is_empty());
+
+        let due = scheduler.due(&states, &opted_out, threshold, start + threshold);
+        assert_eq!(due, vec![idle("stalled", true)]);
+        let later = start + threshold * 3;
+        assert!(scheduler
+            .due(&states, &opted_out, threshold, later)
+            .is_empty());
+
+        // New output starts a fresh idle stretch
+        scheduler.due(&[idle("stalled", false)], &opted_out, threshold, later);
+        let stalled = vec![idle("stalled", true)];
+        assert!(scheduler
+            .due(&stalled, &opted_out, threshold, later)
+            .is_empty());
+        assert_eq!(
+            scheduler.due(&stalled, &opted_out, threshold, later + threshold),
+            stalled
+        );
     }
 
     #[test]
@@ -368,6 +741,7 @@ mod tests {
             merge_conflicting_paths: None,
             merge_is_up_to_date: None,
             worktree_size_bytes: None,
+            base_freshness: None,
         };
 
         mock_emitter
@@ -402,6 +776,7 @@ mod tests {
             merge_conflicting_paths: None,
             merge_is_up_to_date: Some(true),
             worktree_size_bytes: Some(1234),
+            base_freshness: None,
         };
 
         assert_eq!(payload.session_id, "session-456");
@@ -520,6 +895,8 @@ mod tests {
             session_state: SessionState::Running,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         };
         db.create_session(&session).unwrap();
 
@@ -586,6 +963,8 @@ mod tests {
             session_state: SessionState::Running,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         };
         db.create_session(&session).unwrap();
 
@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+const PENDING: u8 = 0;
+const CANCELLED: u8 = 1;
+const COMPLETED: u8 = 2;
+
+/// Shared flag that lets another task stop a long-running session operation between steps.
+///
+/// Once the operation calls [`CancellationToken::complete`] it can no longer be cancelled,
+/// so a cancel either takes effect or reports that it came too late.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<AtomicU8>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Returns false when the operation already completed.
+    pub fn cancel(&self) -> bool {
+        match self
+            .state
+            .compare_exchange(PENDING, CANCELLED, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => true,
+            Err(state) => state == CANCELLED,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == CANCELLED
+    }
+
+    /// Mark the operation as done. Returns false when it was cancelled first.
+    pub fn complete(&self) -> bool {
+        match self
+            .state
+            .compare_exchange(PENDING, COMPLETED, Ordering::SeqCst, Ordering::SeqCst)
+        {
+            Ok(_) => true,
+            Err(state) => state == COMPLETED,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_after_completion_is_refused() {
+        let token = CancellationToken::new();
+        assert!(token.complete());
+        assert!(!token.cancel());
+        assert!(!token.is_cancelled());
+
+        let token = CancellationToken::new();
+        assert!(token.cancel());
+        assert!(!token.complete());
+        assert!(token.is_cancelled());
+    }
+}
@@ -1,9 +1,15 @@
-use crate::domains::sessions::entity::{Session, SessionState, SessionStatus};
+use crate::domains::agents::usage::AgentUsage;
+use crate::domains::sessions::entity::{
+    AgentCrash, DiffSide, PendingFollowUp, ReviewComment, Session, SessionDependency, SessionNotes,
+    SessionRemoteStatus, SessionState, SessionStatus, SessionTimeEntry, SessionType, SetupStatus,
+    SpecRevision, SpecRevisionSource,
+};
+use crate::domains::settings::SettingsOverrides;
 use crate::schaltwerk_core::database::Database;
 use anyhow::Result;
 use chrono::{TimeZone, Utc};
-use rusqlite::{params, Result as SqlResult, ToSql};
-use std::collections::HashMap;
+use rusqlite::{params, OptionalExtension, Result as SqlResult, ToSql, TransactionBehavior};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Instant;
@@ -30,6 +36,14 @@ pub trait SessionMethods {
     fn update_session_display_name(&self, id: &str, display_name: &str) -> Result<()>;
     fn update_session_branch(&self, id: &str, new_branch: &str) -> Result<()>;
     fn update_session_parent_branch(&self, id: &str, new_parent_branch: &str) -> Result<()>;
+    fn update_session_worktree_path(&self, id: &str, worktree_path: &Path) -> Result<()>;
+    fn rename_session(
+        &self,
+        id: &str,
+        new_name: &str,
+        new_branch: &str,
+        worktree_path: &Path,
+    ) -> Result<()>;
     fn update_session_ready_to_merge(&self, id: &str, ready: bool) -> Result<()>;
     fn update_session_state(&self, id: &str, state: SessionState) -> Result<()>;
     fn update_spec_content(&self, id: &str, content: &str) -> Result<()>;
@@ -44,7 +58,69 @@ pub trait SessionMethods {
     ) -> Result<()>;
     fn clear_session_run_state(&self, session_id: &str) -> Result<()>;
     fn set_session_resume_allowed(&self, id: &str, allowed: bool) -> Result<()>;
-    fn set_session_amp_thread_id(&self, id: &str, thread_id: &str) -> Result<()>;
+    fn set_session_amp_thread_id(&self, id: &str, thread_id: Option<&str>) -> Result<()>;
+    fn get_session_codex_profile(&self, id: &str) -> Result<Option<String>>;
+    fn set_session_codex_profile(&self, id: &str, profile: Option<&str>) -> Result<()>;
+    fn set_session_agent_model(&self, id: &str, model: Option<&str>) -> Result<()>;
+    fn get_session_handoff_prompt(&self, id: &str) -> Result<Option<String>>;
+    fn set_session_handoff_prompt(&self, id: &str, prompt: Option<&str>) -> Result<()>;
+    fn set_session_type(&self, id: &str, session_type: SessionType) -> Result<()>;
+    /// Name and worktree of the sessions created with `SessionType::Container`
+    fn list_container_sessions(&self, repo_path: &Path) -> Result<Vec<(String, PathBuf)>>;
+    fn get_session_setup_status(&self, id: &str) -> Result<Option<SetupStatus>>;
+    fn set_session_setup_status(&self, id: &str, status: Option<SetupStatus>) -> Result<()>;
+    fn get_session_environment_variables(&self, id: &str) -> Result<HashMap<String, String>>;
+    fn set_session_labels(&self, id: &str, labels: &[String]) -> Result<()>;
+    fn set_session_auto_nudge_disabled(&self, id: &str, disabled: bool) -> Result<()>;
+    fn list_auto_nudge_opt_outs(&self) -> Result<HashSet<String>>;
+    fn list_session_dependencies(&self, repo_path: &Path) -> Result<Vec<SessionDependency>>;
+    fn set_session_dependencies(&self, id: &str, depends_on_ids: &[String]) -> Result<()>;
+    fn mark_dependency_satisfied(&self, depends_on_id: &str) -> Result<Vec<String>>;
+    fn add_session_active_seconds(&self, id: &str, day: &str, seconds: u64) -> Result<()>;
+    fn list_session_time_entries(&self, repo_path: &Path) -> Result<Vec<SessionTimeEntry>>;
+    fn get_session_notes(&self, id: &str) -> Result<SessionNotes>;
+    fn set_session_notes(&self, id: &str, notes: &SessionNotes) -> Result<()>;
+    fn add_review_comment(&self, id: &str, comment: &ReviewComment) -> Result<()>;
+    fn list_review_comments(&self, id: &str) -> Result<Vec<ReviewComment>>;
+    fn resolve_review_comment(&self, id: &str, comment_id: &str, resolved_at: i64) -> Result<bool>;
+    fn reopen_for_review_changes(
+        &self,
+        id: &str,
+        comment_ids: &[String],
+        resolved_at: i64,
+    ) -> Result<bool>;
+    fn set_session_remote_status(&self, id: &str, status: &SessionRemoteStatus) -> Result<()>;
+    fn add_spec_revision(&self, id: &str, revision: &SpecRevision) -> Result<()>;
+    fn list_spec_revisions(&self, id: &str) -> Result<Vec<SpecRevision>>;
+    fn enqueue_follow_up(&self, id: &str, follow_up: &PendingFollowUp) -> Result<()>;
+    fn list_pending_follow_ups(&self, repo_path: &Path) -> Result<Vec<PendingFollowUp>>;
+    fn record_follow_up_attempt(&self, follow_up_id: &str, attempted_at: i64) -> Result<()>;
+    fn remove_follow_up(&self, follow_up_id: &str) -> Result<()>;
+    fn add_agent_crash(&self, id: &str, crash: &AgentCrash) -> Result<()>;
+    fn list_agent_crashes(&self, id: &str) -> Result<Vec<AgentCrash>>;
+    fn set_agent_run_usage(&self, id: &str, run_id: &str, usage: &AgentUsage) -> Result<()>;
+    fn list_session_usage(&self, repo_path: &Path) -> Result<HashMap<String, AgentUsage>>;
+    fn list_session_remote_statuses(
+        &self,
+        repo_path: &Path,
+    ) -> Result<HashMap<String, SessionRemoteStatus>>;
+    fn set_session_environment_variables(
+        &self,
+        id: &str,
+        env_vars: &HashMap<String, String>,
+    ) -> Result<()>;
+    fn get_session_environment_variables_by_worktree(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<HashMap<String, String>>;
+    fn get_session_settings_overrides(&self, id: &str) -> Result<SettingsOverrides>;
+    fn set_session_settings_overrides(&self, id: &str, overrides: &SettingsOverrides)
+        -> Result<()>;
+    fn get_session_settings_overrides_by_worktree(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<SettingsOverrides>;
+    fn delete_session(&self, id: &str) -> Result<()>;
     fn rename_draft_session(&self, repo_path: &Path, old_name: &str, new_name: &str) -> Result<()>;
     fn set_session_version_info(
         &self,
@@ -82,6 +158,17 @@ struct SessionSummaryRow {
     amp_thread_id: Option<String>,
 }
 
+fn labels_to_json(labels: &[String]) -> Result<Option<String>> {
+    Ok((!labels.is_empty())
+        .then(|| serde_json::to_string(labels))
+        .transpose()?)
+}
+
+fn labels_from_json(json: Option<String>) -> Vec<String> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
 impl Database {
     fn hydrate_session_summaries(
         &self,
@@ -103,12 +190,16 @@ impl Database {
 
         let initial_prompts = Self::fetch_text_column_with_conn(conn, &all_ids, "initial_prompt")?;
         let spec_contents = Self::fetch_text_column_with_conn(conn, &spec_ids, "spec_content")?;
+        let mut labels = Self::fetch_text_column_with_conn(conn, &all_ids, "labels")?;
+        let mut agent_models = Self::fetch_text_column_with_conn(conn, &all_ids, "agent_model")?;
 
         Ok(summaries
             .into_iter()
             .map(|summary| {
                 let initial_prompt = initial_prompts.get(&summary.id).cloned().unwrap_or(None);
                 let spec_content = spec_contents.get(&summary.id).cloned().unwrap_or(None);
+                let labels = labels_from_json(labels.remove(&summary.id).flatten());
+                let agent_model = agent_models.remove(&summary.id).flatten();
 
                 Session {
                     id: summary.id,
@@ -135,6 +226,8 @@ impl Database {
                     session_state: summary.session_state,
                     resume_allowed: summary.resume_allowed,
                     amp_thread_id: summary.amp_thread_id,
+                    labels,
+                    agent_model,
                 }
             })
             .collect())
@@ -184,8 +277,8 @@ impl SessionMethods for Database {
                 branch, parent_branch, worktree_path,
                 status, created_at, updated_at, last_activity, initial_prompt, ready_to_merge,
                 original_agent_type, original_skip_permissions, pending_name_generation, was_auto_generated,
-                spec_content, session_state, resume_allowed, amp_thread_id
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)",
+                spec_content, session_state, resume_allowed, amp_thread_id, labels, agent_model
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26)",
             params![
                 session.id,
                 session.name,
@@ -211,6 +304,8 @@ impl SessionMethods for Database {
                 session.session_state.as_str(),
                 session.resume_allowed,
                 session.amp_thread_id,
+                labels_to_json(&session.labels)?,
+                session.agent_model,
             ],
         )?;
 
@@ -225,7 +320,7 @@ impl SessionMethods for Database {
                     branch, parent_branch, worktree_path,
                     status, created_at, updated_at, last_activity, initial_prompt, ready_to_merge,
                     original_agent_type, original_skip_permissions, pending_name_generation, was_auto_generated,
-                    spec_content, session_state, resume_allowed, amp_thread_id
+                    spec_content, session_state, resume_allowed, amp_thread_id, labels, agent_model
              FROM sessions
              WHERE repository_path = ?1 AND name = ?2"
         )?;
@@ -265,6 +360,8 @@ impl SessionMethods for Database {
                     .unwrap_or(SessionState::Running),
                 resume_allowed: row.get(22).unwrap_or(true),
                 amp_thread_id: row.get(23).ok(),
+                labels: labels_from_json(row.get(24).ok().flatten()),
+                agent_model: row.get(25).ok().flatten(),
             })
         })?;
 
@@ -279,7 +376,7 @@ impl SessionMethods for Database {
                     branch, parent_branch, worktree_path,
                     status, created_at, updated_at, last_activity, initial_prompt, ready_to_merge,
                     original_agent_type, original_skip_permissions, pending_name_generation, was_auto_generated,
-                    spec_content, session_state, resume_allowed, amp_thread_id
+                    spec_content, session_state, resume_allowed, amp_thread_id, labels, agent_model
              FROM sessions
              WHERE id = ?1"
         )?;
@@ -319,6 +416,8 @@ impl SessionMethods for Database {
                     .unwrap_or(SessionState::Running),
                 resume_allowed: row.get(22).unwrap_or(true),
                 amp_thread_id: row.get(23).ok(),
+                labels: labels_from_json(row.get(24).ok().flatten()),
+                agent_model: row.get(25).ok().flatten(),
             })
         })?;
 
@@ -539,6 +638,38 @@ impl SessionMethods for Database {
         Ok(())
     }
 
+    fn update_session_worktree_path(&self, id: &str, worktree_path: &Path) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions SET worktree_path = ?1, updated_at = ?2 WHERE id = ?3",
+            params![worktree_path.to_string_lossy(), Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn rename_session(
+        &self,
+        id: &str,
+        new_name: &str,
+        new_branch: &str,
+        worktree_path: &Path,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions
+             SET name = ?1, branch = ?2, worktree_path = ?3, updated_at = ?4
+             WHERE id = ?5",
+            params![
+                new_name,
+                new_branch,
+                worktree_path.to_string_lossy(),
+                Utc::now().timestamp(),
+                id
+            ],
+        )?;
+        Ok(())
+    }
+
     fn set_pending_name_generation(&self, id: &str, pending: bool) -> Result<()> {
         let conn = self.get_conn()?;
         conn.execute(
@@ -744,7 +875,7 @@ impl SessionMethods for Database {
         Ok(())
     }
 
-    fn set_session_amp_thread_id(&self, id: &str, thread_id: &str) -> Result<()> {
+    fn set_session_amp_thread_id(&self, id: &str, thread_id: Option<&str>) -> Result<()> {
         let conn = self.get_conn()?;
         conn.execute(
             "UPDATE sessions SET amp_thread_id = ?1, updated_at = ?2 WHERE id = ?3",
@@ -753,6 +884,742 @@ impl SessionMethods for Database {
         Ok(())
     }
 
+    fn get_session_codex_profile(&self, id: &str) -> Result<Option<String>> {
+        let conn = self.get_conn()?;
+        let profile: Option<String> = conn.query_row(
+            "SELECT codex_profile FROM sessions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        Ok(profile.filter(|p| !p.trim().is_empty()))
+    }
+
+    fn set_session_codex_profile(&self, id: &str, profile: Option<&str>) -> Result<()> {
+        let conn = self.get_conn()?;
+        let profile = profile.map(str::trim).filter(|p| !p.is_empty());
+        conn.execute(
+            "UPDATE sessions SET codex_profile = ?1, updated_at = ?2 WHERE id = ?3",
+            params![profile, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn set_session_agent_model(&self, id: &str, model: Option<&str>) -> Result<()> {
+        let conn = self.get_conn()?;
+        let model = model.map(str::trim).filter(|m| !m.is_empty());
+        conn.execute(
+            "UPDATE sessions SET agent_model = ?1, updated_at = ?2 WHERE id = ?3",
+            params![model, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn get_session_handoff_prompt(&self, id: &str) -> Result<Option<String>> {
+        let conn = self.get_conn()?;
+        let prompt: Option<String> = conn.query_row(
+            "SELECT handoff_prompt FROM sessions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        Ok(prompt.filter(|p| !p.trim().is_empty()))
+    }
+
+    fn set_session_handoff_prompt(&self, id: &str, prompt: Option<&str>) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions SET handoff_prompt = ?1, updated_at = ?2 WHERE id = ?3",
+            params![prompt, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn set_session_type(&self, id: &str, session_type: SessionType) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions SET session_type = ?1, updated_at = ?2 WHERE id = ?3",
+            params![session_type.as_str(), Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn list_container_sessions(&self, repo_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT name, worktree_path FROM sessions
+             WHERE repository_path = ?1 AND session_type = ?2",
+        )?;
+        let rows = stmt.query_map(
+            params![repo_path.to_string_lossy(), SessionType::Container.as_str()],
+            |row| {
+                let worktree: String = row.get(1)?;
+                Ok((row.get(0)?, PathBuf::from(worktree)))
+            },
+        )?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn get_session_setup_status(&self, id: &str) -> Result<Option<SetupStatus>> {
+        let conn = self.get_conn()?;
+        let status: Option<String> = conn.query_row(
+            "SELECT setup_status FROM sessions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        Ok(status.and_then(|s| s.parse().ok()))
+    }
+
+    fn set_session_setup_status(&self, id: &str, status: Option<SetupStatus>) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions SET setup_status = ?1, updated_at = ?2 WHERE id = ?3",
+            params![status.map(|s| s.as_str()), Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn get_session_environment_variables(&self, id: &str) -> Result<HashMap<String, String>> {
+        let conn = self.get_conn()?;
+        let json: Option<String> = conn.query_row(
+            "SELECT environment_variables FROM sessions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        match json {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    fn set_session_environment_variables(
+        &self,
+        id: &str,
+        env_vars: &HashMap<String, String>,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let json = (!env_vars.is_empty())
+            .then(|| serde_json::to_string(env_vars))
+            .transpose()?;
+        conn.execute(
+            "UPDATE sessions SET environment_variables = ?1, updated_at = ?2 WHERE id = ?3",
+            params![json, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn get_session_settings_overrides(&self, id: &str) -> Result<SettingsOverrides> {
+        let conn = self.get_conn()?;
+        let json: Option<String> = conn.query_row(
+            "SELECT settings_overrides FROM sessions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        match json {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(SettingsOverrides::default()),
+        }
+    }
+
+    fn set_session_settings_overrides(
+        &self,
+        id: &str,
+        overrides: &SettingsOverrides,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let json = (*overrides != SettingsOverrides::default())
+            .then(|| serde_json::to_string(overrides))
+            .transpose()?;
+        conn.execute(
+            "UPDATE sessions SET settings_overrides = ?1, updated_at = ?2 WHERE id = ?3",
+            params![json, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn set_session_labels(&self, id: &str, labels: &[String]) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions SET labels = ?1, updated_at = ?2 WHERE id = ?3",
+            params![labels_to_json(labels)?, Utc::now().timestamp(), id],
+        )?;
+        Ok(())
+    }
+
+    fn set_session_auto_nudge_disabled(&self, id: &str, disabled: bool) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE sessions SET auto_nudge_disabled = ?1 WHERE id = ?2",
+            params![disabled, id],
+        )?;
+        Ok(())
+    }
+
+    fn list_auto_nudge_opt_outs(&self) -> Result<HashSet<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT id FROM sessions WHERE auto_nudge_disabled")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<SqlResult<HashSet<_>>>()?)
+    }
+
+    fn list_session_dependencies(&self, repo_path: &Path) -> Result<Vec<SessionDependency>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT d.session_id, d.depends_on_id, d.satisfied_at IS NOT NULL
+             FROM session_dependencies d
+             JOIN sessions s ON s.id = d.session_id
+             WHERE s.repository_path = ?1",
+        )?;
+        let rows = stmt.query_map(params![repo_path.to_string_lossy()], |row| {
+            Ok(SessionDependency {
+                session_id: row.get(0)?,
+                depends_on_id: row.get(1)?,
+                satisfied: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn set_session_dependencies(&self, id: &str, depends_on_ids: &[String]) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        // Takes the write lock up front: a deferred transaction that reads first cannot
+        // wait for a concurrent writer and fails with "database is locked" instead
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        // Keep rows that stay so an already merged dependency remains satisfied
+        let existing: Vec<String> = {
+            let mut stmt =
+                tx.prepare("SELECT depends_on_id FROM session_dependencies WHERE session_id = ?1")?;
+            let rows = stmt.query_map(params![id], |row| row.get(0))?;
+            rows.collect::<SqlResult<Vec<_>>>()?
+        };
+        for stale in existing.iter().filter(|dep| !depends_on_ids.contains(dep)) {
+            tx.execute(
+                "DELETE FROM session_dependencies WHERE session_id = ?1 AND depends_on_id = ?2",
+                params![id, stale],
+            )?;
+        }
+        for dep in depends_on_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO session_dependencies (session_id, depends_on_id) VALUES (?1, ?2)",
+                params![id, dep],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn mark_dependency_satisfied(&self, depends_on_id: &str) -> Result<Vec<String>> {
+        let conn = self.get_conn()?;
+        let dependents: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT session_id FROM session_dependencies
+                 WHERE depends_on_id = ?1 AND satisfied_at IS NULL",
+            )?;
+            let rows = stmt.query_map(params![depends_on_id], |row| row.get(0))?;
+            rows.collect::<SqlResult<Vec<_>>>()?
+        };
+        conn.execute(
+            "UPDATE session_dependencies SET satisfied_at = ?1
+             WHERE depends_on_id = ?2 AND satisfied_at IS NULL",
+            params![Utc::now().timestamp(), depends_on_id],
+        )?;
+        Ok(dependents)
+    }
+
+    fn add_session_active_seconds(&self, id: &str, day: &str, seconds: u64) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO session_time_entries (session_id, day, active_seconds) VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id, day) DO UPDATE SET active_seconds = active_seconds + ?3",
+            params![id, day, seconds as i64],
+        )?;
+        Ok(())
+    }
+
+    fn list_session_time_entries(&self, repo_path: &Path) -> Result<Vec<SessionTimeEntry>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT t.session_id, t.day, t.active_seconds
+             FROM session_time_entries t
+             JOIN sessions s ON s.id = t.session_id
+             WHERE s.repository_path = ?1
+             ORDER BY t.day",
+        )?;
+        let rows = stmt.query_map(params![repo_path.to_string_lossy()], |row| {
+            Ok(SessionTimeEntry {
+                session_id: row.get(0)?,
+                day: row.get(1)?,
+                active_seconds: row.get::<_, i64>(2)?.max(0) as u64,
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn get_session_notes(&self, id: &str) -> Result<SessionNotes> {
+        let conn = self.get_conn()?;
+        let row: Option<(String, String, i64)> = conn
+            .query_row(
+                "SELECT notes, checklist, updated_at FROM session_notes WHERE session_id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((notes, checklist, updated_at)) = row else {
+            return Ok(SessionNotes::default());
+        };
+        Ok(SessionNotes {
+            notes,
+            checklist: serde_json::from_str(&checklist)?,
+            updated_at: Utc.timestamp_opt(updated_at, 0).single(),
+        })
+    }
+
+    fn set_session_notes(&self, id: &str, notes: &SessionNotes) -> Result<()> {
+        let conn = self.get_conn()?;
+        let checklist = serde_json::to_string(&notes.checklist)?;
+        let updated_at = notes.updated_at.unwrap_or_else(Utc::now).timestamp();
+        conn.execute(
+            "INSERT INTO session_notes (session_id, notes, checklist, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(session_id) DO UPDATE SET
+                notes = excluded.notes,
+                checklist = excluded.checklist,
+                updated_at = excluded.updated_at",
+            params![id, notes.notes, checklist, updated_at],
+        )?;
+        Ok(())
+    }
+
+    fn add_review_comment(&self, id: &str, comment: &ReviewComment) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO session_review_comments
+                (id, session_id, file_path, line_start, line_end, side, selected_text, comment,
+                 created_at, resolved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                comment.id,
+                id,
+                comment.file_path,
+                comment.line_start,
+                comment.line_end,
+                comment.side.as_str(),
+                comment.selected_text,
+                comment.comment,
+                comment.created_at.timestamp(),
+                comment.resolved_at.map(|at| at.timestamp()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_review_comments(&self, id: &str) -> Result<Vec<ReviewComment>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_path, line_start, line_end, side, selected_text, comment,
+                    created_at, resolved_at
+             FROM session_review_comments
+             WHERE session_id = ?1
+             ORDER BY file_path, line_start, created_at",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            let side: String = row.get(4)?;
+            let created_at: i64 = row.get(7)?;
+            let resolved_at: Option<i64> = row.get(8)?;
+            Ok(ReviewComment {
+                id: row.get(0)?,
+                file_path: row.get(1)?,
+                line_start: row.get(2)?,
+                line_end: row.get(3)?,
+                side: DiffSide::from_str(&side).unwrap_or(DiffSide::New),
+                selected_text: row.get(5)?,
+                comment: row.get(6)?,
+                created_at: Utc
+                    .timestamp_opt(created_at, 0)
+                    .single()
+                    .unwrap_or_default(),
+                resolved_at: resolved_at.and_then(|at| Utc.timestamp_opt(at, 0).single()),
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn add_spec_revision(&self, id: &str, revision: &SpecRevision) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO spec_revisions (session_id, revision, content, source, summary, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                revision.revision,
+                revision.content,
+                revision.source.as_str(),
+                revision.summary,
+                revision.created_at.timestamp(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_spec_revisions(&self, id: &str) -> Result<Vec<SpecRevision>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT revision, content, source, summary, created_at
+             FROM spec_revisions
+             WHERE session_id = ?1
+             ORDER BY revision",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            let source: String = row.get(2)?;
+            let created_at: i64 = row.get(4)?;
+            Ok(SpecRevision {
+                revision: row.get(0)?,
+                content: row.get(1)?,
+                source: SpecRevisionSource::from_str(&source).unwrap_or(SpecRevisionSource::Editor),
+                summary: row.get(3)?,
+                created_at: Utc
+                    .timestamp_opt(created_at, 0)
+                    .single()
+                    .unwrap_or_default(),
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn enqueue_follow_up(&self, id: &str, follow_up: &PendingFollowUp) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO pending_follow_ups
+                (id, session_id, message, sent_at, attempts, created_at, last_attempt_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                follow_up.id,
+                id,
+                follow_up.message,
+                follow_up.timestamp as i64,
+                follow_up.attempts,
+                follow_up.created_at.timestamp(),
+                follow_up.last_attempt_at.map(|at| at.timestamp()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_pending_follow_ups(&self, repo_path: &Path) -> Result<Vec<PendingFollowUp>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT f.id, s.name, f.message, f.sent_at, f.attempts, f.created_at, f.last_attempt_at
+             FROM pending_follow_ups f
+             JOIN sessions s ON s.id = f.session_id
+             WHERE s.repository_path = ?1
+             ORDER BY f.rowid",
+        )?;
+        let rows = stmt.query_map(params![repo_path.to_string_lossy()], |row| {
+            let sent_at: i64 = row.get(3)?;
+            let created_at: i64 = row.get(5)?;
+            let last_attempt_at: Option<i64> = row.get(6)?;
+            Ok(PendingFollowUp {
+                id: row.get(0)?,
+                session_name: row.get(1)?,
+                message: row.get(2)?,
+                timestamp: sent_at.max(0) as u64,
+                attempts: row.get(4)?,
+                created_at: Utc
+                    .timestamp_opt(created_at, 0)
+                    .single()
+                    .unwrap_or_default(),
+                last_attempt_at: last_attempt_at.and_then(|at| Utc.timestamp_opt(at, 0).single()),
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn record_follow_up_attempt(&self, follow_up_id: &str, attempted_at: i64) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE pending_follow_ups SET attempts = attempts + 1, last_attempt_at = ?1
+             WHERE id = ?2",
+            params![attempted_at, follow_up_id],
+        )?;
+        Ok(())
+    }
+
+    fn remove_follow_up(&self, follow_up_id: &str) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM pending_follow_ups WHERE id = ?1",
+            params![follow_up_id],
+        )?;
+        Ok(())
+    }
+
+    fn add_agent_crash(&self, id: &str, crash: &AgentCrash) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO session_agent_crashes
+                (session_id, agent_type, exit_code, crashed_at, restart_attempt)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                crash.agent_type,
+                crash.exit_code,
+                crash.crashed_at.timestamp(),
+                crash.restart_attempt,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_agent_crashes(&self, id: &str) -> Result<Vec<AgentCrash>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT agent_type, exit_code, crashed_at, restart_attempt
+             FROM session_agent_crashes
+             WHERE session_id = ?1
+             ORDER BY rowid",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            let crashed_at: i64 = row.get(2)?;
+            Ok(AgentCrash {
+                agent_type: row.get(0)?,
+                exit_code: row.get(1)?,
+                crashed_at: Utc
+                    .timestamp_opt(crashed_at, 0)
+                    .single()
+                    .unwrap_or_default(),
+                restart_attempt: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn set_agent_run_usage(&self, id: &str, run_id: &str, usage: &AgentUsage) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO session_agent_usage
+                (session_id, run_id, input_tokens, output_tokens, cache_read_tokens,
+                 cache_write_tokens, cost_usd, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(session_id, run_id) DO UPDATE SET
+                input_tokens = excluded.input_tokens,
+                output_tokens = excluded.output_tokens,
+                cache_read_tokens = excluded.cache_read_tokens,
+                cache_write_tokens = excluded.cache_write_tokens,
+                cost_usd = excluded.cost_usd,
+                updated_at = excluded.updated_at",
+            params![
+                id,
+                run_id,
+                usage.input_tokens as i64,
+                usage.output_tokens as i64,
+                usage.cache_read_tokens as i64,
+                usage.cache_write_tokens as i64,
+                usage.cost_usd,
+                Utc::now().timestamp(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_session_usage(&self, repo_path: &Path) -> Result<HashMap<String, AgentUsage>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT u.session_id, SUM(u.input_tokens), SUM(u.output_tokens),
+                    SUM(u.cache_read_tokens), SUM(u.cache_write_tokens), SUM(u.cost_usd)
+             FROM session_agent_usage u
+             JOIN sessions s ON s.id = u.session_id
+             WHERE s.repository_path = ?1
+             GROUP BY u.session_id",
+        )?;
+        let rows = stmt.query_map(params![repo_path.to_string_lossy()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                AgentUsage {
+                    input_tokens: row.get::<_, i64>(1)?.max(0) as u64,
+                    output_tokens: row.get::<_, i64>(2)?.max(0) as u64,
+                    cache_read_tokens: row.get::<_, i64>(3)?.max(0) as u64,
+                    cache_write_tokens: row.get::<_, i64>(4)?.max(0) as u64,
+                    cost_usd: row.get(5)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<SqlResult<HashMap<_, _>>>()?)
+    }
+
+    fn resolve_review_comment(&self, id: &str, comment_id: &str, resolved_at: i64) -> Result<bool> {
+        let conn = self.get_conn()?;
+        let updated = conn.execute(
+            "UPDATE session_review_comments SET resolved_at = ?1
+             WHERE session_id = ?2 AND id = ?3 AND resolved_at IS NULL",
+            params![resolved_at, id, comment_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Resolve the given comments and move a reviewed session back to running in one
+    /// transaction. Returns whether the session was reviewed.
+    fn reopen_for_review_changes(
+        &self,
+        id: &str,
+        comment_ids: &[String],
+        resolved_at: i64,
+    ) -> Result<bool> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        for comment_id in comment_ids {
+            tx.execute(
+                "UPDATE session_review_comments SET resolved_at = ?1
+                 WHERE session_id = ?2 AND id = ?3 AND resolved_at IS NULL",
+                params![resolved_at, id, comment_id],
+            )?;
+        }
+        let reopened = tx.execute(
+            "UPDATE sessions
+             SET ready_to_merge = 0, session_state = ?1, updated_at = ?2, last_activity = ?2
+             WHERE id = ?3 AND ready_to_merge = 1",
+            params![SessionState::Running.as_str(), resolved_at, id],
+        )?;
+        tx.commit()?;
+        Ok(reopened > 0)
+    }
+
+    fn set_session_remote_status(&self, id: &str, status: &SessionRemoteStatus) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO session_remote_status
+                (session_id, remote, pushed_commit, pushed_at, pull_request_url)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(session_id) DO UPDATE SET
+                remote = excluded.remote,
+                pushed_commit = excluded.pushed_commit,
+                pushed_at = excluded.pushed_at,
+                pull_request_url = excluded.pull_request_url",
+            params![
+                id,
+                status.remote,
+                status.pushed_commit,
+                status.pushed_at.timestamp(),
+                status.pull_request_url
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_session_remote_statuses(
+        &self,
+        repo_path: &Path,
+    ) -> Result<HashMap<String, SessionRemoteStatus>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT r.session_id, r.remote, r.pushed_commit, r.pushed_at, r.pull_request_url
+             FROM session_remote_status r
+             JOIN sessions s ON s.id = r.session_id
+             WHERE s.repository_path = ?1",
+        )?;
+        let rows = stmt.query_map(params![repo_path.to_string_lossy()], |row| {
+            let pushed_at: i64 = row.get(3)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                SessionRemoteStatus {
+                    remote: row.get(1)?,
+                    pushed_commit: row.get(2)?,
+                    pushed_at: Utc.timestamp_opt(pushed_at, 0).single().unwrap_or_default(),
+                    pull_request_url: row.get(4)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<SqlResult<HashMap<_, _>>>()?)
+    }
+
+    fn get_session_environment_variables_by_worktree(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<HashMap<String, String>> {
+        let conn = self.get_conn()?;
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT environment_variables FROM sessions
+                 WHERE worktree_path = ?1 AND status != ?2
+                 ORDER BY updated_at DESC LIMIT 1",
+                params![
+                    worktree_path.to_string_lossy(),
+                    SessionStatus::Cancelled.as_str()
+                ],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        match json {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    fn get_session_settings_overrides_by_worktree(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<SettingsOverrides> {
+        let conn = self.get_conn()?;
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT settings_overrides FROM sessions
+                 WHERE worktree_path = ?1 AND status != ?2
+                 ORDER BY updated_at DESC LIMIT 1",
+                params![
+                    worktree_path.to_string_lossy(),
+                    SessionStatus::Cancelled.as_str()
+                ],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        match json {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(SettingsOverrides::default()),
+        }
+    }
+
+    fn delete_session(&self, id: &str) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute("DELETE FROM git_stats WHERE session_id = ?1", params![id])?;
+        conn.execute(
+            "DELETE FROM session_dependencies WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM session_time_entries WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM session_notes WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM session_review_comments WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM session_remote_status WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM spec_revisions WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM pending_follow_ups WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM session_agent_crashes WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM session_agent_usage WHERE session_id = ?1",
+            params![id],
+        )?;
+        conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
     fn rename_draft_session(&self, repo_path: &Path, old_name: &str, new_name: &str) -> Result<()> {
         let conn = self.get_conn()?;
 
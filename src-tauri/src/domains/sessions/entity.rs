@@ -1,5 +1,8 @@
+use crate::domains::agents::usage::AgentUsage;
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -29,6 +32,7 @@ pub enum FilterMode {
     Spec,
     Running,
     Reviewed,
+    Label(String),
 }
 
 impl FromStr for FilterMode {
@@ -40,12 +44,17 @@ impl FromStr for FilterMode {
             "spec" => Ok(FilterMode::Spec),
             "running" => Ok(FilterMode::Running),
             "reviewed" => Ok(FilterMode::Reviewed),
-            _ => Err(format!("Invalid filter mode: {s}")),
+            _ => match s.strip_prefix("label:") {
+                Some(label) if !label.trim().is_empty() => {
+                    Ok(FilterMode::Label(label.trim().to_string()))
+                }
+                _ => Err(format!("Invalid filter mode: {s}")),
+            },
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChangedFile {
     pub path: String,
     pub change_type: String,
@@ -87,6 +96,12 @@ pub struct Session {
     pub resume_allowed: bool,
     // Amp thread ID for resuming threads across Schaltwerk sessions
     pub amp_thread_id: Option<String>,
+    // User-assigned labels for grouping sessions by feature area
+    #[serde(default)]
+    pub labels: Vec<String>,
+    // Model passed to the agent (e.g. "opus" or "o3"); None uses the agent's default
+    #[serde(default)]
+    pub agent_model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -151,6 +166,41 @@ impl FromStr for SessionState {
     }
 }
 
+/// Outcome of the project setup script for a session worktree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SetupStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl SetupStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SetupStatus::Running => "running",
+            SetupStatus::Succeeded => "succeeded",
+            SetupStatus::Failed => "failed",
+            SetupStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl FromStr for SetupStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(SetupStatus::Running),
+            "succeeded" => Ok(SetupStatus::Succeeded),
+            "failed" => Ok(SetupStatus::Failed),
+            "cancelled" => Ok(SetupStatus::Cancelled),
+            _ => Err(format!("Invalid setup status: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitStats {
     pub session_id: String,
@@ -162,6 +212,19 @@ pub struct GitStats {
     // Timestamp (unix seconds) of the most recent meaningful diff change:
     // max(latest commit ahead of base, latest mtime among uncommitted changed files)
     pub last_diff_change_ts: Option<i64>,
+    /// Last background comparison against the base branch; `None` until it ran once
+    #[serde(default)]
+    pub base_freshness: Option<BaseFreshness>,
+}
+
+/// How far a session branch and the latest fetched tip of its base branch have diverged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseFreshness {
+    /// Session commits the base branch does not contain
+    pub ahead: u32,
+    /// Base branch commits the session does not contain yet
+    pub behind: u32,
+    pub checked_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -204,6 +267,17 @@ pub enum SessionStatusType {
 #[serde(rename_all = "lowercase")]
 pub enum SessionType {
     Worktree,
+    /// Worktree whose terminals run inside a per-session container
+    Container,
+}
+
+impl SessionType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SessionType::Worktree => "worktree",
+            SessionType::Container => "container",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -242,6 +316,8 @@ pub struct SessionInfo {
     pub container_status: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_agent_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_model: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub current_task: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -253,6 +329,319 @@ pub struct SessionInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub worktree_size_bytes: Option<u64>,
     pub session_state: SessionState,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Names of sessions that have to merge before this one may start
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// The part of `depends_on` that has not merged yet
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+    /// Opted out of follow-up nudges when its agent stalls
+    #[serde(default)]
+    pub auto_nudge_disabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_status: Option<SessionRemoteStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_freshness: Option<BaseFreshness>,
+    /// Token usage and cost the session's agents reported so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<AgentUsage>,
+}
+
+/// A blocked-on relationship: `session_id` may only start once `depends_on_id` merged.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionDependency {
+    pub session_id: String,
+    pub depends_on_id: String,
+    pub satisfied: bool,
+}
+
+/// Busy agent time of one session on one local calendar day (`YYYY-MM-DD`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionTimeEntry {
+    pub session_id: String,
+    pub day: String,
+    pub active_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionTimeTotals {
+    pub session_name: String,
+    /// Time the agent was producing output rather than sitting idle
+    pub active_seconds: u64,
+    /// From creation until now, or until the session was reviewed or cancelled
+    pub wall_clock_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DailyTimeTotals {
+    pub day: String,
+    pub active_seconds: u64,
+    /// Active seconds per session name
+    pub sessions: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionTimeReport {
+    pub sessions: Vec<SessionTimeTotals>,
+    pub days: Vec<DailyTimeTotals>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionCostTotals {
+    pub session_name: String,
+    #[serde(flatten)]
+    pub usage: AgentUsage,
+}
+
+/// Token usage and cost per session, and for the whole project
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionCostReport {
+    pub sessions: Vec<SessionCostTotals>,
+    pub total: AgentUsage,
+}
+
+/// Disk space taken by a session worktree still on disk
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorktreeDiskUsage {
+    pub session_name: String,
+    pub worktree_path: String,
+    pub cancelled: bool,
+    pub total_bytes: u64,
+    /// Part of `total_bytes` in dependency and build output directories
+    pub artifact_bytes: u64,
+}
+
+/// A directory a worktree prune removes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrunedPath {
+    pub path: String,
+    pub session_name: Option<String>,
+    pub bytes: u64,
+}
+
+/// What a worktree prune removes; with `dry_run` nothing has been deleted yet
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorktreePruneReport {
+    pub dry_run: bool,
+    /// Worktrees no session owns anymore
+    pub orphaned_worktrees: Vec<PrunedPath>,
+    /// Build artifacts left in worktrees of cancelled sessions
+    pub build_artifacts: Vec<PrunedPath>,
+    pub reclaimed_bytes: u64,
+}
+
+/// Result of switching a project's branch prefix
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BranchPrefixMigration {
+    pub prefix: String,
+    /// Sessions whose branch moved to the new prefix
+    pub renamed: Vec<String>,
+    /// Sessions that kept their old branch because it could not be renamed
+    pub skipped: Vec<String>,
+}
+
+/// One point of a session's review checklist
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReviewChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub checked: bool,
+}
+
+/// Free-form review notes and checklist a reviewer keeps for a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionNotes {
+    pub notes: String,
+    pub checklist: Vec<ReviewChecklistItem>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Which side of a diff a review comment points at
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffSide {
+    /// The base version of the file
+    Old,
+    /// The session's version of the file
+    New,
+}
+
+impl DiffSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffSide::Old => "old",
+            DiffSide::New => "new",
+        }
+    }
+}
+
+impl FromStr for DiffSide {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "old" => Ok(DiffSide::Old),
+            "new" => Ok(DiffSide::New),
+            _ => Err(format!("Invalid diff side: {s}")),
+        }
+    }
+}
+
+/// A review comment as submitted from the diff viewer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NewReviewComment {
+    pub file_path: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub side: DiffSide,
+    #[serde(default)]
+    pub selected_text: String,
+    pub comment: String,
+}
+
+/// A reviewer's comment on a line range of a session's diff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReviewComment {
+    pub id: String,
+    pub file_path: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub side: DiffSide,
+    pub selected_text: String,
+    pub comment: String,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// A follow-up message for a session's agent that has not reached its terminal yet,
+/// e.g. because the agent was restarting when it arrived
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingFollowUp {
+    pub id: String,
+    pub session_name: String,
+    pub message: String,
+    /// Milliseconds since the Unix epoch at which the message was sent
+    pub timestamp: u64,
+    /// Delivery attempts that found no agent terminal
+    pub attempts: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// An agent process of a session that exited with an error
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentCrash {
+    pub agent_type: String,
+    pub exit_code: Option<i32>,
+    pub crashed_at: DateTime<Utc>,
+    /// Automatic restart the crash led to, counting from 1; None when it was not restarted
+    pub restart_attempt: Option<u32>,
+}
+
+/// Who wrote a stored version of a spec
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecRevisionSource {
+    /// Content found in the spec when history was recorded, written in the app
+    Editor,
+    /// A change proposed through the local API, usually by an MCP agent
+    Api,
+}
+
+impl SpecRevisionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecRevisionSource::Editor => "editor",
+            SpecRevisionSource::Api => "api",
+        }
+    }
+}
+
+impl FromStr for SpecRevisionSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "editor" => Ok(SpecRevisionSource::Editor),
+            "api" => Ok(SpecRevisionSource::Api),
+            _ => Err(format!("Invalid spec revision source: {s}")),
+        }
+    }
+}
+
+/// A stored version of a spec's content; numbers start at 1 per spec
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpecRevision {
+    pub revision: u32,
+    pub content: String,
+    pub source: SpecRevisionSource,
+    pub summary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One search-and-replace step of a proposed spec change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct SpecEdit {
+    /// Text that must occur exactly once in the spec
+    pub find: String,
+    pub replace: String,
+}
+
+/// A proposed spec change: the full new content or edits to the current one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecChange {
+    Content(String),
+    Edits(Vec<SpecEdit>),
+}
+
+/// Why a proposed spec change was not stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecRevisionError {
+    /// The spec moved past the revision the change was made against
+    Conflict {
+        base_revision: u32,
+        current_revision: u32,
+    },
+    /// An edit could not be applied to the current content
+    InvalidEdit(String),
+}
+
+impl std::fmt::Display for SpecRevisionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecRevisionError::Conflict {
+                base_revision,
+                current_revision,
+            } => write!(
+                f,
+                "Spec changed since revision {base_revision}; the current revision is {current_revision}"
+            ),
+            SpecRevisionError::InvalidEdit(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SpecRevisionError {}
+
+/// Where a session branch was last pushed and the pull request opened for it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionRemoteStatus {
+    pub remote: String,
+    pub pushed_commit: String,
+    pub pushed_at: DateTime<Utc>,
+    pub pull_request_url: Option<String>,
+}
+
+/// Session without activity and uncommitted changes for longer than the stale policy allows
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StaleSession {
+    pub session_name: String,
+    pub last_activity: DateTime<Utc>,
+    pub idle_days: i64,
+    /// Reviewed or ahead of its parent branch; the policy labels these instead of cancelling
+    pub has_unmerged_work: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -279,6 +668,28 @@ pub enum TestStatus {
     Unknown,
 }
 
+/// Reusable prompt text with `{{variable}}` placeholders
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    /// Project the template belongs to; `None` for templates shared by every project
+    pub repository_path: Option<PathBuf>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where a rendered prompt template goes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptTemplateTarget {
+    /// The prompt the agent starts with: a spec's content or a running session's prompt
+    InitialPrompt,
+    /// A message pasted into the running agent
+    FollowUp,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchivedSpec {
     pub id: String,
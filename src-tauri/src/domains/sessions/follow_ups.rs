@@ -0,0 +1,248 @@
+//! Delivery of queued follow-up messages to session agents. Messages are stored before
+//! they are pasted, so one that arrives while the agent terminal is being restarted waits
+//! in the queue and is retried until the terminal is back.
+
+use crate::domains::sessions::db_sessions::SessionMethods;
+use crate::domains::terminal::TerminalManager;
+use crate::infrastructure::events::{emit_event, SchaltEvent};
+use crate::schaltwerk_core::database::Database;
+use crate::shared::terminal_id::{
+    legacy_terminal_id_for_session_top, previous_hashed_terminal_id_for_session_top,
+    terminal_id_for_session_top,
+};
+use anyhow::Result;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+
+const FOLLOW_UP_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+// The webhook handler and the retry loop both flush the queue; a message must not be
+// pasted twice because they ran at the same time
+static DELIVERY_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Payload of `SchaltEvent::FollowUpMessage`, emitted once a message reached its agent
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FollowUpMessagePayload {
+    pub session_name: String,
+    pub message: String,
+    pub timestamp: u64,
+    pub terminal_id: String,
+    pub project_path: Option<String>,
+}
+
+/// Paste `message` into the session's agent terminal, trying the terminal ids older app
+/// versions used as well. Returns the terminal that received it.
+pub async fn deliver_to_agent(
+    terminals: &TerminalManager,
+    session_name: &str,
+    message: &str,
+) -> Option<String> {
+    let mut candidate_ids = Vec::new();
+    for candidate in [
+        terminal_id_for_session_top(session_name),
+        previous_hashed_terminal_id_for_session_top(session_name),
+        legacy_terminal_id_for_session_top(session_name),
+    ] {
+        if !candidate_ids.contains(&candidate) {
+            candidate_ids.push(candidate);
+        }
+    }
+
+    for candidate in candidate_ids.iter() {
+        match terminals.terminal_exists(candidate).await {
+            Ok(true) => {
+                // Without bracketed paste each line would run as it arrives
+                if terminals
+                    .paste_needs_confirmation(candidate, message.as_bytes())
+                    .await
+                {
+                    terminals
+                        .hold_paste_for_confirmation(
+                            candidate.clone(),
+                            message.as_bytes().to_vec(),
+                            false,
+                        )
+                        .await;
+                    log::info!(
+                        "Holding multi-line follow-up for terminal {candidate} until confirmed"
+                    );
+                    return Some(candidate.clone());
+                }
+                match terminals
+                    .paste_and_submit_terminal(
+                        candidate.clone(),
+                        message.as_bytes().to_vec(),
+                        false,
+                    )
+                    .await
+                {
+                    Ok(_) => {
+                        log::info!("Successfully pasted follow-up message to terminal {candidate}");
+                        return Some(candidate.clone());
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to paste follow-up message to terminal {candidate}: {e}"
+                        );
+                        // Try next candidate in case this ID is stale.
+                    }
+                }
+            }
+            Ok(false) => {
+                log::debug!("Terminal {candidate} not found while handling follow-up; checking next candidate");
+            }
+            Err(e) => {
+                log::warn!("Failed to check if terminal {candidate} exists: {e}");
+            }
+        }
+    }
+
+    log::info!("No agent terminal for follow-up message to session '{session_name}' yet. Tried: {candidate_ids:?}");
+    None
+}
+
+/// Deliver the project's queued follow-ups, oldest first, optionally only those of one
+/// session. Once a message of a session can't be delivered, the session's later messages
+/// wait too, so an agent always receives its messages in order.
+pub async fn deliver_pending_follow_ups(
+    db: &Database,
+    repo_path: &Path,
+    terminals: &TerminalManager,
+    session_name: Option<&str>,
+) -> Result<Vec<FollowUpMessagePayload>> {
+    let _guard = DELIVERY_LOCK.lock().await;
+    let mut waiting_sessions = HashSet::new();
+    let mut delivered = Vec::new();
+    for follow_up in db.list_pending_follow_ups(repo_path)? {
+        if session_name.is_some_and(|name| name != follow_up.session_name)
+            || waiting_sessions.contains(&follow_up.session_name)
+        {
+            continue;
+        }
+        match deliver_to_agent(terminals, &follow_up.session_name, &follow_up.message).await {
+            Some(terminal_id) => {
+                db.remove_follow_up(&follow_up.id)?;
+                delivered.push(FollowUpMessagePayload {
+                    session_name: follow_up.session_name,
+                    message: follow_up.message,
+                    timestamp: follow_up.timestamp,
+                    terminal_id,
+                    project_path: Some(repo_path.to_string_lossy().to_string()),
+                });
+            }
+            None => {
+                db.record_follow_up_attempt(&follow_up.id, Utc::now().timestamp())?;
+                waiting_sessions.insert(follow_up.session_name);
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+/// Tell the UI which follow-ups reached their agent.
+pub fn emit_delivered_follow_ups(app: &AppHandle, delivered: &[FollowUpMessagePayload]) {
+    for payload in delivered {
+        if let Err(e) = emit_event(app, SchaltEvent::FollowUpMessage, payload) {
+            log::error!("Failed to emit follow-up-message event: {e}");
+        }
+    }
+}
+
+/// Periodically retries queued follow-ups, e.g. messages that arrived while the agent
+/// terminal was closed for a restart.
+pub fn start_follow_up_delivery(
+    db: Arc<Database>,
+    repo_path: PathBuf,
+    terminals: Arc<TerminalManager>,
+    app: AppHandle,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(FOLLOW_UP_RETRY_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match deliver_pending_follow_ups(&db, &repo_path, &terminals, None).await {
+                Ok(delivered) => emit_delivered_follow_ups(&app, &delivered),
+                Err(e) => log::warn!("Failed to deliver queued follow-up messages: {e}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::sessions::entity::{Session, SessionState, SessionStatus};
+    use crate::domains::sessions::service::SessionManager;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn undeliverable_follow_ups_keep_their_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(Some(temp_dir.path().join("test.db"))).unwrap();
+        let repo_path = temp_dir.path().join("repo");
+        std::fs::create_dir_all(&repo_path).unwrap();
+        let session = Session {
+            id: "s-1".into(),
+            name: "restarting-agent".into(),
+            display_name: None,
+            version_group_id: None,
+            version_number: None,
+            repository_path: repo_path.clone(),
+            repository_name: "repo".into(),
+            branch: "schaltwerk/restarting-agent".into(),
+            parent_branch: "main".into(),
+            worktree_path: temp_dir.path().join("worktree"),
+            status: SessionStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_activity: None,
+            initial_prompt: None,
+            ready_to_merge: false,
+            original_agent_type: None,
+            original_skip_permissions: None,
+            pending_name_generation: false,
+            was_auto_generated: false,
+            spec_content: None,
+            session_state: SessionState::Running,
+            resume_allowed: true,
+            amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
+        };
+        db.create_session(&session).unwrap();
+        let manager = SessionManager::new(db.clone(), repo_path.clone());
+
+        manager
+            .queue_follow_up(&session.name, "Run the tests", 1_000)
+            .unwrap();
+        manager
+            .queue_follow_up(&session.name, "Then fix lint", 2_000)
+            .unwrap();
+
+        let terminals = TerminalManager::new();
+        let delivered = deliver_pending_follow_ups(&db, &repo_path, &terminals, None)
+            .await
+            .unwrap();
+        assert!(delivered.is_empty());
+
+        let attempts: Vec<_> = manager
+            .list_pending_follow_ups()
+            .unwrap()
+            .into_iter()
+            .map(|follow_up| (follow_up.message, follow_up.attempts))
+            .collect();
+        assert_eq!(
+            attempts,
+            vec![
+                ("Run the tests".to_string(), 1),
+                ("Then fix lint".to_string(), 0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Supervision of session agents. When enabled, an agent that exits with an error is
+//! started again in its terminal a limited number of times, waiting twice as long after
+//! every crash. Crashes are recorded per session whether or not they are restarted.
+
+use crate::domains::sessions::entity::AgentCrash;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+
+pub const DEFAULT_MAX_AGENT_RESTARTS: u32 = 3;
+pub const DEFAULT_RESTART_BACKOFF_SECS: u64 = 5;
+pub const DEFAULT_MAX_RESTART_BACKOFF_SECS: u64 = 300;
+pub const DEFAULT_RESTART_RESET_AFTER_SECS: u64 = 30 * 60;
+
+/// Exit status of an agent stopped with Ctrl+C; the user ended it on purpose
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Automatic restarts of crashed session agents. Crashes less than `reset_after_secs`
+/// apart form one series, and a series is restarted at most `max_restarts` times.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentRestartConfig {
+    pub enabled: bool,
+    pub max_restarts: u32,
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub reset_after_secs: u64,
+}
+
+impl Default for AgentRestartConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_restarts: DEFAULT_MAX_AGENT_RESTARTS,
+            initial_backoff_secs: DEFAULT_RESTART_BACKOFF_SECS,
+            max_backoff_secs: DEFAULT_MAX_RESTART_BACKOFF_SECS,
+            reset_after_secs: DEFAULT_RESTART_RESET_AFTER_SECS,
+        }
+    }
+}
+
+static AGENT_RESTART_CONFIG: RwLock<Option<AgentRestartConfig>> = RwLock::new(None);
+
+pub fn put_agent_restart_config(config: AgentRestartConfig) {
+    if let Ok(mut guard) = AGENT_RESTART_CONFIG.write() {
+        *guard = Some(config);
+    }
+}
+
+pub fn agent_restart_config() -> AgentRestartConfig {
+    AGENT_RESTART_CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_default()
+}
+
+/// When and as which attempt a crashed agent is started again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPlan {
+    pub attempt: u32,
+    pub delay: Duration,
+}
+
+/// Decide whether an agent that exited with `exit_code` at `now` is restarted, given the
+/// session's earlier crashes, oldest first.
+pub fn plan_restart(
+    config: &AgentRestartConfig,
+    history: &[AgentCrash],
+    exit_code: Option<i32>,
+    now: DateTime<Utc>,
+) -> Option<RestartPlan> {
+    if !config.enabled || exit_code == Some(INTERRUPTED_EXIT_CODE) {
+        return None;
+    }
+
+    let reset_after = chrono::Duration::seconds(config.reset_after_secs as i64);
+    let mut series_start = now;
+    let mut earlier_in_series = 0u32;
+    for crash in history.iter().rev() {
+        if series_start - crash.crashed_at > reset_after {
+            break;
+        }
+        series_start = crash.crashed_at;
+        earlier_in_series += 1;
+    }
+
+    let attempt = earlier_in_series + 1;
+    if attempt > config.max_restarts {
+        return None;
+    }
+    let backoff = config
+        .initial_backoff_secs
+        .saturating_mul(1u64 << (attempt - 1).min(32))
+        .min(config.max_backoff_secs.max(config.initial_backoff_secs));
+    Some(RestartPlan {
+        attempt,
+        delay: Duration::from_secs(backoff),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crash(now: DateTime<Utc>, minutes_ago: i64) -> AgentCrash {
+        AgentCrash {
+            agent_type: "claude".to_string(),
+            exit_code: Some(1),
+            crashed_at: now - chrono::Duration::minutes(minutes_ago),
+            restart_attempt: None,
+        }
+    }
+
+    fn enabled() -> AgentRestartConfig {
+        AgentRestartConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_until_restarts_run_out() {
+        let now = Utc::now();
+        let config = enabled();
+        let mut history = Vec::new();
+        let mut delays = Vec::new();
+        while let Some(plan) = plan_restart(&config, &history, Some(1), now) {
+            assert_eq!(plan.attempt as usize, history.len() + 1);
+            delays.push(plan.delay.as_secs());
+            history.push(crash(now, 0));
+        }
+        assert_eq!(delays, vec![5, 10, 20]);
+
+        let capped = AgentRestartConfig {
+            max_restarts: 10,
+            max_backoff_secs: 30,
+            ..enabled()
+        };
+        let plan = plan_restart(&capped, &history, Some(1), now).unwrap();
+        assert_eq!((plan.attempt, plan.delay.as_secs()), (4, 30));
+    }
+
+    #[test]
+    fn test_series_resets_after_quiet_period() {
+        let now = Utc::now();
+        let config = enabled();
+        // Each crash within 30 minutes of the next: one series with three restarts
+        let history = vec![crash(now, 50), crash(now, 25), crash(now, 1)];
+        assert_eq!(plan_restart(&config, &history, Some(1), now), None);
+
+        let history = vec![crash(now, 90), crash(now, 80), crash(now, 45)];
+        let plan = plan_restart(&config, &history, Some(1), now).unwrap();
+        assert_eq!(plan.attempt, 1);
+    }
+
+    #[test]
+    fn test_no_restart_when_disabled_or_interrupted() {
+        let now = Utc::now();
+        assert_eq!(
+            plan_restart(&AgentRestartConfig::default(), &[], Some(1), now),
+            None
+        );
+        assert_eq!(plan_restart(&enabled(), &[], Some(130), now), None);
+        assert!(plan_restart(&enabled(), &[], None, now).is_some());
+    }
+}
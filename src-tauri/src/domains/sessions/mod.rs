@@ -1,10 +1,16 @@
 pub mod activity;
 pub mod cache;
+pub mod cancellation;
 pub mod db_sessions;
 pub mod entity;
+pub mod follow_ups;
+pub mod lifecycle;
 pub mod process_cleanup;
+pub mod prompt_templates;
 pub mod repository;
 pub mod service;
+pub mod setup_cache;
+pub mod setup_scripts;
 pub mod storage;
 pub mod utils;
 
@@ -0,0 +1,113 @@
+//! Rendering of prompt templates. A template refers to session details with
+//! `{{variable}}` placeholders, which are filled in when it is sent to a session.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Variables a template can use, with what they stand for
+pub const TEMPLATE_VARIABLES: &[(&str, &str)] = &[
+    ("session", "Name of the session"),
+    ("branch", "Branch of the session"),
+    ("base_branch", "Branch the session started from"),
+    ("worktree_path", "Path of the session's worktree"),
+    ("project", "Name of the project"),
+    ("spec", "The session's spec or initial prompt"),
+    ("changed_files", "Files the session changed, one per line"),
+];
+
+/// Placeholders in `content`, by name and in order of first use.
+pub fn template_variables(content: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Reject templates with placeholders that name no known variable.
+pub fn validate_template(content: &str) -> Result<()> {
+    let unknown: Vec<&str> = template_variables(content)
+        .into_iter()
+        .filter(|name| !TEMPLATE_VARIABLES.iter().any(|(known, _)| known == name))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Unknown template variable(s): {}",
+        unknown
+            .iter()
+            .map(|name| format!("{{{{{name}}}}}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// Replace each placeholder with its value; a variable without a value renders empty.
+pub fn render_template(content: &str, values: &HashMap<&str, String>) -> Result<String> {
+    validate_template(content)?;
+    let mut rendered = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        if let Some(value) = values.get(after[..end].trim()) {
+            rendered.push_str(value);
+        }
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_known_variables() {
+        let values = HashMap::from([
+            ("branch", "schaltwerk/login".to_string()),
+            ("changed_files", "src/a.rs\nsrc/b.rs".to_string()),
+        ]);
+        let rendered = render_template(
+            "Review {{branch}} ({{ branch }}):\n{{changed_files}}\nSpec: {{spec}}",
+            &values,
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "Review schaltwerk/login (schaltwerk/login):\nsrc/a.rs\nsrc/b.rs\nSpec: "
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_variables_and_keeps_unclosed_braces() {
+        let err = render_template("Fix {{brnach}} and {{ticket}}", &HashMap::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown template variable(s): {{brnach}}, {{ticket}}"
+        );
+
+        assert_eq!(
+            render_template("Keep {{ this", &HashMap::new()).unwrap(),
+            "Keep {{ this"
+        );
+        assert_eq!(
+            template_variables("{{spec}} {{session}} {{spec}}"),
+            vec!["spec", "session"]
+        );
+    }
+}
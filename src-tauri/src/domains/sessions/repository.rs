@@ -1,16 +1,26 @@
 use crate::{
+    domains::agents::usage::AgentUsage,
     domains::git::db_git_stats::GitStatsMethods,
     domains::git::service as git,
     domains::sessions::db_sessions::SessionMethods,
-    domains::sessions::entity::{GitStats, Session, SessionState, SessionStatus},
+    domains::sessions::entity::{
+        AgentCrash, GitStats, PendingFollowUp, ReviewComment, Session, SessionDependency,
+        SessionNotes, SessionRemoteStatus, SessionState, SessionStatus, SessionTimeEntry,
+        SessionType, SetupStatus, SpecRevision,
+    },
+    domains::sessions::setup_scripts::compose_project_setup,
+    domains::settings::SettingsOverrides,
+    domains::workspace::dev_environment::compose_setup_script,
     schaltwerk_core::database::Database,
     schaltwerk_core::db_app_config::AppConfigMethods,
-    schaltwerk_core::db_project_config::ProjectConfigMethods,
+    schaltwerk_core::db_project_config::{AgentTaskKind, ProjectConfigMethods},
 };
 use anyhow::{anyhow, Result};
 use chrono::Utc;
 use log::warn;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct SessionDbManager {
@@ -223,18 +233,268 @@ impl SessionDbManager {
             .map_err(|e| anyhow!("Failed to clear session run state: {e}"))
     }
 
+    pub fn set_session_agent_model(&self, session_id: &str, model: Option<&str>) -> Result<()> {
+        self.db
+            .set_session_agent_model(session_id, model)
+            .map_err(|e| anyhow!("Failed to set session agent model: {e}"))
+    }
+
+    pub fn get_session_handoff_prompt(&self, session_id: &str) -> Result<Option<String>> {
+        self.db
+            .get_session_handoff_prompt(session_id)
+            .map_err(|e| anyhow!("Failed to get session handoff prompt: {e}"))
+    }
+
+    pub fn set_session_handoff_prompt(&self, session_id: &str, prompt: Option<&str>) -> Result<()> {
+        self.db
+            .set_session_handoff_prompt(session_id, prompt)
+            .map_err(|e| anyhow!("Failed to set session handoff prompt: {e}"))
+    }
+
     pub fn set_session_resume_allowed(&self, session_id: &str, allowed: bool) -> Result<()> {
         self.db
             .set_session_resume_allowed(session_id, allowed)
             .map_err(|e| anyhow!("Failed to set resume_allowed: {e}"))
     }
 
-    pub fn set_session_amp_thread_id(&self, session_id: &str, thread_id: &str) -> Result<()> {
+    pub fn get_session_setup_status(&self, session_id: &str) -> Result<Option<SetupStatus>> {
+        self.db
+            .get_session_setup_status(session_id)
+            .map_err(|e| anyhow!("Failed to get setup status: {e}"))
+    }
+
+    pub fn set_session_setup_status(
+        &self,
+        session_id: &str,
+        status: Option<SetupStatus>,
+    ) -> Result<()> {
+        self.db
+            .set_session_setup_status(session_id, status)
+            .map_err(|e| anyhow!("Failed to set setup status: {e}"))
+    }
+
+    pub fn get_session_environment_variables(
+        &self,
+        session_id: &str,
+    ) -> Result<HashMap<String, String>> {
+        self.db
+            .get_session_environment_variables(session_id)
+            .map_err(|e| anyhow!("Failed to get session environment variables: {e}"))
+    }
+
+    pub fn set_session_environment_variables(
+        &self,
+        session_id: &str,
+        env_vars: &HashMap<String, String>,
+    ) -> Result<()> {
+        self.db
+            .set_session_environment_variables(session_id, env_vars)
+            .map_err(|e| anyhow!("Failed to set session environment variables: {e}"))
+    }
+
+    pub fn get_session_settings_overrides(&self, session_id: &str) -> Result<SettingsOverrides> {
+        self.db
+            .get_session_settings_overrides(session_id)
+            .map_err(|e| anyhow!("Failed to get session settings overrides: {e}"))
+    }
+
+    pub fn set_session_settings_overrides(
+        &self,
+        session_id: &str,
+        overrides: &SettingsOverrides,
+    ) -> Result<()> {
+        self.db
+            .set_session_settings_overrides(session_id, overrides)
+            .map_err(|e| anyhow!("Failed to set session settings overrides: {e}"))
+    }
+
+    pub fn set_session_labels(&self, session_id: &str, labels: &[String]) -> Result<()> {
+        self.db
+            .set_session_labels(session_id, labels)
+            .map_err(|e| anyhow!("Failed to set session labels: {e}"))
+    }
+
+    pub fn set_session_auto_nudge_disabled(&self, session_id: &str, disabled: bool) -> Result<()> {
+        self.db
+            .set_session_auto_nudge_disabled(session_id, disabled)
+            .map_err(|e| anyhow!("Failed to update auto-nudge opt-out: {e}"))
+    }
+
+    pub fn list_auto_nudge_opt_outs(&self) -> Result<HashSet<String>> {
+        self.db
+            .list_auto_nudge_opt_outs()
+            .map_err(|e| anyhow!("Failed to list auto-nudge opt-outs: {e}"))
+    }
+
+    pub fn list_session_dependencies(&self) -> Result<Vec<SessionDependency>> {
+        self.db
+            .list_session_dependencies(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to list session dependencies: {e}"))
+    }
+
+    pub fn set_session_dependencies(
+        &self,
+        session_id: &str,
+        depends_on_ids: &[String],
+    ) -> Result<()> {
+        self.db
+            .set_session_dependencies(session_id, depends_on_ids)
+            .map_err(|e| anyhow!("Failed to set session dependencies: {e}"))
+    }
+
+    /// Marks every dependency on `session_id` as merged; returns the ids of the dependents.
+    pub fn mark_dependency_satisfied(&self, session_id: &str) -> Result<Vec<String>> {
+        self.db
+            .mark_dependency_satisfied(session_id)
+            .map_err(|e| anyhow!("Failed to record merged dependency: {e}"))
+    }
+
+    pub fn list_session_time_entries(&self) -> Result<Vec<SessionTimeEntry>> {
+        self.db
+            .list_session_time_entries(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to list session time entries: {e}"))
+    }
+
+    pub fn get_session_notes(&self, session_id: &str) -> Result<SessionNotes> {
+        self.db
+            .get_session_notes(session_id)
+            .map_err(|e| anyhow!("Failed to get session notes: {e}"))
+    }
+
+    pub fn set_session_notes(&self, session_id: &str, notes: &SessionNotes) -> Result<()> {
+        self.db
+            .set_session_notes(session_id, notes)
+            .map_err(|e| anyhow!("Failed to set session notes: {e}"))
+    }
+
+    pub fn add_review_comment(&self, session_id: &str, comment: &ReviewComment) -> Result<()> {
+        self.db
+            .add_review_comment(session_id, comment)
+            .map_err(|e| anyhow!("Failed to add review comment: {e}"))
+    }
+
+    pub fn list_review_comments(&self, session_id: &str) -> Result<Vec<ReviewComment>> {
+        self.db
+            .list_review_comments(session_id)
+            .map_err(|e| anyhow!("Failed to list review comments: {e}"))
+    }
+
+    pub fn resolve_review_comment(&self, session_id: &str, comment_id: &str) -> Result<bool> {
+        self.db
+            .resolve_review_comment(session_id, comment_id, Utc::now().timestamp())
+            .map_err(|e| anyhow!("Failed to resolve review comment: {e}"))
+    }
+
+    pub fn reopen_for_review_changes(
+        &self,
+        session_id: &str,
+        comment_ids: &[String],
+    ) -> Result<bool> {
+        self.db
+            .reopen_for_review_changes(session_id, comment_ids, Utc::now().timestamp())
+            .map_err(|e| anyhow!("Failed to reopen session for review changes: {e}"))
+    }
+
+    pub fn add_spec_revision(&self, session_id: &str, revision: &SpecRevision) -> Result<()> {
+        self.db
+            .add_spec_revision(session_id, revision)
+            .map_err(|e| anyhow!("Failed to store spec revision: {e}"))
+    }
+
+    pub fn list_spec_revisions(&self, session_id: &str) -> Result<Vec<SpecRevision>> {
+        self.db
+            .list_spec_revisions(session_id)
+            .map_err(|e| anyhow!("Failed to list spec revisions: {e}"))
+    }
+
+    pub fn enqueue_follow_up(&self, session_id: &str, follow_up: &PendingFollowUp) -> Result<()> {
+        self.db
+            .enqueue_follow_up(session_id, follow_up)
+            .map_err(|e| anyhow!("Failed to queue follow-up message: {e}"))
+    }
+
+    pub fn list_pending_follow_ups(&self) -> Result<Vec<PendingFollowUp>> {
+        self.db
+            .list_pending_follow_ups(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to list pending follow-up messages: {e}"))
+    }
+
+    pub fn add_agent_crash(&self, session_id: &str, crash: &AgentCrash) -> Result<()> {
+        self.db
+            .add_agent_crash(session_id, crash)
+            .map_err(|e| anyhow!("Failed to record agent crash: {e}"))
+    }
+
+    pub fn list_agent_crashes(&self, session_id: &str) -> Result<Vec<AgentCrash>> {
+        self.db
+            .list_agent_crashes(session_id)
+            .map_err(|e| anyhow!("Failed to list agent crashes: {e}"))
+    }
+
+    /// Token usage and cost of every session in the repository, by session id.
+    pub fn list_session_usage(&self) -> Result<HashMap<String, AgentUsage>> {
+        self.db
+            .list_session_usage(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to list session usage: {e}"))
+    }
+
+    pub fn set_session_remote_status(
+        &self,
+        session_id: &str,
+        status: &SessionRemoteStatus,
+    ) -> Result<()> {
+        self.db
+            .set_session_remote_status(session_id, status)
+            .map_err(|e| anyhow!("Failed to set session remote status: {e}"))
+    }
+
+    pub fn list_session_remote_statuses(&self) -> Result<HashMap<String, SessionRemoteStatus>> {
+        self.db
+            .list_session_remote_statuses(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to list session remote statuses: {e}"))
+    }
+
+    pub fn delete_session(&self, session_id: &str) -> Result<()> {
+        self.db
+            .delete_session(session_id)
+            .map_err(|e| anyhow!("Failed to delete session: {e}"))
+    }
+
+    pub fn set_session_amp_thread_id(
+        &self,
+        session_id: &str,
+        thread_id: Option<&str>,
+    ) -> Result<()> {
         self.db
             .set_session_amp_thread_id(session_id, thread_id)
             .map_err(|e| anyhow!("Failed to set amp_thread_id: {e}"))
     }
 
+    /// Resolve Codex profile/overrides for a launch: project settings with an
+    /// optional per-session profile taking precedence.
+    pub fn get_codex_settings(
+        &self,
+        session_id: Option<&str>,
+    ) -> crate::schaltwerk_core::db_project_config::ProjectCodexSettings {
+        let mut settings = self
+            .db
+            .get_project_codex_settings(&self.repo_path)
+            .unwrap_or_else(|e| {
+                warn!("Failed to load project Codex settings: {e}");
+                Default::default()
+            });
+
+        if let Some(id) = session_id {
+            match self.db.get_session_codex_profile(id) {
+                Ok(Some(profile)) => settings.profile = Some(profile),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to load Codex profile for session {id}: {e}"),
+            }
+        }
+
+        settings
+    }
+
     pub fn rename_draft_session(&self, old_name: &str, new_name: &str) -> Result<()> {
         self.db
             .rename_draft_session(&self.repo_path, old_name, new_name)
@@ -280,6 +540,34 @@ impl SessionDbManager {
             .map_err(|e| anyhow!("Failed to get project setup script: {e}"))
     }
 
+    /// Setup step for new sessions: the project's dev environment setup followed by its
+    /// setup script and named setup steps
+    pub fn get_session_setup_script(&self) -> Result<Option<String>> {
+        let environment = self
+            .db
+            .get_project_dev_environment(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to get project dev environment: {e}"))?;
+        let steps = self
+            .db
+            .get_project_setup_scripts(&self.repo_path)
+            .map_err(|e| anyhow!("Failed to get project setup scripts: {e}"))?;
+        Ok(compose_setup_script(
+            environment,
+            compose_project_setup(self.get_project_setup_script()?, &steps.scripts),
+        ))
+    }
+
+    /// How long a session setup may run before it is stopped; `None` for no limit
+    pub fn get_session_setup_timeout(&self) -> Option<Duration> {
+        match self.db.get_project_setup_scripts(&self.repo_path) {
+            Ok(scripts) => scripts.timeout_secs.map(Duration::from_secs),
+            Err(e) => {
+                warn!("Failed to load project setup timeout: {e}");
+                None
+            }
+        }
+    }
+
     pub fn get_agent_type(&self) -> Result<String> {
         self.db
             .get_agent_type()
@@ -304,6 +592,102 @@ impl SessionDbManager {
             .map_err(|e| anyhow!("Failed to get orchestrator skip permissions: {e}"))
     }
 
+    /// Whether the project runs session terminals inside containers
+    pub fn container_sessions_enabled(&self) -> bool {
+        match self.db.get_project_container_config(&self.repo_path) {
+            Ok(config) => config.is_some_and(|config| config.enabled),
+            Err(e) => {
+                warn!("Failed to load project container config: {e}");
+                false
+            }
+        }
+    }
+
+    /// Record whether a session that just got its worktree runs in a container. The type is
+    /// kept for the session's lifetime, so toggling the project setting only affects new sessions.
+    pub fn assign_session_type(&self, session_id: &str) -> Result<()> {
+        let session_type = if self.container_sessions_enabled() {
+            SessionType::Container
+        } else {
+            SessionType::Worktree
+        };
+        self.db
+            .set_session_type(session_id, session_type)
+            .map_err(|e| anyhow!("Failed to set session type: {e}"))
+    }
+
+    /// Names of the sessions whose terminals run in a container
+    pub fn container_session_names(&self) -> HashSet<String> {
+        match self.db.list_container_sessions(&self.repo_path) {
+            Ok(sessions) => sessions.into_iter().map(|(name, _)| name).collect(),
+            Err(e) => {
+                warn!("Failed to list container sessions: {e}");
+                HashSet::new()
+            }
+        }
+    }
+
+    /// Whether commits Schaltwerk creates for this project may be signed
+    pub fn commit_signing(&self) -> git::CommitSigning {
+        match self.db.get_project_merge_preferences(&self.repo_path) {
+            Ok(preferences) => git::CommitSigning::from_preference(preferences.sign_commits),
+            Err(e) => {
+                warn!("Failed to load project merge preferences: {e}");
+                git::CommitSigning::default()
+            }
+        }
+    }
+
+    /// Whether commit hooks run for commits Schaltwerk creates for this project
+    pub fn git_hooks(&self) -> git::GitHooks {
+        match self.db.get_project_merge_preferences(&self.repo_path) {
+            Ok(preferences) => git::GitHooks::from_preference(preferences.run_git_hooks),
+            Err(e) => {
+                warn!("Failed to load project merge preferences: {e}");
+                git::GitHooks::default()
+            }
+        }
+    }
+
+    /// Paths a merge of this project only changes after explicit confirmation
+    pub fn protected_paths(&self) -> Vec<String> {
+        match self.db.get_project_merge_preferences(&self.repo_path) {
+            Ok(preferences) => preferences.protected_paths,
+            Err(e) => {
+                warn!("Failed to load project merge preferences: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Resolve the agent type and skip-permissions default for a task kind: the
+    /// project's agent matrix wins, the global settings fill in anything unset.
+    pub fn resolve_agent_defaults(&self, kind: AgentTaskKind) -> Result<(String, bool)> {
+        let project = self
+            .db
+            .get_project_agent_defaults(&self.repo_path)
+            .unwrap_or_else(|e| {
+                warn!("Failed to load project agent defaults: {e}");
+                Default::default()
+            });
+        let entry = project.for_kind(kind);
+
+        let agent_type = match entry.agent_type.clone() {
+            Some(agent_type) => agent_type,
+            None if kind == AgentTaskKind::Orchestrator => self.get_orchestrator_agent_type()?,
+            None => self.get_agent_type()?,
+        };
+        let skip_permissions = match entry.skip_permissions {
+            Some(skip) => skip,
+            None if kind == AgentTaskKind::Orchestrator => {
+                self.get_orchestrator_skip_permissions()?
+            }
+            None => self.get_skip_permissions()?,
+        };
+
+        Ok((agent_type, skip_permissions))
+    }
+
     pub fn set_skip_permissions(&self, skip: bool) -> Result<()> {
         self.db
             .set_skip_permissions(skip)
@@ -1,9 +1,9 @@
-use crate::domains::agents::AgentLaunchSpec;
+use crate::domains::agents::{handoff, AgentLaunchSpec};
 use crate::shared::terminal_id::{terminal_id_for_session_bottom, terminal_id_for_session_top};
 use anyhow::{anyhow, Result};
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, SubsecRound, TimeZone, Utc};
 use log::{info, warn};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -26,6 +26,7 @@ fn get_or_compute_git_stats(
                 let mut updated = git::calculate_git_stats_fast(worktree_path, parent_branch).ok();
                 if let Some(ref mut s) = updated {
                     s.session_id = session_id.to_string();
+                    s.base_freshness = existing.base_freshness.clone();
                     let _ = save_fn(s);
                 }
                 updated.or_else(|| Some(existing.clone()))
@@ -54,30 +55,203 @@ pub struct SessionCreationParams<'a> {
     pub version_number: Option<i32>,
     pub agent_type: Option<&'a str>,
     pub skip_permissions: Option<bool>,
+    /// Model for the agent, e.g. `opus` or `o3`; the agent's own default when None
+    pub agent_model: Option<&'a str>,
 }
 
 const SESSION_READY_COMMIT_MESSAGE: &str = "Complete development work for {}";
+
+fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+const MAX_LABEL_LENGTH: usize = 40;
+
+/// Trim labels and drop empty and case-insensitive duplicates, keeping the first spelling.
+fn normalize_labels(labels: impl IntoIterator<Item = String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.trim();
+        if label.is_empty() {
+            continue;
+        }
+        if label.chars().count() > MAX_LABEL_LENGTH {
+            return Err(anyhow!(
+                "Label '{label}' is longer than {MAX_LABEL_LENGTH} characters"
+            ));
+        }
+        if !normalized
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(label))
+        {
+            normalized.push(label.to_string());
+        }
+    }
+    Ok(normalized)
+}
+
+fn build_time_report(
+    sessions: &[Session],
+    entries: &[SessionTimeEntry],
+    now: DateTime<Utc>,
+) -> SessionTimeReport {
+    let names: HashMap<&str, &str> = sessions
+        .iter()
+        .map(|s| (s.id.as_str(), s.name.as_str()))
+        .collect();
+    let mut active: HashMap<&str, u64> = HashMap::new();
+    let mut days: BTreeMap<&str, DailyTimeTotals> = BTreeMap::new();
+    for entry in entries {
+        let Some(name) = names.get(entry.session_id.as_str()) else {
+            continue;
+        };
+        *active.entry(entry.session_id.as_str()).or_default() += entry.active_seconds;
+        let day = days
+            .entry(entry.day.as_str())
+            .or_insert_with(|| DailyTimeTotals {
+                day: entry.day.clone(),
+                active_seconds: 0,
+                sessions: BTreeMap::new(),
+            });
+        day.active_seconds += entry.active_seconds;
+        *day.sessions.entry(name.to_string()).or_default() += entry.active_seconds;
+    }
+
+    let sessions = sessions
+        .iter()
+        .filter(|s| s.session_state != SessionState::Spec)
+        .map(|s| {
+            // Reviewed and cancelled sessions stopped the clock at their last update
+            let open =
+                s.session_state == SessionState::Running && s.status != SessionStatus::Cancelled;
+            let end = if open { now } else { s.updated_at };
+            SessionTimeTotals {
+                session_name: s.name.clone(),
+                active_seconds: active.get(s.id.as_str()).copied().unwrap_or(0),
+                wall_clock_seconds: (end - s.created_at).num_seconds().max(0) as u64,
+            }
+        })
+        .collect();
+
+    SessionTimeReport {
+        sessions,
+        days: days.into_values().collect(),
+    }
+}
+
+/// Review comments as a prompt for the session's agent, grouped by file in the order
+/// they are listed. Same layout as the prompt the diff viewer sends on "Finish Review".
+fn format_review_prompt(comments: &[ReviewComment]) -> String {
+    let mut prompt = String::from("\n# Code Review Comments\n\n");
+    let mut current_file: Option<&str> = None;
+    for comment in comments {
+        if current_file != Some(comment.file_path.as_str()) {
+            prompt.push_str(&format!("## {}\n\n", comment.file_path));
+            current_file = Some(comment.file_path.as_str());
+        }
+        let side = match comment.side {
+            DiffSide::Old => "base",
+            DiffSide::New => "current",
+        };
+        prompt.push_str(&format!(
+            "### Lines {}-{} ({side}):\n",
+            comment.line_start, comment.line_end
+        ));
+        if !comment.selected_text.is_empty() {
+            prompt.push_str(&format!("```\n{}\n```\n", comment.selected_text));
+        }
+        prompt.push_str(&format!("**Comment:** {}\n\n", comment.comment));
+    }
+    prompt
+}
+
+fn current_revision_number(session: &Session, latest: Option<&SpecRevision>) -> u32 {
+    let content = session.spec_content.as_deref().unwrap_or_default();
+    match latest {
+        Some(revision) if revision.content == content => revision.revision,
+        Some(revision) => revision.revision + 1,
+        None => 1,
+    }
+}
+
+/// Apply search-and-replace edits in order; each `find` must occur exactly once in the
+/// content as changed by the edits before it.
+fn apply_spec_edits(
+    content: &str,
+    edits: &[SpecEdit],
+) -> std::result::Result<String, SpecRevisionError> {
+    if edits.is_empty() {
+        return Err(SpecRevisionError::InvalidEdit(
+            "No edits were proposed".to_string(),
+        ));
+    }
+    let mut result = content.to_string();
+    for (index, edit) in edits.iter().enumerate() {
+        let number = index + 1;
+        if edit.find.is_empty() {
+            return Err(SpecRevisionError::InvalidEdit(format!(
+                "Edit {number} has no text to find"
+            )));
+        }
+        match result.matches(edit.find.as_str()).count() {
+            1 => result = result.replacen(edit.find.as_str(), &edit.replace, 1),
+            0 => {
+                return Err(SpecRevisionError::InvalidEdit(format!(
+                    "Edit {number}: the text to find does not occur in the spec"
+                )))
+            }
+            count => {
+                return Err(SpecRevisionError::InvalidEdit(format!(
+                    "Edit {number}: the text to find occurs {count} times; include more context"
+                )))
+            }
+        }
+    }
+    Ok(result)
+}
+
 use crate::{
     domains::git::service as git,
     domains::sessions::cache::{
         cache_worktree_size, clear_session_prompted_non_test, get_cached_worktree_size,
         SessionCacheManager,
     },
+    domains::sessions::cancellation::CancellationToken,
     domains::sessions::db_sessions::SessionMethods as _,
     domains::sessions::entity::ArchivedSpec,
     domains::sessions::entity::{
-        DiffStats, EnrichedSession, FilterMode, GitStats, Session, SessionInfo, SessionState,
-        SessionStatus, SessionStatusType, SessionType, SortMode,
+        AgentCrash, BranchPrefixMigration, DailyTimeTotals, DiffSide, DiffStats, EnrichedSession,
+        FilterMode, GitStats, NewReviewComment, PendingFollowUp, PromptTemplate, PrunedPath, ReviewChecklistItem,
+        ReviewComment, Session, SessionCostReport, SessionCostTotals, SessionInfo, SessionNotes, SessionRemoteStatus, SessionState, SessionStatus,
+        SessionStatusType, SessionTimeEntry, SessionTimeReport, SessionTimeTotals, SessionType,
+        SetupStatus, SortMode, SpecChange, SpecEdit, SpecRevision, SpecRevisionError,
+        SpecRevisionSource, StaleSession, WorktreeDiskUsage, WorktreePruneReport,
     },
+    domains::sessions::lifecycle::{agent_restart_config, plan_restart, RestartPlan},
     domains::sessions::process_cleanup::terminate_processes_with_cwd,
+    domains::sessions::prompt_templates::{render_template, template_variables, validate_template},
     domains::sessions::repository::SessionDbManager,
-    domains::sessions::storage::compute_worktree_size_bytes,
+    domains::sessions::storage::{compute_worktree_size_bytes, find_build_artifact_dirs},
     domains::sessions::utils::SessionUtils,
+    domains::settings::validation::validate_settings_overrides,
+    domains::settings::{SettingsLayerValues, SettingsOverrides},
     infrastructure::database::db_archived_specs::ArchivedSpecMethods as _,
+    infrastructure::database::db_prompt_templates::PromptTemplateMethods as _,
     schaltwerk_core::database::Database,
+    schaltwerk_core::db_project_config::{
+        normalize_branch_prefix, AgentTaskKind, ProjectConfigMethods as _,
+    },
 };
 use uuid::Uuid;
 
+fn session_creation_cancelled(name: &str) -> anyhow::Error {
+    anyhow!("Session creation cancelled: '{name}'")
+}
+
 #[cfg(test)]
 mod service_unified_tests {
     use super::*;
@@ -98,6 +272,24 @@ mod service_unified_tests {
         (manager, temp_dir)
     }
 
+    fn run_git(dir: &Path, args: &[&str]) {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
+
+    /// Turn `repo_root` into a git repository with a committed README.
+    fn init_test_repo(repo_root: &Path) {
+        run_git(repo_root, &["init"]);
+        run_git(repo_root, &["config", "user.email", "test@example.com"]);
+        run_git(repo_root, &["config", "user.name", "Test User"]);
+        std::fs::write(repo_root.join("README.md"), "Initial").unwrap();
+        run_git(repo_root, &["add", "."]);
+        run_git(repo_root, &["commit", "-m", "init"]);
+    }
+
     fn create_test_session(temp_dir: &TempDir, agent_type: &str, session_suffix: &str) -> Session {
         let repo_path = temp_dir.path().join("repo");
         let session_name = format!("test-session-{}-{}", agent_type, session_suffix);
@@ -129,6 +321,8 @@ mod service_unified_tests {
             session_state: SessionState::Running,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         }
     }
 
@@ -149,6 +343,53 @@ mod service_unified_tests {
         assert_eq!(enriched[0].info.worktree_size_bytes, Some(2048));
     }
 
+    #[test]
+    fn project_agent_defaults_take_precedence_over_globals() {
+        use crate::schaltwerk_core::db_app_config::AppConfigMethods;
+        use crate::schaltwerk_core::db_project_config::{
+            ProjectAgentDefaults, ProjectConfigMethods, TaskAgentDefault,
+        };
+
+        let (manager, temp_dir) = create_test_session_manager();
+        let db = manager.db_manager.db_ref();
+        db.set_agent_type("claude").unwrap();
+        db.set_orchestrator_agent_type("claude").unwrap();
+        db.set_project_agent_defaults(
+            &temp_dir.path().join("repo"),
+            &ProjectAgentDefaults {
+                spec: TaskAgentDefault {
+                    agent_type: Some("codex".to_string()),
+                    skip_permissions: Some(true),
+                },
+                follow_up: TaskAgentDefault {
+                    agent_type: Some("gemini".to_string()),
+                    skip_permissions: None,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let (spec_agent, spec_skip) = manager
+            .db_manager
+            .resolve_agent_defaults(AgentTaskKind::Spec)
+            .unwrap();
+        assert_eq!(spec_agent, "codex");
+        assert!(spec_skip);
+
+        let (orchestrator_agent, _) = manager
+            .db_manager
+            .resolve_agent_defaults(AgentTaskKind::Orchestrator)
+            .unwrap();
+        assert_eq!(orchestrator_agent, "claude");
+
+        // Sessions without stored settings use the follow-up defaults
+        assert_eq!(
+            manager.default_agent_settings().unwrap(),
+            ("gemini".to_string(), false)
+        );
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_resume_gating_after_spec_then_first_start_is_fresh() {
@@ -924,6 +1165,7 @@ mod service_unified_tests {
             version_number: None,
             agent_type: Some("claude"),
             skip_permissions: Some(true),
+            agent_model: None,
         };
 
         let session = manager
@@ -950,181 +1192,1215 @@ mod service_unified_tests {
     }
 
     #[test]
-    fn non_claude_session_does_not_copy_local_overrides() {
+    fn cancelled_session_creation_leaves_no_state() {
         let (manager, temp_dir) = create_test_session_manager();
         let repo_root = temp_dir.path().join("repo");
 
-        std::process::Command::new("git")
-            .args(["init"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["config", "user.email", "test@example.com"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["config", "user.name", "Test User"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::fs::write(repo_root.join("README.md"), "Initial").unwrap();
-        std::process::Command::new("git")
-            .args(["add", "."])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["commit", "-m", "init"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-
-        std::fs::write(repo_root.join("CLAUDE.local.md"), "should-not-copy").unwrap();
-        let claude_dir = repo_root.join(".claude");
-        std::fs::create_dir_all(&claude_dir).unwrap();
-        std::fs::write(claude_dir.join("settings.local.json"), "{\"copy\":false}").unwrap();
+        init_test_repo(&repo_root);
 
-        let params = SessionCreationParams {
-            name: "opencode-session",
+        let params = |name: &'static str| SessionCreationParams {
+            name,
             prompt: None,
             base_branch: None,
             custom_branch: None,
             was_auto_generated: false,
             version_group_id: None,
             version_number: None,
-            agent_type: Some("opencode"),
-            skip_permissions: Some(false),
+            agent_type: None,
+            skip_permissions: None,
+            agent_model: None,
         };
 
-        let session = manager
-            .create_session_with_agent(params)
-            .expect("session creation should succeed");
+        let cancel = CancellationToken::new();
+        assert!(cancel.cancel());
+        let error = manager
+            .create_session_cancellable(params("cancelled"), &cancel)
+            .unwrap_err();
+        assert!(error.to_string().contains("cancelled"));
+        assert!(manager.get_session("cancelled").is_err());
+        assert!(!repo_root.join(".schaltwerk/worktrees/cancelled").exists());
 
-        let worktree = &session.worktree_path;
-        assert!(
-            !worktree.join("CLAUDE.local.md").exists(),
-            "non-Claude sessions should not copy CLAUDE.local.md"
-        );
-        assert!(
-            !worktree.join(".claude").exists(),
-            "non-Claude sessions should not copy .claude overrides"
+        let session = manager
+            .create_session_with_agent(params("rolled-back"))
+            .unwrap();
+        manager.rollback_session_creation(
+            &session.name,
+            &session.branch,
+            &session.worktree_path,
+            Some(&session.id),
         );
+        assert!(manager.get_session("rolled-back").is_err());
+        assert!(!session.worktree_path.exists());
+        assert!(!git::branch_exists(&repo_root, &session.branch).unwrap());
+
+        // The name is free again after the rollback
+        let recreated = manager
+            .create_session_with_agent(params("rolled-back"))
+            .unwrap();
+        assert_eq!(recreated.name, "rolled-back");
+
+        // A cancel that arrives after creation finished is reported as too late
+        let finished = CancellationToken::new();
+        manager
+            .create_session_cancellable(params("finished"), &finished)
+            .unwrap();
+        assert!(!finished.cancel());
+        assert!(manager.get_session("finished").is_ok());
     }
 
     #[test]
-    fn session_creation_persists_selected_agent_settings() {
+    fn session_environment_round_trips_and_rejects_bad_names() {
         let (manager, temp_dir) = create_test_session_manager();
-        let repo_root = temp_dir.path().join("repo");
+        let session = create_test_session(&temp_dir, "claude", "env");
+        manager.db_manager.create_session(&session).unwrap();
+        let name = session.name.as_str();
+        assert!(manager.get_session_environment(name).unwrap().is_empty());
+
+        let env = HashMap::from([
+            (
+                "DATABASE_URL".to_string(),
+                "postgres://localhost/env".to_string(),
+            ),
+            ("_PORT2".to_string(), "4001".to_string()),
+        ]);
+        manager.set_session_environment(name, env.clone()).unwrap();
+        assert_eq!(manager.get_session_environment(name).unwrap(), env);
+        assert_eq!(
+            manager
+                .db_manager
+                .db
+                .get_session_environment_variables_by_worktree(&session.worktree_path)
+                .unwrap(),
+            env
+        );
 
-        std::process::Command::new("git")
-            .args(["init"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["config", "user.email", "test@example.com"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["config", "user.name", "Test User"])
-            .current_dir(&repo_root)
-            .output()
-            .unwrap();
-        std::fs::write(repo_root.join("README.md"), "Initial").unwrap();
-        std::process::Command::new("git")
-            .args(["add", "."])
-            .current_dir(&repo_root)
-            .output()
+        for bad in ["1ABC", "WITH SPACE", "A=B", ""] {
+            let invalid = HashMap::from([(bad.to_string(), "x".to_string())]);
+            assert!(manager.set_session_environment(name, invalid).is_err());
+        }
+        assert_eq!(manager.get_session_environment(name).unwrap(), env);
+    }
+
+    #[test]
+    fn session_labels_are_normalized_and_filterable() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let tagged = create_test_session(&temp_dir, "claude", "tagged");
+        let other = create_test_session(&temp_dir, "claude", "other");
+        manager.db_manager.create_session(&tagged).unwrap();
+        manager.db_manager.create_session(&other).unwrap();
+        let (tagged, other) = (tagged.name.as_str(), other.name.as_str());
+
+        let labels = manager
+            .set_session_labels(
+                tagged,
+                vec![" Auth ".to_string(), "auth".to_string(), "".to_string()],
+            )
             .unwrap();
-        std::process::Command::new("git")
-            .args(["commit", "-m", "init"])
-            .current_dir(&repo_root)
-            .output()
+        assert_eq!(labels, vec!["Auth".to_string()]);
+        assert_eq!(
+            manager.add_session_label(tagged, "billing").unwrap(),
+            vec!["Auth".to_string(), "billing".to_string()]
+        );
+        manager.add_session_label(other, "API").unwrap();
+        assert_eq!(
+            manager.list_session_labels().unwrap(),
+            vec!["API".to_string(), "Auth".to_string(), "billing".to_string()]
+        );
+
+        let filtered = manager
+            .list_enriched_sessions_sorted(SortMode::Name, FilterMode::Label("AUTH".into()))
             .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].info.session_id, tagged);
 
-        let params = SessionCreationParams {
-            name: "compare-gemini",
-            prompt: None,
-            base_branch: None,
-            custom_branch: None,
-            was_auto_generated: false,
-            version_group_id: None,
-            version_number: None,
-            agent_type: Some("gemini"),
-            skip_permissions: Some(true),
-        };
+        assert_eq!(
+            manager.remove_session_label(tagged, "auth").unwrap(),
+            vec!["billing".to_string()]
+        );
+        assert!(manager.add_session_label(other, &"x".repeat(41)).is_err());
+    }
 
-        let session = manager
-            .create_session_with_agent(params)
-            .expect("session creation should succeed");
+    #[test]
+    fn spec_waits_for_dependencies_to_merge() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let dependency = create_test_session(&temp_dir, "claude", "api");
+        manager.db_manager.create_session(&dependency).unwrap();
+        let mut spec = create_test_session(&temp_dir, "claude", "ui");
+        spec.status = SessionStatus::Spec;
+        spec.session_state = SessionState::Spec;
+        manager.db_manager.create_session(&spec).unwrap();
+        let spec_name = spec.name.as_str();
 
+        manager
+            .set_session_dependencies(spec_name, vec![dependency.name.clone()])
+            .unwrap();
         assert_eq!(
-            session.original_agent_type.as_deref(),
-            Some("gemini"),
-            "returned session should reflect override agent type"
+            manager.blocking_dependencies(spec_name).unwrap(),
+            vec![dependency.name.clone()]
         );
+        let err = manager
+            .start_spec_session(spec_name, None, None, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("blocked"), "{err}");
+
+        assert!(manager
+            .set_session_dependencies(&dependency.name, vec![spec_name.to_string()])
+            .is_err());
+        assert!(manager
+            .set_session_dependencies(spec_name, vec![spec_name.to_string()])
+            .is_err());
+
         assert_eq!(
-            session.original_skip_permissions,
-            Some(true),
-            "returned session should reflect requested skip permissions"
+            manager.record_dependency_merged(&dependency.name).unwrap(),
+            vec![spec_name.to_string()]
         );
+        assert!(manager.blocking_dependencies(spec_name).unwrap().is_empty());
 
-        let persisted = manager
-            .db_manager
-            .get_session_by_name(&session.name)
-            .expect("session should be persisted");
+        let enriched = manager.list_enriched_sessions().unwrap();
+        let enriched_spec = enriched
+            .iter()
+            .find(|s| s.info.session_id == spec_name)
+            .unwrap();
+        assert_eq!(enriched_spec.info.depends_on, vec![dependency.name.clone()]);
+        assert!(enriched_spec.info.blocked_by.is_empty());
+    }
+
+    #[test]
+    fn duplicate_session_forks_branch_head_and_agent_settings() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let source = manager
+            .create_session_with_agent(SessionCreationParams {
+                name: "original",
+                prompt: Some("try approach A"),
+                base_branch: None,
+                custom_branch: None,
+                was_auto_generated: false,
+                version_group_id: None,
+                version_number: None,
+                agent_type: Some("codex"),
+                skip_permissions: Some(true),
+                agent_model: None,
+            })
+            .unwrap();
+        std::fs::write(source.worktree_path.join("approach.txt"), "A").unwrap();
+        run_git(&source.worktree_path, &["add", "."]);
+        run_git(&source.worktree_path, &["commit", "-m", "approach A"]);
+        std::fs::write(source.worktree_path.join("scratch.txt"), "wip").unwrap();
+
+        let copy = manager.duplicate_session("original", "variation").unwrap();
+
+        assert_ne!(copy.branch, source.branch);
+        assert_eq!(copy.parent_branch, source.parent_branch);
+        assert!(copy.worktree_path.join("approach.txt").exists());
+        assert!(!copy.worktree_path.join("scratch.txt").exists());
+        let stored = manager.db_manager.get_session_by_name(&copy.name).unwrap();
+        assert_eq!(stored.parent_branch, source.parent_branch);
+        assert_eq!(stored.initial_prompt.as_deref(), Some("try approach A"));
+        assert_eq!(stored.original_agent_type.as_deref(), Some("codex"));
+        assert_eq!(stored.original_skip_permissions, Some(true));
+    }
 
+    #[test]
+    fn rename_session_moves_branch_and_worktree() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let session = manager.create_session("draft-name", None, None).unwrap();
+        std::fs::write(session.worktree_path.join("wip.txt"), "unsaved").unwrap();
+
+        let renamed = manager.rename_session("draft-name", "final-name").unwrap();
+
+        assert_eq!(renamed.id, session.id);
+        assert_eq!(renamed.name, "final-name");
+        assert_eq!(renamed.branch, "schaltwerk/final-name");
         assert_eq!(
-            persisted.original_agent_type.as_deref(),
-            Some("gemini"),
-            "persisted session should keep override agent type"
+            renamed.worktree_path,
+            session.worktree_path.with_file_name("final-name")
         );
+        assert!(!session.worktree_path.exists());
         assert_eq!(
-            persisted.original_skip_permissions,
-            Some(true),
-            "persisted session should keep override skip permissions"
+            std::fs::read_to_string(renamed.worktree_path.join("wip.txt")).unwrap(),
+            "unsaved"
         );
+        let worktree = git2::Repository::open(&renamed.worktree_path).unwrap();
+        assert_eq!(
+            worktree.head().unwrap().shorthand(),
+            Some("schaltwerk/final-name")
+        );
+        assert!(worktree
+            .find_branch(&session.branch, git2::BranchType::Local)
+            .is_err());
+        assert!(manager
+            .db_manager
+            .get_session_by_name("draft-name")
+            .is_err());
     }
 
     #[test]
-    fn spec_sessions_reset_running_state_on_fetch() {
+    fn container_session_type_outlives_project_setting() {
+        use crate::schaltwerk_core::db_project_config::{ContainerRuntime, ProjectContainerConfig};
+
         let (manager, temp_dir) = create_test_session_manager();
-        let session = create_test_session(&temp_dir, "claude", "normalize");
-        manager.db_manager.create_session(&session).unwrap();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
 
+        let mut config = ProjectContainerConfig {
+            enabled: true,
+            runtime: ContainerRuntime::Docker,
+            image: "node:22".to_string(),
+            run_args: Vec::new(),
+        };
         manager
             .db_manager
-            .update_session_status(&session.id, SessionStatus::Spec)
+            .db
+            .set_project_container_config(&repo_root, &config)
             .unwrap();
+        manager.create_session("boxed", None, None).unwrap();
 
-        let fetched = manager
+        config.enabled = false;
+        manager
             .db_manager
-            .get_session_by_name(&session.name)
+            .db
+            .set_project_container_config(&repo_root, &config)
             .unwrap();
+        manager.create_session("local", None, None).unwrap();
 
-        assert_eq!(SessionStatus::Spec, fetched.status);
         assert_eq!(
-            SessionState::Spec,
-            fetched.session_state,
-            "Spec sessions must not remain in running state"
-        );
-
-        let running_sessions = manager
-            .db_manager
-            .list_sessions_by_state(SessionState::Running)
-            .unwrap();
-        assert!(
-            !running_sessions.iter().any(|s| s.id == session.id),
-            "Spec session should not be returned when listing running sessions after normalization"
+            manager.db_manager.container_session_names(),
+            HashSet::from(["boxed".to_string()])
         );
     }
 
     #[test]
-    fn test_get_or_compute_git_stats_returns_cached_when_fresh() {
-        let temp_dir = TempDir::new().unwrap();
+    fn set_branch_prefix_moves_session_branches() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let session = manager.create_session("login-fix", None, None).unwrap();
+        let taken = manager.create_session("taken", None, None).unwrap();
+        run_git(&repo_root, &["branch", "feat/agent/taken"]);
+
+        assert!(manager.set_branch_prefix("feat agent").is_err());
+
+        let migration = manager.set_branch_prefix("/feat/agent/").unwrap();
+
+        assert_eq!(migration.prefix, "feat/agent");
+        assert_eq!(migration.renamed, vec!["login-fix".to_string()]);
+        assert_eq!(migration.skipped, vec!["taken".to_string()]);
+        let moved = manager.db_manager.get_session_by_name("login-fix").unwrap();
+        assert_eq!(moved.branch, "feat/agent/login-fix");
+        let worktree = git2::Repository::open(&session.worktree_path).unwrap();
+        assert_eq!(
+            worktree.head().unwrap().shorthand(),
+            Some("feat/agent/login-fix")
+        );
+        let kept = manager.db_manager.get_session_by_name("taken").unwrap();
+        assert_eq!(kept.branch, taken.branch);
+
+        let fresh = manager.create_session("next", None, None).unwrap();
+        assert_eq!(fresh.branch, "feat/agent/next");
+    }
+
+    #[test]
+    fn orphaned_worktrees_are_found_under_the_configured_root() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+        let custom_root = temp_dir.path().join("trees");
+        manager
+            .db_manager
+            .db
+            .set_project_worktree_root(&repo_root, Some(custom_root.to_str().unwrap()))
+            .unwrap();
+
+        let session = manager.create_session("active", None, None).unwrap();
+        assert!(session.worktree_path.starts_with(&custom_root));
+        let stray = custom_root.join("stray");
+        run_git(
+            &repo_root,
+            &["worktree", "add", "-b", "stray", stray.to_str().unwrap()],
+        );
+        // Worktrees outside the root are not Schaltwerk's to remove
+        let foreign = temp_dir.path().join("foreign");
+        run_git(
+            &repo_root,
+            &["worktree", "add", "-b", "foreign", foreign.to_str().unwrap()],
+        );
+
+        let orphans: Vec<PathBuf> = manager
+            .utils
+            .find_orphaned_worktrees()
+            .unwrap()
+            .iter()
+            .map(|p| p.canonicalize().unwrap())
+            .collect();
+        assert_eq!(orphans, vec![stray.canonicalize().unwrap()]);
+    }
+
+    #[test]
+    fn prune_worktrees_reports_then_removes_orphans_and_cancelled_artifacts() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+        // A tracked `target/` is source, not build output
+        std::fs::write(repo_root.join(".gitignore"), "node_modules/\n").unwrap();
+        std::fs::create_dir_all(repo_root.join("target")).unwrap();
+        std::fs::write(repo_root.join("target").join("fixture.txt"), "tracked").unwrap();
+        run_git(&repo_root, &["add", "."]);
+        run_git(&repo_root, &["commit", "-m", "ignore node_modules"]);
+
+        let active = manager.create_session("active", None, None).unwrap();
+        let cancelled = manager.create_session("cancelled", None, None).unwrap();
+        for session in [&active, &cancelled] {
+            let modules = session.worktree_path.join("node_modules").join("dep");
+            std::fs::create_dir_all(&modules).unwrap();
+            std::fs::write(modules.join("index.js"), vec![0u8; 4096]).unwrap();
+        }
+        manager
+            .db_manager
+            .update_session_status(&cancelled.id, SessionStatus::Cancelled)
+            .unwrap();
+        let stray = repo_root
+            .join(".schaltwerk")
+            .join("worktrees")
+            .join("stray");
+        run_git(
+            &repo_root,
+            &["worktree", "add", "-b", "stray", stray.to_str().unwrap()],
+        );
+
+        let usage = manager.worktree_disk_usage().unwrap();
+        let cancelled_usage = usage
+            .iter()
+            .find(|u| u.session_name == "cancelled")
+            .unwrap();
+        assert!(cancelled_usage.cancelled);
+        assert_eq!(cancelled_usage.artifact_bytes, 4096);
+
+        let preview = manager.prune_worktrees(true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.orphaned_worktrees.len(), 1);
+        assert_eq!(preview.build_artifacts.len(), 1);
+        assert_eq!(
+            preview.build_artifacts[0].session_name.as_deref(),
+            Some("cancelled")
+        );
+        assert!(stray.exists());
+        assert!(cancelled.worktree_path.join("node_modules").exists());
+
+        let pruned = manager.prune_worktrees(false).unwrap();
+        assert_eq!(pruned.reclaimed_bytes, preview.reclaimed_bytes);
+        assert!(!stray.exists());
+        assert!(!cancelled.worktree_path.join("node_modules").exists());
+        assert!(cancelled.worktree_path.join("README.md").exists());
+        assert!(cancelled
+            .worktree_path
+            .join("target")
+            .join("fixture.txt")
+            .exists());
+        assert!(active.worktree_path.join("node_modules").exists());
+    }
+
+    #[test]
+    fn stale_sessions_skip_recent_activity_and_uncommitted_work() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let now = Utc::now();
+        let abandoned = manager.create_session("abandoned", None, None).unwrap();
+        let dirty = manager.create_session("dirty", None, None).unwrap();
+        let fresh = manager.create_session("fresh", None, None).unwrap();
+        std::fs::write(dirty.worktree_path.join("wip.txt"), "unsaved").unwrap();
+        for (session, days_ago) in [(&abandoned, 30), (&dirty, 30), (&fresh, 2)] {
+            manager
+                .db_manager
+                .set_session_activity(&session.id, now - chrono::Duration::days(days_ago))
+                .unwrap();
+        }
+
+        let stale = manager.find_stale_sessions(14, now).unwrap();
+
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].session_name, "abandoned");
+        assert_eq!(stale[0].idle_days, 30);
+    }
+
+    #[test]
+    fn stale_sessions_flag_reviewed_and_committed_work() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let now = Utc::now();
+        let abandoned = manager.create_session("abandoned", None, None).unwrap();
+        let committed = manager.create_session("committed", None, None).unwrap();
+        let reviewed = manager.create_session("reviewed", None, None).unwrap();
+        std::fs::write(committed.worktree_path.join("feature.txt"), "done").unwrap();
+        run_git(&committed.worktree_path, &["add", "."]);
+        run_git(&committed.worktree_path, &["commit", "-m", "feature work"]);
+        manager
+            .db_manager
+            .update_session_ready_to_merge(&reviewed.id, true)
+            .unwrap();
+        for (session, days_ago) in [(&abandoned, 30), (&committed, 29), (&reviewed, 28)] {
+            manager
+                .db_manager
+                .set_session_activity(&session.id, now - chrono::Duration::days(days_ago))
+                .unwrap();
+        }
+
+        let stale = manager.find_stale_sessions(14, now).unwrap();
+
+        let flags: Vec<_> = stale
+            .iter()
+            .map(|session| (session.session_name.as_str(), session.has_unmerged_work))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![("abandoned", false), ("committed", true), ("reviewed", true)]
+        );
+    }
+
+    #[test]
+    fn session_notes_round_trip_without_blank_checklist_items() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let session = create_test_session(&temp_dir, "claude", "review");
+        manager.db_manager.create_session(&session).unwrap();
+
+        assert_eq!(
+            manager.get_session_notes(&session.name).unwrap(),
+            SessionNotes::default()
+        );
+
+        let saved = manager
+            .set_session_notes(
+                &session.name,
+                "Error paths lack tests".to_string(),
+                vec![
+                    ReviewChecklistItem {
+                        text: " Tests pass ".to_string(),
+                        checked: true,
+                    },
+                    ReviewChecklistItem {
+                        text: "  ".to_string(),
+                        checked: false,
+                    },
+                    ReviewChecklistItem {
+                        text: "Docs updated".to_string(),
+                        checked: false,
+                    },
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(saved.checklist.len(), 2);
+        assert_eq!(saved.checklist[0].text, "Tests pass");
+        assert_eq!(manager.get_session_notes(&session.name).unwrap(), saved);
+    }
+
+    #[test]
+    fn agent_crashes_are_recorded_without_restart_by_default() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let session = create_test_session(&temp_dir, "claude", "flaky-agent");
+        manager.db_manager.create_session(&session).unwrap();
+
+        for exit_code in [Some(1), Some(137)] {
+            let plan = manager
+                .record_agent_crash(&session.name, "claude", exit_code)
+                .unwrap();
+            assert_eq!(plan, None);
+        }
+
+        let crashes = manager.list_agent_crashes(&session.name).unwrap();
+        let recorded: Vec<_> = crashes
+            .iter()
+            .map(|crash| (crash.exit_code, crash.restart_attempt))
+            .collect();
+        assert_eq!(recorded, vec![(Some(1), None), (Some(137), None)]);
+        assert_eq!(crashes[0].agent_type, "claude");
+    }
+
+    #[test]
+    fn spec_revisions_apply_edits_and_refuse_stale_proposals() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let mut spec = create_test_session(&temp_dir, "claude", "login-spec");
+        spec.status = SessionStatus::Spec;
+        spec.session_state = SessionState::Spec;
+        spec.spec_content = Some("# Login\n\nUse passwords.\n".to_string());
+        manager.db_manager.create_session(&spec).unwrap();
+        assert_eq!(manager.current_spec_revision(&spec.name).unwrap(), 1);
+
+        let revision = manager
+            .revise_spec_content(
+                &spec.name,
+                Some(1),
+                SpecChange::Edits(vec![SpecEdit {
+                    find: "passwords".to_string(),
+                    replace: "passkeys".to_string(),
+                }]),
+                Some(" Switch to passkeys ".to_string()),
+            )
+            .unwrap();
+        assert_eq!(revision.revision, 2);
+        assert_eq!(revision.summary.as_deref(), Some("Switch to passkeys"));
+        let revised = manager.get_session(&spec.name).unwrap();
+        assert_eq!(
+            revised.spec_content.as_deref(),
+            Some("# Login\n\nUse passkeys.\n")
+        );
+
+        manager
+            .update_spec_content(&spec.name, "# Login\n\nUse passkeys or OTP.\n")
+            .unwrap();
+        let stale = manager
+            .revise_spec_content(
+                &spec.name,
+                Some(2),
+                SpecChange::Content("# Login".to_string()),
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            stale.downcast_ref::<SpecRevisionError>(),
+            Some(&SpecRevisionError::Conflict {
+                base_revision: 2,
+                current_revision: 3,
+            })
+        );
+
+        let ambiguous = manager
+            .revise_spec_content(
+                &spec.name,
+                Some(3),
+                SpecChange::Edits(vec![SpecEdit {
+                    find: "s".to_string(),
+                    replace: "S".to_string(),
+                }]),
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            ambiguous.downcast_ref::<SpecRevisionError>(),
+            Some(SpecRevisionError::InvalidEdit(_))
+        ));
+
+        let history: Vec<_> = manager
+            .list_spec_revisions(&spec.name)
+            .unwrap()
+            .into_iter()
+            .map(|revision| (revision.revision, revision.source))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                (1, SpecRevisionSource::Editor),
+                (2, SpecRevisionSource::Api),
+                (3, SpecRevisionSource::Editor),
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_ups_stay_queued_in_order_until_removed() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let session = create_test_session(&temp_dir, "claude", "restarting");
+        manager.db_manager.create_session(&session).unwrap();
+
+        let first = manager
+            .queue_follow_up(&session.name, "Run the tests", 1_000)
+            .unwrap();
+        let second = manager
+            .queue_follow_up(&session.name, "Then fix lint", 2_000)
+            .unwrap();
+        assert!(manager.queue_follow_up("missing", "hello", 3_000).is_err());
+
+        let db = &manager.db_manager.db;
+        db.record_follow_up_attempt(&first.id, Utc::now().timestamp())
+            .unwrap();
+        let pending = manager.list_pending_follow_ups().unwrap();
+        assert_eq!(
+            pending
+                .iter()
+                .map(|follow_up| (follow_up.message.as_str(), follow_up.attempts))
+                .collect::<Vec<_>>(),
+            vec![("Run the tests", 1), ("Then fix lint", 0)]
+        );
+        assert_eq!(pending[1], second);
+
+        db.remove_follow_up(&first.id).unwrap();
+        assert_eq!(manager.list_pending_follow_ups().unwrap(), vec![second]);
+
+        db.delete_session(&session.id).unwrap();
+        assert!(manager.list_pending_follow_ups().unwrap().is_empty());
+    }
+
+    #[test]
+    fn review_comments_are_listed_by_line_until_resolved() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let session = create_test_session(&temp_dir, "claude", "annotated");
+        manager.db_manager.create_session(&session).unwrap();
+
+        let comment = |file_path: &str, line_start: u32, text: &str| NewReviewComment {
+            file_path: file_path.to_string(),
+            line_start,
+            line_end: line_start + 1,
+            side: DiffSide::New,
+            selected_text: String::new(),
+            comment: text.to_string(),
+        };
+        let later = manager
+            .add_review_comment(&session.name, comment("src/lib.rs", 40, "Handle the error"))
+            .unwrap();
+        let earlier = manager
+            .add_review_comment(&session.name, comment("src/lib.rs", 3, " Rename this "))
+            .unwrap();
+        assert_eq!(earlier.comment, "Rename this");
+        assert!(manager
+            .add_review_comment(&session.name, comment("src/lib.rs", 0, "No line"))
+            .is_err());
+        assert!(manager
+            .add_review_comment(&session.name, comment("src/lib.rs", 5, "  "))
+            .is_err());
+
+        let listed = manager.list_review_comments(&session.name, false).unwrap();
+        assert_eq!(listed, vec![earlier.clone(), later.clone()]);
+
+        manager
+            .resolve_review_comment(&session.name, &earlier.id)
+            .unwrap();
+        assert!(manager
+            .resolve_review_comment(&session.name, &earlier.id)
+            .is_err());
+        assert_eq!(
+            manager.list_review_comments(&session.name, false).unwrap(),
+            vec![later]
+        );
+        let all = manager.list_review_comments(&session.name, true).unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all[0].resolved_at.is_some());
+    }
+
+    #[test]
+    fn review_changes_request_resolves_sent_comments_and_reopens_the_session() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let mut session = create_test_session(&temp_dir, "claude", "reviewed");
+        session.ready_to_merge = true;
+        session.session_state = SessionState::Reviewed;
+        manager.db_manager.create_session(&session).unwrap();
+
+        assert!(manager.review_changes_prompt(&session.name).is_err());
+
+        let add = |file_path: &str, line_start: u32, side: DiffSide, selected: &str| {
+            manager
+                .add_review_comment(
+                    &session.name,
+                    NewReviewComment {
+                        file_path: file_path.to_string(),
+                        line_start,
+                        line_end: line_start + 2,
+                        side,
+                        selected_text: selected.to_string(),
+                        comment: format!("Fix line {line_start}"),
+                    },
+                )
+                .unwrap()
+        };
+        add("src/main.rs", 7, DiffSide::Old, "");
+        add("src/lib.rs", 12, DiffSide::New, "let x = 1;");
+        add("src/lib.rs", 3, DiffSide::New, "");
+
+        let (prompt, comment_ids) = manager.review_changes_prompt(&session.name).unwrap();
+        assert_eq!(comment_ids.len(), 3);
+        assert_eq!(
+            prompt,
+            "\n# Code Review Comments\n\n\
+             ## src/lib.rs\n\n\
+             ### Lines 3-5 (current):\n**Comment:** Fix line 3\n\n\
+             ### Lines 12-14 (current):\n```\nlet x = 1;\n```\n**Comment:** Fix line 12\n\n\
+             ## src/main.rs\n\n\
+             ### Lines 7-9 (base):\n**Comment:** Fix line 7\n\n"
+        );
+
+        // A comment added while the prompt was being delivered stays open
+        add("src/lib.rs", 20, DiffSide::New, "");
+        assert!(manager
+            .complete_review_changes_request(&session.name, &comment_ids)
+            .unwrap());
+
+        let reopened = manager.get_session(&session.name).unwrap();
+        assert!(!reopened.ready_to_merge);
+        assert_eq!(reopened.session_state, SessionState::Running);
+        let open = manager.list_review_comments(&session.name, false).unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].line_start, 20);
+        assert!(!manager
+            .complete_review_changes_request(&session.name, &[open[0].id.clone()])
+            .unwrap());
+    }
+
+    #[test]
+    fn recorded_push_shows_up_on_the_enriched_session() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let session = manager.create_session("pushed", None, None).unwrap();
+        let url = "https://github.com/owner/repo/pull/1".to_string();
+        manager
+            .record_session_push("pushed", "origin", Some(url.clone()))
+            .unwrap();
+        let status = manager
+            .record_session_push("pushed", "origin", None)
+            .unwrap();
+
+        assert_eq!(status.pull_request_url.as_deref(), Some(url.as_str()));
+        let head = git2::Repository::open(&repo_root)
+            .unwrap()
+            .revparse_single(&session.branch)
+            .unwrap()
+            .id();
+        assert_eq!(status.pushed_commit, head.to_string());
+
+        let enriched = manager.list_enriched_sessions().unwrap();
+        let info = &enriched
+            .iter()
+            .find(|s| s.info.session_id == "pushed")
+            .unwrap()
+            .info;
+        assert_eq!(info.remote_status.as_ref(), Some(&status));
+    }
+
+    #[test]
+    fn agent_handoff_switches_agent_and_carries_over_context() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let session = manager
+            .create_session("stuck", Some("Add a login page"), None)
+            .unwrap();
+        manager
+            .db_manager
+            .set_session_original_settings(&session.id, "gemini", false)
+            .unwrap();
+        std::fs::write(session.worktree_path.join("login.html"), "<form>").unwrap();
+
+        let err = manager
+            .prepare_agent_handoff("stuck", "gemini")
+            .unwrap_err();
+        assert!(err.to_string().contains("already runs gemini"));
+
+        let prompt = manager.prepare_agent_handoff("stuck", "claude").unwrap();
+        assert!(prompt.contains("another coding agent (gemini)"));
+        assert!(prompt.contains("Add a login page"));
+        assert!(prompt.contains("login.html"));
+
+        // Nothing changes until the new agent started
+        let spec = manager
+            .start_agent_handoff_with_binary("stuck", "claude", &prompt, &HashMap::new(), None)
+            .unwrap();
+        assert!(spec.shell_command.contains("login.html"));
+        let stored = manager.db_manager.get_session_by_name("stuck").unwrap();
+        assert_eq!(stored.original_agent_type.as_deref(), Some("gemini"));
+
+        manager
+            .commit_agent_handoff("stuck", "claude", &prompt)
+            .unwrap();
+        let stored = manager.db_manager.get_session_by_name("stuck").unwrap();
+        assert_eq!(stored.original_agent_type.as_deref(), Some("claude"));
+        assert_eq!(stored.original_skip_permissions, Some(false));
+        assert_eq!(stored.initial_prompt.as_deref(), Some("Add a login page"));
+        assert_eq!(stored.agent_model, None);
+        assert_eq!(
+            manager
+                .db_manager
+                .get_session_handoff_prompt(&stored.id)
+                .unwrap()
+                .as_deref(),
+            Some(prompt.as_str())
+        );
+
+        // A second handoff still carries the original task
+        let prompt = manager.prepare_agent_handoff("stuck", "gemini").unwrap();
+        assert!(prompt.contains("Add a login page"));
+    }
+
+    #[test]
+    fn relocate_moves_worktree_into_configured_root() {
+        use crate::domains::git::service::is_worktree_registered;
+        use crate::schaltwerk_core::db_project_config::ProjectConfigMethods;
+
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+        init_test_repo(&repo_root);
+
+        let session = manager.create_session("movable", None, None).unwrap();
+        assert!(session
+            .worktree_path
+            .starts_with(repo_root.join(".schaltwerk").join("worktrees")));
+        std::fs::write(session.worktree_path.join("wip.txt"), "unsaved").unwrap();
+
+        let external_root = temp_dir.path().join("external");
+        manager
+            .db_manager
+            .db
+            .set_project_worktree_root(&repo_root, Some(&external_root.to_string_lossy()))
+            .unwrap();
+
+        let moved = manager.relocate_session_worktree("movable").unwrap();
+
+        assert_eq!(moved, external_root.join("movable"));
+        assert!(!session.worktree_path.exists());
+        assert_eq!(
+            std::fs::read_to_string(moved.join("wip.txt")).unwrap(),
+            "unsaved"
+        );
+        let stored = manager.db_manager.get_session_by_name("movable").unwrap();
+        assert_eq!(stored.worktree_path, moved);
+        assert!(is_worktree_registered(&repo_root, &moved).unwrap());
+    }
+
+    #[test]
+    fn cost_report_sums_agent_runs_per_session_and_project() {
+        use crate::domains::agents::usage::AgentUsage;
+
+        let (manager, temp_dir) = create_test_session_manager();
+        let cheap = create_test_session(&temp_dir, "codex", "cheap");
+        manager.db_manager.create_session(&cheap).unwrap();
+        let pricey = create_test_session(&temp_dir, "claude", "pricey");
+        manager.db_manager.create_session(&pricey).unwrap();
+        let unused = create_test_session(&temp_dir, "claude", "unused");
+        manager.db_manager.create_session(&unused).unwrap();
+
+        let usage = |tokens: u64, cost_usd: f64| AgentUsage {
+            input_tokens: tokens,
+            output_tokens: tokens,
+            cost_usd,
+            ..Default::default()
+        };
+        let db = &manager.db_manager.db;
+        db.set_agent_run_usage(&pricey.id, "run-1", &usage(10, 0.10))
+            .unwrap();
+        // A later summary of the same run replaces the earlier one
+        db.set_agent_run_usage(&pricey.id, "run-1", &usage(100, 0.50))
+            .unwrap();
+        db.set_agent_run_usage(&pricey.id, "run-2", &usage(50, 0.25))
+            .unwrap();
+        db.set_agent_run_usage(&cheap.id, "run-3", &usage(500, 0.0))
+            .unwrap();
+
+        let report = manager.get_session_cost_report().unwrap();
+        let names: Vec<&str> = report
+            .sessions
+            .iter()
+            .map(|s| s.session_name.as_str())
+            .collect();
+        assert_eq!(names, vec![pricey.name.as_str(), cheap.name.as_str()]);
+        assert_eq!(report.sessions[0].usage.input_tokens, 150);
+        assert_eq!(report.sessions[0].usage.cost_usd, 0.75);
+        assert_eq!(report.total.output_tokens, 650);
+        assert_eq!(report.total.cost_usd, 0.75);
+    }
+
+    #[test]
+    fn prompt_templates_render_session_details_into_its_prompt() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let session = create_test_session(&temp_dir, "claude", "templated");
+        manager.db_manager.create_session(&session).unwrap();
+
+        let review = manager
+            .save_prompt_template(
+                None,
+                " Review ",
+                "Review {{branch}} against {{base_branch}}: {{spec}}",
+                true,
+            )
+            .unwrap();
+        manager
+            .save_prompt_template(None, "Ship it", "Ship {{session}}", false)
+            .unwrap();
+        let names: Vec<String> = manager
+            .list_prompt_templates()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Ship it", "Review"]);
+
+        let err = manager
+            .save_prompt_template(None, "Review", "again", true)
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert!(manager
+            .save_prompt_template(None, "Typo", "{{brnach}}", false)
+            .is_err());
+
+        let rendered = manager
+            .render_prompt_template(&review.id, &session.name)
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "Review schaltwerk/test-session against main: test prompt"
+        );
+
+        manager
+            .set_session_prompt(&session.name, &rendered)
+            .unwrap();
+        let stored = manager
+            .db_manager
+            .get_session_by_name(&session.name)
+            .unwrap();
+        assert_eq!(stored.initial_prompt.as_deref(), Some(rendered.as_str()));
+
+        let updated = manager
+            .save_prompt_template(Some(&review.id), "Review", "{{project}}", true)
+            .unwrap();
+        assert_eq!(updated.created_at, review.created_at);
+        assert_eq!(
+            manager
+                .render_prompt_template(&review.id, &session.name)
+                .unwrap(),
+            "test-repo"
+        );
+
+        manager.delete_prompt_template(&review.id).unwrap();
+        assert_eq!(manager.list_prompt_templates().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn time_report_sums_active_time_per_session_and_day() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let running = create_test_session(&temp_dir, "claude", "busy");
+        manager.db_manager.create_session(&running).unwrap();
+        let mut reviewed = create_test_session(&temp_dir, "codex", "done");
+        reviewed.session_state = SessionState::Reviewed;
+        reviewed.created_at = reviewed.updated_at - chrono::Duration::hours(2);
+        manager.db_manager.create_session(&reviewed).unwrap();
+        let mut spec = create_test_session(&temp_dir, "claude", "idea");
+        spec.status = SessionStatus::Spec;
+        spec.session_state = SessionState::Spec;
+        manager.db_manager.create_session(&spec).unwrap();
+
+        let db = &manager.db_manager.db;
+        db.add_session_active_seconds(&running.id, "2025-01-01", 60)
+            .unwrap();
+        db.add_session_active_seconds(&running.id, "2025-01-01", 30)
+            .unwrap();
+        db.add_session_active_seconds(&running.id, "2025-01-02", 120)
+            .unwrap();
+        db.add_session_active_seconds(&reviewed.id, "2025-01-02", 300)
+            .unwrap();
+
+        let report = manager.get_session_time_report().unwrap();
+
+        assert_eq!(report.sessions.len(), 2);
+        let busy = report
+            .sessions
+            .iter()
+            .find(|s| s.session_name == running.name)
+            .unwrap();
+        assert_eq!(busy.active_seconds, 210);
+        let done = report
+            .sessions
+            .iter()
+            .find(|s| s.session_name == reviewed.name)
+            .unwrap();
+        assert_eq!(done.active_seconds, 300);
+        assert_eq!(done.wall_clock_seconds, 2 * 60 * 60);
+
+        let days: Vec<(&str, u64)> = report
+            .days
+            .iter()
+            .map(|d| (d.day.as_str(), d.active_seconds))
+            .collect();
+        assert_eq!(days, vec![("2025-01-01", 90), ("2025-01-02", 420)]);
+        assert_eq!(report.days[1].sessions.get(&reviewed.name), Some(&300));
+    }
+
+    #[test]
+    fn non_claude_session_does_not_copy_local_overrides() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+
+        std::process::Command::new("git")
+            .args(["init"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["config", "user.name", "Test User"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::fs::write(repo_root.join("README.md"), "Initial").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "."])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["commit", "-m", "init"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+
+        std::fs::write(repo_root.join("CLAUDE.local.md"), "should-not-copy").unwrap();
+        let claude_dir = repo_root.join(".claude");
+        std::fs::create_dir_all(&claude_dir).unwrap();
+        std::fs::write(claude_dir.join("settings.local.json"), "{\"copy\":false}").unwrap();
+
+        let params = SessionCreationParams {
+            name: "opencode-session",
+            prompt: None,
+            base_branch: None,
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: Some("opencode"),
+            skip_permissions: Some(false),
+            agent_model: None,
+        };
+
+        let session = manager
+            .create_session_with_agent(params)
+            .expect("session creation should succeed");
+
+        let worktree = &session.worktree_path;
+        assert!(
+            !worktree.join("CLAUDE.local.md").exists(),
+            "non-Claude sessions should not copy CLAUDE.local.md"
+        );
+        assert!(
+            !worktree.join(".claude").exists(),
+            "non-Claude sessions should not copy .claude overrides"
+        );
+    }
+
+    #[test]
+    fn session_creation_persists_selected_agent_settings() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let repo_root = temp_dir.path().join("repo");
+
+        std::process::Command::new("git")
+            .args(["init"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["config", "user.name", "Test User"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::fs::write(repo_root.join("README.md"), "Initial").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "."])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["commit", "-m", "init"])
+            .current_dir(&repo_root)
+            .output()
+            .unwrap();
+
+        let params = SessionCreationParams {
+            name: "compare-gemini",
+            prompt: None,
+            base_branch: None,
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: Some("gemini"),
+            skip_permissions: Some(true),
+            agent_model: None,
+        };
+
+        let session = manager
+            .create_session_with_agent(params)
+            .expect("session creation should succeed");
+
+        assert_eq!(
+            session.original_agent_type.as_deref(),
+            Some("gemini"),
+            "returned session should reflect override agent type"
+        );
+        assert_eq!(
+            session.original_skip_permissions,
+            Some(true),
+            "returned session should reflect requested skip permissions"
+        );
+
+        let persisted = manager
+            .db_manager
+            .get_session_by_name(&session.name)
+            .expect("session should be persisted");
+
+        assert_eq!(
+            persisted.original_agent_type.as_deref(),
+            Some("gemini"),
+            "persisted session should keep override agent type"
+        );
+        assert_eq!(
+            persisted.original_skip_permissions,
+            Some(true),
+            "persisted session should keep override skip permissions"
+        );
+    }
+
+    #[test]
+    fn spec_sessions_reset_running_state_on_fetch() {
+        let (manager, temp_dir) = create_test_session_manager();
+        let session = create_test_session(&temp_dir, "claude", "normalize");
+        manager.db_manager.create_session(&session).unwrap();
+
+        manager
+            .db_manager
+            .update_session_status(&session.id, SessionStatus::Spec)
+            .unwrap();
+
+        let fetched = manager
+            .db_manager
+            .get_session_by_name(&session.name)
+            .unwrap();
+
+        assert_eq!(SessionStatus::Spec, fetched.status);
+        assert_eq!(
+            SessionState::Spec,
+            fetched.session_state,
+            "Spec sessions must not remain in running state"
+        );
+
+        let running_sessions = manager
+            .db_manager
+            .list_sessions_by_state(SessionState::Running)
+            .unwrap();
+        assert!(
+            !running_sessions.iter().any(|s| s.id == session.id),
+            "Spec session should not be returned when listing running sessions after normalization"
+        );
+    }
+
+    #[test]
+    fn test_get_or_compute_git_stats_returns_cached_when_fresh() {
+        let temp_dir = TempDir::new().unwrap();
         let worktree = temp_dir.path().join("worktree");
         std::fs::create_dir_all(&worktree).unwrap();
 
@@ -1136,6 +2412,7 @@ mod service_unified_tests {
             has_uncommitted: false,
             calculated_at: Utc::now(),
             last_diff_change_ts: None,
+            base_freshness: None,
         };
 
         let mut save_called = false;
@@ -1197,6 +2474,7 @@ mod service_unified_tests {
             has_uncommitted: false,
             calculated_at: Utc::now() - chrono::Duration::seconds(120),
             last_diff_change_ts: None,
+            base_freshness: None,
         };
 
         let mut save_called = false;
@@ -1350,11 +2628,22 @@ impl SessionManager {
             version_number,
             agent_type: None,
             skip_permissions: None,
+            agent_model: None,
         };
         self.create_session_with_agent(params)
     }
 
     pub fn create_session_with_agent(&self, params: SessionCreationParams) -> Result<Session> {
+        self.create_session_cancellable(params, &CancellationToken::new())
+    }
+
+    /// Create a session, checking `cancel` between steps. On cancellation the branch,
+    /// worktree and database row created so far are removed again.
+    pub fn create_session_cancellable(
+        &self,
+        params: SessionCreationParams,
+        cancel: &CancellationToken,
+    ) -> Result<Session> {
         log::info!(
             "Creating session '{}' in repository: {}",
             params.name,
@@ -1386,11 +2675,7 @@ impl SessionManager {
                 custom_branch.to_string()
             };
 
-            let worktree_path = self
-                .repo_path
-                .join(".schaltwerk")
-                .join("worktrees")
-                .join(params.name);
+            let worktree_path = self.utils.worktrees_root().join(params.name);
 
             (params.name.to_string(), final_branch, worktree_path)
         } else {
@@ -1411,11 +2696,10 @@ impl SessionManager {
         let repo_name = self.utils.get_repo_name()?;
         let now = Utc::now();
 
-        let default_agent_type = self
+        let (default_agent_type, default_skip_permissions) = self
             .db_manager
-            .get_agent_type()
-            .unwrap_or_else(|_| "claude".to_string());
-        let global_skip_default = self.db_manager.get_skip_permissions().unwrap_or(false);
+            .resolve_agent_defaults(AgentTaskKind::FollowUp)
+            .unwrap_or_else(|_| ("claude".to_string(), false));
 
         let agent_type_override = params.agent_type.map(|s| s.to_string());
         let skip_permissions_override = params.skip_permissions;
@@ -1423,7 +2707,7 @@ impl SessionManager {
         let effective_agent_type = agent_type_override
             .clone()
             .unwrap_or_else(|| default_agent_type.clone());
-        let effective_skip_permissions = skip_permissions_override.unwrap_or(global_skip_default);
+        let effective_skip_permissions = skip_permissions_override.unwrap_or(default_skip_permissions);
 
         let should_copy_claude_locals = effective_agent_type.eq_ignore_ascii_case("claude");
 
@@ -1452,6 +2736,12 @@ impl SessionManager {
             session_state: SessionState::Running,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: params
+                .agent_model
+                .map(str::trim)
+                .filter(|model| !model.is_empty())
+                .map(str::to_string),
         };
 
         let repo_was_empty = !git::repository_has_commits(&self.repo_path).unwrap_or(true);
@@ -1466,6 +2756,11 @@ impl SessionManager {
             })?;
         }
 
+        if cancel.is_cancelled() {
+            self.cache_manager.unreserve_name(&unique_name);
+            return Err(session_creation_cancelled(&unique_name));
+        }
+
         let create_result = git::create_worktree_from_base(
             &self.repo_path,
             &branch,
@@ -1487,52 +2782,184 @@ impl SessionManager {
             ));
         }
 
-        let git_dir = worktree_path.join(".git");
-        if !git_dir.exists() {
-            self.cache_manager.unreserve_name(&unique_name);
+        let git_dir = worktree_path.join(".git");
+        if !git_dir.exists() {
+            self.cache_manager.unreserve_name(&unique_name);
+            return Err(anyhow!(
+                "Worktree git directory is missing: {}",
+                git_dir.display()
+            ));
+        }
+
+        log::info!("Worktree verified and ready: {}", worktree_path.display());
+
+        if should_copy_claude_locals {
+            if let Err(err) = self.copy_claude_local_files(&worktree_path) {
+                warn!("Failed to copy Claude local overrides for session '{unique_name}': {err}");
+            }
+        }
+
+        if cancel.is_cancelled() {
+            self.rollback_session_creation(&unique_name, &branch, &worktree_path, None);
+            return Err(session_creation_cancelled(&unique_name));
+        }
+
+        // IMPORTANT: Do not execute project setup script here.
+        // We stream the setup script output directly in the session's top terminal
+        // right before the agent starts (see schaltwerk_core_start_claude). This
+        // keeps session creation fast and provides visible progress to the user.
+
+        if let Err(e) = self.db_manager.create_session(&session) {
+            let _ = git::remove_worktree(&self.repo_path, &worktree_path);
+            let _ = git::delete_branch(&self.repo_path, &branch);
+            self.cache_manager.unreserve_name(&unique_name);
+            return Err(anyhow!("Failed to save session to database: {e}"));
+        }
+        let _ = self.db_manager.set_session_original_settings(
+            &session.id,
+            &effective_agent_type,
+            effective_skip_permissions,
+        );
+        if let Err(e) = self.db_manager.assign_session_type(&session.id) {
+            warn!("Failed to record session type for '{unique_name}': {e}");
+        }
+
+        let mut git_stats = git::calculate_git_stats_fast(&worktree_path, &parent_branch)?;
+        git_stats.session_id = session_id.clone();
+        self.db_manager.save_git_stats(&git_stats)?;
+        if let Some(ts) = git_stats.last_diff_change_ts {
+            if let Some(dt) = Utc.timestamp_opt(ts, 0).single() {
+                let _ = self.db_manager.set_session_activity(&session_id, dt);
+            }
+        }
+
+        // Past this point a cancel is refused, so it cannot race with a finished creation
+        if !cancel.complete() {
+            self.rollback_session_creation(
+                &unique_name,
+                &branch,
+                &worktree_path,
+                Some(&session_id),
+            );
+            return Err(session_creation_cancelled(&unique_name));
+        }
+
+        self.cache_manager.unreserve_name(&unique_name);
+        log::info!("Successfully created session '{unique_name}'");
+        Ok(session)
+    }
+
+    /// Fork `source_name` into a new session whose branch starts at the source branch HEAD.
+    /// Prompt, spec and agent configuration are copied; uncommitted work of the source is
+    /// not. The copy merges back into the same parent branch as the source.
+    pub fn duplicate_session(&self, source_name: &str, new_name: &str) -> Result<Session> {
+        let source = self.db_manager.get_session_by_name(source_name)?;
+        if source.session_state == SessionState::Spec {
+            return Err(anyhow!(
+                "Session '{source_name}' is a spec and has no branch to duplicate"
+            ));
+        }
+
+        let params = SessionCreationParams {
+            name: new_name,
+            prompt: source.initial_prompt.as_deref(),
+            base_branch: Some(&source.branch),
+            custom_branch: None,
+            was_auto_generated: false,
+            version_group_id: None,
+            version_number: None,
+            agent_type: source.original_agent_type.as_deref(),
+            skip_permissions: source.original_skip_permissions,
+            agent_model: None,
+        };
+        let mut session = self.create_session_with_agent(params)?;
+
+        self.db_manager
+            .db
+            .update_session_parent_branch(&session.id, &source.parent_branch)?;
+        session.parent_branch = source.parent_branch.clone();
+        if let Some(spec) = source.spec_content.as_deref() {
+            self.db_manager.update_spec_content(&session.id, spec)?;
+            session.spec_content = Some(spec.to_string());
+        }
+        let env_vars = self
+            .db_manager
+            .get_session_environment_variables(&source.id)?;
+        if !env_vars.is_empty() {
+            self.db_manager
+                .set_session_environment_variables(&session.id, &env_vars)?;
+        }
+        if let Some(profile) = self.db_manager.db.get_session_codex_profile(&source.id)? {
+            self.db_manager
+                .db
+                .set_session_codex_profile(&session.id, Some(&profile))?;
+        }
+        // Stats were computed against the source branch while creating the worktree
+        if let Err(e) = self.db_manager.update_git_stats(&session.id) {
+            warn!("Failed to refresh git stats of duplicated session '{new_name}': {e}");
+        }
+
+        log::info!("Duplicated session '{source_name}' as '{}'", session.name);
+        Ok(session)
+    }
+
+    /// Move the worktree of `session_name` into the project's current worktree root, e.g.
+    /// after the root was pointed at another disk. Returns the new worktree path.
+    pub fn relocate_session_worktree(&self, session_name: &str) -> Result<PathBuf> {
+        let session = self.db_manager.get_session_by_name(session_name)?;
+        if session.session_state == SessionState::Spec {
             return Err(anyhow!(
-                "Worktree git directory is missing: {}",
-                git_dir.display()
+                "Session '{session_name}' is a spec and has no worktree to relocate"
             ));
         }
 
-        log::info!("Worktree verified and ready: {}", worktree_path.display());
-
-        if should_copy_claude_locals {
-            if let Err(err) = self.copy_claude_local_files(&worktree_path) {
-                warn!("Failed to copy Claude local overrides for session '{unique_name}': {err}");
-            }
+        let target = self.utils.worktrees_root().join(&session.name);
+        if target == session.worktree_path {
+            return Ok(target);
+        }
+        if !session.worktree_path.exists() {
+            return Err(anyhow!(
+                "Worktree of session '{session_name}' not found at {}",
+                session.worktree_path.display()
+            ));
         }
 
-        // IMPORTANT: Do not execute project setup script here.
-        // We stream the setup script output directly in the session's top terminal
-        // right before the agent starts (see schaltwerk_core_start_claude). This
-        // keeps session creation fast and provides visible progress to the user.
+        git::move_worktree(&self.repo_path, &session.worktree_path, &target)?;
+        self.db_manager
+            .db
+            .update_session_worktree_path(&session.id, &target)?;
 
-        if let Err(e) = self.db_manager.create_session(&session) {
-            let _ = git::remove_worktree(&self.repo_path, &worktree_path);
-            let _ = git::delete_branch(&self.repo_path, &branch);
-            self.cache_manager.unreserve_name(&unique_name);
-            return Err(anyhow!("Failed to save session to database: {e}"));
-        }
-        let _ = self.db_manager.set_session_original_settings(
-            &session.id,
-            &effective_agent_type,
-            effective_skip_permissions,
+        log::info!(
+            "Relocated worktree of session '{session_name}' from {} to {}",
+            session.worktree_path.display(),
+            target.display()
         );
+        Ok(target)
+    }
 
-        let mut git_stats = git::calculate_git_stats_fast(&worktree_path, &parent_branch)?;
-        git_stats.session_id = session_id.clone();
-        self.db_manager.save_git_stats(&git_stats)?;
-        if let Some(ts) = git_stats.last_diff_change_ts {
-            if let Some(dt) = Utc.timestamp_opt(ts, 0).single() {
-                let _ = self.db_manager.set_session_activity(&session_id, dt);
+    /// Remove what a cancelled creation already made: the DB row, the worktree and its branch.
+    fn rollback_session_creation(
+        &self,
+        unique_name: &str,
+        branch: &str,
+        worktree_path: &Path,
+        session_id: Option<&str>,
+    ) {
+        log::info!("Rolling back creation of session '{unique_name}'");
+        if let Some(session_id) = session_id {
+            if let Err(e) = self.db_manager.delete_session(session_id) {
+                warn!("Failed to remove database row of cancelled session '{unique_name}': {e}");
             }
         }
-
-        self.cache_manager.unreserve_name(&unique_name);
-        log::info!("Successfully created session '{unique_name}'");
-        Ok(session)
+        if worktree_path.exists() {
+            if let Err(e) = git::remove_worktree(&self.repo_path, worktree_path) {
+                warn!("Failed to remove worktree of cancelled session '{unique_name}': {e}");
+            }
+        }
+        if let Err(e) = git::delete_branch(&self.repo_path, branch) {
+            warn!("Failed to delete branch '{branch}' of cancelled session '{unique_name}': {e}");
+        }
+        self.cache_manager.unreserve_name(unique_name);
     }
 
     fn copy_claude_local_files(&self, worktree_path: &Path) -> Result<()> {
@@ -1810,46 +3237,549 @@ impl SessionManager {
             log::warn!("Converting session '{name}' to spec with uncommitted changes");
         }
 
-        if session.worktree_path.exists() {
-            if let Err(e) = git::remove_worktree(&self.repo_path, &session.worktree_path) {
-                log::warn!("Failed to remove worktree when converting to spec (will continue anyway): {e}. This may be due to active processes or file locks in the worktree directory.");
-                // Continue with conversion even if worktree removal fails - the important part
-                // is updating the session state in the database. The orphaned directory
-                // can be cleaned up later via cleanup_orphaned_worktrees()
-            }
+        if session.worktree_path.exists() {
+            if let Err(e) = git::remove_worktree(&self.repo_path, &session.worktree_path) {
+                log::warn!("Failed to remove worktree when converting to spec (will continue anyway): {e}. This may be due to active processes or file locks in the worktree directory.");
+                // Continue with conversion even if worktree removal fails - the important part
+                // is updating the session state in the database. The orphaned directory
+                // can be cleaned up later via cleanup_orphaned_worktrees()
+            }
+        }
+
+        if git::branch_exists(&self.repo_path, &session.branch)? {
+            if let Err(e) = git::delete_branch(&self.repo_path, &session.branch) {
+                log::warn!("Failed to delete branch '{}': {}", session.branch, e);
+            }
+        }
+
+        self.db_manager
+            .update_session_status(&session.id, SessionStatus::Spec)?;
+        self.db_manager
+            .update_session_state(&session.id, SessionState::Spec)?;
+        // Gate resume until first start after conversion
+        let _ = self
+            .db_manager
+            .set_session_resume_allowed(&session.id, false);
+
+        // Reset run state fields when converting to spec
+        self.db_manager
+            .update_session_ready_to_merge(&session.id, false)?;
+        self.db_manager.clear_session_run_state(&session.id)?;
+
+        clear_session_prompted_non_test(&session.worktree_path);
+
+        Ok(())
+    }
+
+    pub fn get_session(&self, name: &str) -> Result<Session> {
+        self.db_manager.get_session_by_name(name)
+    }
+
+    pub fn get_session_by_id(&self, id: &str) -> Result<Session> {
+        self.db_manager.get_session_by_id(id)
+    }
+
+    pub fn get_session_setup_status(&self, name: &str) -> Result<Option<SetupStatus>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager.get_session_setup_status(&session.id)
+    }
+
+    pub fn set_session_setup_status(&self, name: &str, status: Option<SetupStatus>) -> Result<()> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager
+            .set_session_setup_status(&session.id, status)
+    }
+
+    /// Extra environment variables for the session's terminals, applied over project variables.
+    pub fn get_session_environment(&self, name: &str) -> Result<HashMap<String, String>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager
+            .get_session_environment_variables(&session.id)
+    }
+
+    pub fn set_session_environment(
+        &self,
+        name: &str,
+        env_vars: HashMap<String, String>,
+    ) -> Result<()> {
+        for (key, value) in &env_vars {
+            if !is_valid_env_var_name(key) {
+                return Err(anyhow!("Invalid environment variable name: '{key}'"));
+            }
+            if value.contains('\0') {
+                return Err(anyhow!("Environment variable '{key}' contains a NUL byte"));
+            }
+        }
+
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager
+            .set_session_environment_variables(&session.id, &env_vars)
+    }
+
+    /// Agent settings the session sets over the project's and the global ones.
+    pub fn get_session_settings_overrides(&self, name: &str) -> Result<SettingsOverrides> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager.get_session_settings_overrides(&session.id)
+    }
+
+    pub fn set_session_settings_overrides(
+        &self,
+        name: &str,
+        overrides: &SettingsOverrides,
+    ) -> Result<()> {
+        validate_settings_overrides(overrides)?;
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager
+            .set_session_settings_overrides(&session.id, overrides)
+    }
+
+    /// The agent type and skip-permissions a session without stored settings runs with:
+    /// the project's defaults, falling back to the global settings.
+    pub fn default_agent_settings(&self) -> Result<(String, bool)> {
+        self.db_manager
+            .resolve_agent_defaults(AgentTaskKind::FollowUp)
+    }
+
+    /// The project's settings layer and, for a session, the session's layer on top of it.
+    pub fn settings_layers(
+        &self,
+        session_name: Option<&str>,
+    ) -> Result<(SettingsLayerValues, Option<SettingsLayerValues>)> {
+        let project = SettingsLayerValues {
+            overrides: self
+                .db_manager
+                .db
+                .get_project_settings_overrides(&self.repo_path)?,
+            env_vars: self
+                .db_manager
+                .db
+                .get_project_environment_variables(&self.repo_path)?,
+        };
+        let session = match session_name {
+            Some(name) => {
+                let session = self.db_manager.get_session_by_name(name)?;
+                Some(SettingsLayerValues {
+                    overrides: self
+                        .db_manager
+                        .get_session_settings_overrides(&session.id)?,
+                    env_vars: self
+                        .db_manager
+                        .get_session_environment_variables(&session.id)?,
+                })
+            }
+            None => None,
+        };
+        Ok((project, session))
+    }
+
+    /// Replace the session's labels; returns them normalized.
+    pub fn set_session_labels(&self, name: &str, labels: Vec<String>) -> Result<Vec<String>> {
+        let labels = normalize_labels(labels)?;
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager.set_session_labels(&session.id, &labels)?;
+        Ok(labels)
+    }
+
+    pub fn add_session_label(&self, name: &str, label: &str) -> Result<Vec<String>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let labels = normalize_labels(
+            session
+                .labels
+                .into_iter()
+                .chain(std::iter::once(label.to_string())),
+        )?;
+        self.db_manager.set_session_labels(&session.id, &labels)?;
+        Ok(labels)
+    }
+
+    pub fn remove_session_label(&self, name: &str, label: &str) -> Result<Vec<String>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let label = label.trim();
+        let labels: Vec<String> = session
+            .labels
+            .into_iter()
+            .filter(|existing| !existing.eq_ignore_ascii_case(label))
+            .collect();
+        self.db_manager.set_session_labels(&session.id, &labels)?;
+        Ok(labels)
+    }
+
+    /// Declare the sessions `name` waits on; they have to merge before it may start.
+    pub fn set_session_dependencies(&self, name: &str, depends_on: Vec<String>) -> Result<()> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let sessions = self.db_manager.list_sessions()?;
+        let mut depends_on_ids: Vec<String> = Vec::new();
+        for dependency in &depends_on {
+            let dependency = dependency.trim();
+            if dependency == session.name {
+                return Err(anyhow!("Session '{name}' cannot depend on itself"));
+            }
+            let Some(target) = sessions.iter().find(|s| s.name == dependency) else {
+                return Err(anyhow!("Unknown dependency session '{dependency}'"));
+            };
+            if !depends_on_ids.contains(&target.id) {
+                depends_on_ids.push(target.id.clone());
+            }
+        }
+
+        let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+        for dependency in self.db_manager.list_session_dependencies()? {
+            if dependency.session_id != session.id {
+                edges
+                    .entry(dependency.session_id)
+                    .or_default()
+                    .push(dependency.depends_on_id);
+            }
+        }
+        let mut pending = depends_on_ids.clone();
+        let mut visited = HashSet::new();
+        while let Some(id) = pending.pop() {
+            if id == session.id {
+                return Err(anyhow!("Dependencies of '{name}' would form a cycle"));
+            }
+            if visited.insert(id.clone()) {
+                pending.extend(edges.get(&id).cloned().unwrap_or_default());
+            }
+        }
+
+        self.db_manager
+            .set_session_dependencies(&session.id, &depends_on_ids)
+    }
+
+    /// Names of the sessions `name` still waits on.
+    pub fn blocking_dependencies(&self, name: &str) -> Result<Vec<String>> {
+        let sessions = self.db_manager.list_sessions()?;
+        let session = sessions
+            .iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| anyhow!("Session '{name}' not found"))?;
+        Ok(self
+            .dependency_overview(&sessions)?
+            .remove(&session.id)
+            .map(|(_, blocked_by)| blocked_by)
+            .unwrap_or_default())
+    }
+
+    /// Record that `name` merged; returns the dependents that no longer wait on anything.
+    pub fn record_dependency_merged(&self, name: &str) -> Result<Vec<String>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let dependents = self.db_manager.mark_dependency_satisfied(&session.id)?;
+        if dependents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sessions = self.db_manager.list_sessions()?;
+        let overview = self.dependency_overview(&sessions)?;
+        Ok(sessions
+            .iter()
+            .filter(|s| dependents.contains(&s.id))
+            .filter(|s| {
+                overview
+                    .get(&s.id)
+                    .is_none_or(|(_, blocked_by)| blocked_by.is_empty())
+            })
+            .map(|s| s.name.clone())
+            .collect())
+    }
+
+    /// Per session id, the names of its dependencies and of those not merged yet.
+    /// Dependencies that were cancelled without merging no longer count.
+    fn dependency_overview(
+        &self,
+        sessions: &[Session],
+    ) -> Result<HashMap<String, (Vec<String>, Vec<String>)>> {
+        let names: HashMap<&str, &str> = sessions
+            .iter()
+            .map(|s| (s.id.as_str(), s.name.as_str()))
+            .collect();
+        let mut overview: HashMap<String, (Vec<String>, Vec<String>)> = HashMap::new();
+        for dependency in self.db_manager.list_session_dependencies()? {
+            let Some(name) = names.get(dependency.depends_on_id.as_str()) else {
+                continue;
+            };
+            let (depends_on, blocked_by) = overview.entry(dependency.session_id).or_default();
+            depends_on.push(name.to_string());
+            if !dependency.satisfied {
+                blocked_by.push(name.to_string());
+            }
+        }
+        Ok(overview)
+    }
+
+    /// Opt a session in or out of automatic follow-up nudges while its agent is idle.
+    pub fn set_session_auto_nudge(&self, name: &str, enabled: bool) -> Result<()> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager
+            .set_session_auto_nudge_disabled(&session.id, !enabled)
+    }
+
+    pub fn is_session_auto_nudge_enabled(&self, name: &str) -> Result<bool> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        Ok(!self
+            .db_manager
+            .list_auto_nudge_opt_outs()?
+            .contains(&session.id))
+    }
+
+    /// Busy agent time and wall-clock duration per session, and busy time per day.
+    pub fn get_session_time_report(&self) -> Result<SessionTimeReport> {
+        let sessions = self.db_manager.list_sessions()?;
+        let entries = self.db_manager.list_session_time_entries()?;
+        Ok(build_time_report(&sessions, &entries, Utc::now()))
+    }
+
+    /// Token usage and cost the agents reported per session, most expensive first, and
+    /// their sum for the project.
+    pub fn get_session_cost_report(&self) -> Result<SessionCostReport> {
+        let usage = self.db_manager.list_session_usage()?;
+        let mut report = SessionCostReport::default();
+        for session in self.db_manager.list_sessions()? {
+            let Some(usage) = usage.get(&session.id) else {
+                continue;
+            };
+            report.total.add(usage);
+            report.sessions.push(SessionCostTotals {
+                session_name: session.name,
+                usage: *usage,
+            });
+        }
+        report.sessions.sort_by(|a, b| {
+            b.usage
+                .cost_usd
+                .total_cmp(&a.usage.cost_usd)
+                .then_with(|| b.usage.total_tokens().cmp(&a.usage.total_tokens()))
+        });
+        Ok(report)
+    }
+
+    /// Sessions without activity for at least `idle_days` and without uncommitted changes,
+    /// longest idle first. Worktrees that cannot be inspected are left out; sessions whose
+    /// commits cannot be compared with the parent branch count as holding unmerged work.
+    pub fn find_stale_sessions(
+        &self,
+        idle_days: u32,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<StaleSession>> {
+        let threshold = chrono::Duration::days(i64::from(idle_days));
+        let mut stale = Vec::new();
+        for session in self.db_manager.list_sessions()? {
+            if session.status != SessionStatus::Active
+                || session.session_state == SessionState::Spec
+            {
+                continue;
+            }
+            let last_activity = session.last_activity.unwrap_or(session.created_at);
+            let idle = now - last_activity;
+            if idle < threshold {
+                continue;
+            }
+            match git::has_uncommitted_changes(&session.worktree_path) {
+                Ok(false) => {}
+                Ok(true) => continue,
+                Err(e) => {
+                    warn!("Skipping stale check of session '{}': {e}", session.name);
+                    continue;
+                }
+            }
+            let has_unmerged_work = session.ready_to_merge
+                || git::base_freshness(&session.worktree_path, &session.parent_branch, None)
+                    .map_or(true, |freshness| freshness.ahead > 0);
+            stale.push(StaleSession {
+                session_name: session.name,
+                last_activity,
+                idle_days: idle.num_days(),
+                has_unmerged_work,
+            });
+        }
+        stale.sort_by_key(|session| session.last_activity);
+        Ok(stale)
+    }
+
+    pub fn get_session_notes(&self, name: &str) -> Result<SessionNotes> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager.get_session_notes(&session.id)
+    }
+
+    /// Replace the session's review notes and checklist; blank checklist items are dropped.
+    pub fn set_session_notes(
+        &self,
+        name: &str,
+        notes: String,
+        checklist: Vec<ReviewChecklistItem>,
+    ) -> Result<SessionNotes> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let checklist = checklist
+            .into_iter()
+            .filter_map(|item| {
+                let text = item.text.trim();
+                (!text.is_empty()).then(|| ReviewChecklistItem {
+                    text: text.to_string(),
+                    checked: item.checked,
+                })
+            })
+            .collect();
+        let notes = SessionNotes {
+            notes,
+            checklist,
+            updated_at: Utc.timestamp_opt(Utc::now().timestamp(), 0).single(),
+        };
+        self.db_manager.set_session_notes(&session.id, &notes)?;
+        Ok(notes)
+    }
+
+    /// Attach a review comment to a line range of the session's diff.
+    pub fn add_review_comment(
+        &self,
+        name: &str,
+        comment: NewReviewComment,
+    ) -> Result<ReviewComment> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let file_path = comment.file_path.trim();
+        let text = comment.comment.trim();
+        if file_path.is_empty() {
+            return Err(anyhow!("Review comment needs a file path"));
+        }
+        if text.is_empty() {
+            return Err(anyhow!("Review comment is empty"));
+        }
+        if comment.line_start == 0 || comment.line_end < comment.line_start {
+            return Err(anyhow!(
+                "Invalid line range {}-{} for review comment",
+                comment.line_start,
+                comment.line_end
+            ));
+        }
+
+        let review_comment = ReviewComment {
+            id: Uuid::new_v4().to_string(),
+            file_path: file_path.to_string(),
+            line_start: comment.line_start,
+            line_end: comment.line_end,
+            side: comment.side,
+            selected_text: comment.selected_text,
+            comment: text.to_string(),
+            // Stored with second precision
+            created_at: Utc::now().trunc_subsecs(0),
+            resolved_at: None,
+        };
+        self.db_manager
+            .add_review_comment(&session.id, &review_comment)?;
+        Ok(review_comment)
+    }
+
+    /// Review comments of the session ordered by file and line; resolved ones only when
+    /// asked for.
+    pub fn list_review_comments(
+        &self,
+        name: &str,
+        include_resolved: bool,
+    ) -> Result<Vec<ReviewComment>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let mut comments = self.db_manager.list_review_comments(&session.id)?;
+        if !include_resolved {
+            comments.retain(|comment| comment.resolved_at.is_none());
+        }
+        Ok(comments)
+    }
+
+    pub fn resolve_review_comment(&self, name: &str, comment_id: &str) -> Result<()> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        if !self
+            .db_manager
+            .resolve_review_comment(&session.id, comment_id)?
+        {
+            return Err(anyhow!(
+                "No unresolved review comment '{comment_id}' on session '{name}'"
+            ));
         }
+        Ok(())
+    }
 
-        if git::branch_exists(&self.repo_path, &session.branch)? {
-            if let Err(e) = git::delete_branch(&self.repo_path, &session.branch) {
-                log::warn!("Failed to delete branch '{}': {}", session.branch, e);
-            }
+    /// The unresolved review comments of a session as one prompt for its agent, with the
+    /// ids of the comments it covers.
+    pub fn review_changes_prompt(&self, name: &str) -> Result<(String, Vec<String>)> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        if session.session_state == SessionState::Spec {
+            return Err(anyhow!(
+                "Session '{name}' is a spec and has no agent to send review comments to"
+            ));
+        }
+        let mut comments = self.db_manager.list_review_comments(&session.id)?;
+        comments.retain(|comment| comment.resolved_at.is_none());
+        if comments.is_empty() {
+            return Err(anyhow!(
+                "Session '{name}' has no unresolved review comments"
+            ));
         }
+        let comment_ids = comments.iter().map(|comment| comment.id.clone()).collect();
+        Ok((format_review_prompt(&comments), comment_ids))
+    }
 
+    /// Resolve the comments that were sent to the agent and move the session from
+    /// reviewed back to running, together. Returns whether the session was reviewed.
+    pub fn complete_review_changes_request(
+        &self,
+        name: &str,
+        comment_ids: &[String],
+    ) -> Result<bool> {
+        let session = self.db_manager.get_session_by_name(name)?;
         self.db_manager
-            .update_session_status(&session.id, SessionStatus::Spec)?;
-        self.db_manager
-            .update_session_state(&session.id, SessionState::Spec)?;
-        // Gate resume until first start after conversion
-        let _ = self
-            .db_manager
-            .set_session_resume_allowed(&session.id, false);
+            .reopen_for_review_changes(&session.id, comment_ids)
+    }
 
-        // Reset run state fields when converting to spec
+    /// Remember that the session branch was pushed to `remote`. Keeps the pull
+    /// request URL of an earlier push unless a new one is given.
+    pub fn record_session_push(
+        &self,
+        name: &str,
+        remote: &str,
+        pull_request_url: Option<String>,
+    ) -> Result<SessionRemoteStatus> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let repo = git2::Repository::open(&session.repository_path)?;
+        let pushed_commit = repo
+            .find_branch(&session.branch, git2::BranchType::Local)?
+            .get()
+            .target()
+            .ok_or_else(|| anyhow!("Branch '{}' has no target", session.branch))?
+            .to_string();
+        let previous_url = self
+            .db_manager
+            .list_session_remote_statuses()?
+            .remove(&session.id)
+            .and_then(|status| status.pull_request_url);
+        let now = Utc::now();
+        let status = SessionRemoteStatus {
+            remote: remote.to_string(),
+            pushed_commit,
+            pushed_at: Utc
+                .timestamp_opt(now.timestamp(), 0)
+                .single()
+                .unwrap_or(now),
+            pull_request_url: pull_request_url.or(previous_url),
+        };
         self.db_manager
-            .update_session_ready_to_merge(&session.id, false)?;
-        self.db_manager.clear_session_run_state(&session.id)?;
-
-        clear_session_prompted_non_test(&session.worktree_path);
+            .set_session_remote_status(&session.id, &status)?;
+        Ok(status)
+    }
 
-        Ok(())
+    /// Every label used by a session of this project, sorted case-insensitively.
+    pub fn list_session_labels(&self) -> Result<Vec<String>> {
+        let mut labels = normalize_labels(
+            self.db_manager
+                .list_sessions()?
+                .into_iter()
+                .flat_map(|session| session.labels),
+        )?;
+        labels.sort_by_key(|label| label.to_lowercase());
+        Ok(labels)
     }
 
-    pub fn get_session(&self, name: &str) -> Result<Session> {
-        self.db_manager.get_session_by_name(name)
+    pub fn session_setup_script(&self) -> Result<Option<String>> {
+        self.db_manager.get_session_setup_script()
     }
 
-    pub fn get_session_by_id(&self, id: &str) -> Result<Session> {
-        self.db_manager.get_session_by_id(id)
+    pub fn session_setup_timeout(&self) -> Option<std::time::Duration> {
+        self.db_manager.get_session_setup_timeout()
     }
 
     pub fn get_session_task_content(&self, name: &str) -> Result<(Option<String>, Option<String>)> {
@@ -1864,10 +3794,114 @@ impl SessionManager {
         self.db_manager.update_git_stats(session_id)
     }
 
+    /// Fetch the session's base branch and rebase the session onto it, then refresh
+    /// its git stats. Conflicts leave the session as it was.
+    pub fn rebase_session_on_base(&self, name: &str) -> Result<git::SessionRebaseOutcome> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        if session.session_state == SessionState::Spec {
+            return Err(anyhow!("Session '{name}' is a spec and has no worktree"));
+        }
+        let outcome = git::rebase_session_on_base(
+            &session.repository_path,
+            &session.worktree_path,
+            &session.parent_branch,
+        )?;
+        if matches!(outcome, git::SessionRebaseOutcome::Rebased { .. }) {
+            if let Err(e) = self.db_manager.update_git_stats(&session.id) {
+                warn!("Failed to refresh git stats after rebasing '{name}': {e}");
+            }
+        }
+        Ok(outcome)
+    }
+
     pub fn cleanup_orphaned_worktrees(&self) -> Result<()> {
         self.utils.cleanup_orphaned_worktrees()
     }
 
+    /// Size of every session worktree still on disk, largest first.
+    pub fn worktree_disk_usage(&self) -> Result<Vec<WorktreeDiskUsage>> {
+        let mut usage: Vec<WorktreeDiskUsage> = self
+            .db_manager
+            .list_sessions()?
+            .into_iter()
+            .filter(|s| s.session_state != SessionState::Spec)
+            .filter_map(|session| {
+                let total_bytes = compute_worktree_size_bytes(&session.worktree_path)?;
+                cache_worktree_size(&session.worktree_path, total_bytes);
+                let artifact_bytes = find_build_artifact_dirs(&session.worktree_path)
+                    .iter()
+                    .filter_map(|dir| compute_worktree_size_bytes(dir))
+                    .sum();
+                Some(WorktreeDiskUsage {
+                    session_name: session.name,
+                    worktree_path: session.worktree_path.to_string_lossy().to_string(),
+                    cancelled: session.status == SessionStatus::Cancelled,
+                    total_bytes,
+                    artifact_bytes,
+                })
+            })
+            .collect();
+        usage.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes));
+        Ok(usage)
+    }
+
+    /// Reclaim disk space from worktrees no session needs: orphaned worktrees and the
+    /// build artifacts left in worktrees of cancelled sessions. With `dry_run` nothing
+    /// is deleted, so the report can be shown for confirmation first.
+    pub fn prune_worktrees(&self, dry_run: bool) -> Result<WorktreePruneReport> {
+        let mut report = WorktreePruneReport {
+            dry_run,
+            ..Default::default()
+        };
+
+        for worktree_path in self.utils.find_orphaned_worktrees()? {
+            let bytes = compute_worktree_size_bytes(&worktree_path).unwrap_or(0);
+            if !dry_run {
+                self.utils.remove_orphaned_worktree(&worktree_path);
+            }
+            report.reclaimed_bytes += bytes;
+            report.orphaned_worktrees.push(PrunedPath {
+                path: worktree_path.to_string_lossy().to_string(),
+                session_name: None,
+                bytes,
+            });
+        }
+
+        let cancelled = self.db_manager.list_sessions()?.into_iter().filter(|s| {
+            s.status == SessionStatus::Cancelled
+                && s.session_state != SessionState::Spec
+                && s.worktree_path.exists()
+        });
+        for session in cancelled {
+            for dir in find_build_artifact_dirs(&session.worktree_path) {
+                let bytes = compute_worktree_size_bytes(&dir).unwrap_or(0);
+                if !dry_run {
+                    if let Err(e) = fs::remove_dir_all(&dir) {
+                        warn!("Failed to remove build artifacts {}: {e}", dir.display());
+                        continue;
+                    }
+                }
+                report.reclaimed_bytes += bytes;
+                report.build_artifacts.push(PrunedPath {
+                    path: dir.to_string_lossy().to_string(),
+                    session_name: Some(session.name.clone()),
+                    bytes,
+                });
+            }
+        }
+
+        if !dry_run {
+            info!(
+                "Pruned {} orphaned worktrees and {} build artifact directories ({} bytes)",
+                report.orphaned_worktrees.len(),
+                report.build_artifacts.len(),
+                report.reclaimed_bytes
+            );
+        }
+        Ok(report)
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, fields(sessions = tracing::field::Empty))]
     pub fn list_enriched_sessions(&self) -> Result<Vec<EnrichedSession>> {
         let start_time = std::time::Instant::now();
         let sessions = self.db_manager.list_sessions()?;
@@ -1878,6 +3912,29 @@ impl SessionManager {
             db_time.as_millis()
         );
 
+        let dependencies = self.dependency_overview(&sessions).unwrap_or_else(|e| {
+            warn!("Failed to load session dependencies: {e}");
+            HashMap::new()
+        });
+        let auto_nudge_opt_outs = self
+            .db_manager
+            .list_auto_nudge_opt_outs()
+            .unwrap_or_else(|e| {
+                warn!("Failed to load auto-nudge opt-outs: {e}");
+                HashSet::new()
+            });
+        let mut remote_statuses = self
+            .db_manager
+            .list_session_remote_statuses()
+            .unwrap_or_else(|e| {
+                warn!("Failed to load session remote statuses: {e}");
+                HashMap::new()
+            });
+        let mut usage = self.db_manager.list_session_usage().unwrap_or_else(|e| {
+            warn!("Failed to load session usage: {e}");
+            HashMap::new()
+        });
+
         let bulk_stats_start = std::time::Instant::now();
         let session_ids: Vec<String> = sessions
             .iter()
@@ -1905,11 +3962,14 @@ impl SessionManager {
             bulk_stats_time.as_millis()
         );
 
+        let container_sessions = self.db_manager.container_session_names();
+
         let mut enriched = Vec::new();
         let mut git_stats_total_time = std::time::Duration::ZERO;
         let mut worktree_check_time = std::time::Duration::ZERO;
         let mut storage_calc_time = std::time::Duration::ZERO;
         let mut session_count = 0;
+        let default_agent_type = self.default_agent_settings().ok().map(|(agent, _)| agent);
 
         for session in sessions {
             if session.status == SessionStatus::Cancelled {
@@ -1918,7 +3978,11 @@ impl SessionManager {
 
             session_count += 1;
             let session_start = std::time::Instant::now();
+            let _session_span =
+                tracing::trace_span!("enrich_session", session = %session.name).entered();
 
+            let (depends_on, blocked_by) =
+                dependencies.get(&session.id).cloned().unwrap_or_default();
             let is_spec_session = session.session_state == SessionState::Spec;
             if is_spec_session {
                 // Specs do not require git stats or worktree checks; return lightweight metadata
@@ -1941,13 +4005,21 @@ impl SessionManager {
                     original_agent_type: session
                         .original_agent_type
                         .clone()
-                        .or_else(|| self.db_manager.get_agent_type().ok()),
+                        .or_else(|| default_agent_type.clone()),
+                    agent_model: session.agent_model.clone(),
                     current_task: session.initial_prompt.clone(),
                     diff_stats: None,
                     ready_to_merge: session.ready_to_merge,
                     spec_content: session.spec_content.clone(),
                     worktree_size_bytes: None,
                     session_state: session.session_state.clone(),
+                    labels: session.labels.clone(),
+                    depends_on,
+                    blocked_by,
+                    auto_nudge_disabled: false,
+                    remote_status: None,
+                    base_freshness: None,
+                    usage: None,
                 };
 
                 enriched.push(EnrichedSession {
@@ -2071,7 +4143,7 @@ impl SessionManager {
             let original_agent_type = session
                 .original_agent_type
                 .clone()
-                .or_else(|| self.db_manager.get_agent_type().ok());
+                .or_else(|| default_agent_type.clone());
 
             let info = SessionInfo {
                 session_id: session.name.clone(),
@@ -2087,15 +4159,27 @@ impl SessionManager {
                 has_uncommitted_changes: Some(has_uncommitted),
                 has_conflicts: Some(has_conflicts),
                 is_current: false,
-                session_type: SessionType::Worktree,
+                session_type: if container_sessions.contains(&session.name) {
+                    SessionType::Container
+                } else {
+                    SessionType::Worktree
+                },
                 container_status: None,
                 original_agent_type,
+                agent_model: session.agent_model.clone(),
                 current_task: session.initial_prompt.clone(),
                 diff_stats: diff_stats.clone(),
                 ready_to_merge: session.ready_to_merge,
                 spec_content: session.spec_content.clone(),
                 worktree_size_bytes,
                 session_state: session.session_state.clone(),
+                labels: session.labels.clone(),
+                depends_on,
+                blocked_by,
+                auto_nudge_disabled: auto_nudge_opt_outs.contains(&session.id),
+                remote_status: remote_statuses.remove(&session.id),
+                base_freshness: git_stats.as_ref().and_then(|s| s.base_freshness.clone()),
+                usage: usage.remove(&session.id),
             };
 
             let terminals = vec![
@@ -2120,14 +4204,21 @@ impl SessionManager {
         }
 
         let total_elapsed = start_time.elapsed();
-        log::info!("list_enriched_sessions: Returning {} enriched sessions (total: {}ms, db: {}ms, git_stats: {}ms, worktree_checks: {}ms, storage: {}ms, avg per session: {}ms)",
-            enriched.len(),
-            total_elapsed.as_millis(),
-            db_time.as_millis(),
-            git_stats_total_time.as_millis(),
-            worktree_check_time.as_millis(),
-            storage_calc_time.as_millis(),
-            if session_count > 0 { total_elapsed.as_millis() / session_count as u128 } else { 0 }
+        tracing::Span::current().record("sessions", enriched.len());
+        crate::infrastructure::crash::note_session_count(enriched.len());
+        tracing::info!(
+            sessions = enriched.len(),
+            total_ms = total_elapsed.as_millis() as u64,
+            db_ms = db_time.as_millis() as u64,
+            git_stats_ms = git_stats_total_time.as_millis() as u64,
+            worktree_checks_ms = worktree_check_time.as_millis() as u64,
+            storage_ms = storage_calc_time.as_millis() as u64,
+            avg_per_session_ms = if session_count > 0 {
+                total_elapsed.as_millis() as u64 / session_count as u64
+            } else {
+                0
+            },
+            "list_enriched_sessions: Returning enriched sessions"
         );
 
         if total_elapsed.as_millis() > 500 {
@@ -2201,18 +4292,58 @@ impl SessionManager {
         session_name: &str,
         force_restart: bool,
         binary_paths: &HashMap<String, String>,
+        amp_mcp_servers: Option<&HashMap<String, crate::domains::settings::McpServerConfig>>,
+    ) -> Result<AgentLaunchSpec> {
+        let mut session = self.db_manager.get_session_by_name(session_name)?;
+        // A session handed to another agent starts fresh from the handoff context
+        if let Some(prompt) = self.db_manager.get_session_handoff_prompt(&session.id)? {
+            session.initial_prompt = Some(prompt);
+        }
+        self.build_agent_launch_spec(
+            session_name,
+            session,
+            force_restart,
+            binary_paths,
+            amp_mcp_servers,
+        )
+    }
+
+    /// Launch spec starting `agent_type` fresh with the handoff `prompt`, built before the
+    /// switch is stored so a failed start leaves the session on its previous agent.
+    pub fn start_agent_handoff_with_binary(
+        &self,
+        session_name: &str,
+        agent_type: &str,
+        prompt: &str,
+        binary_paths: &HashMap<String, String>,
+        amp_mcp_servers: Option<&HashMap<String, crate::domains::settings::McpServerConfig>>,
+    ) -> Result<AgentLaunchSpec> {
+        let mut session = self.db_manager.get_session_by_name(session_name)?;
+        session.original_agent_type = Some(agent_type.to_string());
+        session.agent_model = None;
+        session.initial_prompt = Some(prompt.to_string());
+        self.build_agent_launch_spec(session_name, session, true, binary_paths, amp_mcp_servers)
+    }
+
+    fn build_agent_launch_spec(
+        &self,
+        session_name: &str,
+        session: Session,
+        force_restart: bool,
+        binary_paths: &HashMap<String, String>,
         _amp_mcp_servers: Option<&HashMap<String, crate::domains::settings::McpServerConfig>>,
     ) -> Result<AgentLaunchSpec> {
-        let session = self.db_manager.get_session_by_name(session_name)?;
-        let skip_permissions = session
-            .original_skip_permissions
-            .unwrap_or(self.db_manager.get_skip_permissions()?);
+        let (default_agent_type, default_skip) = self
+            .db_manager
+            .resolve_agent_defaults(AgentTaskKind::FollowUp)?;
+        let skip_permissions = session.original_skip_permissions.unwrap_or(default_skip);
         let agent_type = session
             .original_agent_type
             .clone()
-            .unwrap_or(self.db_manager.get_agent_type()?);
+            .unwrap_or(default_agent_type);
 
-        let registry = crate::domains::agents::unified::AgentRegistry::new();
+        let registry = crate::domains::agents::unified::AgentRegistry::new()
+            .with_model(session.agent_model.clone());
 
         // Special handling for Claude's session resumption logic
         if agent_type == "claude" {
@@ -2401,16 +4532,19 @@ impl SessionManager {
                 "codex",
                 binary_paths.get("codex").map(|s| s.as_str()),
             );
-            if let Some(spec) = registry.build_launch_spec(
-                "codex",
+            let spec = self.build_codex_launch_spec(
                 &session.worktree_path,
+                Some(&session.id),
                 session_id_to_use.as_deref(),
                 prompt_to_use,
                 skip_permissions,
-                Some(&binary_path),
-            ) {
-                return Ok(spec);
-            }
+                binary_path,
+            );
+            let manifest = crate::domains::agents::manifest::AgentManifest::get("codex");
+            return Ok(match manifest {
+                Some(manifest) => spec.with_model(manifest, session.agent_model.as_deref()),
+                None => spec,
+            });
         }
 
         if agent_type == "opencode" {
@@ -2505,33 +4639,226 @@ impl SessionManager {
             }
         }
 
+        if agent_type == "gemini" {
+            // Only resume from a checkpoint that passed integrity checks; otherwise start fresh
+            let resume_id = if !force_restart && session.resume_allowed {
+                crate::domains::agents::gemini::find_gemini_session(&session.worktree_path)
+            } else {
+                None
+            };
+
+            let prompt_to_use = if resume_id.is_some() {
+                log::info!(
+                    "Session manager: Resuming Gemini session '{session_name}' via --resume {resume_id:?}"
+                );
+                None
+            } else {
+                log::info!(
+                    "Session manager: Starting fresh Gemini session '{session_name}' (force_restart={force_restart}, resume_allowed={})",
+                    session.resume_allowed
+                );
+                self.cache_manager
+                    .mark_session_prompted(&session.worktree_path);
+                if !session.resume_allowed {
+                    let _ = self
+                        .db_manager
+                        .set_session_resume_allowed(&session.id, true);
+                }
+                session.initial_prompt.as_deref()
+            };
+
+            let binary_path = self.utils.get_effective_binary_path_with_override(
+                "gemini",
+                binary_paths.get("gemini").map(|s| s.as_str()),
+            );
+            if let Some(spec) = registry.build_launch_spec(
+                "gemini",
+                &session.worktree_path,
+                resume_id.as_deref(),
+                prompt_to_use,
+                skip_permissions,
+                Some(&binary_path),
+            ) {
+                return Ok(spec);
+            }
+        }
+
+        if agent_type == "aider" {
+            // Aider resumes by reloading the worktree's chat log
+            let resume_id = if !force_restart && session.resume_allowed {
+                crate::domains::agents::aider::find_aider_session(&session.worktree_path)
+            } else {
+                None
+            };
+
+            let prompt_to_use = if resume_id.is_some() {
+                log::info!(
+                    "Session manager: Resuming Aider session '{session_name}' from its chat history"
+                );
+                None
+            } else {
+                log::info!(
+                    "Session manager: Starting fresh Aider session '{session_name}' (force_restart={force_restart}, resume_allowed={})",
+                    session.resume_allowed
+                );
+                self.cache_manager
+                    .mark_session_prompted(&session.worktree_path);
+                if !session.resume_allowed {
+                    let _ = self
+                        .db_manager
+                        .set_session_resume_allowed(&session.id, true);
+                }
+                session.initial_prompt.as_deref()
+            };
+
+            let binary_path = self.utils.get_effective_binary_path_with_override(
+                "aider",
+                binary_paths.get("aider").map(|s| s.as_str()),
+            );
+            if let Some(spec) = registry.build_launch_spec(
+                "aider",
+                &session.worktree_path,
+                resume_id.as_deref(),
+                prompt_to_use,
+                skip_permissions,
+                Some(&binary_path),
+            ) {
+                return Ok(spec);
+            }
+        }
+
+        if agent_type == "goose" {
+            let resume_name = if !force_restart && session.resume_allowed {
+                crate::domains::agents::goose::find_goose_session(&session.worktree_path)
+            } else {
+                None
+            };
+
+            let prompt_to_use = if let Some(name) = resume_name.as_deref() {
+                log::info!(
+                    "Session manager: Resuming Goose session '{session_name}' via --resume --name {name}"
+                );
+                None
+            } else {
+                log::info!(
+                    "Session manager: Starting fresh Goose session '{session_name}' (force_restart={force_restart}, resume_allowed={})",
+                    session.resume_allowed
+                );
+                self.cache_manager
+                    .mark_session_prompted(&session.worktree_path);
+                if !session.resume_allowed {
+                    let _ = self
+                        .db_manager
+                        .set_session_resume_allowed(&session.id, true);
+                }
+                session.initial_prompt.as_deref()
+            };
+
+            let binary_path = self.utils.get_effective_binary_path_with_override(
+                "goose",
+                binary_paths.get("goose").map(|s| s.as_str()),
+            );
+            if let Some(spec) = registry.build_launch_spec(
+                "goose",
+                &session.worktree_path,
+                resume_name.as_deref(),
+                prompt_to_use,
+                skip_permissions,
+                Some(&binary_path),
+            ) {
+                return Ok(spec);
+            }
+        }
+
         // Special handling for Amp with MCP servers
         if agent_type == "amp" {
-            self.cache_manager
-                .mark_session_prompted(&session.worktree_path);
-            let prompt_to_use = session.initial_prompt.as_deref();
+            // The thread recorded by the thread watcher is continued unless the agent restarts
+            let resume_thread = if !force_restart && session.resume_allowed {
+                session.amp_thread_id.clone()
+            } else {
+                None
+            };
+            if force_restart && session.amp_thread_id.is_some() {
+                // Forget the old thread so the watcher records the one the restart creates
+                if let Err(e) = self.db_manager.set_session_amp_thread_id(&session.id, None) {
+                    log::warn!("Failed to clear amp_thread_id for session '{session_name}': {e}");
+                }
+            }
+
+            let prompt_to_use = if let Some(thread_id) = resume_thread.as_deref() {
+                log::info!(
+                    "Session manager: Resuming Amp session '{session_name}' in thread {thread_id}"
+                );
+                None
+            } else {
+                self.cache_manager
+                    .mark_session_prompted(&session.worktree_path);
+                if !session.resume_allowed {
+                    let _ = self
+                        .db_manager
+                        .set_session_resume_allowed(&session.id, true);
+                }
+                session.initial_prompt.as_deref()
+            };
 
             let binary_path = self.utils.get_effective_binary_path_with_override(
                 &agent_type,
                 binary_paths.get(&agent_type).map(|s| s.as_str()),
             );
 
-            let config = crate::domains::agents::amp::AmpConfig {
-                binary_path: Some(binary_path.clone()),
+            if let Some(spec) = registry.build_launch_spec(
+                &agent_type,
+                &session.worktree_path,
+                resume_thread.as_deref(),
+                prompt_to_use,
+                skip_permissions,
+                Some(&binary_path),
+            ) {
+                return Ok(spec);
+            }
+        }
+
+        if crate::domains::agents::custom::custom_agent_config(&agent_type).is_some() {
+            // Custom agents resume once their session file exists in the worktree
+            let resume_id = if !force_restart && session.resume_allowed {
+                registry
+                    .get(&agent_type)
+                    .and_then(|adapter| adapter.find_session(&session.worktree_path))
+            } else {
+                None
+            };
+
+            let prompt_to_use = if resume_id.is_some() {
+                log::info!(
+                    "Session manager: Resuming custom agent '{agent_type}' for session '{session_name}'"
+                );
+                None
+            } else {
+                log::info!(
+                    "Session manager: Starting fresh custom agent '{agent_type}' for session '{session_name}' (force_restart={force_restart}, resume_allowed={})",
+                    session.resume_allowed
+                );
+                self.cache_manager
+                    .mark_session_prompted(&session.worktree_path);
+                if !session.resume_allowed {
+                    let _ = self
+                        .db_manager
+                        .set_session_resume_allowed(&session.id, true);
+                }
+                session.initial_prompt.as_deref()
             };
 
-            let command = crate::domains::agents::amp::build_amp_command_with_config(
+            // Without a resolved path the adapter falls back to the binary of the definition
+            if let Some(spec) = registry.build_launch_spec(
+                &agent_type,
                 &session.worktree_path,
-                None,
+                resume_id.as_deref(),
                 prompt_to_use,
                 skip_permissions,
-                Some(&config),
-            );
-
-            return Ok(crate::domains::agents::AgentLaunchSpec::new(
-                command,
-                session.worktree_path.clone(),
-            ));
+                binary_paths.get(&agent_type).map(|s| s.as_str()),
+            ) {
+                return Ok(spec);
+            }
         }
 
         // For all other agents, use the registry directly
@@ -2595,8 +4922,9 @@ impl SessionManager {
             return Err(anyhow!("The folder '{}' is not a git repository. The orchestrator requires a git repository to function.", self.repo_path.display()));
         }
 
-        let skip_permissions = self.db_manager.get_orchestrator_skip_permissions()?;
-        let agent_type = self.db_manager.get_orchestrator_agent_type()?;
+        let (agent_type, skip_permissions) = self
+            .db_manager
+            .resolve_agent_defaults(AgentTaskKind::Orchestrator)?;
 
         log::info!(
             "Fresh orchestrator agent type: {agent_type}, skip_permissions: {skip_permissions}"
@@ -2643,8 +4971,9 @@ impl SessionManager {
             ));
         }
 
-        let skip_permissions = self.db_manager.get_orchestrator_skip_permissions()?;
-        let agent_type = self.db_manager.get_orchestrator_agent_type()?;
+        let (agent_type, skip_permissions) = self
+            .db_manager
+            .resolve_agent_defaults(AgentTaskKind::Orchestrator)?;
 
         log::info!("Orchestrator agent type: {agent_type}, skip_permissions: {skip_permissions}");
 
@@ -2712,6 +5041,17 @@ impl SessionManager {
             None
         };
 
+        if agent_type == "codex" {
+            return Ok(self.build_codex_launch_spec(
+                &self.repo_path,
+                None,
+                session_id.as_deref(),
+                None,
+                skip_permissions,
+                binary_path,
+            ));
+        }
+
         if let Some(spec) = registry.build_launch_spec(
             agent_type,
             &self.repo_path,
@@ -2730,7 +5070,51 @@ impl SessionManager {
         }
     }
 
-    pub fn mark_session_as_reviewed(&self, session_name: &str) -> Result<()> {
+    /// Build the Codex launch spec with project/session profile and `-c` overrides applied.
+    fn build_codex_launch_spec(
+        &self,
+        worktree_path: &Path,
+        schaltwerk_session_id: Option<&str>,
+        codex_session_id: Option<&str>,
+        initial_prompt: Option<&str>,
+        skip_permissions: bool,
+        binary_path: String,
+    ) -> AgentLaunchSpec {
+        let sandbox_mode = if skip_permissions {
+            "danger-full-access"
+        } else {
+            "workspace-write"
+        };
+
+        let settings = self.db_manager.get_codex_settings(schaltwerk_session_id);
+        let config = crate::domains::agents::codex::CodexConfig {
+            binary_path: Some(binary_path),
+            profile: settings.profile,
+            config_overrides: settings
+                .config_overrides
+                .into_iter()
+                .map(|o| (o.key, o.value))
+                .collect(),
+        };
+
+        let command = crate::domains::agents::codex::build_codex_command_with_config(
+            worktree_path,
+            codex_session_id,
+            initial_prompt,
+            sandbox_mode,
+            Some(&config),
+        );
+        AgentLaunchSpec::new(command, worktree_path.to_path_buf())
+    }
+
+    /// Mark a running session as reviewed for API clients. With `auto_commit`, pending
+    /// changes in the worktree are committed first, using `commit_message` when given.
+    pub fn mark_session_as_reviewed(
+        &self,
+        session_name: &str,
+        auto_commit: bool,
+        commit_message: Option<&str>,
+    ) -> Result<()> {
         // Get session and validate state
         let session = self.db_manager.get_session_by_name(session_name)?;
 
@@ -2745,8 +5129,7 @@ impl SessionManager {
             ));
         }
 
-        // Use existing mark_session_ready logic (with auto_commit=false)
-        self.mark_session_ready(session_name, false)?;
+        self.mark_session_ready_with_message(session_name, auto_commit, commit_message)?;
         Ok(())
     }
 
@@ -2793,9 +5176,39 @@ impl SessionManager {
 
         // Start the draft session
         self.start_spec_session(session_name, base_branch, version_group_id, version_number)?;
+
+        // Explicit choices win over the project's spec defaults stored by start_spec_session
+        if agent_type.is_some() || skip_permissions.is_some() {
+            let session = self.db_manager.get_session_by_name(session_name)?;
+            let agent = agent_type
+                .map(|s| s.to_string())
+                .or(session.original_agent_type)
+                .unwrap_or_else(|| "claude".to_string());
+            let skip = skip_permissions
+                .or(session.original_skip_permissions)
+                .unwrap_or(false);
+            let _ = self
+                .db_manager
+                .set_session_original_settings(&session.id, &agent, skip);
+        }
         Ok(())
     }
 
+    /// Whether commits created for this project may be signed
+    pub fn commit_signing(&self) -> git::CommitSigning {
+        self.db_manager.commit_signing()
+    }
+
+    /// Whether commit hooks run for commits created for this project
+    pub fn git_hooks(&self) -> git::GitHooks {
+        self.db_manager.git_hooks()
+    }
+
+    /// Paths a merge only changes after explicit confirmation
+    pub fn protected_paths(&self) -> Vec<String> {
+        self.db_manager.protected_paths()
+    }
+
     pub fn mark_session_ready(&self, session_name: &str, auto_commit: bool) -> Result<bool> {
         self.mark_session_ready_with_message(session_name, auto_commit, None)
     }
@@ -2815,7 +5228,12 @@ impl SessionManager {
                 .map(|m| m.to_string())
                 .unwrap_or_else(|| SESSION_READY_COMMIT_MESSAGE.replace("{}", session_name));
 
-            git::commit_all_changes(&session.worktree_path, &message)?;
+            git::commit_all_changes(
+                &session.worktree_path,
+                &message,
+                self.db_manager.commit_signing(),
+                self.db_manager.git_hooks(),
+            )?;
         }
 
         self.db_manager
@@ -2932,6 +5350,8 @@ impl SessionManager {
             session_state: SessionState::Spec,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         };
 
         self.db_manager.create_session(&session)?;
@@ -3001,6 +5421,8 @@ impl SessionManager {
             session_state: SessionState::Spec,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         };
 
         if let Err(e) = self.db_manager.create_session(&session) {
@@ -3041,14 +5463,18 @@ impl SessionManager {
 
         log::info!("Worktree verified and ready: {}", worktree_path.display());
 
-        if let Ok(Some(setup_script)) = self.db_manager.get_project_setup_script() {
+        if let Ok(Some(setup_script)) = self.db_manager.get_session_setup_script() {
             if !setup_script.trim().is_empty() {
-                self.utils.execute_setup_script(
+                self.utils.execute_setup_script_cached(
                     &setup_script,
                     &unique_name,
                     &branch,
                     &worktree_path,
+                    false,
+                    self.db_manager.get_session_setup_timeout(),
                 )?;
+                self.db_manager
+                    .set_session_setup_status(&session_id, Some(SetupStatus::Succeeded))?;
             }
         }
 
@@ -3056,6 +5482,7 @@ impl SessionManager {
             .update_session_status(&session_id, SessionStatus::Active)?;
         self.db_manager
             .update_session_state(&session_id, SessionState::Running)?;
+        self.db_manager.assign_session_type(&session_id)?;
 
         log::info!(
             "Copying spec content to initial_prompt for session '{unique_name}': '{spec_content}'"
@@ -3067,14 +5494,13 @@ impl SessionManager {
             "Cleared prompt state for session '{unique_name}' to ensure spec content is used"
         );
 
-        let global_agent = self
+        let (spec_agent, spec_skip) = self
             .db_manager
-            .get_agent_type()
-            .unwrap_or_else(|_| "claude".to_string());
-        let global_skip = self.db_manager.get_skip_permissions().unwrap_or(false);
+            .resolve_agent_defaults(AgentTaskKind::Spec)
+            .unwrap_or_else(|_| ("claude".to_string(), false));
         let _ =
             self.db_manager
-                .set_session_original_settings(&session_id, &global_agent, global_skip);
+                .set_session_original_settings(&session_id, &spec_agent, spec_skip);
 
         let mut git_stats = git::calculate_git_stats_fast(&worktree_path, &parent_branch)?;
         git_stats.session_id = session_id.clone();
@@ -3111,13 +5537,12 @@ impl SessionManager {
         // Override original settings if provided, otherwise keep globals already stored
         if agent_type.is_some() || skip_permissions.is_some() {
             let session = self.db_manager.get_session_by_name(name)?;
-            let agent = agent_type.map(|s| s.to_string()).unwrap_or_else(|| {
-                self.db_manager
-                    .get_agent_type()
-                    .unwrap_or_else(|_| "claude".to_string())
-            });
-            let skip = skip_permissions
-                .unwrap_or_else(|| self.db_manager.get_skip_permissions().unwrap_or(false));
+            let (spec_agent, spec_skip) = self
+                .db_manager
+                .resolve_agent_defaults(AgentTaskKind::Spec)
+                .unwrap_or_else(|_| ("claude".to_string(), false));
+            let agent = agent_type.map(|s| s.to_string()).unwrap_or(spec_agent);
+            let skip = skip_permissions.unwrap_or(spec_skip);
             let _ = self
                 .db_manager
                 .set_session_original_settings(&session.id, &agent, skip);
@@ -3142,6 +5567,14 @@ impl SessionManager {
             self.repo_path.display()
         );
 
+        let blocked_by = self.blocking_dependencies(session_name)?;
+        if !blocked_by.is_empty() {
+            return Err(anyhow!(
+                "Session '{session_name}' is blocked until these sessions merge: {}",
+                blocked_by.join(", ")
+            ));
+        }
+
         let repo_lock = self.cache_manager.get_repo_lock();
         let _guard = repo_lock.lock().unwrap();
 
@@ -3244,14 +5677,18 @@ impl SessionManager {
             session.worktree_path.display()
         );
 
-        if let Ok(Some(setup_script)) = self.db_manager.get_project_setup_script() {
+        if let Ok(Some(setup_script)) = self.db_manager.get_session_setup_script() {
             if !setup_script.trim().is_empty() {
-                self.utils.execute_setup_script(
+                self.utils.execute_setup_script_cached(
                     &setup_script,
                     &session.name,
                     &session.branch,
                     &session.worktree_path,
+                    false,
+                    self.db_manager.get_session_setup_timeout(),
                 )?;
+                self.db_manager
+                    .set_session_setup_status(&session.id, Some(SetupStatus::Succeeded))?;
             }
         }
 
@@ -3259,6 +5696,7 @@ impl SessionManager {
             .update_session_status(&session.id, SessionStatus::Active)?;
         self.db_manager
             .update_session_state(&session.id, SessionState::Running)?;
+        self.db_manager.assign_session_type(&session.id)?;
         // Ensure we gate resume on first agent start after spec start
         self.db_manager
             .set_session_resume_allowed(&session.id, false)?;
@@ -3275,14 +5713,13 @@ impl SessionManager {
             log::warn!("No spec_content found for session '{session_name}' - initial_prompt will not be set");
         }
 
-        let global_agent = self
+        let (spec_agent, spec_skip) = self
             .db_manager
-            .get_agent_type()
-            .unwrap_or_else(|_| "claude".to_string());
-        let global_skip = self.db_manager.get_skip_permissions().unwrap_or(false);
+            .resolve_agent_defaults(AgentTaskKind::Spec)
+            .unwrap_or_else(|_| ("claude".to_string(), false));
         let _ =
             self.db_manager
-                .set_session_original_settings(&session.id, &global_agent, global_skip);
+                .set_session_original_settings(&session.id, &spec_agent, spec_skip);
 
         let mut git_stats = git::calculate_git_stats_fast(&session.worktree_path, &parent_branch)?;
         git_stats.session_id = session.id.clone();
@@ -3332,7 +5769,8 @@ impl SessionManager {
                     "Amp thread watcher: Detected thread '{thread_id}' for session '{session_name}'"
                 );
 
-                if let Err(e) = db_manager.set_session_amp_thread_id(&session_id, &thread_id) {
+                if let Err(e) = db_manager.set_session_amp_thread_id(&session_id, Some(&thread_id))
+                {
                     log::error!(
                         "Failed to store amp_thread_id '{thread_id}' for session '{session_name}': {e}"
                     );
@@ -3411,10 +5849,295 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Stored versions of a spec, oldest first.
+    pub fn list_spec_revisions(&self, name: &str) -> Result<Vec<SpecRevision>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager.list_spec_revisions(&session.id)
+    }
+
+    /// Revision number of the spec's current content. Content edited in the app after the
+    /// last stored revision counts as the next revision.
+    pub fn current_spec_revision(&self, name: &str) -> Result<u32> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let revisions = self.db_manager.list_spec_revisions(&session.id)?;
+        Ok(current_revision_number(&session, revisions.last()))
+    }
+
+    /// Store a proposed change as the spec's next revision and make it the spec's content.
+    /// The change is refused when `base_revision` is no longer current. Content edited in
+    /// the app since the last stored revision is recorded first so the history keeps it.
+    pub fn revise_spec_content(
+        &self,
+        name: &str,
+        base_revision: Option<u32>,
+        change: SpecChange,
+        summary: Option<String>,
+    ) -> Result<SpecRevision> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        if session.session_state != SessionState::Spec {
+            return Err(anyhow!(
+                "Cannot revise session '{name}': only specs keep a revision history"
+            ));
+        }
+
+        let current_content = session.spec_content.clone().unwrap_or_default();
+        let revisions = self.db_manager.list_spec_revisions(&session.id)?;
+        let latest = revisions.last();
+        let current_revision = current_revision_number(&session, latest);
+        if latest.is_none_or(|revision| revision.revision != current_revision) {
+            self.db_manager.add_spec_revision(
+                &session.id,
+                &SpecRevision {
+                    revision: current_revision,
+                    content: current_content.clone(),
+                    source: SpecRevisionSource::Editor,
+                    summary: None,
+                    created_at: Utc::now().trunc_subsecs(0),
+                },
+            )?;
+        }
+
+        if let Some(base_revision) = base_revision {
+            if base_revision != current_revision {
+                return Err(SpecRevisionError::Conflict {
+                    base_revision,
+                    current_revision,
+                }
+                .into());
+            }
+        }
+
+        let content = match change {
+            SpecChange::Content(content) => content,
+            SpecChange::Edits(edits) => apply_spec_edits(&current_content, &edits)?,
+        };
+        if content == current_content {
+            return Err(SpecRevisionError::InvalidEdit(
+                "The proposed change leaves the spec unchanged".to_string(),
+            )
+            .into());
+        }
+
+        let revision = SpecRevision {
+            revision: current_revision + 1,
+            content,
+            source: SpecRevisionSource::Api,
+            summary: summary
+                .map(|summary| summary.trim().to_string())
+                .filter(|summary| !summary.is_empty()),
+            created_at: Utc::now().trunc_subsecs(0),
+        };
+        self.db_manager.add_spec_revision(&session.id, &revision)?;
+        self.update_spec_content(name, &revision.content)?;
+        Ok(revision)
+    }
+
+    /// Queue a follow-up message for the session's agent. It stays queued until it was
+    /// pasted into the agent terminal or the session is deleted.
+    pub fn queue_follow_up(
+        &self,
+        name: &str,
+        message: &str,
+        timestamp: u64,
+    ) -> Result<PendingFollowUp> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let follow_up = PendingFollowUp {
+            id: Uuid::new_v4().to_string(),
+            session_name: session.name.clone(),
+            message: message.to_string(),
+            timestamp,
+            attempts: 0,
+            // Stored with second precision
+            created_at: Utc::now().trunc_subsecs(0),
+            last_attempt_at: None,
+        };
+        self.db_manager.enqueue_follow_up(&session.id, &follow_up)?;
+        Ok(follow_up)
+    }
+
+    /// Follow-up messages of the project still waiting for their agent, oldest first.
+    pub fn list_pending_follow_ups(&self) -> Result<Vec<PendingFollowUp>> {
+        self.db_manager.list_pending_follow_ups()
+    }
+
+    /// Record that the session's agent exited with `exit_code` and decide, by the agent
+    /// restart settings, whether it is started again.
+    pub fn record_agent_crash(
+        &self,
+        name: &str,
+        agent_type: &str,
+        exit_code: Option<i32>,
+    ) -> Result<Option<RestartPlan>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        let history = self.db_manager.list_agent_crashes(&session.id)?;
+        // Stored with second precision
+        let now = Utc::now().trunc_subsecs(0);
+        let plan = plan_restart(&agent_restart_config(), &history, exit_code, now);
+        self.db_manager.add_agent_crash(
+            &session.id,
+            &AgentCrash {
+                agent_type: agent_type.to_string(),
+                exit_code,
+                crashed_at: now,
+                restart_attempt: plan.map(|plan| plan.attempt),
+            },
+        )?;
+        Ok(plan)
+    }
+
+    /// Crashes of the session's agent, oldest first.
+    pub fn list_agent_crashes(&self, name: &str) -> Result<Vec<AgentCrash>> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        self.db_manager.list_agent_crashes(&session.id)
+    }
+
     pub fn list_sessions_by_state(&self, state: SessionState) -> Result<Vec<Session>> {
         self.db_manager.list_sessions_by_state(state)
     }
 
+    /// Rename a session together with its branch (when it follows the `<prefix>/<name>`
+    /// convention) and worktree directory. Specs have neither and are renamed in place.
+    /// Terminals keyed by the old name must be closed by the caller beforehand.
+    pub fn rename_session(&self, old_name: &str, new_name: &str) -> Result<Session> {
+        if !git::is_valid_session_name(new_name) {
+            return Err(anyhow!(
+                "Invalid session name: use only letters, numbers, hyphens, and underscores"
+            ));
+        }
+
+        let session = self.db_manager.get_session_by_name(old_name)?;
+        if session.session_state == SessionState::Spec {
+            self.db_manager.rename_draft_session(old_name, new_name)?;
+            return self.db_manager.get_session_by_name(new_name);
+        }
+        if old_name == new_name {
+            return Ok(session);
+        }
+        if !self.utils.check_name_availability(new_name)? {
+            return Err(anyhow!("Session name '{new_name}' is already taken"));
+        }
+
+        let branch_prefix = self.utils.branch_prefix();
+        let new_branch = if session.branch == format!("{branch_prefix}/{old_name}") {
+            format!("{branch_prefix}/{new_name}")
+        } else {
+            session.branch.clone()
+        };
+        let new_worktree_path = session
+            .worktree_path
+            .parent()
+            .map(|parent| parent.join(new_name))
+            .ok_or_else(|| anyhow!("Worktree of session '{old_name}' has no parent directory"))?;
+
+        let restore_branch = || {
+            if new_branch != session.branch {
+                if let Err(e) = git::rename_branch(&self.repo_path, &new_branch, &session.branch) {
+                    warn!("Failed to restore branch '{}': {e}", session.branch);
+                }
+            }
+        };
+
+        if new_branch != session.branch {
+            git::rename_branch(&self.repo_path, &session.branch, &new_branch)?;
+        }
+        if let Err(e) =
+            git::move_worktree(&self.repo_path, &session.worktree_path, &new_worktree_path)
+        {
+            restore_branch();
+            return Err(e);
+        }
+        if let Err(e) = self.db_manager.db.rename_session(
+            &session.id,
+            new_name,
+            &new_branch,
+            &new_worktree_path,
+        ) {
+            if let Err(move_err) =
+                git::move_worktree(&self.repo_path, &new_worktree_path, &session.worktree_path)
+            {
+                warn!("Failed to move worktree of '{old_name}' back: {move_err}");
+            }
+            restore_branch();
+            return Err(e);
+        }
+
+        log::info!("Renamed session '{old_name}' to '{new_name}' (branch {new_branch})");
+        self.db_manager.get_session_by_name(new_name)
+    }
+
+    /// Switch the project's branch prefix and move sessions whose branch follows the old
+    /// `<prefix>/<name>` convention over to the new one. Branches that cannot be renamed
+    /// (e.g. the new name is taken) are left alone and reported as skipped.
+    pub fn set_branch_prefix(&self, prefix: &str) -> Result<BranchPrefixMigration> {
+        let new_prefix = normalize_branch_prefix(prefix);
+        if !git::is_valid_branch_name(&format!("{new_prefix}/session")) {
+            return Err(anyhow!(
+                "Invalid branch prefix '{new_prefix}': use letters, numbers, '-', '_' and '.', with '/' between segments"
+            ));
+        }
+
+        let old_prefix = self.utils.branch_prefix();
+        self.db_manager
+            .db
+            .set_project_branch_prefix(&self.repo_path, &new_prefix)?;
+
+        let mut migration = BranchPrefixMigration {
+            prefix: new_prefix.clone(),
+            ..Default::default()
+        };
+        if old_prefix == new_prefix {
+            return Ok(migration);
+        }
+
+        for session in self.db_manager.list_sessions()? {
+            if session.status == SessionStatus::Cancelled
+                || session.branch != format!("{old_prefix}/{}", session.name)
+            {
+                continue;
+            }
+            let new_branch = format!("{new_prefix}/{}", session.name);
+            // Specs only record the branch they will be started on
+            let has_branch = session.session_state != SessionState::Spec;
+            if has_branch {
+                if let Err(e) = git::rename_branch(&self.repo_path, &session.branch, &new_branch) {
+                    warn!(
+                        "Keeping branch '{}' of session '{}': {e}",
+                        session.branch, session.name
+                    );
+                    migration.skipped.push(session.name);
+                    continue;
+                }
+            }
+            if let Err(e) = self
+                .db_manager
+                .db
+                .update_session_branch(&session.id, &new_branch)
+            {
+                warn!(
+                    "Failed to record branch '{new_branch}' for '{}': {e}",
+                    session.name
+                );
+                if has_branch {
+                    if let Err(e) =
+                        git::rename_branch(&self.repo_path, &new_branch, &session.branch)
+                    {
+                        warn!("Failed to restore branch '{}': {e}", session.branch);
+                    }
+                }
+                migration.skipped.push(session.name);
+                continue;
+            }
+            migration.renamed.push(session.name);
+        }
+
+        info!(
+            "Branch prefix changed from '{old_prefix}' to '{new_prefix}': {} sessions moved, {} kept",
+            migration.renamed.len(),
+            migration.skipped.len()
+        );
+        Ok(migration)
+    }
+
     pub fn rename_draft_session(&self, old_name: &str, new_name: &str) -> Result<()> {
         if !git::is_valid_session_name(new_name) {
             return Err(anyhow!(
@@ -3496,6 +6219,192 @@ impl SessionManager {
         self.db_manager.db.delete_archived_spec(archived_id)
     }
 
+    /// Templates shared by every project, then this project's own.
+    pub fn list_prompt_templates(&self) -> Result<Vec<PromptTemplate>> {
+        self.db_manager.db.list_prompt_templates(&self.repo_path)
+    }
+
+    /// A shared template or one of this project's.
+    fn get_prompt_template(&self, id: &str) -> Result<PromptTemplate> {
+        self.db_manager
+            .db
+            .get_prompt_template(id)?
+            .filter(|template| {
+                template
+                    .repository_path
+                    .as_ref()
+                    .is_none_or(|path| *path == self.repo_path)
+            })
+            .ok_or_else(|| anyhow!("Prompt template '{id}' not found"))
+    }
+
+    /// Create a template, or update the one with `id`. A project template is only offered
+    /// in this project; names are unique among the shared and among a project's templates.
+    pub fn save_prompt_template(
+        &self,
+        id: Option<&str>,
+        name: &str,
+        content: &str,
+        project_only: bool,
+    ) -> Result<PromptTemplate> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Prompt template name must not be empty"));
+        }
+        validate_template(content)?;
+
+        let repository_path = project_only.then(|| self.repo_path.clone());
+        let duplicate = self.list_prompt_templates()?.into_iter().any(|existing| {
+            existing.name == name
+                && existing.repository_path == repository_path
+                && Some(existing.id.as_str()) != id
+        });
+        if duplicate {
+            return Err(anyhow!("A prompt template named '{name}' already exists"));
+        }
+
+        let now = Utc::now().trunc_subsecs(0);
+        let template = match id {
+            Some(id) => PromptTemplate {
+                name: name.to_string(),
+                content: content.to_string(),
+                repository_path,
+                updated_at: now,
+                ..self.get_prompt_template(id)?
+            },
+            None => PromptTemplate {
+                id: Uuid::new_v4().to_string(),
+                name: name.to_string(),
+                content: content.to_string(),
+                repository_path,
+                created_at: now,
+                updated_at: now,
+            },
+        };
+        self.db_manager.db.save_prompt_template(&template)?;
+        Ok(template)
+    }
+
+    pub fn delete_prompt_template(&self, id: &str) -> Result<()> {
+        let template = self.get_prompt_template(id)?;
+        self.db_manager.db.delete_prompt_template(&template.id)?;
+        Ok(())
+    }
+
+    /// The template with its variables filled in from the session.
+    pub fn render_prompt_template(&self, id: &str, session_name: &str) -> Result<String> {
+        let template = self.get_prompt_template(id)?;
+        let session = self.db_manager.get_session_by_name(session_name)?;
+
+        let mut values: HashMap<&str, String> = HashMap::from([
+            ("session", session.name.clone()),
+            ("branch", session.branch.clone()),
+            ("base_branch", session.parent_branch.clone()),
+            (
+                "worktree_path",
+                session.worktree_path.to_string_lossy().to_string(),
+            ),
+            ("project", session.repository_name.clone()),
+            (
+                "spec",
+                session
+                    .spec_content
+                    .clone()
+                    .or_else(|| session.initial_prompt.clone())
+                    .unwrap_or_default(),
+            ),
+        ]);
+        // Diffing the worktree is the expensive part, so only for templates that list files
+        if template_variables(&template.content).contains(&"changed_files")
+            && session.session_state != SessionState::Spec
+            && session.worktree_path.exists()
+        {
+            let files = git::get_changed_files(&session.worktree_path, &session.parent_branch)?;
+            let listed = files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            values.insert("changed_files", listed);
+        }
+
+        render_template(&template.content, &values)
+    }
+
+    /// Make `prompt` what the session's agent starts with: the content of a spec, or the
+    /// initial prompt of a running session for its next agent start.
+    pub fn set_session_prompt(&self, session_name: &str, prompt: &str) -> Result<()> {
+        let session = self.db_manager.get_session_by_name(session_name)?;
+        if session.session_state == SessionState::Spec {
+            return self.update_spec_content(session_name, prompt);
+        }
+        // A new task replaces the context of an earlier handoff
+        self.db_manager
+            .set_session_handoff_prompt(&session.id, None)?;
+        self.db_manager
+            .update_session_initial_prompt(&session.id, prompt)
+    }
+
+    /// Build the prompt handing a running session to a different agent type, with the
+    /// spec and the work so far. Nothing is stored: [`Self::commit_agent_handoff`] records
+    /// the switch once the new agent started.
+    pub fn prepare_agent_handoff(&self, session_name: &str, agent_type: &str) -> Result<String> {
+        let session = self.db_manager.get_session_by_name(session_name)?;
+        if session.session_state == SessionState::Spec {
+            return Err(anyhow!(
+                "Session '{session_name}' is a spec; start it before switching agents"
+            ));
+        }
+        let (default_agent_type, _) = self.default_agent_settings()?;
+        let previous_agent = session
+            .original_agent_type
+            .clone()
+            .unwrap_or(default_agent_type);
+        if previous_agent == agent_type {
+            return Err(anyhow!(
+                "Session '{session_name}' already runs {agent_type}"
+            ));
+        }
+
+        let changed_files = git::get_changed_files(&session.worktree_path, &session.parent_branch)?;
+        let uncommitted_summary =
+            git::uncommitted_diff_summary(&session.worktree_path, handoff::HANDOFF_PATCH_LIMIT)?;
+        let spec = session
+            .spec_content
+            .as_deref()
+            .or(session.initial_prompt.as_deref());
+        Ok(handoff::build_handoff_prompt(&handoff::HandoffContext {
+            previous_agent: &previous_agent,
+            spec,
+            base_branch: &session.parent_branch,
+            changed_files: &changed_files,
+            uncommitted_summary: &uncommitted_summary,
+        }))
+    }
+
+    /// Record that the session now runs `agent_type`, started with the handoff `prompt`.
+    /// The prompt is kept apart from the initial prompt, which still holds the task.
+    pub fn commit_agent_handoff(
+        &self,
+        session_name: &str,
+        agent_type: &str,
+        prompt: &str,
+    ) -> Result<()> {
+        let session = self.db_manager.get_session_by_name(session_name)?;
+        let (_, default_skip_permissions) = self.default_agent_settings()?;
+        let skip_permissions = session
+            .original_skip_permissions
+            .unwrap_or(default_skip_permissions);
+        self.db_manager
+            .set_session_original_settings(&session.id, agent_type, skip_permissions)?;
+        // A model picked for the previous agent means nothing to the next one
+        self.db_manager.set_session_agent_model(&session.id, None)?;
+        self.db_manager
+            .set_session_handoff_prompt(&session.id, Some(prompt))?;
+        info!("Handed session '{session_name}' to {agent_type}");
+        Ok(())
+    }
+
     pub fn get_archive_max_entries(&self) -> Result<i32> {
         self.db_manager.db.get_archive_max_entries()
     }
@@ -3607,6 +6516,41 @@ impl SessionManager {
         crate::domains::git::worktrees::discard_path_in_worktree(&session.worktree_path, path)
     }
 
+    /// Uncommitted hunks of a single file in a session's worktree, for hunk-level discard.
+    pub fn list_session_file_hunks(
+        &self,
+        name: &str,
+        rel_file_path: &str,
+    ) -> Result<Vec<git::WorktreeHunk>> {
+        let worktree_path = self.worktree_for_hunk_discard(name, rel_file_path)?;
+        git::list_worktree_hunks(&worktree_path, rel_file_path)
+    }
+
+    /// Revert only the selected hunks of a file in a session's worktree.
+    pub fn discard_hunks_in_session(
+        &self,
+        name: &str,
+        rel_file_path: &str,
+        hunk_ids: &[String],
+    ) -> Result<usize> {
+        let worktree_path = self.worktree_for_hunk_discard(name, rel_file_path)?;
+        git::discard_hunks_in_worktree(&worktree_path, rel_file_path, hunk_ids)
+    }
+
+    fn worktree_for_hunk_discard(&self, name: &str, rel_file_path: &str) -> Result<PathBuf> {
+        let session = self.db_manager.get_session_by_name(name)?;
+        if session.session_state == SessionState::Spec {
+            return Err(anyhow!("Session '{name}' is a spec and has no worktree"));
+        }
+        if !session.worktree_path.starts_with(&self.repo_path) {
+            return Err(anyhow!("Invalid worktree path for this project"));
+        }
+        if rel_file_path.starts_with(".schaltwerk/") {
+            return Err(anyhow!("Refusing to discard changes under .schaltwerk"));
+        }
+        Ok(session.worktree_path)
+    }
+
     pub fn mark_session_prompted(&self, worktree_path: &std::path::Path) {
         self.cache_manager.mark_session_prompted(worktree_path);
     }
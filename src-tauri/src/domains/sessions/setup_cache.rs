@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+// Lockfiles at the worktree root whose contents decide whether setup output can be reused
+const LOCKFILES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "bun.lock",
+    "bun.lockb",
+    "Cargo.lock",
+    "poetry.lock",
+    "uv.lock",
+    "Pipfile.lock",
+    "requirements.txt",
+    "Gemfile.lock",
+    "go.sum",
+    "composer.lock",
+    "flake.lock",
+];
+// Directories produced by setup that are restored into new worktrees on a cache hit.
+// Virtualenvs are left out: their scripts hardcode the absolute path they were created at.
+const CACHED_ARTIFACTS: &[&str] = &["node_modules", "vendor/bundle"];
+const COMPLETE_MARKER: &str = ".schaltwerk-setup-complete";
+const MAX_CACHE_ENTRIES: usize = 3;
+
+/// Fingerprint of a setup run: the script plus the lockfiles present in the worktree.
+pub fn setup_fingerprint(script: &str, worktree_path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(script.as_bytes());
+    for lockfile in LOCKFILES {
+        if let Ok(contents) = fs::read(worktree_path.join(lockfile)) {
+            hasher.update(b"\0");
+            hasher.update(lockfile.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(&contents);
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Setup artifacts cached per fingerprint under `.schaltwerk/setup-cache` of the repository.
+pub struct SetupCache {
+    root: PathBuf,
+}
+
+impl SetupCache {
+    pub fn new(repo_path: &Path) -> Self {
+        Self {
+            root: repo_path.join(".schaltwerk").join("setup-cache"),
+        }
+    }
+
+    fn entry_dir(&self, fingerprint: &str) -> PathBuf {
+        self.root.join(fingerprint)
+    }
+
+    pub fn has_entry(&self, fingerprint: &str) -> bool {
+        self.entry_dir(fingerprint).join(COMPLETE_MARKER).is_file()
+    }
+
+    /// Copy the artifacts of a successful setup into the cache.
+    /// Returns false when the worktree had nothing worth caching.
+    pub fn store(&self, fingerprint: &str, worktree_path: &Path) -> Result<bool> {
+        let artifacts: Vec<&str> = CACHED_ARTIFACTS
+            .iter()
+            .copied()
+            .filter(|artifact| worktree_path.join(artifact).is_dir())
+            .collect();
+        if artifacts.is_empty() {
+            return Ok(false);
+        }
+
+        let entry = self.entry_dir(fingerprint);
+        let staging = self.root.join(format!("{fingerprint}.partial"));
+        let _ = fs::remove_dir_all(&staging);
+        for artifact in &artifacts {
+            copy_tree(&worktree_path.join(artifact), &staging.join(artifact))?;
+        }
+        fs::write(staging.join(COMPLETE_MARKER), fingerprint)?;
+
+        let _ = fs::remove_dir_all(&entry);
+        fs::rename(&staging, &entry)?;
+        log::info!(
+            "Cached setup artifacts {artifacts:?} from {} as {fingerprint}",
+            worktree_path.display()
+        );
+
+        self.prune();
+        Ok(true)
+    }
+
+    /// Restore cached artifacts into a worktree. Returns false on a cache miss.
+    pub fn restore(&self, fingerprint: &str, worktree_path: &Path) -> Result<bool> {
+        if !self.has_entry(fingerprint) {
+            return Ok(false);
+        }
+
+        let entry = self.entry_dir(fingerprint);
+        for artifact in CACHED_ARTIFACTS {
+            let cached = entry.join(artifact);
+            if !cached.is_dir() {
+                continue;
+            }
+            let target = worktree_path.join(artifact);
+            if target.exists() {
+                fs::remove_dir_all(&target)?;
+            }
+            copy_tree(&cached, &target)?;
+        }
+        // Mark the entry as recently used so pruning keeps it
+        fs::write(entry.join(COMPLETE_MARKER), fingerprint)?;
+
+        log::info!(
+            "Restored cached setup {fingerprint} into {}",
+            worktree_path.display()
+        );
+        Ok(true)
+    }
+
+    /// Drop the least recently used entries beyond the cache limit.
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return;
+        };
+        let mut complete: Vec<(std::time::SystemTime, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let modified = fs::metadata(entry.path().join(COMPLETE_MARKER))
+                    .and_then(|meta| meta.modified())
+                    .ok()?;
+                Some((modified, entry.path()))
+            })
+            .collect();
+        complete.sort_by(|a, b| b.0.cmp(&a.0));
+
+        for (_, path) in complete.into_iter().skip(MAX_CACHE_ENTRIES) {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                log::warn!("Failed to prune setup cache entry {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// Copy a directory tree, recreating symlinks instead of following them.
+fn copy_tree(source: &Path, target: &Path) -> Result<()> {
+    for entry in WalkDir::new(source).follow_links(false) {
+        let entry = entry?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| anyhow!("Failed to resolve {}: {e}", entry.path().display()))?;
+        let destination = target.join(relative);
+        let file_type = entry.file_type();
+
+        if file_type.is_dir() {
+            fs::create_dir_all(&destination)?;
+        } else if file_type.is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&link, &destination)?;
+            #[cfg(not(unix))]
+            fs::copy(entry.path(), &destination).map(|_| ())?;
+        } else {
+            // Uses copy-on-write clones where the filesystem supports them
+            fs::copy(entry.path(), &destination)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn fingerprint_tracks_script_and_lockfiles() {
+        let worktree = TempDir::new().unwrap();
+        let base = setup_fingerprint("npm ci", worktree.path());
+        assert_eq!(base, setup_fingerprint("npm ci", worktree.path()));
+        assert_ne!(base, setup_fingerprint("npm install", worktree.path()));
+
+        fs::write(worktree.path().join("package-lock.json"), "{}").unwrap();
+        let with_lockfile = setup_fingerprint("npm ci", worktree.path());
+        assert_ne!(base, with_lockfile);
+
+        fs::write(worktree.path().join("README.md"), "docs").unwrap();
+        assert_eq!(with_lockfile, setup_fingerprint("npm ci", worktree.path()));
+    }
+
+    #[test]
+    fn stores_and_restores_artifacts() {
+        let repo = TempDir::new().unwrap();
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        let cache = SetupCache::new(repo.path());
+
+        assert!(!cache.store("empty", source.path()).unwrap());
+
+        fs::create_dir_all(source.path().join("node_modules/left-pad")).unwrap();
+        fs::write(source.path().join("node_modules/left-pad/index.js"), "pad").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(
+            "../left-pad/index.js",
+            source.path().join("node_modules/left-pad/link.js"),
+        )
+        .unwrap();
+
+        assert!(!cache.restore("abc", target.path()).unwrap());
+        assert!(cache.store("abc", source.path()).unwrap());
+        assert!(cache.has_entry("abc"));
+        assert!(cache.restore("abc", target.path()).unwrap());
+
+        assert_eq!(
+            fs::read_to_string(target.path().join("node_modules/left-pad/index.js")).unwrap(),
+            "pad"
+        );
+        #[cfg(unix)]
+        assert!(
+            fs::symlink_metadata(target.path().join("node_modules/left-pad/link.js"))
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+    }
+
+    #[test]
+    fn prunes_old_entries() {
+        let repo = TempDir::new().unwrap();
+        let source = TempDir::new().unwrap();
+        fs::create_dir_all(source.path().join("node_modules")).unwrap();
+        let cache = SetupCache::new(repo.path());
+
+        for i in 0..=MAX_CACHE_ENTRIES {
+            cache.store(&format!("entry{i}"), source.path()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        assert!(!cache.has_entry("entry0"));
+        assert!(cache.has_entry(&format!("entry{MAX_CACHE_ENTRIES}")));
+    }
+}
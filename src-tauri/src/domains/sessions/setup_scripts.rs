@@ -0,0 +1,90 @@
+//! The project's setup steps combined into the one script a session setup runs.
+
+use crate::domains::terminal::sh_quote_string;
+use crate::schaltwerk_core::db_project_config::NamedSetupScript;
+
+// Header shown for the project's own setup script when named steps follow it
+const PROJECT_SCRIPT_NAME: &str = "setup";
+
+/// The project's setup script followed by its enabled named steps; `None` when there is
+/// nothing to run. Each step runs as its own script after a `==> name` header, so the
+/// streamed output shows which step is running, and a failing step stops the rest.
+pub fn compose_project_setup(
+    project_script: Option<String>,
+    steps: &[NamedSetupScript],
+) -> Option<String> {
+    let project_script = project_script.filter(|script| !script.trim().is_empty());
+    let named: Vec<&NamedSetupScript> = steps
+        .iter()
+        .filter(|step| step.enabled && !step.script.trim().is_empty())
+        .collect();
+    if named.is_empty() {
+        // A lone project script runs unchanged, which keeps earlier cached setups valid
+        return project_script;
+    }
+
+    let mut composed = String::from("set -e\n");
+    let project_step = project_script
+        .as_deref()
+        .map(|script| (PROJECT_SCRIPT_NAME, script));
+    let named_steps = named
+        .iter()
+        .map(|step| (step.name.as_str(), step.script.as_str()));
+    for (name, script) in project_step.into_iter().chain(named_steps) {
+        composed.push_str(&format!(
+            "printf '\\n==> %s\\n' {}\nprintf '%s' {} | sh\n",
+            sh_quote_string(name),
+            sh_quote_string(script)
+        ));
+    }
+    Some(composed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(name: &str, script: &str, enabled: bool) -> NamedSetupScript {
+        NamedSetupScript {
+            name: name.to_string(),
+            script: script.to_string(),
+            enabled,
+        }
+    }
+
+    #[test]
+    fn lone_project_script_is_unchanged() {
+        assert_eq!(compose_project_setup(None, &[]), None);
+        assert_eq!(
+            compose_project_setup(Some("bun install".to_string()), &[step("off", "x", false)]),
+            Some("bun install".to_string())
+        );
+    }
+
+    #[test]
+    fn named_steps_run_in_order_and_stop_on_failure() {
+        let composed = compose_project_setup(
+            Some("echo project".to_string()),
+            &[
+                step("deps", "echo deps", true),
+                step("skipped", "echo skipped", false),
+                step("broken", "exit 3", true),
+                step("after", "echo after", true),
+            ],
+        )
+        .unwrap();
+
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&composed)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(
+            stdout,
+            "\n==> setup\nproject\n\n==> deps\ndeps\n\n==> broken\n"
+        );
+    }
+}
@@ -50,6 +50,8 @@ mod session_sorting_tests {
             session_state: state,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         }
     }
 
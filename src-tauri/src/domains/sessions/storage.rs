@@ -1,6 +1,7 @@
 use std::ffi::OsStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use git2::Repository;
 use walkdir::WalkDir;
 
 pub fn compute_worktree_size_bytes(worktree_path: &Path) -> Option<u64> {
@@ -50,10 +51,78 @@ pub fn compute_worktree_size_bytes(worktree_path: &Path) -> Option<u64> {
     Some(total)
 }
 
+/// Dependency and build output directories that a rebuild regenerates.
+pub const BUILD_ARTIFACT_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    ".next",
+    ".turbo",
+    ".gradle",
+    ".venv",
+    "__pycache__",
+];
+
+/// Build artifact directories inside a worktree that git ignores and that hold no tracked
+/// files, so deleting one loses nothing a rebuild cannot restore. Artifacts nested in another
+/// artifact directory (e.g. `node_modules/x/node_modules`) are covered by their parent.
+pub fn find_build_artifact_dirs(worktree_path: &Path) -> Vec<PathBuf> {
+    let (repo, index) = match Repository::open(worktree_path).and_then(|repo| {
+        let index = repo.index()?;
+        Ok((repo, index))
+    }) {
+        Ok(opened) => opened,
+        Err(e) => {
+            log::warn!(
+                "Not looking for build artifacts in {}: {e}",
+                worktree_path.display()
+            );
+            return Vec::new();
+        }
+    };
+    let disposable = |path: &Path| {
+        let Ok(relative) = path.strip_prefix(worktree_path) else {
+            return false;
+        };
+        let tracked_prefix = format!("{}/", relative.to_string_lossy().replace('\\', "/"));
+        repo.is_path_ignored(relative).unwrap_or(false)
+            && index.find_prefix(tracked_prefix.as_str()).is_err()
+    };
+
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(worktree_path)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter();
+
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name();
+        if name == OsStr::new(".git") {
+            walker.skip_current_dir();
+        } else if BUILD_ARTIFACT_DIRS
+            .iter()
+            .any(|dir| name == OsStr::new(dir))
+            && disposable(entry.path())
+        {
+            walker.skip_current_dir();
+            found.push(entry.into_path());
+        }
+    }
+
+    found
+}
+
 #[cfg(test)]
 mod tests {
-    use super::compute_worktree_size_bytes;
+    use super::{compute_worktree_size_bytes, find_build_artifact_dirs};
+    use git2::Repository;
     use std::fs;
+    use std::path::Path;
     use tempfile::TempDir;
 
     #[test]
@@ -79,4 +148,63 @@ mod tests {
         let size = compute_worktree_size_bytes(root).unwrap();
         assert_eq!(size, 2048 + 1024);
     }
+
+    fn init_repo(root: &Path, gitignore: &str) -> Repository {
+        let repo = Repository::init(root).unwrap();
+        fs::write(root.join(".gitignore"), gitignore).unwrap();
+        repo
+    }
+
+    #[test]
+    fn finds_top_level_build_artifacts_only() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        init_repo(root, "node_modules/\ntarget/\n");
+
+        fs::create_dir_all(root.join("node_modules").join("dep").join("node_modules")).unwrap();
+        fs::create_dir_all(root.join("crates").join("core").join("target")).unwrap();
+        fs::create_dir_all(root.join(".git").join("target")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        let mut found = find_build_artifact_dirs(root);
+        found.sort();
+
+        assert_eq!(
+            found,
+            vec![
+                root.join("crates").join("core").join("target"),
+                root.join("node_modules"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_artifact_dirs_that_git_does_not_ignore() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let repo = init_repo(root, "node_modules/\n__pycache__/\n");
+
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        // Not ignored
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("target").join("fixture.txt"), "tracked").unwrap();
+        fs::create_dir_all(root.join(".venv")).unwrap();
+        // Ignored, but holds a tracked file
+        fs::create_dir_all(root.join("__pycache__")).unwrap();
+        fs::write(root.join("__pycache__").join("keep.pyc"), "tracked").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("target/fixture.txt")).unwrap();
+        index.add_path(Path::new("__pycache__/keep.pyc")).unwrap();
+        index.write().unwrap();
+
+        assert_eq!(
+            find_build_artifact_dirs(root),
+            vec![root.join("node_modules")]
+        );
+
+        // Outside a repository nothing is known to be disposable
+        let plain = TempDir::new().unwrap();
+        fs::create_dir_all(plain.path().join("node_modules")).unwrap();
+        assert!(find_build_artifact_dirs(plain.path()).is_empty());
+    }
 }
@@ -3,13 +3,19 @@ use crate::{
     domains::sessions::cache::SessionCacheManager,
     domains::sessions::entity::{EnrichedSession, FilterMode, SessionState, SortMode},
     domains::sessions::repository::SessionDbManager,
+    domains::sessions::setup_cache::{setup_fingerprint, SetupCache},
     domains::terminal::{build_login_shell_invocation, sh_quote_string},
-    schaltwerk_core::db_project_config::{ProjectConfigMethods, DEFAULT_BRANCH_PREFIX},
+    schaltwerk_core::db_project_config::{
+        project_worktrees_root, ProjectConfigMethods, DEFAULT_BRANCH_PREFIX,
+    },
 };
 use anyhow::{anyhow, Result};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+const SETUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct SessionUtils {
     repo_path: PathBuf,
     cache_manager: SessionCacheManager,
@@ -17,7 +23,7 @@ pub struct SessionUtils {
 }
 
 impl SessionUtils {
-    fn branch_prefix(&self) -> String {
+    pub fn branch_prefix(&self) -> String {
         self.db_manager
             .db
             .get_project_branch_prefix(&self.repo_path)
@@ -27,13 +33,13 @@ impl SessionUtils {
             })
     }
 
+    pub fn worktrees_root(&self) -> PathBuf {
+        project_worktrees_root(&self.db_manager.db, &self.repo_path)
+    }
+
     fn check_name_availability_with_prefix(&self, name: &str, branch_prefix: &str) -> Result<bool> {
         let branch = format!("{branch_prefix}/{name}");
-        let worktree_path = self
-            .repo_path
-            .join(".schaltwerk")
-            .join("worktrees")
-            .join(name);
+        let worktree_path = self.worktrees_root().join(name);
 
         let worktree_exists = worktree_path.exists();
         let session_exists = self.db_manager.session_exists(name);
@@ -85,11 +91,7 @@ impl SessionUtils {
 
         if self.check_name_availability_with_prefix(base_name, &branch_prefix)? {
             let branch = format!("{branch_prefix}/{base_name}");
-            let worktree_path = self
-                .repo_path
-                .join(".schaltwerk")
-                .join("worktrees")
-                .join(base_name);
+            let worktree_path = self.worktrees_root().join(base_name);
 
             self.cache_manager.reserve_name(base_name);
             return Ok((base_name.to_string(), branch, worktree_path));
@@ -101,11 +103,7 @@ impl SessionUtils {
 
             if self.check_name_availability_with_prefix(&candidate, &branch_prefix)? {
                 let branch = format!("{branch_prefix}/{candidate}");
-                let worktree_path = self
-                    .repo_path
-                    .join(".schaltwerk")
-                    .join("worktrees")
-                    .join(&candidate);
+                let worktree_path = self.worktrees_root().join(&candidate);
 
                 self.cache_manager.reserve_name(&candidate);
                 return Ok((candidate, branch, worktree_path));
@@ -117,11 +115,7 @@ impl SessionUtils {
 
             if self.check_name_availability_with_prefix(&candidate, &branch_prefix)? {
                 let branch = format!("{branch_prefix}/{candidate}");
-                let worktree_path = self
-                    .repo_path
-                    .join(".schaltwerk")
-                    .join("worktrees")
-                    .join(&candidate);
+                let worktree_path = self.worktrees_root().join(&candidate);
 
                 self.cache_manager.reserve_name(&candidate);
                 return Ok((candidate, branch, worktree_path));
@@ -167,64 +161,71 @@ impl SessionUtils {
     }
 
     pub fn cleanup_orphaned_worktrees(&self) -> Result<()> {
-        let worktrees = git::list_worktrees(&self.repo_path)?;
-
-        for worktree_path in worktrees {
-            if !worktree_path
-                .to_string_lossy()
-                .contains("/.schaltwerk/worktrees/")
-            {
-                continue;
-            }
+        for worktree_path in self.find_orphaned_worktrees()? {
+            self.remove_orphaned_worktree(&worktree_path);
+        }
 
-            // Canonicalize paths to handle symlinks (like /var -> /private/var on macOS)
-            let canonical_worktree = worktree_path
-                .canonicalize()
-                .unwrap_or_else(|_| worktree_path.clone());
+        Ok(())
+    }
 
-            let sessions = self.db_manager.list_sessions()?;
-            // IMPORTANT: Only check against sessions that should have worktrees
-            // Spec sessions should NOT have worktree directories, so we exclude them
-            let sessions_with_worktrees: Vec<_> = sessions
-                .into_iter()
-                .filter(|s| s.session_state != SessionState::Spec)
-                .collect();
+    /// Schaltwerk worktrees registered with git that no non-spec session points at.
+    pub fn find_orphaned_worktrees(&self) -> Result<Vec<PathBuf>> {
+        let worktrees = git::list_worktrees(&self.repo_path)?;
+        let worktrees_root = self.worktrees_root();
+        let worktrees_root = worktrees_root.canonicalize().unwrap_or(worktrees_root);
+        // IMPORTANT: Only check against sessions that should have worktrees
+        // Spec sessions should NOT have worktree directories, so we exclude them
+        let session_worktrees: Vec<PathBuf> = self
+            .db_manager
+            .list_sessions()?
+            .into_iter()
+            .filter(|s| s.session_state != SessionState::Spec)
+            .map(|s| {
+                s.worktree_path
+                    .canonicalize()
+                    .unwrap_or_else(|_| s.worktree_path.clone())
+            })
+            .collect();
 
-            let exists = sessions_with_worktrees.iter().any(|s| {
-                let canonical_session = s
-                    .worktree_path
+        Ok(worktrees
+            .into_iter()
+            .filter(|worktree_path| {
+                // Canonicalize paths to handle symlinks (like /var -> /private/var on macOS)
+                let canonical_worktree = worktree_path
                     .canonicalize()
-                    .unwrap_or_else(|_| s.worktree_path.clone());
-                canonical_session == canonical_worktree
-            });
+                    .unwrap_or_else(|_| worktree_path.clone());
+                canonical_worktree.starts_with(&worktrees_root)
+                    && !session_worktrees.contains(&canonical_worktree)
+            })
+            .collect())
+    }
 
-            if !exists {
-                log::info!(
-                    "Removing orphaned worktree: {} (no matching non-spec session found)",
-                    worktree_path.display()
-                );
-                let _ = git::remove_worktree(&self.repo_path, &worktree_path);
-                if worktree_path.exists() {
-                    log::debug!(
-                        "Forcefully removing worktree directory: {}",
-                        worktree_path.display()
-                    );
-                    let _ = std::fs::remove_dir_all(&worktree_path);
-                }
-            }
+    pub fn remove_orphaned_worktree(&self, worktree_path: &Path) {
+        log::info!(
+            "Removing orphaned worktree: {} (no matching non-spec session found)",
+            worktree_path.display()
+        );
+        let _ = git::remove_worktree(&self.repo_path, worktree_path);
+        if worktree_path.exists() {
+            log::debug!(
+                "Forcefully removing worktree directory: {}",
+                worktree_path.display()
+            );
+            let _ = std::fs::remove_dir_all(worktree_path);
         }
-
-        Ok(())
     }
 
+    /// Run the setup script to completion, killing it once `timeout` has passed.
     pub fn execute_setup_script(
         &self,
         script: &str,
         session_name: &str,
         branch_name: &str,
         worktree_path: &Path,
+        timeout: Option<Duration>,
     ) -> Result<()> {
-        use std::process::Command;
+        use std::io::Read;
+        use std::process::{Command, Stdio};
 
         log::info!("Executing setup script for session {session_name}");
 
@@ -255,19 +256,50 @@ impl SessionUtils {
         let mut cmd = Command::new(&shell_invocation.program);
         cmd.args(&shell_invocation.args);
 
-        let output = cmd
+        let mut child = cmd
             .current_dir(worktree_path)
             .env("WORKTREE_PATH", worktree_path)
             .env("REPO_PATH", &self.repo_path)
             .env("SESSION_NAME", session_name)
             .env("BRANCH_NAME", branch_name)
-            .output()?;
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Drained on its own thread so a chatty script cannot block on a full pipe
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut stderr = String::new();
+            if let Some(pipe) = stderr_pipe.as_mut() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            stderr
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            std::thread::sleep(SETUP_POLL_INTERVAL);
+        };
 
         // Clean up the temporary script file
         let _ = std::fs::remove_file(&script_path);
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
+        let Some(status) = status else {
+            return Err(anyhow!(
+                "Setup script timed out after {}s",
+                timeout.unwrap_or_default().as_secs()
+            ));
+        };
+        if !status.success() {
+            let stderr = stderr_reader.join().unwrap_or_default();
             return Err(anyhow!("Setup script failed: {stderr}"));
         }
 
@@ -275,6 +307,39 @@ impl SessionUtils {
         Ok(())
     }
 
+    /// Run the setup script unless an identical setup was cached, restoring its artifacts instead.
+    pub fn execute_setup_script_cached(
+        &self,
+        script: &str,
+        session_name: &str,
+        branch_name: &str,
+        worktree_path: &Path,
+        force_setup: bool,
+        timeout: Option<Duration>,
+    ) -> Result<()> {
+        let cache = SetupCache::new(&self.repo_path);
+        let fingerprint = setup_fingerprint(script, worktree_path);
+        if !force_setup {
+            match cache.restore(&fingerprint, worktree_path) {
+                Ok(true) => {
+                    log::info!(
+                        "Skipped setup script for session {session_name}: restored from cache"
+                    );
+                    return Ok(());
+                }
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to restore cached setup for {session_name}: {e}"),
+            }
+        }
+
+        self.execute_setup_script(script, session_name, branch_name, worktree_path, timeout)?;
+
+        if let Err(e) = cache.store(&fingerprint, worktree_path) {
+            log::warn!("Failed to cache setup artifacts for {session_name}: {e}");
+        }
+        Ok(())
+    }
+
     pub fn apply_session_filter(
         &self,
         sessions: Vec<EnrichedSession>,
@@ -294,6 +359,10 @@ impl SessionUtils {
                 .into_iter()
                 .filter(|s| s.info.ready_to_merge)
                 .collect(),
+            FilterMode::Label(label) => sessions
+                .into_iter()
+                .filter(|s| s.info.labels.iter().any(|l| l.eq_ignore_ascii_case(label)))
+                .collect(),
         }
     }
 
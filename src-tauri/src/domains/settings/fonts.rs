@@ -0,0 +1,194 @@
+//! The configured terminal font checked against the installed fonts: whether it is
+//! installed, monospaced and draws ligatures, and what to use when it is missing.
+
+use serde::{Deserialize, Serialize};
+use ttf_parser::Tag;
+
+/// Monospace fonts tried in this order when nothing installed resembles the configured one
+const PREFERRED_FALLBACKS: &[&str] = &[
+    "JetBrains Mono",
+    "SF Mono",
+    "Menlo",
+    "Cascadia Code",
+    "Fira Code",
+    "Consolas",
+    "DejaVu Sans Mono",
+    "Liberation Mono",
+    "Ubuntu Mono",
+];
+
+/// OpenType features that draw sequences like `=>` or `!=` as one glyph
+const LIGATURE_FEATURES: &[&[u8; 4]] = &[b"liga", b"calt", b"dlig"];
+
+/// Normalized names sharing fewer leading characters are not considered related
+const MIN_SHARED_PREFIX: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledFont {
+    pub family: String,
+    pub monospace: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FontValidation {
+    /// The installed family the configured name matched, spelled as installed
+    pub family: Option<String>,
+    pub installed: bool,
+    pub monospace: bool,
+    pub ligatures: bool,
+    /// Closest installed monospace font, for a font that is missing or not monospaced
+    pub fallback: Option<String>,
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The installed font `requested` names, ignoring case, spaces and dashes.
+pub fn find_installed<'a>(
+    requested: &str,
+    installed: &'a [InstalledFont],
+) -> Option<&'a InstalledFont> {
+    let wanted = normalize(requested);
+    installed
+        .iter()
+        .find(|font| !wanted.is_empty() && normalize(&font.family) == wanted)
+}
+
+/// The installed monospace font closest to `requested`: the one sharing the longest name
+/// prefix (`JetBrains Mono` for `JetBrainsMono Nerd Font`), otherwise a common terminal
+/// font, otherwise the first monospace font.
+pub fn nearest_font(requested: &str, installed: &[InstalledFont]) -> Option<String> {
+    let wanted = normalize(requested);
+    let monospace: Vec<&InstalledFont> = installed.iter().filter(|font| font.monospace).collect();
+
+    let related = monospace
+        .iter()
+        .filter(|font| normalize(&font.family) != wanted)
+        .map(|font| {
+            let shared = normalize(&font.family)
+                .chars()
+                .zip(wanted.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            (shared, font)
+        })
+        .filter(|(shared, _)| *shared >= MIN_SHARED_PREFIX)
+        .max_by(|(a, a_font), (b, b_font)| {
+            a.cmp(b)
+                .then_with(|| b_font.family.len().cmp(&a_font.family.len()))
+        });
+    if let Some((_, font)) = related {
+        return Some(font.family.clone());
+    }
+
+    PREFERRED_FALLBACKS
+        .iter()
+        .find_map(|preferred| {
+            find_installed(preferred, installed)
+                .filter(|font| font.monospace && normalize(&font.family) != wanted)
+        })
+        .or_else(|| {
+            monospace
+                .iter()
+                .copied()
+                .find(|font| normalize(&font.family) != wanted)
+        })
+        .map(|font| font.family.clone())
+}
+
+/// Whether the font in `data` (face `index` of a collection) substitutes ligatures.
+pub fn has_ligatures(data: &[u8], index: u32) -> bool {
+    let Ok(face) = ttf_parser::Face::parse(data, index) else {
+        return false;
+    };
+    let Some(gsub) = face.tables().gsub else {
+        return false;
+    };
+    gsub.features.into_iter().any(|feature| {
+        LIGATURE_FEATURES
+            .iter()
+            .any(|tag| feature.tag == Tag::from_bytes(tag))
+    })
+}
+
+/// Validate `requested` against the installed fonts. `ligatures` tells whether an
+/// installed family draws ligatures; it is only asked about the matched family.
+pub fn validate_font(
+    requested: &str,
+    installed: &[InstalledFont],
+    ligatures: impl FnOnce(&str) -> bool,
+) -> FontValidation {
+    match find_installed(requested, installed) {
+        Some(font) => FontValidation {
+            family: Some(font.family.clone()),
+            installed: true,
+            monospace: font.monospace,
+            ligatures: ligatures(&font.family),
+            fallback: (!font.monospace)
+                .then(|| nearest_font(requested, installed))
+                .flatten(),
+        },
+        None => FontValidation {
+            family: None,
+            installed: false,
+            monospace: false,
+            ligatures: false,
+            fallback: nearest_font(requested, installed),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font(family: &str, monospace: bool) -> InstalledFont {
+        InstalledFont {
+            family: family.to_string(),
+            monospace,
+        }
+    }
+
+    #[test]
+    fn validates_installed_and_missing_fonts() {
+        let installed = vec![
+            font("DejaVu Sans Mono", true),
+            font("Fira Code", true),
+            font("Inter", false),
+            font("JetBrains Mono", true),
+        ];
+
+        let fira = validate_font("fira-code", &installed, |family| family == "Fira Code");
+        assert_eq!(fira.family.as_deref(), Some("Fira Code"));
+        assert!(fira.installed && fira.monospace && fira.ligatures);
+        assert_eq!(fira.fallback, None);
+
+        let nerd = validate_font("JetBrainsMono Nerd Font", &installed, |_| true);
+        assert!(!nerd.installed && !nerd.ligatures);
+        assert_eq!(nerd.fallback.as_deref(), Some("JetBrains Mono"));
+
+        let inter = validate_font("Inter", &installed, |_| false);
+        assert!(inter.installed && !inter.monospace);
+        assert_eq!(inter.fallback.as_deref(), Some("JetBrains Mono"));
+
+        assert_eq!(
+            nearest_font(
+                "Comic Mono",
+                &[font("Noto Mono", true), font("Menlo", true)]
+            ),
+            Some("Menlo".to_string())
+        );
+        assert_eq!(nearest_font("Menlo", &[font("Inter", false)]), None);
+    }
+
+    #[test]
+    fn garbage_is_not_a_ligature_font() {
+        assert!(!has_ligatures(b"not a font", 0));
+    }
+}
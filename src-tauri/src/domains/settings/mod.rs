@@ -1,4 +1,7 @@
+pub mod fonts;
 pub mod service;
+pub mod shortcuts;
+pub mod themes;
 pub mod types;
 pub mod validation;
 
@@ -1,11 +1,19 @@
+use super::shortcuts::{validate_shortcuts, ShortcutValidation};
+use super::themes::{builtin_themes, is_builtin_theme};
 use super::types::*;
-use super::validation::clean_invalid_binary_paths;
-use std::collections::HashMap;
+use super::validation::{clean_invalid_binary_paths, validate_env_set, validate_terminal_theme};
+use crate::domains::agents::custom::{custom_agent_config, CustomAgentConfig};
+use crate::infrastructure::secrets::{register_secret, KeychainStore, SecretStore};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone)]
 pub enum SettingsServiceError {
     UnknownAgentType(String),
     RepositoryError(String),
+    InvalidKeyboardShortcuts(ShortcutValidation),
+    InvalidEnvSet(String),
+    SecretStoreError(String),
+    InvalidTheme(String),
 }
 
 impl std::fmt::Display for SettingsServiceError {
@@ -15,6 +23,12 @@ impl std::fmt::Display for SettingsServiceError {
                 write!(f, "Unknown agent type: {agent}")
             }
             SettingsServiceError::RepositoryError(msg) => write!(f, "Repository error: {msg}"),
+            SettingsServiceError::InvalidKeyboardShortcuts(validation) => {
+                write!(f, "Invalid keyboard shortcuts: {validation}")
+            }
+            SettingsServiceError::InvalidEnvSet(msg) => write!(f, "Invalid env set: {msg}"),
+            SettingsServiceError::SecretStoreError(msg) => write!(f, "Keychain error: {msg}"),
+            SettingsServiceError::InvalidTheme(msg) => write!(f, "Invalid theme: {msg}"),
         }
     }
 }
@@ -28,16 +42,35 @@ pub trait SettingsRepository: Send + Sync {
 
 pub struct SettingsService {
     repository: Box<dyn SettingsRepository>,
+    /// Where the values of secret env vars are kept
+    secrets: Box<dyn SecretStore>,
     settings: Settings,
 }
 
+/// Keychain account of a secret env var
+fn env_secret_account(set_name: &str, key: &str) -> String {
+    format!("env-set/{set_name}/{key}")
+}
+
+fn secret_error(e: anyhow::Error) -> SettingsServiceError {
+    SettingsServiceError::SecretStoreError(e.to_string())
+}
+
 impl SettingsService {
     pub fn new(repository: Box<dyn SettingsRepository>) -> Self {
+        Self::with_secret_store(repository, Box::new(KeychainStore))
+    }
+
+    pub fn with_secret_store(
+        repository: Box<dyn SettingsRepository>,
+        secrets: Box<dyn SecretStore>,
+    ) -> Self {
         let mut settings = repository.load().unwrap_or_default();
         clean_invalid_binary_paths(&mut settings);
 
         Self {
             repository,
+            secrets,
             settings,
         }
     }
@@ -57,6 +90,8 @@ impl SettingsService {
     "droid" => self.settings.agent_env_vars.droid.clone(),
     "qwen" => self.settings.agent_env_vars.qwen.clone(),
     "amp" => self.settings.agent_env_vars.amp.clone(),
+    "aider" => self.settings.agent_env_vars.aider.clone(),
+    "goose" => self.settings.agent_env_vars.goose.clone(),
     "terminal" => self.settings.agent_env_vars.terminal.clone(),
         _ => HashMap::new(),
         }
@@ -75,6 +110,8 @@ impl SettingsService {
     "droid" => self.settings.agent_env_vars.droid = env_vars,
     "qwen" => self.settings.agent_env_vars.qwen = env_vars,
     "amp" => self.settings.agent_env_vars.amp = env_vars,
+    "aider" => self.settings.agent_env_vars.aider = env_vars,
+    "goose" => self.settings.agent_env_vars.goose = env_vars,
     "terminal" => self.settings.agent_env_vars.terminal = env_vars,
     _ => {
     return Err(SettingsServiceError::UnknownAgentType(
@@ -119,6 +156,8 @@ impl SettingsService {
     "droid" => self.settings.agent_cli_args.droid.clone(),
     "qwen" => self.settings.agent_cli_args.qwen.clone(),
     "amp" => self.settings.agent_cli_args.amp.clone(),
+    "aider" => self.settings.agent_cli_args.aider.clone(),
+    "goose" => self.settings.agent_cli_args.goose.clone(),
         _ => String::new(),
         }
     }
@@ -145,6 +184,8 @@ impl SettingsService {
         "droid" => self.settings.agent_cli_args.droid = cli_args.clone(),
         "qwen" => self.settings.agent_cli_args.qwen = cli_args.clone(),
         "amp" => self.settings.agent_cli_args.amp = cli_args.clone(),
+        "aider" => self.settings.agent_cli_args.aider = cli_args.clone(),
+        "goose" => self.settings.agent_cli_args.goose = cli_args.clone(),
         _ => {
         let error = format!("Unknown agent type: {agent_type}");
         log::error!("Invalid agent type in set_agent_cli_args: {error}");
@@ -177,6 +218,8 @@ impl SettingsService {
     "droid" => self.settings.agent_initial_commands.droid.clone(),
     "qwen" => self.settings.agent_initial_commands.qwen.clone(),
     "amp" => self.settings.agent_initial_commands.amp.clone(),
+    "aider" => self.settings.agent_initial_commands.aider.clone(),
+    "goose" => self.settings.agent_initial_commands.goose.clone(),
     "terminal" => String::new(),
         _ => String::new(),
         }
@@ -200,6 +243,8 @@ impl SettingsService {
         "droid" => self.settings.agent_initial_commands.droid = initial_command.clone(),
         "qwen" => self.settings.agent_initial_commands.qwen = initial_command.clone(),
         "amp" => self.settings.agent_initial_commands.amp = initial_command.clone(),
+        "aider" => self.settings.agent_initial_commands.aider = initial_command.clone(),
+        "goose" => self.settings.agent_initial_commands.goose = initial_command.clone(),
         "terminal" => {}
         _ => {
         let error = format!("Unknown agent type: {agent_type}");
@@ -232,10 +277,58 @@ impl SettingsService {
         &mut self,
         terminal: TerminalSettings,
     ) -> Result<(), SettingsServiceError> {
+        if let Some(theme) = terminal.theme.as_deref() {
+            if !self.get_terminal_themes().iter().any(|t| t.name == theme) {
+                return Err(SettingsServiceError::InvalidTheme(format!(
+                    "No theme named '{theme}'"
+                )));
+            }
+        }
         self.settings.terminal = terminal;
         self.save()
     }
 
+    /// Built-in themes followed by the user's own
+    pub fn get_terminal_themes(&self) -> Vec<TerminalTheme> {
+        let mut themes = builtin_themes();
+        themes.extend(self.settings.terminal_themes.iter().cloned());
+        themes
+    }
+
+    /// Store `theme` in place of the user theme with its name
+    pub fn set_terminal_theme(
+        &mut self,
+        mut theme: TerminalTheme,
+    ) -> Result<(), SettingsServiceError> {
+        validate_terminal_theme(&theme)?;
+        theme.name = theme.name.trim().to_string();
+        theme.builtin = false;
+        match self
+            .settings
+            .terminal_themes
+            .iter_mut()
+            .find(|t| t.name == theme.name)
+        {
+            Some(existing) => *existing = theme,
+            None => self.settings.terminal_themes.push(theme),
+        }
+        self.save()
+    }
+
+    /// Remove a user theme; terminals using it go back to the default palette
+    pub fn delete_terminal_theme(&mut self, name: &str) -> Result<(), SettingsServiceError> {
+        if is_builtin_theme(name) {
+            return Err(SettingsServiceError::InvalidTheme(format!(
+                "'{name}' is a built-in theme"
+            )));
+        }
+        self.settings.terminal_themes.retain(|t| t.name != name);
+        if self.settings.terminal.theme.as_deref() == Some(name) {
+            self.settings.terminal.theme = None;
+        }
+        self.save()
+    }
+
     pub fn get_diff_view_preferences(&self) -> DiffViewPreferences {
         self.settings.diff_view.clone()
     }
@@ -268,7 +361,11 @@ impl SettingsService {
         &mut self,
         shortcuts: HashMap<String, Vec<String>>,
     ) -> Result<(), SettingsServiceError> {
-        self.settings.keyboard_shortcuts = shortcuts;
+        let validation = validate_shortcuts(&shortcuts);
+        if !validation.is_valid() {
+            return Err(SettingsServiceError::InvalidKeyboardShortcuts(validation));
+        }
+        self.settings.keyboard_shortcuts = validation.shortcuts;
         self.save()
     }
 
@@ -293,6 +390,15 @@ impl SettingsService {
         self.save()
     }
 
+    pub fn get_json_logs(&self) -> bool {
+        self.settings.json_logs
+    }
+
+    pub fn set_json_logs(&mut self, enabled: bool) -> Result<(), SettingsServiceError> {
+        self.settings.json_logs = enabled;
+        self.save()
+    }
+
     pub fn get_auto_update_enabled(&self) -> bool {
         self.settings.updater.auto_update_enabled
     }
@@ -311,6 +417,8 @@ impl SettingsService {
     "droid" => self.settings.agent_binaries.droid.clone(),
     "qwen" => self.settings.agent_binaries.qwen.clone(),
     "amp" => self.settings.agent_binaries.amp.clone(),
+    "aider" => self.settings.agent_binaries.aider.clone(),
+    "goose" => self.settings.agent_binaries.goose.clone(),
     "terminal" => None,
         _ => None,
         }
@@ -333,6 +441,8 @@ impl SettingsService {
         "droid" => self.settings.agent_binaries.droid = Some(config),
         "qwen" => self.settings.agent_binaries.qwen = Some(config),
         "amp" => self.settings.agent_binaries.amp = Some(config),
+        "aider" => self.settings.agent_binaries.aider = Some(config),
+        "goose" => self.settings.agent_binaries.goose = Some(config),
             _ => return Err(SettingsServiceError::UnknownAgentType(config.agent_name)),
         }
         self.save()
@@ -358,6 +468,12 @@ impl SettingsService {
         if let Some(config) = &self.settings.agent_binaries.qwen {
             configs.push(config.clone());
         }
+        if let Some(config) = &self.settings.agent_binaries.aider {
+            configs.push(config.clone());
+        }
+        if let Some(config) = &self.settings.agent_binaries.goose {
+            configs.push(config.clone());
+        }
         configs
     }
 
@@ -379,9 +495,184 @@ impl SettingsService {
             }
         }
 
+        // Custom agents name their binary in their definition
+        if let Some(custom) = custom_agent_config(agent_name) {
+            return Ok(custom.binary.trim().to_string());
+        }
+
         Ok(agent_name.to_string())
     }
 
+    /// Settings for `agent_type` with the project's and then the session's values applied
+    /// over the global ones. CLI args are replaced as a whole; environment variables are
+    /// resolved one by one.
+    pub fn resolve_effective_settings(
+        &self,
+        agent_type: &str,
+        project: &SettingsLayerValues,
+        session: Option<&SettingsLayerValues>,
+    ) -> EffectiveSettings {
+        let layers = [
+            Some((SettingsLayer::Project, project)),
+            session.map(|session| (SettingsLayer::Session, session)),
+        ];
+
+        let mut agent_cli_args = EffectiveValue {
+            value: self.get_agent_cli_args(agent_type),
+            source: SettingsLayer::Global,
+        };
+        // The agent's own variables win over those of its env sets
+        let mut global_env = self.resolve_env_sets(self.agent_env_set_names(agent_type));
+        global_env.extend(self.get_agent_env_vars(agent_type));
+        let mut env_vars: BTreeMap<String, EffectiveValue<String>> = global_env
+            .into_iter()
+            .map(|(key, value)| {
+                let value = EffectiveValue {
+                    value,
+                    source: SettingsLayer::Global,
+                };
+                (key, value)
+            })
+            .collect();
+
+        for (source, values) in layers.into_iter().flatten() {
+            if let Some(cli_args) = values.overrides.agent_cli_args.get(agent_type) {
+                agent_cli_args = EffectiveValue {
+                    value: cli_args.clone(),
+                    source,
+                };
+            }
+            for (key, value) in &values.env_vars {
+                let value = EffectiveValue {
+                    value: value.clone(),
+                    source,
+                };
+                env_vars.insert(key.clone(), value);
+            }
+        }
+
+        EffectiveSettings {
+            agent_type: agent_type.to_string(),
+            agent_cli_args,
+            env_vars,
+        }
+    }
+
+    pub fn get_env_sets(&self) -> Vec<EnvVarSet> {
+        self.settings.env_sets.clone()
+    }
+
+    /// Store `set` in place of the set with its name. Secret values move to the keychain;
+    /// a secret without a value keeps the one stored before.
+    pub fn set_env_set(&mut self, mut set: EnvVarSet) -> Result<(), SettingsServiceError> {
+        validate_env_set(&set)?;
+
+        for entry in set.vars.iter_mut().filter(|entry| entry.secret) {
+            if entry.value.is_empty() {
+                continue;
+            }
+            let value = std::mem::take(&mut entry.value);
+            self.secrets
+                .set(&env_secret_account(&set.name, &entry.key), &value)
+                .map_err(secret_error)?;
+            register_secret(&value);
+        }
+
+        let previous = self.settings.env_sets.iter().find(|s| s.name == set.name);
+        let dropped: Vec<String> = previous
+            .map(|previous| {
+                previous
+                    .vars
+                    .iter()
+                    .filter(|old| old.secret)
+                    .filter(|old| !set.vars.iter().any(|e| e.secret && e.key == old.key))
+                    .map(|old| old.key.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for key in dropped {
+            self.secrets
+                .delete(&env_secret_account(&set.name, &key))
+                .map_err(secret_error)?;
+        }
+
+        match self
+            .settings
+            .env_sets
+            .iter_mut()
+            .find(|s| s.name == set.name)
+        {
+            Some(existing) => *existing = set,
+            None => self.settings.env_sets.push(set),
+        }
+        self.save()
+    }
+
+    pub fn delete_env_set(&mut self, name: &str) -> Result<(), SettingsServiceError> {
+        let Some(index) = self.settings.env_sets.iter().position(|s| s.name == name) else {
+            return Ok(());
+        };
+        let set = self.settings.env_sets.remove(index);
+        for entry in set.vars.iter().filter(|entry| entry.secret) {
+            self.secrets
+                .delete(&env_secret_account(&set.name, &entry.key))
+                .map_err(secret_error)?;
+        }
+        self.save()
+    }
+
+    fn agent_env_set_names(&self, agent_type: &str) -> Vec<String> {
+        self.settings
+            .env_sets
+            .iter()
+            .filter(|set| set.agents.iter().any(|agent| agent == agent_type))
+            .map(|set| set.name.clone())
+            .collect()
+    }
+
+    /// Variables of the named sets, later sets winning, with secrets read from the
+    /// keychain and registered for masking. Unknown sets and unreadable secrets are
+    /// skipped.
+    pub fn resolve_env_sets<I, S>(&self, names: I) -> HashMap<String, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut env = HashMap::new();
+        for name in names {
+            let name = name.as_ref();
+            let Some(set) = self.settings.env_sets.iter().find(|s| s.name == name) else {
+                log::warn!("Env set '{name}' does not exist");
+                continue;
+            };
+            for entry in &set.vars {
+                if !entry.secret {
+                    env.insert(entry.key.clone(), entry.value.clone());
+                    continue;
+                }
+                match self.secrets.get(&env_secret_account(name, &entry.key)) {
+                    Ok(Some(value)) => {
+                        register_secret(&value);
+                        env.insert(entry.key.clone(), value);
+                    }
+                    Ok(None) => log::warn!(
+                        "Secret {} of env set '{name}' is not in the keychain",
+                        entry.key
+                    ),
+                    Err(e) => log::warn!(
+                        "Failed to read secret {} of env set '{name}': {e}",
+                        entry.key
+                    ),
+                }
+            }
+        }
+        env
+    }
+
+    pub fn get_custom_agents(&self) -> HashMap<String, CustomAgentConfig> {
+        self.settings.custom_agents.clone()
+    }
+
     pub fn get_amp_mcp_servers(&self) -> HashMap<String, McpServerConfig> {
         self.settings.amp_mcp_servers.clone()
     }
@@ -445,6 +736,18 @@ mod tests {
         assert!(repo_handle.snapshot().updater.auto_update_enabled);
     }
 
+    #[test]
+    fn set_json_logs_persists_value() {
+        let repo = InMemoryRepository::default();
+        let repo_handle = repo.clone();
+        let mut service = SettingsService::new(Box::new(repo));
+
+        assert!(!service.get_json_logs());
+        assert!(service.set_json_logs(true).is_ok());
+        assert!(service.get_json_logs());
+        assert!(repo_handle.snapshot().json_logs);
+    }
+
     #[test]
     fn set_agent_cli_args_supports_droid() {
         let repo = InMemoryRepository::default();
@@ -575,6 +878,56 @@ mod tests {
         assert_eq!(service.get_agent_env_vars("terminal"), vars);
     }
 
+    #[test]
+    fn effective_settings_prefer_session_then_project_values() {
+        let repo = InMemoryRepository::default();
+        let mut service = SettingsService::new(Box::new(repo));
+        service
+            .set_agent_cli_args("claude", "--verbose".to_string())
+            .unwrap();
+        service
+            .set_agent_env_vars(
+                "claude",
+                HashMap::from([
+                    ("API_URL".to_string(), "https://global".to_string()),
+                    ("LOG".to_string(), "info".to_string()),
+                ]),
+            )
+            .unwrap();
+
+        let project = SettingsLayerValues {
+            overrides: SettingsOverrides {
+                agent_cli_args: HashMap::from([("claude".to_string(), "--fast".to_string())]),
+            },
+            env_vars: HashMap::from([
+                ("API_URL".to_string(), "https://project".to_string()),
+                ("LOG".to_string(), "debug".to_string()),
+            ]),
+        };
+        let session = SettingsLayerValues {
+            overrides: SettingsOverrides::default(),
+            env_vars: HashMap::from([("LOG".to_string(), "trace".to_string())]),
+        };
+
+        let effective = service.resolve_effective_settings("claude", &project, Some(&session));
+        assert_eq!(effective.agent_cli_args.value, "--fast");
+        assert_eq!(effective.agent_cli_args.source, SettingsLayer::Project);
+        assert_eq!(
+            effective.env_vars["API_URL"],
+            EffectiveValue {
+                value: "https://project".to_string(),
+                source: SettingsLayer::Project,
+            }
+        );
+        assert_eq!(effective.env_vars["LOG"].value, "trace");
+        assert_eq!(effective.env_vars["LOG"].source, SettingsLayer::Session);
+
+        let effective = service.resolve_effective_settings("codex", &project, None);
+        assert_eq!(effective.agent_cli_args.value, "");
+        assert_eq!(effective.agent_cli_args.source, SettingsLayer::Global);
+        assert_eq!(effective.env_vars["LOG"].source, SettingsLayer::Project);
+    }
+
     #[test]
     fn set_agent_binary_config_supports_droid() {
         let repo = InMemoryRepository::default();
@@ -586,6 +939,7 @@ mod tests {
             custom_path: Some("/custom/droid".to_string()),
             auto_detect: false,
             detected_binaries: vec![],
+            detected_version: None,
         };
 
         service
@@ -606,6 +960,7 @@ mod tests {
             custom_path: Some("/custom/qwen".to_string()),
             auto_detect: false,
             detected_binaries: vec![],
+            detected_version: None,
         };
 
         service
@@ -614,4 +969,175 @@ mod tests {
 
         assert_eq!(repo_handle.snapshot().agent_binaries.qwen, Some(config));
     }
+
+    #[test]
+    fn set_keyboard_shortcuts_rejects_conflicts() {
+        let repo = InMemoryRepository::default();
+        let repo_handle = repo.clone();
+        let mut service = SettingsService::new(Box::new(repo));
+
+        let shortcuts = HashMap::from([
+            ("newSession".to_string(), vec!["mod+n".to_string()]),
+            ("newSpec".to_string(), vec!["Mod+Shift+N".to_string()]),
+        ]);
+        service
+            .set_keyboard_shortcuts(shortcuts)
+            .expect("should accept distinct shortcuts");
+        assert_eq!(
+            repo_handle.snapshot().keyboard_shortcuts["newSession"],
+            vec!["Mod+N".to_string()]
+        );
+
+        let conflicting = HashMap::from([
+            ("newSession".to_string(), vec!["Mod+N".to_string()]),
+            ("newSpec".to_string(), vec!["Mod+N".to_string()]),
+        ]);
+        let err = service.set_keyboard_shortcuts(conflicting).unwrap_err();
+        assert!(matches!(
+            &err,
+            SettingsServiceError::InvalidKeyboardShortcuts(validation)
+                if validation.conflicts.len() == 1
+        ));
+        assert_eq!(
+            repo_handle.snapshot().keyboard_shortcuts["newSpec"],
+            vec!["Mod+Shift+N".to_string()]
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct InMemorySecrets {
+        values: Arc<Mutex<HashMap<String, String>>>,
+    }
+
+    impl SecretStore for InMemorySecrets {
+        fn get(&self, account: &str) -> anyhow::Result<Option<String>> {
+            Ok(self.values.lock().unwrap().get(account).cloned())
+        }
+
+        fn set(&self, account: &str, value: &str) -> anyhow::Result<()> {
+            self.values
+                .lock()
+                .unwrap()
+                .insert(account.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, account: &str) -> anyhow::Result<()> {
+            self.values.lock().unwrap().remove(account);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn env_sets_keep_secret_values_in_the_secret_store() {
+        let repo = InMemoryRepository::default();
+        let repo_handle = repo.clone();
+        let secrets = InMemorySecrets::default();
+        let mut service =
+            SettingsService::with_secret_store(Box::new(repo), Box::new(secrets.clone()));
+        let entry = |key: &str, value: &str, secret: bool| EnvVarEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+            secret,
+        };
+
+        service
+            .set_env_set(EnvVarSet {
+                name: "openai".to_string(),
+                vars: vec![
+                    entry("OPENAI_BASE_URL", "https://api.example.com", false),
+                    entry("OPENAI_API_KEY", "sk-live-7f3e2d", true),
+                ],
+                agents: vec!["codex".to_string()],
+            })
+            .expect("should store env set");
+
+        assert_eq!(repo_handle.snapshot().env_sets[0].vars[1].value, "");
+        assert_eq!(
+            secrets.get("env-set/openai/OPENAI_API_KEY").unwrap(),
+            Some("sk-live-7f3e2d".to_string())
+        );
+
+        let codex =
+            service.resolve_effective_settings("codex", &SettingsLayerValues::default(), None);
+        assert_eq!(codex.env_vars["OPENAI_API_KEY"].value, "sk-live-7f3e2d");
+        assert_eq!(
+            codex.env_vars["OPENAI_BASE_URL"].source,
+            SettingsLayer::Global
+        );
+        let claude =
+            service.resolve_effective_settings("claude", &SettingsLayerValues::default(), None);
+        assert!(!claude.env_vars.contains_key("OPENAI_API_KEY"));
+
+        // Saving the set again without the secret's value keeps it
+        service
+            .set_env_set(EnvVarSet {
+                name: "openai".to_string(),
+                vars: vec![entry("OPENAI_API_KEY", "", true)],
+                agents: vec!["codex".to_string()],
+            })
+            .unwrap();
+        assert_eq!(
+            service.resolve_env_sets(["openai"]).get("OPENAI_API_KEY"),
+            Some(&"sk-live-7f3e2d".to_string())
+        );
+
+        service.delete_env_set("openai").unwrap();
+        assert!(secrets.values.lock().unwrap().is_empty());
+        assert!(service.get_env_sets().is_empty());
+
+        let invalid = service.set_env_set(EnvVarSet {
+            name: "broken".to_string(),
+            vars: vec![entry("NOT-VALID", "x", false)],
+            agents: Vec::new(),
+        });
+        assert!(matches!(
+            invalid,
+            Err(SettingsServiceError::InvalidEnvSet(_))
+        ));
+    }
+
+    #[test]
+    fn terminal_themes_extend_the_builtin_ones() {
+        let repo = InMemoryRepository::default();
+        let repo_handle = repo.clone();
+        let mut service = SettingsService::new(Box::new(repo));
+
+        let theme = TerminalTheme {
+            name: "paper".to_string(),
+            colors: BTreeMap::from([("background".to_string(), "#fdf6e3".to_string())]),
+            builtin: false,
+        };
+        service.set_terminal_theme(theme.clone()).unwrap();
+        assert_eq!(repo_handle.snapshot().terminal_themes, vec![theme]);
+        assert!(service
+            .get_terminal_themes()
+            .iter()
+            .any(|t| t.name == "solarized-dark" && t.builtin));
+
+        let mut terminal = service.get_terminal_settings();
+        terminal.theme = Some("paper".to_string());
+        service.set_terminal_settings(terminal.clone()).unwrap();
+        terminal.theme = Some("missing".to_string());
+        assert!(matches!(
+            service.set_terminal_settings(terminal),
+            Err(SettingsServiceError::InvalidTheme(_))
+        ));
+
+        let builtin = TerminalTheme {
+            name: "solarized-dark".to_string(),
+            ..Default::default()
+        };
+        assert!(service.set_terminal_theme(builtin).is_err());
+        let bad_color = TerminalTheme {
+            name: "neon".to_string(),
+            colors: BTreeMap::from([("cursor".to_string(), "pink".to_string())]),
+            builtin: false,
+        };
+        assert!(service.set_terminal_theme(bad_color).is_err());
+
+        service.delete_terminal_theme("paper").unwrap();
+        assert_eq!(service.get_terminal_settings().theme, None);
+        assert!(repo_handle.snapshot().terminal_themes.is_empty());
+    }
 }
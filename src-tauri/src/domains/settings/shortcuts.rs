@@ -0,0 +1,508 @@
+//! Validation of keyboard shortcut bindings. A binding is one key combination such as
+//! `Mod+Shift+N`, or a chord of combinations pressed one after another such as
+//! `Mod+K Mod+S`. Bindings are normalized the way the frontend writes them, and two
+//! actions that can be triggered by the same keys in the same place are a conflict.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Modifier {
+    /// Meta on macOS, Ctrl elsewhere
+    Mod,
+    Meta,
+    Ctrl,
+    Alt,
+    Shift,
+}
+
+impl Modifier {
+    fn parse(token: &str) -> Option<Self> {
+        match token.to_ascii_lowercase().as_str() {
+            "mod" | "primary" => Some(Self::Mod),
+            "meta" | "cmd" | "command" => Some(Self::Meta),
+            "ctrl" | "control" => Some(Self::Ctrl),
+            "alt" | "option" => Some(Self::Alt),
+            "shift" => Some(Self::Shift),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Mod => "Mod",
+            Self::Meta => "Meta",
+            Self::Ctrl => "Ctrl",
+            Self::Alt => "Alt",
+            Self::Shift => "Shift",
+        }
+    }
+
+    /// The physical modifier on macOS or on other platforms
+    fn resolve(self, mac: bool) -> Self {
+        match (self, mac) {
+            (Self::Mod, true) => Self::Meta,
+            (Self::Mod, false) => Self::Ctrl,
+            (modifier, _) => modifier,
+        }
+    }
+}
+
+/// Key names as the frontend's shortcut matcher spells them
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("arrowdown", "ArrowDown"),
+    ("arrowleft", "ArrowLeft"),
+    ("arrowright", "ArrowRight"),
+    ("arrowup", "ArrowUp"),
+    ("esc", "Escape"),
+    ("escape", "Escape"),
+    ("return", "Enter"),
+    ("enter", "Enter"),
+    ("space", "Space"),
+    ("spacebar", "Space"),
+    ("plus", "="),
+    ("add", "="),
+    ("minus", "-"),
+    ("subtract", "-"),
+    ("slash", "/"),
+    ("backslash", "\\"),
+    ("bracketleft", "["),
+    ("bracketright", "]"),
+    ("semicolon", ";"),
+    ("quote", "'"),
+];
+
+fn normalize_key(token: &str) -> String {
+    let key = token
+        .strip_prefix("Key")
+        .or_else(|| token.strip_prefix("Digit"))
+        .unwrap_or(token);
+    let lowered = key.to_ascii_lowercase();
+    if let Some((_, alias)) = KEY_ALIASES.iter().find(|(name, _)| *name == lowered) {
+        return alias.to_string();
+    }
+    if key == "+" {
+        // Plus and equals share a key, so Mod+= works without Shift
+        return "=".to_string();
+    }
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(first) if key.chars().count() == 1 => first.to_uppercase().collect(),
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// One key press with the modifiers it requires and the ones it accepts either way
+/// (written in brackets, e.g. `Mod+[Shift]+=`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyCombo {
+    key: String,
+    required: BTreeSet<Modifier>,
+    optional: BTreeSet<Modifier>,
+}
+
+impl KeyCombo {
+    fn parse(combo: &str) -> Result<Self, String> {
+        let mut key = None;
+        let mut required = BTreeSet::new();
+        let mut optional = BTreeSet::new();
+        for token in combo.split('+').map(str::trim).filter(|t| !t.is_empty()) {
+            let (token, is_optional) =
+                match token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                    Some(inner) => (inner.trim(), true),
+                    None => (token, false),
+                };
+            match Modifier::parse(token) {
+                Some(modifier) if is_optional => {
+                    optional.insert(modifier);
+                }
+                Some(modifier) => {
+                    required.insert(modifier);
+                }
+                // An optional key is just the key
+                None => {
+                    if let Some(existing) = key.replace(normalize_key(token)) {
+                        return Err(format!("'{combo}' has more than one key ({existing})"));
+                    }
+                }
+            }
+        }
+        let key = key.ok_or_else(|| format!("'{combo}' has no key besides modifiers"))?;
+        optional.retain(|modifier| !required.contains(modifier));
+        Ok(Self {
+            key,
+            required,
+            optional,
+        })
+    }
+
+    /// Whether some key press on the given platform triggers both combos.
+    fn overlaps_on(&self, other: &Self, mac: bool) -> bool {
+        // Each physical modifier is accepted pressed, released, or both. Like the
+        // frontend matcher, Mod also lets the other platform's primary key be held.
+        let states = |combo: &Self, modifier: Modifier| {
+            let required = combo.required.iter().any(|m| m.resolve(mac) == modifier);
+            let optional = combo
+                .optional
+                .iter()
+                .chain(&combo.required)
+                .any(|m| m.resolve(mac) == modifier || m.resolve(!mac) == modifier);
+            (required || optional, !required)
+        };
+        self.key == other.key
+            && [
+                Modifier::Meta,
+                Modifier::Ctrl,
+                Modifier::Alt,
+                Modifier::Shift,
+            ]
+            .into_iter()
+            .all(|modifier| {
+                let (a_down, a_up) = states(self, modifier);
+                let (b_down, b_up) = states(other, modifier);
+                (a_down && b_down) || (a_up && b_up)
+            })
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.overlaps_on(other, true) || self.overlaps_on(other, false)
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = self.required.union(&self.optional).collect::<BTreeSet<_>>();
+        for modifier in modifiers {
+            if self.optional.contains(modifier) {
+                write!(f, "[{}]+", modifier.name())?;
+            } else {
+                write!(f, "{}+", modifier.name())?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// A binding: one key combination, or several pressed in sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut(Vec<KeyCombo>);
+
+impl Shortcut {
+    pub fn parse(binding: &str) -> Result<Self, String> {
+        // Spaces around `+` belong to one combination; other spaces separate a chord's steps
+        let binding = binding
+            .split('+')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("+");
+        let combos = binding
+            .split_whitespace()
+            .map(KeyCombo::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if combos.is_empty() {
+            return Err("Binding is empty".to_string());
+        }
+        Ok(Self(combos))
+    }
+
+    /// Whether the key presses of `self` start with those of `other`.
+    fn starts_with(&self, other: &Self) -> bool {
+        self.0.len() >= other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| a.overlaps(b))
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let combos: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        write!(f, "{}", combos.join(" "))
+    }
+}
+
+/// Where an action's shortcut is handled. Actions in different non-global scopes may
+/// share keys because only one of them is focused at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShortcutScope {
+    Global,
+    Terminal,
+    DiffViewer,
+    DiffComment,
+    SpecEditor,
+}
+
+impl ShortcutScope {
+    fn of(action: &str) -> Self {
+        match action {
+            "insertTerminalNewLine" | "openTerminalSearch" => Self::Terminal,
+            "finishReview" | "openDiffSearch" => Self::DiffViewer,
+            "submitDiffComment" => Self::DiffComment,
+            "runSpecAgent" => Self::SpecEditor,
+            _ => Self::Global,
+        }
+    }
+
+    fn overlaps(self, other: Self) -> bool {
+        self == other || self == Self::Global || other == Self::Global
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutConflictKind {
+    /// Both bindings fire on the same keys
+    Duplicate,
+    /// `binding` is the start of the chord `other_binding`, which can then never complete
+    ChordPrefix,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShortcutConflict {
+    pub action: String,
+    pub binding: String,
+    pub other_action: String,
+    pub other_binding: String,
+    pub kind: ShortcutConflictKind,
+}
+
+impl fmt::Display for ShortcutConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ShortcutConflictKind::Duplicate => write!(
+                f,
+                "{} of {} is also bound to {}",
+                self.binding, self.action, self.other_action
+            ),
+            ShortcutConflictKind::ChordPrefix => write!(
+                f,
+                "{} of {} blocks the chord {} of {}",
+                self.binding, self.action, self.other_binding, self.other_action
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidShortcut {
+    pub action: String,
+    pub binding: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ShortcutValidation {
+    /// The valid bindings per action, normalized and without duplicates
+    pub shortcuts: HashMap<String, Vec<String>>,
+    pub invalid: Vec<InvalidShortcut>,
+    pub conflicts: Vec<ShortcutConflict>,
+}
+
+impl ShortcutValidation {
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+impl fmt::Display for ShortcutValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems: Vec<String> = self
+            .invalid
+            .iter()
+            .map(|invalid| {
+                format!(
+                    "'{}' of {} is invalid: {}",
+                    invalid.binding, invalid.action, invalid.reason
+                )
+            })
+            .chain(self.conflicts.iter().map(ToString::to_string))
+            .collect();
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+/// The conflict of `binding` with `other`, whose key presses start with those of `binding`
+fn conflict(
+    action: &str,
+    binding: &Shortcut,
+    other_action: &str,
+    other: &Shortcut,
+) -> ShortcutConflict {
+    let kind = if binding.0.len() == other.0.len() {
+        ShortcutConflictKind::Duplicate
+    } else {
+        ShortcutConflictKind::ChordPrefix
+    };
+    ShortcutConflict {
+        action: action.to_string(),
+        binding: binding.to_string(),
+        other_action: other_action.to_string(),
+        other_binding: other.to_string(),
+        kind,
+    }
+}
+
+/// Normalize every binding and report the ones that cannot be parsed or that collide
+/// with another action's binding in the same scope.
+pub fn validate_shortcuts(shortcuts: &HashMap<String, Vec<String>>) -> ShortcutValidation {
+    let mut validation = ShortcutValidation::default();
+    let mut parsed: BTreeMap<&str, Vec<Shortcut>> = BTreeMap::new();
+
+    let sorted: BTreeMap<&String, &Vec<String>> = shortcuts.iter().collect();
+    for (action, bindings) in sorted {
+        let mut normalized: Vec<Shortcut> = Vec::new();
+        for binding in bindings {
+            match Shortcut::parse(binding) {
+                Ok(shortcut) if !normalized.contains(&shortcut) => normalized.push(shortcut),
+                Ok(_) => {}
+                Err(reason) => validation.invalid.push(InvalidShortcut {
+                    action: action.clone(),
+                    binding: binding.clone(),
+                    reason,
+                }),
+            }
+        }
+        validation.shortcuts.insert(
+            action.clone(),
+            normalized.iter().map(ToString::to_string).collect(),
+        );
+        parsed.insert(action, normalized);
+    }
+
+    let actions: Vec<(&str, Vec<Shortcut>)> = parsed.into_iter().collect();
+    for (i, (action, bindings)) in actions.iter().enumerate() {
+        for (other_action, other_bindings) in &actions[i + 1..] {
+            if !ShortcutScope::of(action).overlaps(ShortcutScope::of(other_action)) {
+                continue;
+            }
+            for binding in bindings {
+                for other in other_bindings {
+                    if other.starts_with(binding) {
+                        validation
+                            .conflicts
+                            .push(conflict(action, binding, other_action, other));
+                    } else if binding.starts_with(other) {
+                        validation
+                            .conflicts
+                            .push(conflict(other_action, other, action, binding));
+                    }
+                }
+            }
+        }
+    }
+
+    validation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(action, bindings)| {
+                (
+                    action.to_string(),
+                    bindings.iter().map(|b| b.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_bindings_and_chords() {
+        let shortcut = |binding: &str| Shortcut::parse(binding).unwrap().to_string();
+        assert_eq!(shortcut("shift+cmd+KeyN"), "Meta+Shift+N");
+        assert_eq!(shortcut("primary+[shift]+plus"), "Mod+[Shift]+=");
+        assert_eq!(shortcut("ctrl+esc"), "Ctrl+Escape");
+        assert_eq!(shortcut("  mod + Shift + d "), "Mod+Shift+D");
+        assert_eq!(shortcut("  mod+k   mod+Digit1 "), "Mod+K Mod+1");
+        assert_eq!(Shortcut::parse("Mod+K Mod+S").unwrap().0.len(), 2);
+
+        assert!(Shortcut::parse("Mod+Shift").is_err());
+        assert!(Shortcut::parse("Mod+A+B").is_err());
+        assert!(Shortcut::parse("Mod+[X]+A").is_err());
+        assert!(Shortcut::parse("   ").is_err());
+    }
+
+    #[test]
+    fn reports_duplicates_across_actions() {
+        let validation = validate_shortcuts(&config(&[
+            ("newSession", &["Mod+N", "mod+n"]),
+            ("newSpec", &["Meta+N"]),
+            ("focusClaude", &["Mod+[Shift]+T"]),
+            ("focusTerminal", &["Mod+Shift+T"]),
+            ("openDiffViewer", &["Mod+Alt+N"]),
+        ]));
+
+        assert_eq!(validation.shortcuts["newSession"], vec!["Mod+N"]);
+        assert!(validation.invalid.is_empty());
+        assert_eq!(
+            validation.conflicts,
+            vec![
+                ShortcutConflict {
+                    action: "focusClaude".to_string(),
+                    binding: "Mod+[Shift]+T".to_string(),
+                    other_action: "focusTerminal".to_string(),
+                    other_binding: "Mod+Shift+T".to_string(),
+                    kind: ShortcutConflictKind::Duplicate,
+                },
+                ShortcutConflict {
+                    action: "newSession".to_string(),
+                    binding: "Mod+N".to_string(),
+                    other_action: "newSpec".to_string(),
+                    other_binding: "Meta+N".to_string(),
+                    kind: ShortcutConflictKind::Duplicate,
+                },
+            ]
+        );
+        assert!(!validation.is_valid());
+    }
+
+    #[test]
+    fn reports_chord_prefixes_and_allows_scoped_reuse() {
+        let validation = validate_shortcuts(&config(&[
+            ("openSettings", &["Mod+K Mod+S"]),
+            ("openKeymap", &["Mod+K"]),
+            ("insertTerminalNewLine", &["Mod+Enter"]),
+            ("finishReview", &["Mod+Enter"]),
+            ("openTerminalSearch", &["Mod+F"]),
+            ("openDiffSearch", &["Mod+F"]),
+            ("switchOrchestrator", &["Mod+K Mod+T"]),
+        ]));
+
+        assert_eq!(
+            validation.conflicts,
+            vec![
+                ShortcutConflict {
+                    action: "openKeymap".to_string(),
+                    binding: "Mod+K".to_string(),
+                    other_action: "openSettings".to_string(),
+                    other_binding: "Mod+K Mod+S".to_string(),
+                    kind: ShortcutConflictKind::ChordPrefix,
+                },
+                ShortcutConflict {
+                    action: "openKeymap".to_string(),
+                    binding: "Mod+K".to_string(),
+                    other_action: "switchOrchestrator".to_string(),
+                    other_binding: "Mod+K Mod+T".to_string(),
+                    kind: ShortcutConflictKind::ChordPrefix,
+                },
+            ]
+        );
+        assert_eq!(
+            validation.to_string(),
+            "Mod+K of openKeymap blocks the chord Mod+K Mod+S of openSettings; \
+             Mod+K of openKeymap blocks the chord Mod+K Mod+T of switchOrchestrator"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_bindings_and_keeps_the_rest() {
+        let validation = validate_shortcuts(&config(&[("newSession", &["Mod+N", "Mod+Shift"])]));
+        assert_eq!(validation.shortcuts["newSession"], vec!["Mod+N"]);
+        assert_eq!(validation.invalid.len(), 1);
+        assert_eq!(validation.invalid[0].binding, "Mod+Shift");
+        assert!(validation.conflicts.is_empty());
+    }
+}
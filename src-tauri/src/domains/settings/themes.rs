@@ -0,0 +1,103 @@
+//! Terminal themes shipped with the app; user-defined ones live in the settings file.
+
+use super::types::TerminalTheme;
+
+/// Keys of xterm.js `ITheme` a terminal theme may set
+pub const THEME_COLOR_KEYS: &[&str] = &[
+    "background",
+    "foreground",
+    "cursor",
+    "cursorAccent",
+    "selectionBackground",
+    "selectionForeground",
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "brightBlack",
+    "brightRed",
+    "brightGreen",
+    "brightYellow",
+    "brightBlue",
+    "brightMagenta",
+    "brightCyan",
+    "brightWhite",
+];
+
+/// The renderer's own palette
+pub const DEFAULT_THEME: &str = "schaltwerk";
+
+const SOLARIZED_DARK: &[(&str, &str)] = &[
+    ("background", "#002b36"),
+    ("foreground", "#839496"),
+    ("cursor", "#93a1a1"),
+    ("cursorAccent", "#002b36"),
+    ("selectionBackground", "#073642"),
+    ("black", "#073642"),
+    ("red", "#dc322f"),
+    ("green", "#859900"),
+    ("yellow", "#b58900"),
+    ("blue", "#268bd2"),
+    ("magenta", "#d33682"),
+    ("cyan", "#2aa198"),
+    ("white", "#eee8d5"),
+    ("brightBlack", "#002b36"),
+    ("brightRed", "#cb4b16"),
+    ("brightGreen", "#586e75"),
+    ("brightYellow", "#657b83"),
+    ("brightBlue", "#839496"),
+    ("brightMagenta", "#6c71c4"),
+    ("brightCyan", "#93a1a1"),
+    ("brightWhite", "#fdf6e3"),
+];
+
+pub fn builtin_themes() -> Vec<TerminalTheme> {
+    [(DEFAULT_THEME, &[][..]), ("solarized-dark", SOLARIZED_DARK)]
+        .into_iter()
+        .map(|(name, colors)| TerminalTheme {
+            name: name.to_string(),
+            colors: colors
+                .iter()
+                .map(|(key, color)| (key.to_string(), color.to_string()))
+                .collect(),
+            builtin: true,
+        })
+        .collect()
+}
+
+pub fn is_builtin_theme(name: &str) -> bool {
+    builtin_themes().iter().any(|theme| theme.name == name)
+}
+
+/// `#rgb`, `#rrggbb` or `#rrggbbaa`
+pub fn is_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_use_known_keys_and_hex_colors() {
+        let themes = builtin_themes();
+        assert_eq!(themes[0].name, DEFAULT_THEME);
+        assert!(themes[0].colors.is_empty());
+        for theme in &themes {
+            assert!(theme.builtin);
+            for (key, color) in &theme.colors {
+                assert!(THEME_COLOR_KEYS.contains(&key.as_str()), "{key}");
+                assert!(is_hex_color(color), "{color}");
+            }
+        }
+        assert!(is_builtin_theme("solarized-dark"));
+        assert!(!is_hex_color("#12345"));
+        assert!(!is_hex_color("red"));
+    }
+}
@@ -1,6 +1,16 @@
 use crate::binary_detector::DetectedBinary;
+use crate::domains::agents::custom::CustomAgentConfig;
+use crate::domains::sessions::activity::AutoNudgeConfig;
+use crate::domains::sessions::lifecycle::AgentRestartConfig;
+use crate::domains::terminal::idle_detection::{
+    default_prompt_patterns, DEFAULT_IDLE_THRESHOLD_MS,
+};
+use crate::domains::terminal::persistence::TerminalPersistenceMode;
+use crate::domains::terminal::process_tree::DEFAULT_KILL_GRACE_PERIOD_MS;
+use crate::domains::terminal::resource_limits::AgentResourceLimits;
+use crate::domains::terminal::scrollback::ScrollbackLimits;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
@@ -29,6 +39,10 @@ pub struct AgentCliArgs {
     pub qwen: String,
     #[serde(default)]
     pub amp: String,
+    #[serde(default)]
+    pub aider: String,
+    #[serde(default)]
+    pub goose: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -41,6 +55,10 @@ pub struct AgentInitialCommands {
     pub qwen: String,
     #[serde(default)]
     pub amp: String,
+    #[serde(default)]
+    pub aider: String,
+    #[serde(default)]
+    pub goose: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -53,6 +71,10 @@ pub struct AgentEnvVars {
     pub qwen: HashMap<String, String>,
     #[serde(default)]
     pub amp: HashMap<String, String>,
+    #[serde(default)]
+    pub aider: HashMap<String, String>,
+    #[serde(default)]
+    pub goose: HashMap<String, String>,
     pub terminal: HashMap<String, String>,
 }
 
@@ -95,6 +117,15 @@ pub struct SessionPreferences {
     pub auto_commit_on_review: bool,
     #[serde(default)]
     pub skip_confirmation_modals: bool,
+    /// Follow-up message for session agents that sit idle too long
+    #[serde(default)]
+    pub auto_nudge: AutoNudgeConfig,
+    /// Automatic restarts of session agents that crash
+    #[serde(default)]
+    pub agent_restart: AgentRestartConfig,
+    /// Priority, memory and CPU caps for agent processes
+    #[serde(default)]
+    pub agent_resource_limits: AgentResourceLimits,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -120,6 +151,41 @@ pub struct TerminalSettings {
     pub font_family: Option<String>,
     #[serde(default = "default_true")]
     pub webgl_enabled: bool,
+    /// Pause a terminal's output stream when it exceeds `output_max_bytes_per_second`
+    #[serde(default = "default_true")]
+    pub output_throttle_enabled: bool,
+    #[serde(default = "default_output_max_bytes_per_second")]
+    pub output_max_bytes_per_second: u64,
+    /// Keep session terminals alive in tmux or screen across app restarts
+    #[serde(default)]
+    pub persistence: TerminalPersistenceMode,
+    /// Quiet time after which an agent terminal counts as idle
+    #[serde(default = "default_idle_threshold_ms")]
+    pub idle_threshold_ms: u64,
+    /// Per agent type, screen patterns that mean the agent waits for input
+    #[serde(default = "default_prompt_patterns")]
+    pub idle_prompt_patterns: HashMap<String, Vec<String>>,
+    /// Output bytes kept for agent, shell and run terminals
+    #[serde(default)]
+    pub scrollback: ScrollbackLimits,
+    /// Time a closed terminal's processes get after SIGTERM before they are SIGKILLed
+    #[serde(default = "default_kill_grace_period_ms")]
+    pub kill_grace_period_ms: u64,
+    /// Name of the terminal theme; the renderer's default palette when unset
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+fn default_idle_threshold_ms() -> u64 {
+    DEFAULT_IDLE_THRESHOLD_MS
+}
+
+fn default_kill_grace_period_ms() -> u64 {
+    DEFAULT_KILL_GRACE_PERIOD_MS
+}
+
+fn default_output_max_bytes_per_second() -> u64 {
+    4 * 1024 * 1024
 }
 
 impl Default for TerminalSettings {
@@ -129,16 +195,39 @@ impl Default for TerminalSettings {
             shell_args: Vec::new(),
             font_family: None,
             webgl_enabled: true,
+            output_throttle_enabled: true,
+            output_max_bytes_per_second: default_output_max_bytes_per_second(),
+            persistence: TerminalPersistenceMode::Off,
+            idle_threshold_ms: DEFAULT_IDLE_THRESHOLD_MS,
+            idle_prompt_patterns: default_prompt_patterns(),
+            scrollback: ScrollbackLimits::default(),
+            kill_grace_period_ms: DEFAULT_KILL_GRACE_PERIOD_MS,
+            theme: None,
         }
     }
 }
 
+/// Terminal colors keyed like xterm.js `ITheme` (`background`, `cursor`, `brightBlue`, ...);
+/// keys a theme leaves out keep the renderer's default palette
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct TerminalTheme {
+    pub name: String,
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+    /// Shipped with the app; built-in themes cannot be changed or removed
+    #[serde(default)]
+    pub builtin: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AgentBinaryConfig {
     pub agent_name: String,
     pub custom_path: Option<String>,
     pub auto_detect: bool,
     pub detected_binaries: Vec<DetectedBinary>,
+    /// `--version` output of the binary in use: the custom path, else the recommended one
+    #[serde(default)]
+    pub detected_version: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -151,6 +240,10 @@ pub struct AgentBinaryConfigs {
     pub qwen: Option<AgentBinaryConfig>,
     #[serde(default)]
     pub amp: Option<AgentBinaryConfig>,
+    #[serde(default)]
+    pub aider: Option<AgentBinaryConfig>,
+    #[serde(default)]
+    pub goose: Option<AgentBinaryConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -172,4 +265,119 @@ pub struct Settings {
     pub tutorial_completed: bool,
     #[serde(default)]
     pub amp_mcp_servers: HashMap<String, McpServerConfig>,
+    /// User-defined agents by id, next to the built-in ones
+    #[serde(default)]
+    pub custom_agents: HashMap<String, CustomAgentConfig>,
+    #[serde(default)]
+    pub env_sets: Vec<EnvVarSet>,
+    /// User-defined terminal themes, next to the built-in ones
+    #[serde(default)]
+    pub terminal_themes: Vec<TerminalTheme>,
+    /// Write log lines as JSON objects; `SCHALTWERK_LOG_FORMAT` overrides it
+    #[serde(default)]
+    pub json_logs: bool,
+}
+
+/// A named group of environment variables that agents and projects use together
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct EnvVarSet {
+    pub name: String,
+    #[serde(default)]
+    pub vars: Vec<EnvVarEntry>,
+    /// Agent types that get the set in every project
+    #[serde(default)]
+    pub agents: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct EnvVarEntry {
+    pub key: String,
+    /// Always empty for secrets, whose value is kept in the OS keychain
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// Where a setting's value comes from. Later layers take precedence: a session's value
+/// beats the project's, which beats the global one.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsLayer {
+    Global,
+    Project,
+    Session,
+}
+
+/// Values a project or a session sets instead of the layer below, by agent type
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct SettingsOverrides {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub agent_cli_args: HashMap<String, String>,
+}
+
+/// Everything one layer above the global settings sets
+#[derive(Debug, Clone, Default)]
+pub struct SettingsLayerValues {
+    pub overrides: SettingsOverrides,
+    /// Environment variables of the project or session, for every agent type
+    pub env_vars: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EffectiveValue<T> {
+    pub value: T,
+    pub source: SettingsLayer,
+}
+
+/// The settings an agent launches with once all layers are applied
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EffectiveSettings {
+    pub agent_type: String,
+    pub agent_cli_args: EffectiveValue<String>,
+    pub env_vars: BTreeMap<String, EffectiveValue<String>>,
+}
+
+/// A persisted settings change, sent with `SchaltEvent::SettingsChanged`. Each variant
+/// carries the section that changed with its new value.
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingsChange {
+    AgentEnvVars {
+        agent_type: String,
+        env_vars: HashMap<String, String>,
+    },
+    AgentCliArgs {
+        agent_type: String,
+        cli_args: String,
+    },
+    Terminal {
+        terminal: TerminalSettings,
+        /// Terminals opened before the change keep running the previous shell
+        shell_changed: bool,
+    },
+    DiffViewPreferences {
+        preferences: DiffViewPreferences,
+    },
+    SessionPreferences {
+        preferences: SessionPreferences,
+    },
+    KeyboardShortcuts {
+        shortcuts: HashMap<String, Vec<String>>,
+    },
+    /// Secret values are never included
+    EnvSets {
+        env_sets: Vec<EnvVarSet>,
+    },
+    /// Built-in and user-defined themes
+    TerminalThemes {
+        themes: Vec<TerminalTheme>,
+    },
+    FontSizes {
+        terminal_font_size: i32,
+        ui_font_size: i32,
+    },
+    JsonLogs {
+        enabled: bool,
+    },
 }
@@ -1,4 +1,7 @@
-use super::types::{AgentBinaryConfig, Settings};
+use super::service::SettingsServiceError;
+use super::themes::{is_builtin_theme, is_hex_color, THEME_COLOR_KEYS};
+use super::types::{AgentBinaryConfig, EnvVarSet, Settings, SettingsOverrides, TerminalTheme};
+use crate::domains::agents::manifest::AgentManifest;
 
 pub fn clean_invalid_binary_paths(settings: &mut Settings) {
     let fix_config = |config: &mut Option<AgentBinaryConfig>| {
@@ -53,3 +56,88 @@ pub fn clean_invalid_binary_paths(settings: &mut Settings) {
     fix_config(&mut settings.agent_binaries.gemini);
     fix_config(&mut settings.agent_binaries.codex);
 }
+
+/// Reject overrides for agent types that are neither built in nor custom agents
+pub fn validate_settings_overrides(
+    overrides: &SettingsOverrides,
+) -> Result<(), SettingsServiceError> {
+    match overrides
+        .agent_cli_args
+        .keys()
+        .find(|agent_type| AgentManifest::get(agent_type).is_none())
+    {
+        Some(agent_type) => Err(SettingsServiceError::UnknownAgentType(agent_type.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Reject env sets without a name, with keys a shell cannot export, or with a key twice,
+/// and sets for unknown agent types
+pub fn validate_env_set(set: &EnvVarSet) -> Result<(), SettingsServiceError> {
+    if set.name.trim().is_empty() {
+        return Err(SettingsServiceError::InvalidEnvSet(
+            "Env set name must not be empty".to_string(),
+        ));
+    }
+    let mut keys = std::collections::HashSet::new();
+    for entry in &set.vars {
+        let valid = entry
+            .key
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && entry
+                .key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(SettingsServiceError::InvalidEnvSet(format!(
+                "'{}' is not a valid environment variable name",
+                entry.key
+            )));
+        }
+        if !keys.insert(entry.key.as_str()) {
+            return Err(SettingsServiceError::InvalidEnvSet(format!(
+                "{} is set twice in '{}'",
+                entry.key, set.name
+            )));
+        }
+    }
+    match set
+        .agents
+        .iter()
+        .find(|agent_type| AgentManifest::get(agent_type).is_none())
+    {
+        Some(agent_type) => Err(SettingsServiceError::UnknownAgentType(agent_type.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Reject user themes without a name or named like a built-in one, and colors that are
+/// not hex or keys xterm.js does not know
+pub fn validate_terminal_theme(theme: &TerminalTheme) -> Result<(), SettingsServiceError> {
+    let name = theme.name.trim();
+    if name.is_empty() {
+        return Err(SettingsServiceError::InvalidTheme(
+            "Theme name must not be empty".to_string(),
+        ));
+    }
+    if is_builtin_theme(name) {
+        return Err(SettingsServiceError::InvalidTheme(format!(
+            "'{name}' is a built-in theme"
+        )));
+    }
+    for (key, color) in &theme.colors {
+        if !THEME_COLOR_KEYS.contains(&key.as_str()) {
+            return Err(SettingsServiceError::InvalidTheme(format!(
+                "Unknown theme color '{key}'"
+            )));
+        }
+        if !is_hex_color(color) {
+            return Err(SettingsServiceError::InvalidTheme(format!(
+                "{key} is not a hex color: '{color}'"
+            )));
+        }
+    }
+    Ok(())
+}
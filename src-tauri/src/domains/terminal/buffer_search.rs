@@ -0,0 +1,181 @@
+use super::ansi::strip_ansi;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_CONTEXT_CHARS: usize = 40;
+const DEFAULT_MAX_MATCHES: usize = 500;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferSearchQuery {
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression instead of plain text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Characters of surrounding text returned on each side of a match, within its line
+    pub context_chars: Option<usize>,
+    pub max_matches: Option<usize>,
+}
+
+/// A match in the buffer with escape sequences removed; offsets and columns count characters.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferMatch {
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub context_before: String,
+    pub context_after: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferSearchResult {
+    pub matches: Vec<BufferMatch>,
+    /// True when `max_matches` cut the result short
+    pub truncated: bool,
+}
+
+/// Search the printable text of a terminal buffer.
+pub fn search_buffer(data: &[u8], query: &BufferSearchQuery) -> Result<BufferSearchResult, String> {
+    if query.pattern.is_empty() {
+        return Ok(BufferSearchResult {
+            matches: Vec::new(),
+            truncated: false,
+        });
+    }
+
+    let pattern = if query.regex {
+        query.pattern.clone()
+    } else {
+        regex::escape(&query.pattern)
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(!query.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {e}"))?;
+
+    let text = strip_ansi(&String::from_utf8_lossy(data)).replace('\r', "");
+    let context_chars = query.context_chars.unwrap_or(DEFAULT_CONTEXT_CHARS);
+    let max_matches = query.max_matches.unwrap_or(DEFAULT_MAX_MATCHES);
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    // Walk forward through the text once instead of recounting from the start for each match
+    let mut scanned_bytes = 0;
+    let mut scanned_chars = 0;
+    let mut line = 0;
+    let mut line_start = 0;
+
+    for found in matcher.find_iter(&text) {
+        if found.start() == found.end() {
+            continue;
+        }
+        if matches.len() >= max_matches {
+            truncated = true;
+            break;
+        }
+
+        let skipped = &text[scanned_bytes..found.start()];
+        scanned_chars += skipped.chars().count();
+        if let Some(last_newline) = skipped.rfind('\n') {
+            line += skipped.matches('\n').count();
+            line_start = scanned_bytes + last_newline + 1;
+        }
+        scanned_bytes = found.start();
+
+        let before = &text[line_start..found.start()];
+        let line_end = text[found.end()..]
+            .find('\n')
+            .map_or(text.len(), |i| found.end() + i);
+        let after = &text[found.end()..line_end];
+
+        matches.push(BufferMatch {
+            offset: scanned_chars,
+            length: found.as_str().chars().count(),
+            line,
+            column: before.chars().count(),
+            text: found.as_str().to_string(),
+            context_before: tail_chars(before, context_chars),
+            context_after: after.chars().take(context_chars).collect(),
+        });
+    }
+
+    Ok(BufferSearchResult { matches, truncated })
+}
+
+fn tail_chars(text: &str, count: usize) -> String {
+    let skip = text.chars().count().saturating_sub(count);
+    text.chars().skip(skip).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pattern: &str) -> BufferSearchQuery {
+        BufferSearchQuery {
+            pattern: pattern.to_string(),
+            regex: false,
+            case_sensitive: false,
+            context_chars: Some(5),
+            max_matches: None,
+        }
+    }
+
+    #[test]
+    fn finds_plain_text_with_line_and_context() {
+        let data = b"first line\r\n\x1b[31merror\x1b[0m: \xc3\xbcnicode Error here\r\n";
+        let result = search_buffer(data, &query("error")).unwrap();
+
+        assert!(!result.truncated);
+        assert_eq!(result.matches.len(), 2);
+        assert_eq!(
+            result.matches[0],
+            BufferMatch {
+                offset: 11,
+                length: 5,
+                line: 1,
+                column: 0,
+                text: "error".to_string(),
+                context_before: String::new(),
+                context_after: ": üni".to_string(),
+            }
+        );
+        assert_eq!(result.matches[1].text, "Error");
+        assert_eq!(result.matches[1].column, 15);
+        assert_eq!(result.matches[1].context_before, "code ");
+    }
+
+    #[test]
+    fn plain_text_is_not_a_pattern_and_case_can_matter() {
+        let data = b"a.b axb A.B";
+        assert_eq!(search_buffer(data, &query("a.b")).unwrap().matches.len(), 2);
+
+        let mut sensitive = query("a.b");
+        sensitive.case_sensitive = true;
+        assert_eq!(search_buffer(data, &sensitive).unwrap().matches.len(), 1);
+
+        let mut pattern = query("a.b");
+        pattern.regex = true;
+        assert_eq!(search_buffer(data, &pattern).unwrap().matches.len(), 3);
+    }
+
+    #[test]
+    fn caps_matches_and_rejects_bad_patterns() {
+        let mut capped = query("x");
+        capped.max_matches = Some(2);
+        let result = search_buffer(b"x x x", &capped).unwrap();
+        assert_eq!(result.matches.len(), 2);
+        assert!(result.truncated);
+
+        let mut invalid = query("(");
+        invalid.regex = true;
+        assert!(search_buffer(b"(", &invalid).is_err());
+    }
+}
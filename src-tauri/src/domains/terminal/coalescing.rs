@@ -1,8 +1,10 @@
 use super::ansi;
 use super::utf8_stream::Utf8Stream;
+use crate::infrastructure::secrets::{SecretMaskingStream, HELD_TAIL_TIMEOUT};
 use log::warn;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::{Mutex, RwLock};
 
@@ -15,6 +17,8 @@ pub struct CoalescingState {
     pub emit_buffers_norm: Arc<RwLock<HashMap<String, Vec<u8>>>>,
     pub norm_last_cr: Arc<RwLock<HashMap<String, bool>>>,
     pub utf8_streams: Arc<RwLock<HashMap<String, Utf8Stream>>>,
+    pub secret_streams: Arc<RwLock<HashMap<String, SecretMaskingStream>>>,
+    pub throttles: Arc<RwLock<HashMap<String, OutputThrottle>>>,
 }
 
 impl CoalescingState {
@@ -27,6 +31,8 @@ impl CoalescingState {
         self.emit_buffers_norm.write().await.remove(id);
         self.norm_last_cr.write().await.remove(id);
         self.utf8_streams.write().await.remove(id);
+        self.secret_streams.write().await.remove(id);
+        self.throttles.write().await.remove(id);
     }
 
     /// Clear all coalescing buffers for all terminals in parallel
@@ -37,9 +43,161 @@ impl CoalescingState {
             async { self.emit_scheduled.write().await.clear() },
             async { self.emit_buffers_norm.write().await.clear() },
             async { self.norm_last_cr.write().await.clear() },
-            async { self.utf8_streams.write().await.clear() }
+            async { self.utf8_streams.write().await.clear() },
+            async { self.secret_streams.write().await.clear() },
+            async { self.throttles.write().await.clear() }
         );
     }
+
+    /// Resume output of a terminal paused by the throttle.
+    /// Returns the number of bytes that were not shown, or None when it was not paused.
+    pub async fn resume_output(&self, id: &str) -> Option<u64> {
+        let suppressed = self
+            .throttles
+            .write()
+            .await
+            .get_mut(id)
+            .and_then(|throttle| throttle.resume(Instant::now()))?;
+
+        self.emit_buffers
+            .write()
+            .await
+            .entry(id.to_string())
+            .or_default()
+            .extend_from_slice(resumed_marker(suppressed).as_bytes());
+        handle_coalesced_output(
+            self,
+            CoalescingParams {
+                terminal_id: id,
+                data: &[],
+            },
+        )
+        .await;
+        Some(suppressed)
+    }
+}
+
+/// Limits how fast a terminal may stream output to the UI before it is paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputThrottleConfig {
+    pub enabled: bool,
+    pub max_bytes_per_second: u64,
+}
+
+pub const DEFAULT_MAX_OUTPUT_BYTES_PER_SECOND: u64 = 4 * 1024 * 1024;
+
+const DEFAULT_OUTPUT_THROTTLE: OutputThrottleConfig = OutputThrottleConfig {
+    enabled: true,
+    max_bytes_per_second: DEFAULT_MAX_OUTPUT_BYTES_PER_SECOND,
+};
+
+impl Default for OutputThrottleConfig {
+    fn default() -> Self {
+        DEFAULT_OUTPUT_THROTTLE
+    }
+}
+
+static OUTPUT_THROTTLE_CONFIG: std::sync::RwLock<OutputThrottleConfig> =
+    std::sync::RwLock::new(DEFAULT_OUTPUT_THROTTLE);
+
+const THROTTLE_WINDOW: Duration = Duration::from_secs(1);
+
+pub fn put_output_throttle_config(config: OutputThrottleConfig) {
+    if let Ok(mut guard) = OUTPUT_THROTTLE_CONFIG.write() {
+        *guard = config;
+    }
+}
+
+fn output_throttle_config() -> OutputThrottleConfig {
+    OUTPUT_THROTTLE_CONFIG
+        .read()
+        .map(|guard| *guard)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThrottleDecision {
+    Emit,
+    /// The limit was just exceeded; the chunk is dropped and a pause marker shown
+    Pause,
+    Suppress,
+    /// The flood is over; carries the number of bytes skipped while paused
+    Resume(u64),
+}
+
+/// Per-terminal output rate, measured over fixed windows.
+#[derive(Debug)]
+pub struct OutputThrottle {
+    window_start: Instant,
+    window_bytes: u64,
+    paused: bool,
+    suppressed_bytes: u64,
+}
+
+impl OutputThrottle {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            window_bytes: 0,
+            paused: false,
+            suppressed_bytes: 0,
+        }
+    }
+
+    fn observe(
+        &mut self,
+        config: OutputThrottleConfig,
+        now: Instant,
+        len: usize,
+    ) -> ThrottleDecision {
+        let len = len as u64;
+        let elapsed = now.duration_since(self.window_start);
+        let mut flood_over = !config.enabled;
+        if elapsed >= THROTTLE_WINDOW {
+            let rate = self.window_bytes as f64 / elapsed.as_secs_f64();
+            flood_over |= rate <= config.max_bytes_per_second as f64;
+            self.window_start = now;
+            self.window_bytes = 0;
+        }
+        self.window_bytes = self.window_bytes.saturating_add(len);
+
+        if self.paused {
+            if flood_over {
+                self.paused = false;
+                return ThrottleDecision::Resume(std::mem::take(&mut self.suppressed_bytes));
+            }
+            self.suppressed_bytes = self.suppressed_bytes.saturating_add(len);
+            return ThrottleDecision::Suppress;
+        }
+
+        if config.enabled && self.window_bytes > config.max_bytes_per_second {
+            self.paused = true;
+            self.suppressed_bytes = len;
+            return ThrottleDecision::Pause;
+        }
+        ThrottleDecision::Emit
+    }
+
+    fn resume(&mut self, now: Instant) -> Option<u64> {
+        if !self.paused {
+            return None;
+        }
+        self.paused = false;
+        self.window_start = now;
+        self.window_bytes = 0;
+        Some(std::mem::take(&mut self.suppressed_bytes))
+    }
+}
+
+fn paused_marker(config: OutputThrottleConfig) -> String {
+    format!(
+        "\r\n\x1b[0m\x1b[33m[schaltwerk] Output paused: more than {} KiB/s. Resume the terminal to continue.\x1b[0m\r\n",
+        config.max_bytes_per_second / 1024
+    )
+}
+
+fn resumed_marker(suppressed: u64) -> String {
+    format!("\r\n\x1b[0m\x1b[33m[schaltwerk] Output resumed; {suppressed} bytes were skipped.\x1b[0m\r\n")
 }
 
 fn terminal_output_event_name(terminal_id: &str) -> String {
@@ -64,13 +222,64 @@ pub async fn handle_coalesced_output(
     coalescing_state: &CoalescingState,
     params: CoalescingParams<'_>,
 ) {
-    {
+    let config = output_throttle_config();
+    let decision = coalescing_state
+        .throttles
+        .write()
+        .await
+        .entry(params.terminal_id.to_string())
+        .or_insert_with(|| OutputThrottle::new(Instant::now()))
+        .observe(config, Instant::now(), params.data.len());
+
+    if decision == ThrottleDecision::Pause {
+        warn!(
+            "Terminal {} exceeded {} bytes/s of output; pausing its stream",
+            params.terminal_id, config.max_bytes_per_second
+        );
+        // A partially decoded character would otherwise be glued onto the marker
+        coalescing_state
+            .utf8_streams
+            .write()
+            .await
+            .remove(params.terminal_id);
+    }
+
+    let discarded = {
         let mut buffers = coalescing_state.emit_buffers.write().await;
         let buf_ref = buffers
             .entry(params.terminal_id.to_string())
             .or_insert_with(Vec::new);
 
-        buf_ref.extend_from_slice(params.data);
+        match decision {
+            ThrottleDecision::Emit => {
+                buf_ref.extend_from_slice(params.data);
+                0
+            }
+            ThrottleDecision::Suppress => return,
+            ThrottleDecision::Pause => {
+                // Held-back bytes may end inside an escape sequence; the marker replaces them
+                let discarded = buf_ref.len() as u64;
+                buf_ref.clear();
+                buf_ref.extend_from_slice(paused_marker(config).as_bytes());
+                discarded
+            }
+            ThrottleDecision::Resume(suppressed) => {
+                buf_ref.extend_from_slice(resumed_marker(suppressed).as_bytes());
+                buf_ref.extend_from_slice(params.data);
+                0
+            }
+        }
+    };
+    if discarded > 0 {
+        // Replaced by the marker, so they count as skipped output too
+        if let Some(throttle) = coalescing_state
+            .throttles
+            .write()
+            .await
+            .get_mut(params.terminal_id)
+        {
+            throttle.suppressed_bytes = throttle.suppressed_bytes.saturating_add(discarded);
+        }
     }
 
     // Deterministic, read-driven emission without timers
@@ -122,8 +331,23 @@ pub async fn handle_coalesced_output(
             }
 
             if let Some(text) = payload {
-                if let Err(e) = handle.emit(&event_name, text) {
-                    warn!("Failed to emit terminal output: {e}");
+                let (masked, holding) = {
+                    let mut streams = coalescing_state.secret_streams.write().await;
+                    let stream = streams.entry(params.terminal_id.to_string()).or_default();
+                    let masked = stream.mask_chunk(&text);
+                    (masked, stream.is_holding())
+                };
+                if !masked.is_empty() {
+                    if let Err(e) = handle.emit(&event_name, masked) {
+                        warn!("Failed to emit terminal output: {e}");
+                    }
+                }
+                if holding {
+                    schedule_held_tail_flush(
+                        coalescing_state.clone(),
+                        handle.clone(),
+                        params.terminal_id.to_string(),
+                    );
                 }
             }
         } else {
@@ -138,6 +362,25 @@ pub async fn handle_coalesced_output(
     }
 }
 
+/// Show the end of the output held back by secret masking once the terminal has been quiet
+/// for [`HELD_TAIL_TIMEOUT`]. Output arriving in the meantime takes the held text along.
+fn schedule_held_tail_flush(state: CoalescingState, handle: AppHandle, terminal_id: String) {
+    tokio::spawn(async move {
+        tokio::time::sleep(HELD_TAIL_TIMEOUT).await;
+        // Emitted under the lock so later output cannot overtake it
+        let mut streams = state.secret_streams.write().await;
+        let Some(tail) = streams
+            .get_mut(&terminal_id)
+            .and_then(|stream| stream.take_stale_tail(Instant::now()))
+        else {
+            return;
+        };
+        if let Err(e) = handle.emit(&terminal_output_event_name(&terminal_id), tail) {
+            warn!("Failed to emit terminal output: {e}");
+        }
+    });
+}
+
 fn decode_coalesced_bytes(
     bytes: Vec<u8>,
     terminal_id: &str,
@@ -174,6 +417,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         let buffers = state.emit_buffers.read().await;
@@ -200,6 +445,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         let params = CoalescingParams {
@@ -224,6 +471,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // First call
@@ -267,6 +516,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         handle_coalesced_output(
@@ -374,6 +625,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         handle_coalesced_output(
@@ -402,6 +655,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Add data for terminal 1
@@ -442,6 +697,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         let terminal_id = "test-terminal-cleanup";
@@ -484,6 +741,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Add data for multiple terminals
@@ -534,6 +793,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Should not panic when clearing non-existent terminal
@@ -556,6 +817,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         handle_coalesced_output(
@@ -590,6 +853,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         handle_coalesced_output(
@@ -633,6 +898,8 @@ mod tests {
             emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
             norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
             utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // CRLF sequences should be preserved as they are actual line endings
@@ -659,4 +926,100 @@ mod tests {
         // CRLF sequences should be preserved
         assert_eq!(buffer, b"Line 1\r\nLine 2\r\nLine 3");
     }
+
+    #[test]
+    fn test_throttle_pauses_on_flood_and_resumes_when_quiet() {
+        let config = OutputThrottleConfig {
+            enabled: true,
+            max_bytes_per_second: 100,
+        };
+        let start = Instant::now();
+        let mut throttle = OutputThrottle::new(start);
+
+        assert_eq!(throttle.observe(config, start, 60), ThrottleDecision::Emit);
+        assert_eq!(throttle.observe(config, start, 60), ThrottleDecision::Pause);
+        assert_eq!(
+            throttle.observe(config, start + Duration::from_millis(500), 500),
+            ThrottleDecision::Suppress
+        );
+        // Still flooding in the next window keeps the stream paused
+        assert_eq!(
+            throttle.observe(config, start + Duration::from_millis(1100), 10),
+            ThrottleDecision::Suppress
+        );
+        // A quiet window ends the pause and reports what was skipped
+        assert_eq!(
+            throttle.observe(config, start + Duration::from_secs(5), 5),
+            ThrottleDecision::Resume(570)
+        );
+        assert_eq!(
+            throttle.observe(config, start + Duration::from_secs(5), 5),
+            ThrottleDecision::Emit
+        );
+
+        let disabled = OutputThrottleConfig {
+            enabled: false,
+            ..config
+        };
+        let mut unlimited = OutputThrottle::new(start);
+        assert_eq!(
+            unlimited.observe(disabled, start, 10_000),
+            ThrottleDecision::Emit
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flood_is_replaced_by_marker_until_resumed() {
+        let state = CoalescingState {
+            app_handle: Arc::new(Mutex::new(None)),
+            emit_buffers: Arc::new(RwLock::new(HashMap::new())),
+            emit_scheduled: Arc::new(RwLock::new(HashMap::new())),
+            emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
+            norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
+            utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+            secret_streams: Arc::new(RwLock::new(HashMap::new())),
+            throttles: Arc::new(RwLock::new(HashMap::new())),
+        };
+        let flood = vec![b'x'; DEFAULT_MAX_OUTPUT_BYTES_PER_SECOND as usize + 1];
+
+        handle_coalesced_output(
+            &state,
+            CoalescingParams {
+                terminal_id: "flood-term",
+                data: b"before",
+            },
+        )
+        .await;
+        handle_coalesced_output(
+            &state,
+            CoalescingParams {
+                terminal_id: "flood-term",
+                data: &flood,
+            },
+        )
+        .await;
+        handle_coalesced_output(
+            &state,
+            CoalescingParams {
+                terminal_id: "flood-term",
+                data: b"more",
+            },
+        )
+        .await;
+
+        let paused = state.emit_buffers.read().await.get("flood-term").cloned();
+        assert_eq!(
+            paused,
+            Some(paused_marker(OutputThrottleConfig::default()).into_bytes())
+        );
+
+        // The held-back "before" was replaced by the marker and counts as skipped
+        let skipped = b"before".len() as u64 + flood.len() as u64 + 4;
+        assert_eq!(state.resume_output("flood-term").await, Some(skipped));
+        assert_eq!(state.resume_output("flood-term").await, None);
+
+        let buffers = state.emit_buffers.read().await;
+        let buffer = String::from_utf8(buffers.get("flood-term").unwrap().clone()).unwrap();
+        assert!(buffer.ends_with(&resumed_marker(skipped)));
+    }
 }
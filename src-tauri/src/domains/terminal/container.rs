@@ -0,0 +1,453 @@
+use super::{ApplicationSpec, CreateParams, LocalPtyAdapter, TerminalBackend, TerminalSnapshot};
+use crate::schaltwerk_core::db_project_config::{ContainerRuntime, ProjectContainerConfig};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+const CONTAINER_NAME_PREFIX: &str = "schaltwerk-";
+const SESSION_LABEL: &str = "dev.schaltwerk.session";
+// Prefer bash when the image ships it; plain `sh` is always present
+const CONTAINER_SHELL_SCRIPT: &str =
+    "command -v bash >/dev/null 2>&1 && exec bash -l || exec sh -l";
+
+/// Name and worktree of the container-backed session a terminal working directory lies in.
+/// Backed by the sessions persisted as `SessionType::Container`.
+pub type ContainerSessionResolver = Arc<dyn Fn(&Path) -> Option<(String, PathBuf)> + Send + Sync>;
+
+#[derive(Clone)]
+struct ContainerSettings {
+    config: ProjectContainerConfig,
+    sessions: ContainerSessionResolver,
+}
+
+/// Terminal backend that runs session terminals inside a per-session container.
+///
+/// Terminals of sessions created as `SessionType::Container` are started through `docker exec`
+/// in a long-lived container named after the session, with the worktree mounted at the same
+/// path. Everything else, and every call after creation, goes to the local PTY.
+#[derive(Clone)]
+pub struct ContainerPtyAdapter {
+    local: Arc<LocalPtyAdapter>,
+    settings: Arc<RwLock<Option<ContainerSettings>>>,
+    // Containers already verified running during this app run
+    ready: Arc<Mutex<HashSet<String>>>,
+}
+
+/// Container name used for a session; container names only allow `[a-zA-Z0-9_.-]`.
+pub fn container_name_for_session(session_name: &str) -> String {
+    let sanitized: String = session_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{CONTAINER_NAME_PREFIX}{sanitized}")
+}
+
+/// The session among `sessions` (name and worktree) whose worktree contains `cwd`.
+pub fn session_for_cwd(sessions: Vec<(String, PathBuf)>, cwd: &Path) -> Option<(String, PathBuf)> {
+    sessions
+        .into_iter()
+        .find(|(_, worktree)| cwd.starts_with(worktree))
+}
+
+fn runtime_base_args(runtime: ContainerRuntime) -> Vec<String> {
+    match runtime {
+        ContainerRuntime::Docker => Vec::new(),
+        ContainerRuntime::Orbstack => vec!["--context".to_string(), "orbstack".to_string()],
+    }
+}
+
+fn build_run_args(
+    config: &ProjectContainerConfig,
+    container: &str,
+    session_name: &str,
+    worktree: &Path,
+) -> Vec<String> {
+    let mount = worktree.to_string_lossy().to_string();
+    let mut args = runtime_base_args(config.runtime);
+    args.extend([
+        "run".to_string(),
+        "-d".to_string(),
+        "--name".to_string(),
+        container.to_string(),
+        "--label".to_string(),
+        format!("{SESSION_LABEL}={session_name}"),
+        "-v".to_string(),
+        format!("{mount}:{mount}"),
+        "-w".to_string(),
+        mount,
+    ]);
+    args.extend(config.run_args.iter().cloned());
+    args.extend([
+        config.image.clone(),
+        "sleep".to_string(),
+        "infinity".to_string(),
+    ]);
+    args
+}
+
+/// `docker exec` arguments running `app` (or an interactive shell) inside the container.
+/// Agent binaries resolved on the host are invoked by file name so the image's PATH applies.
+fn build_exec_args(
+    runtime: ContainerRuntime,
+    container: &str,
+    cwd: &str,
+    app: Option<&ApplicationSpec>,
+) -> Vec<String> {
+    let mut args = runtime_base_args(runtime);
+    args.extend([
+        "exec".to_string(),
+        "-it".to_string(),
+        "-w".to_string(),
+        cwd.to_string(),
+    ]);
+
+    if let Some(app) = app {
+        // The host shell path is meaningless inside the container
+        for (key, value) in app.env.iter().filter(|(key, _)| key != "SHELL") {
+            args.push("-e".to_string());
+            args.push(format!("{key}={value}"));
+        }
+    }
+    args.push(container.to_string());
+
+    match app {
+        Some(app) if !is_host_shell(&app.command) => {
+            let program = Path::new(&app.command)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| app.command.clone());
+            args.push(program);
+            args.extend(app.args.iter().cloned());
+        }
+        _ => {
+            args.extend([
+                "sh".to_string(),
+                "-c".to_string(),
+                CONTAINER_SHELL_SCRIPT.to_string(),
+            ]);
+        }
+    }
+    args
+}
+
+fn is_host_shell(command: &str) -> bool {
+    super::get_effective_shell().0 == command
+}
+
+impl ContainerPtyAdapter {
+    pub fn new(local: Arc<LocalPtyAdapter>) -> Self {
+        Self {
+            local,
+            settings: Arc::new(RwLock::new(None)),
+            ready: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Apply the project's container configuration. A disabled config keeps serving sessions
+    /// already created as containers; `None` runs every terminal locally.
+    pub fn configure(
+        &self,
+        config: Option<ProjectContainerConfig>,
+        sessions: ContainerSessionResolver,
+    ) {
+        let settings = config.map(|config| ContainerSettings { config, sessions });
+        if let Ok(mut guard) = self.settings.write() {
+            *guard = settings;
+        }
+    }
+
+    fn current_settings(&self) -> Option<ContainerSettings> {
+        self.settings.read().ok().and_then(|guard| guard.clone())
+    }
+
+    async fn run_cli(runtime: ContainerRuntime, args: Vec<String>) -> Result<String, String> {
+        let mut full_args = runtime_base_args(runtime);
+        full_args.extend(args);
+        let output = Command::new("docker")
+            .args(&full_args)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run docker: {e}"))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    /// Make sure the session container exists and is running.
+    async fn ensure_container(
+        &self,
+        settings: &ContainerSettings,
+        session_name: &str,
+        worktree: &Path,
+    ) -> Result<String, String> {
+        let container = container_name_for_session(session_name);
+        let mut ready = self.ready.lock().await;
+        if ready.contains(&container) {
+            return Ok(container);
+        }
+
+        let runtime = settings.config.runtime;
+        let state = Self::run_cli(
+            runtime,
+            vec![
+                "inspect".to_string(),
+                "-f".to_string(),
+                "{{.State.Running}}".to_string(),
+                container.clone(),
+            ],
+        )
+        .await;
+
+        match state.as_deref() {
+            Ok("true") => {}
+            Ok(_) => {
+                info!("Starting stopped container {container} for session {session_name}");
+                Self::run_cli(runtime, vec!["start".to_string(), container.clone()])
+                    .await
+                    .map_err(|e| format!("Failed to start container {container}: {e}"))?;
+            }
+            Err(_) => {
+                info!(
+                    "Creating container {container} from {} for session {session_name}",
+                    settings.config.image
+                );
+                let args = build_run_args(&settings.config, &container, session_name, worktree);
+                let output = Command::new("docker")
+                    .args(&args)
+                    .output()
+                    .await
+                    .map_err(|e| format!("Failed to run docker: {e}"))?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "Failed to create container {container}: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+            }
+        }
+
+        ready.insert(container.clone());
+        Ok(container)
+    }
+
+    /// Route terminals in container-backed session worktrees through `docker exec`.
+    pub(super) async fn prepare(&self, params: CreateParams) -> Result<CreateParams, String> {
+        let Some(settings) = self.current_settings() else {
+            return Ok(params);
+        };
+        let Some((session_name, worktree)) = (settings.sessions)(Path::new(&params.cwd)) else {
+            return Ok(params);
+        };
+
+        let container = self
+            .ensure_container(&settings, &session_name, &worktree)
+            .await?;
+        let args = build_exec_args(
+            settings.config.runtime,
+            &container,
+            &params.cwd,
+            params.app.as_ref(),
+        );
+        let ready_timeout_ms = params
+            .app
+            .as_ref()
+            .map(|app| app.ready_timeout_ms)
+            .unwrap_or(5000);
+
+        info!("Terminal {} runs in container {container}", params.id);
+        Ok(CreateParams {
+            id: params.id,
+            cwd: params.cwd,
+            app: Some(ApplicationSpec {
+                command: "docker".to_string(),
+                args,
+                env: Vec::new(),
+                ready_timeout_ms,
+            }),
+        })
+    }
+
+    /// Remove the container of a session, e.g. once the session is cancelled.
+    pub async fn remove_session_container(&self, session_name: &str) -> Result<(), String> {
+        let Some(settings) = self.current_settings() else {
+            return Ok(());
+        };
+        let container = container_name_for_session(session_name);
+        self.ready.lock().await.remove(&container);
+        match Self::run_cli(
+            settings.config.runtime,
+            vec!["rm".to_string(), "-f".to_string(), container.clone()],
+        )
+        .await
+        {
+            Ok(_) => {
+                info!("Removed container {container}");
+                Ok(())
+            }
+            Err(e) if e.contains("No such container") => Ok(()),
+            Err(e) => {
+                warn!("Failed to remove container {container}: {e}");
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TerminalBackend for ContainerPtyAdapter {
+    async fn create(&self, params: CreateParams) -> Result<(), String> {
+        let params = self.prepare(params).await?;
+        self.local.create(params).await
+    }
+
+    async fn create_with_size(
+        &self,
+        params: CreateParams,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(), String> {
+        let params = self.prepare(params).await?;
+        self.local.create_with_size(params, cols, rows).await
+    }
+
+    async fn write(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        self.local.write(id, data).await
+    }
+
+    async fn write_immediate(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        self.local.write_immediate(id, data).await
+    }
+
+    async fn resize(&self, id: &str, cols: u16, rows: u16) -> Result<(), String> {
+        self.local.resize(id, cols, rows).await
+    }
+
+    async fn close(&self, id: &str) -> Result<(), String> {
+        self.local.close(id).await
+    }
+
+    async fn exists(&self, id: &str) -> Result<bool, String> {
+        self.local.exists(id).await
+    }
+
+    async fn snapshot(&self, id: &str, from_seq: Option<u64>) -> Result<TerminalSnapshot, String> {
+        self.local.snapshot(id, from_seq).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(runtime: ContainerRuntime) -> ProjectContainerConfig {
+        ProjectContainerConfig {
+            enabled: true,
+            runtime,
+            image: "node:22".to_string(),
+            run_args: vec!["--cpus=2".to_string()],
+        }
+    }
+
+    #[test]
+    fn container_names_are_sanitized() {
+        assert_eq!(
+            container_name_for_session("Feature X/1"),
+            "schaltwerk-feature-x-1"
+        );
+        assert_eq!(
+            container_name_for_session("fix_bug.2"),
+            "schaltwerk-fix_bug.2"
+        );
+    }
+
+    #[test]
+    fn resolves_session_from_worktree_cwd() {
+        let root = Path::new("/repo/.schaltwerk/worktrees");
+        let sessions = || {
+            vec![
+                ("demo".to_string(), root.join("demo")),
+                ("other".to_string(), root.join("other")),
+            ]
+        };
+        assert_eq!(
+            session_for_cwd(sessions(), &root.join("demo/src")),
+            Some(("demo".to_string(), root.join("demo")))
+        );
+        // Worktrees of sessions that are not container-backed are not listed
+        assert_eq!(session_for_cwd(sessions(), &root.join("local")), None);
+        assert_eq!(session_for_cwd(sessions(), Path::new("/repo")), None);
+    }
+
+    #[test]
+    fn run_args_mount_worktree_and_use_runtime_context() {
+        let args = build_run_args(
+            &config(ContainerRuntime::Orbstack),
+            "schaltwerk-demo",
+            "demo",
+            Path::new("/wt/demo"),
+        );
+        assert_eq!(&args[..3], ["--context", "orbstack", "run"]);
+        assert!(args.windows(2).any(|w| w == ["-v", "/wt/demo:/wt/demo"]));
+        assert!(args.contains(&"--cpus=2".to_string()));
+        assert!(args.ends_with(&[
+            "node:22".to_string(),
+            "sleep".to_string(),
+            "infinity".to_string()
+        ]));
+    }
+
+    #[test]
+    fn exec_args_forward_env_and_use_binary_name() {
+        let app = ApplicationSpec {
+            command: "/Users/me/.local/bin/claude".to_string(),
+            args: vec!["--continue".to_string()],
+            env: vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("SHELL".to_string(), "/bin/zsh".to_string()),
+            ],
+            ready_timeout_ms: 5000,
+        };
+        let args = build_exec_args(
+            ContainerRuntime::Docker,
+            "schaltwerk-demo",
+            "/wt/demo",
+            Some(&app),
+        );
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "-it",
+                "-w",
+                "/wt/demo",
+                "-e",
+                "FOO=bar",
+                "schaltwerk-demo",
+                "claude",
+                "--continue"
+            ]
+        );
+
+        let shell = build_exec_args(
+            ContainerRuntime::Docker,
+            "schaltwerk-demo",
+            "/wt/demo",
+            None,
+        );
+        assert_eq!(
+            shell.last().map(String::as_str),
+            Some(CONTAINER_SHELL_SCRIPT)
+        );
+    }
+}
@@ -1,14 +1,52 @@
+use super::ansi::strip_ansi;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+const MAX_COMMAND_HISTORY: usize = 200;
+const MAX_COMMAND_LINE_BYTES: usize = 4096;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SequenceResponse {
     Immediate(Vec<u8>),
 }
 
+/// OSC 133 shell integration marker (FinalTerm semantic prompts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellMarker {
+    /// `A`: the prompt is about to be drawn
+    PromptStart,
+    /// `B`: the prompt ended, user input follows
+    CommandStart,
+    /// `C`: the command line was submitted and is running
+    CommandExecuted,
+    /// `D[;exit]`: the command finished
+    CommandFinished { exit_code: Option<i32> },
+}
+
+fn parse_shell_marker(text: &str) -> Option<ShellMarker> {
+    let mut parts = text.strip_prefix("133;")?.split(';');
+    match parts.next()? {
+        "A" => Some(ShellMarker::PromptStart),
+        "B" => Some(ShellMarker::CommandStart),
+        "C" => Some(ShellMarker::CommandExecuted),
+        "D" => Some(ShellMarker::CommandFinished {
+            exit_code: parts.next().and_then(|code| code.parse().ok()),
+        }),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SanitizedOutput {
     pub data: Vec<u8>,
     pub remainder: Option<Vec<u8>>,
     pub cursor_query_offsets: Vec<usize>,
     pub responses: Vec<SequenceResponse>,
+    /// OSC 133 markers with the offset in `data` where each sequence starts
+    pub shell_markers: Vec<(usize, ShellMarker)>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -50,6 +88,7 @@ pub fn sanitize_control_sequences(input: &[u8]) -> SanitizedOutput {
     let mut remainder = None;
     let mut cursor_query_offsets = Vec::new();
     let mut responses = Vec::new();
+    let mut shell_markers = Vec::new();
 
     let mut i = 0;
     while i < input.len() {
@@ -150,6 +189,10 @@ pub fn sanitize_control_sequences(input: &[u8]) -> SanitizedOutput {
                                 b"\x1b]11;rgb:1e/1e/1e\x07".to_vec(),
                             ));
                             i = term_idx + terminator_len;
+                        } else if let Some(marker) = parse_shell_marker(text) {
+                            shell_markers.push((data.len(), marker));
+                            data.extend_from_slice(&input[i..=term_idx + terminator_len - 1]);
+                            i = term_idx + terminator_len;
                         } else if text.starts_with("8;") {
                             data.extend_from_slice(&input[i..=term_idx + terminator_len - 1]);
                             i = term_idx + terminator_len;
@@ -181,12 +224,151 @@ pub fn sanitize_control_sequences(input: &[u8]) -> SanitizedOutput {
         remainder,
         cursor_query_offsets,
         responses,
+        shell_markers,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CommandRecord {
+    /// Command line as echoed by the shell between the `B` and `C` markers
+    pub command: String,
+    pub started_at_ms: u64,
+    /// `None` while the command is still running
+    pub duration_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+}
+
+struct RunningCommand {
+    record: CommandRecord,
+    started: Instant,
+}
+
+#[derive(Default)]
+struct CommandHistory {
+    finished: VecDeque<CommandRecord>,
+    // Output echoed since the `B` marker, i.e. the command line being typed
+    input: Option<Vec<u8>>,
+    running: Option<RunningCommand>,
+}
+
+impl CommandHistory {
+    fn capture(&mut self, segment: &[u8]) {
+        if let Some(input) = self.input.as_mut() {
+            let room = MAX_COMMAND_LINE_BYTES.saturating_sub(input.len());
+            input.extend_from_slice(&segment[..segment.len().min(room)]);
+        }
+    }
+
+    fn observe_at(
+        &mut self,
+        data: &[u8],
+        markers: &[(usize, ShellMarker)],
+        now: Instant,
+        now_ms: u64,
+    ) {
+        let mut position = 0;
+        for &(offset, marker) in markers {
+            let offset = offset.clamp(position, data.len());
+            self.capture(&data[position..offset]);
+            position = offset;
+
+            match marker {
+                ShellMarker::PromptStart => self.input = None,
+                ShellMarker::CommandStart => self.input = Some(Vec::new()),
+                ShellMarker::CommandExecuted => {
+                    let input = self.input.take().unwrap_or_default();
+                    let command = strip_ansi(&String::from_utf8_lossy(&input))
+                        .trim()
+                        .to_string();
+                    self.running = Some(RunningCommand {
+                        record: CommandRecord {
+                            command,
+                            started_at_ms: now_ms,
+                            duration_ms: None,
+                            exit_code: None,
+                        },
+                        started: now,
+                    });
+                }
+                ShellMarker::CommandFinished { exit_code } => {
+                    // Shells also send `D` before the first prompt, without a command in flight
+                    if let Some(running) = self.running.take() {
+                        let mut record = running.record;
+                        record.duration_ms =
+                            Some(now.duration_since(running.started).as_millis() as u64);
+                        record.exit_code = exit_code;
+                        if self.finished.len() == MAX_COMMAND_HISTORY {
+                            self.finished.pop_front();
+                        }
+                        self.finished.push_back(record);
+                    }
+                }
+            }
+        }
+        self.capture(&data[position..]);
+    }
+
+    fn records(&self) -> Vec<CommandRecord> {
+        self.finished
+            .iter()
+            .cloned()
+            .chain(self.running.as_ref().map(|running| running.record.clone()))
+            .collect()
+    }
+}
+
+/// Builds per-terminal command history from OSC 133 markers in terminal output.
+#[derive(Clone, Default)]
+pub struct CommandHistoryTracker {
+    histories: Arc<Mutex<HashMap<String, CommandHistory>>>,
+}
+
+impl CommandHistoryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed sanitized output and the markers found in it.
+    pub async fn observe(&self, id: &str, data: &[u8], markers: &[(usize, ShellMarker)]) {
+        let mut histories = self.histories.lock().await;
+        // Terminals whose shell never emitted a marker are not tracked at all
+        let history = match histories.get_mut(id) {
+            Some(history) => history,
+            None if !markers.is_empty() => histories.entry(id.to_string()).or_default(),
+            None => return,
+        };
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        history.observe_at(data, markers, Instant::now(), now_ms);
+    }
+
+    /// Finished commands, oldest first, followed by the running one if any.
+    pub async fn history(&self, id: &str) -> Vec<CommandRecord> {
+        self.histories
+            .lock()
+            .await
+            .get(id)
+            .map(CommandHistory::records)
+            .unwrap_or_default()
+    }
+
+    pub async fn clear(&self, id: &str) {
+        self.histories.lock().await.remove(id);
+    }
+
+    pub async fn clear_all(&self) {
+        self.histories.lock().await.clear();
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{sanitize_control_sequences, SanitizedOutput, SequenceResponse};
+    use super::{
+        sanitize_control_sequences, CommandHistory, SanitizedOutput, SequenceResponse, ShellMarker,
+    };
+    use std::time::{Duration, Instant};
 
     #[test]
     fn handles_cursor_position_queries() {
@@ -198,6 +380,7 @@ mod tests {
                 remainder: None,
                 cursor_query_offsets: vec![3],
                 responses: Vec::new(),
+                shell_markers: Vec::new(),
             }
         );
     }
@@ -326,4 +509,53 @@ mod tests {
         assert!(result.cursor_query_offsets.is_empty());
         assert!(result.responses.is_empty());
     }
+
+    #[test]
+    fn parses_osc_133_markers_with_offsets() {
+        let result =
+            sanitize_control_sequences(b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;D;127\x07");
+
+        assert_eq!(
+            result.shell_markers,
+            vec![
+                (0, ShellMarker::PromptStart),
+                (10, ShellMarker::CommandStart),
+                (
+                    23,
+                    ShellMarker::CommandFinished {
+                        exit_code: Some(127)
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            sanitize_control_sequences(b"\x1b]133;D\x07").shell_markers,
+            vec![(0, ShellMarker::CommandFinished { exit_code: None })]
+        );
+    }
+
+    #[test]
+    fn records_commands_with_exit_codes_and_durations() {
+        let mut history = CommandHistory::default();
+        let start = Instant::now();
+        let mut feed = |chunk: &[u8], at: Instant| {
+            let output = sanitize_control_sequences(chunk);
+            history.observe_at(&output.data, &output.shell_markers, at, 1_000);
+        };
+
+        feed(b"\x1b]133;A\x07$ \x1b]133;B\x07car", start);
+        feed(b"go \x1b[1mtest\x1b[0m\r\n\x1b]133;C\x07running", start);
+        feed(
+            b"ok\r\n\x1b]133;D;1\x07\x1b]133;A\x07$ \x1b]133;B\x07sleep 5\r\n\x1b]133;C\x07",
+            start + Duration::from_millis(1500),
+        );
+
+        let records = history.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].command, "cargo test");
+        assert_eq!(records[0].exit_code, Some(1));
+        assert_eq!(records[0].duration_ms, Some(1500));
+        assert_eq!(records[1].command, "sleep 5");
+        assert_eq!(records[1].duration_ms, None);
+    }
 }
@@ -1,13 +1,114 @@
+use super::lifecycle::get_agent_type_from_terminal;
 use super::visible::{ScreenSnapshot, VisibleScreen};
-use log::info;
+use crate::domains::agents::manifest::{AgentDefinition, AgentManifest};
+use log::{info, warn};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
 use std::time::Instant;
 
+pub const DEFAULT_IDLE_THRESHOLD_MS: u64 = 5000;
+
+/// How terminals decide they are idle, and which screens mean an agent waits for input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdleDetectionConfig {
+    pub threshold_ms: u64,
+    /// Regexes per agent type; a match on the visible screen of an idle agent terminal
+    /// means the agent is waiting for input rather than quietly working
+    pub prompt_patterns: HashMap<String, Vec<String>>,
+}
+
+impl Default for IdleDetectionConfig {
+    fn default() -> Self {
+        Self {
+            threshold_ms: DEFAULT_IDLE_THRESHOLD_MS,
+            prompt_patterns: default_prompt_patterns(),
+        }
+    }
+}
+
+/// Patterns matching the `idle_markers` of the agents in the manifest
+pub fn default_prompt_patterns() -> HashMap<String, Vec<String>> {
+    AgentManifest::all()
+        .values()
+        .filter(|definition| !definition.idle_markers.is_empty())
+        .map(|definition| (definition.id.clone(), manifest_prompt_patterns(definition)))
+        .collect()
+}
+
+fn manifest_prompt_patterns(definition: &AgentDefinition) -> Vec<String> {
+    definition
+        .idle_markers
+        .iter()
+        .map(|marker| regex::escape(marker))
+        .collect()
+}
+
+static IDLE_DETECTION_CONFIG: RwLock<Option<IdleDetectionConfig>> = RwLock::new(None);
+
+/// Applies to terminals created after the call.
+pub fn put_idle_detection_config(config: IdleDetectionConfig) {
+    if let Ok(mut guard) = IDLE_DETECTION_CONFIG.write() {
+        *guard = Some(config);
+    }
+}
+
+fn idle_detection_config() -> IdleDetectionConfig {
+    IDLE_DETECTION_CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_default()
+}
+
+fn compile_prompt_patterns(terminal_id: &str, patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                warn!("[{terminal_id}] Ignoring invalid idle prompt pattern {pattern:?}: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Detector for a new terminal using the configured threshold and the prompt patterns of
+/// the agent it runs. Agents without configured patterns use their manifest's idle markers,
+/// so custom agents are covered too.
+pub fn detector_for_terminal(terminal_id: &str, agent_type: Option<&str>) -> IdleDetector {
+    let config = idle_detection_config();
+    let patterns = agent_type
+        .or_else(|| get_agent_type_from_terminal(terminal_id))
+        .and_then(|agent| {
+            config
+                .prompt_patterns
+                .get(agent)
+                .cloned()
+                .or_else(|| AgentManifest::get(agent).map(manifest_prompt_patterns))
+        })
+        .map(|patterns| compile_prompt_patterns(terminal_id, &patterns))
+        .unwrap_or_default();
+    IdleDetector::new(config.threshold_ms, terminal_id.to_string()).with_prompt_patterns(patterns)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IdleTransition {
     BecameIdle,
     BecameActive,
 }
 
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SessionIdlePayload {
+    pub session_id: String,
+    pub terminal_id: String,
+    pub idle: bool,
+    /// Idle with the agent's input prompt on screen
+    pub waiting_for_input: bool,
+}
+
 pub struct IdleDetector {
     terminal_id: String,
     threshold_ms: u64,
@@ -17,6 +118,8 @@ pub struct IdleDetector {
     idle_reported: bool,
     dirty: bool,
     pending_bytes: Vec<u8>,
+    prompt_patterns: Vec<Regex>,
+    waiting_for_input: bool,
 }
 
 impl IdleDetector {
@@ -30,9 +133,25 @@ impl IdleDetector {
             idle_reported: false,
             dirty: false,
             pending_bytes: Vec::with_capacity(65536),
+            prompt_patterns: Vec::new(),
+            waiting_for_input: false,
         }
     }
 
+    pub fn with_prompt_patterns(mut self, patterns: Vec<Regex>) -> Self {
+        self.prompt_patterns = patterns;
+        self
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle_reported
+    }
+
+    /// Whether the terminal went idle with an input prompt on screen.
+    pub fn waiting_for_input(&self) -> bool {
+        self.idle_reported && self.waiting_for_input
+    }
+
     pub fn observe_bytes(&mut self, now: Instant, bytes: &[u8]) {
         self.last_bytes_at = Some(now);
 
@@ -107,6 +226,11 @@ impl IdleDetector {
                 self.terminal_id, bytes_elapsed, visible_elapsed, self.threshold_ms
             );
             self.idle_reported = true;
+            let contents = screen.contents();
+            self.waiting_for_input = self
+                .prompt_patterns
+                .iter()
+                .any(|pattern| pattern.is_match(&contents));
             return Some(IdleTransition::BecameIdle);
         }
 
@@ -120,10 +244,34 @@ impl IdleDetector {
 
 #[cfg(test)]
 mod tests {
-    use super::{IdleDetector, IdleTransition};
+    use super::{
+        compile_prompt_patterns, default_prompt_patterns, detector_for_terminal, IdleDetector,
+        IdleTransition,
+    };
     use crate::domains::terminal::visible::VisibleScreen;
     use std::time::{Duration, Instant};
 
+    #[test]
+    fn prompt_patterns_come_from_manifest_idle_markers() {
+        let defaults = default_prompt_patterns();
+        assert_eq!(
+            defaults.get("claude"),
+            Some(&vec![r"\? for shortcuts".to_string()])
+        );
+        assert_eq!(
+            defaults.get("qwen"),
+            Some(&vec!["Type your message".to_string()])
+        );
+        assert!(!defaults.contains_key("terminal"));
+
+        let detector = detector_for_terminal("session-demo-top", Some("codex"));
+        assert_eq!(detector.prompt_patterns.len(), 1);
+        assert!(detector.prompt_patterns[0].is_match("  ⏎ send   ⌃J newline"));
+        assert!(detector_for_terminal("session-demo-top", None)
+            .prompt_patterns
+            .is_empty());
+    }
+
     #[test]
     fn detects_idle_after_threshold() {
         let threshold = 100u64;
@@ -508,4 +656,42 @@ mod tests {
             "Cannot distinguish between: 1) Agent analyzing files silently, 2) Agent waiting for user input, 3) Agent crashed/stuck"
         );
     }
+
+    #[test]
+    fn reports_waiting_for_input_when_prompt_pattern_matches() {
+        let threshold = 100u64;
+        let patterns = compile_prompt_patterns(
+            "session-claude-top",
+            &[r"\? for shortcuts".to_string(), "(unclosed".to_string()],
+        );
+        assert_eq!(patterns.len(), 1);
+
+        let mut detector = IdleDetector::new(threshold, "session-claude-top".to_string())
+            .with_prompt_patterns(patterns);
+        let mut screen = VisibleScreen::new(5, 40, "session-claude-top".to_string());
+
+        let baseline = Instant::now();
+        detector.observe_bytes(baseline, b"Running tests...");
+        detector.tick(baseline, &mut screen);
+        let idle_time = baseline + Duration::from_millis(threshold + 10);
+        assert_eq!(
+            detector.tick(idle_time, &mut screen),
+            Some(IdleTransition::BecameIdle)
+        );
+        assert!(detector.is_idle());
+        assert!(!detector.waiting_for_input());
+
+        let prompt_time = idle_time + Duration::from_millis(10);
+        detector.observe_bytes(prompt_time, b"\r\n> \r\n  ? for shortcuts");
+        assert_eq!(
+            detector.tick(prompt_time, &mut screen),
+            Some(IdleTransition::BecameActive)
+        );
+        let waiting_time = prompt_time + Duration::from_millis(threshold + 10);
+        assert_eq!(
+            detector.tick(waiting_time, &mut screen),
+            Some(IdleTransition::BecameIdle)
+        );
+        assert!(detector.waiting_for_input());
+    }
 }
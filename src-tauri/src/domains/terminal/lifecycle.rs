@@ -1,9 +1,11 @@
 use super::local::TerminalState;
+use crate::infrastructure::events::outbound::{notify_outbound, OutboundEvent};
 use crate::infrastructure::events::{emit_event, SchaltEvent};
 use log::{debug, error, info, warn};
 use portable_pty::{Child, ExitStatus, MasterPty};
 use std::collections::HashMap;
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::AppHandle;
@@ -116,7 +118,12 @@ async fn check_agent_health(
     }
 }
 
-async fn handle_agent_crash(terminal_id: String, status: ExitStatus, deps: LifecycleDeps) {
+async fn handle_agent_crash(
+    terminal_id: String,
+    status: ExitStatus,
+    deps: LifecycleDeps,
+    project_path: Option<PathBuf>,
+) {
     error!("HANDLING AGENT CRASH for terminal: {terminal_id}");
 
     let agent_type = get_agent_type_from_terminal(&terminal_id).unwrap_or("unknown");
@@ -142,6 +149,21 @@ async fn handle_agent_crash(terminal_id: String, status: ExitStatus, deps: Lifec
 
     cleanup_dead_terminal(terminal_id.clone(), &deps).await;
 
+    if let (false, Some(project_path), Some(session)) =
+        (status.success(), &project_path, &session_name)
+    {
+        notify_outbound(
+            project_path,
+            OutboundEvent::AgentCrashed,
+            session,
+            serde_json::json!({
+                "agent_type": agent_type,
+                "exit_code": status.exit_code(),
+                "terminal_id": terminal_id,
+            }),
+        );
+    }
+
     let handle_guard = deps.app_handle.lock().await;
     if let Some(handle) = handle_guard.as_ref() {
         #[derive(serde::Serialize, Clone)]
@@ -152,6 +174,7 @@ async fn handle_agent_crash(terminal_id: String, status: ExitStatus, deps: Lifec
             exit_code: Option<i32>,
             buffer_size: usize,
             last_seq: u64,
+            project_path: Option<PathBuf>,
         }
 
         let payload = AgentCrashPayload {
@@ -161,6 +184,7 @@ async fn handle_agent_crash(terminal_id: String, status: ExitStatus, deps: Lifec
             exit_code: Some(status.exit_code() as i32),
             buffer_size,
             last_seq,
+            project_path,
         };
 
         if let Err(e) = emit_event(handle, SchaltEvent::AgentCrashed, &payload) {
@@ -202,7 +226,11 @@ pub(super) async fn cleanup_dead_terminal(id: String, deps: &LifecycleDeps) {
     info!("Dead terminal cleanup completed");
 }
 
-pub(super) async fn start_process_monitor(id: String, deps: LifecycleDeps) {
+pub(super) async fn start_process_monitor(
+    id: String,
+    deps: LifecycleDeps,
+    project_path: Option<PathBuf>,
+) {
     let monitor_id = id.clone();
     let is_agent = is_agent_terminal(&monitor_id);
 
@@ -276,7 +304,8 @@ pub(super) async fn start_process_monitor(id: String, deps: LifecycleDeps) {
                         );
                     }
 
-                    handle_agent_crash(monitor_id.clone(), status, deps.clone()).await;
+                    handle_agent_crash(monitor_id.clone(), status, deps.clone(), project_path)
+                        .await;
                 } else {
                     info!("Terminal {monitor_id} process exited with status: {status:?}");
                     cleanup_dead_terminal(monitor_id.clone(), &deps).await;
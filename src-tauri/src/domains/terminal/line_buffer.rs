@@ -0,0 +1,45 @@
+//! Splits terminal output into whole lines for scanners that look at one line at a time.
+
+// Cap on a buffered partial line so a stream without newlines cannot grow unbounded
+const MAX_PENDING_LINE: usize = 4096;
+
+/// Output of one terminal that has not ended in a line break yet.
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    pending: String,
+}
+
+impl LineBuffer {
+    /// Append `data` and take the complete lines it finished, still joined by their
+    /// `\n` or `\r` breaks. `None` while no line is complete.
+    pub fn push(&mut self, data: &[u8]) -> Option<String> {
+        self.pending.push_str(&String::from_utf8_lossy(data));
+        let Some(last_break) = self.pending.rfind(['\n', '\r']) else {
+            if self.pending.len() > MAX_PENDING_LINE {
+                self.pending.clear();
+            }
+            return None;
+        };
+        Some(self.pending.drain(..=last_break).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holds_partial_lines_until_they_end() {
+        let mut buffer = LineBuffer::default();
+        assert_eq!(buffer.push(b"Local: http://local"), None);
+        assert_eq!(
+            buffer.push(b"host:5173\r\nnext").as_deref(),
+            Some("Local: http://localhost:5173\r\n")
+        );
+        assert_eq!(buffer.push(b" line\n").as_deref(), Some("next line\n"));
+
+        // A runaway line is dropped instead of buffered forever
+        buffer.push(&[b'x'; MAX_PENDING_LINE + 1]);
+        assert_eq!(buffer.push(b"tail\n").as_deref(), Some("tail\n"));
+    }
+}
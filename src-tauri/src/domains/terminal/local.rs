@@ -1,24 +1,36 @@
 use super::coalescing::{handle_coalesced_output, CoalescingParams, CoalescingState};
 use super::command_builder::build_command_spec;
-use super::control_sequences::{sanitize_control_sequences, SanitizedOutput, SequenceResponse};
-use super::idle_detection::{IdleDetector, IdleTransition};
+use super::control_sequences::{
+    sanitize_control_sequences, CommandHistoryTracker, CommandRecord, SanitizedOutput,
+    SequenceResponse,
+};
+use super::idle_detection::{
+    detector_for_terminal, IdleDetector, IdleTransition, SessionIdlePayload,
+};
 use super::lifecycle::{self, LifecycleDeps};
+use super::port_detection::{DetectedPort, PortDetector};
+use super::process_tree::{kill_grace_period, kill_process_tree};
+use super::resource_limits;
+use super::run_status::{is_run_terminal_id, RunStatusTracker};
+use super::scrollback::scrollback_limit_for_terminal;
 use super::visible::VisibleScreen;
 use super::{CreateParams, TerminalBackend, TerminalSnapshot};
+use crate::domains::agents::status::{
+    agent_type_from_command, AgentStateParser, SessionAgentStatePayload,
+};
+use crate::domains::agents::usage::{UsageTracker, UsageUpdate};
 use crate::infrastructure::events::{emit_event, SchaltEvent};
 use crate::shared::terminal_id::is_session_top_terminal_id;
 use log::{debug, error, info, trace, warn};
 use portable_pty::{Child, MasterPty, NativePtySystem, PtySize, PtySystem};
 use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant, SystemTime};
 use tauri::AppHandle;
 use tokio::sync::{broadcast, Mutex, RwLock};
 
-const DEFAULT_MAX_BUFFER_SIZE: usize = 2 * 1024 * 1024;
-const AGENT_MAX_BUFFER_SIZE: usize = 64 * 1024 * 1024;
-const IDLE_THRESHOLD_MS: u64 = 5000;
 pub(super) struct TerminalState {
     pub(super) buffer: Vec<u8>,
     pub(super) seq: u64,
@@ -27,6 +39,8 @@ pub(super) struct TerminalState {
     pub(super) screen: VisibleScreen,
     pub(super) idle_detector: IdleDetector,
     pub(super) session_id: Option<String>,
+    /// What the session's agent is doing; only set for session agent terminals
+    pub(super) agent_state: Option<AgentStateParser>,
 }
 
 impl TerminalState {
@@ -148,10 +162,26 @@ pub struct LocalPtyAdapter {
     coalescing_state: CoalescingState,
     pending_control_sequences: Arc<Mutex<HashMap<String, Vec<u8>>>>,
     initial_commands: Arc<Mutex<HashMap<String, InitialCommandState>>>,
+    // Exit status tracking for commands started in run terminals
+    run_status: RunStatusTracker,
+    // Dev-server URLs announced in run terminal output
+    port_detector: PortDetector,
+    // Commands delimited by OSC 133 shell integration markers
+    command_history: CommandHistoryTracker,
+    // Token usage and cost summaries printed by session agents
+    usage_tracker: UsageTracker,
     // Event broadcasting for deterministic testing
     output_event_sender: Arc<broadcast::Sender<(String, u64)>>, // (terminal_id, new_seq)
+    // Project the terminals belong to, for outbound crash notifications
+    project_path: OnceLock<PathBuf>,
+    // The id each reader reports under; renaming a terminal updates it in place
+    reader_ids: Arc<Mutex<HashMap<String, ReaderId>>>,
+    // Terminals running a tmux/screen client; closing them detaches instead of killing the tree
+    persistent_ids: Arc<Mutex<HashSet<String>>>,
 }
 
+type ReaderId = Arc<std::sync::RwLock<String>>;
+
 struct ReaderState {
     terminals: Arc<RwLock<HashMap<String, TerminalState>>>,
     pty_children: Arc<Mutex<HashMap<String, Box<dyn Child + Send>>>>,
@@ -160,6 +190,10 @@ struct ReaderState {
     coalescing_state: CoalescingState,
     pending_control_sequences: Arc<Mutex<HashMap<String, Vec<u8>>>>,
     initial_commands: Arc<Mutex<HashMap<String, InitialCommandState>>>,
+    run_status: RunStatusTracker,
+    port_detector: PortDetector,
+    command_history: CommandHistoryTracker,
+    usage_tracker: UsageTracker,
     output_event_sender: Arc<broadcast::Sender<(String, u64)>>,
 }
 
@@ -196,13 +230,26 @@ impl LocalPtyAdapter {
                 emit_buffers_norm: Arc::new(RwLock::new(HashMap::new())),
                 norm_last_cr: Arc::new(RwLock::new(HashMap::new())),
                 utf8_streams: Arc::new(RwLock::new(HashMap::new())),
+                secret_streams: Arc::new(RwLock::new(HashMap::new())),
+                throttles: Arc::new(RwLock::new(HashMap::new())),
             },
             pending_control_sequences: Arc::new(Mutex::new(HashMap::new())),
             initial_commands: Arc::new(Mutex::new(HashMap::new())),
+            run_status: RunStatusTracker::new(),
+            port_detector: PortDetector::new(),
+            command_history: CommandHistoryTracker::new(),
+            usage_tracker: UsageTracker::new(),
             output_event_sender: Arc::new(output_event_sender),
+            project_path: OnceLock::new(),
+            reader_ids: Arc::new(Mutex::new(HashMap::new())),
+            persistent_ids: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
+    pub fn set_project_path(&self, path: PathBuf) {
+        let _ = self.project_path.set(path);
+    }
+
     pub async fn get_activity_status(&self, id: &str) -> Result<(bool, u64), String> {
         let terminals = self.terminals.read().await;
         if let Some(state) = terminals.get(id) {
@@ -230,6 +277,40 @@ impl LocalPtyAdapter {
         results
     }
 
+    /// What each session's agent is doing, for agents that printed anything recognizable.
+    pub async fn session_agent_states(&self) -> Vec<SessionAgentStatePayload> {
+        self.terminals
+            .read()
+            .await
+            .iter()
+            .filter_map(|(id, state)| {
+                Some(SessionAgentStatePayload {
+                    session_id: state.session_id.clone()?,
+                    terminal_id: id.clone(),
+                    state: state.agent_state.as_ref()?.state()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Current idle state of every session agent terminal.
+    pub async fn session_idle_states(&self) -> Vec<SessionIdlePayload> {
+        self.terminals
+            .read()
+            .await
+            .iter()
+            .filter(|(id, _)| is_session_top_terminal_id(id))
+            .filter_map(|(id, state)| {
+                Some(SessionIdlePayload {
+                    session_id: state.session_id.clone()?,
+                    terminal_id: id.clone(),
+                    idle: state.idle_detector.is_idle(),
+                    waiting_for_input: state.idle_detector.waiting_for_input(),
+                })
+            })
+            .collect()
+    }
+
     pub async fn wait_for_output_change(&self, id: &str, min_seq: u64) -> Result<u64, String> {
         let mut receiver = self.output_event_sender.subscribe();
 
@@ -285,6 +366,135 @@ impl LocalPtyAdapter {
         self.wait_for_output_change(id, initial_seq).await
     }
 
+    /// Process id of the program running in each terminal.
+    pub async fn terminal_pids(&self) -> Vec<(String, u32)> {
+        self.pty_children
+            .lock()
+            .await
+            .iter()
+            .filter_map(|(id, child)| child.process_id().map(|pid| (id.clone(), pid)))
+            .collect()
+    }
+
+    pub async fn get_detected_ports(&self, id: &str) -> Vec<DetectedPort> {
+        self.port_detector.ports_for(id).await
+    }
+
+    pub async fn command_history(&self, id: &str) -> Vec<CommandRecord> {
+        self.command_history.history(id).await
+    }
+
+    pub async fn take_usage_updates(&self) -> Vec<UsageUpdate> {
+        self.usage_tracker.take_updates().await
+    }
+
+    pub async fn bracketed_paste_enabled(&self, id: &str) -> bool {
+        self.terminals
+            .read()
+            .await
+            .get(id)
+            .is_some_and(|state| state.screen.bracketed_paste())
+    }
+
+    /// Mark whether `id` runs the client of a tmux/screen session. The session must outlive
+    /// the terminal, so closing it only ends the client instead of its whole process tree.
+    pub async fn set_persistent(&self, id: &str, persistent: bool) {
+        let mut persistent_ids = self.persistent_ids.lock().await;
+        if persistent {
+            persistent_ids.insert(id.to_string());
+        } else {
+            persistent_ids.remove(id);
+        }
+    }
+
+    /// Move a running terminal to `new_id`, e.g. when its session is renamed. The process
+    /// and its scrollback are kept; the reader switches over with its next read.
+    pub async fn rename_terminal(&self, old_id: &str, new_id: &str) -> Result<(), String> {
+        {
+            let mut terminals = self.terminals.write().await;
+            if terminals.contains_key(new_id) {
+                return Err(format!("Terminal {new_id} already exists"));
+            }
+            let mut state = terminals
+                .remove(old_id)
+                .ok_or_else(|| format!("Terminal {old_id} not found"))?;
+            state.session_id = session_id_from_terminal_id(new_id);
+            terminals.insert(new_id.to_string(), state);
+        }
+        {
+            let mut reader_ids = self.reader_ids.lock().await;
+            if let Some(Ok(mut id)) = reader_ids.get(old_id).map(|id| id.write()) {
+                *id = new_id.to_string();
+            }
+            move_entry(&mut reader_ids, old_id, new_id);
+        }
+        // Taken one at a time: the process monitor holds the children while reading terminals
+        move_entry(&mut *self.pty_children.lock().await, old_id, new_id);
+        move_entry(&mut *self.pty_masters.lock().await, old_id, new_id);
+        move_entry(&mut *self.pty_writers.lock().await, old_id, new_id);
+        move_entry(&mut *self.reader_handles.lock().await, old_id, new_id);
+        move_entry(
+            &mut *self.pending_control_sequences.lock().await,
+            old_id,
+            new_id,
+        );
+        move_entry(&mut *self.initial_commands.lock().await, old_id, new_id);
+        {
+            let mut persistent_ids = self.persistent_ids.lock().await;
+            if persistent_ids.remove(old_id) {
+                persistent_ids.insert(new_id.to_string());
+            }
+        }
+
+        self.run_status.clear(old_id).await;
+        self.port_detector.clear(old_id).await;
+        self.command_history.clear(old_id).await;
+        self.usage_tracker.clear(old_id).await;
+        self.coalescing_state.clear_for(old_id).await;
+
+        // The monitor of the old id stops once it no longer finds the child
+        lifecycle::start_process_monitor(
+            new_id.to_string(),
+            self.lifecycle_deps(),
+            self.project_path.get().cloned(),
+        )
+        .await;
+        info!("Renamed terminal {old_id} to {new_id}");
+        Ok(())
+    }
+
+    pub async fn resume_output(&self, id: &str) -> Option<u64> {
+        self.coalescing_state.resume_output(id).await
+    }
+
+    /// Show `data` in a terminal as if the shell had printed it, without sending it to the shell.
+    pub async fn append_output(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        {
+            let mut terminals = self.terminals.write().await;
+            let state = terminals
+                .get_mut(id)
+                .ok_or_else(|| format!("Terminal {id} not found"))?;
+            state.buffer.extend_from_slice(data);
+            state.screen.feed_bytes(data);
+            state.seq = state.seq.saturating_add(data.len() as u64);
+            let max_size = scrollback_limit_for_terminal(id);
+            if state.buffer.len() > max_size {
+                let excess = state.buffer.len() - max_size;
+                state.buffer.drain(0..excess);
+                state.start_seq = state.start_seq.saturating_add(excess as u64);
+            }
+        }
+        handle_coalesced_output(
+            &self.coalescing_state,
+            CoalescingParams {
+                terminal_id: id,
+                data,
+            },
+        )
+        .await;
+        Ok(())
+    }
+
     pub async fn set_app_handle(&self, handle: AppHandle) {
         *self.coalescing_state.app_handle.lock().await = Some(handle.clone());
         self.spawn_idle_ticker(handle).await;
@@ -298,9 +508,10 @@ impl LocalPtyAdapter {
                 interval.tick().await;
                 let now = Instant::now();
 
-                let transitions = {
+                let (transitions, agent_states) = {
                     let mut terminals = terminals.write().await;
                     let mut transitions = Vec::new();
+                    let mut agent_states = Vec::new();
                     for (id, state) in terminals.iter_mut() {
                         if state.session_id.is_none() {
                             continue;
@@ -318,18 +529,38 @@ impl LocalPtyAdapter {
                                 IdleTransition::BecameIdle => true,
                                 IdleTransition::BecameActive => false,
                             };
+                            if let Some(agent_state) = state
+                                .agent_state
+                                .as_mut()
+                                .filter(|_| needs_attention)
+                                .and_then(|parser| parser.observe_idle())
+                            {
+                                agent_states.push(SessionAgentStatePayload {
+                                    session_id: state.session_id.clone().unwrap(),
+                                    terminal_id: id.clone(),
+                                    state: agent_state,
+                                });
+                            }
                             transitions.push((
                                 state.session_id.clone().unwrap(),
                                 id.clone(),
                                 needs_attention,
+                                state.idle_detector.waiting_for_input(),
                             ));
                         }
                     }
-                    transitions
+                    (transitions, agent_states)
                 };
 
+                for payload in agent_states {
+                    if let Err(e) = emit_event(&handle, SchaltEvent::SessionAgentState, &payload) {
+                        error!("Failed to emit SessionAgentState event: {e}");
+                    }
+                }
+
                 if !transitions.is_empty() {
-                    for (session_id, terminal_id, needs_attention) in transitions {
+                    for (session_id, terminal_id, needs_attention, waiting_for_input) in transitions
+                    {
                         info!(
                             "Emitting TerminalAttention event: session={session_id}, terminal={terminal_id}, attention={needs_attention}"
                         );
@@ -343,6 +574,17 @@ impl LocalPtyAdapter {
                         {
                             error!("Failed to emit TerminalAttention event: {e}");
                         }
+
+                        let idle_payload = SessionIdlePayload {
+                            session_id,
+                            terminal_id,
+                            idle: needs_attention,
+                            waiting_for_input,
+                        };
+                        if let Err(e) = emit_event(&handle, SchaltEvent::SessionIdle, &idle_payload)
+                        {
+                            error!("Failed to emit SessionIdle event: {e}");
+                        }
                     }
                 }
             }
@@ -350,7 +592,7 @@ impl LocalPtyAdapter {
     }
 
     fn start_reader(
-        id: String,
+        reader_id: ReaderId,
         mut reader: Box<dyn Read + Send>,
         reader_state: ReaderState,
     ) -> tokio::task::JoinHandle<()> {
@@ -359,7 +601,12 @@ impl LocalPtyAdapter {
             let mut buf = [0u8; 8192];
 
             loop {
-                match reader.read(&mut buf) {
+                let read = reader.read(&mut buf);
+                let id = reader_id
+                    .read()
+                    .map(|id| id.clone())
+                    .unwrap_or_else(|poisoned| poisoned.into_inner().clone());
+                match read {
                     Ok(0) => {
                         info!("Terminal {id} EOF");
                         // Clean up terminal maps and notify UI about closure
@@ -402,6 +649,7 @@ impl LocalPtyAdapter {
                             remainder,
                             cursor_query_offsets,
                             responses,
+                            shell_markers,
                         } = sanitize_control_sequences(&data);
 
                         for response in responses {
@@ -446,21 +694,22 @@ impl LocalPtyAdapter {
                         let pty_writers_clone_for_ready = Arc::clone(&reader_state.pty_writers);
                         let output_event_sender_clone =
                             Arc::clone(&reader_state.output_event_sender);
+                        let run_status_clone = reader_state.run_status.clone();
+                        let port_detector_clone = reader_state.port_detector.clone();
+                        let command_history_clone = reader_state.command_history.clone();
+                        let usage_tracker_clone = reader_state.usage_tracker.clone();
 
                         let cursor_responses = runtime.block_on(async move {
                             let mut responses: Vec<Vec<u8>> = Vec::new();
                             let mut current_seq: Option<u64> = None;
+                            let mut agent_state_change: Option<SessionAgentStatePayload> = None;
 
                             {
                                 let mut terminals = terminals_clone.write().await;
                                 if let Some(state) = terminals.get_mut(&id_clone) {
                                     let total_len = sanitized_data.len();
                                     let mut processed = 0usize;
-                                    let max_size = if lifecycle::is_agent_terminal(&id_clone) {
-                                        AGENT_MAX_BUFFER_SIZE
-                                    } else {
-                                        DEFAULT_MAX_BUFFER_SIZE
-                                    };
+                                    let max_size = scrollback_limit_for_terminal(&id_clone);
 
                                     let apply_segment =
                                         |state: &mut TerminalState, segment: &[u8]| {
@@ -509,6 +758,32 @@ impl LocalPtyAdapter {
                                     }
 
                                     current_seq = Some(state.seq);
+
+                                    if let Some(agent_state) = state
+                                        .agent_state
+                                        .as_mut()
+                                        .and_then(|parser| parser.observe_output(&sanitized_data))
+                                    {
+                                        agent_state_change = state.session_id.clone().map(
+                                            |session_id| SessionAgentStatePayload {
+                                                session_id,
+                                                terminal_id: id_clone.clone(),
+                                                state: agent_state,
+                                            },
+                                        );
+                                    }
+                                }
+                            }
+
+                            if let Some(payload) = agent_state_change {
+                                if let Some(handle) =
+                                    coalescing_state_ready.app_handle.lock().await.as_ref()
+                                {
+                                    if let Err(e) =
+                                        emit_event(handle, SchaltEvent::SessionAgentState, &payload)
+                                    {
+                                        warn!("Failed to emit SessionAgentState event: {e}");
+                                    }
                                 }
                             }
 
@@ -542,6 +817,45 @@ impl LocalPtyAdapter {
                                     &sanitized_data,
                                 )
                                 .await;
+
+                                command_history_clone
+                                    .observe(&id_clone, &sanitized_data, &shell_markers)
+                                    .await;
+
+                                if let Some(finished) = run_status_clone
+                                    .observe_output(&id_clone, &sanitized_data)
+                                    .await
+                                {
+                                    info!(
+                                        "Run in {id_clone} finished with exit code {:?} after {}ms",
+                                        finished.exit_code, finished.duration_ms
+                                    );
+                                    if let Some(handle) =
+                                        coalescing_state_ready.app_handle.lock().await.as_ref()
+                                    {
+                                        if let Err(e) =
+                                            emit_event(handle, SchaltEvent::RunFinished, &finished)
+                                        {
+                                            warn!("Failed to emit RunFinished event: {e}");
+                                        }
+                                    }
+                                }
+
+                                if is_run_terminal_id(&id_clone)
+                                    && port_detector_clone
+                                        .observe_output(&id_clone, &sanitized_data)
+                                        .await
+                                {
+                                    debug!("Detected new dev-server port in {id_clone}");
+                                }
+
+                                if is_session_top_terminal_id(&id_clone)
+                                    && usage_tracker_clone
+                                        .observe_output(&id_clone, &sanitized_data)
+                                        .await
+                                {
+                                    debug!("Agent in {id_clone} reported token usage");
+                                }
                             }
 
                             responses
@@ -622,8 +936,13 @@ impl LocalPtyAdapter {
                 .map_err(|e| format!("Failed to clone reader for terminal {id}: {e}"))?
         };
 
+        let reader_id: ReaderId = Arc::new(std::sync::RwLock::new(id.to_string()));
+        self.reader_ids
+            .lock()
+            .await
+            .insert(id.to_string(), Arc::clone(&reader_id));
         let reader_handle = Self::start_reader(
-            id.to_string(),
+            reader_id,
             reader,
             ReaderState {
                 terminals: Arc::clone(&self.terminals),
@@ -633,6 +952,10 @@ impl LocalPtyAdapter {
                 coalescing_state: self.coalescing_state.clone(),
                 pending_control_sequences: Arc::clone(&self.pending_control_sequences),
                 initial_commands: Arc::clone(&self.initial_commands),
+                run_status: self.run_status.clone(),
+                port_detector: self.port_detector.clone(),
+                command_history: self.command_history.clone(),
+                usage_tracker: self.usage_tracker.clone(),
                 output_event_sender: Arc::clone(&self.output_event_sender),
             },
         );
@@ -698,7 +1021,12 @@ impl TerminalBackend for LocalPtyAdapter {
             })
             .map_err(|e| format!("Failed to open PTY: {e}"))?;
 
-        let spec = build_command_spec(&params, cols, rows).await?;
+        let mut spec = build_command_spec(&params, cols, rows).await?;
+        if lifecycle::is_agent_terminal(&id) {
+            let limits = resource_limits::agent_resource_limits();
+            (spec.program, spec.args) =
+                resource_limits::wrap_command(spec.program, spec.args, &limits);
+        }
         let mut cmd = spec.into_builder();
 
         // OPTIMIZATION 3: Skip working directory validation in release for faster startup
@@ -735,8 +1063,9 @@ impl TerminalBackend for LocalPtyAdapter {
                 start_seq: 0,
                 last_output: SystemTime::now(),
                 screen: VisibleScreen::new(rows, cols, id.clone()),
-                idle_detector: IdleDetector::new(IDLE_THRESHOLD_MS, id.clone()),
+                idle_detector: detector_for_terminal(&id, None),
                 session_id,
+                agent_state: None,
             };
 
             let creating_clone = Arc::clone(&self.creating);
@@ -775,24 +1104,39 @@ impl TerminalBackend for LocalPtyAdapter {
             .insert(id.clone(), pair.master);
         self.pty_writers.lock().await.insert(id.clone(), writer);
 
-        lifecycle::start_process_monitor(id.clone(), self.lifecycle_deps()).await;
+        lifecycle::start_process_monitor(
+            id.clone(),
+            self.lifecycle_deps(),
+            self.project_path.get().cloned(),
+        )
+        .await;
 
         let session_id = session_id_from_terminal_id(&id);
         if session_id.is_some() {
             debug!("Terminal {id} mapped to session {session_id:?}");
         }
 
+        let agent_type = params
+            .app
+            .as_ref()
+            .and_then(|app| agent_type_from_command(&app.command));
+        let agent_state =
+            is_session_top_terminal_id(&id).then(|| AgentStateParser::for_agent(agent_type));
+
         let state = TerminalState {
             buffer: Vec::new(),
             seq: 0,
             start_seq: 0,
             last_output: SystemTime::now(),
             screen: VisibleScreen::new(rows, cols, id.clone()),
-            idle_detector: IdleDetector::new(IDLE_THRESHOLD_MS, id.clone()),
+            idle_detector: detector_for_terminal(&id, agent_type),
             session_id,
+            agent_state,
         };
 
         self.terminals.write().await.insert(id.clone(), state);
+        // A terminal started again under the same id runs a new agent
+        self.usage_tracker.clear(&id).await;
 
         // Start reader agent and record the handle so we can abort on close
         self.spawn_reader_for(&id).await?;
@@ -816,6 +1160,10 @@ impl TerminalBackend for LocalPtyAdapter {
 
     async fn write(&self, id: &str, data: &[u8]) -> Result<(), String> {
         let start = Instant::now();
+        if self.run_status.observe_input(id, data).await {
+            // A fresh run may bind different ports
+            self.port_detector.clear(id).await;
+        }
 
         if let Some(writer) = self.pty_writers.lock().await.get_mut(id) {
             writer
@@ -840,6 +1188,10 @@ impl TerminalBackend for LocalPtyAdapter {
 
     async fn write_immediate(&self, id: &str, data: &[u8]) -> Result<(), String> {
         let start = Instant::now();
+        if self.run_status.observe_input(id, data).await {
+            // A fresh run may bind different ports
+            self.port_detector.clear(id).await;
+        }
 
         if let Some(writer) = self.pty_writers.lock().await.get_mut(id) {
             writer
@@ -922,9 +1274,18 @@ impl TerminalBackend for LocalPtyAdapter {
 
         // Abort reader first to stop any further emission for this terminal id
         self.abort_reader(id).await;
+        self.reader_ids.lock().await.remove(id);
 
         // Try to terminate the child process and wait deterministically without polling
-        if let Some(mut child) = self.pty_children.lock().await.remove(id) {
+        let child = self.pty_children.lock().await.remove(id);
+        let persistent = self.persistent_ids.lock().await.remove(id);
+        let mut cleanup_report = None;
+        if let Some(mut child) = child {
+            // Background jobs started from the terminal outlive a plain kill of the shell.
+            // A multiplexer client is only detached: its session keeps running.
+            if let Some(pid) = child.process_id().filter(|_| !persistent) {
+                cleanup_report = Some(kill_process_tree(id, pid, kill_grace_period()).await);
+            }
             if let Err(e) = child.kill() {
                 warn!("Failed to kill terminal process {id}: {e}");
             }
@@ -961,12 +1322,21 @@ impl TerminalBackend for LocalPtyAdapter {
         self.terminals.write().await.remove(id);
         self.pending_control_sequences.lock().await.remove(id);
         self.initial_commands.lock().await.remove(id);
+        self.run_status.clear(id).await;
+        self.port_detector.clear(id).await;
+        self.command_history.clear(id).await;
+        self.usage_tracker.clear(id).await;
 
         // Clear coalescing buffers
         self.coalescing_state.clear_for(id).await;
 
         // Emit terminal closed event
         if let Some(handle) = self.coalescing_state.app_handle.lock().await.as_ref() {
+            if let Some(report) = cleanup_report.filter(|report| report.is_noteworthy()) {
+                if let Err(e) = emit_event(handle, SchaltEvent::TerminalCleanupReport, &report) {
+                    warn!("Failed to emit terminal cleanup report for {id}: {e}");
+                }
+            }
             let _ = emit_event(
                 handle,
                 SchaltEvent::TerminalClosed,
@@ -1002,12 +1372,14 @@ impl TerminalBackend for LocalPtyAdapter {
             Ok(TerminalSnapshot {
                 seq,
                 start_seq,
+                from_seq: effective_from,
                 data,
             })
         } else {
             Ok(TerminalSnapshot {
                 seq: 0,
                 start_seq: 0,
+                from_seq: 0,
                 data: Vec::new(),
             })
         }
@@ -1016,11 +1388,24 @@ impl TerminalBackend for LocalPtyAdapter {
     async fn force_kill_all(&self) -> Result<(), String> {
         info!("Force killing all terminals for app exit");
 
-        let mut children = self.pty_children.lock().await;
-        for (_id, mut child) in children.drain() {
+        let children: Vec<_> = self.pty_children.lock().await.drain().collect();
+        let persistent = std::mem::take(&mut *self.persistent_ids.lock().await);
+        // No grace period on exit: descendants are SIGKILLed right after SIGTERM. Multiplexer
+        // clients are only detached so their sessions survive the app.
+        futures::future::join_all(
+            children
+                .iter()
+                .filter(|(id, _)| !persistent.contains(id.as_str()))
+                .filter_map(|(id, child)| {
+                    child
+                        .process_id()
+                        .map(|pid| kill_process_tree(id, pid, Duration::ZERO))
+                }),
+        )
+        .await;
+        for (_id, mut child) in children {
             let _ = child.kill();
         }
-        drop(children);
 
         self.pty_masters.lock().await.clear();
         self.pty_writers.lock().await.clear();
@@ -1028,6 +1413,7 @@ impl TerminalBackend for LocalPtyAdapter {
         self.terminals.write().await.clear();
         self.pending_control_sequences.lock().await.clear();
         self.initial_commands.lock().await.clear();
+        self.command_history.clear_all().await;
         self.coalescing_state.clear_all().await;
 
         info!("All terminals force killed");
@@ -1035,6 +1421,12 @@ impl TerminalBackend for LocalPtyAdapter {
     }
 }
 
+fn move_entry<V>(map: &mut HashMap<String, V>, old_id: &str, new_id: &str) {
+    if let Some(value) = map.remove(old_id) {
+        map.insert(new_id.to_string(), value);
+    }
+}
+
 fn session_id_from_terminal_id(id: &str) -> Option<String> {
     let mut rest = if let Some(suffix) = id.strip_prefix("session-") {
         suffix
@@ -1069,6 +1461,7 @@ fn session_id_from_terminal_id(id: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
+    use super::super::process_tree::process_alive;
     use super::super::ApplicationSpec;
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -1138,6 +1531,51 @@ mod tests {
         assert!(!adapter.exists(&id).await.unwrap());
     }
 
+    #[tokio::test]
+    #[cfg(target_family = "unix")]
+    async fn force_kill_all_leaves_persistent_sessions_running() {
+        let adapter = LocalPtyAdapter::new();
+        let id = unique_id("persistent");
+        let pid_file = std::env::temp_dir().join(format!("{id}.pid"));
+        // The detached sleep stands in for the tmux/screen server the client started
+        let script = format!(
+            "nohup sleep 30 >/dev/null 2>&1 & echo $! > '{}'; wait",
+            pid_file.display()
+        );
+        let params = CreateParams {
+            id: id.clone(),
+            cwd: "/tmp".to_string(),
+            app: Some(ApplicationSpec {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), script],
+                env: vec![],
+                ready_timeout_ms: 1000,
+            }),
+        };
+        adapter.create(params).await.unwrap();
+        adapter.set_persistent(&id, true).await;
+
+        let mut server = None;
+        for _ in 0..40 {
+            server = std::fs::read_to_string(&pid_file)
+                .ok()
+                .and_then(|pid| pid.trim().parse::<u32>().ok());
+            if server.is_some() {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+        let server = server.expect("server pid");
+
+        adapter.force_kill_all().await.unwrap();
+        sleep(Duration::from_millis(300)).await;
+
+        let survived = process_alive(server);
+        unsafe { libc::kill(server as libc::pid_t, libc::SIGKILL) };
+        let _ = std::fs::remove_file(&pid_file);
+        assert!(survived, "persistent session was killed with its terminal");
+    }
+
     #[tokio::test]
     async fn test_create_with_custom_size() {
         let adapter = LocalPtyAdapter::new();
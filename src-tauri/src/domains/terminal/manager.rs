@@ -1,10 +1,20 @@
+use super::buffer_search::{search_buffer, BufferSearchQuery, BufferSearchResult};
+use super::container::{ContainerPtyAdapter, ContainerSessionResolver};
+use super::paste::{build_paste_payload, is_multiline, PasteOutcome, PendingPastes};
+use super::persistence::PersistentPtyAdapter;
+use super::resource_usage::{ResourceSampler, TerminalResourceUsage};
 use super::{
     get_effective_shell, ApplicationSpec, CreateParams, LocalPtyAdapter, TerminalBackend,
     TerminalSnapshot,
 };
+use crate::domains::workspace::dependency_cache::dependency_cache_env;
 use crate::infrastructure::events::{emit_event, SchaltEvent};
+use crate::schaltwerk_core::db_project_config::{
+    ProjectContainerConfig, ProjectDependencyCacheConfig,
+};
 use log::{debug, error, info, warn};
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::AppHandle;
 use tokio::sync::RwLock;
@@ -20,6 +30,8 @@ pub struct CreateTerminalWithAppAndSizeParams {
     pub rows: u16,
 }
 
+const STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct SessionKey {
     project_id: String,
@@ -40,12 +52,29 @@ struct TerminalMetadata {
     session: SessionKey,
 }
 
+#[derive(Clone, Debug)]
+struct DependencyCacheSettings {
+    worktrees_root: PathBuf,
+    env: Vec<(String, String)>,
+}
+
 pub struct TerminalManager {
     backend: Arc<LocalPtyAdapter>,
+    containers: ContainerPtyAdapter,
+    // Terminal creation goes through here so session terminals start inside their container
+    // and, when enabled, inside a tmux/screen session that outlives the app
+    persistence: PersistentPtyAdapter,
+    dependency_cache: Arc<std::sync::RwLock<Option<DependencyCacheSettings>>>,
     active_ids: Arc<RwLock<HashSet<String>>>,
     metadata: Arc<RwLock<HashMap<String, TerminalMetadata>>>,
     session_index: Arc<RwLock<HashMap<SessionKey, HashSet<String>>>>,
     app_handle: Arc<RwLock<Option<AppHandle>>>,
+    resource_sampler: Arc<tokio::sync::Mutex<ResourceSampler>>,
+    // Started by the first `set_app_handle`, stopped by `cleanup_all`
+    stats_ticker: Arc<std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    pending_pastes: Arc<tokio::sync::Mutex<PendingPastes>>,
+    // Serializes input per terminal so a broadcast never interleaves with other writes
+    write_locks: Arc<std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
 }
 
 impl Default for TerminalManager {
@@ -61,12 +90,21 @@ impl TerminalManager {
 
     pub fn new() -> Self {
         let backend = Arc::new(LocalPtyAdapter::new());
+        let containers = ContainerPtyAdapter::new(backend.clone());
+        let persistence = PersistentPtyAdapter::new(containers.clone(), backend.clone());
         Self {
             backend,
+            containers,
+            persistence,
+            dependency_cache: Arc::new(std::sync::RwLock::new(None)),
             active_ids: Arc::new(RwLock::new(HashSet::new())),
             metadata: Arc::new(RwLock::new(HashMap::new())),
             session_index: Arc::new(RwLock::new(HashMap::new())),
             app_handle: Arc::new(RwLock::new(None)),
+            resource_sampler: Arc::new(tokio::sync::Mutex::new(ResourceSampler::new())),
+            stats_ticker: Arc::new(std::sync::Mutex::new(None)),
+            pending_pastes: Arc::new(tokio::sync::Mutex::new(PendingPastes::default())),
+            write_locks: Arc::new(std::sync::Mutex::new(HashMap::new())),
         }
     }
 
@@ -97,9 +135,182 @@ impl TerminalManager {
         }
     }
 
+    /// Run terminals of container-backed sessions (as `sessions` resolves them) inside
+    /// per-session containers.
+    pub fn configure_containers(
+        &self,
+        config: Option<ProjectContainerConfig>,
+        sessions: ContainerSessionResolver,
+    ) {
+        self.containers.configure(config, sessions);
+    }
+
+    /// Point terminals of session worktrees at package caches shared across the project.
+    pub fn configure_dependency_cache(
+        &self,
+        repo_path: &Path,
+        worktrees_root: &Path,
+        config: ProjectDependencyCacheConfig,
+    ) {
+        let settings = config.is_enabled().then(|| DependencyCacheSettings {
+            worktrees_root: worktrees_root.to_path_buf(),
+            env: dependency_cache_env(repo_path, &config),
+        });
+        if let Ok(mut guard) = self.dependency_cache.write() {
+            *guard = settings;
+        }
+    }
+
+    /// Shared cache variables go first so explicitly passed variables override them.
+    fn with_dependency_cache_env(
+        &self,
+        cwd: &str,
+        env: Vec<(String, String)>,
+    ) -> Vec<(String, String)> {
+        let Some(settings) = self
+            .dependency_cache
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone())
+        else {
+            return env;
+        };
+        if !Path::new(cwd).starts_with(&settings.worktrees_root) {
+            return env;
+        }
+        let mut combined = settings.env;
+        combined.extend(env);
+        combined
+    }
+
+    pub async fn remove_session_container(&self, session_name: &str) -> Result<(), String> {
+        self.containers.remove_session_container(session_name).await
+    }
+
+    /// End the tmux/screen sessions kept alive for these terminals of a session worktree.
+    pub async fn kill_persistent_terminals(&self, terminal_ids: &[String], worktree: &Path) {
+        self.persistence
+            .kill_persistent_sessions(terminal_ids, worktree)
+            .await;
+    }
+
+    /// Move the live terminals whose ids start with `old_base` over to `new_base`, e.g. once
+    /// their session was renamed to `new_session` and its worktree moved. The processes keep
+    /// running. Returns the `(old, new)` ids of the moved terminals.
+    pub async fn rename_session_terminals(
+        &self,
+        old_base: &str,
+        new_base: &str,
+        new_session: &str,
+        old_worktree: &Path,
+        new_worktree: &Path,
+    ) -> Vec<(String, String)> {
+        let prefix = format!("{old_base}-");
+        let ids: Vec<String> = self
+            .active_ids
+            .read()
+            .await
+            .iter()
+            .filter(|id| id.starts_with(&prefix))
+            .cloned()
+            .collect();
+
+        let mut renamed = Vec::new();
+        for old_id in ids {
+            let new_id = format!("{new_base}{}", &old_id[old_base.len()..]);
+            if let Err(e) = self.backend.rename_terminal(&old_id, &new_id).await {
+                warn!("Failed to move terminal {old_id} to {new_id}: {e}");
+                continue;
+            }
+            {
+                let mut active = self.active_ids.write().await;
+                active.remove(&old_id);
+                active.insert(new_id.clone());
+            }
+            let session = self
+                .metadata
+                .read()
+                .await
+                .get(&old_id)
+                .map(|meta| meta.session.clone());
+            if let Some(session) = session {
+                self.unregister_terminal_session(&old_id).await;
+                let key = SessionKey::new(session.project_id, Some(new_session.to_string()));
+                self.register_terminal_session(&new_id, key).await;
+            }
+            self.write_locks
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .remove(&old_id);
+            renamed.push((old_id, new_id));
+        }
+
+        self.persistence
+            .rename_persistent_sessions(&renamed, old_worktree, new_worktree)
+            .await;
+        renamed
+    }
+
+    /// Tie the terminals to their project, so agent crashes reach its outbound webhooks.
+    pub fn set_project_path(&self, project_path: &Path) {
+        self.backend.set_project_path(project_path.to_path_buf());
+    }
+
+    /// Where the project's session worktrees live; terminals below it can be persisted.
+    pub fn set_worktrees_root(&self, worktrees_root: &Path) {
+        self.persistence.set_worktrees_root(worktrees_root);
+    }
+
     pub async fn set_app_handle(&self, handle: AppHandle) {
         *self.app_handle.write().await = Some(handle.clone());
-        self.backend.set_app_handle(handle).await;
+        self.backend.set_app_handle(handle.clone()).await;
+        self.ensure_stats_ticker(handle);
+    }
+
+    /// Periodically emit `TerminalStats` with the resource usage of every terminal.
+    /// `set_app_handle` runs before most terminal commands, so only one ticker is started.
+    fn ensure_stats_ticker(&self, handle: AppHandle) {
+        let Ok(mut ticker) = self.stats_ticker.lock() else {
+            return;
+        };
+        if ticker.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        let backend = Arc::clone(&self.backend);
+        let sampler = Arc::clone(&self.resource_sampler);
+        *ticker = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STATS_INTERVAL);
+            loop {
+                interval.tick().await;
+                let terminals = backend.terminal_pids().await;
+                if terminals.is_empty() {
+                    continue;
+                }
+                let usage = match sampler.lock().await.sample(terminals).await {
+                    Ok(usage) => usage,
+                    Err(e) => {
+                        debug!("Skipping terminal stats sample: {e}");
+                        continue;
+                    }
+                };
+                if let Err(e) = emit_event(&handle, SchaltEvent::TerminalStats, &usage) {
+                    warn!("Failed to emit terminal stats event: {e}");
+                }
+            }
+        }));
+    }
+
+    fn stop_stats_ticker(&self) {
+        if let Some(task) = self.stats_ticker.lock().ok().and_then(|mut t| t.take()) {
+            task.abort();
+        }
+    }
+
+    /// CPU and memory of each terminal's process tree.
+    pub async fn get_terminal_resource_usage(&self) -> Result<Vec<TerminalResourceUsage>, String> {
+        let terminals = self.backend.terminal_pids().await;
+        self.resource_sampler.lock().await.sample(terminals).await
     }
 
     pub async fn attach_terminals_to_session(
@@ -158,6 +369,7 @@ impl TerminalManager {
         Ok(())
     }
 
+    #[tracing::instrument(level = "trace", skip(self, cwd))]
     pub async fn create_terminal(&self, id: String, cwd: String) -> Result<(), String> {
         let start = std::time::Instant::now();
         let result = self.create_terminal_with_env(id.clone(), cwd, vec![]).await;
@@ -184,6 +396,7 @@ impl TerminalManager {
         );
 
         let cwd_for_event = cwd.clone();
+        let env = self.with_dependency_cache_env(&cwd, env);
         let params = if env.is_empty() {
             CreateParams {
                 id: id.clone(),
@@ -208,7 +421,7 @@ impl TerminalManager {
             }
         };
 
-        self.backend.create(params).await?;
+        self.persistence.create(params).await?;
         self.active_ids.write().await.insert(id.clone());
 
         // Start event bridge for this terminal
@@ -268,6 +481,7 @@ impl TerminalManager {
         info!("Creating terminal through manager with size: id={id}, cwd={cwd}, size={cols}x{rows}, env_count={}", env.len());
 
         let cwd_for_event = cwd.clone();
+        let env = self.with_dependency_cache_env(&cwd, env);
         let params = if env.is_empty() {
             CreateParams {
                 id: id.clone(),
@@ -292,7 +506,9 @@ impl TerminalManager {
             }
         };
 
-        self.backend.create_with_size(params, cols, rows).await?;
+        self.persistence
+            .create_with_size(params, cols, rows)
+            .await?;
         self.active_ids.write().await.insert(id.clone());
 
         // Start event bridge for this terminal
@@ -320,7 +536,7 @@ impl TerminalManager {
         let app_spec = ApplicationSpec {
             command,
             args,
-            env,
+            env: self.with_dependency_cache_env(&cwd, env),
             ready_timeout_ms: 5000,
         };
 
@@ -331,7 +547,7 @@ impl TerminalManager {
             app: Some(app_spec),
         };
 
-        self.backend.create(params).await?;
+        self.persistence.create(params).await?;
         self.active_ids.write().await.insert(id.clone());
 
         // Start event bridge for this terminal
@@ -356,7 +572,7 @@ impl TerminalManager {
         let app_spec = ApplicationSpec {
             command: params.command,
             args: params.args,
-            env: params.env,
+            env: self.with_dependency_cache_env(&params.cwd, params.env),
             ready_timeout_ms: 30000,
         };
 
@@ -367,7 +583,7 @@ impl TerminalManager {
             app: Some(app_spec),
         };
 
-        self.backend
+        self.persistence
             .create_with_size(create_params, params.cols, params.rows)
             .await?;
         self.active_ids.write().await.insert(params.id.clone());
@@ -385,11 +601,25 @@ impl TerminalManager {
         Ok(())
     }
 
+    fn write_lock(&self, id: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self
+            .write_locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        locks.entry(id.to_string()).or_default().clone()
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, data), fields(bytes = data.len()))]
     pub async fn write_terminal(&self, id: String, data: Vec<u8>) -> Result<(), String> {
+        let lock = self.write_lock(&id);
+        let _guard = lock.lock().await;
         self.backend.write(&id, &data).await
     }
 
+    #[tracing::instrument(level = "trace", skip(self, data), fields(bytes = data.len()))]
     pub async fn write_terminal_immediate(&self, id: String, data: Vec<u8>) -> Result<(), String> {
+        let lock = self.write_lock(&id);
+        let _guard = lock.lock().await;
         self.backend.write_immediate(&id, &data).await
     }
 
@@ -399,21 +629,25 @@ impl TerminalManager {
         data: Vec<u8>,
         use_bracketed_paste: bool,
     ) -> Result<(), String> {
-        let mut buf = Vec::with_capacity(data.len() + 20);
-
-        if use_bracketed_paste {
-            buf.extend_from_slice(b"\x1b[200~");
-        }
-
-        buf.extend_from_slice(&data);
-
-        if use_bracketed_paste {
-            buf.extend_from_slice(b"\x1b[201~");
-        }
+        let lock = self.write_lock(&id);
+        let _guard = lock.lock().await;
+        self.paste_and_submit_locked(&id, &data, use_bracketed_paste)
+            .await
+    }
 
-        buf.push(b'\r');
+    /// Caller must hold the write lock of `id`.
+    async fn paste_and_submit_locked(
+        &self,
+        id: &str,
+        data: &[u8],
+        use_bracketed_paste: bool,
+    ) -> Result<(), String> {
+        // Multi-line text typed without bracketing would run each line as it arrives
+        let bracketed = use_bracketed_paste
+            || (is_multiline(data) && self.backend.bracketed_paste_enabled(id).await);
+        let buf = build_paste_payload(data, bracketed);
 
-        self.backend.write_immediate(&id, &buf).await?;
+        self.backend.write_immediate(id, &buf).await?;
 
         if let Some(app_handle) = self.app_handle.read().await.as_ref() {
             let event_payload = serde_json::json!({ "terminal_id": id });
@@ -426,6 +660,114 @@ impl TerminalManager {
         Ok(())
     }
 
+    /// Write the same input to several terminals as one operation.
+    ///
+    /// Every target is locked (in sorted order, so concurrent broadcasts cannot deadlock) and
+    /// checked before anything is written, so either all terminals receive the input without
+    /// other writes interleaving or, when one is missing, none do. With `submit` the input is
+    /// pasted and followed by enter, like `paste_and_submit_terminal`.
+    pub async fn broadcast_to_terminals(
+        &self,
+        mut ids: Vec<String>,
+        data: Vec<u8>,
+        submit: bool,
+    ) -> Result<(), String> {
+        ids.sort();
+        ids.dedup();
+        if ids.is_empty() {
+            return Err("No terminals selected for broadcast".to_string());
+        }
+
+        let locks: Vec<_> = ids.iter().map(|id| self.write_lock(id)).collect();
+        let mut guards = Vec::with_capacity(locks.len());
+        for lock in &locks {
+            guards.push(lock.lock().await);
+        }
+
+        let mut missing = Vec::new();
+        for id in &ids {
+            if !self.backend.exists(id).await? {
+                missing.push(id.as_str());
+            }
+        }
+        if !missing.is_empty() {
+            return Err(format!(
+                "Broadcast aborted, terminals not found: {}",
+                missing.join(", ")
+            ));
+        }
+
+        let mut failures = Vec::new();
+        for id in &ids {
+            let result = if submit {
+                self.paste_and_submit_locked(id, &data, false).await
+            } else {
+                self.backend.write_immediate(id, &data).await
+            };
+            if let Err(e) = result {
+                warn!("Broadcast to terminal {id} failed: {e}");
+                failures.push(format!("{id}: {e}"));
+            }
+        }
+        drop(guards);
+
+        info!("Broadcast {} bytes to {} terminals", data.len(), ids.len());
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Broadcast failed for {}", failures.join("; ")))
+        }
+    }
+
+    /// Whether `data` would reach the terminal as separate lines because its program
+    /// has not enabled bracketed paste, so submitting it should wait for confirmation.
+    pub async fn paste_needs_confirmation(&self, id: &str, data: &[u8]) -> bool {
+        is_multiline(data) && !self.backend.bracketed_paste_enabled(id).await
+    }
+
+    /// Hold a paste until `confirm_paste` is called and ask the UI to confirm it.
+    pub async fn hold_paste_for_confirmation(
+        &self,
+        id: String,
+        data: Vec<u8>,
+        use_bracketed_paste: bool,
+    ) -> PasteOutcome {
+        let info = self
+            .pending_pastes
+            .lock()
+            .await
+            .hold(id, data, use_bracketed_paste);
+
+        if let Some(app_handle) = self.app_handle.read().await.as_ref() {
+            if let Err(e) = emit_event(app_handle, SchaltEvent::TerminalPasteConfirmation, &info) {
+                warn!(
+                    "Failed to emit paste confirmation event for {}: {e}",
+                    info.terminal_id
+                );
+            }
+        }
+
+        PasteOutcome::PendingConfirmation(info)
+    }
+
+    /// Submit or discard a held paste. Returns true when it was written to the terminal.
+    pub async fn confirm_paste(&self, paste_id: &str, accept: bool) -> Result<bool, String> {
+        let paste = self
+            .pending_pastes
+            .lock()
+            .await
+            .take(paste_id)
+            .ok_or_else(|| format!("No pending paste with id {paste_id}"))?;
+        if !accept {
+            info!("Discarded pending paste for terminal {}", paste.terminal_id);
+            return Ok(false);
+        }
+
+        self.paste_and_submit_terminal(paste.terminal_id, paste.data, paste.bracketed)
+            .await?;
+        Ok(true)
+    }
+
     pub async fn resize_terminal(&self, id: String, cols: u16, rows: u16) -> Result<(), String> {
         debug!("Resizing terminal {id}: {cols}x{rows}");
         self.backend.resize(&id, cols, rows).await
@@ -435,6 +777,10 @@ impl TerminalManager {
         info!("Closing terminal through manager: {id}");
         self.active_ids.write().await.remove(&id);
         self.unregister_terminal_session(&id).await;
+        self.write_locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&id);
         self.backend.close(&id).await
     }
 
@@ -475,6 +821,18 @@ impl TerminalManager {
         Ok(snapshot)
     }
 
+    /// Search the retained output of a terminal without sending the buffer to the frontend.
+    pub async fn search_terminal_buffer(
+        &self,
+        id: String,
+        query: BufferSearchQuery,
+    ) -> Result<BufferSearchResult, String> {
+        let snapshot = self.backend.snapshot(&id, None).await?;
+        tokio::task::spawn_blocking(move || search_buffer(&snapshot.data, &query))
+            .await
+            .map_err(|e| format!("Terminal search task failed: {e}"))?
+    }
+
     pub async fn close_all(&self) -> Result<(), String> {
         info!("Closing all terminals");
         let ids: Vec<String> = self.active_ids.read().await.iter().cloned().collect();
@@ -490,6 +848,7 @@ impl TerminalManager {
 
     pub async fn cleanup_all(&self) -> Result<(), String> {
         info!("Starting comprehensive terminal cleanup");
+        self.stop_stats_ticker();
 
         // First try to close all known terminals
         let close_result = self.close_all().await;
@@ -545,6 +904,45 @@ impl TerminalManager {
     pub async fn get_all_terminal_activity(&self) -> Vec<(String, u64)> {
         self.backend.get_all_terminal_activity().await
     }
+
+    pub async fn get_detected_ports(&self, id: &str) -> Vec<super::port_detection::DetectedPort> {
+        self.backend.get_detected_ports(id).await
+    }
+
+    pub async fn get_session_idle_states(&self) -> Vec<super::idle_detection::SessionIdlePayload> {
+        self.backend.session_idle_states().await
+    }
+
+    pub async fn get_session_agent_states(
+        &self,
+    ) -> Vec<crate::domains::agents::status::SessionAgentStatePayload> {
+        self.backend.session_agent_states().await
+    }
+
+    /// Usage summaries agents reported since the last call, one per agent run.
+    pub async fn take_agent_usage_updates(
+        &self,
+    ) -> Vec<crate::domains::agents::usage::UsageUpdate> {
+        self.backend.take_usage_updates().await
+    }
+
+    /// Commands run in a terminal whose shell emits OSC 133 markers, oldest first.
+    pub async fn get_command_history(
+        &self,
+        id: &str,
+    ) -> Vec<super::control_sequences::CommandRecord> {
+        self.backend.command_history(id).await
+    }
+
+    /// Resume a terminal whose output was paused for flooding; returns the skipped byte count.
+    pub async fn resume_terminal_output(&self, id: &str) -> Option<u64> {
+        self.backend.resume_output(id).await
+    }
+
+    /// Print a message into a terminal's output without sending it to its shell.
+    pub async fn append_terminal_output(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        self.backend.append_output(id, data).await
+    }
 }
 
 #[cfg(test)]
@@ -595,4 +993,43 @@ mod tests {
 
         manager.close_terminal("buf-term".into()).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_broadcast_is_all_or_nothing() {
+        let manager = TerminalManager::new();
+        for id in ["cast-a", "cast-b"] {
+            manager
+                .create_terminal(id.to_string(), "/tmp".to_string())
+                .await
+                .unwrap();
+        }
+
+        let err = manager
+            .broadcast_to_terminals(
+                vec!["cast-a".into(), "cast-missing".into()],
+                b"echo skipped-marker".to_vec(),
+                true,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("cast-missing"));
+
+        manager
+            .broadcast_to_terminals(
+                vec!["cast-b".into(), "cast-a".into(), "cast-a".into()],
+                b"echo broadcast-marker".to_vec(),
+                true,
+            )
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+        for id in ["cast-a", "cast-b"] {
+            let snapshot = manager.get_terminal_buffer(id.into(), None).await.unwrap();
+            let output = String::from_utf8_lossy(&snapshot.data);
+            assert!(output.contains("broadcast-marker"));
+            assert!(!output.contains("skipped-marker"));
+            manager.close_terminal(id.into()).await.unwrap();
+        }
+    }
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::*;
+    use std::path::Path;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Duration;
     use tokio::time::{sleep, timeout};
@@ -330,6 +331,63 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_renamed_session_terminal_keeps_running() {
+        let manager = TerminalManager::new();
+        let old_base = unique_id("session-before");
+        let new_base = unique_id("session-after");
+        let old_id = format!("{old_base}-bottom-0");
+        let new_id = format!("{new_base}-bottom-0");
+
+        manager
+            .create_terminal(old_id.clone(), "/tmp".to_string())
+            .await
+            .unwrap();
+        manager
+            .write_terminal(old_id.clone(), b"echo before-rename\n".to_vec())
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        let renamed = manager
+            .rename_session_terminals(
+                &old_base,
+                &new_base,
+                "after",
+                Path::new("/tmp"),
+                Path::new("/tmp"),
+            )
+            .await;
+        assert_eq!(renamed, vec![(old_id.clone(), new_id.clone())]);
+        assert!(!manager.terminal_exists(&old_id).await.unwrap());
+
+        manager
+            .write_terminal(new_id.clone(), b"echo after-rename\n".to_vec())
+            .await
+            .unwrap();
+        let result = timeout(Duration::from_secs(5), async {
+            loop {
+                if read_buffer(&manager, new_id.clone())
+                    .await
+                    .contains("after-rename")
+                {
+                    break;
+                }
+                sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await;
+        assert!(
+            result.is_ok(),
+            "Output should keep flowing after the rename"
+        );
+        assert!(read_buffer(&manager, new_id.clone())
+            .await
+            .contains("before-rename"));
+
+        safe_close(&manager, &new_id).await;
+    }
+
     #[tokio::test]
     async fn test_race_conditions_during_creation_destruction() {
         let manager = Arc::new(TerminalManager::new());
@@ -19,6 +19,8 @@ pub struct ApplicationSpec {
 pub struct TerminalSnapshot {
     pub seq: u64,
     pub start_seq: u64,
+    /// Where `data` begins: the requested `from_seq` for a delta, `start_seq` for the full buffer
+    pub from_seq: u64,
     pub data: Vec<u8>,
 }
 
@@ -60,13 +62,27 @@ pub trait TerminalBackend: Send + Sync {
 }
 
 pub mod ansi;
+pub mod buffer_search;
 pub mod coalescing;
 pub mod command_builder;
+pub mod container;
 pub mod control_sequences;
 pub mod idle_detection;
 pub mod lifecycle;
+pub mod line_buffer;
 pub mod local;
 pub mod manager;
+pub mod paste;
+pub mod persistence;
+pub mod port_detection;
+pub mod preview_proxy;
+pub mod process_tree;
+pub mod resource_limits;
+pub mod resource_usage;
+pub mod run_status;
+pub mod run_watch;
+pub mod scrollback;
+pub mod setup_runner;
 pub mod shell_invocation;
 pub mod utf8_stream;
 pub mod visible;
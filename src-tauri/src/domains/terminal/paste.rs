@@ -0,0 +1,156 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+const PREVIEW_CHARS: usize = 200;
+// Unconfirmed pastes are dropped after this long so stale follow-ups are never submitted late
+const PENDING_PASTE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// True when submitting `data` as typed input would press enter before its end.
+pub fn is_multiline(data: &[u8]) -> bool {
+    data.iter().any(|byte| matches!(byte, b'\n' | b'\r'))
+}
+
+/// Bytes written to the PTY for a paste followed by enter.
+/// Paste-end markers inside `data` are removed so the payload cannot leave bracketed mode early.
+pub fn build_paste_payload(data: &[u8], bracketed: bool) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(data.len() + PASTE_START.len() + PASTE_END.len() + 1);
+
+    if bracketed {
+        buf.extend_from_slice(PASTE_START);
+        let mut rest = data;
+        while let Some(pos) = rest.windows(PASTE_END.len()).position(|w| w == PASTE_END) {
+            buf.extend_from_slice(&rest[..pos]);
+            rest = &rest[pos + PASTE_END.len()..];
+        }
+        buf.extend_from_slice(rest);
+        buf.extend_from_slice(PASTE_END);
+    } else {
+        buf.extend_from_slice(data);
+    }
+
+    buf.push(b'\r');
+    buf
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PasteOutcome {
+    Submitted,
+    /// Held until `confirm_terminal_paste` accepts or rejects it
+    PendingConfirmation(PendingPasteInfo),
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PendingPasteInfo {
+    pub paste_id: String,
+    pub terminal_id: String,
+    pub line_count: usize,
+    pub preview: String,
+}
+
+#[derive(Debug)]
+pub struct PendingPaste {
+    pub terminal_id: String,
+    pub data: Vec<u8>,
+    pub bracketed: bool,
+    created: Instant,
+}
+
+/// Multi-line pastes waiting for the user to confirm them.
+#[derive(Default)]
+pub struct PendingPastes {
+    entries: HashMap<String, PendingPaste>,
+}
+
+impl PendingPastes {
+    pub fn hold(
+        &mut self,
+        terminal_id: String,
+        data: Vec<u8>,
+        bracketed: bool,
+    ) -> PendingPasteInfo {
+        self.hold_at(terminal_id, data, bracketed, Instant::now())
+    }
+
+    fn hold_at(
+        &mut self,
+        terminal_id: String,
+        data: Vec<u8>,
+        bracketed: bool,
+        now: Instant,
+    ) -> PendingPasteInfo {
+        self.entries
+            .retain(|_, paste| now.duration_since(paste.created) < PENDING_PASTE_TTL);
+
+        let text = String::from_utf8_lossy(&data);
+        let info = PendingPasteInfo {
+            paste_id: uuid::Uuid::new_v4().to_string(),
+            terminal_id: terminal_id.clone(),
+            line_count: text.lines().count(),
+            preview: text.chars().take(PREVIEW_CHARS).collect(),
+        };
+        self.entries.insert(
+            info.paste_id.clone(),
+            PendingPaste {
+                terminal_id,
+                data,
+                bracketed,
+                created: now,
+            },
+        );
+        info
+    }
+
+    pub fn take(&mut self, paste_id: &str) -> Option<PendingPaste> {
+        self.take_at(paste_id, Instant::now())
+    }
+
+    fn take_at(&mut self, paste_id: &str, now: Instant) -> Option<PendingPaste> {
+        self.entries
+            .remove(paste_id)
+            .filter(|paste| now.duration_since(paste.created) < PENDING_PASTE_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_and_neutralizes_embedded_paste_end() {
+        assert_eq!(build_paste_payload(b"ls", false), b"ls\r".to_vec());
+        assert_eq!(
+            build_paste_payload(b"one\ntwo", true),
+            b"\x1b[200~one\ntwo\x1b[201~\r".to_vec()
+        );
+        assert_eq!(
+            build_paste_payload(b"a\x1b[201~rm -rf x\n\x1b[201~b", true),
+            b"\x1b[200~arm -rf x\nb\x1b[201~\r".to_vec()
+        );
+        assert!(is_multiline(b"a\nb"));
+        assert!(is_multiline(b"a\rb"));
+        assert!(!is_multiline(b"single line"));
+    }
+
+    #[test]
+    fn pending_pastes_are_taken_once_and_expire() {
+        let mut pending = PendingPastes::default();
+        let start = Instant::now();
+
+        let info = pending.hold_at("term".to_string(), b"one\ntwo\n".to_vec(), true, start);
+        assert_eq!(info.line_count, 2);
+        assert_eq!(info.preview, "one\ntwo\n");
+        let taken = pending.take_at(&info.paste_id, start).unwrap();
+        assert_eq!(taken.terminal_id, "term");
+        assert!(taken.bracketed);
+        assert!(pending.take_at(&info.paste_id, start).is_none());
+
+        let stale = pending.hold_at("term".to_string(), b"x\ny".to_vec(), false, start);
+        assert!(pending
+            .take_at(&stale.paste_id, start + PENDING_PASTE_TTL)
+            .is_none());
+    }
+}
@@ -0,0 +1,560 @@
+use super::container::ContainerPtyAdapter;
+use super::{
+    sh_quote_string, ApplicationSpec, CreateParams, LocalPtyAdapter, TerminalBackend,
+    TerminalSnapshot,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+// Dedicated tmux server so the user's own sessions and configuration stay untouched
+const TMUX_SOCKET: &str = "schaltwerk";
+const SESSION_NAME_PREFIX: &str = "schaltwerk-";
+
+/// Whether session terminals run inside a terminal multiplexer that outlives the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalPersistenceMode {
+    #[default]
+    Off,
+    Tmux,
+    Screen,
+}
+
+static PERSISTENCE_MODE: RwLock<TerminalPersistenceMode> =
+    RwLock::new(TerminalPersistenceMode::Off);
+
+pub fn put_terminal_persistence_mode(mode: TerminalPersistenceMode) {
+    if let Ok(mut guard) = PERSISTENCE_MODE.write() {
+        *guard = mode;
+    }
+}
+
+fn persistence_mode() -> TerminalPersistenceMode {
+    PERSISTENCE_MODE
+        .read()
+        .map(|guard| *guard)
+        .unwrap_or_default()
+}
+
+/// Multiplexer session name for a terminal; the worktree hash keeps equally named sessions
+/// of different projects apart. tmux rejects `.` and `:` in names.
+pub fn persistent_session_name(terminal_id: &str, worktree: &Path) -> String {
+    let sanitized: String = terminal_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let digest = Sha256::digest(worktree.to_string_lossy().as_bytes());
+    let hash: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+    format!("{SESSION_NAME_PREFIX}{sanitized}-{hash}")
+}
+
+/// The session worktree directly below `worktrees_root` that contains `cwd`, if any.
+fn session_worktree(worktrees_root: &Path, cwd: &Path) -> Option<PathBuf> {
+    match cwd.strip_prefix(worktrees_root).ok()?.components().next()? {
+        Component::Normal(session) => Some(worktrees_root.join(session)),
+        _ => None,
+    }
+}
+
+// Sources the environment file named by $0, deletes it, then runs the command
+const SOURCE_ENV_SCRIPT: &str = r#". "$0" && rm -f "$0" && exec "$@""#;
+
+/// Write `env` as a shell script only the current user can read. A running tmux server does
+/// not see the client's environment, and `-e KEY=VALUE` would expose values such as API keys
+/// to anyone listing processes, so the new session sources this file instead.
+fn write_env_file(name: &str, env: &[(String, String)]) -> Result<PathBuf, String> {
+    use std::io::Write;
+
+    let script: String = env
+        .iter()
+        .map(|(key, value)| format!("export {key}={}\n", sh_quote_string(value)))
+        .collect();
+    let path = std::env::temp_dir().join(format!("{name}-{}.env", uuid::Uuid::new_v4()));
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&path)
+        .map_err(|e| format!("Failed to create environment file for {name}: {e}"))?;
+    file.write_all(script.as_bytes())
+        .map_err(|e| format!("Failed to write environment file for {name}: {e}"))?;
+    Ok(path)
+}
+
+fn build_tmux_args(
+    name: &str,
+    cwd: &str,
+    app: Option<&ApplicationSpec>,
+    env_file: Option<&Path>,
+    reattach: bool,
+) -> Vec<String> {
+    let mut args = vec!["-L".to_string(), TMUX_SOCKET.to_string()];
+    if reattach {
+        args.extend([
+            "attach-session".to_string(),
+            "-t".to_string(),
+            format!("={name}"),
+        ]);
+        return args;
+    }
+
+    args.extend(
+        [
+            "start-server",
+            ";",
+            "set-option",
+            "-g",
+            "status",
+            "off",
+            ";",
+            "new-session",
+            "-s",
+            name,
+            "-c",
+            cwd,
+        ]
+        .map(String::from),
+    );
+    if let Some(app) = app {
+        args.push("--".to_string());
+        if let Some(env_file) = env_file {
+            args.extend([
+                "sh".to_string(),
+                "-c".to_string(),
+                SOURCE_ENV_SCRIPT.to_string(),
+                env_file.to_string_lossy().to_string(),
+            ]);
+        }
+        args.push(app.command.clone());
+        args.extend(app.args.iter().cloned());
+    }
+    args
+}
+
+fn build_screen_args(name: &str, app: Option<&ApplicationSpec>, reattach: bool) -> Vec<String> {
+    if reattach {
+        return vec!["-D".to_string(), "-r".to_string(), name.to_string()];
+    }
+    let mut args = vec!["-S".to_string(), name.to_string()];
+    if let Some(app) = app {
+        args.push(app.command.clone());
+        args.extend(app.args.iter().cloned());
+    }
+    args
+}
+
+/// Whether `screen -ls` output lists a session called exactly `name`.
+fn screen_lists_session(output: &str, name: &str) -> bool {
+    let suffix = format!(".{name}");
+    output.lines().any(|line| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|entry| entry.ends_with(&suffix))
+    })
+}
+
+async fn has_session(mode: TerminalPersistenceMode, name: &str) -> bool {
+    match mode {
+        TerminalPersistenceMode::Off => false,
+        TerminalPersistenceMode::Tmux => Command::new("tmux")
+            .args(["-L", TMUX_SOCKET, "has-session", "-t", &format!("={name}")])
+            .output()
+            .await
+            .is_ok_and(|output| output.status.success()),
+        // `screen -ls` exits non-zero even when it lists sessions
+        TerminalPersistenceMode::Screen => Command::new("screen")
+            .args(["-ls", name])
+            .output()
+            .await
+            .is_ok_and(|output| {
+                screen_lists_session(&String::from_utf8_lossy(&output.stdout), name)
+            }),
+    }
+}
+
+/// Run a tmux command (`-L` socket prepended) or a screen command sent to session `name`.
+async fn session_command(
+    mode: TerminalPersistenceMode,
+    name: &str,
+    tmux_args: &[&str],
+    screen_args: &[&str],
+) -> Result<(), String> {
+    let output = match mode {
+        TerminalPersistenceMode::Off => return Ok(()),
+        TerminalPersistenceMode::Tmux => {
+            Command::new("tmux")
+                .args(["-L", TMUX_SOCKET])
+                .args(tmux_args)
+                .output()
+                .await
+        }
+        TerminalPersistenceMode::Screen => {
+            Command::new("screen")
+                .args(["-S", name, "-X"])
+                .args(screen_args)
+                .output()
+                .await
+        }
+    }
+    .map_err(|e| format!("Failed to run {mode:?} for {name}: {e}"))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+async fn kill_session(mode: TerminalPersistenceMode, name: &str) -> Result<(), String> {
+    let target = format!("={name}");
+    session_command(mode, name, &["kill-session", "-t", &target], &["quit"]).await
+}
+
+async fn rename_session(
+    mode: TerminalPersistenceMode,
+    name: &str,
+    new_name: &str,
+) -> Result<(), String> {
+    let target = format!("={name}");
+    session_command(
+        mode,
+        name,
+        &["rename-session", "-t", &target, new_name],
+        &["sessionname", new_name],
+    )
+    .await
+}
+
+/// Terminal backend that keeps session terminals alive in tmux or screen.
+///
+/// The first time a terminal id is created during an app run and its multiplexer session
+/// still exists, the terminal reattaches to it instead of starting the command again.
+/// Recreating a terminal later in the same run (e.g. an agent restart) starts it fresh.
+#[derive(Clone)]
+pub struct PersistentPtyAdapter {
+    containers: ContainerPtyAdapter,
+    local: Arc<LocalPtyAdapter>,
+    // The project's configured worktree root; terminals below it are session terminals
+    worktrees_root: Arc<RwLock<Option<PathBuf>>>,
+    started: Arc<Mutex<HashSet<String>>>,
+}
+
+impl PersistentPtyAdapter {
+    pub fn new(containers: ContainerPtyAdapter, local: Arc<LocalPtyAdapter>) -> Self {
+        Self {
+            containers,
+            local,
+            worktrees_root: Arc::new(RwLock::new(None)),
+            started: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    pub fn set_worktrees_root(&self, worktrees_root: &Path) {
+        if let Ok(mut guard) = self.worktrees_root.write() {
+            *guard = Some(worktrees_root.to_path_buf());
+        }
+    }
+
+    /// Wrap `params` in a multiplexer session when it is a session terminal; the flag says
+    /// whether it was.
+    async fn prepare(&self, params: CreateParams) -> Result<(CreateParams, bool), String> {
+        let mode = persistence_mode();
+        if mode == TerminalPersistenceMode::Off {
+            return Ok((params, false));
+        }
+        let worktrees_root = self
+            .worktrees_root
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone());
+        let Some(worktree) =
+            worktrees_root.and_then(|root| session_worktree(&root, Path::new(&params.cwd)))
+        else {
+            return Ok((params, false));
+        };
+
+        let name = persistent_session_name(&params.id, &worktree);
+        let first_start = self.started.lock().await.insert(params.id.clone());
+        let exists = has_session(mode, &name).await;
+        let reattach = exists && first_start;
+        if reattach {
+            info!(
+                "Reattaching terminal {} to {mode:?} session {name}",
+                params.id
+            );
+        } else if exists {
+            if let Err(e) = kill_session(mode, &name).await {
+                warn!("Failed to end previous {mode:?} session {name}: {e}");
+            }
+        }
+
+        let app = params.app.as_ref();
+        let (command, args) = match mode {
+            TerminalPersistenceMode::Tmux => {
+                let env_file = match app {
+                    Some(app) if !reattach && !app.env.is_empty() => {
+                        Some(write_env_file(&name, &app.env)?)
+                    }
+                    _ => None,
+                };
+                (
+                    "tmux",
+                    build_tmux_args(&name, &params.cwd, app, env_file.as_deref(), reattach),
+                )
+            }
+            _ => ("screen", build_screen_args(&name, app, reattach)),
+        };
+
+        let params = CreateParams {
+            id: params.id,
+            cwd: params.cwd,
+            app: Some(ApplicationSpec {
+                command: command.to_string(),
+                args,
+                env: params
+                    .app
+                    .as_ref()
+                    .map(|app| app.env.clone())
+                    .unwrap_or_default(),
+                ready_timeout_ms: params
+                    .app
+                    .as_ref()
+                    .map(|app| app.ready_timeout_ms)
+                    .unwrap_or(5000),
+            }),
+        };
+        Ok((params, true))
+    }
+
+    /// End the multiplexer sessions behind `terminal_ids`, e.g. once their session is cancelled.
+    pub async fn kill_persistent_sessions(&self, terminal_ids: &[String], worktree: &Path) {
+        let mode = persistence_mode();
+        if mode == TerminalPersistenceMode::Off {
+            return;
+        }
+        for id in terminal_ids {
+            self.started.lock().await.remove(id);
+            let name = persistent_session_name(id, worktree);
+            if has_session(mode, &name).await {
+                match kill_session(mode, &name).await {
+                    Ok(()) => info!("Ended {mode:?} session {name}"),
+                    Err(e) => warn!("Failed to end {mode:?} session {name}: {e}"),
+                }
+            }
+        }
+    }
+
+    /// Carry the multiplexer sessions of renamed terminals over to their new ids and
+    /// worktree, so they are still reattached after a restart.
+    pub async fn rename_persistent_sessions(
+        &self,
+        renamed: &[(String, String)],
+        old_worktree: &Path,
+        new_worktree: &Path,
+    ) {
+        let mode = persistence_mode();
+        if mode == TerminalPersistenceMode::Off {
+            return;
+        }
+        for (old_id, new_id) in renamed {
+            {
+                let mut started = self.started.lock().await;
+                if started.remove(old_id) {
+                    started.insert(new_id.clone());
+                }
+            }
+            let name = persistent_session_name(old_id, old_worktree);
+            let new_name = persistent_session_name(new_id, new_worktree);
+            if has_session(mode, &name).await {
+                match rename_session(mode, &name, &new_name).await {
+                    Ok(()) => info!("Renamed {mode:?} session {name} to {new_name}"),
+                    Err(e) => warn!("Failed to rename {mode:?} session {name}: {e}"),
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TerminalBackend for PersistentPtyAdapter {
+    async fn create(&self, params: CreateParams) -> Result<(), String> {
+        let params = self.containers.prepare(params).await?;
+        let (params, persistent) = self.prepare(params).await?;
+        let id = params.id.clone();
+        self.local.create(params).await?;
+        self.local.set_persistent(&id, persistent).await;
+        Ok(())
+    }
+
+    async fn create_with_size(
+        &self,
+        params: CreateParams,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(), String> {
+        let params = self.containers.prepare(params).await?;
+        let (params, persistent) = self.prepare(params).await?;
+        let id = params.id.clone();
+        self.local.create_with_size(params, cols, rows).await?;
+        self.local.set_persistent(&id, persistent).await;
+        Ok(())
+    }
+
+    async fn write(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        self.local.write(id, data).await
+    }
+
+    async fn write_immediate(&self, id: &str, data: &[u8]) -> Result<(), String> {
+        self.local.write_immediate(id, data).await
+    }
+
+    async fn resize(&self, id: &str, cols: u16, rows: u16) -> Result<(), String> {
+        self.local.resize(id, cols, rows).await
+    }
+
+    async fn close(&self, id: &str) -> Result<(), String> {
+        self.local.close(id).await
+    }
+
+    async fn exists(&self, id: &str) -> Result<bool, String> {
+        self.local.exists(id).await
+    }
+
+    async fn snapshot(&self, id: &str, from_seq: Option<u64>) -> Result<TerminalSnapshot, String> {
+        self.local.snapshot(id, from_seq).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent() -> ApplicationSpec {
+        ApplicationSpec {
+            command: "/usr/local/bin/claude".to_string(),
+            args: vec!["--continue".to_string()],
+            env: vec![("FOO".to_string(), "bar".to_string())],
+            ready_timeout_ms: 5000,
+        }
+    }
+
+    #[test]
+    fn session_names_are_sanitized_and_scoped_by_worktree() {
+        let a = Path::new("/repo-a/.schaltwerk/worktrees/demo");
+        let b = Path::new("/repo-b/.schaltwerk/worktrees/demo");
+        let name = persistent_session_name("session-demo.x~1a2b-top", a);
+        assert!(name.starts_with("schaltwerk-session-demo-x-1a2b-top-"));
+        assert_eq!(name, persistent_session_name("session-demo.x~1a2b-top", a));
+        assert_ne!(name, persistent_session_name("session-demo.x~1a2b-top", b));
+    }
+
+    #[test]
+    fn only_session_worktrees_are_persisted() {
+        let root = Path::new("/repo/.schaltwerk/worktrees");
+        let worktree = Some(PathBuf::from("/repo/.schaltwerk/worktrees/demo"));
+        assert_eq!(
+            session_worktree(root, Path::new("/repo/.schaltwerk/worktrees/demo")),
+            worktree
+        );
+        assert_eq!(
+            session_worktree(root, Path::new("/repo/.schaltwerk/worktrees/demo/src")),
+            worktree
+        );
+        assert_eq!(session_worktree(root, root), None);
+        assert_eq!(session_worktree(root, Path::new("/repo")), None);
+
+        // A custom worktree root outside the repository
+        let custom = Path::new("/work/trees");
+        assert_eq!(
+            session_worktree(custom, Path::new("/work/trees/demo/src")),
+            Some(PathBuf::from("/work/trees/demo"))
+        );
+        assert_eq!(
+            session_worktree(custom, Path::new("/repo/.schaltwerk/worktrees/demo")),
+            None
+        );
+    }
+
+    #[test]
+    fn tmux_args_start_fresh_or_reattach() {
+        let env_file = Path::new("/tmp/schaltwerk-t.env");
+        let fresh = build_tmux_args(
+            "schaltwerk-t",
+            "/wt/demo",
+            Some(&agent()),
+            Some(env_file),
+            false,
+        );
+        assert_eq!(&fresh[..2], ["-L", TMUX_SOCKET]);
+        assert!(fresh
+            .windows(4)
+            .any(|w| w == ["new-session", "-s", "schaltwerk-t", "-c"]));
+        // Environment values never appear on the command line
+        assert!(!fresh.iter().any(|arg| arg.contains("bar")));
+        assert!(fresh.ends_with(&[
+            "--".to_string(),
+            "sh".to_string(),
+            "-c".to_string(),
+            SOURCE_ENV_SCRIPT.to_string(),
+            "/tmp/schaltwerk-t.env".to_string(),
+            "/usr/local/bin/claude".to_string(),
+            "--continue".to_string()
+        ]));
+
+        let reattach = build_tmux_args("schaltwerk-t", "/wt/demo", Some(&agent()), None, true);
+        assert_eq!(
+            reattach,
+            ["-L", TMUX_SOCKET, "attach-session", "-t", "=schaltwerk-t"]
+        );
+    }
+
+    #[test]
+    fn env_file_exports_quoted_values() {
+        let env = vec![("API_KEY".to_string(), "it's secret".to_string())];
+        let path = write_env_file("schaltwerk-env-test", &env).unwrap();
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(script, "export API_KEY='it'\\''s secret'\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn screen_args_and_session_listing() {
+        assert_eq!(
+            build_screen_args("schaltwerk-t", Some(&agent()), false),
+            ["-S", "schaltwerk-t", "/usr/local/bin/claude", "--continue"]
+        );
+        assert_eq!(
+            build_screen_args("schaltwerk-t", None, true),
+            ["-D", "-r", "schaltwerk-t"]
+        );
+
+        let listing =
+            "There is a screen on:\n\t4242.schaltwerk-t\t(Detached)\n1 Socket in /run/screen.\n";
+        assert!(screen_lists_session(listing, "schaltwerk-t"));
+        assert!(!screen_lists_session(listing, "schaltwerk"));
+    }
+}
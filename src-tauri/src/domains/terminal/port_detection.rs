@@ -0,0 +1,172 @@
+use super::ansi::strip_ansi;
+use super::line_buffer::LineBuffer;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// Hosts dev servers typically announce; wildcard binds are reported as localhost
+const LOCAL_HOSTS: &[(&str, &str)] = &[
+    ("localhost", "localhost"),
+    ("127.0.0.1", "127.0.0.1"),
+    ("0.0.0.0", "localhost"),
+    ("[::1]", "localhost"),
+    ("[::]", "localhost"),
+];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DetectedPort {
+    pub port: u16,
+    pub url: String,
+    pub detected_at: i64,
+}
+
+#[derive(Default)]
+struct DetectorState {
+    ports: Vec<DetectedPort>,
+    pending: LineBuffer,
+}
+
+/// Collects local dev-server URLs announced in run terminal output.
+#[derive(Clone, Default)]
+pub struct PortDetector {
+    terminals: Arc<Mutex<HashMap<String, DetectorState>>>,
+}
+
+/// Extract `(port, url)` pairs for local addresses mentioned in a line of output.
+pub fn extract_local_urls(line: &str) -> Vec<(u16, String)> {
+    let line = strip_ansi(line);
+    let mut found: Vec<(u16, String)> = Vec::new();
+
+    for (host, display_host) in LOCAL_HOSTS {
+        let mut search_from = 0;
+        while let Some(offset) = line[search_from..].find(host) {
+            let host_start = search_from + offset;
+            let host_end = host_start + host.len();
+            search_from = host_end;
+
+            let rest = &line[host_end..];
+            let Some(port_str) = rest.strip_prefix(':') else {
+                continue;
+            };
+            let digits: String = port_str
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            let Ok(port) = digits.parse::<u16>() else {
+                continue;
+            };
+            if port == 0 {
+                continue;
+            }
+
+            let scheme = if line[..host_start].ends_with("https://") {
+                "https"
+            } else {
+                "http"
+            };
+            let url = format!("{scheme}://{display_host}:{port}");
+            if !found.iter().any(|(p, _)| *p == port) {
+                found.push((port, url));
+            }
+        }
+    }
+
+    found
+}
+
+impl PortDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan output for newly announced ports. Returns true when a new port was recorded.
+    pub async fn observe_output(&self, id: &str, data: &[u8]) -> bool {
+        let mut terminals = self.terminals.lock().await;
+        let state = terminals.entry(id.to_string()).or_default();
+        let Some(complete) = state.pending.push(data) else {
+            return false;
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut added = false;
+        for line in complete.split(['\n', '\r']) {
+            for (port, url) in extract_local_urls(line) {
+                if state.ports.iter().any(|p| p.port == port) {
+                    continue;
+                }
+                state.ports.push(DetectedPort {
+                    port,
+                    url,
+                    detected_at: now,
+                });
+                added = true;
+            }
+        }
+        added
+    }
+
+    pub async fn ports_for(&self, id: &str) -> Vec<DetectedPort> {
+        self.terminals
+            .lock()
+            .await
+            .get(id)
+            .map(|state| state.ports.clone())
+            .unwrap_or_default()
+    }
+
+    pub async fn clear(&self, id: &str) {
+        self.terminals.lock().await.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_common_dev_server_announcements() {
+        assert_eq!(
+            extract_local_urls("  ➜  Local:   http://localhost:5173/"),
+            vec![(5173, "http://localhost:5173".to_string())]
+        );
+        assert_eq!(
+            extract_local_urls("Listening on 0.0.0.0:8080"),
+            vec![(8080, "http://localhost:8080".to_string())]
+        );
+        assert_eq!(
+            extract_local_urls("ready - started server on https://127.0.0.1:3443"),
+            vec![(3443, "https://127.0.0.1:3443".to_string())]
+        );
+        assert!(extract_local_urls("compiled successfully in 120ms").is_empty());
+    }
+
+    #[test]
+    fn strips_ansi_before_matching() {
+        assert_eq!(
+            extract_local_urls("\x1b[36mhttp://localhost:\x1b[1m3000\x1b[22m/\x1b[39m"),
+            vec![(3000, "http://localhost:3000".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn detector_handles_split_lines_and_dedupes() {
+        let detector = PortDetector::new();
+        let id = "run-terminal-demo";
+
+        assert!(!detector.observe_output(id, b"Server at http://local").await);
+        assert!(detector.observe_output(id, b"host:4000\r\n").await);
+        assert!(
+            !detector
+                .observe_output(id, b"again http://localhost:4000\n")
+                .await
+        );
+
+        let ports = detector.ports_for(id).await;
+        assert_eq!(ports.len(), 1);
+        assert_eq!(ports[0].port, 4000);
+
+        detector.clear(id).await;
+        assert!(detector.ports_for(id).await.is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+use futures::future::BoxFuture;
+use log::{debug, info, warn};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+pub const DEFAULT_PREVIEW_PROXY_PORT: u16 = 8650;
+// Request and response heads larger than this are rejected instead of buffered
+const MAX_HEAD: usize = 16 * 1024;
+// Pause after a failed accept (e.g. out of file descriptors) so the loop does not spin
+const ACCEPT_ERROR_BACKOFF: std::time::Duration = std::time::Duration::from_millis(100);
+const PREVIEW_HOST_SUFFIX: &str = ".localhost";
+
+/// Resolves the lowercase session label of a preview host to the dev-server port to forward to.
+/// The label is empty for the bare `localhost` host, which serves the orchestrator.
+pub type PreviewPortResolver = Arc<dyn Fn(String) -> BoxFuture<'static, Option<u16>> + Send + Sync>;
+
+/// Local reverse proxy serving `http://{session}.localhost:PORT` from each session's dev server.
+///
+/// The route is picked from the `Host` header of the first request, so every connection is
+/// limited to one request with `Connection: close` in both directions; a reused keep-alive
+/// connection could otherwise reach the wrong session. Websocket upgrades (e.g. HMR) are
+/// forwarded as raw TCP.
+pub struct PreviewProxy {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl PreviewProxy {
+    /// Bind on `127.0.0.1:port` (`0` picks a free port) and start accepting connections.
+    pub async fn start(port: u16, resolver: PreviewPortResolver) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Failed to bind preview proxy on port {port}: {e}"))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read preview proxy address: {e}"))?
+            .port();
+
+        info!("Preview proxy listening on http://*.localhost:{port}");
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(stream, resolver.clone()));
+                    }
+                    Err(e) => {
+                        warn!("Preview proxy failed to accept connection: {e}");
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { port, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Stable preview URL for a session, or the bare `localhost` host for the orchestrator
+    /// (session names are never empty). Hostnames are case-insensitive so the label is lowercased.
+    pub fn preview_url(&self, session_name: Option<&str>) -> String {
+        match session_name {
+            Some(name) => format!(
+                "http://{}{PREVIEW_HOST_SUFFIX}:{}",
+                name.to_lowercase(),
+                self.port
+            ),
+            None => format!("http://localhost:{}", self.port),
+        }
+    }
+}
+
+impl Drop for PreviewProxy {
+    fn drop(&mut self) {
+        info!("Stopping preview proxy on port {}", self.port);
+        self.task.abort();
+    }
+}
+
+/// Session label from the `Host` header of a request head, e.g. `feature-x` for `feature-x.localhost:8650`.
+/// The bare `localhost` host yields an empty label.
+pub fn session_label_from_head(head: &[u8]) -> Option<String> {
+    let host = header_lines(head)
+        .find_map(|(name, value)| name.eq_ignore_ascii_case("host").then_some(value))?;
+
+    let hostname = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let hostname = hostname.to_ascii_lowercase();
+    if hostname == PREVIEW_HOST_SUFFIX[1..] {
+        return Some(String::new());
+    }
+    let label = hostname.strip_suffix(PREVIEW_HOST_SUFFIX)?.to_string();
+
+    if label.is_empty() || label.contains('.') {
+        return None;
+    }
+    Some(label)
+}
+
+fn find_head_end(buffer: &[u8]) -> Option<usize> {
+    buffer
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+fn header_lines(head: &[u8]) -> impl Iterator<Item = (&str, &str)> {
+    std::str::from_utf8(head)
+        .unwrap_or_default()
+        .split("\r\n")
+        .skip(1)
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        })
+}
+
+fn is_upgrade(head: &[u8]) -> bool {
+    header_lines(head).any(|(name, _)| name.eq_ignore_ascii_case("upgrade"))
+}
+
+/// Rewrite the head of an HTTP message (body bytes after it are kept) so it asks for the
+/// connection to close after this exchange.
+fn with_connection_close(message: &[u8], head_end: usize) -> Vec<u8> {
+    let Ok(head) = std::str::from_utf8(&message[..head_end]) else {
+        return message.to_vec();
+    };
+    let mut rewritten = String::with_capacity(head.len() + 20);
+    for (index, line) in head.trim_end_matches("\r\n").split("\r\n").enumerate() {
+        let name = line.split_once(':').map(|(name, _)| name.trim());
+        let hop_by_hop = index > 0
+            && name.is_some_and(|name| {
+                ["connection", "keep-alive", "proxy-connection"]
+                    .iter()
+                    .any(|hop| name.eq_ignore_ascii_case(hop))
+            });
+        if !hop_by_hop {
+            rewritten.push_str(line);
+            rewritten.push_str("\r\n");
+        }
+    }
+    rewritten.push_str("Connection: close\r\n\r\n");
+
+    let mut out = rewritten.into_bytes();
+    out.extend_from_slice(&message[head_end..]);
+    out
+}
+
+/// Read from `stream` into `buffer` until it holds a complete message head and return
+/// where the head ends; `None` when the peer closed first.
+async fn read_head(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> std::io::Result<Option<usize>> {
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = find_head_end(buffer) {
+            return Ok(Some(end));
+        }
+        if buffer.len() > MAX_HEAD {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "message head too large",
+            ));
+        }
+        match stream.read(&mut chunk).await? {
+            0 => return Ok(None),
+            n => buffer.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+async fn respond_error(mut stream: TcpStream, status: &str, message: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}",
+        message.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn handle_connection(mut client: TcpStream, resolver: PreviewPortResolver) {
+    let mut buffer = Vec::with_capacity(1024);
+    let head_end = match read_head(&mut client, &mut buffer).await {
+        Ok(Some(end)) => end,
+        Ok(None) => return,
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            respond_error(
+                client,
+                "431 Request Header Fields Too Large",
+                "Request head too large",
+            )
+            .await;
+            return;
+        }
+        Err(e) => {
+            debug!("Preview proxy failed to read request: {e}");
+            return;
+        }
+    };
+
+    let Some(label) = session_label_from_head(&buffer) else {
+        respond_error(
+            client,
+            "400 Bad Request",
+            "Use http://<session>.localhost:<port> to open a session preview",
+        )
+        .await;
+        return;
+    };
+    let target = if label.is_empty() {
+        "the orchestrator".to_string()
+    } else {
+        format!("session '{label}'")
+    };
+
+    let Some(upstream_port) = resolver(label.clone()).await else {
+        respond_error(
+            client,
+            "502 Bad Gateway",
+            &format!("No running dev server detected for {target}"),
+        )
+        .await;
+        return;
+    };
+
+    let mut upstream = match TcpStream::connect(("127.0.0.1", upstream_port)).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            respond_error(
+                client,
+                "502 Bad Gateway",
+                &format!("Dev server for {target} on port {upstream_port} is unreachable: {e}"),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let upgrade = is_upgrade(&buffer[..head_end]);
+    let request = if upgrade {
+        buffer
+    } else {
+        with_connection_close(&buffer, head_end)
+    };
+    if let Err(e) = upstream.write_all(&request).await {
+        debug!("Preview proxy failed to forward request for {label}: {e}");
+        return;
+    }
+
+    // Plain responses get `Connection: close` too, so the browser opens a new connection
+    // (and gets routed again) for its next request
+    let mut response = Vec::with_capacity(1024);
+    let response_head_end = match read_head(&mut upstream, &mut response).await {
+        Ok(Some(end)) => end,
+        Ok(None) => return,
+        Err(e) => {
+            debug!("Preview proxy failed to read response for {label}: {e}");
+            return;
+        }
+    };
+    let switching = response.starts_with(b"HTTP/1.1 101") || response.starts_with(b"HTTP/1.0 101");
+    let response = if upgrade && switching {
+        response
+    } else {
+        with_connection_close(&response, response_head_end)
+    };
+    if let Err(e) = client.write_all(&response).await {
+        debug!("Preview proxy failed to forward response for {label}: {e}");
+        return;
+    }
+
+    if let Err(e) = tokio::io::copy_bidirectional(&mut client, &mut upstream).await {
+        debug!("Preview proxy connection for {label} ended: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_session_label_from_host_header() {
+        assert_eq!(
+            session_label_from_head(b"GET / HTTP/1.1\r\nHost: Feature-X.localhost:8650\r\n\r\n"),
+            Some("feature-x".to_string())
+        );
+        assert_eq!(
+            session_label_from_head(b"GET / HTTP/1.1\r\nhost: demo.localhost\r\n\r\n"),
+            Some("demo".to_string())
+        );
+        assert_eq!(
+            session_label_from_head(b"GET / HTTP/1.1\r\nHost: localhost:8650\r\n\r\n"),
+            Some(String::new())
+        );
+        assert_eq!(
+            session_label_from_head(b"GET / HTTP/1.1\r\nHost: .localhost:8650\r\n\r\n"),
+            None
+        );
+        assert_eq!(
+            session_label_from_head(b"GET / HTTP/1.1\r\nHost: a.b.localhost\r\n\r\n"),
+            None
+        );
+        assert_eq!(session_label_from_head(b"GET / HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn websocket_upgrades_keep_their_connection_header() {
+        let upgrade = b"GET /hmr HTTP/1.1\r\nHost: demo.localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\r\n";
+        assert!(is_upgrade(upgrade));
+
+        let plain = b"GET / HTTP/1.1\r\nHost: demo.localhost\r\nKeep-Alive: timeout=5\r\n\r\nbody";
+        assert!(!is_upgrade(plain));
+        let end = find_head_end(plain).unwrap();
+        assert_eq!(
+            with_connection_close(plain, end),
+            b"GET / HTTP/1.1\r\nHost: demo.localhost\r\nConnection: close\r\n\r\nbody".to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn forwards_requests_to_resolved_port() {
+        let upstream = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = upstream.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_string();
+            assert!(request.starts_with("GET /app HTTP/1.1"));
+            assert!(request.ends_with("\r\nConnection: close\r\n\r\n"));
+            assert!(!request.contains("keep-alive"));
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .await
+                .unwrap();
+        });
+
+        let resolver: PreviewPortResolver = Arc::new(move |label: String| {
+            Box::pin(async move { (label == "demo").then_some(upstream_port) })
+        });
+        let proxy = PreviewProxy::start(0, resolver).await.unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        client
+            .write_all(
+                b"GET /app HTTP/1.1\r\nHost: demo.localhost\r\nConnection: keep-alive\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+        assert!(response.ends_with(b"\r\nConnection: close\r\n\r\nok"));
+
+        let mut missing = TcpStream::connect(("127.0.0.1", proxy.port()))
+            .await
+            .unwrap();
+        missing
+            .write_all(b"GET / HTTP/1.1\r\nHost: other.localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        missing.read_to_end(&mut response).await.unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 502 Bad Gateway"));
+    }
+}
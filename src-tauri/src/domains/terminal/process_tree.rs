@@ -0,0 +1,244 @@
+use super::resource_usage::{list_processes, ProcessRow};
+use log::{debug, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+pub const DEFAULT_KILL_GRACE_PERIOD_MS: u64 = 1500;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// How long SIGKILLed processes get to disappear before they count as orphaned
+const KILL_SETTLE_TIME: Duration = Duration::from_millis(500);
+
+static KILL_GRACE_PERIOD_MS: RwLock<u64> = RwLock::new(DEFAULT_KILL_GRACE_PERIOD_MS);
+
+/// Time processes get between SIGTERM and SIGKILL when a terminal closes.
+pub fn put_kill_grace_period_ms(grace_ms: u64) {
+    if let Ok(mut guard) = KILL_GRACE_PERIOD_MS.write() {
+        *guard = grace_ms;
+    }
+}
+
+pub fn kill_grace_period() -> Duration {
+    Duration::from_millis(
+        KILL_GRACE_PERIOD_MS
+            .read()
+            .map(|guard| *guard)
+            .unwrap_or(DEFAULT_KILL_GRACE_PERIOD_MS),
+    )
+}
+
+/// Outcome of tearing down the processes of a closed terminal.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TerminalCleanupReport {
+    pub terminal_id: String,
+    /// Every process of the tree, root first
+    pub signaled: Vec<u32>,
+    /// Processes that ignored SIGTERM for the whole grace period
+    pub escalated: Vec<u32>,
+    /// Processes still alive after SIGKILL
+    pub orphaned: Vec<u32>,
+}
+
+impl TerminalCleanupReport {
+    /// Whether anything needed more than a polite SIGTERM.
+    pub fn is_noteworthy(&self) -> bool {
+        !self.escalated.is_empty() || !self.orphaned.is_empty()
+    }
+}
+
+/// `root` followed by all of its descendants, parents before children.
+fn tree_pids(rows: &[ProcessRow], root: u32) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for row in rows {
+        if row.pid != row.ppid {
+            children.entry(row.ppid).or_default().push(row.pid);
+        }
+    }
+
+    let mut pids = vec![root];
+    let mut index = 0;
+    while index < pids.len() {
+        if let Some(kids) = children.get(&pids[index]) {
+            for kid in kids {
+                if !pids.contains(kid) {
+                    pids.push(*kid);
+                }
+            }
+        }
+        index += 1;
+    }
+    pids
+}
+
+/// SIGHUP the terminal's root process and SIGTERM its whole tree, wait up to `grace`,
+/// then SIGKILL the descendants that are left. The tree is captured up front so children
+/// that get reparented when their parent exits are still reached. The root is the caller's
+/// child and only gets reaped by the caller, so it is signaled but not waited for here.
+pub async fn kill_process_tree(
+    terminal_id: &str,
+    root_pid: u32,
+    grace: Duration,
+) -> TerminalCleanupReport {
+    let mut report = TerminalCleanupReport {
+        terminal_id: terminal_id.to_string(),
+        ..Default::default()
+    };
+
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (root_pid, grace);
+        report
+    }
+
+    #[cfg(target_family = "unix")]
+    {
+        let pids = match list_processes().await {
+            Ok(rows) => tree_pids(&rows, root_pid),
+            Err(e) => {
+                warn!("[{terminal_id}] Could not list processes, only signaling {root_pid}: {e}");
+                vec![root_pid]
+            }
+        };
+
+        signal(root_pid, libc::SIGHUP);
+        for pid in &pids {
+            if signal(*pid, libc::SIGTERM) {
+                report.signaled.push(*pid);
+            }
+        }
+        debug!(
+            "[{terminal_id}] Sent SIGTERM to {} process(es): {:?}",
+            report.signaled.len(),
+            report.signaled
+        );
+
+        let descendants: Vec<u32> = report
+            .signaled
+            .iter()
+            .copied()
+            .filter(|pid| *pid != root_pid)
+            .collect();
+        let survivors = wait_for_exit(&descendants, grace).await;
+        if survivors.is_empty() {
+            return report;
+        }
+
+        warn!("[{terminal_id}] Escalating to SIGKILL for {survivors:?}");
+        for pid in &survivors {
+            signal(*pid, libc::SIGKILL);
+        }
+        report.orphaned = wait_for_exit(&survivors, KILL_SETTLE_TIME).await;
+        report.escalated = survivors;
+        if !report.orphaned.is_empty() {
+            warn!(
+                "[{terminal_id}] Processes survived SIGKILL: {:?}",
+                report.orphaned
+            );
+        }
+        report
+    }
+}
+
+/// Send `signal` to `pid`; false when the process no longer exists.
+#[cfg(target_family = "unix")]
+fn signal(pid: u32, signal: libc::c_int) -> bool {
+    let result = unsafe { libc::kill(pid as libc::pid_t, signal) };
+    if result == -1 {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::ESRCH) {
+            return false;
+        }
+        debug!("Failed to send signal {signal} to process {pid}: {err}");
+    }
+    true
+}
+
+#[cfg(target_family = "unix")]
+pub(super) fn process_alive(pid: u32) -> bool {
+    if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+        return !is_zombie(pid);
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Exited processes waiting to be reaped still accept signals.
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: u32) -> bool {
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            stat.rsplit_once(')')
+                .map(|(_, rest)| rest.trim_start().starts_with('Z'))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(all(target_family = "unix", not(target_os = "linux")))]
+fn is_zombie(_pid: u32) -> bool {
+    false
+}
+
+/// Polls until every pid is gone or `timeout` passes; returns the ones still alive.
+#[cfg(target_family = "unix")]
+async fn wait_for_exit(pids: &[u32], timeout: Duration) -> Vec<u32> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let alive: Vec<u32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| process_alive(*pid))
+            .collect();
+        if alive.is_empty() || tokio::time::Instant::now() >= deadline {
+            return alive;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pid: u32, ppid: u32) -> ProcessRow {
+        ProcessRow {
+            pid,
+            ppid,
+            cpu_seconds: 0.0,
+            rss_kb: 0,
+        }
+    }
+
+    #[test]
+    fn collects_descendants_parents_first() {
+        let rows = vec![
+            row(10, 1),
+            row(12, 11),
+            row(11, 10),
+            row(13, 10),
+            row(20, 1),
+        ];
+        assert_eq!(tree_pids(&rows, 10), vec![10, 11, 13, 12]);
+        assert_eq!(tree_pids(&rows, 99), vec![99]);
+    }
+
+    #[tokio::test]
+    #[cfg(target_family = "unix")]
+    async fn kills_background_children_of_the_root() {
+        let mut shell = tokio::process::Command::new("sh")
+            .args(["-c", "sleep 30 & sleep 30 & wait"])
+            .spawn()
+            .expect("spawn shell");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let root = shell.id().expect("shell pid");
+        let report = kill_process_tree("test-term", root, Duration::from_millis(500)).await;
+
+        assert!(report.signaled.len() >= 3, "report: {report:?}");
+        assert!(report.orphaned.is_empty(), "report: {report:?}");
+        let _ = shell.wait().await;
+        for pid in &report.signaled[1..] {
+            assert!(!process_alive(*pid), "child {pid} survived");
+        }
+    }
+}
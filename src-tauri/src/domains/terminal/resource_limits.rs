@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+const MAX_NICE: i32 = 19;
+
+/// Limits applied to agent processes when their terminal spawns them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentResourceLimits {
+    /// Niceness the agent runs with, 0-19; 0 keeps the app's priority
+    pub nice: i32,
+    /// Data segment cap (`RLIMIT_DATA`), which counts the heap and anonymous mappings but
+    /// not the virtual address space runtimes like V8 reserve up front
+    pub max_memory_mb: Option<u64>,
+    /// CPU time after which the agent is killed (`RLIMIT_CPU`)
+    pub max_cpu_seconds: Option<u64>,
+}
+
+impl AgentResourceLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.nice <= 0 && self.max_memory_mb.is_none() && self.max_cpu_seconds.is_none()
+    }
+}
+
+static AGENT_RESOURCE_LIMITS: RwLock<Option<AgentResourceLimits>> = RwLock::new(None);
+
+/// Applies to agents spawned afterwards; running agents keep their limits.
+pub fn put_agent_resource_limits(limits: AgentResourceLimits) {
+    if let Ok(mut guard) = AGENT_RESOURCE_LIMITS.write() {
+        *guard = Some(limits);
+    }
+}
+
+pub fn agent_resource_limits() -> AgentResourceLimits {
+    AGENT_RESOURCE_LIMITS
+        .read()
+        .ok()
+        .and_then(|guard| *guard)
+        .unwrap_or_default()
+}
+
+/// `program` with `args`, started through a shell that lowers its priority and caps its
+/// memory and CPU time before exec'ing it. The limits are in place before the agent runs
+/// and every process it forks inherits them.
+pub fn wrap_command(
+    program: String,
+    args: Vec<String>,
+    limits: &AgentResourceLimits,
+) -> (String, Vec<String>) {
+    if limits.is_unlimited() || cfg!(not(target_family = "unix")) {
+        return (program, args);
+    }
+
+    // A failing ulimit is reported in the terminal but does not keep the agent from starting
+    let mut script = String::new();
+    if let Some(mb) = limits.max_memory_mb {
+        script.push_str(&format!("ulimit -d {}; ", mb.saturating_mul(1024)));
+    }
+    if let Some(seconds) = limits.max_cpu_seconds {
+        script.push_str(&format!("ulimit -t {seconds}; "));
+    }
+    script.push_str("exec ");
+    if limits.nice > 0 {
+        script.push_str(&format!("nice -n {} ", limits.nice.min(MAX_NICE)));
+    }
+    script.push_str("\"$@\"");
+
+    let mut wrapped = vec!["-c".to_string(), script, "sh".to_string(), program];
+    wrapped.extend(args);
+    ("/bin/sh".to_string(), wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_agents_start_unwrapped() {
+        let (program, args) = wrap_command(
+            "claude".to_string(),
+            vec!["--continue".to_string()],
+            &AgentResourceLimits::default(),
+        );
+        assert_eq!(program, "claude");
+        assert_eq!(args, vec!["--continue"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn limits_are_in_place_when_the_agent_starts() {
+        let limits = AgentResourceLimits {
+            nice: 7,
+            max_memory_mb: Some(4096),
+            max_cpu_seconds: Some(600),
+        };
+        let (program, args) = wrap_command(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "echo $(nice) $(ulimit -d) $(ulimit -t)".to_string(),
+            ],
+            &limits,
+        );
+
+        let output = std::process::Command::new(program)
+            .args(args)
+            .output()
+            .expect("run wrapped command");
+
+        let reported = String::from_utf8_lossy(&output.stdout);
+        let values: Vec<&str> = reported.split_whitespace().collect();
+        let nice: i32 = values[0].parse().unwrap();
+        assert!(nice >= 7, "niceness {nice}");
+        assert_eq!(values[1..], ["4194304", "600"]);
+    }
+}
@@ -0,0 +1,183 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TerminalResourceUsage {
+    pub terminal_id: String,
+    pub pid: u32,
+    /// CPU used by the process tree since the previous sample; 100.0 is one full core
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+    pub process_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ProcessRow {
+    pub(super) pid: u32,
+    pub(super) ppid: u32,
+    pub(super) cpu_seconds: f64,
+    pub(super) rss_kb: u64,
+}
+
+/// Parse `ps` CPU time: `[[dd-]hh:]mm:ss` on Linux, `mm:ss.cc` on macOS.
+fn parse_cpu_time(value: &str) -> Option<f64> {
+    let (days, clock) = match value.split_once('-') {
+        Some((days, clock)) => (days.parse::<f64>().ok()?, clock),
+        None => (0.0, value),
+    };
+    let mut seconds = 0.0;
+    for part in clock.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(days * 86_400.0 + seconds)
+}
+
+fn parse_ps_output(output: &str) -> Vec<ProcessRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(ProcessRow {
+                pid: fields.next()?.parse().ok()?,
+                ppid: fields.next()?.parse().ok()?,
+                cpu_seconds: parse_cpu_time(fields.next()?)?,
+                rss_kb: fields.next()?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Total CPU seconds, resident memory in KiB and process count of `root` and its descendants.
+fn tree_totals(rows: &[ProcessRow], root: u32) -> Option<(f64, u64, usize)> {
+    let mut children: HashMap<u32, Vec<&ProcessRow>> = HashMap::new();
+    for row in rows {
+        children.entry(row.ppid).or_default().push(row);
+    }
+    let root_row = rows.iter().find(|row| row.pid == root)?;
+
+    let mut totals = (0.0, 0, 0);
+    let mut stack = vec![root_row];
+    while let Some(row) = stack.pop() {
+        totals.0 += row.cpu_seconds;
+        totals.1 += row.rss_kb;
+        totals.2 += 1;
+        if let Some(kids) = children.get(&row.pid) {
+            stack.extend(kids.iter().filter(|kid| kid.pid != row.pid));
+        }
+    }
+    Some(totals)
+}
+
+pub(super) async fn list_processes() -> Result<Vec<ProcessRow>, String> {
+    let output = Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,time=,rss="])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ps: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("ps exited with {}", output.status));
+    }
+    Ok(parse_ps_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Samples the process trees of terminals; CPU usage is derived from consecutive samples.
+#[derive(Default)]
+pub struct ResourceSampler {
+    previous: HashMap<String, (Instant, f64)>,
+}
+
+impl ResourceSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn sample(
+        &mut self,
+        terminals: Vec<(String, u32)>,
+    ) -> Result<Vec<TerminalResourceUsage>, String> {
+        let rows = list_processes().await?;
+        let now = Instant::now();
+        Ok(self.sample_rows(&rows, terminals, now))
+    }
+
+    fn sample_rows(
+        &mut self,
+        rows: &[ProcessRow],
+        terminals: Vec<(String, u32)>,
+        now: Instant,
+    ) -> Vec<TerminalResourceUsage> {
+        let mut usage = Vec::new();
+        let mut previous = HashMap::new();
+
+        for (terminal_id, pid) in terminals {
+            let Some((cpu_seconds, rss_kb, process_count)) = tree_totals(rows, pid) else {
+                continue;
+            };
+            let cpu_percent = match self.previous.get(&terminal_id) {
+                Some((at, prior)) => {
+                    let elapsed = now.duration_since(*at).as_secs_f64();
+                    if elapsed > 0.0 {
+                        ((cpu_seconds - prior).max(0.0) / elapsed) * 100.0
+                    } else {
+                        0.0
+                    }
+                }
+                None => 0.0,
+            };
+            previous.insert(terminal_id.clone(), (now, cpu_seconds));
+            usage.push(TerminalResourceUsage {
+                terminal_id,
+                pid,
+                cpu_percent,
+                memory_bytes: rss_kb * 1024,
+                process_count,
+            });
+        }
+
+        // Only keep terminals that still exist so closed ones do not accumulate
+        self.previous = previous;
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_cpu_time_formats() {
+        assert_eq!(parse_cpu_time("00:01:05"), Some(65.0));
+        assert_eq!(parse_cpu_time("1-00:00:01"), Some(86_401.0));
+        assert_eq!(parse_cpu_time("0:02.50"), Some(2.5));
+        assert_eq!(parse_cpu_time("bogus"), None);
+    }
+
+    #[test]
+    fn sums_process_tree_and_derives_cpu_percent() {
+        let rows = parse_ps_output(
+            "  10     1 00:00:10  1000\n  11    10 00:00:05   500\n  12    11 00:00:01   100\n  20     1 00:01:00  9000\n",
+        );
+        assert_eq!(rows.len(), 4);
+        assert_eq!(tree_totals(&rows, 10), Some((16.0, 1600, 3)));
+        assert_eq!(tree_totals(&rows, 99), None);
+
+        let mut sampler = ResourceSampler::new();
+        let start = Instant::now();
+        let first = sampler.sample_rows(&rows, vec![("term".to_string(), 10)], start);
+        assert_eq!(first[0].cpu_percent, 0.0);
+        assert_eq!(first[0].memory_bytes, 1600 * 1024);
+
+        let busier = parse_ps_output("  10     1 00:00:12  1000\n  11    10 00:00:05   500\n");
+        let second = sampler.sample_rows(
+            &busier,
+            vec![("term".to_string(), 10)],
+            start + Duration::from_secs(4),
+        );
+        // 17s of CPU now vs 16s before, over 4s: a quarter of a core
+        assert_eq!(second[0].process_count, 2);
+        assert!((second[0].cpu_percent - 25.0).abs() < 1e-9);
+    }
+}
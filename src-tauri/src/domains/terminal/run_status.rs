@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+const RUN_TERMINAL_PREFIX: &str = "run-terminal-";
+// Printed by the run terminal wrapper once the user's command returns: `__SCHALTWERK_RUN_EXIT__=<code>\r`
+const RUN_EXIT_SENTINEL: &[u8] = b"__SCHALTWERK_RUN_EXIT__=";
+// Written as the first statement of every wrapped run command
+const RUN_START_MARKER: &[u8] = b"__schaltwerk_exit_code=0";
+// Longest exit code we expect after the sentinel (i32 with sign)
+const MAX_EXIT_CODE_LEN: usize = 11;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct RunFinishedPayload {
+    pub terminal_id: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+}
+
+#[derive(Default)]
+struct RunState {
+    started_at: Option<Instant>,
+    // Unconsumed output tail, kept so sentinels split across reads are still found
+    pending: Vec<u8>,
+}
+
+/// Tracks commands started in run terminals and reports their exit status.
+#[derive(Clone, Default)]
+pub struct RunStatusTracker {
+    runs: Arc<Mutex<HashMap<String, RunState>>>,
+}
+
+pub fn is_run_terminal_id(id: &str) -> bool {
+    id.starts_with(RUN_TERMINAL_PREFIX)
+}
+
+/// Run terminal id for a session, or for the orchestrator when `session_name` is `None`.
+pub fn run_terminal_id(session_name: Option<&str>) -> String {
+    format!(
+        "{RUN_TERMINAL_PREFIX}{}",
+        session_name.unwrap_or("orchestrator")
+    )
+}
+
+/// Session name a run terminal belongs to (`orchestrator` for the main repository).
+pub fn run_terminal_session_name(id: &str) -> Option<&str> {
+    id.strip_prefix(RUN_TERMINAL_PREFIX)
+}
+
+/// Wrap a run command so its exit code is reported through the sentinel.
+/// Mirrors the wrapper the run terminal UI writes.
+pub fn wrap_run_command(command: &str) -> String {
+    [
+        "__schaltwerk_exit_code=0",
+        command,
+        "__schaltwerk_exit_code=$?",
+        "printf '__SCHALTWERK_RUN_EXIT__=%s\\r' \"$__schaltwerk_exit_code\"",
+        "printf '\\r\\033[K'",
+        "unset __schaltwerk_exit_code",
+    ]
+    .join("; ")
+        + "\n"
+}
+
+fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Extract every completed exit code from `pending`, leaving any partial sentinel in place.
+fn drain_exit_codes(pending: &mut Vec<u8>) -> Vec<Option<i32>> {
+    let mut codes = Vec::new();
+
+    loop {
+        let Some(start) = find_subsequence(pending, RUN_EXIT_SENTINEL) else {
+            // Keep just enough bytes to complete a sentinel split across reads
+            let keep = RUN_EXIT_SENTINEL.len().saturating_sub(1);
+            if pending.len() > keep {
+                pending.drain(..pending.len() - keep);
+            }
+            break;
+        };
+
+        let value_start = start + RUN_EXIT_SENTINEL.len();
+        let value_end = pending[value_start..]
+            .iter()
+            .position(|b| !(b.is_ascii_digit() || *b == b'-'))
+            .map(|offset| value_start + offset);
+
+        let Some(value_end) = value_end else {
+            if pending.len() - value_start > MAX_EXIT_CODE_LEN {
+                pending.drain(..value_start);
+                continue;
+            }
+            // Exit code not fully received yet
+            pending.drain(..start);
+            break;
+        };
+
+        // The echoed wrapper command prints the sentinel followed by `%s`; only digits count
+        if value_end > value_start {
+            let code = std::str::from_utf8(&pending[value_start..value_end])
+                .ok()
+                .and_then(|s| s.parse::<i32>().ok());
+            codes.push(code);
+        }
+        pending.drain(..value_end);
+    }
+
+    codes
+}
+
+impl RunStatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the start of a run command when the wrapper is written to a run terminal.
+    /// Returns true when a new run started.
+    pub async fn observe_input(&self, id: &str, data: &[u8]) -> bool {
+        if !is_run_terminal_id(id) || find_subsequence(data, RUN_START_MARKER).is_none() {
+            return false;
+        }
+        let mut runs = self.runs.lock().await;
+        let state = runs.entry(id.to_string()).or_default();
+        state.started_at = Some(Instant::now());
+        state.pending.clear();
+        true
+    }
+
+    /// Scan run terminal output for the exit sentinel of the command in flight.
+    pub async fn observe_output(&self, id: &str, data: &[u8]) -> Option<RunFinishedPayload> {
+        if !is_run_terminal_id(id) {
+            return None;
+        }
+
+        let mut runs = self.runs.lock().await;
+        let state = runs.get_mut(id)?;
+        if state.started_at.is_none() {
+            return None;
+        }
+
+        state.pending.extend_from_slice(data);
+        let exit_code = drain_exit_codes(&mut state.pending).into_iter().next()?;
+
+        let duration_ms = state
+            .started_at
+            .take()
+            .map(|started| started.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+        state.pending.clear();
+
+        Some(RunFinishedPayload {
+            terminal_id: id.to_string(),
+            exit_code,
+            duration_ms,
+        })
+    }
+
+    pub async fn clear(&self, id: &str) {
+        self.runs.lock().await.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_exit_code_for_run_terminals() {
+        let tracker = RunStatusTracker::new();
+        let id = "run-terminal-demo";
+
+        tracker
+            .observe_input(
+                id,
+                b"__schaltwerk_exit_code=0; npm test; __schaltwerk_exit_code=$?\n",
+            )
+            .await;
+        // Echo of the wrapper itself must not count as completion
+        let echoed = tracker
+            .observe_output(id, b"printf '__SCHALTWERK_RUN_EXIT__=%s\\r'")
+            .await;
+        assert!(echoed.is_none());
+
+        assert!(tracker
+            .observe_output(id, b"tests failed\r\n__SCHALTWERK_RUN_")
+            .await
+            .is_none());
+        let finished = tracker
+            .observe_output(id, b"EXIT__=1\r")
+            .await
+            .expect("run finished");
+
+        assert_eq!(finished.terminal_id, id);
+        assert_eq!(finished.exit_code, Some(1));
+
+        // A second sentinel without a new run is ignored
+        assert!(tracker
+            .observe_output(id, b"__SCHALTWERK_RUN_EXIT__=0\r")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn ignores_other_terminals() {
+        let tracker = RunStatusTracker::new();
+        let id = "session-demo-bottom";
+
+        tracker
+            .observe_input(id, b"__schaltwerk_exit_code=0\n")
+            .await;
+        assert!(tracker
+            .observe_output(id, b"__SCHALTWERK_RUN_EXIT__=0\r")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn wrapped_command_starts_a_run() {
+        let tracker = RunStatusTracker::new();
+        let id = "run-terminal-orchestrator";
+        let wrapped = wrap_run_command("npm run dev");
+
+        assert!(wrapped.contains("; npm run dev; "));
+        assert!(wrapped.ends_with('\n'));
+
+        tracker.observe_input(id, wrapped.as_bytes()).await;
+        let finished = tracker
+            .observe_output(id, b"__SCHALTWERK_RUN_EXIT__=0\r")
+            .await;
+        assert_eq!(finished.map(|f| f.exit_code), Some(Some(0)));
+    }
+
+    #[test]
+    fn drain_keeps_partial_sentinel() {
+        let mut pending = b"noise __SCHALTWERK_RUN_EX".to_vec();
+        assert!(drain_exit_codes(&mut pending).is_empty());
+        assert!(pending.ends_with(b"__SCHALTWERK_RUN_EX"));
+
+        pending.extend_from_slice(b"IT__=130\r");
+        assert_eq!(drain_exit_codes(&mut pending), vec![Some(130)]);
+    }
+}
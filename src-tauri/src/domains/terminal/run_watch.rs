@@ -0,0 +1,120 @@
+use super::run_status::wrap_run_command;
+use super::TerminalManager;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+
+// Extra quiet period after a change so saves touching several files restart only once
+const RESTART_SETTLE: Duration = Duration::from_millis(300);
+// Give the interrupted command a moment to exit before re-running it
+const INTERRUPT_GRACE: Duration = Duration::from_millis(200);
+
+/// Restarts run terminal commands when their worktree changes.
+#[derive(Default)]
+pub struct RunWatchManager {
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl RunWatchManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start restarting `command` in `terminal_id` whenever `session_name` reports changes.
+    /// Replaces any existing watch for the terminal.
+    pub async fn start(
+        &self,
+        terminal_id: String,
+        session_name: String,
+        command: String,
+        changes: broadcast::Receiver<String>,
+        terminal_manager: Arc<TerminalManager>,
+    ) {
+        let mut tasks = self.tasks.lock().await;
+        if let Some(previous) = tasks.remove(&terminal_id) {
+            previous.abort();
+        }
+
+        info!("Starting run watch for {terminal_id} (session {session_name})");
+        let task = tokio::spawn(run_watch_loop(
+            terminal_id.clone(),
+            session_name,
+            command,
+            changes,
+            terminal_manager,
+        ));
+        tasks.insert(terminal_id, task);
+    }
+
+    pub async fn stop(&self, terminal_id: &str) -> bool {
+        match self.tasks.lock().await.remove(terminal_id) {
+            Some(task) => {
+                task.abort();
+                info!("Stopped run watch for {terminal_id}");
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn is_watching(&self, terminal_id: &str) -> bool {
+        self.tasks
+            .lock()
+            .await
+            .get(terminal_id)
+            .is_some_and(|task| !task.is_finished())
+    }
+}
+
+async fn run_watch_loop(
+    terminal_id: String,
+    session_name: String,
+    command: String,
+    mut changes: broadcast::Receiver<String>,
+    terminal_manager: Arc<TerminalManager>,
+) {
+    loop {
+        match changes.recv().await {
+            Ok(name) if name == session_name => {}
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("Run watch for {terminal_id} skipped {skipped} change notifications");
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+
+        // Swallow follow-up notifications that arrive while the burst settles
+        while let Ok(result) = tokio::time::timeout(RESTART_SETTLE, changes.recv()).await {
+            if let Err(broadcast::error::RecvError::Closed) = result {
+                return;
+            }
+        }
+
+        match terminal_manager.terminal_exists(&terminal_id).await {
+            Ok(true) => {}
+            _ => {
+                info!("Run terminal {terminal_id} is gone; ending run watch");
+                break;
+            }
+        }
+
+        info!("Restarting run command in {terminal_id} after changes in {session_name}");
+        if let Err(e) = terminal_manager
+            .write_terminal(terminal_id.clone(), b"\x03".to_vec())
+            .await
+        {
+            warn!("Failed to interrupt run command in {terminal_id}: {e}");
+            continue;
+        }
+        tokio::time::sleep(INTERRUPT_GRACE).await;
+        if let Err(e) = terminal_manager
+            .write_terminal(terminal_id.clone(), wrap_run_command(&command).into_bytes())
+            .await
+        {
+            warn!("Failed to restart run command in {terminal_id}: {e}");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::lifecycle::is_agent_terminal;
+use super::run_status::is_run_terminal_id;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+pub const DEFAULT_AGENT_SCROLLBACK_BYTES: u64 = 64 * 1024 * 1024;
+pub const DEFAULT_SHELL_SCROLLBACK_BYTES: u64 = 2 * 1024 * 1024;
+pub const DEFAULT_RUN_SCROLLBACK_BYTES: u64 = 2 * 1024 * 1024;
+// Below this a reattaching terminal cannot even repaint one screen
+const MIN_SCROLLBACK_BYTES: u64 = 64 * 1024;
+
+/// Bytes of raw output kept per terminal, by kind of terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScrollbackLimits {
+    pub agent_bytes: u64,
+    pub shell_bytes: u64,
+    pub run_bytes: u64,
+}
+
+impl Default for ScrollbackLimits {
+    fn default() -> Self {
+        Self {
+            agent_bytes: DEFAULT_AGENT_SCROLLBACK_BYTES,
+            shell_bytes: DEFAULT_SHELL_SCROLLBACK_BYTES,
+            run_bytes: DEFAULT_RUN_SCROLLBACK_BYTES,
+        }
+    }
+}
+
+impl ScrollbackLimits {
+    pub fn limit_for(&self, terminal_id: &str) -> usize {
+        let bytes = if is_agent_terminal(terminal_id) {
+            self.agent_bytes
+        } else if is_run_terminal_id(terminal_id) {
+            self.run_bytes
+        } else {
+            self.shell_bytes
+        };
+        usize::try_from(bytes.max(MIN_SCROLLBACK_BYTES)).unwrap_or(usize::MAX)
+    }
+}
+
+static SCROLLBACK_LIMITS: RwLock<Option<ScrollbackLimits>> = RwLock::new(None);
+
+/// Takes effect on the next output of every terminal; larger buffers are trimmed then.
+pub fn put_scrollback_limits(limits: ScrollbackLimits) {
+    if let Ok(mut guard) = SCROLLBACK_LIMITS.write() {
+        *guard = Some(limits);
+    }
+}
+
+pub fn scrollback_limit_for_terminal(terminal_id: &str) -> usize {
+    SCROLLBACK_LIMITS
+        .read()
+        .ok()
+        .and_then(|guard| *guard)
+        .unwrap_or_default()
+        .limit_for(terminal_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_follow_terminal_kind_with_a_floor() {
+        let limits = ScrollbackLimits {
+            agent_bytes: 8 * 1024 * 1024,
+            shell_bytes: 1024 * 1024,
+            run_bytes: 0,
+        };
+
+        assert_eq!(limits.limit_for("session-main-top"), 8 * 1024 * 1024);
+        assert_eq!(limits.limit_for("session-main-bottom"), 1024 * 1024);
+        assert_eq!(
+            limits.limit_for("run-terminal-main"),
+            MIN_SCROLLBACK_BYTES as usize
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use super::{
+    build_login_shell_invocation_with_shell, get_effective_shell, sh_quote_string, TerminalManager,
+};
+use crate::domains::sessions::entity::SetupStatus;
+use futures::future::BoxFuture;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone)]
+pub struct SetupRequest {
+    pub session_name: String,
+    pub terminal_id: String,
+    pub worktree_path: PathBuf,
+    pub repo_path: PathBuf,
+    pub branch_name: String,
+    pub script: String,
+    /// The run is stopped and fails once it takes longer than this
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupFinished {
+    pub session_name: String,
+    pub terminal_id: String,
+    pub status: SetupStatus,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+}
+
+/// Called once when a setup run ends, whatever the outcome.
+pub type SetupFinishedHandler = Box<dyn FnOnce(SetupFinished) -> BoxFuture<'static, ()> + Send>;
+
+struct SetupRun {
+    terminal_id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Runs session setup scripts in dedicated terminals and reports how they ended.
+#[derive(Clone, Default)]
+pub struct SetupRunner {
+    runs: Arc<Mutex<HashMap<String, SetupRun>>>,
+}
+
+/// Shell snippet that runs the setup script, records its exit code and keeps the
+/// terminal open on failure so the output can be inspected.
+pub fn setup_wrapper_command(script_path: &Path, status_path: &Path) -> String {
+    let script_q = sh_quote_string(&script_path.display().to_string());
+    let status_q = sh_quote_string(&status_path.display().to_string());
+    [
+        format!("sh {script_q}"),
+        "__schaltwerk_setup_code=$?".to_string(),
+        format!("rm -f {script_q}"),
+        format!("printf '%s' \"$__schaltwerk_setup_code\" > {status_q}"),
+        "if [ \"$__schaltwerk_setup_code\" -ne 0 ]; then printf '\\r\\nSetup script failed with exit code %s. Press Enter to close.\\r\\n' \"$__schaltwerk_setup_code\"; read -r _; fi".to_string(),
+        "exit \"$__schaltwerk_setup_code\"".to_string(),
+    ]
+    .join("; ")
+}
+
+/// Exit code recorded by the wrapper, or `None` while the script is still running. The
+/// redirect creates the file before the code is written, so an empty file counts as running.
+pub fn read_exit_code(status_path: &Path) -> Option<Option<i32>> {
+    let contents = std::fs::read_to_string(status_path).ok()?;
+    let contents = contents.trim();
+    if contents.is_empty() {
+        return None;
+    }
+    Some(contents.parse::<i32>().ok())
+}
+
+fn temp_file(session_name: &str, extension: &str) -> PathBuf {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let safe_name = crate::shared::terminal_id::sanitize_session_name(session_name);
+    std::env::temp_dir().join(format!("schalt_setup_{safe_name}_{ts}.{extension}"))
+}
+
+impl SetupRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the setup script for a session. Fails when a setup run is already in flight.
+    pub async fn start(
+        &self,
+        request: SetupRequest,
+        terminal_manager: Arc<TerminalManager>,
+        on_finish: SetupFinishedHandler,
+    ) -> Result<(), String> {
+        let mut runs = self.runs.lock().await;
+        if runs.contains_key(&request.session_name) {
+            return Err(format!(
+                "Setup is already running for session '{}'",
+                request.session_name
+            ));
+        }
+
+        let script_path = temp_file(&request.session_name, "sh");
+        let status_path = temp_file(&request.session_name, "status");
+        std::fs::write(&script_path, &request.script)
+            .map_err(|e| format!("Failed to write setup script: {e}"))?;
+
+        if terminal_manager
+            .terminal_exists(&request.terminal_id)
+            .await?
+        {
+            terminal_manager
+                .close_terminal(request.terminal_id.clone())
+                .await?;
+        }
+
+        let (user_shell, default_args) = get_effective_shell();
+        let invocation = build_login_shell_invocation_with_shell(
+            &user_shell,
+            &default_args,
+            &setup_wrapper_command(&script_path, &status_path),
+        );
+        let env = vec![
+            (
+                "WORKTREE_PATH".to_string(),
+                request.worktree_path.display().to_string(),
+            ),
+            (
+                "REPO_PATH".to_string(),
+                request.repo_path.display().to_string(),
+            ),
+            ("SESSION_NAME".to_string(), request.session_name.clone()),
+            ("BRANCH_NAME".to_string(), request.branch_name.clone()),
+        ];
+
+        if let Err(e) = terminal_manager
+            .create_terminal_with_app(
+                request.terminal_id.clone(),
+                request.worktree_path.display().to_string(),
+                invocation.program,
+                invocation.args,
+                env,
+            )
+            .await
+        {
+            let _ = std::fs::remove_file(&script_path);
+            return Err(e);
+        }
+
+        info!(
+            "Started setup script for session {} in {}",
+            request.session_name, request.terminal_id
+        );
+        let cancelled = Arc::new(AtomicBool::new(false));
+        runs.insert(
+            request.session_name.clone(),
+            SetupRun {
+                terminal_id: request.terminal_id.clone(),
+                cancelled: cancelled.clone(),
+            },
+        );
+        drop(runs);
+
+        tokio::spawn(monitor_setup(
+            self.runs.clone(),
+            request,
+            status_path,
+            script_path,
+            cancelled,
+            terminal_manager,
+            on_finish,
+        ));
+        Ok(())
+    }
+
+    /// Stop the setup run of a session. Returns false when none is in flight.
+    pub async fn cancel(
+        &self,
+        session_name: &str,
+        terminal_manager: &TerminalManager,
+    ) -> Result<bool, String> {
+        let terminal_id = {
+            let runs = self.runs.lock().await;
+            let Some(run) = runs.get(session_name) else {
+                return Ok(false);
+            };
+            run.cancelled.store(true, Ordering::SeqCst);
+            run.terminal_id.clone()
+        };
+
+        info!("Cancelling setup script for session {session_name}");
+        if terminal_manager.terminal_exists(&terminal_id).await? {
+            terminal_manager.close_terminal(terminal_id).await?;
+        }
+        Ok(true)
+    }
+
+    pub async fn is_running(&self, session_name: &str) -> bool {
+        self.runs.lock().await.contains_key(session_name)
+    }
+}
+
+async fn monitor_setup(
+    runs: Arc<Mutex<HashMap<String, SetupRun>>>,
+    request: SetupRequest,
+    status_path: PathBuf,
+    script_path: PathBuf,
+    cancelled: Arc<AtomicBool>,
+    terminal_manager: Arc<TerminalManager>,
+    on_finish: SetupFinishedHandler,
+) {
+    let started = Instant::now();
+    let mut timed_out = false;
+    let (status, exit_code) = loop {
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+
+        if cancelled.load(Ordering::SeqCst) {
+            break (SetupStatus::Cancelled, None);
+        }
+        if request
+            .timeout
+            .is_some_and(|timeout| started.elapsed() >= timeout)
+            && read_exit_code(&status_path).is_none()
+        {
+            warn!(
+                "Setup script for session {} timed out after {}s",
+                request.session_name,
+                started.elapsed().as_secs()
+            );
+            if let Err(e) = terminal_manager
+                .close_terminal(request.terminal_id.clone())
+                .await
+            {
+                warn!("Failed to close timed out setup terminal: {e}");
+            }
+            timed_out = true;
+            break (SetupStatus::Failed, None);
+        }
+        if let Some(code) = read_exit_code(&status_path) {
+            let status = if code == Some(0) {
+                SetupStatus::Succeeded
+            } else {
+                SetupStatus::Failed
+            };
+            break (status, code);
+        }
+        match terminal_manager.terminal_exists(&request.terminal_id).await {
+            Ok(true) => {}
+            _ => {
+                // The wrapper writes the status before exiting; check once more before giving up
+                match read_exit_code(&status_path) {
+                    Some(Some(0)) => break (SetupStatus::Succeeded, Some(0)),
+                    Some(code) => break (SetupStatus::Failed, code),
+                    None => {
+                        warn!(
+                            "Setup terminal {} closed before the script finished",
+                            request.terminal_id
+                        );
+                        break (SetupStatus::Cancelled, None);
+                    }
+                }
+            }
+        }
+    };
+
+    let _ = std::fs::remove_file(&status_path);
+    let _ = std::fs::remove_file(&script_path);
+    runs.lock().await.remove(&request.session_name);
+
+    info!(
+        "Setup script for session {} finished: {}",
+        request.session_name,
+        status.as_str()
+    );
+    on_finish(SetupFinished {
+        session_name: request.session_name,
+        terminal_id: request.terminal_id,
+        status,
+        exit_code,
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out,
+    })
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapper_records_exit_code_and_cleans_up() {
+        let command = setup_wrapper_command(
+            Path::new("/tmp/setup script.sh"),
+            Path::new("/tmp/setup.status"),
+        );
+        assert!(command.starts_with("sh '/tmp/setup script.sh'; "));
+        assert!(command.contains("rm -f '/tmp/setup script.sh'"));
+        assert!(command.contains("> '/tmp/setup.status'"));
+        assert!(command.ends_with("exit \"$__schaltwerk_setup_code\""));
+    }
+
+    #[test]
+    fn wrapper_reports_script_exit_code() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script = tmp.path().join("setup.sh");
+        let status = tmp.path().join("setup.status");
+        std::fs::write(&script, "exit 0\n").unwrap();
+        assert_eq!(read_exit_code(&status), None);
+        std::fs::write(&status, "").unwrap();
+        assert_eq!(read_exit_code(&status), None);
+
+        let result = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(setup_wrapper_command(&script, &status))
+            .status()
+            .unwrap();
+
+        assert!(result.success());
+        assert_eq!(read_exit_code(&status), Some(Some(0)));
+        assert!(!script.exists());
+    }
+}
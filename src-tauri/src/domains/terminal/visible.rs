@@ -66,7 +66,17 @@ impl VisibleScreen {
         self.parser.screen_mut().set_size(rows, cols);
     }
 
+    /// Text currently visible on screen, one line per row.
+    pub fn contents(&self) -> String {
+        self.parser.screen().contents()
+    }
+
     pub fn cursor_position(&self) -> (u16, u16) {
         self.parser.screen().cursor_position()
     }
+
+    /// Whether the program in the terminal has enabled bracketed paste mode (DECSET 2004).
+    pub fn bracketed_paste(&self) -> bool {
+        self.parser.screen().bracketed_paste()
+    }
 }
@@ -0,0 +1,71 @@
+use crate::schaltwerk_core::db_project_config::ProjectDependencyCacheConfig;
+use std::path::{Path, PathBuf};
+
+/// Root of the caches shared by all worktrees of a repository.
+pub fn dependency_cache_root(repo_path: &Path) -> PathBuf {
+    repo_path.join(".schaltwerk").join("dependency-cache")
+}
+
+/// Environment variables that point package managers at the shared caches.
+///
+/// The caches live inside the repository so they sit on the same filesystem as the
+/// worktrees, which lets pnpm hard-link from its store instead of copying.
+/// Builds in different worktrees that share a cargo target dir take turns on its lock.
+pub fn dependency_cache_env(
+    repo_path: &Path,
+    config: &ProjectDependencyCacheConfig,
+) -> Vec<(String, String)> {
+    let root = dependency_cache_root(repo_path);
+    let path = |name: &str| root.join(name).to_string_lossy().to_string();
+    let mut env = Vec::new();
+
+    if config.pnpm_store {
+        env.push(("npm_config_store_dir".to_string(), path("pnpm-store")));
+    }
+    if config.cargo_target_dir {
+        env.push(("CARGO_TARGET_DIR".to_string(), path("cargo-target")));
+    }
+    if config.pip_cache {
+        env.push(("PIP_CACHE_DIR".to_string(), path("pip")));
+        env.push(("UV_CACHE_DIR".to_string(), path("uv")));
+    }
+
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_enabled_caches_are_exported() {
+        let repo = Path::new("/repo");
+        assert!(dependency_cache_env(repo, &ProjectDependencyCacheConfig::default()).is_empty());
+
+        let env = dependency_cache_env(
+            repo,
+            &ProjectDependencyCacheConfig {
+                pnpm_store: false,
+                cargo_target_dir: true,
+                pip_cache: true,
+            },
+        );
+        assert_eq!(
+            env,
+            vec![
+                (
+                    "CARGO_TARGET_DIR".to_string(),
+                    "/repo/.schaltwerk/dependency-cache/cargo-target".to_string()
+                ),
+                (
+                    "PIP_CACHE_DIR".to_string(),
+                    "/repo/.schaltwerk/dependency-cache/pip".to_string()
+                ),
+                (
+                    "UV_CACHE_DIR".to_string(),
+                    "/repo/.schaltwerk/dependency-cache/uv".to_string()
+                ),
+            ]
+        );
+    }
+}
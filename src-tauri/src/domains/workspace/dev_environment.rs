@@ -0,0 +1,189 @@
+use crate::domains::terminal::sh_quote_string;
+use crate::schaltwerk_core::db_project_config::DevEnvironmentKind;
+use serde::Serialize;
+use std::path::Path;
+
+const DEVCONTAINER_CONFIGS: &[&str] = &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+const NIX_FLAKE: &str = "flake.nix";
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DetectedDevEnvironment {
+    pub kind: DevEnvironmentKind,
+    /// Config file relative to the repository root
+    pub config_path: String,
+}
+
+/// Development environments the project declares, in the order they should be offered.
+pub fn detect_dev_environments(repo_path: &Path) -> Vec<DetectedDevEnvironment> {
+    let mut detected = Vec::new();
+
+    if let Some(config) = DEVCONTAINER_CONFIGS
+        .iter()
+        .find(|config| repo_path.join(config).is_file())
+    {
+        detected.push(DetectedDevEnvironment {
+            kind: DevEnvironmentKind::Devcontainer,
+            config_path: config.to_string(),
+        });
+    }
+    if repo_path.join(NIX_FLAKE).is_file() {
+        detected.push(DetectedDevEnvironment {
+            kind: DevEnvironmentKind::Nix,
+            config_path: NIX_FLAKE.to_string(),
+        });
+    }
+
+    detected
+}
+
+/// Shell snippet that prepares the environment; runs from the worktree root.
+pub fn environment_setup_script(kind: DevEnvironmentKind) -> &'static str {
+    match kind {
+        DevEnvironmentKind::Devcontainer => "devcontainer up --workspace-folder .",
+        // Builds the dev shell once so later terminals enter it without waiting
+        DevEnvironmentKind::Nix => "nix develop --command true",
+    }
+}
+
+/// Session setup step: the environment setup (if any) followed by the project's setup script,
+/// which then runs inside the environment.
+pub fn compose_setup_script(
+    environment: Option<DevEnvironmentKind>,
+    project_script: Option<String>,
+) -> Option<String> {
+    let project_script = project_script.filter(|script| !script.trim().is_empty());
+    match (environment, project_script) {
+        (None, script) => script,
+        (Some(kind), None) => Some(format!("set -e\n{}\n", environment_setup_script(kind))),
+        (Some(kind), Some(script)) => {
+            // The project script may carry its own shebang; pipe it into a shell in the environment
+            let quoted = sh_quote_string(&script);
+            let (launcher, args) =
+                wrap_in_environment(kind, Path::new("."), "sh".to_string(), Vec::new());
+            let shell = std::iter::once(&launcher)
+                .chain(&args)
+                .map(|word| sh_quote_string(word))
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(format!(
+                "set -e\n{}\nprintf '%s' {quoted} | {shell}\n",
+                environment_setup_script(kind)
+            ))
+        }
+    }
+}
+
+/// Run `program` inside the development environment of `worktree`.
+pub fn wrap_in_environment(
+    kind: DevEnvironmentKind,
+    worktree: &Path,
+    program: String,
+    args: Vec<String>,
+) -> (String, Vec<String>) {
+    let worktree = worktree.to_string_lossy().to_string();
+    let mut wrapped = match kind {
+        DevEnvironmentKind::Devcontainer => vec![
+            "exec".to_string(),
+            "--workspace-folder".to_string(),
+            worktree,
+        ],
+        DevEnvironmentKind::Nix => vec!["develop".to_string(), worktree, "--command".to_string()],
+    };
+    wrapped.push(program);
+    wrapped.extend(args);
+
+    let launcher = match kind {
+        DevEnvironmentKind::Devcontainer => "devcontainer",
+        DevEnvironmentKind::Nix => "nix",
+    };
+    (launcher.to_string(), wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_devcontainer_and_flake() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(detect_dev_environments(tmp.path()).is_empty());
+
+        std::fs::create_dir_all(tmp.path().join(".devcontainer")).unwrap();
+        std::fs::write(tmp.path().join(".devcontainer/devcontainer.json"), "{}").unwrap();
+        std::fs::write(tmp.path().join("flake.nix"), "{}").unwrap();
+
+        let detected = detect_dev_environments(tmp.path());
+        assert_eq!(
+            detected
+                .iter()
+                .map(|env| (env.kind, env.config_path.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    DevEnvironmentKind::Devcontainer,
+                    ".devcontainer/devcontainer.json"
+                ),
+                (DevEnvironmentKind::Nix, "flake.nix"),
+            ]
+        );
+    }
+
+    #[test]
+    fn wraps_commands_for_each_environment() {
+        let worktree = Path::new("/wt/demo");
+        assert_eq!(
+            wrap_in_environment(
+                DevEnvironmentKind::Nix,
+                worktree,
+                "claude".to_string(),
+                vec!["--continue".to_string()]
+            ),
+            (
+                "nix".to_string(),
+                vec!["develop", "/wt/demo", "--command", "claude", "--continue"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+        let (launcher, args) = wrap_in_environment(
+            DevEnvironmentKind::Devcontainer,
+            worktree,
+            "/bin/bash".to_string(),
+            vec!["-l".to_string()],
+        );
+        assert_eq!(launcher, "devcontainer");
+        assert_eq!(
+            args,
+            vec!["exec", "--workspace-folder", "/wt/demo", "/bin/bash", "-l"]
+        );
+    }
+
+    #[test]
+    fn setup_script_runs_environment_before_project_script() {
+        assert_eq!(compose_setup_script(None, Some("  ".to_string())), None);
+        assert_eq!(
+            compose_setup_script(None, Some("echo hi".to_string())),
+            Some("echo hi".to_string())
+        );
+
+        let composed =
+            compose_setup_script(Some(DevEnvironmentKind::Nix), Some("echo hi".to_string()))
+                .unwrap();
+        let env_pos = composed.find("nix develop --command true").unwrap();
+        let script_pos = composed.find("echo hi").unwrap();
+        assert!(env_pos < script_pos);
+    }
+
+    #[test]
+    fn project_setup_script_runs_inside_the_environment() {
+        let composed = compose_setup_script(
+            Some(DevEnvironmentKind::Devcontainer),
+            Some("npm ci".to_string()),
+        )
+        .unwrap();
+        assert!(composed.ends_with(
+            "printf '%s' 'npm ci' | 'devcontainer' 'exec' '--workspace-folder' '.' 'sh'\n"
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod dependency_cache;
+pub mod dev_environment;
 pub mod diff_engine;
 pub mod file_index;
 pub mod file_utils;
@@ -14,7 +14,7 @@ use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
 
 use crate::domains::git::service as git;
 use crate::domains::sessions::entity::ChangedFile;
@@ -59,6 +59,7 @@ impl FileWatcher {
         worktree_path: PathBuf,
         base_branch: String,
         app_handle: AppHandle,
+        change_sender: broadcast::Sender<String>,
     ) -> Result<Self, String> {
         let (tx, mut rx) = mpsc::channel(100);
 
@@ -87,6 +88,10 @@ impl FileWatcher {
                             session_name_clone
                         );
 
+                        if Self::has_source_changes(&events) && change_sender.receiver_count() > 0 {
+                            let _ = change_sender.send(session_name_clone.clone());
+                        }
+
                         if let Err(e) = Self::handle_file_changes(
                             &session_name_clone,
                             &worktree_path_clone,
@@ -310,6 +315,7 @@ impl FileWatcher {
                     merge_conflicting_paths: merge_snapshot.merge_conflicting_paths,
                     merge_is_up_to_date: merge_snapshot.merge_is_up_to_date,
                     worktree_size_bytes,
+                    base_freshness: None,
                 };
                 let _ = emit_event(app_handle, SchaltEvent::SessionGitStats, &payload);
                 debug!(
@@ -329,6 +335,17 @@ impl FileWatcher {
         Ok(())
     }
 
+    /// Whether any event touches a worktree file outside `.git` (used to restart watched runs)
+    fn has_source_changes(events: &[notify_debouncer_mini::DebouncedEvent]) -> bool {
+        events.iter().any(|event| {
+            !Self::should_ignore_path(&event.path)
+                && !event
+                    .path
+                    .components()
+                    .any(|c| c.as_os_str() == std::ffi::OsStr::new(".git"))
+        })
+    }
+
     fn should_ignore_path(path: &Path) -> bool {
         if let Some(path_str) = path.to_str() {
             // Treat critical .git files as signalers of commits/branch moves
@@ -501,16 +518,26 @@ const ORCHESTRATOR_SESSION_NAME: &str = "orchestrator";
 pub struct FileWatcherManager {
     watchers: Arc<Mutex<HashMap<String, FileWatcher>>>,
     app_handle: AppHandle,
+    // Session names whose worktree saw source changes, after debouncing
+    change_sender: broadcast::Sender<String>,
 }
 
 impl FileWatcherManager {
     pub fn new(app_handle: AppHandle) -> Self {
+        let (change_sender, _) = broadcast::channel(64);
         Self {
             watchers: Arc::new(Mutex::new(HashMap::new())),
             app_handle,
+            change_sender,
         }
     }
 
+    /// Subscribe to debounced source changes; yields the session name
+    /// (`orchestrator` for the main repository).
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<String> {
+        self.change_sender.subscribe()
+    }
+
     pub async fn start_watching_session(
         &self,
         session_name: String,
@@ -529,6 +556,7 @@ impl FileWatcherManager {
             worktree_path,
             base_branch,
             self.app_handle.clone(),
+            self.change_sender.clone(),
         )?;
 
         watchers.insert(session_name.clone(), watcher);
@@ -586,6 +614,7 @@ impl FileWatcherManager {
             repo_path,
             base_branch,
             self.app_handle.clone(),
+            self.change_sender.clone(),
         )?;
 
         watchers.insert(ORCHESTRATOR_WATCHER_KEY.to_string(), watcher);
@@ -804,6 +833,22 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn test_has_source_changes_ignores_git_internals() {
+        use notify_debouncer_mini::{DebouncedEvent, DebouncedEventKind};
+
+        let event = |path: &str| DebouncedEvent::new(PathBuf::from(path), DebouncedEventKind::Any);
+
+        assert!(!FileWatcher::has_source_changes(&[
+            event("/repo/.git/index"),
+            event("/repo/node_modules/pkg/index.js"),
+        ]));
+        assert!(FileWatcher::has_source_changes(&[
+            event("/repo/.git/index"),
+            event("/repo/src/main.rs"),
+        ]));
+    }
+
     #[test]
     fn test_file_change_event_serialization() {
         let event = FileChangeEvent {
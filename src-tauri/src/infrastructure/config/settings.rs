@@ -1,4 +1,15 @@
-use crate::domains::settings::{Settings, SettingsRepository, SettingsService};
+use crate::domains::sessions::activity::put_auto_nudge_config;
+use crate::domains::sessions::lifecycle::put_agent_restart_config;
+use crate::domains::settings::{Settings, SettingsChange, SettingsRepository, SettingsService};
+use crate::domains::terminal::coalescing::{put_output_throttle_config, OutputThrottleConfig};
+use crate::domains::terminal::idle_detection::{put_idle_detection_config, IdleDetectionConfig};
+use crate::domains::terminal::persistence::put_terminal_persistence_mode;
+use crate::domains::terminal::process_tree::put_kill_grace_period_ms;
+use crate::domains::terminal::put_terminal_shell_override;
+use crate::domains::terminal::resource_limits::put_agent_resource_limits;
+use crate::domains::terminal::scrollback::put_scrollback_limits;
+use crate::infrastructure::events::{emit_event, SchaltEvent};
+use crate::infrastructure::logging::set_json_logs;
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
@@ -64,8 +75,41 @@ impl SettingsRepository for FileSettingsRepository {
     }
 }
 
+/// Hand a persisted change to the subsystems that read it from memory, so it takes
+/// effect without restarting terminals. Agent env vars, CLI args and shortcuts are read
+/// from the settings whenever they are used.
+pub fn apply_settings_change(change: &SettingsChange) {
+    match change {
+        SettingsChange::Terminal { terminal, .. } => {
+            let shell = terminal.shell.clone().unwrap_or_else(|| {
+                std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
+            });
+            put_terminal_shell_override(shell, terminal.shell_args.clone());
+            put_output_throttle_config(OutputThrottleConfig {
+                enabled: terminal.output_throttle_enabled,
+                max_bytes_per_second: terminal.output_max_bytes_per_second,
+            });
+            put_terminal_persistence_mode(terminal.persistence);
+            put_scrollback_limits(terminal.scrollback);
+            put_kill_grace_period_ms(terminal.kill_grace_period_ms);
+            put_idle_detection_config(IdleDetectionConfig {
+                threshold_ms: terminal.idle_threshold_ms,
+                prompt_patterns: terminal.idle_prompt_patterns.clone(),
+            });
+        }
+        SettingsChange::SessionPreferences { preferences } => {
+            put_auto_nudge_config(preferences.auto_nudge.clone());
+            put_agent_restart_config(preferences.agent_restart.clone());
+            put_agent_resource_limits(preferences.agent_resource_limits);
+        }
+        SettingsChange::JsonLogs { enabled } => set_json_logs(*enabled),
+        _ => {}
+    }
+}
+
 pub struct SettingsManager {
     service: SettingsService,
+    app_handle: AppHandle,
 }
 
 impl SettingsManager {
@@ -73,7 +117,17 @@ impl SettingsManager {
         let repository = Box::new(FileSettingsRepository::new(app_handle)?);
         let service = SettingsService::new(repository);
 
-        Ok(Self { service })
+        Ok(Self {
+            service,
+            app_handle: app_handle.clone(),
+        })
+    }
+
+    fn publish(&self, change: SettingsChange) {
+        apply_settings_change(&change);
+        if let Err(e) = emit_event(&self.app_handle, SchaltEvent::SettingsChanged, &change) {
+            log::warn!("Failed to emit settings change: {e}");
+        }
     }
 
     pub fn get_agent_env_vars(
@@ -90,7 +144,12 @@ impl SettingsManager {
     ) -> Result<(), String> {
         self.service
             .set_agent_env_vars(agent_type, env_vars)
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::AgentEnvVars {
+            agent_type: agent_type.to_string(),
+            env_vars: self.service.get_agent_env_vars(agent_type),
+        });
+        Ok(())
     }
 
     pub fn get_terminal_ui_preferences(&self) -> crate::domains::settings::TerminalUIPreferences {
@@ -116,7 +175,22 @@ impl SettingsManager {
     pub fn set_agent_cli_args(&mut self, agent_type: &str, cli_args: String) -> Result<(), String> {
         self.service
             .set_agent_cli_args(agent_type, cli_args)
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::AgentCliArgs {
+            agent_type: agent_type.to_string(),
+            cli_args: self.service.get_agent_cli_args(agent_type),
+        });
+        Ok(())
+    }
+
+    pub fn resolve_effective_settings(
+        &self,
+        agent_type: &str,
+        project: &crate::domains::settings::SettingsLayerValues,
+        session: Option<&crate::domains::settings::SettingsLayerValues>,
+    ) -> crate::domains::settings::EffectiveSettings {
+        self.service
+            .resolve_effective_settings(agent_type, project, session)
     }
 
     pub fn get_agent_initial_command(&self, agent_type: &str) -> String {
@@ -141,9 +215,51 @@ impl SettingsManager {
         &mut self,
         terminal: crate::domains::settings::TerminalSettings,
     ) -> Result<(), String> {
+        let previous = self.service.get_terminal_settings();
+        let shell_changed =
+            previous.shell != terminal.shell || previous.shell_args != terminal.shell_args;
         self.service
             .set_terminal_settings(terminal)
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::Terminal {
+            terminal: self.service.get_terminal_settings(),
+            shell_changed,
+        });
+        Ok(())
+    }
+
+    pub fn get_terminal_themes(&self) -> Vec<crate::domains::settings::TerminalTheme> {
+        self.service.get_terminal_themes()
+    }
+
+    pub fn set_terminal_theme(
+        &mut self,
+        theme: crate::domains::settings::TerminalTheme,
+    ) -> Result<(), String> {
+        self.service
+            .set_terminal_theme(theme)
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::TerminalThemes {
+            themes: self.service.get_terminal_themes(),
+        });
+        Ok(())
+    }
+
+    pub fn delete_terminal_theme(&mut self, name: &str) -> Result<(), String> {
+        let selected = self.service.get_terminal_settings().theme;
+        self.service
+            .delete_terminal_theme(name)
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::TerminalThemes {
+            themes: self.service.get_terminal_themes(),
+        });
+        if selected.as_deref() == Some(name) {
+            self.publish(SettingsChange::Terminal {
+                terminal: self.service.get_terminal_settings(),
+                shell_changed: false,
+            });
+        }
+        Ok(())
     }
 
     pub fn get_diff_view_preferences(&self) -> crate::domains::settings::DiffViewPreferences {
@@ -156,7 +272,11 @@ impl SettingsManager {
     ) -> Result<(), String> {
         self.service
             .set_diff_view_preferences(preferences)
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::DiffViewPreferences {
+            preferences: self.service.get_diff_view_preferences(),
+        });
+        Ok(())
     }
 
     pub fn get_session_preferences(&self) -> crate::domains::settings::SessionPreferences {
@@ -169,7 +289,11 @@ impl SettingsManager {
     ) -> Result<(), String> {
         self.service
             .set_session_preferences(preferences)
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::SessionPreferences {
+            preferences: self.service.get_session_preferences(),
+        });
+        Ok(())
     }
 
     pub fn get_keyboard_shortcuts(&self) -> std::collections::HashMap<String, Vec<String>> {
@@ -182,7 +306,37 @@ impl SettingsManager {
     ) -> Result<(), String> {
         self.service
             .set_keyboard_shortcuts(shortcuts)
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::KeyboardShortcuts {
+            shortcuts: self.service.get_keyboard_shortcuts(),
+        });
+        Ok(())
+    }
+
+    pub fn get_env_sets(&self) -> Vec<crate::domains::settings::EnvVarSet> {
+        self.service.get_env_sets()
+    }
+
+    pub fn set_env_set(&mut self, set: crate::domains::settings::EnvVarSet) -> Result<(), String> {
+        self.service.set_env_set(set).map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::EnvSets {
+            env_sets: self.service.get_env_sets(),
+        });
+        Ok(())
+    }
+
+    pub fn delete_env_set(&mut self, name: &str) -> Result<(), String> {
+        self.service
+            .delete_env_set(name)
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::EnvSets {
+            env_sets: self.service.get_env_sets(),
+        });
+        Ok(())
+    }
+
+    pub fn resolve_env_sets(&self, names: &[String]) -> std::collections::HashMap<String, String> {
+        self.service.resolve_env_sets(names)
     }
 
     pub fn get_tutorial_completed(&self) -> bool {
@@ -205,6 +359,18 @@ impl SettingsManager {
             .map_err(|e| e.to_string())
     }
 
+    pub fn get_json_logs(&self) -> bool {
+        self.service.get_json_logs()
+    }
+
+    pub fn set_json_logs(&mut self, enabled: bool) -> Result<(), String> {
+        self.service
+            .set_json_logs(enabled)
+            .map_err(|e| e.to_string())?;
+        self.publish(SettingsChange::JsonLogs { enabled });
+        Ok(())
+    }
+
     pub fn get_auto_update_enabled(&self) -> bool {
         self.service.get_auto_update_enabled()
     }
@@ -241,6 +407,12 @@ impl SettingsManager {
             .map_err(|e| e.to_string())
     }
 
+    pub fn get_custom_agents(
+        &self,
+    ) -> std::collections::HashMap<String, crate::domains::agents::custom::CustomAgentConfig> {
+        self.service.get_custom_agents()
+    }
+
     pub fn get_amp_mcp_servers(&self) -> std::collections::HashMap<String, crate::domains::settings::McpServerConfig> {
         self.service.get_amp_mcp_servers()
     }
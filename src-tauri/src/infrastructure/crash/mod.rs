@@ -0,0 +1,227 @@
+//! Crash reports: a panic anywhere in the backend writes a JSON report (backtrace,
+//! the end of the log, open project, session count) to the app data dir before the
+//! process goes down. Nothing leaves the machine unless the user exports a report.
+
+use crate::infrastructure::logging::recent_log_lines;
+use crate::infrastructure::secrets::mask_secrets;
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Log lines kept in a report
+const LOG_TAIL_LINES: usize = 200;
+
+/// Reports kept on disk; older ones are deleted when a new one is written
+const MAX_CRASH_REPORTS: usize = 20;
+
+static OPEN_PROJECT: Mutex<Option<PathBuf>> = Mutex::new(None);
+static SESSION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub created_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub open_project: Option<String>,
+    pub session_count: usize,
+    pub log_tail: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportSummary {
+    /// File name of the report, used to export it
+    pub id: String,
+    pub created_at: String,
+    pub message: String,
+}
+
+pub fn crash_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("schaltwerk")
+        .join("crashes")
+}
+
+/// Remember the project a crash report should name.
+pub fn note_open_project(path: &Path) {
+    if let Ok(mut project) = OPEN_PROJECT.lock() {
+        *project = Some(path.to_path_buf());
+    }
+}
+
+/// Remember how many sessions the open project had when last listed.
+pub fn note_session_count(count: usize) {
+    SESSION_COUNT.store(count, Ordering::Relaxed);
+}
+
+fn panic_message(info: &std::panic::PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+fn build_report(message: String, location: Option<String>) -> CrashReport {
+    // try_lock: the panic may have happened while this thread held the lock
+    let open_project = OPEN_PROJECT
+        .try_lock()
+        .ok()
+        .and_then(|project| project.as_ref().map(|p| p.display().to_string()));
+    CrashReport {
+        created_at: Local::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("unnamed")
+            .to_string(),
+        message: mask_secrets(&message).into_owned(),
+        location,
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        open_project,
+        session_count: SESSION_COUNT.load(Ordering::Relaxed),
+        log_tail: recent_log_lines(LOG_TAIL_LINES)
+            .iter()
+            .map(|line| mask_secrets(line).into_owned())
+            .collect(),
+    }
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let mut path = dir.join(format!(
+        "crash-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!(
+            "crash-{}-{counter}.json",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        counter += 1;
+    }
+    fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    prune_reports(dir, MAX_CRASH_REPORTS);
+    Ok(path)
+}
+
+fn report_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension().and_then(|ext| ext.to_str()) == Some("json")
+                        && path
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| name.starts_with("crash-"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Names carry the time, so name order is age order
+    files.sort();
+    files
+}
+
+fn prune_reports(dir: &Path, keep: usize) {
+    let files = report_files(dir);
+    let excess = files.len().saturating_sub(keep);
+    for path in files.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Write a crash report for every panic, then run the previous hook (which prints it).
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let report = build_report(panic_message(info), location);
+        match write_report(&crash_dir(), &report) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {e}"),
+        }
+        previous(info);
+    }));
+}
+
+/// Reports on disk, newest first.
+pub fn list_crash_reports_in(dir: &Path) -> Vec<CrashReportSummary> {
+    report_files(dir)
+        .into_iter()
+        .rev()
+        .filter_map(|path| {
+            let report: CrashReport = serde_json::from_slice(&fs::read(&path).ok()?).ok()?;
+            Some(CrashReportSummary {
+                id: path.file_name()?.to_string_lossy().to_string(),
+                created_at: report.created_at,
+                message: report.message,
+            })
+        })
+        .collect()
+}
+
+/// Copy report `id` to `destination`, for attaching to a bug report.
+pub fn export_crash_report_from(dir: &Path, id: &str, destination: &Path) -> Result<()> {
+    let source = report_files(dir)
+        .into_iter()
+        .find(|path| path.file_name().and_then(|name| name.to_str()) == Some(id))
+        .ok_or_else(|| anyhow!("Crash report '{id}' not found"))?;
+    fs::copy(&source, destination).map_err(|e| {
+        anyhow!(
+            "Failed to export crash report to {}: {e}",
+            destination.display()
+        )
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reports_are_written_listed_exported_and_pruned() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("crashes");
+        note_session_count(4);
+
+        let report = build_report("index out of bounds".to_string(), None);
+        assert_eq!(report.session_count, 4);
+        assert!(!report.backtrace.is_empty());
+        let path = write_report(&dir, &report).unwrap();
+
+        let listed = list_crash_reports_in(&dir);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].message, "index out of bounds");
+        assert_eq!(listed[0].id, path.file_name().unwrap().to_string_lossy());
+
+        let exported = tmp.path().join("report.json");
+        export_crash_report_from(&dir, &listed[0].id, &exported).unwrap();
+        assert!(exported.exists());
+        assert!(export_crash_report_from(&dir, "../secrets.json", &exported).is_err());
+
+        for _ in 0..3 {
+            write_report(&dir, &report).unwrap();
+        }
+        prune_reports(&dir, 2);
+        assert_eq!(report_files(&dir).len(), 2);
+    }
+}
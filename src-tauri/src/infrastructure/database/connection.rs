@@ -3,7 +3,7 @@ use r2d2::{ManageConnection, Pool, PooledConnection};
 use rusqlite::Connection;
 #[cfg(test)]
 use rusqlite::OpenFlags;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -11,15 +11,25 @@ use std::time::Duration;
 use super::db_schema;
 
 const DEFAULT_POOL_SIZE: u32 = 4;
+const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5_000;
+
+/// The WAL is truncated to this size after a checkpoint instead of keeping the size of
+/// the largest write burst
+const JOURNAL_SIZE_LIMIT_BYTES: i64 = 64 * 1024 * 1024;
 
 #[derive(Clone)]
 pub struct Database {
     pool: Arc<Pool<SqliteConnectionManager>>,
+    /// The database file; `None` for in-memory test databases
+    path: Option<PathBuf>,
 }
 
 #[derive(Clone)]
 pub(crate) struct SqliteConnectionManager {
     config: SqliteConfig,
+    /// How long a connection waits for another one's write lock before failing with
+    /// "database is locked"
+    busy_timeout: Duration,
 }
 
 #[derive(Clone)]
@@ -30,9 +40,10 @@ pub(crate) enum SqliteConfig {
 }
 
 impl SqliteConnectionManager {
-    fn file(path: PathBuf) -> Self {
+    fn file(path: PathBuf, busy_timeout: Duration) -> Self {
         Self {
             config: SqliteConfig::File(path),
+            busy_timeout,
         }
     }
 
@@ -45,6 +56,7 @@ impl SqliteConnectionManager {
         let uri = format!("file:schaltwerk_mem_{id}?mode=memory&cache=shared");
         Self {
             config: SqliteConfig::Memory(uri),
+            busy_timeout: Duration::from_millis(DEFAULT_BUSY_TIMEOUT_MS),
         }
     }
 
@@ -53,12 +65,21 @@ impl SqliteConnectionManager {
         conn.pragma_update(None, "synchronous", "NORMAL")?;
 
         if matches!(self.config, SqliteConfig::File(_)) {
-            if let Err(err) = conn.pragma_update(None, "journal_mode", "WAL") {
-                log::warn!("Failed to enable WAL journal mode: {err}");
+            // SQLite answers with the mode it ended up in, which stays the rollback
+            // journal on filesystems without shared memory support
+            match conn
+                .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+            {
+                Ok(mode) if mode.eq_ignore_ascii_case("wal") => {}
+                Ok(mode) => log::warn!(
+                    "SQLite kept journal mode '{mode}'; readers will block writers on this database"
+                ),
+                Err(err) => log::warn!("Failed to enable WAL journal mode: {err}"),
             }
+            conn.pragma_update(None, "journal_size_limit", JOURNAL_SIZE_LIMIT_BYTES)?;
         }
 
-        conn.busy_timeout(Duration::from_millis(5_000))?;
+        conn.busy_timeout(self.busy_timeout)?;
         Ok(())
     }
 }
@@ -103,6 +124,15 @@ impl Database {
                 .join("sessions.db")
         });
 
+        let busy_timeout = std::env::var("SCHALTWERK_DB_BUSY_TIMEOUT_MS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS);
+
+        Self::open(path, Duration::from_millis(busy_timeout))
+    }
+
+    fn open(path: PathBuf, busy_timeout: Duration) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -113,7 +143,7 @@ impl Database {
             .filter(|size| *size > 0)
             .unwrap_or(DEFAULT_POOL_SIZE);
 
-        let manager = SqliteConnectionManager::file(path.clone());
+        let manager = SqliteConnectionManager::file(path.clone(), busy_timeout);
 
         let pool = Pool::builder()
             .max_size(pool_size)
@@ -122,6 +152,7 @@ impl Database {
 
         let db = Self {
             pool: Arc::new(pool),
+            path: Some(path),
         };
 
         db.initialize_schema()?;
@@ -129,13 +160,17 @@ impl Database {
         Ok(db)
     }
 
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     pub(crate) fn get_conn(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
         self.pool
             .get()
             .context("failed to borrow SQLite connection from pool")
     }
 
-    fn initialize_schema(&self) -> Result<()> {
+    pub(crate) fn initialize_schema(&self) -> Result<()> {
         db_schema::initialize_schema(self)
     }
 
@@ -150,6 +185,7 @@ impl Database {
 
         let db = Self {
             pool: Arc::new(pool),
+            path: None,
         };
 
         db.initialize_schema()?;
@@ -157,3 +193,58 @@ impl Database {
         Ok(db)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_databases_use_wal_and_wait_for_writers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::open(
+            temp_dir.path().join("sessions.db"),
+            Duration::from_millis(1_234),
+        )
+        .unwrap();
+        {
+            let conn = db.get_conn().unwrap();
+            let mode: String = conn
+                .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(mode.to_lowercase(), "wal");
+            let timeout: i64 = conn
+                .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(timeout, 1_234);
+        }
+
+        // Writers queue behind each other while readers keep reading
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let db = db.clone();
+                std::thread::spawn(move || {
+                    for round in 0..20 {
+                        let conn = db.get_conn().unwrap();
+                        if i % 2 == 0 {
+                            conn.execute(
+                                "INSERT OR REPLACE INTO session_time_entries
+                                 (session_id, day, active_seconds) VALUES (?1, '2026-01-02', ?2)",
+                                rusqlite::params![format!("s{i}"), round],
+                            )
+                            .unwrap();
+                        } else {
+                            let _: i64 = conn
+                                .query_row("SELECT COUNT(*) FROM session_time_entries", [], |row| {
+                                    row.get(0)
+                                })
+                                .unwrap();
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
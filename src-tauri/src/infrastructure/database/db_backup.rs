@@ -0,0 +1,243 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::schaltwerk_core::database::Database;
+
+/// Pages copied per backup step; the source stays usable between steps
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 256;
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    /// What `PRAGMA integrity_check` reported, at most 20 lines
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DatabaseBackup {
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+pub trait BackupMethods {
+    /// Snapshot the database to `destination` with SQLite's online backup API.
+    fn backup_database(&self, destination: &Path) -> Result<()>;
+    /// Replace the database contents with the backup at `source`. The current
+    /// contents are backed up first.
+    fn restore_database(&self, source: &Path) -> Result<()>;
+    fn check_database_integrity(&self) -> Result<IntegrityReport>;
+    /// Back up into the backups directory next to the database and keep the newest `keep`.
+    fn create_automatic_backup(&self, keep: usize) -> Result<PathBuf>;
+    /// Backups in the backups directory, newest first.
+    fn list_database_backups(&self) -> Result<Vec<DatabaseBackup>>;
+}
+
+fn integrity_problems(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check(20)")?;
+    let lines = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(lines.into_iter().filter(|line| line != "ok").collect())
+}
+
+fn copy_database(source: &Connection, destination: &mut Connection) -> Result<()> {
+    Backup::new(source, destination)?.run_to_completion(
+        BACKUP_PAGES_PER_STEP,
+        BACKUP_STEP_PAUSE,
+        None,
+    )?;
+    Ok(())
+}
+
+fn backup_dir(db: &Database) -> Result<PathBuf> {
+    let path = db
+        .path()
+        .ok_or_else(|| anyhow!("In-memory databases have no backup directory"))?;
+    Ok(path
+        .parent()
+        .map(|parent| parent.join("backups"))
+        .unwrap_or_else(|| PathBuf::from("backups")))
+}
+
+fn is_backup_file(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("db")
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("sessions-"))
+}
+
+fn new_backup_path(dir: &Path, suffix: &str) -> PathBuf {
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let mut path = dir.join(format!("sessions-{stamp}{suffix}.db"));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!("sessions-{stamp}{suffix}-{counter}.db"));
+        counter += 1;
+    }
+    path
+}
+
+fn prune_backups(dir: &Path, keep: usize) -> Result<()> {
+    let mut backups: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_backup_file(path))
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .collect();
+    backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in backups.into_iter().skip(keep) {
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!(
+                "Failed to remove old database backup {}: {e}",
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+impl BackupMethods for Database {
+    fn backup_database(&self, destination: &Path) -> Result<()> {
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Written next to the destination and renamed, so a failed backup never
+        // replaces a good one
+        let partial = destination.with_extension("db-partial");
+        let result = (|| {
+            let conn = self.get_conn()?;
+            let mut target = Connection::open(&partial)?;
+            copy_database(&conn, &mut target)?;
+            let problems = integrity_problems(&target)?;
+            if !problems.is_empty() {
+                bail!("Backup failed its integrity check: {}", problems.join("; "));
+            }
+            drop(target);
+            fs::rename(&partial, destination)?;
+            Ok(())
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+        result.with_context(|| format!("Failed to back up database to {}", destination.display()))
+    }
+
+    fn restore_database(&self, source: &Path) -> Result<()> {
+        let backup = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open backup {}", source.display()))?;
+        let problems = integrity_problems(&backup)?;
+        if !problems.is_empty() {
+            bail!(
+                "Backup {} failed its integrity check: {}",
+                source.display(),
+                problems.join("; ")
+            );
+        }
+
+        if let Ok(dir) = backup_dir(self) {
+            let safety = new_backup_path(&dir, "-pre-restore");
+            self.backup_database(&safety)?;
+            log::info!(
+                "Backed up the database to {} before restoring",
+                safety.display()
+            );
+        }
+
+        {
+            let mut conn = self.get_conn()?;
+            copy_database(&backup, &mut conn)?;
+        }
+        // Backups of older versions are brought up to the current schema
+        self.initialize_schema()?;
+        log::info!("Restored the database from {}", source.display());
+        Ok(())
+    }
+
+    fn check_database_integrity(&self) -> Result<IntegrityReport> {
+        let conn = self.get_conn()?;
+        let problems = integrity_problems(&conn)?;
+        Ok(IntegrityReport {
+            ok: problems.is_empty(),
+            problems,
+        })
+    }
+
+    fn create_automatic_backup(&self, keep: usize) -> Result<PathBuf> {
+        let dir = backup_dir(self)?;
+        let path = new_backup_path(&dir, "");
+        self.backup_database(&path)?;
+        prune_backups(&dir, keep.max(1))?;
+        Ok(path)
+    }
+
+    fn list_database_backups(&self) -> Result<Vec<DatabaseBackup>> {
+        let dir = backup_dir(self)?;
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+        let mut backups: Vec<DatabaseBackup> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_backup_file(path))
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                Some(DatabaseBackup {
+                    path: path.display().to_string(),
+                    created_at: metadata.modified().ok()?.into(),
+                    size_bytes: metadata.len(),
+                })
+            })
+            .collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+        Ok(backups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::database::db_app_config::AppConfigMethods;
+    use tempfile::TempDir;
+
+    #[test]
+    fn backup_and_restore_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let db = Database::new(Some(tmp.path().join("sessions.db"))).unwrap();
+        assert!(db.check_database_integrity().unwrap().ok);
+
+        db.set_font_sizes(15, 12).unwrap();
+        let backup = db.create_automatic_backup(5).unwrap();
+        assert!(backup.starts_with(tmp.path().join("backups")));
+
+        db.set_font_sizes(21, 12).unwrap();
+        db.restore_database(&backup).unwrap();
+        assert_eq!(db.get_font_sizes().unwrap().0, 15);
+
+        // The restore kept a backup of the contents it replaced
+        let backups = db.list_database_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups
+            .iter()
+            .any(|backup| backup.path.contains("pre-restore")));
+    }
+
+    #[test]
+    fn restore_refuses_a_damaged_backup() {
+        let tmp = TempDir::new().unwrap();
+        let db = Database::new(Some(tmp.path().join("sessions.db"))).unwrap();
+        let damaged = tmp.path().join("damaged.db");
+        fs::write(&damaged, b"not a database").unwrap();
+
+        assert!(db.restore_database(&damaged).is_err());
+        assert!(db.list_database_backups().unwrap().is_empty());
+    }
+}
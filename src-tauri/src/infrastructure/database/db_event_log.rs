@@ -0,0 +1,79 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Result as SqlResult};
+use serde::Serialize;
+
+use crate::schaltwerk_core::database::Database;
+
+/// An emitted event as stored for replay; `seq` increases with every stored event
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LoggedEvent {
+    pub seq: u64,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+pub trait EventLogMethods {
+    fn append_event(&self, event: &str, payload: &serde_json::Value) -> Result<u64>;
+    fn list_events_since(&self, since_seq: u64, limit: usize) -> Result<Vec<LoggedEvent>>;
+    fn event_seq_range(&self) -> Result<Option<(u64, u64)>>;
+    fn prune_event_log(&self, keep: u64) -> Result<()>;
+}
+
+impl EventLogMethods for Database {
+    fn append_event(&self, event: &str, payload: &serde_json::Value) -> Result<u64> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO event_log (event, payload, created_at) VALUES (?1, ?2, ?3)",
+            params![event, payload.to_string(), Utc::now().timestamp_millis()],
+        )?;
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn list_events_since(&self, since_seq: u64, limit: usize) -> Result<Vec<LoggedEvent>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT seq, event, payload, created_at FROM event_log
+             WHERE seq > ?1
+             ORDER BY seq
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![since_seq as i64, limit as i64], |row| {
+            let payload: String = row.get(2)?;
+            let created_at: i64 = row.get(3)?;
+            Ok(LoggedEvent {
+                seq: row.get::<_, i64>(0)? as u64,
+                event: row.get(1)?,
+                payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+                created_at: Utc
+                    .timestamp_millis_opt(created_at)
+                    .single()
+                    .unwrap_or_default(),
+            })
+        })?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    /// Oldest and newest stored sequence numbers, `None` while the log is empty.
+    fn event_seq_range(&self) -> Result<Option<(u64, u64)>> {
+        let conn = self.get_conn()?;
+        let range: (Option<i64>, Option<i64>) =
+            conn.query_row("SELECT MIN(seq), MAX(seq) FROM event_log", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+        Ok(match range {
+            (Some(oldest), Some(latest)) => Some((oldest as u64, latest as u64)),
+            _ => None,
+        })
+    }
+
+    fn prune_event_log(&self, keep: u64) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM event_log WHERE seq <= (SELECT MAX(seq) FROM event_log) - ?1",
+            params![keep as i64],
+        )?;
+        Ok(())
+    }
+}
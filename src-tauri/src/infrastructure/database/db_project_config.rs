@@ -1,14 +1,16 @@
 use super::connection::Database;
+use crate::domains::settings::SettingsOverrides;
 use anyhow::{anyhow, Result};
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub const DEFAULT_BRANCH_PREFIX: &str = "schaltwerk";
 
-fn normalize_branch_prefix(input: &str) -> String {
+/// Trim whitespace and surrounding slashes; an empty prefix falls back to the default.
+pub fn normalize_branch_prefix(input: &str) -> String {
     let trimmed = input.trim();
     let trimmed = trimmed.trim_matches('/');
     let normalized = trimmed.trim();
@@ -19,6 +21,44 @@ fn normalize_branch_prefix(input: &str) -> String {
     }
 }
 
+/// Repository-relative form of a protected path: surrounding whitespace, a leading
+/// `./` and surrounding slashes removed. `None` for an empty entry.
+fn normalize_protected_path(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    let trimmed = trimmed.strip_prefix("./").unwrap_or(trimmed);
+    let normalized = trimmed.trim_matches('/').trim();
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized.to_string())
+    }
+}
+
+fn normalize_worktree_root(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+/// Directory new session worktrees are created in. Relative roots resolve
+/// against the repository; without a configured root `.schaltwerk/worktrees` is used.
+pub fn resolve_worktree_root(repo_path: &Path, configured: Option<&str>) -> PathBuf {
+    match configured.and_then(normalize_worktree_root) {
+        Some(root) => {
+            let root = PathBuf::from(root);
+            if root.is_absolute() {
+                root
+            } else {
+                repo_path.join(root)
+            }
+        }
+        None => repo_path.join(".schaltwerk").join("worktrees"),
+    }
+}
+
 const SQUASH_MERGE_MAIN_PROMPT: &str = r#"Task: Squash-merge all reviewed Schaltwerk sessions
 
 Find all reviewed sessions and merge them to main branch with proper validation and fallback handling.
@@ -157,6 +197,24 @@ pub struct ProjectSessionsSettings {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectMergePreferences {
     pub auto_cancel_after_merge: bool,
+    /// Sign merge and auto-commits when git is configured to (`commit.gpgsign`)
+    #[serde(default = "default_sign_commits")]
+    pub sign_commits: bool,
+    /// Run pre-commit and commit-msg hooks for merge and auto-commits
+    #[serde(default = "default_run_git_hooks")]
+    pub run_git_hooks: bool,
+    /// Files or directories, relative to the repository root, that a merge only changes
+    /// after explicit confirmation
+    #[serde(default)]
+    pub protected_paths: Vec<String>,
+}
+
+fn default_sign_commits() -> bool {
+    true
+}
+
+fn default_run_git_hooks() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -177,6 +235,9 @@ pub struct RunScript {
     pub working_directory: Option<String>,
     #[serde(default)]
     pub environment_variables: HashMap<String, String>,
+    /// Restart the command whenever the session worktree changes
+    #[serde(default)]
+    pub watch: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -186,9 +247,206 @@ pub struct ProjectGithubConfig {
     pub default_branch: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCodexSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub config_overrides: Vec<CodexConfigOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CodexConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+/// Kinds of work a project can assign a default agent to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentTaskKind {
+    /// Sessions started from a spec
+    Spec,
+    /// The project orchestrator
+    Orchestrator,
+    /// Sessions created directly from a prompt, and restarts of sessions without stored settings
+    FollowUp,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAgentDefault {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_permissions: Option<bool>,
+}
+
+/// Per-project agent defaults by task kind; unset entries fall back to the global settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectAgentDefaults {
+    #[serde(default)]
+    pub spec: TaskAgentDefault,
+    #[serde(default)]
+    pub orchestrator: TaskAgentDefault,
+    #[serde(default)]
+    pub follow_up: TaskAgentDefault,
+}
+
+impl ProjectAgentDefaults {
+    pub fn for_kind(&self, kind: AgentTaskKind) -> &TaskAgentDefault {
+        match kind {
+            AgentTaskKind::Spec => &self.spec,
+            AgentTaskKind::Orchestrator => &self.orchestrator,
+            AgentTaskKind::FollowUp => &self.follow_up,
+        }
+    }
+}
+
+/// Container engine used for container-backed sessions
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    Orbstack,
+}
+
+/// Runs session terminals inside a per-session container with the worktree mounted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectContainerConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub runtime: ContainerRuntime,
+    pub image: String,
+    /// Extra arguments passed to `docker run` when the session container is created
+    #[serde(default)]
+    pub run_args: Vec<String>,
+}
+
+/// Declarative development environment a project can run its sessions in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DevEnvironmentKind {
+    Devcontainer,
+    Nix,
+}
+
+/// Package caches shared by all worktrees of a project (opt-in per tool)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDependencyCacheConfig {
+    /// Point pnpm at one content-addressable store so installs hard-link instead of download
+    #[serde(default)]
+    pub pnpm_store: bool,
+    /// Share one `CARGO_TARGET_DIR` so crates are compiled once per project
+    #[serde(default)]
+    pub cargo_target_dir: bool,
+    /// Share the pip and uv download caches
+    #[serde(default)]
+    pub pip_cache: bool,
+}
+
+impl ProjectDependencyCacheConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.pnpm_store || self.cargo_target_dir || self.pip_cache
+    }
+}
+
+pub const STALE_SESSION_LABEL: &str = "stale";
+
+/// What the stale session policy does with a session that went quiet
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleSessionAction {
+    /// Add the `stale` label and leave the session alone
+    #[default]
+    Label,
+    /// Cancel the session, removing its worktree and branch
+    Cancel,
+}
+
+/// Clean up sessions without activity and uncommitted changes for `idle_days`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStaleSessionPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_stale_idle_days")]
+    pub idle_days: u32,
+    #[serde(default)]
+    pub action: StaleSessionAction,
+}
+
+fn default_stale_idle_days() -> u32 {
+    14
+}
+
+impl Default for ProjectStaleSessionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_days: default_stale_idle_days(),
+            action: StaleSessionAction::default(),
+        }
+    }
+}
+
+/// A named step of the session setup, run after the project's setup script
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedSetupScript {
+    pub name: String,
+    pub script: String,
+    #[serde(default = "default_setup_script_enabled")]
+    pub enabled: bool,
+}
+
+fn default_setup_script_enabled() -> bool {
+    true
+}
+
+/// Named setup steps and how long a whole setup run may take
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSetupScripts {
+    #[serde(default)]
+    pub scripts: Vec<NamedSetupScript>,
+    /// Setup still running after this many seconds is stopped and fails; no limit when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// URLs that receive outbound notifications (Slack webhooks, CI triggers) and which
+/// events are sent to them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectOutboundWebhookConfig {
+    #[serde(default)]
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub session_reviewed: bool,
+    #[serde(default)]
+    pub merge_completed: bool,
+    #[serde(default)]
+    pub agent_crashed: bool,
+}
+
 pub trait ProjectConfigMethods {
     fn get_project_setup_script(&self, repo_path: &Path) -> Result<Option<String>>;
     fn set_project_setup_script(&self, repo_path: &Path, setup_script: &str) -> Result<()>;
+    fn get_project_test_script(&self, repo_path: &Path) -> Result<Option<String>>;
+    fn set_project_test_script(&self, repo_path: &Path, test_script: &str) -> Result<()>;
+    fn get_project_test_timeout_minutes(&self, repo_path: &Path) -> Result<Option<u32>>;
+    fn set_project_test_timeout_minutes(
+        &self,
+        repo_path: &Path,
+        minutes: Option<u32>,
+    ) -> Result<()>;
     fn get_project_sessions_settings(&self, repo_path: &Path) -> Result<ProjectSessionsSettings>;
     fn set_project_sessions_settings(
         &self,
@@ -227,6 +485,80 @@ pub trait ProjectConfigMethods {
         config: &ProjectGithubConfig,
     ) -> Result<()>;
     fn clear_project_github_config(&self, repo_path: &Path) -> Result<()>;
+    fn get_project_codex_settings(&self, repo_path: &Path) -> Result<ProjectCodexSettings>;
+    fn set_project_codex_settings(
+        &self,
+        repo_path: &Path,
+        settings: &ProjectCodexSettings,
+    ) -> Result<()>;
+    fn get_project_agent_defaults(&self, repo_path: &Path) -> Result<ProjectAgentDefaults>;
+    fn set_project_agent_defaults(
+        &self,
+        repo_path: &Path,
+        defaults: &ProjectAgentDefaults,
+    ) -> Result<()>;
+    fn get_project_container_config(
+        &self,
+        repo_path: &Path,
+    ) -> Result<Option<ProjectContainerConfig>>;
+    fn set_project_container_config(
+        &self,
+        repo_path: &Path,
+        config: &ProjectContainerConfig,
+    ) -> Result<()>;
+    fn get_project_dev_environment(&self, repo_path: &Path) -> Result<Option<DevEnvironmentKind>>;
+    fn set_project_dev_environment(
+        &self,
+        repo_path: &Path,
+        environment: Option<DevEnvironmentKind>,
+    ) -> Result<()>;
+    fn get_project_dependency_cache_config(
+        &self,
+        repo_path: &Path,
+    ) -> Result<ProjectDependencyCacheConfig>;
+    fn set_project_dependency_cache_config(
+        &self,
+        repo_path: &Path,
+        config: &ProjectDependencyCacheConfig,
+    ) -> Result<()>;
+    fn get_project_worktree_root(&self, repo_path: &Path) -> Result<Option<String>>;
+    fn set_project_worktree_root(
+        &self,
+        repo_path: &Path,
+        worktree_root: Option<&str>,
+    ) -> Result<()>;
+    fn get_project_stale_session_policy(
+        &self,
+        repo_path: &Path,
+    ) -> Result<ProjectStaleSessionPolicy>;
+    fn set_project_stale_session_policy(
+        &self,
+        repo_path: &Path,
+        policy: &ProjectStaleSessionPolicy,
+    ) -> Result<()>;
+    fn get_project_outbound_webhook_config(
+        &self,
+        repo_path: &Path,
+    ) -> Result<ProjectOutboundWebhookConfig>;
+    fn set_project_outbound_webhook_config(
+        &self,
+        repo_path: &Path,
+        config: &ProjectOutboundWebhookConfig,
+    ) -> Result<()>;
+    fn get_project_settings_overrides(&self, repo_path: &Path) -> Result<SettingsOverrides>;
+    fn set_project_settings_overrides(
+        &self,
+        repo_path: &Path,
+        overrides: &SettingsOverrides,
+    ) -> Result<()>;
+    fn get_project_env_sets(&self, repo_path: &Path) -> Result<Vec<String>>;
+    fn set_project_env_sets(&self, repo_path: &Path, env_sets: &[String]) -> Result<()>;
+    fn get_project_setup_scripts(&self, repo_path: &Path) -> Result<ProjectSetupScripts>;
+    fn set_project_setup_scripts(
+        &self,
+        repo_path: &Path,
+        scripts: &ProjectSetupScripts,
+    ) -> Result<()>;
 }
 
 impl ProjectConfigMethods for Database {
@@ -270,6 +602,86 @@ impl ProjectConfigMethods for Database {
         Ok(())
     }
 
+    fn get_project_test_script(&self, repo_path: &Path) -> Result<Option<String>> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let result: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT test_script FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(script) => Ok(script.filter(|script| !script.trim().is_empty())),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_test_script(&self, repo_path: &Path, test_script: &str) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, test_script, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    test_script = excluded.test_script,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), test_script, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_test_timeout_minutes(&self, repo_path: &Path) -> Result<Option<u32>> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let result: rusqlite::Result<Option<u32>> = conn.query_row(
+            "SELECT test_timeout_minutes FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(minutes) => Ok(minutes.filter(|minutes| *minutes > 0)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_test_timeout_minutes(
+        &self,
+        repo_path: &Path,
+        minutes: Option<u32>,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, test_timeout_minutes, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    test_timeout_minutes = excluded.test_timeout_minutes,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), minutes, now, now],
+        )?;
+
+        Ok(())
+    }
+
     fn get_project_sessions_settings(&self, repo_path: &Path) -> Result<ProjectSessionsSettings> {
         let conn = self.get_conn()?;
 
@@ -429,20 +841,33 @@ impl ProjectConfigMethods for Database {
         let canonical_path =
             std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
 
-        let query_res: rusqlite::Result<Option<i64>> = conn.query_row(
-            "SELECT auto_cancel_after_merge FROM project_config WHERE repository_path = ?1",
-            params![canonical_path.to_string_lossy()],
-            |row| row.get(0),
-        );
-
-        let auto_cancel = match query_res {
-            Ok(Some(value)) => value != 0,
-            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => true,
+        let query_res: rusqlite::Result<(Option<i64>, Option<i64>, Option<i64>, Option<String>)> =
+            conn.query_row(
+                "SELECT auto_cancel_after_merge, sign_commits, run_git_hooks, protected_paths
+                 FROM project_config WHERE repository_path = ?1",
+                params![canonical_path.to_string_lossy()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            );
+
+        let (auto_cancel, sign_commits, run_git_hooks, protected_paths) = match query_res {
+            Ok((auto_cancel, sign_commits, run_git_hooks, protected_paths)) => (
+                auto_cancel.is_none_or(|value| value != 0),
+                sign_commits.is_none_or(|value| value != 0),
+                run_git_hooks.is_none_or(|value| value != 0),
+                match protected_paths {
+                    Some(json_str) => serde_json::from_str(&json_str)?,
+                    None => Vec::new(),
+                },
+            ),
+            Err(rusqlite::Error::QueryReturnedNoRows) => (true, true, true, Vec::new()),
             Err(e) => return Err(e.into()),
         };
 
         Ok(ProjectMergePreferences {
             auto_cancel_after_merge: auto_cancel,
+            sign_commits,
+            run_git_hooks,
+            protected_paths,
         })
     }
 
@@ -461,15 +886,39 @@ impl ProjectConfigMethods for Database {
         } else {
             0
         };
+        let sign_commits = if preferences.sign_commits { 1 } else { 0 };
+        let run_git_hooks = if preferences.run_git_hooks { 1 } else { 0 };
+        let mut protected_paths: Vec<String> = Vec::new();
+        for path in preferences
+            .protected_paths
+            .iter()
+            .filter_map(|path| normalize_protected_path(path))
+        {
+            if !protected_paths.contains(&path) {
+                protected_paths.push(path);
+            }
+        }
+        let protected_paths = serde_json::to_string(&protected_paths)?;
 
         conn.execute(
-            "INSERT INTO project_config (repository_path, auto_cancel_after_merge,
-                                            created_at, updated_at)
-                VALUES (?1, ?2, ?3, ?4)
+            "INSERT INTO project_config (repository_path, auto_cancel_after_merge, sign_commits,
+                                            run_git_hooks, protected_paths, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
                 ON CONFLICT(repository_path) DO UPDATE SET
                     auto_cancel_after_merge = excluded.auto_cancel_after_merge,
+                    sign_commits            = excluded.sign_commits,
+                    run_git_hooks           = excluded.run_git_hooks,
+                    protected_paths         = excluded.protected_paths,
                     updated_at              = excluded.updated_at",
-            params![canonical_path.to_string_lossy(), value, now, now],
+            params![
+                canonical_path.to_string_lossy(),
+                value,
+                sign_commits,
+                run_git_hooks,
+                protected_paths,
+                now,
+                now
+            ],
         )?;
 
         Ok(())
@@ -662,50 +1111,701 @@ impl ProjectConfigMethods for Database {
 
         Ok(())
     }
-}
 
-impl Database {
-    fn get_default_action_buttons() -> Vec<HeaderActionConfig> {
-        vec![
-            HeaderActionConfig {
-                id: "squash-merge-main".to_string(),
-                label: "Squash Merge Main".to_string(),
-                prompt: SQUASH_MERGE_MAIN_PROMPT.to_string(),
-                color: Some("green".to_string()),
-            },
-            HeaderActionConfig {
-                id: "create-pr".to_string(),
-                label: "PR".to_string(),
-                prompt: "Create a pull request for the current branch with a comprehensive description of changes.".to_string(),
-                color: Some("blue".to_string()),
-            },
-            HeaderActionConfig {
-                id: "run-tests".to_string(),
-                label: "Test".to_string(),
-                prompt: "Run all tests and fix any failures that occur.".to_string(),
-                color: Some("amber".to_string()),
-            },
-        ]
-    }
-}
+    fn get_project_codex_settings(&self, repo_path: &Path) -> Result<ProjectCodexSettings> {
+        let conn = self.get_conn()?;
 
-pub fn default_action_buttons() -> Vec<HeaderActionConfig> {
-    Database::get_default_action_buttons()
-}
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::infrastructure::database::connection::Database;
-    use tempfile::TempDir;
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT codex_settings FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
 
-    fn create_temp_repo_path() -> (TempDir, std::path::PathBuf) {
-        let temp_dir = TempDir::new().expect("temp dir");
-        let project_path = temp_dir.path().join("repo");
-        std::fs::create_dir_all(&project_path).expect("create project path");
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(ProjectCodexSettings::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_codex_settings(
+        &self,
+        repo_path: &Path,
+        settings: &ProjectCodexSettings,
+    ) -> Result<()> {
+        if let Some(invalid) = settings
+            .config_overrides
+            .iter()
+            .find(|o| o.key.trim().is_empty() || o.key.contains('='))
+        {
+            return Err(anyhow!(
+                "Invalid Codex config override key: '{}'",
+                invalid.key
+            ));
+        }
+
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(settings)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, codex_settings, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    codex_settings = excluded.codex_settings,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_agent_defaults(&self, repo_path: &Path) -> Result<ProjectAgentDefaults> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT agent_defaults FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(ProjectAgentDefaults::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_agent_defaults(
+        &self,
+        repo_path: &Path,
+        defaults: &ProjectAgentDefaults,
+    ) -> Result<()> {
+        for entry in [&defaults.spec, &defaults.orchestrator, &defaults.follow_up] {
+            if let Some(agent_type) = entry.agent_type.as_deref() {
+                if crate::domains::agents::manifest::AgentManifest::get(agent_type).is_none() {
+                    return Err(anyhow!("Unknown agent type: '{agent_type}'"));
+                }
+            }
+        }
+
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(defaults)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, agent_defaults, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    agent_defaults = excluded.agent_defaults,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_container_config(
+        &self,
+        repo_path: &Path,
+    ) -> Result<Option<ProjectContainerConfig>> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT container_config FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(Some(serde_json::from_str(&json_str)?)),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_container_config(
+        &self,
+        repo_path: &Path,
+        config: &ProjectContainerConfig,
+    ) -> Result<()> {
+        if config.enabled && config.image.trim().is_empty() {
+            return Err(anyhow!(
+                "A container image is required to enable container sessions"
+            ));
+        }
+
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(config)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, container_config, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    container_config = excluded.container_config,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_dev_environment(&self, repo_path: &Path) -> Result<Option<DevEnvironmentKind>> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT dev_environment FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_dev_environment(
+        &self,
+        repo_path: &Path,
+        environment: Option<DevEnvironmentKind>,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = environment
+            .map(|kind| serde_json::to_string(&kind))
+            .transpose()?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, dev_environment, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    dev_environment = excluded.dev_environment,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_dependency_cache_config(
+        &self,
+        repo_path: &Path,
+    ) -> Result<ProjectDependencyCacheConfig> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT dependency_cache_config FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(ProjectDependencyCacheConfig::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_dependency_cache_config(
+        &self,
+        repo_path: &Path,
+        config: &ProjectDependencyCacheConfig,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(config)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, dependency_cache_config, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    dependency_cache_config = excluded.dependency_cache_config,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_worktree_root(&self, repo_path: &Path) -> Result<Option<String>> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let result: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT worktree_root FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(value) => Ok(value.as_deref().and_then(normalize_worktree_root)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_worktree_root(
+        &self,
+        repo_path: &Path,
+        worktree_root: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let normalized = worktree_root.and_then(normalize_worktree_root);
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, worktree_root, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    worktree_root = excluded.worktree_root,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), normalized, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_stale_session_policy(
+        &self,
+        repo_path: &Path,
+    ) -> Result<ProjectStaleSessionPolicy> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT stale_session_policy FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(ProjectStaleSessionPolicy::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_stale_session_policy(
+        &self,
+        repo_path: &Path,
+        policy: &ProjectStaleSessionPolicy,
+    ) -> Result<()> {
+        if policy.idle_days == 0 {
+            return Err(anyhow!("Stale session policy needs at least one idle day"));
+        }
+
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(policy)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, stale_session_policy, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    stale_session_policy = excluded.stale_session_policy,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_outbound_webhook_config(
+        &self,
+        repo_path: &Path,
+    ) -> Result<ProjectOutboundWebhookConfig> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT outbound_webhook_config FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(ProjectOutboundWebhookConfig::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_outbound_webhook_config(
+        &self,
+        repo_path: &Path,
+        config: &ProjectOutboundWebhookConfig,
+    ) -> Result<()> {
+        let mut config = config.clone();
+        config.urls = config
+            .urls
+            .iter()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        for url in &config.urls {
+            let parsed =
+                url::Url::parse(url).map_err(|e| anyhow!("Invalid webhook URL '{url}': {e}"))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(anyhow!("Webhook URL '{url}' must use http or https"));
+            }
+        }
+
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(&config)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, outbound_webhook_config, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    outbound_webhook_config = excluded.outbound_webhook_config,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_settings_overrides(&self, repo_path: &Path) -> Result<SettingsOverrides> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT settings_overrides FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(SettingsOverrides::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_settings_overrides(
+        &self,
+        repo_path: &Path,
+        overrides: &SettingsOverrides,
+    ) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(overrides)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, settings_overrides, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    settings_overrides = excluded.settings_overrides,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_env_sets(&self, repo_path: &Path) -> Result<Vec<String>> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT env_sets FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_env_sets(&self, repo_path: &Path, env_sets: &[String]) -> Result<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(env_sets)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, env_sets, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    env_sets = excluded.env_sets,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_project_setup_scripts(&self, repo_path: &Path) -> Result<ProjectSetupScripts> {
+        let conn = self.get_conn()?;
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let query_res: rusqlite::Result<Option<String>> = conn.query_row(
+            "SELECT setup_scripts FROM project_config WHERE repository_path = ?1",
+            params![canonical_path.to_string_lossy()],
+            |row| row.get(0),
+        );
+
+        match query_res {
+            Ok(Some(json_str)) => Ok(serde_json::from_str(&json_str)?),
+            Ok(None) | Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Ok(ProjectSetupScripts::default())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_project_setup_scripts(
+        &self,
+        repo_path: &Path,
+        scripts: &ProjectSetupScripts,
+    ) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+        for script in &scripts.scripts {
+            let name = script.name.trim();
+            if name.is_empty() {
+                return Err(anyhow!("Setup scripts need a name"));
+            }
+            if !names.insert(name) {
+                return Err(anyhow!("Duplicate setup script name: '{name}'"));
+            }
+        }
+        if scripts.timeout_secs == Some(0) {
+            return Err(anyhow!("Setup timeout must be at least one second"));
+        }
+
+        let conn = self.get_conn()?;
+        let now = Utc::now().timestamp();
+
+        let canonical_path =
+            std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+
+        let json_str = serde_json::to_string(scripts)?;
+
+        conn.execute(
+            "INSERT INTO project_config (repository_path, setup_scripts, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(repository_path) DO UPDATE SET
+                    setup_scripts = excluded.setup_scripts,
+                    updated_at = excluded.updated_at",
+            params![canonical_path.to_string_lossy(), json_str, now, now],
+        )?;
+
+        Ok(())
+    }
+}
+
+impl Database {
+    fn get_default_action_buttons() -> Vec<HeaderActionConfig> {
+        vec![
+            HeaderActionConfig {
+                id: "squash-merge-main".to_string(),
+                label: "Squash Merge Main".to_string(),
+                prompt: SQUASH_MERGE_MAIN_PROMPT.to_string(),
+                color: Some("green".to_string()),
+            },
+            HeaderActionConfig {
+                id: "create-pr".to_string(),
+                label: "PR".to_string(),
+                prompt: "Create a pull request for the current branch with a comprehensive description of changes.".to_string(),
+                color: Some("blue".to_string()),
+            },
+            HeaderActionConfig {
+                id: "run-tests".to_string(),
+                label: "Test".to_string(),
+                prompt: "Run all tests and fix any failures that occur.".to_string(),
+                color: Some("amber".to_string()),
+            },
+        ]
+    }
+}
+
+pub fn default_action_buttons() -> Vec<HeaderActionConfig> {
+    Database::get_default_action_buttons()
+}
+
+/// Resolved worktree root of the project; lookup errors fall back to the default root.
+pub fn project_worktrees_root(db: &Database, repo_path: &Path) -> PathBuf {
+    let configured = db
+        .get_project_worktree_root(repo_path)
+        .unwrap_or_else(|err| {
+            log::warn!("Falling back to default worktree root due to error: {err}");
+            None
+        });
+    resolve_worktree_root(repo_path, configured.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::database::connection::Database;
+    use tempfile::TempDir;
+
+    fn create_temp_repo_path() -> (TempDir, std::path::PathBuf) {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let project_path = temp_dir.path().join("repo");
+        std::fs::create_dir_all(&project_path).expect("create project path");
         (temp_dir, project_path)
     }
 
+    #[test]
+    fn settings_overrides_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            db.get_project_settings_overrides(&repo_path).unwrap(),
+            SettingsOverrides::default()
+        );
+
+        let overrides = SettingsOverrides {
+            agent_cli_args: HashMap::from([("codex".to_string(), "--search".to_string())]),
+        };
+        db.set_project_settings_overrides(&repo_path, &overrides)
+            .expect("store overrides");
+
+        assert_eq!(
+            db.get_project_settings_overrides(&repo_path).unwrap(),
+            overrides
+        );
+    }
+
+    #[test]
+    fn env_sets_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert!(db.get_project_env_sets(&repo_path).unwrap().is_empty());
+
+        let env_sets = vec!["openai".to_string(), "staging".to_string()];
+        db.set_project_env_sets(&repo_path, &env_sets)
+            .expect("store env sets");
+
+        assert_eq!(db.get_project_env_sets(&repo_path).unwrap(), env_sets);
+    }
+
+    #[test]
+    fn setup_scripts_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            db.get_project_setup_scripts(&repo_path).unwrap(),
+            ProjectSetupScripts::default()
+        );
+
+        let scripts = ProjectSetupScripts {
+            scripts: vec![
+                NamedSetupScript {
+                    name: "deps".to_string(),
+                    script: "bun install".to_string(),
+                    enabled: true,
+                },
+                NamedSetupScript {
+                    name: "seed".to_string(),
+                    script: "just seed-db".to_string(),
+                    enabled: false,
+                },
+            ],
+            timeout_secs: Some(600),
+        };
+        db.set_project_setup_scripts(&repo_path, &scripts)
+            .expect("store setup scripts");
+        assert_eq!(db.get_project_setup_scripts(&repo_path).unwrap(), scripts);
+
+        let duplicate = ProjectSetupScripts {
+            scripts: vec![scripts.scripts[0].clone(), scripts.scripts[0].clone()],
+            timeout_secs: None,
+        };
+        assert!(db
+            .set_project_setup_scripts(&repo_path, &duplicate)
+            .is_err());
+    }
+
     #[test]
     fn github_config_round_trip() {
         let db = Database::new_in_memory().expect("db");
@@ -748,4 +1848,372 @@ mod tests {
 
         assert!(loaded.is_none());
     }
+
+    #[test]
+    fn test_script_round_trip_treats_blank_as_unset() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(db.get_project_test_script(&repo_path).expect("load"), None);
+
+        db.set_project_test_script(&repo_path, "cargo test")
+            .expect("store script");
+        assert_eq!(
+            db.get_project_test_script(&repo_path).expect("load"),
+            Some("cargo test".to_string())
+        );
+
+        db.set_project_test_script(&repo_path, "  ")
+            .expect("clear script");
+        assert_eq!(db.get_project_test_script(&repo_path).expect("load"), None);
+    }
+
+    #[test]
+    fn test_timeout_round_trip_treats_zero_as_unset() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            db.get_project_test_timeout_minutes(&repo_path)
+                .expect("load"),
+            None
+        );
+
+        db.set_project_test_timeout_minutes(&repo_path, Some(15))
+            .expect("store timeout");
+        assert_eq!(
+            db.get_project_test_timeout_minutes(&repo_path)
+                .expect("load"),
+            Some(15)
+        );
+
+        db.set_project_test_timeout_minutes(&repo_path, Some(0))
+            .expect("clear timeout");
+        assert_eq!(
+            db.get_project_test_timeout_minutes(&repo_path)
+                .expect("load"),
+            None
+        );
+    }
+
+    #[test]
+    fn protected_paths_are_normalized_when_stored() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        let preferences = db.get_project_merge_preferences(&repo_path).expect("load");
+        assert!(preferences.protected_paths.is_empty());
+
+        db.set_project_merge_preferences(
+            &repo_path,
+            &ProjectMergePreferences {
+                protected_paths: vec![
+                    "./.github/workflows/".to_string(),
+                    " infra/ ".to_string(),
+                    "".to_string(),
+                    "infra".to_string(),
+                ],
+                ..preferences
+            },
+        )
+        .expect("store preferences");
+        assert_eq!(
+            db.get_project_merge_preferences(&repo_path)
+                .expect("load")
+                .protected_paths,
+            vec![".github/workflows".to_string(), "infra".to_string()]
+        );
+    }
+
+    #[test]
+    fn codex_settings_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            ProjectCodexSettings::default(),
+            db.get_project_codex_settings(&repo_path).expect("defaults")
+        );
+
+        let settings = ProjectCodexSettings {
+            profile: Some("work".to_string()),
+            config_overrides: vec![CodexConfigOverride {
+                key: "model".to_string(),
+                value: "o3".to_string(),
+            }],
+        };
+
+        db.set_project_codex_settings(&repo_path, &settings)
+            .expect("store settings");
+
+        let loaded = db
+            .get_project_codex_settings(&repo_path)
+            .expect("load settings");
+
+        assert_eq!(settings, loaded);
+    }
+
+    #[test]
+    fn codex_settings_reject_invalid_keys() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        let settings = ProjectCodexSettings {
+            profile: None,
+            config_overrides: vec![CodexConfigOverride {
+                key: "model=o3".to_string(),
+                value: String::new(),
+            }],
+        };
+
+        assert!(db
+            .set_project_codex_settings(&repo_path, &settings)
+            .is_err());
+    }
+
+    #[test]
+    fn agent_defaults_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            ProjectAgentDefaults::default(),
+            db.get_project_agent_defaults(&repo_path).expect("defaults")
+        );
+
+        let defaults = ProjectAgentDefaults {
+            spec: TaskAgentDefault {
+                agent_type: Some("codex".to_string()),
+                skip_permissions: Some(true),
+            },
+            orchestrator: TaskAgentDefault {
+                agent_type: Some("claude".to_string()),
+                skip_permissions: None,
+            },
+            follow_up: TaskAgentDefault::default(),
+        };
+
+        db.set_project_agent_defaults(&repo_path, &defaults)
+            .expect("store defaults");
+
+        let loaded = db
+            .get_project_agent_defaults(&repo_path)
+            .expect("load defaults");
+
+        assert_eq!(defaults, loaded);
+        assert_eq!(
+            Some("codex"),
+            loaded.for_kind(AgentTaskKind::Spec).agent_type.as_deref()
+        );
+    }
+
+    #[test]
+    fn agent_defaults_reject_unknown_agent() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        let defaults = ProjectAgentDefaults {
+            follow_up: TaskAgentDefault {
+                agent_type: Some("not-an-agent".to_string()),
+                skip_permissions: None,
+            },
+            ..Default::default()
+        };
+
+        assert!(db
+            .set_project_agent_defaults(&repo_path, &defaults)
+            .is_err());
+    }
+
+    #[test]
+    fn container_config_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert!(db
+            .get_project_container_config(&repo_path)
+            .expect("load")
+            .is_none());
+
+        let config = ProjectContainerConfig {
+            enabled: true,
+            runtime: ContainerRuntime::Orbstack,
+            image: "node:22".to_string(),
+            run_args: vec!["--cpus=2".to_string()],
+        };
+        db.set_project_container_config(&repo_path, &config)
+            .expect("store config");
+
+        assert_eq!(
+            Some(config),
+            db.get_project_container_config(&repo_path).expect("load")
+        );
+
+        let missing_image = ProjectContainerConfig {
+            enabled: true,
+            runtime: ContainerRuntime::Docker,
+            image: " ".to_string(),
+            run_args: Vec::new(),
+        };
+        assert!(db
+            .set_project_container_config(&repo_path, &missing_image)
+            .is_err());
+    }
+
+    #[test]
+    fn dev_environment_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            None,
+            db.get_project_dev_environment(&repo_path).expect("load")
+        );
+
+        db.set_project_dev_environment(&repo_path, Some(DevEnvironmentKind::Nix))
+            .expect("store");
+        assert_eq!(
+            Some(DevEnvironmentKind::Nix),
+            db.get_project_dev_environment(&repo_path).expect("load")
+        );
+
+        db.set_project_dev_environment(&repo_path, None)
+            .expect("clear");
+        assert_eq!(
+            None,
+            db.get_project_dev_environment(&repo_path).expect("load")
+        );
+    }
+
+    #[test]
+    fn dependency_cache_config_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        let loaded = db
+            .get_project_dependency_cache_config(&repo_path)
+            .expect("load");
+        assert!(!loaded.is_enabled());
+
+        let config = ProjectDependencyCacheConfig {
+            pnpm_store: true,
+            cargo_target_dir: true,
+            pip_cache: false,
+        };
+        db.set_project_dependency_cache_config(&repo_path, &config)
+            .expect("store");
+        assert_eq!(
+            config,
+            db.get_project_dependency_cache_config(&repo_path)
+                .expect("load")
+        );
+    }
+
+    #[test]
+    fn stale_session_policy_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        let loaded = db
+            .get_project_stale_session_policy(&repo_path)
+            .expect("load");
+        assert_eq!(ProjectStaleSessionPolicy::default(), loaded);
+        assert!(!loaded.enabled);
+
+        let policy = ProjectStaleSessionPolicy {
+            enabled: true,
+            idle_days: 7,
+            action: StaleSessionAction::Cancel,
+        };
+        db.set_project_stale_session_policy(&repo_path, &policy)
+            .expect("store");
+        assert_eq!(
+            policy,
+            db.get_project_stale_session_policy(&repo_path)
+                .expect("load")
+        );
+
+        let invalid = ProjectStaleSessionPolicy {
+            idle_days: 0,
+            ..policy
+        };
+        assert!(db
+            .set_project_stale_session_policy(&repo_path, &invalid)
+            .is_err());
+    }
+
+    #[test]
+    fn outbound_webhook_config_round_trip() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            ProjectOutboundWebhookConfig::default(),
+            db.get_project_outbound_webhook_config(&repo_path)
+                .expect("load")
+        );
+
+        let config = ProjectOutboundWebhookConfig {
+            urls: vec![
+                " https://hooks.slack.com/services/T000/B000/XXX ".to_string(),
+                String::new(),
+            ],
+            session_reviewed: true,
+            merge_completed: false,
+            agent_crashed: true,
+        };
+        db.set_project_outbound_webhook_config(&repo_path, &config)
+            .expect("store");
+        let loaded = db
+            .get_project_outbound_webhook_config(&repo_path)
+            .expect("load");
+        assert_eq!(
+            loaded.urls,
+            vec!["https://hooks.slack.com/services/T000/B000/XXX".to_string()]
+        );
+        assert!(loaded.session_reviewed && loaded.agent_crashed && !loaded.merge_completed);
+
+        let invalid = ProjectOutboundWebhookConfig {
+            urls: vec!["ftp://example.com/hook".to_string()],
+            ..config
+        };
+        assert!(db
+            .set_project_outbound_webhook_config(&repo_path, &invalid)
+            .is_err());
+    }
+
+    #[test]
+    fn worktree_root_round_trip_and_resolution() {
+        let db = Database::new_in_memory().expect("db");
+        let (_tmp, repo_path) = create_temp_repo_path();
+
+        assert_eq!(
+            None,
+            db.get_project_worktree_root(&repo_path).expect("load")
+        );
+        assert_eq!(
+            repo_path.join(".schaltwerk").join("worktrees"),
+            resolve_worktree_root(&repo_path, None)
+        );
+
+        db.set_project_worktree_root(&repo_path, Some(" /Volumes/fast/worktrees "))
+            .expect("store");
+        let stored = db.get_project_worktree_root(&repo_path).expect("load");
+        assert_eq!(Some("/Volumes/fast/worktrees".to_string()), stored);
+        assert_eq!(
+            PathBuf::from("/Volumes/fast/worktrees"),
+            resolve_worktree_root(&repo_path, stored.as_deref())
+        );
+        assert_eq!(
+            repo_path.join("../trees"),
+            resolve_worktree_root(&repo_path, Some("../trees"))
+        );
+
+        db.set_project_worktree_root(&repo_path, Some("  "))
+            .expect("clear");
+        assert_eq!(
+            None,
+            db.get_project_worktree_root(&repo_path).expect("load")
+        );
+    }
 }
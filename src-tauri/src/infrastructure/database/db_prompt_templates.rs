@@ -0,0 +1,92 @@
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use rusqlite::{params, OptionalExtension, Result as SqlResult, Row};
+use std::path::{Path, PathBuf};
+
+use crate::domains::sessions::entity::PromptTemplate;
+use crate::schaltwerk_core::database::Database;
+
+pub trait PromptTemplateMethods {
+    fn save_prompt_template(&self, template: &PromptTemplate) -> Result<()>;
+    fn get_prompt_template(&self, id: &str) -> Result<Option<PromptTemplate>>;
+    fn list_prompt_templates(&self, repo_path: &Path) -> Result<Vec<PromptTemplate>>;
+    fn delete_prompt_template(&self, id: &str) -> Result<bool>;
+}
+
+const TEMPLATE_COLUMNS: &str = "id, name, content, repository_path, created_at, updated_at";
+
+fn template_from_row(row: &Row) -> SqlResult<PromptTemplate> {
+    let created_at: i64 = row.get(4)?;
+    let updated_at: i64 = row.get(5)?;
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        content: row.get(2)?,
+        repository_path: row.get::<_, Option<String>>(3)?.map(PathBuf::from),
+        created_at: Utc
+            .timestamp_opt(created_at, 0)
+            .single()
+            .unwrap_or_default(),
+        updated_at: Utc
+            .timestamp_opt(updated_at, 0)
+            .single()
+            .unwrap_or_default(),
+    })
+}
+
+impl PromptTemplateMethods for Database {
+    fn save_prompt_template(&self, template: &PromptTemplate) -> Result<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO prompt_templates
+                (id, name, content, repository_path, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                content = excluded.content,
+                repository_path = excluded.repository_path,
+                updated_at = excluded.updated_at",
+            params![
+                template.id,
+                template.name,
+                template.content,
+                template
+                    .repository_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string()),
+                template.created_at.timestamp(),
+                template.updated_at.timestamp(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn get_prompt_template(&self, id: &str) -> Result<Option<PromptTemplate>> {
+        let conn = self.get_conn()?;
+        Ok(conn
+            .query_row(
+                &format!("SELECT {TEMPLATE_COLUMNS} FROM prompt_templates WHERE id = ?1"),
+                params![id],
+                template_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Templates shared by every project followed by the project's own, each by name.
+    fn list_prompt_templates(&self, repo_path: &Path) -> Result<Vec<PromptTemplate>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {TEMPLATE_COLUMNS} FROM prompt_templates
+             WHERE repository_path IS NULL OR repository_path = ?1
+             ORDER BY repository_path IS NOT NULL, name COLLATE NOCASE"
+        ))?;
+        let rows = stmt.query_map(params![repo_path.to_string_lossy()], template_from_row)?;
+        Ok(rows.collect::<SqlResult<Vec<_>>>()?)
+    }
+
+    fn delete_prompt_template(&self, id: &str) -> Result<bool> {
+        let conn = self.get_conn()?;
+        let deleted = conn.execute("DELETE FROM prompt_templates WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+}
@@ -1,8 +1,46 @@
 use super::connection::Database;
+use super::migrations::{check_integrity, run_migrations, Migration, MigrationStep};
+
+/// Schema versions in order. Schema changes are added here as new migrations; a
+/// migration that has shipped is never edited or reordered.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        up: MigrationStep::Code(create_baseline_schema),
+        down: None,
+    },
+    Migration {
+        version: 2,
+        name: "pending_follow_ups_session_index",
+        up: MigrationStep::Sql(
+            "CREATE INDEX IF NOT EXISTS idx_pending_follow_ups_session ON pending_follow_ups(session_id)",
+        ),
+        down: Some(MigrationStep::Sql(
+            "DROP INDEX IF EXISTS idx_pending_follow_ups_session",
+        )),
+    },
+    Migration {
+        version: 3,
+        name: "sessions_session_type",
+        up: MigrationStep::Sql(
+            "ALTER TABLE sessions ADD COLUMN session_type TEXT NOT NULL DEFAULT 'worktree'",
+        ),
+        down: Some(MigrationStep::Sql(
+            "ALTER TABLE sessions DROP COLUMN session_type",
+        )),
+    },
+];
 
 pub fn initialize_schema(db: &Database) -> anyhow::Result<()> {
-    let conn = db.get_conn()?;
+    let mut conn = db.get_conn()?;
+    check_integrity(&conn)?;
+    run_migrations(&mut conn, MIGRATIONS)
+}
 
+/// The schema as it was before versioned migrations. Every statement tolerates an
+/// existing table or column, so databases of any earlier version end up the same.
+fn create_baseline_schema(conn: &rusqlite::Connection) -> anyhow::Result<()> {
     // Main sessions table - consolidated schema
     conn.execute(
         "CREATE TABLE IF NOT EXISTS sessions (
@@ -71,6 +109,133 @@ pub fn initialize_schema(db: &Database) -> anyhow::Result<()> {
         [],
     )?;
 
+    // Blocked-on relationships between sessions; satisfied once the dependency merged
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_dependencies (
+            session_id TEXT NOT NULL,
+            depends_on_id TEXT NOT NULL,
+            satisfied_at INTEGER,
+            PRIMARY KEY(session_id, depends_on_id)
+        )",
+        [],
+    )?;
+
+    // Seconds a session's agent was busy, per local calendar day
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_time_entries (
+            session_id TEXT NOT NULL,
+            day TEXT NOT NULL,
+            active_seconds INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY(session_id, day)
+        )",
+        [],
+    )?;
+
+    // Review notes and checklist (JSON) per session
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_notes (
+            session_id TEXT PRIMARY KEY,
+            notes TEXT NOT NULL DEFAULT '',
+            checklist TEXT NOT NULL DEFAULT '[]',
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // Reviewer comments on line ranges of a session's diff
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_review_comments (
+            id TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            line_start INTEGER NOT NULL,
+            line_end INTEGER NOT NULL,
+            side TEXT NOT NULL,
+            selected_text TEXT NOT NULL DEFAULT '',
+            comment TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            resolved_at INTEGER
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_review_comments_session ON session_review_comments(session_id, file_path, line_start)",
+        [],
+    )?;
+
+    // Last push of each session branch and the pull request opened for it
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_remote_status (
+            session_id TEXT PRIMARY KEY,
+            remote TEXT NOT NULL,
+            pushed_commit TEXT NOT NULL,
+            pushed_at INTEGER NOT NULL,
+            pull_request_url TEXT
+        )",
+        [],
+    )?;
+
+    // Stored versions of spec content, numbered per spec
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS spec_revisions (
+            session_id TEXT NOT NULL,
+            revision INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            source TEXT NOT NULL,
+            summary TEXT,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY(session_id, revision)
+        )",
+        [],
+    )?;
+
+    // Follow-up messages waiting for a session's agent terminal, delivered in rowid order
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pending_follow_ups (
+            id TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            message TEXT NOT NULL,
+            sent_at INTEGER NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            last_attempt_at INTEGER
+        )",
+        [],
+    )?;
+
+    // Agent processes of a session that exited with an error, in rowid order
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_agent_crashes (
+            session_id TEXT NOT NULL,
+            agent_type TEXT NOT NULL,
+            exit_code INTEGER,
+            crashed_at INTEGER NOT NULL,
+            restart_attempt INTEGER
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_agent_crashes_session ON session_agent_crashes(session_id)",
+        [],
+    )?;
+
+    // Latest token usage and cost an agent run reported, per session and run
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_agent_usage (
+            session_id TEXT NOT NULL,
+            run_id TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_write_tokens INTEGER NOT NULL DEFAULT 0,
+            cost_usd REAL NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY(session_id, run_id)
+        )",
+        [],
+    )?;
+
     conn.execute(
         "CREATE TABLE IF NOT EXISTS app_config (
             id INTEGER PRIMARY KEY CHECK (id = 1),
@@ -87,7 +252,7 @@ pub fn initialize_schema(db: &Database) -> anyhow::Result<()> {
     )?;
 
     // Apply migrations for app_config
-    apply_app_config_migrations(&conn)?;
+    apply_app_config_migrations(conn)?;
 
     conn.execute(
         "INSERT OR IGNORE INTO app_config (
@@ -105,7 +270,7 @@ pub fn initialize_schema(db: &Database) -> anyhow::Result<()> {
     )?;
 
     // Apply migrations for sessions table
-    apply_sessions_migrations(&conn)?;
+    apply_sessions_migrations(conn)?;
 
     // Create project_config table for project-specific settings
     conn.execute(
@@ -122,7 +287,7 @@ pub fn initialize_schema(db: &Database) -> anyhow::Result<()> {
     )?;
 
     // Apply migrations for project_config
-    apply_project_config_migrations(&conn)?;
+    apply_project_config_migrations(conn)?;
 
     // Create agent_binaries table for storing agent binary configurations
     conn.execute(
@@ -159,6 +324,36 @@ pub fn initialize_schema(db: &Database) -> anyhow::Result<()> {
         [],
     )?;
 
+    // Reusable prompts; templates without a repository are shared by every project
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS prompt_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            content TEXT NOT NULL,
+            repository_path TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_prompt_templates_scope_name
+         ON prompt_templates(COALESCE(repository_path, ''), name)",
+        [],
+    )?;
+
+    // Emitted events kept for `/api/events/replay`; AUTOINCREMENT keeps pruned
+    // sequence numbers from being handed out again
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS event_log (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
     Ok(())
 }
 
@@ -245,6 +440,53 @@ fn apply_sessions_migrations(conn: &rusqlite::Connection) -> anyhow::Result<()>
         "ALTER TABLE sessions ADD COLUMN amp_thread_id TEXT",
         [],
     );
+    // Per-session Codex profile override (falls back to project settings)
+    let _ = conn.execute(
+        "ALTER TABLE sessions ADD COLUMN codex_profile TEXT",
+        [],
+    );
+    // Result of the project setup script; agents wait for it to succeed
+    let _ = conn.execute(
+        "ALTER TABLE sessions ADD COLUMN setup_status TEXT",
+        [],
+    );
+    // Extra environment for the session's terminals, layered over project variables (JSON object)
+    let _ = conn.execute(
+        "ALTER TABLE sessions ADD COLUMN environment_variables TEXT",
+        [],
+    );
+    // User-assigned labels (JSON array of strings)
+    let _ = conn.execute(
+        "ALTER TABLE sessions ADD COLUMN labels TEXT",
+        [],
+    );
+    // Model the session's agent runs with, e.g. `opus` or `o3`; the agent default when NULL
+    let _ = conn.execute("ALTER TABLE sessions ADD COLUMN agent_model TEXT", []);
+    // Agent settings the session sets over the project and global ones (JSON object)
+    let _ = conn.execute(
+        "ALTER TABLE sessions ADD COLUMN settings_overrides TEXT",
+        [],
+    );
+    // Per-session opt-out from automatic follow-up nudges
+    let _ = conn.execute(
+        "ALTER TABLE sessions ADD COLUMN auto_nudge_disabled BOOLEAN NOT NULL DEFAULT FALSE",
+        [],
+    );
+    // Context a fresh start receives after the session was handed to another agent
+    let _ = conn.execute("ALTER TABLE sessions ADD COLUMN handoff_prompt TEXT", []);
+    // Ahead/behind counts against the fetched base branch, kept across stats refreshes
+    let _ = conn.execute(
+        "ALTER TABLE git_stats ADD COLUMN base_ahead INTEGER",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE git_stats ADD COLUMN base_behind INTEGER",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE git_stats ADD COLUMN base_checked_at INTEGER",
+        [],
+    );
     Ok(())
 }
 
@@ -296,5 +538,66 @@ fn apply_project_config_migrations(conn: &rusqlite::Connection) -> anyhow::Resul
         "ALTER TABLE project_config ADD COLUMN github_default_branch TEXT",
         [],
     );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN codex_settings TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN agent_defaults TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN container_config TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN dev_environment TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN dependency_cache_config TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN worktree_root TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN stale_session_policy TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN outbound_webhook_config TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN sign_commits INTEGER DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN run_git_hooks INTEGER DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN test_script TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN test_timeout_minutes INTEGER",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN protected_paths TEXT",
+        [],
+    );
+    // Agent settings the project sets over the global ones (JSON object)
+    let _ = conn.execute(
+        "ALTER TABLE project_config ADD COLUMN settings_overrides TEXT",
+        [],
+    );
+    // Names of the env sets the project's agents get (JSON array)
+    let _ = conn.execute("ALTER TABLE project_config ADD COLUMN env_sets TEXT", []);
+    // Named session setup steps and the setup timeout (JSON object)
+    let _ = conn.execute("ALTER TABLE project_config ADD COLUMN setup_scripts TEXT", []);
     Ok(())
 }
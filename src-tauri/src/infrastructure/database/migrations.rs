@@ -0,0 +1,240 @@
+//! Versioned schema migrations. Each migration runs once, inside a transaction together
+//! with the row in `schema_migrations` that records it, so an interrupted upgrade leaves
+//! the database at the previous version instead of half migrated.
+
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::{params, Connection, TransactionBehavior};
+
+pub enum MigrationStep {
+    Sql(&'static str),
+    /// For steps that cannot be plain SQL, like the idempotent baseline
+    Code(fn(&Connection) -> Result<()>),
+}
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub up: MigrationStep,
+    /// Reverses `up`; `None` for migrations that cannot be rolled back
+    pub down: Option<MigrationStep>,
+}
+
+impl MigrationStep {
+    fn run(&self, conn: &Connection) -> Result<()> {
+        match self {
+            MigrationStep::Sql(sql) => conn.execute_batch(sql).map_err(Into::into),
+            MigrationStep::Code(step) => step(conn),
+        }
+    }
+}
+
+fn ensure_migrations_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Applied migrations, oldest first.
+pub fn applied_migrations(conn: &Connection) -> Result<Vec<(u32, String)>> {
+    ensure_migrations_table(conn)?;
+    let mut stmt = conn.prepare("SELECT version, name FROM schema_migrations ORDER BY version")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Fail when SQLite reports damage, so nothing is written on top of it.
+pub fn check_integrity(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA quick_check")?;
+    let problems: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|line| line != "ok")
+        .collect();
+    if !problems.is_empty() {
+        bail!(
+            "Database integrity check failed: {}",
+            problems.into_iter().take(5).collect::<Vec<_>>().join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// Applied migrations must carry the names `migrations` gives them; a mismatch means this
+/// build and the database disagree about what the schema is.
+fn check_history(applied: &[(u32, String)], migrations: &[Migration]) -> Result<()> {
+    for (version, name) in applied {
+        match migrations.iter().find(|m| m.version == *version) {
+            Some(migration) if migration.name == name => {}
+            Some(migration) => bail!(
+                "Database migration {version} is '{name}', but this build calls it '{}'",
+                migration.name
+            ),
+            // Written by a newer build; it only added to the schema, so keep going
+            None => log::warn!(
+                "Database has migration {version} ('{name}') from a newer Schaltwerk version"
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Apply the migrations the database has not seen yet, in version order.
+pub fn run_migrations(conn: &mut Connection, migrations: &[Migration]) -> Result<()> {
+    ensure_migrations_table(conn)?;
+    check_history(&applied_migrations(conn)?, migrations)?;
+
+    for migration in migrations {
+        // IMMEDIATE takes the write lock up front, so two processes opening the same
+        // database do not both apply the migration
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let applied: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM schema_migrations WHERE version = ?1)",
+            params![migration.version],
+            |row| row.get(0),
+        )?;
+        if applied {
+            continue;
+        }
+
+        log::info!(
+            "Applying database migration {} ({})",
+            migration.version,
+            migration.name
+        );
+        migration
+            .up
+            .run(&tx)
+            .with_context(|| format!("Database migration {} failed", migration.version))?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![
+                migration.version,
+                migration.name,
+                chrono::Utc::now().timestamp()
+            ],
+        )?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Undo applied migrations newer than `target`, newest first.
+pub fn rollback_to(conn: &mut Connection, migrations: &[Migration], target: u32) -> Result<()> {
+    let applied = applied_migrations(conn)?;
+    for (version, _) in applied
+        .iter()
+        .rev()
+        .filter(|(version, _)| *version > target)
+    {
+        let migration = migrations
+            .iter()
+            .find(|m| m.version == *version)
+            .ok_or_else(|| anyhow!("Database migration {version} is unknown to this build"))?;
+        let down = migration
+            .down
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database migration {version} cannot be rolled back"))?;
+
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        down.run(&tx)
+            .with_context(|| format!("Rolling back database migration {version} failed"))?;
+        tx.execute(
+            "DELETE FROM schema_migrations WHERE version = ?1",
+            params![version],
+        )?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_notes(conn: &Connection) -> Result<()> {
+        conn.execute("CREATE TABLE IF NOT EXISTS notes (id TEXT PRIMARY KEY)", [])?;
+        Ok(())
+    }
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "baseline",
+            up: MigrationStep::Code(create_notes),
+            down: None,
+        },
+        Migration {
+            version: 2,
+            name: "notes_body",
+            up: MigrationStep::Sql("ALTER TABLE notes ADD COLUMN body TEXT"),
+            down: Some(MigrationStep::Sql("ALTER TABLE notes DROP COLUMN body")),
+        },
+    ];
+
+    fn has_body(conn: &Connection) -> bool {
+        conn.prepare("SELECT body FROM notes").is_ok()
+    }
+
+    #[test]
+    fn migrations_apply_once_and_roll_back() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        check_integrity(&conn).unwrap();
+
+        run_migrations(&mut conn, MIGRATIONS).unwrap();
+        run_migrations(&mut conn, MIGRATIONS).unwrap();
+        let applied = applied_migrations(&conn).unwrap();
+        assert_eq!(
+            applied,
+            vec![(1, "baseline".to_string()), (2, "notes_body".to_string())]
+        );
+        assert!(has_body(&conn));
+
+        rollback_to(&mut conn, MIGRATIONS, 1).unwrap();
+        assert!(!has_body(&conn));
+        assert_eq!(applied_migrations(&conn).unwrap().len(), 1);
+        assert!(rollback_to(&mut conn, MIGRATIONS, 0).is_err());
+
+        run_migrations(&mut conn, MIGRATIONS).unwrap();
+        assert!(has_body(&conn));
+    }
+
+    #[test]
+    fn failed_migration_leaves_the_previous_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let broken = [
+            Migration {
+                version: 1,
+                name: "baseline",
+                up: MigrationStep::Code(create_notes),
+                down: None,
+            },
+            Migration {
+                version: 2,
+                name: "broken",
+                up: MigrationStep::Sql(
+                    "ALTER TABLE notes ADD COLUMN body TEXT; ALTER TABLE missing ADD COLUMN x TEXT;",
+                ),
+                down: None,
+            },
+        ];
+
+        assert!(run_migrations(&mut conn, &broken).is_err());
+        assert_eq!(applied_migrations(&conn).unwrap().len(), 1);
+        assert!(!has_body(&conn));
+
+        let renamed = [Migration {
+            version: 1,
+            name: "renamed",
+            up: MigrationStep::Code(create_notes),
+            down: None,
+        }];
+        assert!(run_migrations(&mut conn, &renamed).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 pub mod connection;
 pub mod db_app_config;
 pub mod db_archived_specs;
+pub mod db_backup;
+pub mod db_event_log;
 pub mod db_project_config;
+pub mod db_prompt_templates;
 pub mod db_schema;
+pub mod migrations;
 
 pub use connection::Database;
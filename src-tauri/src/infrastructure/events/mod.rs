@@ -1,23 +1,94 @@
+pub mod outbound;
+
+use crate::infrastructure::database::db_event_log::{EventLogMethods, LoggedEvent};
+use crate::infrastructure::database::Database;
+use crate::infrastructure::logging::current_log_context;
 use serde::{Deserialize, Serialize};
+use std::sync::{LazyLock, OnceLock};
 use tauri::Emitter;
+use tokio::sync::broadcast;
+
+/// Events buffered per `/api/events` subscriber before it starts missing some
+const EVENT_STREAM_CAPACITY: usize = 256;
+
+/// Logged events kept for replay; older ones are pruned
+const EVENT_LOG_RETENTION: u64 = 10_000;
+
+/// Logged events between two prunes of the log
+const EVENT_LOG_PRUNE_INTERVAL: u64 = 500;
+
+static EVENT_STREAM: LazyLock<broadcast::Sender<StreamedEvent>> =
+    LazyLock::new(|| broadcast::channel(EVENT_STREAM_CAPACITY).0);
+
+static EVENT_LOG: OnceLock<Database> = OnceLock::new();
+
+/// An event as streamed to external clients over the `/api/events` WebSocket.
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamedEvent {
+    /// Position in the event log, for events that are kept for replay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    pub event: &'static str,
+    pub payload: serde_json::Value,
+}
+
+/// Ties an event to the operation that emitted it. Object payloads carry it as `_meta`,
+/// so the events of one session creation can be matched with its log lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EventMetadata {
+    pub correlation_id: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl EventMetadata {
+    /// Metadata of the operation the caller runs in, if it runs in one.
+    pub fn current() -> Option<Self> {
+        current_log_context()
+            .filter(|context| context.correlation_id.is_some())
+            .map(|context| Self {
+                correlation_id: context.correlation_id,
+                session_id: context.session_id,
+            })
+    }
+}
+
+/// Logged events after a client's last seen sequence number
+#[derive(Debug, Clone, Serialize)]
+pub struct EventReplay {
+    pub events: Vec<LoggedEvent>,
+    /// Newest sequence number in the log; 0 while nothing was logged
+    pub latest_seq: u64,
+    /// More events follow the returned page
+    pub has_more: bool,
+    /// Events after `since_seq` were pruned already; the client has to refetch its state
+    pub missed_events: bool,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SchaltEvent {
     SessionsRefreshed,
     SessionAdded,
     SessionRemoved,
+    SessionRenamed,
     ArchiveUpdated,
     SessionCancelling,
+    SessionUnblocked,
     CancelError,
     TerminalCreated,
 
     SessionActivity,
     SessionGitStats,
     TerminalAttention,
+    SessionIdle,
+    SessionAgentState,
     TerminalClosed,
+    TerminalCleanupReport,
     TerminalForceScroll,
     TerminalAgentStarted,
+    TerminalStats,
+    TerminalPasteConfirmation,
     AgentCrashed,
+    AgentVersionWarning,
     ProjectReady,
     OpenDirectory,
     OpenHome,
@@ -27,8 +98,17 @@ pub enum SchaltEvent {
     GitOperationStarted,
     GitOperationCompleted,
     GitOperationFailed,
+    MergeQueueUpdated,
     ProjectFilesUpdated,
     GitHubStatusChanged,
+    McpStatusChanged,
+    SettingsChanged,
+    AgentProgressUpdated,
+    RunFinished,
+    SetupStarted,
+    SetupFinished,
+    SetupError,
+    LogLine,
 }
 
 impl SchaltEvent {
@@ -37,18 +117,26 @@ impl SchaltEvent {
             SchaltEvent::SessionsRefreshed => "schaltwerk:sessions-refreshed",
             SchaltEvent::SessionAdded => "schaltwerk:session-added",
             SchaltEvent::SessionRemoved => "schaltwerk:session-removed",
+            SchaltEvent::SessionRenamed => "schaltwerk:session-renamed",
             SchaltEvent::ArchiveUpdated => "schaltwerk:archive-updated",
             SchaltEvent::SessionCancelling => "schaltwerk:session-cancelling",
+            SchaltEvent::SessionUnblocked => "schaltwerk:session-unblocked",
             SchaltEvent::CancelError => "schaltwerk:cancel-error",
             SchaltEvent::TerminalCreated => "schaltwerk:terminal-created",
 
             SchaltEvent::SessionActivity => "schaltwerk:session-activity",
             SchaltEvent::SessionGitStats => "schaltwerk:session-git-stats",
             SchaltEvent::TerminalAttention => "schaltwerk:terminal-attention",
+            SchaltEvent::SessionIdle => "schaltwerk:session-idle",
+            SchaltEvent::SessionAgentState => "schaltwerk:session-agent-state",
             SchaltEvent::TerminalClosed => "schaltwerk:terminal-closed",
+            SchaltEvent::TerminalCleanupReport => "schaltwerk:terminal-cleanup-report",
             SchaltEvent::TerminalForceScroll => "schaltwerk:terminal-force-scroll",
             SchaltEvent::TerminalAgentStarted => "schaltwerk:terminal-agent-started",
+            SchaltEvent::TerminalStats => "schaltwerk:terminal-stats",
+            SchaltEvent::TerminalPasteConfirmation => "schaltwerk:terminal-paste-confirmation",
             SchaltEvent::AgentCrashed => "schaltwerk:agent-crashed",
+            SchaltEvent::AgentVersionWarning => "schaltwerk:agent-version-warning",
             SchaltEvent::ProjectReady => "schaltwerk:project-ready",
             SchaltEvent::OpenDirectory => "schaltwerk:open-directory",
             SchaltEvent::OpenHome => "schaltwerk:open-home",
@@ -58,23 +146,249 @@ impl SchaltEvent {
             SchaltEvent::GitOperationStarted => "schaltwerk:git-operation-started",
             SchaltEvent::GitOperationCompleted => "schaltwerk:git-operation-completed",
             SchaltEvent::GitOperationFailed => "schaltwerk:git-operation-failed",
+            SchaltEvent::MergeQueueUpdated => "schaltwerk:merge-queue-updated",
             SchaltEvent::ProjectFilesUpdated => "schaltwerk:project-files-updated",
             SchaltEvent::GitHubStatusChanged => "schaltwerk:github-status-changed",
+            SchaltEvent::McpStatusChanged => "schaltwerk:mcp-status-changed",
+            SchaltEvent::SettingsChanged => "schaltwerk:settings-changed",
+            SchaltEvent::AgentProgressUpdated => "schaltwerk:agent-progress-updated",
+            SchaltEvent::RunFinished => "schaltwerk:run-finished",
+            SchaltEvent::SetupStarted => "schaltwerk:setup-started",
+            SchaltEvent::SetupFinished => "schaltwerk:setup-finished",
+            SchaltEvent::SetupError => "schaltwerk:setup-error",
+            SchaltEvent::LogLine => "schaltwerk:log-line",
         }
     }
+
+    /// Whether external clients get this event; terminal rendering and window
+    /// interactions only concern the app's own UI.
+    pub fn is_streamed(&self) -> bool {
+        !matches!(
+            self,
+            SchaltEvent::TerminalForceScroll
+                | SchaltEvent::TerminalStats
+                | SchaltEvent::TerminalPasteConfirmation
+                | SchaltEvent::OpenDirectory
+                | SchaltEvent::OpenHome
+                | SchaltEvent::Selection
+                | SchaltEvent::LogLine
+        )
+    }
+
+    /// Whether the event is stored for `/api/events/replay`: changes of session state an
+    /// offline client has to catch up on, not activity and progress updates.
+    pub fn is_logged(&self) -> bool {
+        matches!(
+            self,
+            SchaltEvent::SessionAdded
+                | SchaltEvent::SessionRemoved
+                | SchaltEvent::SessionRenamed
+                | SchaltEvent::ArchiveUpdated
+                | SchaltEvent::SessionCancelling
+                | SchaltEvent::SessionUnblocked
+                | SchaltEvent::CancelError
+                | SchaltEvent::AgentCrashed
+                | SchaltEvent::FollowUpMessage
+                | SchaltEvent::GitOperationCompleted
+                | SchaltEvent::GitOperationFailed
+                | SchaltEvent::MergeQueueUpdated
+                | SchaltEvent::RunFinished
+                | SchaltEvent::SetupFinished
+                | SchaltEvent::SetupError
+        )
+    }
+}
+
+/// Store session state events in `db` from now on, so clients can replay them.
+pub fn configure_event_log(db: Database) {
+    let _ = EVENT_LOG.set(db);
 }
 
+/// Emit `event` to the webview and external clients, tagged with the metadata of the
+/// operation it is emitted from.
 pub fn emit_event<T: Serialize + Clone>(
     app: &tauri::AppHandle,
     event: SchaltEvent,
     payload: &T,
 ) -> Result<(), tauri::Error> {
-    app.emit(event.as_str(), payload)
+    emit_event_with_metadata(app, event, payload, EventMetadata::current())
+}
+
+/// Emit `event` with explicit `metadata`, e.g. from a task that outlived its operation.
+pub fn emit_event_with_metadata<T: Serialize + Clone>(
+    app: &tauri::AppHandle,
+    event: SchaltEvent,
+    payload: &T,
+    metadata: Option<EventMetadata>,
+) -> Result<(), tauri::Error> {
+    match metadata.and_then(|metadata| attach_metadata(payload, &metadata)) {
+        Some(tagged) => {
+            log::debug!("Emitting {}", event.as_str());
+            publish_to_stream(&event, &tagged);
+            app.emit(event.as_str(), tagged)
+        }
+        None => {
+            publish_to_stream(&event, payload);
+            app.emit(event.as_str(), payload)
+        }
+    }
+}
+
+/// `payload` with `metadata` under `_meta`; `None` for payloads that are not objects.
+fn attach_metadata<T: Serialize>(
+    payload: &T,
+    metadata: &EventMetadata,
+) -> Option<serde_json::Value> {
+    let serde_json::Value::Object(mut fields) = serde_json::to_value(payload).ok()? else {
+        return None;
+    };
+    fields.insert("_meta".to_string(), serde_json::to_value(metadata).ok()?);
+    Some(serde_json::Value::Object(fields))
+}
+
+/// Events emitted from now on, for the `/api/events` WebSocket.
+pub fn subscribe_events() -> broadcast::Receiver<StreamedEvent> {
+    EVENT_STREAM.subscribe()
+}
+
+/// Logged events after `since_seq`, oldest first and at most `limit` of them.
+pub fn replay_events(since_seq: u64, limit: usize) -> anyhow::Result<EventReplay> {
+    let db = EVENT_LOG
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("The event log is not available"))?;
+    replay_from(db, since_seq, limit)
+}
+
+fn replay_from(db: &Database, since_seq: u64, limit: usize) -> anyhow::Result<EventReplay> {
+    let Some((oldest_seq, latest_seq)) = db.event_seq_range()? else {
+        return Ok(EventReplay {
+            events: Vec::new(),
+            latest_seq: 0,
+            has_more: false,
+            missed_events: false,
+        });
+    };
+    let events = db.list_events_since(since_seq, limit)?;
+    Ok(EventReplay {
+        has_more: events.last().is_some_and(|last| last.seq < latest_seq),
+        missed_events: since_seq + 1 < oldest_seq,
+        events,
+        latest_seq,
+    })
+}
+
+fn record_event(db: &Database, event: &SchaltEvent, payload: &serde_json::Value) -> Option<u64> {
+    match db.append_event(event.as_str(), payload) {
+        Ok(seq) => {
+            if seq % EVENT_LOG_PRUNE_INTERVAL == 0 {
+                if let Err(e) = db.prune_event_log(EVENT_LOG_RETENTION) {
+                    log::warn!("Failed to prune the event log: {e}");
+                }
+            }
+            Some(seq)
+        }
+        Err(e) => {
+            log::warn!("Failed to log {}: {e}", event.as_str());
+            None
+        }
+    }
+}
+
+fn publish_to_stream<T: Serialize>(event: &SchaltEvent, payload: &T) {
+    let streamed = EVENT_STREAM.receiver_count() > 0 && event.is_streamed();
+    let log = EVENT_LOG.get().filter(|_| event.is_logged());
+    if !streamed && log.is_none() {
+        return;
+    }
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log::warn!(
+                "Failed to serialize {} for the event stream: {e}",
+                event.as_str()
+            );
+            return;
+        }
+    };
+
+    let seq = log.and_then(|db| record_event(db, event, &payload));
+    if streamed {
+        let _ = EVENT_STREAM.send(StreamedEvent {
+            seq,
+            event: event.as_str(),
+            payload,
+        });
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SchaltEvent;
+    use super::{
+        attach_metadata, publish_to_stream, record_event, replay_from, subscribe_events,
+        EventMetadata, SchaltEvent,
+    };
+    use crate::infrastructure::database::db_event_log::EventLogMethods;
+    use crate::infrastructure::database::Database;
+
+    #[test]
+    fn streamed_events_reach_subscribers() {
+        let mut receiver = subscribe_events();
+
+        publish_to_stream(&SchaltEvent::TerminalForceScroll, &"term-1");
+        publish_to_stream(
+            &SchaltEvent::FollowUpMessage,
+            &serde_json::json!({ "session_name": "alpha" }),
+        );
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.event, "schaltwerk:follow-up-message");
+        assert_eq!(event.payload["session_name"], "alpha");
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn metadata_is_attached_to_object_payloads() {
+        let metadata = EventMetadata {
+            correlation_id: Some("abc".to_string()),
+            session_id: Some("alpha".to_string()),
+        };
+        let tagged =
+            attach_metadata(&serde_json::json!({ "session_name": "alpha" }), &metadata).unwrap();
+        assert_eq!(tagged["session_name"], "alpha");
+        assert_eq!(tagged["_meta"]["correlation_id"], "abc");
+        assert!(attach_metadata(&"term-1", &metadata).is_none());
+        assert!(EventMetadata::current().is_none());
+    }
+
+    #[test]
+    fn logged_events_replay_in_sequence() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::new(Some(temp_dir.path().join("events.db"))).unwrap();
+        assert_eq!(replay_from(&db, 0, 10).unwrap().latest_seq, 0);
+
+        let mut seqs = Vec::new();
+        for name in ["alpha", "beta", "gamma"] {
+            let payload = serde_json::json!({ "session_name": name });
+            seqs.push(record_event(&db, &SchaltEvent::SessionAdded, &payload).unwrap());
+        }
+        assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let replay = replay_from(&db, seqs[0], 1).unwrap();
+        assert_eq!(replay.events.len(), 1);
+        assert_eq!(replay.events[0].seq, seqs[1]);
+        assert_eq!(replay.events[0].event, "schaltwerk:session-added");
+        assert_eq!(replay.events[0].payload["session_name"], "beta");
+        assert_eq!(replay.latest_seq, seqs[2]);
+        assert!(replay.has_more);
+        assert!(!replay.missed_events);
+
+        db.prune_event_log(1).unwrap();
+        let replay = replay_from(&db, 0, 10).unwrap();
+        assert_eq!(replay.events.len(), 1);
+        assert!(replay.missed_events);
+        assert!(!replay.has_more);
+        assert!(!SchaltEvent::SessionGitStats.is_logged());
+    }
 
     #[test]
     fn test_event_names_for_new_variants() {
@@ -106,5 +420,61 @@ mod tests {
             SchaltEvent::GitHubStatusChanged.as_str(),
             "schaltwerk:github-status-changed"
         );
+        assert_eq!(
+            SchaltEvent::McpStatusChanged.as_str(),
+            "schaltwerk:mcp-status-changed"
+        );
+        assert_eq!(
+            SchaltEvent::AgentProgressUpdated.as_str(),
+            "schaltwerk:agent-progress-updated"
+        );
+        assert_eq!(SchaltEvent::RunFinished.as_str(), "schaltwerk:run-finished");
+        assert_eq!(
+            SchaltEvent::SetupStarted.as_str(),
+            "schaltwerk:setup-started"
+        );
+        assert_eq!(
+            SchaltEvent::SetupFinished.as_str(),
+            "schaltwerk:setup-finished"
+        );
+        assert_eq!(SchaltEvent::SetupError.as_str(), "schaltwerk:setup-error");
+        assert_eq!(SchaltEvent::LogLine.as_str(), "schaltwerk:log-line");
+        assert_eq!(
+            SchaltEvent::TerminalStats.as_str(),
+            "schaltwerk:terminal-stats"
+        );
+        assert_eq!(
+            SchaltEvent::TerminalPasteConfirmation.as_str(),
+            "schaltwerk:terminal-paste-confirmation"
+        );
+        assert_eq!(SchaltEvent::SessionIdle.as_str(), "schaltwerk:session-idle");
+        assert_eq!(
+            SchaltEvent::SessionAgentState.as_str(),
+            "schaltwerk:session-agent-state"
+        );
+        assert_eq!(
+            SchaltEvent::SessionUnblocked.as_str(),
+            "schaltwerk:session-unblocked"
+        );
+        assert_eq!(
+            SchaltEvent::TerminalCleanupReport.as_str(),
+            "schaltwerk:terminal-cleanup-report"
+        );
+        assert_eq!(
+            SchaltEvent::SessionRenamed.as_str(),
+            "schaltwerk:session-renamed"
+        );
+        assert_eq!(
+            SchaltEvent::MergeQueueUpdated.as_str(),
+            "schaltwerk:merge-queue-updated"
+        );
+        assert_eq!(
+            SchaltEvent::AgentVersionWarning.as_str(),
+            "schaltwerk:agent-version-warning"
+        );
+        assert_eq!(
+            SchaltEvent::SettingsChanged.as_str(),
+            "schaltwerk:settings-changed"
+        );
     }
 }
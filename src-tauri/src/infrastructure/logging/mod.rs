@@ -1,28 +1,230 @@
-use chrono::Local;
+mod trace_export;
+pub mod viewer;
+
+use crate::infrastructure::secrets::mask_secrets;
+use chrono::{Local, SecondsFormat};
 use env_logger::Builder;
 use log::LevelFilter;
+use std::collections::HashMap;
 use std::env;
 use std::fs::{self, File, OpenOptions};
+use std::future::Future;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
 use std::time::{Duration, SystemTime};
 
 static LOG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
-static LOG_FILE_WRITER: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+static LOG_FILE_WRITER: Mutex<Option<RotatingLogWriter>> = Mutex::new(None);
 static LOGGER_INITIALIZED: Mutex<bool> = Mutex::new(false);
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+// Set when SCHALTWERK_LOG_FORMAT chose the format; the setting is ignored then
+static FORMAT_FROM_ENV: AtomicBool = AtomicBool::new(false);
+// Correlation ids of session creations, handed on to the agent start that follows
+static PENDING_SESSION_CORRELATIONS: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+tokio::task_local! {
+    static LOG_CONTEXT: LogContext;
+}
 
 const DEFAULT_RETENTION_HOURS: u64 = 72;
 const SECONDS_PER_HOUR: u64 = 3_600;
+const DEFAULT_MAX_FILE_MB: u64 = 20;
+const DEFAULT_MAX_TOTAL_MB: u64 = 200;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `[timestamp LEVEL target] message`
+    Text,
+    /// One JSON object per line, for log tooling
+    Json,
+}
 
 #[derive(Debug)]
 struct LoggingConfig {
     file_logging_enabled: bool,
     retention: Duration,
+    /// Rotate to a new file past this size; 0 never rotates
+    max_file_bytes: u64,
+    /// Delete the oldest files while the directory is larger; 0 keeps everything
+    max_total_bytes: u64,
     log_dir: PathBuf,
+    format: Option<LogFormat>,
+    /// Chrome trace of the `tracing` spans, from `SCHALTWERK_TRACE_FILE`
+    trace_file: Option<PathBuf>,
     deferred_warnings: Vec<String>,
 }
 
+/// Identifies the session and the operation log lines belong to, so the lines of
+/// one session start or merge can be picked out when several run at once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogContext {
+    pub session_id: Option<String>,
+    pub correlation_id: Option<String>,
+}
+
+impl LogContext {
+    /// Context for a new operation on `session_id`, with a fresh correlation id.
+    pub fn for_session(session_id: &str) -> Self {
+        Self {
+            session_id: Some(session_id.to_string()),
+            correlation_id: Some(uuid::Uuid::new_v4().simple().to_string()),
+        }
+    }
+
+    /// Context for creating `session_id`. Its correlation id is kept for the first
+    /// agent start of the session, so creation and start share one id.
+    pub fn for_new_session(session_id: &str) -> Self {
+        let context = Self::for_session(session_id);
+        if let (Ok(mut pending), Some(id)) = (
+            PENDING_SESSION_CORRELATIONS.lock(),
+            context.correlation_id.as_ref(),
+        ) {
+            pending.insert(session_id.to_string(), id.clone());
+        }
+        context
+    }
+
+    /// Context for an operation that continues the creation of `session_id`, when
+    /// one is pending, and a new operation otherwise.
+    pub fn continuing_session(session_id: &str) -> Self {
+        let pending = PENDING_SESSION_CORRELATIONS
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(session_id));
+        match pending {
+            Some(correlation_id) => Self {
+                session_id: Some(session_id.to_string()),
+                correlation_id: Some(correlation_id),
+            },
+            None => Self::for_session(session_id),
+        }
+    }
+}
+
+/// Run `future` with `context` attached to every line it logs. Tasks it spawns do not
+/// inherit the context.
+pub async fn with_log_context<F: Future>(context: LogContext, future: F) -> F::Output {
+    LOG_CONTEXT.scope(context, future).await
+}
+
+/// The context of the operation the caller runs in, if any.
+pub fn current_log_context() -> Option<LogContext> {
+    LOG_CONTEXT.try_with(LogContext::clone).ok()
+}
+
+/// Run `future` in the caller's context, for tasks spawned by an operation that
+/// belong to it.
+pub fn in_current_log_context<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let context = current_log_context();
+    async move {
+        match context {
+            Some(context) => LOG_CONTEXT.scope(context, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// Switch between text and JSON log lines, unless `SCHALTWERK_LOG_FORMAT` chose one.
+pub fn set_json_logs(enabled: bool) {
+    if !FORMAT_FROM_ENV.load(Ordering::Relaxed) {
+        JSON_FORMAT.store(enabled, Ordering::Relaxed);
+    }
+}
+
+pub fn log_format() -> LogFormat {
+    if JSON_FORMAT.load(Ordering::Relaxed) {
+        LogFormat::Json
+    } else {
+        LogFormat::Text
+    }
+}
+
+fn format_json_line(
+    timestamp: &str,
+    level: log::Level,
+    target: &str,
+    message: &str,
+    context: Option<&LogContext>,
+) -> String {
+    let context = context.cloned().unwrap_or_default();
+    serde_json::json!({
+        "timestamp": timestamp,
+        "level": level.as_str(),
+        "target": target,
+        "message": message,
+        "session_id": context.session_id,
+        "correlation_id": context.correlation_id,
+    })
+    .to_string()
+}
+
+/// The log file writer; starts a new file once the current one reaches its size cap
+/// and then trims the directory to its total cap.
+struct RotatingLogWriter {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    log_dir: PathBuf,
+    written: u64,
+    max_file_bytes: u64,
+    max_total_bytes: u64,
+}
+
+impl RotatingLogWriter {
+    fn open(log_dir: &Path, max_file_bytes: u64, max_total_bytes: u64) -> std::io::Result<Self> {
+        let path = new_log_file_path(log_dir);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path,
+            log_dir: log_dir.to_path_buf(),
+            written,
+            max_file_bytes,
+            max_total_bytes,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        writeln!(self.writer, "{line}")?;
+        self.writer.flush()?;
+        self.written += line.len() as u64 + 1;
+        if self.max_file_bytes > 0 && self.written >= self.max_file_bytes {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let next = Self::open(&self.log_dir, self.max_file_bytes, self.max_total_bytes)?;
+        *self = next;
+        if let Ok(mut guard) = LOG_PATH.lock() {
+            *guard = Some(self.path.clone());
+        }
+        // Logging from here would re-enter the writer lock
+        for warning in enforce_total_size(&self.log_dir, self.max_total_bytes, Some(&self.path)) {
+            eprintln!("{warning}");
+        }
+        Ok(())
+    }
+}
+
+/// `schaltwerk-<timestamp>.log`, with a counter when a file of that name exists already
+/// (a rotation within the same second).
+fn new_log_file_path(log_dir: &Path) -> PathBuf {
+    let stem = format!("schaltwerk-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let mut path = log_dir.join(format!("{stem}.log"));
+    let mut counter = 1;
+    while path.exists() {
+        path = log_dir.join(format!("{stem}-{counter}.log"));
+        counter += 1;
+    }
+    path
+}
+
 /// Get the application's log directory
 pub fn get_log_dir() -> PathBuf {
     dirs::data_local_dir()
@@ -31,6 +233,33 @@ pub fn get_log_dir() -> PathBuf {
         .join("logs")
 }
 
+/// The file log lines are being written to, when file logging is on
+pub fn current_log_file() -> Option<PathBuf> {
+    LOG_FILE_WRITER
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|writer| writer.path.clone()))
+}
+
+/// The last `count` lines of the current log file. Never blocks on the logger's locks,
+/// so it is safe to call from a panic hook.
+pub fn recent_log_lines(count: usize) -> Vec<String> {
+    let path = LOG_FILE_WRITER
+        .try_lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|writer| writer.path.clone()))
+        .or_else(|| LOG_PATH.try_lock().ok().and_then(|guard| guard.clone()));
+    let Some(contents) = path.and_then(|path| fs::read(path).ok()) else {
+        return Vec::new();
+    };
+    let contents = String::from_utf8_lossy(&contents);
+    let lines: Vec<&str> = contents.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
 /// Get the current log file path
 pub fn get_log_path() -> PathBuf {
     if let Ok(guard) = LOG_PATH.lock() {
@@ -71,6 +300,11 @@ pub fn init_logging() {
     let mut config = resolve_logging_config();
     let mut log_path: Option<PathBuf> = None;
 
+    if let Some(format) = config.format {
+        JSON_FORMAT.store(format == LogFormat::Json, Ordering::Relaxed);
+        FORMAT_FROM_ENV.store(true, Ordering::Relaxed);
+    }
+
     if config.file_logging_enabled {
         if let Err(e) = fs::create_dir_all(&config.log_dir) {
             config.deferred_warnings.push(format!(
@@ -80,19 +314,16 @@ pub fn init_logging() {
         } else {
             let cleanup_warnings = cleanup_old_logs(&config.log_dir, config.retention);
             config.deferred_warnings.extend(cleanup_warnings);
-
-            let candidate = config.log_dir.join(format!(
-                "schaltwerk-{}.log",
-                Local::now().format("%Y%m%d-%H%M%S")
-            ));
-
-            match OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&candidate)
-            {
-                Ok(file) => {
-                    let writer = BufWriter::new(file);
+            let size_warnings = enforce_total_size(&config.log_dir, config.max_total_bytes, None);
+            config.deferred_warnings.extend(size_warnings);
+
+            match RotatingLogWriter::open(
+                &config.log_dir,
+                config.max_file_bytes,
+                config.max_total_bytes,
+            ) {
+                Ok(writer) => {
+                    let candidate = writer.path.clone();
                     if let Ok(mut guard) = LOG_FILE_WRITER.lock() {
                         *guard = Some(writer);
                     }
@@ -103,8 +334,8 @@ pub fn init_logging() {
                 }
                 Err(e) => {
                     config.deferred_warnings.push(format!(
-                        "Failed to open log file {}: {e}. Continuing with console logging only.",
-                        candidate.display()
+                        "Failed to open a log file in {}: {e}. Continuing with console logging only.",
+                        config.log_dir.display()
                     ));
                 }
             }
@@ -136,36 +367,67 @@ pub fn init_logging() {
 
     // Custom format with timestamps and module info
     builder.format(move |buf, record| {
-        let level_str = match record.level() {
-            log::Level::Error => "ERROR",
-            log::Level::Warn => "WARN ",
-            log::Level::Info => "INFO ",
-            log::Level::Debug => "DEBUG",
-            log::Level::Trace => "TRACE",
+        let message = record.args().to_string();
+        let message = mask_secrets(&message);
+        let context = current_log_context();
+
+        let log_line = match log_format() {
+            LogFormat::Json => format_json_line(
+                &Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+                record.level(),
+                record.target(),
+                &message,
+                context.as_ref(),
+            ),
+            LogFormat::Text => {
+                let level_str = match record.level() {
+                    log::Level::Error => "ERROR",
+                    log::Level::Warn => "WARN ",
+                    log::Level::Info => "INFO ",
+                    log::Level::Debug => "DEBUG",
+                    log::Level::Trace => "TRACE",
+                };
+                let correlation = context
+                    .as_ref()
+                    .and_then(|context| context.correlation_id.as_deref())
+                    .map(|id| format!(" [correlation_id={id}]"))
+                    .unwrap_or_default();
+                format!(
+                    "[{} {} {}] {}{}",
+                    Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                    level_str,
+                    record.target(),
+                    message,
+                    correlation
+                )
+            }
         };
 
-        let log_line = format!(
-            "[{} {} {}] {}",
-            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-            level_str,
-            record.target(),
-            record.args()
-        );
-
         // Write to the buffer (stderr via env_logger)
         writeln!(buf, "{log_line}")?;
         // Force flush to ensure immediate output
         buf.flush()?;
 
-        // Also write to buffered file writer (with error handling)
+        // Also write to the rotating file writer (with error handling)
         if let Ok(mut guard) = LOG_FILE_WRITER.lock() {
             if let Some(ref mut writer) = *guard {
-                let _ = writeln!(writer, "{log_line}");
-                // Only flush periodically for better performance
-                let _ = writer.flush();
+                let _ = writer.write_line(&log_line);
             }
         }
 
+        viewer::publish_log_line(|| {
+            let context = context.unwrap_or_default();
+            viewer::LogEntry {
+                line: 0,
+                timestamp: Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                level: record.level().as_str().to_string(),
+                target: record.target().to_string(),
+                message: message.to_string(),
+                session_id: context.session_id,
+                correlation_id: context.correlation_id,
+            }
+        });
+
         Ok(())
     });
 
@@ -194,6 +456,10 @@ pub fn init_logging() {
     log::info!("Process ID: {}", std::process::id());
     log::info!("========================================");
 
+    if let Some(path) = config.trace_file.take() {
+        start_trace_export(&path, &mut config.deferred_warnings);
+    }
+
     // Print to console so user knows where logs are (skip in tests to avoid noisy outputs)
     if !cfg!(test) {
         if let Some(path) = log_path {
@@ -209,6 +475,29 @@ pub fn init_logging() {
     }
 }
 
+/// Record `tracing` spans into a Chrome trace at `path` for the rest of the run.
+fn start_trace_export(path: &Path, warnings: &mut Vec<String>) {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let layer = match trace_export::ChromeTraceLayer::create(path) {
+        Ok(layer) => layer,
+        Err(e) => {
+            warnings.push(format!(
+                "Failed to create trace file {}: {e}",
+                path.display()
+            ));
+            return;
+        }
+    };
+    match tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer)) {
+        Ok(()) => log::info!("Chrome trace: {}", path.display()),
+        Err(e) => warnings.push(format!("Failed to start trace export: {e}")),
+    }
+}
+
 fn resolve_logging_config() -> LoggingConfig {
     let mut deferred_warnings = Vec::new();
 
@@ -227,6 +516,17 @@ fn resolve_logging_config() -> LoggingConfig {
         Err(_) => Duration::from_secs(DEFAULT_RETENTION_HOURS * SECONDS_PER_HOUR),
     };
 
+    let max_file_bytes = size_limit_from_env(
+        "SCHALTWERK_LOG_MAX_FILE_MB",
+        DEFAULT_MAX_FILE_MB,
+        &mut deferred_warnings,
+    );
+    let max_total_bytes = size_limit_from_env(
+        "SCHALTWERK_LOG_MAX_TOTAL_MB",
+        DEFAULT_MAX_TOTAL_MB,
+        &mut deferred_warnings,
+    );
+
     let mut file_logging_enabled = cfg!(debug_assertions);
     if let Ok(value) = env::var("SCHALTWERK_ENABLE_LOGS") {
         match parse_bool(&value) {
@@ -237,14 +537,48 @@ fn resolve_logging_config() -> LoggingConfig {
         }
     }
 
+    let format = match env::var("SCHALTWERK_LOG_FORMAT") {
+        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(LogFormat::Json),
+            "text" => Some(LogFormat::Text),
+            _ => {
+                deferred_warnings.push(format!(
+                    "Invalid SCHALTWERK_LOG_FORMAT value '{value}'. Expected 'json' or 'text'. Using the settings value."
+                ));
+                None
+            }
+        },
+        Err(_) => None,
+    };
+
     LoggingConfig {
         file_logging_enabled,
         retention,
+        max_file_bytes,
+        max_total_bytes,
         log_dir,
+        format,
+        trace_file: env::var("SCHALTWERK_TRACE_FILE")
+            .ok()
+            .and_then(|value| trace_export::trace_path(&value, &get_log_dir())),
         deferred_warnings,
     }
 }
 
+/// A size in megabytes from `var`, in bytes
+fn size_limit_from_env(var: &str, default_mb: u64, warnings: &mut Vec<String>) -> u64 {
+    let megabytes = match env::var(var) {
+        Ok(value) => value.trim().parse::<u64>().unwrap_or_else(|_| {
+            warnings.push(format!(
+                "Invalid {var} value '{value}'. Using default {default_mb} MB."
+            ));
+            default_mb
+        }),
+        Err(_) => default_mb,
+    };
+    megabytes.saturating_mul(BYTES_PER_MB)
+}
+
 fn parse_bool(value: &str) -> Option<bool> {
     match value.trim().to_ascii_lowercase().as_str() {
         "1" | "true" | "yes" | "on" => Some(true),
@@ -271,10 +605,7 @@ fn cleanup_old_logs(log_dir: &Path, retention: Duration) -> Vec<String> {
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-        if path.extension().and_then(|ext| ext.to_str()).unwrap_or("") != "log" {
+        if !is_log_file(&path) {
             continue;
         }
 
@@ -298,6 +629,57 @@ fn cleanup_old_logs(log_dir: &Path, retention: Duration) -> Vec<String> {
     warnings
 }
 
+fn is_log_file(path: &Path) -> bool {
+    path.is_file() && path.extension().and_then(|ext| ext.to_str()).unwrap_or("") == "log"
+}
+
+/// Delete the oldest log files until the directory holds at most `max_total_bytes`.
+/// `keep` (the file being written) is never deleted, even when it alone is larger.
+fn enforce_total_size(log_dir: &Path, max_total_bytes: u64, keep: Option<&Path>) -> Vec<String> {
+    if max_total_bytes == 0 {
+        return Vec::new();
+    }
+
+    let mut warnings = Vec::new();
+    let entries = match fs::read_dir(log_dir) {
+        Ok(entries) => entries,
+        Err(_) => return warnings,
+    };
+
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_log_file(path))
+        .filter_map(|path| {
+            let meta = fs::metadata(&path).ok()?;
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((modified, meta.len(), path))
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    // Oldest first; file names carry the start time, which breaks mtime ties
+    files.sort();
+
+    for (_, len, path) in files {
+        if total <= max_total_bytes {
+            break;
+        }
+        if keep == Some(path.as_path()) {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => total = total.saturating_sub(len),
+            Err(e) => warnings.push(format!(
+                "Failed to delete log file {} over the size limit: {e}",
+                path.display()
+            )),
+        }
+    }
+
+    warnings
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -370,6 +752,47 @@ mod tests {
         assert!(recent_log.exists());
     }
 
+    #[test]
+    fn test_enforce_total_size_keeps_the_newest_files() {
+        let tmp = TempDir::new().unwrap();
+        let log_dir = tmp.path();
+        let now = SystemTime::now();
+        for (name, minutes_ago) in [("a.log", 30), ("b.log", 20), ("c.log", 10), ("d.log", 0)] {
+            let path = log_dir.join(name);
+            std::fs::write(&path, vec![b'x'; 100]).unwrap();
+            let modified = now - Duration::from_secs(minutes_ago * 60);
+            set_file_mtime(&path, FileTime::from_system_time(modified)).unwrap();
+        }
+        std::fs::write(log_dir.join("notes.txt"), vec![b'x'; 500]).unwrap();
+
+        let warnings = enforce_total_size(log_dir, 250, Some(&log_dir.join("a.log")));
+        assert!(warnings.is_empty());
+        assert!(log_dir.join("a.log").exists());
+        assert!(!log_dir.join("b.log").exists());
+        assert!(!log_dir.join("c.log").exists());
+        assert!(log_dir.join("d.log").exists());
+        assert!(log_dir.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_writer_rotates_past_the_file_cap() {
+        let tmp = TempDir::new().unwrap();
+        let mut writer = RotatingLogWriter::open(tmp.path(), 64, 0).unwrap();
+        let first = writer.path.clone();
+
+        writer.write_line(&"x".repeat(40)).unwrap();
+        assert_eq!(writer.path, first);
+        writer.write_line(&"y".repeat(40)).unwrap();
+        assert_ne!(writer.path, first);
+        writer.write_line("after rotation").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&first).unwrap().lines().count(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&writer.path).unwrap(),
+            "after rotation\n"
+        );
+    }
+
     #[test]
     #[serial]
     fn test_resolve_logging_config_respects_env_toggle() {
@@ -397,4 +820,71 @@ mod tests {
             env::remove_var("HOME");
         }
     }
+
+    #[test]
+    #[serial]
+    fn test_resolve_logging_config_reads_log_format() {
+        let prev = env::var("SCHALTWERK_LOG_FORMAT").ok();
+
+        env::set_var("SCHALTWERK_LOG_FORMAT", "JSON");
+        assert_eq!(resolve_logging_config().format, Some(LogFormat::Json));
+
+        env::set_var("SCHALTWERK_LOG_FORMAT", "yaml");
+        let config = resolve_logging_config();
+        assert_eq!(config.format, None);
+        assert!(config.deferred_warnings[0].contains("SCHALTWERK_LOG_FORMAT"));
+
+        if let Some(prev) = prev {
+            env::set_var("SCHALTWERK_LOG_FORMAT", prev);
+        } else {
+            env::remove_var("SCHALTWERK_LOG_FORMAT");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_line_carries_the_log_context() {
+        let context = LogContext::for_session("fix-login");
+        let line = with_log_context(context.clone(), async {
+            format_json_line(
+                "2026-01-02T03:04:05.678+01:00",
+                log::Level::Info,
+                "schaltwerk::merge",
+                "merged",
+                current_log_context().as_ref(),
+            )
+        })
+        .await;
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["timestamp"], "2026-01-02T03:04:05.678+01:00");
+        assert_eq!(value["level"], "INFO");
+        assert_eq!(value["target"], "schaltwerk::merge");
+        assert_eq!(value["message"], "merged");
+        assert_eq!(value["session_id"], "fix-login");
+        assert_eq!(
+            value["correlation_id"],
+            context.correlation_id.unwrap().as_str()
+        );
+
+        assert!(current_log_context().is_none());
+        let bare = format_json_line("t", log::Level::Warn, "x", "y", None);
+        assert!(bare.contains("\"session_id\":null"));
+    }
+
+    #[tokio::test]
+    async fn test_agent_start_continues_the_session_creation() {
+        let created = LogContext::for_new_session("correlated-session");
+        let started = LogContext::continuing_session("correlated-session");
+        assert_eq!(started.correlation_id, created.correlation_id);
+        let restarted = LogContext::continuing_session("correlated-session");
+        assert_ne!(restarted.correlation_id, created.correlation_id);
+
+        let spawned = with_log_context(created.clone(), async {
+            tokio::spawn(in_current_log_context(async { current_log_context() }))
+                .await
+                .unwrap()
+        })
+        .await;
+        assert_eq!(spawned, Some(created));
+    }
 }
@@ -0,0 +1,218 @@
+//! Export of `tracing` spans as a Chrome trace (the JSON Trace Event Format), which
+//! chrome://tracing and https://ui.perfetto.dev open as a timeline. Every closed span
+//! becomes one complete event with its duration and fields; events become instants.
+
+use serde_json::{Map, Value};
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // Small stable ids keep the trace viewer's thread rows readable
+    static THREAD_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+fn thread_id() -> u64 {
+    THREAD_ID.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    })
+}
+
+struct FieldVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+struct SpanTiming {
+    started: Instant,
+    args: Map<String, Value>,
+}
+
+pub struct ChromeTraceLayer {
+    origin: Instant,
+    pid: u32,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl ChromeTraceLayer {
+    /// Start a trace in a new file at `path`.
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        // The format allows leaving the array open, so the file stays valid if the app
+        // is killed mid-trace
+        writer.write_all(b"[\n")?;
+        writer.flush()?;
+        Ok(Self {
+            origin: Instant::now(),
+            pid: std::process::id(),
+            writer: Mutex::new(writer),
+        })
+    }
+
+    fn micros_since_origin(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.origin).as_micros() as u64
+    }
+
+    fn write(&self, event: Value) {
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{event},");
+            let _ = writer.flush();
+        }
+    }
+}
+
+impl<S> Layer<S> for ChromeTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut args = Map::new();
+        attrs.record(&mut FieldVisitor(&mut args));
+        span.extensions_mut().insert(SpanTiming {
+            started: Instant::now(),
+            args,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                values.record(&mut FieldVisitor(&mut timing.args));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut args = Map::new();
+        event.record(&mut FieldVisitor(&mut args));
+        let metadata = event.metadata();
+        self.write(serde_json::json!({
+            "name": args.remove("message").unwrap_or_else(|| metadata.name().into()),
+            "cat": metadata.target(),
+            "ph": "i",
+            "s": "t",
+            "ts": self.micros_since_origin(Instant::now()),
+            "pid": self.pid,
+            "tid": thread_id(),
+            "args": args,
+        }));
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        self.write(serde_json::json!({
+            "name": metadata.name(),
+            "cat": metadata.target(),
+            "ph": "X",
+            "ts": self.micros_since_origin(timing.started),
+            "dur": timing.started.elapsed().as_micros() as u64,
+            "pid": self.pid,
+            "tid": thread_id(),
+            "args": timing.args,
+        }));
+    }
+}
+
+/// Where `SCHALTWERK_TRACE_FILE` asks for a trace: a path, or `1`/`true` for a new
+/// file next to the logs.
+pub(super) fn trace_path(value: &str, log_dir: &Path) -> Option<PathBuf> {
+    match super::parse_bool(value) {
+        Some(true) => Some(log_dir.join(format!(
+            "schaltwerk-trace-{}.json",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))),
+        Some(false) => None,
+        None if value.trim().is_empty() => None,
+        None => Some(PathBuf::from(value.trim())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn writes_spans_and_events_as_trace_events() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("trace.json");
+        let layer = ChromeTraceLayer::create(&path).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span =
+                tracing::trace_span!("list_enriched_sessions", sessions = tracing::field::Empty);
+            let _entered = span.enter();
+            tracing::info!(total_ms = 12u64, "listed");
+            span.record("sessions", 3u64);
+        });
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let json = format!("{}]", contents.trim_end().trim_end_matches(','));
+        let events: Vec<Value> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["ph"], "i");
+        assert_eq!(events[0]["name"], "listed");
+        assert_eq!(events[0]["args"]["total_ms"], 12);
+        assert_eq!(events[1]["ph"], "X");
+        assert_eq!(events[1]["name"], "list_enriched_sessions");
+        assert_eq!(events[1]["args"]["sessions"], 3);
+        assert!(events[1]["dur"].is_u64());
+    }
+
+    #[test]
+    fn trace_path_accepts_flags_and_paths() {
+        let logs = Path::new("/tmp/logs");
+        assert!(trace_path("1", logs).unwrap().starts_with(logs));
+        assert_eq!(trace_path("off", logs), None);
+        assert_eq!(
+            trace_path("/tmp/run.json", logs),
+            Some(PathBuf::from("/tmp/run.json"))
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Reading the current log file back for the diagnostics panel, and the live stream
+//! of log lines it shows while open.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+
+/// Log lines buffered per stream subscriber before it starts missing some
+const LOG_STREAM_CAPACITY: usize = 1_024;
+
+static LOG_STREAM: LazyLock<broadcast::Sender<LogEntry>> =
+    LazyLock::new(|| broadcast::channel(LOG_STREAM_CAPACITY).0);
+
+/// One log record, parsed from either log format. Text lines carry no session or
+/// correlation id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// 1-based line in the log file where the record starts; 0 for streamed records
+    #[serde(default)]
+    pub line: usize,
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogFilter {
+    /// Least severe level to include: `warn` keeps warnings and errors
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Part of the module path, e.g. `merge` or `schaltwerk::domains::terminal`
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Records tagged with this session, and untagged records that mention it
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// A page of matching records, oldest first. `offset` counts matches back from the newest.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub file: Option<String>,
+    pub entries: Vec<LogEntry>,
+    pub total: usize,
+    pub offset: usize,
+    pub has_more: bool,
+}
+
+/// Records appended after a known line; `last_line` is the cursor for the next call.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogTail {
+    pub file: Option<String>,
+    pub entries: Vec<LogEntry>,
+    pub last_line: usize,
+}
+
+impl LogFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(max) = self
+            .level
+            .as_deref()
+            .and_then(|l| l.parse::<log::Level>().ok())
+        {
+            match entry.level.parse::<log::Level>() {
+                Ok(level) if level <= max => {}
+                _ => return false,
+            }
+        }
+        if let Some(target) = self.target.as_deref().filter(|t| !t.is_empty()) {
+            if !entry.target.contains(target) {
+                return false;
+            }
+        }
+        if let Some(session) = self.session_id.as_deref().filter(|s| !s.is_empty()) {
+            let matches = match entry.session_id.as_deref() {
+                Some(id) => id == session,
+                None => entry.message.contains(session),
+            };
+            if !matches {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// `[2026-01-02 03:04:05.678 INFO  schaltwerk::merge] message`
+fn parse_text_line(line_number: usize, line: &str) -> Option<LogEntry> {
+    let (header, message) = line.strip_prefix('[')?.split_once(']')?;
+    let mut parts = header.split_whitespace();
+    let (date, time, level, target) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || level.parse::<log::Level>().is_err() {
+        return None;
+    }
+    let timestamp = format!("{date} {time}");
+    chrono::NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%d %H:%M:%S%.3f").ok()?;
+    Some(LogEntry {
+        line: line_number,
+        timestamp,
+        level: level.to_string(),
+        target: target.to_string(),
+        message: message.strip_prefix(' ').unwrap_or(message).to_string(),
+        session_id: None,
+        correlation_id: None,
+    })
+}
+
+fn parse_json_line(line_number: usize, line: &str) -> Option<LogEntry> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let mut entry: LogEntry = serde_json::from_str(line).ok()?;
+    entry.line = line_number;
+    Some(entry)
+}
+
+/// Text lines end in ` [correlation_id=<id>]` when logged inside an operation.
+fn take_text_correlation(entry: &mut LogEntry) {
+    if entry.correlation_id.is_some() {
+        return;
+    }
+    let Some((message, rest)) = entry.message.rsplit_once(" [correlation_id=") else {
+        return;
+    };
+    let Some(id) = rest.strip_suffix(']') else {
+        return;
+    };
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return;
+    }
+    entry.correlation_id = Some(id.to_string());
+    entry.message.truncate(message.len());
+}
+
+/// Records in `contents`, oldest first. Lines that start no record (the rest of a
+/// multi-line message) are appended to the record before them.
+pub fn parse_log(contents: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        match parse_json_line(line_number, line).or_else(|| parse_text_line(line_number, line)) {
+            Some(entry) => entries.push(entry),
+            None => {
+                if let Some(previous) = entries.last_mut() {
+                    previous.message.push('\n');
+                    previous.message.push_str(line);
+                }
+            }
+        }
+    }
+    entries.iter_mut().for_each(take_text_correlation);
+    entries
+}
+
+fn read_log(path: Option<&Path>) -> std::io::Result<(Option<String>, String)> {
+    match path {
+        Some(path) => Ok((
+            Some(path.display().to_string()),
+            String::from_utf8_lossy(&std::fs::read(path)?).into_owned(),
+        )),
+        None => Ok((None, String::new())),
+    }
+}
+
+/// The `limit` matching records that come `offset` matches before the newest one.
+pub fn query_log_file(
+    path: Option<&Path>,
+    filter: &LogFilter,
+    offset: usize,
+    limit: usize,
+) -> std::io::Result<LogPage> {
+    let (file, contents) = read_log(path)?;
+    let matching: Vec<LogEntry> = parse_log(&contents)
+        .into_iter()
+        .filter(|entry| filter.matches(entry))
+        .collect();
+    let total = matching.len();
+    let end = total.saturating_sub(offset);
+    let start = end.saturating_sub(limit);
+    Ok(LogPage {
+        file,
+        entries: matching[start..end].to_vec(),
+        total,
+        offset,
+        has_more: start > 0,
+    })
+}
+
+/// Matching records that start after `after_line`, at most the newest `limit`. Without
+/// a cursor, the newest `limit` records.
+pub fn tail_log_file(
+    path: Option<&Path>,
+    filter: &LogFilter,
+    after_line: Option<usize>,
+    limit: usize,
+) -> std::io::Result<LogTail> {
+    let (file, contents) = read_log(path)?;
+    let last_line = contents.lines().count();
+    let after_line = after_line.unwrap_or(0);
+    let matching: Vec<LogEntry> = parse_log(&contents)
+        .into_iter()
+        .filter(|entry| entry.line > after_line && filter.matches(entry))
+        .collect();
+    let start = matching.len().saturating_sub(limit);
+    Ok(LogTail {
+        file,
+        entries: matching[start..].to_vec(),
+        last_line,
+    })
+}
+
+/// Records logged from now on.
+pub fn subscribe_log_lines() -> broadcast::Receiver<LogEntry> {
+    LOG_STREAM.subscribe()
+}
+
+/// Hand a record to the stream subscribers; `entry` is only built when there are any.
+pub(super) fn publish_log_line(entry: impl FnOnce() -> LogEntry) {
+    if LOG_STREAM.receiver_count() > 0 {
+        let _ = LOG_STREAM.send(entry());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = r#"[2026-01-02 03:04:05.001 INFO  schaltwerk::merge] Merging fix-login
+[2026-01-02 03:04:05.002 WARN  schaltwerk::domains::terminal] Slow write
+second line of the warning [correlation_id=f00d]
+{"timestamp":"2026-01-02T03:04:06.000+01:00","level":"ERROR","target":"schaltwerk::merge","message":"Merge failed","session_id":"fix-login","correlation_id":"abc"}
+{"timestamp":"2026-01-02T03:04:07.000+01:00","level":"DEBUG","target":"tauri","message":"ipc","session_id":null,"correlation_id":null}
+"#;
+
+    #[test]
+    fn parses_both_formats_and_continuation_lines() {
+        let entries = parse_log(LOG);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].timestamp, "2026-01-02 03:04:05.001");
+        assert_eq!(entries[0].message, "Merging fix-login");
+        assert_eq!(entries[1].level, "WARN");
+        assert_eq!(entries[1].message, "Slow write\nsecond line of the warning");
+        assert_eq!(entries[1].correlation_id.as_deref(), Some("f00d"));
+        assert_eq!(entries[2].line, 4);
+        assert_eq!(entries[2].session_id.as_deref(), Some("fix-login"));
+    }
+
+    #[test]
+    fn filters_and_pages_from_the_newest_record() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("schaltwerk.log");
+        std::fs::write(&path, LOG).unwrap();
+
+        let warnings = LogFilter {
+            level: Some("warn".to_string()),
+            ..Default::default()
+        };
+        let page = query_log_file(Some(&path), &warnings, 0, 1).unwrap();
+        assert_eq!(page.total, 2);
+        assert!(page.has_more);
+        assert_eq!(page.entries[0].message, "Merge failed");
+        let older = query_log_file(Some(&path), &warnings, 1, 1).unwrap();
+        assert!(!older.has_more);
+        assert_eq!(older.entries[0].level, "WARN");
+
+        let session = LogFilter {
+            target: Some("merge".to_string()),
+            session_id: Some("fix-login".to_string()),
+            ..Default::default()
+        };
+        let page = query_log_file(Some(&path), &session, 0, 10).unwrap();
+        assert_eq!(page.entries.len(), 2);
+
+        let tail = tail_log_file(Some(&path), &LogFilter::default(), Some(3), 10).unwrap();
+        assert_eq!(tail.last_line, 5);
+        assert_eq!(tail.entries.len(), 2);
+        assert_eq!(tail.entries[0].line, 4);
+
+        let none = query_log_file(None, &LogFilter::default(), 0, 10).unwrap();
+        assert!(none.file.is_none() && none.entries.is_empty());
+    }
+}
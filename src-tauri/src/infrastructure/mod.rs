@@ -1,5 +1,7 @@
 pub mod config;
+pub mod crash;
 pub mod database;
 pub mod events;
 pub mod logging;
 pub mod pty;
+pub mod secrets;
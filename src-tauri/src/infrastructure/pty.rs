@@ -1,6 +1,7 @@
 use pty_host::{
-    AckRequest, EventSink, KillRequest, PtyHost, ResizeRequest, SpawnRequest, SpawnResponse,
-    SubscribeRequest, SubscribeResponse, WriteRequest,
+    AckRequest, EventSink, ExportRecordingRequest, KillRequest, PtyHost, RecordingRequest,
+    RecordingResponse, ResizeRequest, SpawnRequest, SpawnResponse, SubscribeRequest,
+    SubscribeResponse, WriteRequest,
 };
 use serde::Serialize;
 use std::sync::RwLock;
@@ -123,6 +124,36 @@ impl PtyHostManager {
         self.host.ack(request).await.map_err(|e| e.to_string())
     }
 
+    pub async fn start_recording(
+        &self,
+        request: RecordingRequest,
+    ) -> Result<RecordingResponse, String> {
+        self.host
+            .start_recording(request)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn stop_recording(
+        &self,
+        request: RecordingRequest,
+    ) -> Result<RecordingResponse, String> {
+        self.host
+            .stop_recording(request)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn export_recording(
+        &self,
+        request: ExportRecordingRequest,
+    ) -> Result<RecordingResponse, String> {
+        self.host
+            .export_recording(request)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn subscribe(&self, request: SubscribeRequest) -> Result<SubscribeResponse, String> {
         self.host
             .subscribe(request)
@@ -0,0 +1,375 @@
+//! Secrets kept in the OS keychain instead of the settings file, and masking of their
+//! values wherever text leaves the app: log lines and terminal output.
+
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::process::{Command, Stdio};
+use std::sync::{LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// Keychain service every Schaltwerk secret is stored under
+pub const KEYCHAIN_SERVICE: &str = "schaltwerk";
+
+/// Shown instead of a secret value
+pub const SECRET_MASK: &str = "********";
+
+/// Shorter values are not masked; they would hide unrelated text
+const MIN_MASKED_LEN: usize = 4;
+
+static KNOWN_SECRETS: LazyLock<RwLock<BTreeSet<String>>> =
+    LazyLock::new(|| RwLock::new(BTreeSet::new()));
+
+pub trait SecretStore: Send + Sync {
+    fn get(&self, account: &str) -> Result<Option<String>>;
+    fn set(&self, account: &str, value: &str) -> Result<()>;
+    /// Removing a secret that does not exist is not an error.
+    fn delete(&self, account: &str) -> Result<()>;
+}
+
+/// The OS keychain: the login keychain through `security` on macOS, the Secret Service
+/// through `secret-tool` (libsecret) on Linux.
+pub struct KeychainStore;
+
+fn run(command: &mut Command, stdin: Option<&str>) -> Result<std::process::Output> {
+    use std::io::Write;
+
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run {program}: {e}"))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+#[cfg(target_os = "macos")]
+impl SecretStore for KeychainStore {
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        let output = run(
+            Command::new("security").args([
+                "find-generic-password",
+                "-s",
+                KEYCHAIN_SERVICE,
+                "-a",
+                account,
+                "-w",
+            ]),
+            None,
+        )?;
+        if output.status.success() {
+            let value = String::from_utf8_lossy(&output.stdout);
+            return Ok(Some(value.trim_end_matches('\n').to_string()));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("could not be found") {
+            return Ok(None);
+        }
+        Err(anyhow!(
+            "Failed to read {account} from the keychain: {}",
+            stderr.trim()
+        ))
+    }
+
+    fn set(&self, account: &str, value: &str) -> Result<()> {
+        use std::os::unix::process::CommandExt;
+
+        if value.contains('\n') {
+            return Err(anyhow!("{account} cannot contain a line break"));
+        }
+        let mut command = Command::new("security");
+        command.args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ]);
+        // A trailing `-w` makes `security` prompt for the value and a retype, which go
+        // through stdin so the value never shows up in the process list. The prompt
+        // prefers the controlling terminal, so the child starts a session without one.
+        unsafe {
+            command.pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            });
+        }
+        let output = run(&mut command, Some(&format!("{value}\n{value}\n")))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!(
+                "Failed to store {account} in the keychain: {}",
+                stderr.trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        let output = run(
+            Command::new("security").args([
+                "delete-generic-password",
+                "-s",
+                KEYCHAIN_SERVICE,
+                "-a",
+                account,
+            ]),
+            None,
+        )?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && !stderr.contains("could not be found") {
+            return Err(anyhow!(
+                "Failed to remove {account} from the keychain: {}",
+                stderr.trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SecretStore for KeychainStore {
+    fn get(&self, account: &str) -> Result<Option<String>> {
+        let output = run(
+            Command::new("secret-tool").args([
+                "lookup",
+                "service",
+                KEYCHAIN_SERVICE,
+                "account",
+                account,
+            ]),
+            None,
+        )?;
+        // `lookup` exits with 1 and prints nothing when there is no such secret
+        if output.stdout.is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if output.status.success() || stderr.trim().is_empty() {
+                return Ok(None);
+            }
+            return Err(anyhow!(
+                "Failed to read {account} from the keychain: {}",
+                stderr.trim()
+            ));
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+    }
+
+    fn set(&self, account: &str, value: &str) -> Result<()> {
+        let label = format!("Schaltwerk: {account}");
+        // The value goes through stdin so it never shows up in the process list
+        let output = run(
+            Command::new("secret-tool").args([
+                "store",
+                "--label",
+                &label,
+                "service",
+                KEYCHAIN_SERVICE,
+                "account",
+                account,
+            ]),
+            Some(value),
+        )?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!(
+                "Failed to store {account} in the keychain: {}",
+                stderr.trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<()> {
+        let output = run(
+            Command::new("secret-tool").args([
+                "clear",
+                "service",
+                KEYCHAIN_SERVICE,
+                "account",
+                account,
+            ]),
+            None,
+        )?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && !stderr.trim().is_empty() {
+            return Err(anyhow!(
+                "Failed to remove {account} from the keychain: {}",
+                stderr.trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+impl SecretStore for KeychainStore {
+    fn get(&self, _account: &str) -> Result<Option<String>> {
+        Err(anyhow!("No OS keychain is supported on this platform"))
+    }
+
+    fn set(&self, _account: &str, _value: &str) -> Result<()> {
+        Err(anyhow!("No OS keychain is supported on this platform"))
+    }
+
+    fn delete(&self, _account: &str) -> Result<()> {
+        Err(anyhow!("No OS keychain is supported on this platform"))
+    }
+}
+
+/// Mask `value` in log lines and terminal output from now on.
+pub fn register_secret(value: &str) {
+    if value.len() < MIN_MASKED_LEN {
+        return;
+    }
+    if let Ok(mut secrets) = KNOWN_SECRETS.write() {
+        secrets.insert(value.to_string());
+    }
+}
+
+/// `text` with every registered secret replaced by [`SECRET_MASK`].
+pub fn mask_secrets(text: &str) -> Cow<'_, str> {
+    let Ok(secrets) = KNOWN_SECRETS.read() else {
+        return Cow::Borrowed(text);
+    };
+    mask_with(&secrets, text)
+}
+
+fn mask_with<'a>(secrets: &BTreeSet<String>, text: &'a str) -> Cow<'a, str> {
+    let mut found: Vec<&String> = secrets
+        .iter()
+        .filter(|s| text.contains(s.as_str()))
+        .collect();
+    if found.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    // Longer values first, so a secret containing another one is masked whole
+    found.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    let mut masked = text.to_string();
+    for secret in found {
+        masked = masked.replace(secret.as_str(), SECRET_MASK);
+    }
+    Cow::Owned(masked)
+}
+
+/// Length of the longest end of `text` that is the start, but not all, of a secret
+fn partial_secret_len(secrets: &BTreeSet<String>, text: &str) -> usize {
+    secrets
+        .iter()
+        .filter_map(|secret| {
+            (1..secret.len())
+                .rev()
+                .filter(|&len| secret.is_char_boundary(len))
+                .find(|&len| text.ends_with(&secret[..len]))
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// How long a held-back chunk end waits for the rest of a secret before it is shown as is
+pub const HELD_TAIL_TIMEOUT: Duration = Duration::from_millis(150);
+
+/// Masking for text that arrives in chunks, like terminal output, so a secret split
+/// across two chunks is still masked. The end of a chunk that could be the start of a
+/// secret is held back until the next chunk shows whether it is one, or until the stream
+/// has been idle for [`HELD_TAIL_TIMEOUT`].
+#[derive(Debug, Default)]
+pub struct SecretMaskingStream {
+    held: String,
+    held_since: Option<Instant>,
+}
+
+impl SecretMaskingStream {
+    /// The masked text that can be shown now; empty while all of it is held back.
+    pub fn mask_chunk(&mut self, chunk: &str) -> String {
+        let mut text = std::mem::take(&mut self.held);
+        text.push_str(chunk);
+        self.held_since = None;
+        let Ok(secrets) = KNOWN_SECRETS.read() else {
+            return text;
+        };
+        let mut masked = mask_with(&secrets, &text).into_owned();
+        let held = partial_secret_len(&secrets, &masked);
+        self.held = masked.split_off(masked.len() - held);
+        if !self.held.is_empty() {
+            self.held_since = Some(Instant::now());
+        }
+        masked
+    }
+
+    /// Whether the end of the last chunk is held back.
+    pub fn is_holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    /// The held-back text once no chunk arrived for [`HELD_TAIL_TIMEOUT`]: no secret
+    /// followed, so a prompt ending in the start of one is not hidden indefinitely.
+    pub fn take_stale_tail(&mut self, now: Instant) -> Option<String> {
+        let since = self.held_since?;
+        if now.duration_since(since) < HELD_TAIL_TIMEOUT {
+            return None;
+        }
+        self.held_since = None;
+        Some(std::mem::take(&mut self.held))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_registered_secrets() {
+        register_secret("sk-test-4c1f9a");
+        register_secret("sk-test-4c1f9a-extended");
+        register_secret("abc");
+
+        assert_eq!(
+            mask_secrets("export OPENAI_API_KEY=sk-test-4c1f9a-extended; echo sk-test-4c1f9a"),
+            format!("export OPENAI_API_KEY={SECRET_MASK}; echo {SECRET_MASK}")
+        );
+        assert!(matches!(
+            mask_secrets("abc and nothing else"),
+            Cow::Borrowed(_)
+        ));
+    }
+    #[test]
+    fn masks_secrets_split_across_chunks() {
+        register_secret("sk-stream-7d2e0b");
+        let mut stream = SecretMaskingStream::default();
+
+        assert_eq!(stream.mask_chunk("key=sk-str"), "key=");
+        assert_eq!(
+            stream.mask_chunk("eam-7d2e0b done\r\n"),
+            format!("{SECRET_MASK} done\r\n")
+        );
+        // Held text that turns out not to be a secret is shown with the next chunk
+        assert_eq!(stream.mask_chunk("$ sk-s"), "$ ");
+        assert_eq!(stream.mask_chunk("lim\r\n"), "sk-slim\r\n");
+    }
+
+    #[test]
+    fn held_tail_is_shown_once_the_stream_goes_idle() {
+        register_secret("sk-idle-5e8a1c");
+        let mut stream = SecretMaskingStream::default();
+
+        assert_eq!(stream.mask_chunk("user@host:~/sk"), "user@host:~/");
+        assert!(stream.is_holding());
+        assert_eq!(stream.take_stale_tail(Instant::now()), None);
+
+        let idle = Instant::now() + HELD_TAIL_TIMEOUT;
+        assert_eq!(stream.take_stale_tail(idle), Some("sk".to_string()));
+        assert!(!stream.is_holding());
+        assert_eq!(stream.take_stale_tail(idle), None);
+        assert_eq!(stream.mask_chunk(" $ "), " $ ");
+    }
+}
@@ -24,14 +24,12 @@ mod updater;
 use crate::commands::sessions_refresh::{request_sessions_refresh, SessionsRefreshReason};
 use clap::Parser;
 use schaltwerk::domains::git::repository;
+use schaltwerk::domains::settings::SettingsChange;
+use schaltwerk::infrastructure::config::settings::apply_settings_change;
 use schaltwerk::infrastructure::config::SettingsManager;
-use schaltwerk::project_manager::ProjectManager;
+use schaltwerk::project_manager::{Project, ProjectManager};
 use schaltwerk::schaltwerk_core::db_app_config::AppConfigMethods;
 use schaltwerk::services::ServiceHandles;
-use schaltwerk::shared::terminal_id::{
-    legacy_terminal_id_for_session_top, previous_hashed_terminal_id_for_session_top,
-    terminal_id_for_session_top,
-};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{Mutex, OnceCell, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
@@ -358,6 +356,17 @@ use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper::{body::Incoming as IncomingBody, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use mcp_api::limits::{self, RateLimiter};
+use mcp_api::webhook::{
+    self, FollowUpMessageWebhook, SessionAddedWebhook, SessionRemovedWebhook, SpecCreatedWebhook,
+};
+#[cfg(unix)]
+use schaltwerk::api::mcp::socket;
+use schaltwerk::domains::sessions::follow_ups;
+use std::net::IpAddr;
+#[cfg(unix)]
+use std::net::Ipv4Addr;
+use std::time::Instant;
 use tokio::net::TcpListener;
 
 async fn find_available_port(base_port: u16) -> u16 {
@@ -401,273 +410,307 @@ fn calculate_project_port(project_path: &str) -> u16 {
 async fn start_webhook_server(app: tauri::AppHandle) -> bool {
     async fn handle_webhook(
         app: tauri::AppHandle,
+        limiter: Arc<RateLimiter>,
+        remote_ip: IpAddr,
         req: Request<IncomingBody>,
     ) -> Result<Response<String>, hyper::Error> {
-        let method = req.method();
-        let path = req.uri().path();
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
 
         log::debug!("Webhook request: {method} {path}");
 
-        match (method, path) {
-            (&hyper::Method::POST, "/webhook/session-added") => {
-                // Parse the JSON body
-                let body = req.into_body();
-                let body_bytes = body.collect().await?.to_bytes();
-
-                if let Ok(body_str) = String::from_utf8(body_bytes.to_vec()) {
-                    if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&body_str) {
-                        log::info!("Received session-added webhook: {payload}");
-
-                        // Extract session information and emit the event
-                        if let Some(session_name) =
-                            payload.get("session_name").and_then(|v| v.as_str())
-                        {
-                            #[derive(serde::Serialize, Clone)]
-                            struct SessionAddedPayload {
-                                session_name: String,
-                                branch: String,
-                                worktree_path: String,
-                                parent_branch: String,
-                                created_at: String,
-                                last_modified: Option<String>,
-                            }
+        if let Err(retry_after) = limiter.check(remote_ip, Instant::now()) {
+            log::debug!("Rate limited {method} {path} from {remote_ip}");
+            return Ok(mcp_api::too_many_requests_response(retry_after));
+        }
+        if let Some(message) = limits::body_size_violation(&req) {
+            return Ok(mcp_api::payload_too_large_response(message));
+        }
 
-                            let session_payload = SessionAddedPayload {
-                                session_name: session_name.to_string(),
-                                branch: payload
-                                    .get("branch")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("")
-                                    .to_string(),
-                                worktree_path: payload
-                                    .get("worktree_path")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("")
-                                    .to_string(),
-                                parent_branch: payload
-                                    .get("parent_branch")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("")
-                                    .to_string(),
-                                created_at: payload
-                                    .get("created_at")
-                                    .and_then(|v| v.as_str())
-                                    .map(|s| s.to_string())
-                                    .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
-                                last_modified: payload
-                                    .get("last_modified")
-                                    .and_then(|v| v.as_str())
-                                    .map(|s| s.to_string()),
-                            };
+        // `/projects/{hash}/webhook/...` targets one open project, so bridges of projects
+        // in background tabs reach their own sessions; `/webhook/...` acts on the current one
+        let scoped = path
+            .strip_prefix("/projects/")
+            .and_then(|rest| rest.split_once('/'));
+        let (project, route) = match scoped {
+            Some((hash, rest)) if rest.starts_with("webhook/") => {
+                let Some(project) = get_project_manager().await.project_for_hash(hash).await else {
+                    log::warn!("Webhook for unknown project {hash}: {path}");
+                    return Ok(not_found_response());
+                };
+                if !mcp_api::is_authorized_for_project(&req, &project.path) {
+                    return Ok(mcp_api::unauthorized_response());
+                }
+                (Some(project), format!("/{rest}"))
+            }
+            Some(_) => return Ok(not_found_response()),
+            None if path.starts_with("/webhook/") => {
+                if !mcp_api::is_authorized_request(&req) {
+                    return Ok(mcp_api::unauthorized_response());
+                }
+                let project = get_project_manager().await.current_project().await.ok();
+                (project, path.clone())
+            }
+            None => (None, path.clone()),
+        };
+        let project_path = project
+            .as_ref()
+            .map(|project| project.path.to_string_lossy().to_string());
+        // Session refreshes only ever cover the project the UI shows
+        let is_current_project = match &project {
+            Some(project) => get_project_manager()
+                .await
+                .current_project_path()
+                .await
+                .is_some_and(|current| current == project.path),
+            None => false,
+        };
 
-                            if let Err(e) =
-                                emit_event(&app, SchaltEvent::SessionAdded, &session_payload)
-                            {
-                                log::error!("Failed to emit session-added event: {e}");
-                            }
-                        }
-                    }
+        match (&method, route.as_str()) {
+            (&hyper::Method::POST, "/webhook/session-added") => {
+                let body_bytes = req.into_body().collect().await?.to_bytes();
+                let payload: SessionAddedWebhook =
+                    match webhook::parse_payload("session-added", &body_bytes) {
+                        Ok(payload) => payload,
+                        Err(message) => return Ok(mcp_api::bad_request_response(message)),
+                    };
+                log::info!("Received session-added webhook: {payload:?}");
+
+                #[derive(serde::Serialize, Clone)]
+                struct SessionAddedPayload {
+                    session_name: String,
+                    branch: String,
+                    worktree_path: String,
+                    parent_branch: String,
+                    created_at: String,
+                    last_modified: Option<String>,
+                    project_path: Option<String>,
+                }
+
+                let session_payload = SessionAddedPayload {
+                    session_name: payload.session_name,
+                    branch: payload.branch,
+                    worktree_path: payload.worktree_path,
+                    parent_branch: payload.parent_branch,
+                    created_at: payload
+                        .created_at
+                        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+                    last_modified: payload.last_modified,
+                    project_path,
+                };
+
+                if let Err(e) = emit_event(&app, SchaltEvent::SessionAdded, &session_payload) {
+                    log::error!("Failed to emit session-added event: {e}");
                 }
 
                 Ok(Response::new("OK".to_string()))
             }
             (&hyper::Method::POST, "/webhook/session-removed") => {
-                // Parse the JSON body for session removal
-                let body = req.into_body();
-                let body_bytes = body.collect().await?.to_bytes();
-
-                if let Ok(body_str) = String::from_utf8(body_bytes.to_vec()) {
-                    if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&body_str) {
-                        log::info!("Received session-removed webhook: {payload}");
-
-                        if let Some(session_name) =
-                            payload.get("session_name").and_then(|v| v.as_str())
-                        {
-                            #[derive(serde::Serialize, Clone)]
-                            struct SessionRemovedPayload {
-                                session_name: String,
-                            }
+                let body_bytes = req.into_body().collect().await?.to_bytes();
+                let payload: SessionRemovedWebhook =
+                    match webhook::parse_payload("session-removed", &body_bytes) {
+                        Ok(payload) => payload,
+                        Err(message) => return Ok(mcp_api::bad_request_response(message)),
+                    };
+                log::info!("Received session-removed webhook: {payload:?}");
 
-                            let session_payload = SessionRemovedPayload {
-                                session_name: session_name.to_string(),
-                            };
+                #[derive(serde::Serialize, Clone)]
+                struct SessionRemovedPayload {
+                    session_name: String,
+                    project_path: Option<String>,
+                }
 
-                            if let Err(e) =
-                                emit_event(&app, SchaltEvent::SessionRemoved, &session_payload)
-                            {
-                                log::error!("Failed to emit session-removed event: {e}");
-                            }
-                        }
-                    }
+                let session_payload = SessionRemovedPayload {
+                    session_name: payload.session_name,
+                    project_path,
+                };
+
+                if let Err(e) = emit_event(&app, SchaltEvent::SessionRemoved, &session_payload) {
+                    log::error!("Failed to emit session-removed event: {e}");
                 }
 
                 Ok(Response::new("OK".to_string()))
             }
             (&hyper::Method::POST, "/webhook/follow-up-message") => {
-                // Parse the JSON body for follow-up message
-                let body = req.into_body();
-                let body_bytes = body.collect().await?.to_bytes();
-
-                if let Ok(body_str) = String::from_utf8(body_bytes.to_vec()) {
-                    if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&body_str) {
-                        log::info!("Received follow-up-message webhook: {payload}");
-
-                        if let (Some(session_name), Some(message)) = (
-                            payload.get("session_name").and_then(|v| v.as_str()),
-                            payload.get("message").and_then(|v| v.as_str()),
-                        ) {
-                            let timestamp = payload
-                                .get("timestamp")
-                                .and_then(|v| v.as_u64())
-                                .unwrap_or_else(|| {
-                                    std::time::SystemTime::now()
-                                        .duration_since(std::time::UNIX_EPOCH)
-                                        .unwrap()
-                                        .as_millis() as u64
-                                });
-
-                            // Move reviewed sessions back to running upon follow-up (only if reviewed)
-                            if let Ok(core) = get_core_write().await {
-                                let manager = core.session_manager();
-                                match manager.unmark_reviewed_on_follow_up(session_name) {
-                                    Ok(true) => {
-                                        log::info!("Follow-up unmarked review state for '{session_name}', scheduling sessions refresh");
-                                        request_sessions_refresh(
-                                            &app,
-                                            SessionsRefreshReason::AgentActivity,
-                                        );
-                                    }
-                                    Ok(false) => {
-                                        log::debug!("Follow-up received for '{session_name}' with no review state to clear");
-                                    }
-                                    Err(e) => {
-                                        log::warn!("Failed to process follow-up review state for '{session_name}': {e}");
-                                    }
-                                }
-                            } else {
-                                log::warn!("Could not access SchaltwerkCore to update session state on follow-up");
-                            }
-
-                            let primary_terminal_id = terminal_id_for_session_top(session_name);
-                            let mut candidate_ids = Vec::new();
-                            for candidate in [
-                                primary_terminal_id.clone(),
-                                previous_hashed_terminal_id_for_session_top(session_name),
-                                legacy_terminal_id_for_session_top(session_name),
-                            ] {
-                                if !candidate_ids.contains(&candidate) {
-                                    candidate_ids.push(candidate);
-                                }
-                            }
-
-                            let mut delivered_terminal_id = primary_terminal_id.clone();
-                            let mut delivered = false;
-
-                            if let Ok(manager) = get_terminal_manager().await {
-                                for candidate in candidate_ids.iter() {
-                                    match manager.terminal_exists(candidate).await {
-                                        Ok(true) => {
-                                            match manager
-                                                .paste_and_submit_terminal(
-                                                    candidate.clone(),
-                                                    message.as_bytes().to_vec(),
-                                                    false,
-                                                )
-                                                .await
-                                            {
-                                                Ok(_) => {
-                                                    delivered_terminal_id = candidate.clone();
-                                                    delivered = true;
-                                                    log::info!("Successfully pasted follow-up message to terminal {candidate}");
-                                                    break;
-                                                }
-                                                Err(e) => {
-                                                    log::warn!("Failed to paste follow-up message to terminal {candidate}: {e}");
-                                                    // Try next candidate in case this ID is stale.
-                                                }
-                                            }
-                                        }
-                                        Ok(false) => {
-                                            log::debug!("Terminal {candidate} not found while handling follow-up; checking next candidate");
-                                        }
-                                        Err(e) => {
-                                            log::warn!("Failed to check if terminal {candidate} exists: {e}");
-                                        }
-                                    }
-                                }
-
-                                if !delivered {
-                                    log::warn!("No matching terminal found for follow-up message to session '{session_name}'. Tried: {candidate_ids:?}");
-                                }
-                            } else {
-                                log::warn!("Could not get terminal manager for follow-up message");
-                            }
-
-                            #[derive(serde::Serialize, Clone)]
-                            struct FollowUpMessagePayload {
-                                session_name: String,
-                                message: String,
-                                timestamp: u64,
-                                terminal_id: String,
-                            }
-
-                            let message_payload = FollowUpMessagePayload {
-                                session_name: session_name.to_string(),
-                                message: message.to_string(),
-                                timestamp,
-                                terminal_id: delivered_terminal_id,
-                            };
+                let body_bytes = req.into_body().collect().await?.to_bytes();
+                let payload: FollowUpMessageWebhook =
+                    match webhook::parse_payload("follow-up-message", &body_bytes) {
+                        Ok(payload) => payload,
+                        Err(message) => return Ok(mcp_api::bad_request_response(message)),
+                    };
+                if let Some(message) = limits::follow_up_message_violation(&payload.message) {
+                    return Ok(mcp_api::payload_too_large_response(message));
+                }
+                log::info!("Received follow-up-message webhook: {payload:?}");
+
+                let session_name = payload.session_name.as_str();
+                let message = payload.message.as_str();
+                let timestamp = payload.timestamp.unwrap_or_else(|| {
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_millis() as u64
+                });
 
-                            if let Err(e) =
-                                emit_event(&app, SchaltEvent::FollowUpMessage, &message_payload)
-                            {
-                                log::error!("Failed to emit follow-up-message event: {e}");
-                            }
+                let Some(project) = &project else {
+                    log::warn!(
+                        "Could not access SchaltwerkCore for follow-up message to '{session_name}'"
+                    );
+                    return Ok(not_found_response());
+                };
+
+                let (db, repo_path) = {
+                    let core = project.schaltwerk_core.write().await;
+                    let manager = core.session_manager();
+                    // Move reviewed sessions back to running upon follow-up (only if reviewed)
+                    match manager.unmark_reviewed_on_follow_up(session_name) {
+                        Ok(true) if is_current_project => {
+                            log::info!("Follow-up unmarked review state for '{session_name}', scheduling sessions refresh");
+                            request_sessions_refresh(&app, SessionsRefreshReason::AgentActivity);
+                        }
+                        Ok(true) => {
+                            log::info!("Follow-up unmarked review state for '{session_name}' in a background project");
+                        }
+                        Ok(false) => {
+                            log::debug!("Follow-up received for '{session_name}' with no review state to clear");
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to process follow-up review state for '{session_name}': {e}");
                         }
                     }
-                }
 
-                Ok(Response::new("OK".to_string()))
+                    // Stored first, so the message survives an agent restart or app crash
+                    if let Err(e) = manager.queue_follow_up(session_name, message, timestamp) {
+                        log::warn!("Failed to queue follow-up message for '{session_name}': {e}");
+                        return Ok(mcp_api::session_not_found_response(session_name));
+                    }
+                    (core.db.clone(), core.repo_path.clone())
+                };
+
+                let delivered = match follow_ups::deliver_pending_follow_ups(
+                    &db,
+                    &repo_path,
+                    &project.terminal_manager,
+                    Some(session_name),
+                )
+                .await
+                {
+                    Ok(delivered) => delivered,
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to deliver follow-up messages for '{session_name}': {e}"
+                        );
+                        Vec::new()
+                    }
+                };
+                follow_ups::emit_delivered_follow_ups(&app, &delivered);
+
+                if delivered
+                    .iter()
+                    .any(|sent| sent.message == message && sent.timestamp == timestamp)
+                {
+                    Ok(Response::new("OK".to_string()))
+                } else {
+                    // Retried in the background until the agent terminal exists again
+                    let mut response = Response::new("Queued".to_string());
+                    *response.status_mut() = StatusCode::ACCEPTED;
+                    Ok(response)
+                }
             }
             (&hyper::Method::POST, "/webhook/spec-created") => {
-                // Parse the JSON body for spec creation notification
-                let body = req.into_body();
-                let body_bytes = body.collect().await?.to_bytes();
-
-                if let Ok(body_str) = String::from_utf8(body_bytes.to_vec()) {
-                    if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&body_str) {
-                        log::info!("Received spec-created webhook: {payload}");
-
-                        if let Some(draft_name) =
-                            payload.get("session_name").and_then(|v| v.as_str())
-                        {
-                            log::info!("Spec created via MCP: {draft_name}");
-
-                            log::info!("Queueing sessions refresh after MCP spec creation");
-                            request_sessions_refresh(&app, SessionsRefreshReason::SpecSync);
+                let body_bytes = req.into_body().collect().await?.to_bytes();
+                let payload: SpecCreatedWebhook =
+                    match webhook::parse_payload("spec-created", &body_bytes) {
+                        Ok(payload) => payload,
+                        Err(message) => return Ok(mcp_api::bad_request_response(message)),
+                    };
+                let draft_name = payload.session_name;
+                log::info!("Spec created via MCP: {draft_name}");
 
-                            // Don't emit Selection event - let the user stay focused on their current session
-                            // The spec will appear in the sidebar but won't steal focus
-                            log::info!("Spec created via MCP: {draft_name} - preserving current user focus");
-                        } else {
-                            log::warn!("Spec-created webhook payload missing 'name' field");
-                        }
-                    } else {
-                        log::warn!("Failed to parse spec-created webhook JSON payload");
-                    }
-                } else {
-                    log::warn!("Failed to convert spec-created webhook body to UTF-8");
+                if is_current_project {
+                    log::info!("Queueing sessions refresh after MCP spec creation");
+                    request_sessions_refresh(&app, SessionsRefreshReason::SpecSync);
                 }
 
+                // Don't emit Selection event - let the user stay focused on their current session
+                // The spec will appear in the sidebar but won't steal focus
+                log::info!("Spec created via MCP: {draft_name} - preserving current user focus");
+
                 Ok(Response::new("OK".to_string()))
             }
             // Delegate all MCP API endpoints to the api module
             (_, path) if path.starts_with("/api/") => mcp_api::handle_mcp_request(req, app).await,
-            _ => {
-                let mut response = Response::new("Not Found".to_string());
-                *response.status_mut() = StatusCode::NOT_FOUND;
-                Ok(response)
+            _ => Ok(not_found_response()),
+        }
+    }
+
+    fn not_found_response() -> Response<String> {
+        let mut response = Response::new("Not Found".to_string());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        response
+    }
+
+    fn serve_connection<S>(
+        app: tauri::AppHandle,
+        limiter: Arc<RateLimiter>,
+        remote_ip: IpAddr,
+        stream: S,
+    ) where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    {
+        tokio::spawn(async move {
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(
+                    TokioIo::new(stream),
+                    service_fn(move |req| {
+                        handle_webhook(app.clone(), limiter.clone(), remote_ip, req)
+                    }),
+                )
+                .with_upgrades()
+                .await
+            {
+                log::error!("Error serving webhook connection: {err:?}");
             }
+        });
+    }
+
+    // Shared by all connections so reconnecting doesn't reset a client's budget
+    let limiter = Arc::new(RateLimiter::default());
+
+    #[cfg(unix)]
+    {
+        let socket_path = socket::default_socket_path();
+        match socket::bind_socket(&socket_path) {
+            Ok(Some(listener)) => {
+                log::info!("Webhook server listening on {}", socket_path.display());
+                socket::set_listening_socket(socket_path);
+                let app = app.clone();
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move {
+                    loop {
+                        match listener.accept().await {
+                            // Socket clients share the budget of local TCP clients
+                            Ok((stream, _)) => serve_connection(
+                                app.clone(),
+                                Arc::clone(&limiter),
+                                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                                stream,
+                            ),
+                            Err(e) => {
+                                log::error!("Failed to accept webhook socket connection: {e}")
+                            }
+                        }
+                    }
+                });
+            }
+            Ok(None) => log::info!(
+                "Another Schaltwerk instance serves {}, using TCP only",
+                socket_path.display()
+            ),
+            Err(e) => log::warn!(
+                "Failed to listen on {}: {e}, using TCP only",
+                socket_path.display()
+            ),
         }
     }
 
@@ -675,6 +718,7 @@ async fn start_webhook_server(app: tauri::AppHandle) -> bool {
     let project_manager = get_project_manager().await;
     let base_port = if let Some(active_project) = project_manager.current_project_path().await {
         let project_str = active_project.to_string_lossy();
+        issue_mcp_token(&active_project);
         let calculated_port = calculate_project_port(&project_str);
         log::info!("Using project-specific base port {calculated_port} for project: {project_str}");
         calculated_port
@@ -698,28 +742,14 @@ async fn start_webhook_server(app: tauri::AppHandle) -> bool {
     log::info!("Webhook server listening on http://{}:{}", addr.0, addr.1);
 
     loop {
-        let (stream, _) = match listener.accept().await {
+        let (stream, remote_addr) = match listener.accept().await {
             Ok(conn) => conn,
             Err(e) => {
                 log::error!("Failed to accept webhook connection: {e}");
                 continue;
             }
         };
-
-        let io = TokioIo::new(stream);
-        let app_clone = app.clone();
-
-        tokio::spawn(async move {
-            if let Err(err) = http1::Builder::new()
-                .serve_connection(
-                    io,
-                    service_fn(move |req| handle_webhook(app_clone.clone(), req)),
-                )
-                .await
-            {
-                log::error!("Error serving webhook connection: {err:?}");
-            }
-        });
+        serve_connection(app.clone(), Arc::clone(&limiter), remote_addr.ip(), stream);
     }
 }
 
@@ -755,6 +785,7 @@ fn main() {
 
     // Initialize logging
     schaltwerk::infrastructure::logging::init_logging();
+    schaltwerk::infrastructure::crash::install_panic_hook();
     log::info!("Schaltwerk starting...");
     log::info!(
         "[startup] Effective PATH: {}",
@@ -815,6 +846,8 @@ fn main() {
             github_authenticate,
             github_connect_project,
             github_create_reviewed_pr,
+            github_push_session_branch,
+            github_create_pull_request_for_session,
             // Permission commands
             permissions::check_folder_access,
             permissions::trigger_folder_permission_request,
@@ -828,16 +861,41 @@ fn main() {
             create_run_terminal,
             write_terminal,
             paste_and_submit_terminal,
+            confirm_terminal_paste,
+            broadcast_to_terminals,
             resize_terminal,
             close_terminal,
             terminal_exists,
             terminals_exist_bulk,
             get_terminal_buffer,
+            search_terminal_buffer,
+            get_terminal_resource_usage,
+            resume_terminal_output,
+            get_terminal_command_history,
+            get_session_idle_states,
+            get_session_agent_states,
+            get_session_time_report,
+            get_session_cost_report,
+            list_pending_follow_ups,
+            list_prompt_templates,
+            save_prompt_template,
+            delete_prompt_template,
+            render_prompt_template,
+            apply_prompt_template,
+            get_worktree_disk_usage,
+            prune_worktrees,
             get_terminal_activity_status,
             get_all_terminal_activity,
             register_session_terminals,
             suspend_session_terminals,
             resume_session_terminals,
+            start_run_watch,
+            stop_run_watch,
+            is_run_watch_active,
+            get_detected_ports,
+            start_preview_proxy,
+            stop_preview_proxy,
+            get_preview_urls,
             // PTY plugin commands
             pty_spawn,
             pty_write,
@@ -845,9 +903,13 @@ fn main() {
             pty_kill,
             pty_ack,
             pty_subscribe,
+            start_terminal_recording,
+            stop_terminal_recording,
+            export_terminal_recording,
             // Utility commands
             get_current_directory,
             open_in_vscode,
+            open_in_browser,
             path_exists,
             get_environment_variable,
             get_app_version,
@@ -858,8 +920,16 @@ fn main() {
             commands::clipboard::clipboard_read_text,
             // MCP commands
             start_mcp_server,
+            get_mcp_health,
             // Para core commands
             schaltwerk_core_create_session,
+            schaltwerk_core_duplicate_session,
+            schaltwerk_core_relocate_session_worktree,
+            schaltwerk_core_preview_stale_sessions,
+            schaltwerk_core_list_agent_crashes,
+            schaltwerk_core_enqueue_merge,
+            schaltwerk_core_remove_from_merge_queue,
+            schaltwerk_core_get_merge_queue,
             schaltwerk_core_rename_version_group,
             schaltwerk_core_list_sessions,
             schaltwerk_core_list_enriched_sessions,
@@ -879,18 +949,43 @@ fn main() {
             schaltwerk_core_reset_orchestrator,
             schaltwerk_core_reset_session_worktree,
             schaltwerk_core_discard_file_in_session,
+            schaltwerk_core_list_session_file_hunks,
+            schaltwerk_core_discard_hunks_in_session,
             schaltwerk_core_discard_file_in_orchestrator,
             schaltwerk_core_set_skip_permissions,
             schaltwerk_core_get_skip_permissions,
             schaltwerk_core_set_orchestrator_skip_permissions,
             schaltwerk_core_get_orchestrator_skip_permissions,
             schaltwerk_core_get_merge_preview,
+            schaltwerk_core_merge_session_dry_run,
+            schaltwerk_core_cherry_pick_session_commits,
             schaltwerk_core_merge_session_to_main,
+            generate_commit_message,
+            summarize_spec,
             schaltwerk_core_mark_session_ready,
             schaltwerk_core_has_uncommitted_changes,
+            schaltwerk_core_stash_session_changes,
+            schaltwerk_core_list_session_stashes,
+            schaltwerk_core_pop_session_stash,
+            schaltwerk_core_rebase_session_on_base,
             schaltwerk_core_unmark_session_ready,
             schaltwerk_core_set_agent_type,
             schaltwerk_core_set_session_agent_type,
+            switch_session_agent,
+            schaltwerk_core_set_session_codex_profile,
+            schaltwerk_core_get_session_environment,
+            schaltwerk_core_set_session_environment,
+            schaltwerk_core_set_session_dependencies,
+            schaltwerk_core_set_session_auto_nudge,
+            schaltwerk_core_list_session_labels,
+            schaltwerk_core_set_session_labels,
+            schaltwerk_core_add_session_label,
+            schaltwerk_core_remove_session_label,
+            schaltwerk_core_run_session_setup,
+            schaltwerk_core_cancel_session_setup,
+            schaltwerk_core_cancel_session_creation,
+            schaltwerk_core_get_session_setup_status,
+            get_agent_progress,
             schaltwerk_core_get_agent_type,
             schaltwerk_core_set_orchestrator_agent_type,
             schaltwerk_core_get_orchestrator_agent_type,
@@ -903,6 +998,13 @@ fn main() {
             schaltwerk_core_update_spec_content,
             schaltwerk_core_append_spec_content,
             schaltwerk_core_rename_draft_session,
+            schaltwerk_core_rename_session,
+            schaltwerk_core_get_session_notes,
+            schaltwerk_core_set_session_notes,
+            add_review_comment,
+            list_review_comments,
+            resolve_review_comment,
+            request_review_changes,
             schaltwerk_core_list_sessions_by_state,
             schaltwerk_core_archive_spec_session,
             schaltwerk_core_list_archived_specs,
@@ -960,6 +1062,10 @@ fn main() {
             get_terminal_settings,
             set_terminal_settings,
             list_installed_fonts,
+            validate_terminal_font,
+            get_terminal_themes,
+            set_terminal_theme,
+            delete_terminal_theme,
             get_diff_view_preferences,
             set_diff_view_preferences,
             get_session_preferences,
@@ -970,12 +1076,23 @@ fn main() {
             set_auto_update_enabled,
             get_keyboard_shortcuts,
             set_keyboard_shortcuts,
+            validate_keyboard_shortcuts,
             get_project_settings,
             set_project_settings,
             get_project_sessions_settings,
             set_project_sessions_settings,
             get_project_environment_variables,
             set_project_environment_variables,
+            get_project_settings_overrides,
+            set_project_settings_overrides,
+            get_session_settings_overrides,
+            set_session_settings_overrides,
+            get_env_sets,
+            set_env_set,
+            delete_env_set,
+            get_project_env_sets,
+            set_project_env_sets,
+            get_effective_settings,
             get_project_merge_preferences,
             set_project_merge_preferences,
             get_project_action_buttons,
@@ -983,8 +1100,39 @@ fn main() {
             reset_project_action_buttons_to_defaults,
             get_project_run_script,
             set_project_run_script,
+            get_project_codex_settings,
+            set_project_codex_settings,
+            get_project_agent_defaults,
+            set_project_agent_defaults,
+            get_project_setup_scripts,
+            set_project_setup_scripts,
+            get_project_container_config,
+            set_project_container_config,
+            get_project_dependency_cache_config,
+            set_project_dependency_cache_config,
+            get_project_worktree_root,
+            set_project_worktree_root,
+            get_project_stale_session_policy,
+            set_project_stale_session_policy,
+            get_project_outbound_webhook_config,
+            set_project_outbound_webhook_config,
+            detect_project_dev_environments,
+            get_project_dev_environment,
+            set_project_dev_environment,
             get_tutorial_completed,
             set_tutorial_completed,
+            get_json_logs,
+            set_json_logs,
+            query_logs,
+            tail_logs,
+            start_log_stream,
+            stop_log_stream,
+            list_crash_reports,
+            export_crash_report,
+            backup_database,
+            restore_database,
+            check_database_integrity,
+            list_database_backups,
             // Agent binary commands
             detect_agent_binaries,
             get_agent_binary_config,
@@ -1002,6 +1150,7 @@ fn main() {
             get_mcp_status,
             configure_mcp_for_project,
             remove_mcp_for_project,
+            sync_mcp_configs,
             ensure_mcp_gitignored,
             get_amp_mcp_servers,
             set_amp_mcp_servers
@@ -1011,6 +1160,12 @@ fn main() {
             let services = ServiceHandles::new(Arc::clone(&project_manager), app.handle().clone());
             app.manage(services);
 
+            // Session state events are kept in the app database so API clients can replay them
+            match open_global_app_config_db() {
+                Ok(db) => schaltwerk::infrastructure::events::configure_event_log(db),
+                Err(e) => log::warn!("Event replay is unavailable: {e}"),
+            }
+
             // Get current git branch and update window title asynchronously
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -1095,16 +1250,28 @@ fn main() {
                         let _ = SETTINGS_MANAGER.set(arc_mgr.clone());
                         log::info!("Settings manager initialized successfully");
 
-                        // Propagate terminal shell preferences to the domain layer and schedule updater
-                        let (auto_update_enabled, shell, args) = {
+                        // Propagate terminal and session preferences to the domain layer and schedule updater
+                        let (auto_update_enabled, terminal, preferences, json_logs) = {
                             let mgr = arc_mgr.lock().await;
-                            let term = mgr.get_terminal_settings();
-                            let shell = term.shell.unwrap_or_else(|| {
-                                std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
-                            });
-                            (mgr.get_auto_update_enabled(), shell, term.shell_args)
+                            (
+                                mgr.get_auto_update_enabled(),
+                                mgr.get_terminal_settings(),
+                                mgr.get_session_preferences(),
+                                mgr.get_json_logs(),
+                            )
                         };
-                        schaltwerk::domains::terminal::put_terminal_shell_override(shell, args);
+                        apply_settings_change(&SettingsChange::Terminal {
+                            terminal,
+                            shell_changed: false,
+                        });
+                        apply_settings_change(&SettingsChange::SessionPreferences { preferences });
+                        apply_settings_change(&SettingsChange::JsonLogs { enabled: json_logs });
+
+                        let custom_agents = arc_mgr.lock().await.get_custom_agents();
+                        schaltwerk::domains::agents::custom::register_custom_agents(
+                            schaltwerk::domains::agents::custom::CustomAgentSource::Settings,
+                            custom_agents,
+                        );
 
                         let updater_handle = settings_handle.clone();
                         tauri::async_runtime::spawn(async move {
@@ -1141,6 +1308,8 @@ fn main() {
                             Ok(core) => {
                                 let db = Arc::new(core.db.clone());
                                 schaltwerk::domains::sessions::activity::start_activity_tracking_with_app(db, activity_handle.clone());
+                                start_database_backups();
+                                start_agent_crash_supervisor(activity_handle.clone());
                                 break;
                             }
                             Err(e) => {
@@ -1151,6 +1320,42 @@ fn main() {
                     }
                 });
 
+                // Start the trackers of every project once its core is initialized
+                let tracker_handle = app_handle.clone();
+                get_project_manager()
+                    .await
+                    .on_project_initialized(Arc::new(move |project: Arc<Project>| {
+                        let app = tracker_handle.clone();
+                        tokio::spawn(async move {
+                            let (db, repo_path) = {
+                                let core = project.schaltwerk_core.read().await;
+                                (Arc::new(core.db.clone()), core.repo_path.clone())
+                            };
+                            let terminals = project.terminal_manager.clone();
+                            project.track_background_task(
+                                schaltwerk::domains::sessions::activity::start_runtime_tracking(db.clone(), terminals.clone()),
+                            );
+                            project.track_background_task(
+                                schaltwerk::domains::sessions::activity::start_usage_tracking(db.clone(), terminals.clone()),
+                            );
+                            project.track_background_task(
+                                schaltwerk::domains::git::service::start_base_freshness_tracking(db.clone(), app.clone()),
+                            );
+                            project.track_background_task(
+                                schaltwerk::domains::sessions::activity::start_auto_nudge(db.clone(), terminals.clone()),
+                            );
+                            project.track_background_task(start_stale_session_cleanup(
+                                app.clone(),
+                                project.schaltwerk_core.clone(),
+                                terminals.clone(),
+                            ));
+                            project.track_background_task(follow_ups::start_follow_up_delivery(
+                                db, repo_path, terminals, app,
+                            ));
+                        });
+                    }))
+                    .await;
+
                 // Start webhook server for MCP notifications
                 let webhook_handle = app_handle.clone();
                 tokio::spawn(async move {
@@ -1,36 +1,82 @@
+use futures::{SinkExt, StreamExt};
 use http_body_util::BodyExt;
 use hyper::{body::Incoming, Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
 use log::{error, info, warn};
+use schemars::JsonSchema;
 use serde::Serialize;
 use url::form_urlencoded;
 
 use crate::commands::github::{github_create_reviewed_pr, CreateReviewedPrArgs};
+use crate::commands::schaltwerk_core::events::emit_session_added;
 use crate::commands::schaltwerk_core::{
     merge_session_with_events, schaltwerk_core_cancel_session, MergeCommandError,
 };
 use crate::commands::sessions_refresh::{request_sessions_refresh, SessionsRefreshReason};
 use crate::mcp_api::diff_api::{DiffApiError, DiffChunkRequest, DiffScope, SummaryQuery};
+use crate::mcp_api::session_create::{
+    validate_create_request, CreateSessionRequest, SessionCreateError, SessionCreateErrorCode,
+};
 use crate::{get_core_read, get_core_write};
+use schaltwerk::api::mcp::auth;
 use schaltwerk::domains::merge::MergeMode;
-use schaltwerk::domains::sessions::entity::Session;
-use schaltwerk::infrastructure::events::{emit_event, SchaltEvent};
+use schaltwerk::domains::sessions::entity::{
+    Session, SpecChange, SpecEdit, SpecRevision, SpecRevisionError,
+};
+use schaltwerk::infrastructure::events::outbound::{notify_outbound, OutboundEvent};
+use schaltwerk::infrastructure::events::{
+    emit_event, subscribe_events, EventReplay, SchaltEvent, StreamedEvent,
+};
 use schaltwerk::schaltwerk_core::{SessionManager, SessionState};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
 
 mod diff_api;
+pub mod limits;
+mod openapi;
+mod query;
+mod session_create;
+pub mod webhook;
 
 pub async fn handle_mcp_request(
     req: Request<Incoming>,
     app: tauri::AppHandle,
 ) -> Result<Response<String>, hyper::Error> {
+    // The schema holds nothing project specific, so tools can fetch it without a token
+    if req.method() == Method::GET && req.uri().path() == "/api/openapi.json" {
+        return Ok(json_response(
+            StatusCode::OK,
+            openapi::openapi_document().to_string(),
+        ));
+    }
+
+    if !is_authorized_request(&req) {
+        return Ok(unauthorized_response());
+    }
+
     let method = req.method().clone();
     let path = req.uri().path().to_string();
 
     match (&method, path.as_str()) {
+        (&Method::GET, "/api/events") => event_stream(req).await,
+        (&Method::GET, "/api/events/replay") => replay_events(req).await,
         (&Method::GET, "/api/diff/summary") => diff_summary(req).await,
         (&Method::GET, "/api/diff/file") => diff_chunk(req).await,
         (&Method::POST, "/api/specs") => create_draft(req, app).await,
         (&Method::GET, "/api/specs") => list_drafts().await,
         (&Method::GET, "/api/specs/summary") => list_spec_summaries().await,
+        (&Method::GET, path) if path.starts_with("/api/specs/") && path.ends_with("/revisions") => {
+            let name = extract_spec_name_for_action(path, "/revisions");
+            list_spec_revisions(&name).await
+        }
+        (&Method::POST, path)
+            if path.starts_with("/api/specs/") && path.ends_with("/revisions") =>
+        {
+            let name = extract_spec_name_for_action(path, "/revisions");
+            propose_spec_revision(req, &name, app).await
+        }
         (&Method::GET, path) if path.starts_with("/api/specs/") && !path.ends_with("/start") => {
             let name = extract_draft_name(path, "/api/specs/");
             get_spec_content(&name).await
@@ -53,6 +99,7 @@ pub async fn handle_mcp_request(
             get_session_spec(&name).await
         }
         (&Method::GET, "/api/sessions") => list_sessions(req).await,
+        (&Method::POST, "/api/query") => query_sessions(req).await,
         (&Method::GET, path) if path.starts_with("/api/sessions/") => {
             let name = extract_session_name(path);
             get_session(&name).await
@@ -75,7 +122,7 @@ pub async fn handle_mcp_request(
             if path.starts_with("/api/sessions/") && path.ends_with("/mark-reviewed") =>
         {
             let name = extract_session_name_for_action(path, "/mark-reviewed");
-            mark_session_reviewed(&name, app).await
+            mark_session_reviewed(req, &name, app).await
         }
         (&Method::POST, path)
             if path.starts_with("/api/sessions/") && path.ends_with("/convert-to-spec") =>
@@ -98,9 +145,12 @@ fn extract_draft_name(path: &str, prefix: &str) -> String {
 }
 
 fn extract_draft_name_for_start(path: &str) -> String {
+    extract_spec_name_for_action(path, "/start")
+}
+
+fn extract_spec_name_for_action(path: &str, action: &str) -> String {
     let prefix = "/api/specs/";
-    let suffix = "/start";
-    let name = &path[prefix.len()..path.len() - suffix.len()];
+    let name = &path[prefix.len()..path.len() - action.len()];
     urlencoding::decode(name)
         .unwrap_or(std::borrow::Cow::Borrowed(name))
         .to_string()
@@ -123,6 +173,188 @@ fn extract_session_name_for_action(path: &str, action: &str) -> String {
         .to_string()
 }
 
+/// Upgrade to a WebSocket that sends every streamed `SchaltEvent` as a JSON text
+/// message `{ "event": ..., "payload": ... }`.
+async fn event_stream(mut req: Request<Incoming>) -> Result<Response<String>, hyper::Error> {
+    let is_upgrade = request_header(&req, "Upgrade")
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    let Some(key) = request_header(&req, "Sec-WebSocket-Key").filter(|_| is_upgrade) else {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Expected a WebSocket upgrade request".to_string(),
+        ));
+    };
+    let accept_key = derive_accept_key(key.as_bytes());
+
+    // Subscribe before answering so no event between handshake and upgrade is lost
+    let receiver = subscribe_events();
+    let upgrade = hyper::upgrade::on(&mut req);
+    tokio::spawn(async move {
+        match upgrade.await {
+            Ok(upgraded) => {
+                let socket =
+                    WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None)
+                        .await;
+                forward_events(socket, receiver).await;
+            }
+            Err(e) => warn!("WebSocket upgrade for /api/events failed: {e}"),
+        }
+    });
+
+    let mut response = Response::new(String::new());
+    *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let headers = response.headers_mut();
+    headers.insert("Connection", "Upgrade".parse().unwrap());
+    headers.insert("Upgrade", "websocket".parse().unwrap());
+    headers.insert("Sec-WebSocket-Accept", accept_key.parse().unwrap());
+    Ok(response)
+}
+
+async fn forward_events<S>(
+    socket: WebSocketStream<S>,
+    mut receiver: broadcast::Receiver<StreamedEvent>,
+) where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut sink, mut incoming) = socket.split();
+    info!("Event stream client connected");
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let text = match event {
+                    Ok(event) => serde_json::to_string(&event).unwrap_or_default(),
+                    // A slow client missed events; tell it to refetch state instead
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => serde_json::json!({
+                        "event": "schaltwerk:events-lagged",
+                        "payload": { "skipped": skipped },
+                    })
+                    .to_string(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if sink.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+    info!("Event stream client disconnected");
+}
+
+/// Logged events returned by one `/api/events/replay` request when no `limit` is given
+const DEFAULT_REPLAY_LIMIT: usize = 500;
+const MAX_REPLAY_LIMIT: usize = 2_000;
+
+async fn replay_events(req: Request<Incoming>) -> Result<Response<String>, hyper::Error> {
+    let query = req.uri().query().unwrap_or("");
+    let mut since_seq = 0;
+    let mut limit = DEFAULT_REPLAY_LIMIT;
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            "since_seq" => match value.parse::<u64>() {
+                Ok(seq) => since_seq = seq,
+                Err(_) => {
+                    return Ok(bad_request_response(format!(
+                        "since_seq must be a non-negative integer, got '{value}'"
+                    )))
+                }
+            },
+            "limit" => match value.parse::<usize>() {
+                Ok(value) if value > 0 => limit = value.min(MAX_REPLAY_LIMIT),
+                _ => {
+                    return Ok(bad_request_response(format!(
+                        "limit must be a positive integer, got '{value}'"
+                    )))
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let replay: EventReplay =
+        match schaltwerk::infrastructure::events::replay_events(since_seq, limit) {
+            Ok(replay) => replay,
+            Err(e) => {
+                error!("Failed to replay events: {e}");
+                return Ok(error_response(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    format!("Failed to replay events: {e}"),
+                ));
+            }
+        };
+    match serde_json::to_string(&replay) {
+        Ok(json) => Ok(json_response(StatusCode::OK, json)),
+        Err(e) => Ok(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to serialize events: {e}"),
+        )),
+    }
+}
+
+fn request_header<'a, B>(req: &'a Request<B>, name: &str) -> Option<&'a str> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Whether the request carries the bearer token issued for its project.
+pub fn is_authorized_request<B>(req: &Request<B>) -> bool {
+    auth::is_authorized(
+        request_header(req, auth::PROJECT_PATH_HEADER),
+        request_header(req, "Authorization"),
+    )
+}
+
+/// Like [`is_authorized_request`], but the token has to be the given project's
+pub fn is_authorized_for_project<B>(req: &Request<B>, project_path: &std::path::Path) -> bool {
+    auth::is_authorized(
+        Some(&project_path.to_string_lossy()),
+        request_header(req, "Authorization"),
+    )
+}
+
+pub fn unauthorized_response() -> Response<String> {
+    warn!("Rejected MCP request without a valid token");
+    error_response(
+        StatusCode::UNAUTHORIZED,
+        format!(
+            "Missing or invalid MCP token. Reconfigure the Schaltwerk MCP server so {} is set.",
+            auth::MCP_TOKEN_ENV
+        ),
+    )
+}
+
+pub fn bad_request_response(message: String) -> Response<String> {
+    error_response(StatusCode::BAD_REQUEST, message)
+}
+
+pub fn session_not_found_response(session_name: &str) -> Response<String> {
+    error_response(
+        StatusCode::NOT_FOUND,
+        format!("Session '{session_name}' not found"),
+    )
+}
+
+pub fn payload_too_large_response(message: String) -> Response<String> {
+    warn!("Rejected oversized MCP request: {message}");
+    error_response(StatusCode::PAYLOAD_TOO_LARGE, message)
+}
+
+pub fn too_many_requests_response(retry_after: std::time::Duration) -> Response<String> {
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let mut response = error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        format!("Too many requests; retry in {seconds}s"),
+    );
+    response
+        .headers_mut()
+        .insert("Retry-After", seconds.to_string().parse().unwrap());
+    response
+}
+
 fn not_found_response() -> Response<String> {
     let mut response = Response::new("Not Found".to_string());
     *response.status_mut() = StatusCode::NOT_FOUND;
@@ -443,6 +675,8 @@ mod tests {
             session_state: SessionState::Spec,
             resume_allowed: false,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         }
     }
 
@@ -505,12 +739,103 @@ mod tests {
     #[test]
     fn spec_content_response_defaults_to_empty_when_missing() {
         let session = make_spec_session("beta", None);
-        let response = SpecContentResponse::from_session(&session);
+        let response = SpecContentResponse::from_session(&session, 1);
         assert_eq!(response.session_id, "beta");
+        assert_eq!(response.revision, 1);
         assert_eq!(response.display_name.as_deref(), Some("Display beta"));
         assert_eq!(response.content, "");
         assert_eq!(response.content_length, 0);
     }
+
+    #[test]
+    fn session_creation_requests_are_validated_with_error_codes() {
+        let (_tmp, repo_path) = init_test_repo();
+        let request = |body: serde_json::Value| -> CreateSessionRequest {
+            serde_json::from_value(body).unwrap()
+        };
+        let code = |body: serde_json::Value| {
+            validate_create_request(&request(body), &repo_path)
+                .unwrap_err()
+                .code
+        };
+
+        let head = Repository::open(&repo_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .shorthand()
+            .unwrap()
+            .to_string();
+        let valid = request(serde_json::json!({
+            "name": "fix-login",
+            "base_branch": head,
+            "agent_type": "codex",
+            "agent_model": "o3",
+            "version_group_id": "group-1",
+            "version_number": 2
+        }));
+        assert!(validate_create_request(&valid, &repo_path).is_ok());
+
+        assert_eq!(
+            code(serde_json::json!({ "name": "fix login" })),
+            SessionCreateErrorCode::InvalidName
+        );
+        assert_eq!(
+            code(serde_json::json!({ "name": "fix-login", "custom_branch": "feature//x" })),
+            SessionCreateErrorCode::InvalidBranchName
+        );
+        assert_eq!(
+            code(serde_json::json!({ "name": "fix-login", "base_branch": "does-not-exist" })),
+            SessionCreateErrorCode::UnknownBaseBranch
+        );
+        assert_eq!(
+            code(serde_json::json!({ "name": "fix-login", "agent_type": "skynet" })),
+            SessionCreateErrorCode::UnknownAgentType
+        );
+        assert_eq!(
+            code(serde_json::json!({ "name": "fix-login", "agent_model": "gpt 4" })),
+            SessionCreateErrorCode::InvalidModel
+        );
+        assert_eq!(
+            code(serde_json::json!({
+                "name": "fix-login",
+                "agent_type": "amp",
+                "agent_model": "opus"
+            })),
+            SessionCreateErrorCode::InvalidModel
+        );
+        assert_eq!(
+            code(serde_json::json!({ "name": "fix-login", "version_number": 1 })),
+            SessionCreateErrorCode::InvalidVersion
+        );
+
+        let error = validate_create_request(
+            &request(serde_json::json!({ "name": "fix-login", "base_branch": "nope" })),
+            &repo_path,
+        )
+        .unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "unknown_base_branch",
+                "error": "Base branch 'nope' does not exist in the repository",
+                "field": "base_branch"
+            })
+        );
+    }
+}
+
+#[derive(Debug, serde::Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct CreateSpecRequest {
+    name: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    agent_type: Option<String>,
+    #[serde(default)]
+    skip_permissions: Option<bool>,
 }
 
 async fn create_draft(
@@ -519,7 +844,7 @@ async fn create_draft(
 ) -> Result<Response<String>, hyper::Error> {
     let body = req.into_body();
     let body_bytes = body.collect().await?.to_bytes();
-    let payload: serde_json::Value = match serde_json::from_slice(&body_bytes) {
+    let payload: CreateSpecRequest = match serde_json::from_slice(&body_bytes) {
         Ok(p) => p,
         Err(e) => {
             error!("Failed to parse spec creation request: {e}");
@@ -530,18 +855,10 @@ async fn create_draft(
         }
     };
 
-    let name = match payload["name"].as_str() {
-        Some(n) => n,
-        None => {
-            return Ok(error_response(
-                StatusCode::BAD_REQUEST,
-                "Missing 'name' field".to_string(),
-            ));
-        }
-    };
-    let content = payload["content"].as_str().unwrap_or("");
-    let agent_type = payload["agent_type"].as_str();
-    let skip_permissions = payload["skip_permissions"].as_bool();
+    let name = payload.name.as_str();
+    let content = payload.content.as_str();
+    let agent_type = payload.agent_type.as_deref();
+    let skip_permissions = payload.skip_permissions;
 
     let manager = match get_core_write().await {
         Ok(core) => core.session_manager(),
@@ -582,12 +899,12 @@ async fn create_draft(
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct SpecSummaryResponse {
     specs: Vec<SpecSummary>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct SpecSummary {
     session_id: String,
     display_name: Option<String>,
@@ -595,13 +912,15 @@ struct SpecSummary {
     updated_at: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 struct SpecContentResponse {
     session_id: String,
     display_name: Option<String>,
     content: String,
     content_length: usize,
     updated_at: String,
+    /// Pass as `base_revision` when proposing a revision of this content
+    revision: u32,
 }
 
 impl SpecSummary {
@@ -621,7 +940,7 @@ impl SpecSummary {
 }
 
 impl SpecContentResponse {
-    fn from_session(session: &Session) -> Self {
+    fn from_session(session: &Session, revision: u32) -> Self {
         let content = session.spec_content.clone().unwrap_or_default();
         let content_length = content.chars().count();
         Self {
@@ -630,6 +949,7 @@ impl SpecContentResponse {
             content,
             content_length,
             updated_at: session.updated_at.to_rfc3339(),
+            revision,
         }
     }
 }
@@ -734,7 +1054,17 @@ async fn get_spec_content(name: &str) -> Result<Response<String>, hyper::Error>
         ));
     }
 
-    let payload = SpecContentResponse::from_session(&session);
+    let revision = match manager.current_spec_revision(&session.name) {
+        Ok(revision) => revision,
+        Err(e) => {
+            error!("Failed to read revision of spec '{name}': {e}");
+            return Ok(json_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read spec revision: {e}"),
+            ));
+        }
+    };
+    let payload = SpecContentResponse::from_session(&session, revision);
     match serde_json::to_string(&payload) {
         Ok(json) => Ok(json_response(StatusCode::OK, json)),
         Err(e) => {
@@ -747,6 +1077,15 @@ async fn get_spec_content(name: &str) -> Result<Response<String>, hyper::Error>
     }
 }
 
+#[derive(Debug, serde::Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct UpdateSpecRequest {
+    content: String,
+    /// Append to the current spec instead of replacing it
+    #[serde(default)]
+    append: bool,
+}
+
 async fn update_spec_content(
     req: Request<Incoming>,
     name: &str,
@@ -754,7 +1093,7 @@ async fn update_spec_content(
 ) -> Result<Response<String>, hyper::Error> {
     let body = req.into_body();
     let body_bytes = body.collect().await?.to_bytes();
-    let payload: serde_json::Value = match serde_json::from_slice(&body_bytes) {
+    let payload: UpdateSpecRequest = match serde_json::from_slice(&body_bytes) {
         Ok(p) => p,
         Err(e) => {
             error!("Failed to parse spec update request: {e}");
@@ -765,17 +1104,8 @@ async fn update_spec_content(
         }
     };
 
-    let content = match payload["content"].as_str() {
-        Some(c) => c,
-        None => {
-            return Ok(error_response(
-                StatusCode::BAD_REQUEST,
-                "Missing 'content' field".to_string(),
-            ));
-        }
-    };
-
-    let append = payload["append"].as_bool().unwrap_or(false);
+    let content = payload.content.as_str();
+    let append = payload.append;
 
     let manager = match get_core_write().await {
         Ok(core) => core.session_manager(),
@@ -814,6 +1144,195 @@ async fn update_spec_content(
     }
 }
 
+#[derive(Debug, Serialize, JsonSchema)]
+struct SpecRevisionResponse {
+    revision: u32,
+    content: String,
+    /// `editor` for content written in the app, `api` for accepted proposals
+    source: String,
+    summary: Option<String>,
+    created_at: String,
+}
+
+impl From<SpecRevision> for SpecRevisionResponse {
+    fn from(revision: SpecRevision) -> Self {
+        Self {
+            revision: revision.revision,
+            content: revision.content,
+            source: revision.source.as_str().to_string(),
+            summary: revision.summary,
+            created_at: revision.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct SpecRevisionListResponse {
+    revisions: Vec<SpecRevisionResponse>,
+}
+
+#[derive(Debug, serde::Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct ProposeSpecRevisionRequest {
+    /// The `revision` the proposal was made against; the proposal is refused with `409`
+    /// when the spec changed since. Omit to apply to whatever is current.
+    #[serde(default)]
+    base_revision: Option<u32>,
+    /// Search-and-replace edits applied in order; each `find` must occur exactly once
+    #[serde(default)]
+    edits: Vec<SpecEdit>,
+    /// Full new content, instead of `edits`
+    #[serde(default)]
+    content: Option<String>,
+    /// What the revision changes, kept in the history
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+async fn list_spec_revisions(name: &str) -> Result<Response<String>, hyper::Error> {
+    let manager = match get_core_read().await {
+        Ok(core) => core.session_manager(),
+        Err(e) => {
+            error!("Failed to get core for spec revisions: {e}");
+            return Ok(json_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Internal error: {e}"),
+            ));
+        }
+    };
+
+    if manager.get_session(name).is_err() {
+        return Ok(json_error_response(
+            StatusCode::NOT_FOUND,
+            format!("Spec '{name}' not found"),
+        ));
+    }
+
+    match manager.list_spec_revisions(name) {
+        Ok(revisions) => {
+            let payload = SpecRevisionListResponse {
+                revisions: revisions.into_iter().map(Into::into).collect(),
+            };
+            match serde_json::to_string(&payload) {
+                Ok(json) => Ok(json_response(StatusCode::OK, json)),
+                Err(e) => {
+                    error!("Failed to serialize spec revisions: {e}");
+                    Ok(json_error_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to serialize spec revisions: {e}"),
+                    ))
+                }
+            }
+        }
+        Err(e) => {
+            error!("Failed to list revisions of spec '{name}': {e}");
+            Ok(json_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list spec revisions: {e}"),
+            ))
+        }
+    }
+}
+
+async fn propose_spec_revision(
+    req: Request<Incoming>,
+    name: &str,
+    app: tauri::AppHandle,
+) -> Result<Response<String>, hyper::Error> {
+    let body_bytes = req.into_body().collect().await?.to_bytes();
+    let payload: ProposeSpecRevisionRequest = match serde_json::from_slice(&body_bytes) {
+        Ok(p) => p,
+        Err(e) => {
+            return Ok(json_error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid JSON payload: {e}"),
+            ))
+        }
+    };
+    let change = match (payload.content, payload.edits.is_empty()) {
+        (Some(content), true) => SpecChange::Content(content),
+        (None, false) => SpecChange::Edits(payload.edits),
+        _ => {
+            return Ok(json_error_response(
+                StatusCode::BAD_REQUEST,
+                "Send either `content` or `edits`".to_string(),
+            ))
+        }
+    };
+
+    let manager = match get_core_write().await {
+        Ok(core) => core.session_manager(),
+        Err(e) => {
+            error!("Failed to get core for spec revision: {e}");
+            return Ok(json_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Internal error: {e}"),
+            ));
+        }
+    };
+
+    match manager.get_session(name) {
+        Ok(session) if session.session_state == SessionState::Spec => {}
+        Ok(_) => {
+            return Ok(json_error_response(
+                StatusCode::NOT_FOUND,
+                format!("Spec '{name}' is not available in spec state"),
+            ))
+        }
+        Err(_) => {
+            return Ok(json_error_response(
+                StatusCode::NOT_FOUND,
+                format!("Spec '{name}' not found"),
+            ))
+        }
+    }
+
+    match manager.revise_spec_content(name, payload.base_revision, change, payload.summary) {
+        Ok(revision) => {
+            info!(
+                "Stored revision {} of spec '{name}' via API",
+                revision.revision
+            );
+            request_sessions_refresh(&app, SessionsRefreshReason::SpecSync);
+            let response = SpecRevisionResponse::from(revision);
+            match serde_json::to_string(&response) {
+                Ok(json) => Ok(json_response(StatusCode::CREATED, json)),
+                Err(e) => {
+                    error!("Failed to serialize spec revision: {e}");
+                    Ok(json_error_response(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to serialize spec revision: {e}"),
+                    ))
+                }
+            }
+        }
+        Err(e) => {
+            let status = match e.downcast_ref::<SpecRevisionError>() {
+                Some(SpecRevisionError::Conflict { .. }) => StatusCode::CONFLICT,
+                Some(SpecRevisionError::InvalidEdit(_)) => StatusCode::UNPROCESSABLE_ENTITY,
+                None => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            warn!("Refused revision of spec '{name}': {e}");
+            Ok(json_error_response(status, e.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct StartSpecRequest {
+    #[serde(default)]
+    base_branch: Option<String>,
+    #[serde(default)]
+    agent_type: Option<String>,
+    #[serde(default)]
+    skip_permissions: Option<bool>,
+    #[serde(default)]
+    version_group_id: Option<String>,
+    #[serde(default)]
+    version_number: Option<i32>,
+}
+
 async fn start_spec_session(
     req: Request<Incoming>,
     name: &str,
@@ -821,7 +1340,7 @@ async fn start_spec_session(
 ) -> Result<Response<String>, hyper::Error> {
     let body = req.into_body();
     let body_bytes = body.collect().await?.to_bytes();
-    let payload: serde_json::Value = match serde_json::from_slice(&body_bytes) {
+    let payload: StartSpecRequest = match serde_json::from_slice(&body_bytes) {
         Ok(p) => p,
         Err(e) => {
             error!("Failed to parse start draft session request: {e}");
@@ -832,12 +1351,6 @@ async fn start_spec_session(
         }
     };
 
-    let base_branch = payload["base_branch"].as_str().map(|s| s.to_string());
-    let agent_type = payload["agent_type"].as_str();
-    let skip_permissions = payload["skip_permissions"].as_bool();
-    let version_group_id = payload["version_group_id"].as_str().map(|s| s.to_string());
-    let version_number = payload["version_number"].as_i64().map(|n| n as i32);
-
     let manager = match get_core_write().await {
         Ok(core) => core.session_manager(),
         Err(e) => {
@@ -852,11 +1365,11 @@ async fn start_spec_session(
     // Use the manager method that encapsulates all configuration and session starting logic
     match manager.start_spec_session_with_config(
         name,
-        base_branch.as_deref(),
-        version_group_id.as_deref(),
-        version_number,
-        agent_type,
-        skip_permissions,
+        payload.base_branch.as_deref(),
+        payload.version_group_id.as_deref(),
+        payload.version_number,
+        payload.agent_type.as_deref(),
+        payload.skip_permissions,
     ) {
         Ok(()) => {
             info!("Started spec session via API: {name}");
@@ -919,35 +1432,31 @@ async fn create_session(
 ) -> Result<Response<String>, hyper::Error> {
     let body = req.into_body();
     let body_bytes = body.collect().await?.to_bytes();
-    let payload: serde_json::Value = match serde_json::from_slice(&body_bytes) {
+    let payload: CreateSessionRequest = match serde_json::from_slice(&body_bytes) {
         Ok(p) => p,
         Err(e) => {
             error!("Failed to parse session creation request: {e}");
-            return Ok(error_response(
-                StatusCode::BAD_REQUEST,
+            return Ok(session_create_error_response(SessionCreateError::new(
+                SessionCreateErrorCode::InvalidJson,
+                None,
                 format!("Invalid JSON: {e}"),
-            ));
+            )));
         }
     };
 
-    let name = match payload["name"].as_str() {
-        Some(n) => n,
-        None => {
-            return Ok(error_response(
-                StatusCode::BAD_REQUEST,
-                "Missing 'name' field".to_string(),
-            ));
-        }
-    };
-    let prompt = payload["prompt"].as_str().map(|s| s.to_string());
-    let base_branch = payload["base_branch"].as_str().map(|s| s.to_string());
-    let custom_branch = payload["custom_branch"].as_str().map(|s| s.to_string());
-    let user_edited_name = payload["user_edited_name"].as_bool();
-    let agent_type = payload["agent_type"].as_str().map(|s| s.to_string());
-    let skip_permissions = payload["skip_permissions"].as_bool();
+    let name = payload.name.as_str();
 
     let manager = match get_core_write().await {
-        Ok(core) => core.session_manager(),
+        Ok(core) => {
+            if let Err(e) = validate_create_request(&payload, &core.repo_path) {
+                warn!(
+                    "Rejected session creation request for '{name}': {}",
+                    e.error
+                );
+                return Ok(session_create_error_response(e));
+            }
+            core.session_manager()
+        }
         Err(e) => {
             error!("Failed to get para core: {e}");
             return Ok(error_response(
@@ -958,26 +1467,28 @@ async fn create_session(
     };
 
     let looks_docker_style = name.contains('_') && name.split('_').count() == 2;
-    let was_user_edited = user_edited_name.unwrap_or(false);
+    let was_user_edited = payload.user_edited_name.unwrap_or(false);
     let was_auto_generated = looks_docker_style && !was_user_edited;
 
     use schaltwerk::domains::sessions::service::SessionCreationParams;
 
     let params = SessionCreationParams {
         name,
-        prompt: prompt.as_deref(),
-        base_branch: base_branch.as_deref(),
-        custom_branch: custom_branch.as_deref(),
+        prompt: payload.prompt.as_deref(),
+        base_branch: payload.base_branch.as_deref(),
+        custom_branch: payload.custom_branch.as_deref(),
         was_auto_generated,
-        version_group_id: None,
-        version_number: None,
-        agent_type: agent_type.as_deref(),
-        skip_permissions,
+        version_group_id: payload.version_group_id.as_deref(),
+        version_number: payload.version_number,
+        agent_type: payload.agent_type.as_deref(),
+        skip_permissions: payload.skip_permissions,
+        agent_model: payload.agent_model.as_deref(),
     };
 
     match manager.create_session_with_agent(params) {
         Ok(session) => {
             info!("Created session via API: {name}");
+            emit_session_added(&app, &session);
             request_sessions_refresh(&app, SessionsRefreshReason::SessionLifecycle);
 
             let json = serde_json::to_string(&session).unwrap_or_else(|e| {
@@ -989,14 +1500,20 @@ async fn create_session(
         }
         Err(e) => {
             error!("Failed to create session: {e}");
-            Ok(error_response(
-                StatusCode::INTERNAL_SERVER_ERROR,
+            Ok(session_create_error_response(SessionCreateError::new(
+                SessionCreateErrorCode::CreationFailed,
+                None,
                 format!("Failed to create session: {e}"),
-            ))
+            )))
         }
     }
 }
 
+fn session_create_error_response(error: SessionCreateError) -> Response<String> {
+    let body = serde_json::to_string(&error).unwrap_or_else(|_| "{}".to_string());
+    json_response(error.status(), body)
+}
+
 async fn list_sessions(req: Request<Incoming>) -> Result<Response<String>, hyper::Error> {
     // Parse query parameters
     let query = req.uri().query().unwrap_or("");
@@ -1026,13 +1543,7 @@ async fn list_sessions(req: Request<Incoming>) -> Result<Response<String>, hyper
         Ok(mut sessions) => {
             // Apply filtering if requested
             if let Some(state) = filter_state {
-                sessions.retain(|s| match state {
-                    SessionState::Reviewed => s.info.ready_to_merge,
-                    SessionState::Running => {
-                        !s.info.ready_to_merge && s.info.session_state == SessionState::Running
-                    }
-                    SessionState::Spec => s.info.session_state == SessionState::Spec,
-                });
+                sessions.retain(|s| query::matches_state(s, &state));
             }
 
             let json = serde_json::to_string(&sessions).unwrap_or_else(|e| {
@@ -1051,6 +1562,55 @@ async fn list_sessions(req: Request<Incoming>) -> Result<Response<String>, hyper
     }
 }
 
+async fn query_sessions(req: Request<Incoming>) -> Result<Response<String>, hyper::Error> {
+    let body_bytes = req.into_body().collect().await?.to_bytes();
+    let payload: query::SessionQuery = match serde_json::from_slice(&body_bytes) {
+        Ok(p) => p,
+        Err(e) => {
+            return Ok(json_error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid JSON payload: {e}"),
+            ))
+        }
+    };
+
+    let manager = match get_core_read().await {
+        Ok(core) => core.session_manager(),
+        Err(e) => {
+            error!("Failed to get core for session query: {e}");
+            return Ok(json_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Internal error: {e}"),
+            ));
+        }
+    };
+
+    let sessions = match manager.list_enriched_sessions() {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            error!("Failed to list sessions for query: {e}");
+            return Ok(json_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list sessions: {e}"),
+            ));
+        }
+    };
+
+    match query::run_query(sessions, &payload) {
+        Ok(response) => match serde_json::to_string(&response) {
+            Ok(json) => Ok(json_response(StatusCode::OK, json)),
+            Err(e) => {
+                error!("Failed to serialize session query response: {e}");
+                Ok(json_error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to serialize sessions: {e}"),
+                ))
+            }
+        },
+        Err(message) => Ok(json_error_response(StatusCode::BAD_REQUEST, message)),
+    }
+}
+
 async fn get_session(name: &str) -> Result<Response<String>, hyper::Error> {
     let manager = match get_core_read().await {
         Ok(core) => core.session_manager(),
@@ -1081,7 +1641,7 @@ async fn get_session(name: &str) -> Result<Response<String>, hyper::Error> {
     }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 struct MergeSessionRequest {
     #[serde(default)]
@@ -1090,9 +1650,12 @@ struct MergeSessionRequest {
     commit_message: Option<String>,
     #[serde(default)]
     cancel_after_merge: bool,
+    /// Acknowledge changes to the project's protected paths
+    #[serde(default)]
+    confirm_protected_paths: bool,
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, JsonSchema)]
 struct MergeSessionResponse {
     session_name: String,
     parent_branch: String,
@@ -1155,18 +1718,25 @@ async fn merge_session(
     };
 
     let mode = payload.mode.unwrap_or(MergeMode::Squash);
-    let outcome =
-        match merge_session_with_events(&app, name, mode, payload.commit_message.clone()).await {
-            Ok(outcome) => outcome,
-            Err(MergeCommandError { message, conflict }) => {
-                let status = if conflict {
-                    StatusCode::CONFLICT
-                } else {
-                    StatusCode::BAD_REQUEST
-                };
-                return Ok(error_response(status, message));
-            }
-        };
+    let outcome = match merge_session_with_events(
+        &app,
+        name,
+        mode,
+        payload.commit_message.clone(),
+        payload.confirm_protected_paths,
+    )
+    .await
+    {
+        Ok(outcome) => outcome,
+        Err(MergeCommandError { message, conflict }) => {
+            let status = if conflict {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            return Ok(error_response(status, message));
+        }
+    };
 
     let mut cancel_error = None;
     let mut cancel_queued = false;
@@ -1202,7 +1772,7 @@ async fn merge_session(
     Ok(json_response(StatusCode::OK, json))
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 struct PullRequestRequest {
     #[serde(default)]
@@ -1215,7 +1785,7 @@ struct PullRequestRequest {
     cancel_after_pr: bool,
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, JsonSchema)]
 struct PullRequestResponse {
     session_name: String,
     branch: String,
@@ -1364,12 +1934,44 @@ async fn delete_session(
     }
 }
 
+#[derive(Debug, Default, serde::Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+struct MarkReviewedRequest {
+    /// Commit pending worktree changes before marking the session reviewed
+    #[serde(default)]
+    auto_commit: bool,
+    #[serde(default)]
+    commit_message: Option<String>,
+}
+
 async fn mark_session_reviewed(
+    req: Request<Incoming>,
     name: &str,
     app: tauri::AppHandle,
 ) -> Result<Response<String>, hyper::Error> {
-    let manager = match get_core_write().await {
-        Ok(core) => core.session_manager(),
+    // The body is optional; older clients send none
+    let body_bytes = req.into_body().collect().await?.to_bytes();
+    let payload: MarkReviewedRequest = if body_bytes.iter().all(u8::is_ascii_whitespace) {
+        MarkReviewedRequest::default()
+    } else {
+        match serde_json::from_slice(&body_bytes) {
+            Ok(p) => p,
+            Err(e) => {
+                return Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid JSON payload: {e}"),
+                ))
+            }
+        }
+    };
+    let commit_message = payload
+        .commit_message
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty());
+
+    let (manager, repo_path) = match get_core_write().await {
+        Ok(core) => (core.session_manager(), core.repo_path.clone()),
         Err(e) => {
             error!("Failed to get schaltwerk core: {e}");
             return Ok(error_response(
@@ -1380,9 +1982,15 @@ async fn mark_session_reviewed(
     };
 
     // Use the manager method that encapsulates all validation and business logic
-    match manager.mark_session_as_reviewed(name) {
+    match manager.mark_session_as_reviewed(name, payload.auto_commit, commit_message) {
         Ok(()) => {
             info!("Marked session '{name}' as reviewed via API");
+            notify_outbound(
+                &repo_path,
+                OutboundEvent::SessionReviewed,
+                name,
+                serde_json::json!({ "auto_commit": payload.auto_commit }),
+            );
             request_sessions_refresh(&app, SessionsRefreshReason::MergeWorkflow);
 
             Ok(Response::new("OK".to_string()))
@@ -7,6 +7,7 @@ use chrono::{DateTime, Utc};
 use git2::{Oid, Repository};
 use hyper::StatusCode;
 use log::info;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -17,7 +18,7 @@ use schaltwerk::binary_detection::{get_unsupported_reason, is_binary_file_by_ext
 use schaltwerk::domains::git;
 use schaltwerk::domains::sessions::entity::{ChangedFile, Session, SessionStatus};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum DiffScopeKind {
     Session,
@@ -103,7 +104,7 @@ pub struct SummaryQuery {
     pub page_size: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct DiffSummaryResponse {
     pub scope: DiffScopeKind,
     pub session_id: Option<String>,
@@ -113,7 +114,7 @@ pub struct DiffSummaryResponse {
     pub paging: PagingInfo,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct BranchInfo {
     pub current_branch: String,
     pub parent_branch: String,
@@ -121,7 +122,7 @@ pub struct BranchInfo {
     pub head_short: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct PagingInfo {
     pub next_cursor: Option<String>,
     pub total_files: usize,
@@ -134,7 +135,7 @@ pub struct DiffChunkRequest {
     pub line_limit: Option<usize>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
 pub struct DiffLineEntry {
     pub content: String,
     pub line_type: String,
@@ -144,7 +145,7 @@ pub struct DiffLineEntry {
     pub collapsed_count: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct DiffChunkResponse {
     pub file: ChangedFile,
     pub branch_info: BranchInfo,
@@ -154,23 +155,24 @@ pub struct DiffChunkResponse {
     pub paging: DiffChunkPaging,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct DiffStatsSummary {
     pub additions: u32,
     pub deletions: u32,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct DiffChunkPaging {
     pub cursor: Option<String>,
     pub next_cursor: Option<String>,
     pub returned: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct SessionSpecResponse {
     pub session_id: String,
     pub content: String,
+    #[schemars(with = "String")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -752,6 +754,8 @@ mod tests {
             session_state: SessionState::Running,
             resume_allowed: true,
             amp_thread_id: None,
+            labels: Vec::new(),
+            agent_model: None,
         }
     }
 
@@ -0,0 +1,154 @@
+//! Guards for the local HTTP server: a per-IP token bucket and a cap on request bodies,
+//! so an agent stuck in a loop can't flood the app with events or huge pastes.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use hyper::body::Body;
+use hyper::Request;
+
+/// Largest request body the server reads
+pub const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// Largest follow-up message pasted into an agent terminal
+pub const MAX_FOLLOW_UP_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Requests a client may send in a burst
+const BURST_REQUESTS: f64 = 120.0;
+
+/// Requests per second a client regains after a burst
+const REQUESTS_PER_SECOND: f64 = 40.0;
+
+/// Bucket count above which idle clients are forgotten
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+pub struct RateLimiter {
+    burst: f64,
+    per_second: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(BURST_REQUESTS, REQUESTS_PER_SECOND)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(burst: f64, per_second: f64) -> Self {
+        Self {
+            burst,
+            per_second,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for a request from `ip`. When the bucket is empty, returns how long
+    /// the client should wait before retrying.
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(&ip) {
+            let (burst, per_second) = (self.burst, self.per_second);
+            buckets.retain(|_, bucket| {
+                bucket.tokens + elapsed_secs(bucket.updated_at, now) * per_second < burst
+            });
+        }
+
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: self.burst,
+            updated_at: now,
+        });
+        let refilled = elapsed_secs(bucket.updated_at, now) * self.per_second;
+        bucket.tokens = (bucket.tokens + refilled).min(self.burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+fn elapsed_secs(since: Instant, now: Instant) -> f64 {
+    now.saturating_duration_since(since).as_secs_f64()
+}
+
+/// Why a request body can't be accepted, or `None` when it fits. Hyper never reads past
+/// a declared `Content-Length`, so bodies of unknown length are turned away as well.
+pub fn body_size_violation<B: Body>(req: &Request<B>) -> Option<String> {
+    match req.body().size_hint().upper() {
+        Some(len) if len <= MAX_BODY_BYTES => None,
+        Some(len) => Some(format!(
+            "Request body is {len} bytes; the limit is {MAX_BODY_BYTES} bytes"
+        )),
+        None => Some("Request body must have a Content-Length".to_string()),
+    }
+}
+
+/// Why a follow-up message is too large to paste, or `None` when it fits.
+pub fn follow_up_message_violation(message: &str) -> Option<String> {
+    (message.len() > MAX_FOLLOW_UP_MESSAGE_BYTES).then(|| {
+        format!(
+            "Follow-up message is {} bytes; the limit is {MAX_FOLLOW_UP_MESSAGE_BYTES} bytes",
+            message.len()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn bursts_are_limited_per_client_and_refill_over_time() {
+        let limiter = RateLimiter::new(3.0, 2.0);
+        let agent = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check(agent, start).is_ok());
+        }
+        let retry_after = limiter.check(agent, start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+        assert!(limiter.check(other, start).is_ok());
+
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check(agent, later).is_ok());
+        assert!(limiter.check(agent, later).is_err());
+    }
+
+    #[test]
+    fn oversized_bodies_and_messages_are_rejected() {
+        let small = Request::new(http_body_util::Full::new(hyper::body::Bytes::from("{}")));
+        assert!(body_size_violation(&small).is_none());
+
+        let large = Request::new(http_body_util::Full::new(hyper::body::Bytes::from(
+            vec![b'a'; MAX_BODY_BYTES as usize + 1],
+        )));
+        let violation = body_size_violation(&large).unwrap();
+        assert!(
+            violation.contains("the limit is 1048576 bytes"),
+            "{violation}"
+        );
+
+        assert!(follow_up_message_violation("continue").is_none());
+        let huge = "x".repeat(MAX_FOLLOW_UP_MESSAGE_BYTES + 1);
+        assert!(follow_up_message_violation(&huge).is_some());
+    }
+}
@@ -0,0 +1,593 @@
+//! OpenAPI document for the webhook and `/api/*` routes, served at `/api/openapi.json`.
+//! Body schemas are generated from the types the handlers parse and serialize.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+use super::diff_api::{DiffChunkResponse, DiffSummaryResponse, SessionSpecResponse};
+use super::query::{SessionQuery, SessionQueryResponse};
+use super::session_create::{CreateSessionRequest, SessionCreateError};
+use super::webhook::{
+    FollowUpMessageWebhook, SessionAddedWebhook, SessionRemovedWebhook, SpecCreatedWebhook,
+};
+use super::{
+    CreateSpecRequest, MarkReviewedRequest, MergeSessionRequest, MergeSessionResponse,
+    ProposeSpecRevisionRequest, PullRequestRequest, PullRequestResponse, SpecContentResponse,
+    SpecRevisionListResponse, SpecRevisionResponse, SpecSummaryResponse, StartSpecRequest,
+    UpdateSpecRequest,
+};
+
+struct ApiDoc {
+    generator: SchemaGenerator,
+    paths: Map<String, Value>,
+}
+
+impl ApiDoc {
+    fn new() -> Self {
+        Self {
+            generator: SchemaSettings::openapi3().into_generator(),
+            paths: Map::new(),
+        }
+    }
+
+    /// Schema for `T`; structs and enums become `$ref`s into `components/schemas`.
+    fn schema<T: JsonSchema>(&mut self) -> Value {
+        serde_json::to_value(self.generator.subschema_for::<T>()).unwrap_or_default()
+    }
+
+    fn add(&mut self, method: &str, path: &str, operation: Value) {
+        let item = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(|| json!({}));
+        item[method] = operation;
+    }
+
+    fn into_document(self) -> Value {
+        let mut schemas = serde_json::to_value(self.generator.definitions())
+            .ok()
+            .and_then(|value| value.as_object().cloned())
+            .unwrap_or_default();
+        // Session records come from the domain layer and are described only loosely
+        schemas.insert(
+            "Session".to_string(),
+            json!({ "type": "object", "description": "A session or spec as stored by Schaltwerk" }),
+        );
+        schemas.insert(
+            "EnrichedSession".to_string(),
+            json!({
+                "type": "object",
+                "description": "A session with its git stats, monitor status and terminals",
+                "properties": {
+                    "info": { "type": "object" },
+                    "status": { "type": "object", "nullable": true },
+                    "terminals": { "type": "array", "items": { "type": "string" } }
+                }
+            }),
+        );
+        schemas.insert(
+            "EventReplay".to_string(),
+            json!({
+                "type": "object",
+                "description": "Logged session state events after `since_seq`, oldest first",
+                "properties": {
+                    "events": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "seq": { "type": "integer" },
+                                "event": { "type": "string" },
+                                "payload": {},
+                                "created_at": { "type": "string", "format": "date-time" }
+                            }
+                        }
+                    },
+                    "latest_seq": { "type": "integer" },
+                    "has_more": { "type": "boolean" },
+                    "missed_events": {
+                        "type": "boolean",
+                        "description": "Events after `since_seq` were pruned; refetch state instead of replaying"
+                    }
+                }
+            }),
+        );
+
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "Schaltwerk local API",
+                "version": env!("CARGO_PKG_VERSION"),
+                "description": "Served on 127.0.0.1 for the MCP bridge and other local orchestrators."
+            },
+            "paths": self.paths,
+            "components": {
+                "schemas": schemas,
+                "securitySchemes": {
+                    "projectToken": {
+                        "type": "http",
+                        "scheme": "bearer",
+                        "description": "The project's token from SCHALTWERK_MCP_TOKEN. Send X-Project-Path to pick the project."
+                    }
+                }
+            },
+            "security": [{ "projectToken": [] }]
+        })
+    }
+}
+
+fn session_ref() -> Value {
+    json!({ "$ref": "#/components/schemas/Session" })
+}
+
+fn json_body(schema: Value) -> Value {
+    json!({ "required": true, "content": { "application/json": { "schema": schema } } })
+}
+
+fn json_content(description: &str, schema: Value) -> Value {
+    json!({ "description": description, "content": { "application/json": { "schema": schema } } })
+}
+
+fn error_object() -> Value {
+    json!({ "type": "object", "properties": { "error": { "type": "string" } } })
+}
+
+fn text_content(description: &str) -> Value {
+    json!({ "description": description, "content": { "text/plain": { "schema": { "type": "string" } } } })
+}
+
+/// `responses` for an operation: the given success responses plus the shared error cases.
+fn responses(success: &[(&str, Value)]) -> Value {
+    let mut responses = Map::new();
+    for (status, response) in success {
+        responses.insert(status.to_string(), response.clone());
+    }
+    responses.insert(
+        "401".to_string(),
+        text_content("Missing or invalid project token"),
+    );
+    responses.insert("default".to_string(), text_content("Error message"));
+    Value::Object(responses)
+}
+
+fn name_param(description: &str) -> Value {
+    json!({
+        "name": "name",
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": { "type": "string" }
+    })
+}
+
+fn query_param(name: &str, description: &str, required: bool, schema: Value) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": required,
+        "description": description,
+        "schema": schema
+    })
+}
+
+pub fn openapi_document() -> Value {
+    let mut doc = ApiDoc::new();
+    let ok = || text_content("OK");
+
+    let webhooks = [
+        (
+            "/projects/{hash}/webhook/session-added",
+            "The bridge created a session",
+            doc.schema::<SessionAddedWebhook>(),
+        ),
+        (
+            "/projects/{hash}/webhook/session-removed",
+            "The bridge removed a session",
+            doc.schema::<SessionRemovedWebhook>(),
+        ),
+        (
+            "/projects/{hash}/webhook/follow-up-message",
+            "Queue a follow-up message for the session's agent terminal; `202` while it waits for the terminal",
+            doc.schema::<FollowUpMessageWebhook>(),
+        ),
+        (
+            "/projects/{hash}/webhook/spec-created",
+            "The bridge created a spec",
+            doc.schema::<SpecCreatedWebhook>(),
+        ),
+    ];
+    for (path, summary, schema) in webhooks {
+        doc.add(
+            "post",
+            path,
+            json!({
+                "tags": ["webhooks"],
+                "summary": summary,
+                "description": "`/webhook/...` without the project prefix acts on the current project",
+                "parameters": [{
+                    "name": "hash",
+                    "in": "path",
+                    "required": true,
+                    "description": "First 16 hex characters of the SHA-256 of the canonical project path",
+                    "schema": { "type": "string" }
+                }],
+                "requestBody": json_body(schema),
+                "responses": responses(&[("200", ok())])
+            }),
+        );
+    }
+
+    doc.add(
+        "get",
+        "/api/openapi.json",
+        json!({
+            "summary": "This document",
+            "security": [],
+            "responses": { "200": { "description": "OpenAPI document" } }
+        }),
+    );
+    doc.add(
+        "get",
+        "/api/events",
+        json!({
+            "summary": "WebSocket streaming app events as `{ \"event\": ..., \"payload\": ... }` messages",
+            "responses": responses(&[("101", json!({ "description": "Switching to WebSocket" }))])
+        }),
+    );
+    doc.add(
+        "get",
+        "/api/events/replay",
+        json!({
+            "summary": "Session state events logged after a sequence number, for clients catching up after being offline",
+            "parameters": [
+                query_param("since_seq", "Last `seq` the client has seen; 0 for the whole log", false, json!({ "type": "integer", "minimum": 0 })),
+                query_param("limit", "Events per page, at most 2000", false, json!({ "type": "integer", "minimum": 1, "default": 500 }))
+            ],
+            "responses": responses(&[
+                ("200", json_content("Logged events", json!({ "$ref": "#/components/schemas/EventReplay" }))),
+                ("400", json_content("Invalid query parameter", error_object())),
+                ("503", json_content("The event log is not available", error_object()))
+            ])
+        }),
+    );
+
+    let session_query = query_param(
+        "session",
+        "Session name; omit for the orchestrator's uncommitted changes",
+        false,
+        json!({ "type": "string" }),
+    );
+    let cursor_query = query_param(
+        "cursor",
+        "Cursor from the previous page",
+        false,
+        json!({ "type": "string" }),
+    );
+    let summary = doc.schema::<DiffSummaryResponse>();
+    doc.add(
+        "get",
+        "/api/diff/summary",
+        json!({
+            "tags": ["diff"],
+            "summary": "Changed files of a session",
+            "parameters": [
+                session_query,
+                cursor_query,
+                query_param("page_size", "Files per page", false, json!({ "type": "integer", "minimum": 1 }))
+            ],
+            "responses": responses(&[("200", json_content("Changed files", summary))])
+        }),
+    );
+    let chunk = doc.schema::<DiffChunkResponse>();
+    doc.add(
+        "get",
+        "/api/diff/file",
+        json!({
+            "tags": ["diff"],
+            "summary": "Diff lines of one changed file",
+            "parameters": [
+                session_query,
+                query_param("path", "Repository-relative file path", true, json!({ "type": "string" })),
+                cursor_query,
+                query_param("line_limit", "Lines per page", false, json!({ "type": "integer", "minimum": 1 }))
+            ],
+            "responses": responses(&[("200", json_content("Diff lines", chunk))])
+        }),
+    );
+
+    let create_spec = doc.schema::<CreateSpecRequest>();
+    doc.add(
+        "post",
+        "/api/specs",
+        json!({
+            "tags": ["specs"],
+            "summary": "Create a spec",
+            "requestBody": json_body(create_spec),
+            "responses": responses(&[("201", json_content("The new spec", session_ref()))])
+        }),
+    );
+    doc.add(
+        "get",
+        "/api/specs",
+        json!({
+            "tags": ["specs"],
+            "summary": "List specs",
+            "responses": responses(&[(
+                "200",
+                json_content("Specs", json!({ "type": "array", "items": session_ref() }))
+            )])
+        }),
+    );
+    let spec_summaries = doc.schema::<SpecSummaryResponse>();
+    doc.add(
+        "get",
+        "/api/specs/summary",
+        json!({
+            "tags": ["specs"],
+            "summary": "List specs without their content",
+            "responses": responses(&[("200", json_content("Spec summaries", spec_summaries))])
+        }),
+    );
+    let spec_content = doc.schema::<SpecContentResponse>();
+    doc.add(
+        "get",
+        "/api/specs/{name}",
+        json!({
+            "tags": ["specs"],
+            "summary": "Read a spec",
+            "parameters": [name_param("Spec name")],
+            "responses": responses(&[("200", json_content("Spec content", spec_content))])
+        }),
+    );
+    let update_spec = doc.schema::<UpdateSpecRequest>();
+    doc.add(
+        "patch",
+        "/api/specs/{name}",
+        json!({
+            "tags": ["specs"],
+            "summary": "Replace or append to a spec's content",
+            "parameters": [name_param("Spec name")],
+            "requestBody": json_body(update_spec),
+            "responses": responses(&[("200", ok())])
+        }),
+    );
+    doc.add(
+        "delete",
+        "/api/specs/{name}",
+        json!({
+            "tags": ["specs"],
+            "summary": "Delete a spec",
+            "parameters": [name_param("Spec name")],
+            "responses": responses(&[("200", ok())])
+        }),
+    );
+    let start_spec = doc.schema::<StartSpecRequest>();
+    doc.add(
+        "post",
+        "/api/specs/{name}/start",
+        json!({
+            "tags": ["specs"],
+            "summary": "Start a spec as a running session",
+            "parameters": [name_param("Spec name")],
+            "requestBody": json_body(start_spec),
+            "responses": responses(&[("200", ok())])
+        }),
+    );
+
+    let revisions = doc.schema::<SpecRevisionListResponse>();
+    doc.add(
+        "get",
+        "/api/specs/{name}/revisions",
+        json!({
+            "tags": ["specs"],
+            "summary": "List the stored revisions of a spec, oldest first",
+            "parameters": [name_param("Spec name")],
+            "responses": responses(&[("200", json_content("Spec revisions", revisions))])
+        }),
+    );
+    let propose_revision = doc.schema::<ProposeSpecRevisionRequest>();
+    let revision = doc.schema::<SpecRevisionResponse>();
+    doc.add(
+        "post",
+        "/api/specs/{name}/revisions",
+        json!({
+            "tags": ["specs"],
+            "summary": "Propose new content or edits; stored as the spec's next revision",
+            "parameters": [name_param("Spec name")],
+            "requestBody": json_body(propose_revision),
+            "responses": responses(&[
+                ("201", json_content("The stored revision", revision)),
+                ("409", json_content("The spec changed since `base_revision`", error_object())),
+                ("422", json_content("An edit does not apply to the current content", error_object()))
+            ])
+        }),
+    );
+
+    let query = doc.schema::<SessionQuery>();
+    let query_response = doc.schema::<SessionQueryResponse>();
+    doc.add(
+        "post",
+        "/api/query",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Fetch chosen fields of many sessions in one request",
+            "requestBody": json_body(query),
+            "responses": responses(&[("200", json_content("Matching sessions", query_response))])
+        }),
+    );
+
+    let create_session = doc.schema::<CreateSessionRequest>();
+    let create_error = doc.schema::<SessionCreateError>();
+    doc.add(
+        "post",
+        "/api/sessions",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Create a running session",
+            "description": "The request is validated before a branch or worktree is created; failures carry a `code` and the offending `field`",
+            "requestBody": json_body(create_session),
+            "responses": responses(&[
+                ("201", json_content("The new session", session_ref())),
+                ("400", json_content("The body is not valid JSON", create_error.clone())),
+                ("422", json_content("A field failed validation", create_error.clone())),
+                ("500", json_content("Creating the branch or worktree failed", create_error))
+            ])
+        }),
+    );
+    doc.add(
+        "get",
+        "/api/sessions",
+        json!({
+            "tags": ["sessions"],
+            "summary": "List sessions",
+            "parameters": [query_param(
+                "state",
+                "Only sessions in this state",
+                false,
+                json!({ "type": "string", "enum": ["spec", "running", "reviewed"] })
+            )],
+            "responses": responses(&[(
+                "200",
+                json_content(
+                    "Sessions",
+                    json!({ "type": "array", "items": { "$ref": "#/components/schemas/EnrichedSession" } })
+                )
+            )])
+        }),
+    );
+    doc.add(
+        "get",
+        "/api/sessions/{name}",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Read a session",
+            "parameters": [name_param("Session name")],
+            "responses": responses(&[("200", json_content("The session", session_ref()))])
+        }),
+    );
+    doc.add(
+        "delete",
+        "/api/sessions/{name}",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Cancel a session and remove its worktree",
+            "parameters": [name_param("Session name")],
+            "responses": responses(&[("200", ok())])
+        }),
+    );
+    let session_spec = doc.schema::<SessionSpecResponse>();
+    doc.add(
+        "get",
+        "/api/sessions/{name}/spec",
+        json!({
+            "tags": ["sessions"],
+            "summary": "The spec a session was started from",
+            "parameters": [name_param("Session name")],
+            "responses": responses(&[("200", json_content("Spec content", session_spec))])
+        }),
+    );
+    let merge_request = doc.schema::<MergeSessionRequest>();
+    let merge_response = doc.schema::<MergeSessionResponse>();
+    doc.add(
+        "post",
+        "/api/sessions/{name}/merge",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Merge a reviewed session into its parent branch",
+            "parameters": [name_param("Session name")],
+            "requestBody": json_body(merge_request),
+            "responses": responses(&[
+                ("200", json_content("Merge result", merge_response)),
+                ("409", text_content("The merge hit conflicts"))
+            ])
+        }),
+    );
+    let pr_request = doc.schema::<PullRequestRequest>();
+    let pr_response = doc.schema::<PullRequestResponse>();
+    doc.add(
+        "post",
+        "/api/sessions/{name}/pull-request",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Push a reviewed session and open a GitHub pull request",
+            "parameters": [name_param("Session name")],
+            "requestBody": json_body(pr_request),
+            "responses": responses(&[("200", json_content("Pull request", pr_response))])
+        }),
+    );
+    let mut mark_reviewed_body = json_body(doc.schema::<MarkReviewedRequest>());
+    mark_reviewed_body["required"] = json!(false);
+    doc.add(
+        "post",
+        "/api/sessions/{name}/mark-reviewed",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Mark a running session as reviewed",
+            "parameters": [name_param("Session name")],
+            "requestBody": mark_reviewed_body,
+            "responses": responses(&[("200", ok())])
+        }),
+    );
+    doc.add(
+        "post",
+        "/api/sessions/{name}/convert-to-spec",
+        json!({
+            "tags": ["sessions"],
+            "summary": "Turn a running session back into a spec",
+            "parameters": [name_param("Session name")],
+            "responses": responses(&[("200", ok())])
+        }),
+    );
+
+    doc.into_document()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    refs.push(target.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn document_describes_routes_with_resolvable_schemas() {
+        let doc = openapi_document();
+        assert_eq!(doc["openapi"], "3.0.3");
+
+        let paths = doc["paths"].as_object().unwrap();
+        for (path, method) in [
+            ("/projects/{hash}/webhook/follow-up-message", "post"),
+            ("/api/specs/{name}", "patch"),
+            ("/api/specs/{name}/revisions", "post"),
+            ("/api/query", "post"),
+            ("/api/sessions/{name}/merge", "post"),
+            ("/api/diff/file", "get"),
+        ] {
+            assert!(paths[path].get(method).is_some(), "{method} {path}");
+        }
+
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        let follow_up = &schemas["FollowUpMessageWebhook"];
+        assert_eq!(follow_up["required"], json!(["message", "session_name"]));
+        assert!(schemas.contains_key("MergeMode"));
+
+        let mut refs = Vec::new();
+        collect_refs(&doc, &mut refs);
+        assert!(!refs.is_empty());
+        for target in refs {
+            let name = target.strip_prefix("#/components/schemas/").unwrap();
+            assert!(schemas.contains_key(name), "unresolved {target}");
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! `POST /api/query`: chosen fields of many sessions in one round trip, so orchestration
+//! scripts don't need a request per session and data source on every refresh.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use schaltwerk::domains::sessions::entity::EnrichedSession;
+use schaltwerk::schaltwerk_core::SessionState;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Fields a query can ask for; everything but `terminals` comes from the session info
+pub const QUERYABLE_FIELDS: &[&str] = &[
+    "display_name",
+    "branch",
+    "base_branch",
+    "worktree_path",
+    "status",
+    "session_state",
+    "ready_to_merge",
+    "created_at",
+    "last_modified",
+    "original_agent_type",
+    "current_task",
+    "has_uncommitted_changes",
+    "has_conflicts",
+    "diff_stats",
+    "base_freshness",
+    "remote_status",
+    "worktree_size_bytes",
+    "labels",
+    "depends_on",
+    "blocked_by",
+    "spec_content",
+    "terminals",
+];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SessionQuery {
+    /// Session names to return; every session when omitted
+    #[serde(default)]
+    pub sessions: Option<Vec<String>>,
+    /// Only sessions in this state: `spec`, `running` or `reviewed`
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Fields to return per session; `name` is always included
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SessionQueryResponse {
+    /// One object per session with `name` and the requested fields; absent values are `null`
+    pub sessions: Vec<Map<String, Value>>,
+    /// Requested session names that matched no session
+    pub missing: Vec<String>,
+}
+
+/// Whether `session` belongs to the `state` filter of the session list routes. Reviewed
+/// sessions are still `running` in the database, so they are told apart by their flag.
+pub fn matches_state(session: &EnrichedSession, state: &SessionState) -> bool {
+    match state {
+        SessionState::Reviewed => session.info.ready_to_merge,
+        SessionState::Running => {
+            !session.info.ready_to_merge && session.info.session_state == SessionState::Running
+        }
+        SessionState::Spec => session.info.session_state == SessionState::Spec,
+    }
+}
+
+/// Answer `query` from the enriched session list; the error is the message for the `400`
+/// response.
+pub fn run_query(
+    sessions: Vec<EnrichedSession>,
+    query: &SessionQuery,
+) -> Result<SessionQueryResponse, String> {
+    let unknown: Vec<&str> = query
+        .fields
+        .iter()
+        .map(String::as_str)
+        .filter(|field| !QUERYABLE_FIELDS.contains(field))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown fields: {}. Available fields: {}",
+            unknown.join(", "),
+            QUERYABLE_FIELDS.join(", ")
+        ));
+    }
+    let state = query
+        .state
+        .as_deref()
+        .map(SessionState::from_str)
+        .transpose()?;
+
+    let requested: Option<HashSet<&str>> = query
+        .sessions
+        .as_ref()
+        .map(|names| names.iter().map(String::as_str).collect());
+    let mut found = HashSet::new();
+    let mut rows = Vec::new();
+    for session in sessions {
+        let name = session.info.session_id.clone();
+        if requested
+            .as_ref()
+            .is_some_and(|names| !names.contains(name.as_str()))
+        {
+            continue;
+        }
+        found.insert(name.clone());
+        if state
+            .as_ref()
+            .is_some_and(|state| !matches_state(&session, state))
+        {
+            continue;
+        }
+        rows.push(select_fields(&session, &query.fields)?);
+    }
+
+    let missing = query
+        .sessions
+        .iter()
+        .flatten()
+        .filter(|name| !found.contains(*name))
+        .cloned()
+        .collect();
+    Ok(SessionQueryResponse {
+        sessions: rows,
+        missing,
+    })
+}
+
+fn select_fields(
+    session: &EnrichedSession,
+    fields: &[String],
+) -> Result<Map<String, Value>, String> {
+    let info = match serde_json::to_value(&session.info) {
+        Ok(Value::Object(info)) => info,
+        Ok(_) => Map::new(),
+        Err(e) => return Err(format!("Failed to serialize session: {e}")),
+    };
+    let mut row = Map::new();
+    row.insert(
+        "name".to_string(),
+        Value::String(session.info.session_id.clone()),
+    );
+    for field in fields {
+        let value = if field == "terminals" {
+            Value::from(session.terminals.clone())
+        } else {
+            info.get(field).cloned().unwrap_or(Value::Null)
+        };
+        row.insert(field.clone(), value);
+    }
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn enriched(name: &str, state: &str, ready_to_merge: bool) -> EnrichedSession {
+        let info = serde_json::from_value(json!({
+            "session_id": name,
+            "branch": format!("schaltwerk/{name}"),
+            "worktree_path": format!("/repo/.schaltwerk/worktrees/{name}"),
+            "base_branch": "main",
+            "status": "active",
+            "created_at": null,
+            "last_modified": null,
+            "has_uncommitted_changes": false,
+            "is_current": false,
+            "session_type": "worktree",
+            "container_status": null,
+            "diff_stats": {
+                "files_changed": 2, "additions": 10, "deletions": 1, "insertions": 10
+            },
+            "ready_to_merge": ready_to_merge,
+            "session_state": state,
+        }))
+        .unwrap();
+        EnrichedSession {
+            info,
+            status: None,
+            terminals: vec![format!("session-{name}-top")],
+        }
+    }
+
+    #[test]
+    fn queries_return_only_the_requested_fields() {
+        let sessions = vec![
+            enriched("alpha", "running", false),
+            enriched("beta", "running", true),
+            enriched("gamma", "spec", false),
+        ];
+        let query: SessionQuery = serde_json::from_value(json!({
+            "sessions": ["alpha", "beta", "ghost"],
+            "state": "running",
+            "fields": ["diff_stats", "terminals", "spec_content"]
+        }))
+        .unwrap();
+
+        let response = run_query(sessions, &query).unwrap();
+        assert_eq!(response.missing, vec!["ghost".to_string()]);
+        assert_eq!(response.sessions.len(), 1);
+        assert_eq!(
+            Value::Object(response.sessions[0].clone()),
+            json!({
+                "name": "alpha",
+                "diff_stats": {
+                    "files_changed": 2, "additions": 10, "deletions": 1, "insertions": 10
+                },
+                "terminals": ["session-alpha-top"],
+                "spec_content": null
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_fields_and_states_are_rejected() {
+        let query = SessionQuery {
+            sessions: None,
+            state: None,
+            fields: vec!["branch".to_string(), "secrets".to_string()],
+        };
+        let error = run_query(Vec::new(), &query).unwrap_err();
+        assert!(error.starts_with("Unknown fields: secrets."), "{error}");
+
+        let query = SessionQuery {
+            sessions: None,
+            state: Some("paused".to_string()),
+            fields: Vec::new(),
+        };
+        assert!(run_query(Vec::new(), &query).is_err());
+    }
+}
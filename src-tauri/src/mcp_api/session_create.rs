@@ -0,0 +1,171 @@
+//! `POST /api/sessions`: the typed contract for creating a running session. Requests are
+//! validated before anything touches git, and failures carry a machine readable `code`
+//! so clients can tell a bad name from a missing base branch without parsing messages.
+
+use std::path::Path;
+
+use hyper::StatusCode;
+use schaltwerk::domains::agents::manifest::AgentManifest;
+use schaltwerk::domains::git;
+use schaltwerk::domains::git::repository::get_commit_hash;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateSessionRequest {
+    /// Letters, numbers, hyphens and underscores; a suffix is added when it is taken
+    pub name: String,
+    /// Initial prompt for the agent
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Branch or ref the session starts from; the current branch when omitted
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    #[serde(default)]
+    pub custom_branch: Option<String>,
+    /// The name was typed by the user rather than generated
+    #[serde(default)]
+    pub user_edited_name: Option<bool>,
+    /// Agent from the agent manifest, e.g. `claude` or `codex`; the project default when omitted
+    #[serde(default)]
+    pub agent_type: Option<String>,
+    #[serde(default)]
+    pub skip_permissions: Option<bool>,
+    /// Model the agent runs with, e.g. `opus` or `o3`; the agent's default when omitted
+    #[serde(default)]
+    pub agent_model: Option<String>,
+    /// Groups sessions that run the same prompt with different agents
+    #[serde(default)]
+    pub version_group_id: Option<String>,
+    /// Position of the session in its version group, starting at 1
+    #[serde(default)]
+    pub version_number: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionCreateErrorCode {
+    InvalidJson,
+    InvalidName,
+    InvalidBranchName,
+    UnknownBaseBranch,
+    UnknownAgentType,
+    InvalidModel,
+    InvalidVersion,
+    CreationFailed,
+}
+
+/// Body of a failed session creation
+#[derive(Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct SessionCreateError {
+    pub code: SessionCreateErrorCode,
+    pub error: String,
+    /// Request field the error is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<&'static str>,
+}
+
+impl SessionCreateError {
+    pub fn new(code: SessionCreateErrorCode, field: Option<&'static str>, error: String) -> Self {
+        Self { code, error, field }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self.code {
+            SessionCreateErrorCode::InvalidJson => StatusCode::BAD_REQUEST,
+            SessionCreateErrorCode::CreationFailed => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+/// Check `request` against the repository at `repo_path` before any branch or worktree
+/// is created.
+pub fn validate_create_request(
+    request: &CreateSessionRequest,
+    repo_path: &Path,
+) -> Result<(), SessionCreateError> {
+    if !git::is_valid_session_name(&request.name) {
+        return Err(SessionCreateError::new(
+            SessionCreateErrorCode::InvalidName,
+            Some("name"),
+            format!(
+                "Invalid session name '{}': use 1-100 letters, numbers, hyphens and underscores",
+                request.name
+            ),
+        ));
+    }
+
+    if let Some(branch) = &request.custom_branch {
+        if !git::is_valid_branch_name(branch) {
+            return Err(SessionCreateError::new(
+                SessionCreateErrorCode::InvalidBranchName,
+                Some("custom_branch"),
+                format!("Invalid branch name '{branch}': it must be a valid git reference"),
+            ));
+        }
+    }
+
+    // An empty repository gets its initial commit while the session is created
+    let has_commits = git::repository_has_commits(repo_path).unwrap_or(true);
+    if let Some(base) = request.base_branch.as_deref().map(str::trim) {
+        if !base.is_empty() && has_commits && get_commit_hash(repo_path, base).is_err() {
+            return Err(SessionCreateError::new(
+                SessionCreateErrorCode::UnknownBaseBranch,
+                Some("base_branch"),
+                format!("Base branch '{base}' does not exist in the repository"),
+            ));
+        }
+    }
+
+    if let Some(agent_type) = &request.agent_type {
+        if AgentManifest::get(agent_type).is_none() {
+            return Err(SessionCreateError::new(
+                SessionCreateErrorCode::UnknownAgentType,
+                Some("agent_type"),
+                format!(
+                    "Unknown agent type '{agent_type}'. Supported agents: {}",
+                    AgentManifest::supported_agents().join(", ")
+                ),
+            ));
+        }
+    }
+
+    if let Some(model) = &request.agent_model {
+        if model.trim().is_empty() || model.chars().any(char::is_whitespace) {
+            return Err(SessionCreateError::new(
+                SessionCreateErrorCode::InvalidModel,
+                Some("agent_model"),
+                format!("Invalid model '{model}': it must be a single identifier"),
+            ));
+        }
+        let agent = request.agent_type.as_deref().and_then(AgentManifest::get);
+        if let Some(agent) = agent.filter(|agent| !agent.supports_model()) {
+            return Err(SessionCreateError::new(
+                SessionCreateErrorCode::InvalidModel,
+                Some("agent_model"),
+                format!("{} does not support choosing a model", agent.display_name),
+            ));
+        }
+    }
+
+    match (&request.version_group_id, request.version_number) {
+        (None, Some(_)) => Err(SessionCreateError::new(
+            SessionCreateErrorCode::InvalidVersion,
+            Some("version_number"),
+            "version_number needs a version_group_id".to_string(),
+        )),
+        (Some(group), _) if group.trim().is_empty() => Err(SessionCreateError::new(
+            SessionCreateErrorCode::InvalidVersion,
+            Some("version_group_id"),
+            "version_group_id must not be empty".to_string(),
+        )),
+        (_, Some(number)) if number < 1 => Err(SessionCreateError::new(
+            SessionCreateErrorCode::InvalidVersion,
+            Some("version_number"),
+            format!("version_number must be 1 or greater, got {number}"),
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,78 @@
+//! Payloads the MCP bridge posts to `/webhook/*` after it changed sessions on its own.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SessionAddedWebhook {
+    pub session_name: String,
+    #[serde(default)]
+    pub branch: String,
+    #[serde(default)]
+    pub worktree_path: String,
+    #[serde(default)]
+    pub parent_branch: String,
+    /// RFC 3339 timestamp; defaults to the time the webhook arrives
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SessionRemovedWebhook {
+    pub session_name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FollowUpMessageWebhook {
+    pub session_name: String,
+    /// Text pasted into the session's agent terminal
+    pub message: String,
+    /// Milliseconds since the Unix epoch; defaults to the time the webhook arrives
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SpecCreatedWebhook {
+    pub session_name: String,
+}
+
+/// Parse a webhook body; the error is the message for the `400` response.
+pub fn parse_payload<T: DeserializeOwned>(route: &str, body: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(body).map_err(|e| {
+        log::warn!("Rejected {route} webhook with invalid payload: {e}");
+        format!("Invalid JSON payload: {e}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payloads_are_validated() {
+        let payload: FollowUpMessageWebhook = parse_payload(
+            "follow-up-message",
+            br#"{"session_name":"alpha","message":"continue","timestamp":42}"#,
+        )
+        .unwrap();
+        assert_eq!(payload.session_name, "alpha");
+        assert_eq!(payload.timestamp, Some(42));
+
+        let added: SessionAddedWebhook =
+            parse_payload("session-added", br#"{"session_name":"alpha","extra":true}"#).unwrap();
+        assert_eq!(added.branch, "");
+        assert!(added.created_at.is_none());
+
+        let rejected = parse_payload::<FollowUpMessageWebhook>(
+            "follow-up-message",
+            br#"{"session_name":"alpha"}"#,
+        )
+        .unwrap_err();
+        assert!(rejected.contains("missing field `message`"), "{rejected}");
+        assert!(parse_payload::<SessionRemovedWebhook>("session-removed", b"not json").is_err());
+    }
+}
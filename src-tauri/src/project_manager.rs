@@ -3,17 +3,56 @@ use log::{debug, info, warn};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
-
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::domains::agents::custom::{
+    load_project_custom_agents, register_custom_agents, set_current_custom_agent_project,
+    CustomAgentSource,
+};
+use crate::domains::sessions::db_sessions::SessionMethods;
+use crate::domains::terminal::container::{session_for_cwd, ContainerSessionResolver};
 use crate::domains::terminal::TerminalManager;
+use crate::infrastructure::events::outbound::configure_outbound_webhooks;
+use crate::schaltwerk_core::database::Database;
+use crate::schaltwerk_core::db_project_config::{project_worktrees_root, ProjectConfigMethods};
 use crate::schaltwerk_core::SchaltwerkCore;
 
+/// Short identifier for a canonical project path: the first 16 hex characters of its
+/// SHA-256. Names the project's data folder and its webhook routes
+/// (`/projects/{hash}/webhook/...`); the MCP bridge computes the same value.
+pub fn project_hash(canonical_path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(canonical_path.to_string_lossy().as_bytes());
+    let hash_hex = format!("{:x}", hasher.finalize());
+    hash_hex[..16].to_string()
+}
+
+/// Resolve terminal working directories to the project's container-backed sessions.
+pub fn container_session_resolver(db: &Database, repo_path: &Path) -> ContainerSessionResolver {
+    let db = db.clone();
+    let repo_path = repo_path.to_path_buf();
+    Arc::new(
+        move |cwd: &Path| match db.list_container_sessions(&repo_path) {
+            Ok(sessions) => session_for_cwd(sessions, cwd),
+            Err(e) => {
+                warn!("Failed to list container sessions: {e}");
+                None
+            }
+        },
+    )
+}
+
+/// Called with every project once its core is initialized, e.g. to start its trackers
+pub type ProjectInitHook = Arc<dyn Fn(Arc<Project>) + Send + Sync>;
+
 /// Represents a single project with its own terminals and sessions
 pub struct Project {
     pub path: PathBuf,
     pub terminal_manager: Arc<TerminalManager>,
     pub schaltwerk_core: Arc<RwLock<SchaltwerkCore>>,
+    background_tasks: Mutex<Vec<AbortHandle>>,
 }
 
 impl Project {
@@ -33,18 +72,55 @@ impl Project {
 
         info!("Using database at: {}", db_path.display());
 
-        let schaltwerk_core = Arc::new(RwLock::new(SchaltwerkCore::new_with_repo_path(
-            Some(db_path),
-            path.clone(),
-        )?));
+        let core = SchaltwerkCore::new_with_repo_path(Some(db_path), path.clone())?;
+        let worktrees_root = project_worktrees_root(&core.db, &path);
+        match core.db.get_project_container_config(&path) {
+            Ok(config) => terminal_manager
+                .configure_containers(config, container_session_resolver(&core.db, &path)),
+            Err(e) => warn!(
+                "Failed to load container config for {}: {e}",
+                path.display()
+            ),
+        }
+        terminal_manager.set_project_path(&path);
+        terminal_manager.set_worktrees_root(&worktrees_root);
+        match core.db.get_project_outbound_webhook_config(&path) {
+            Ok(config) => configure_outbound_webhooks(&path, config),
+            Err(e) => warn!(
+                "Failed to load outbound webhook config for {}: {e}",
+                path.display()
+            ),
+        }
+        match core.db.get_project_dependency_cache_config(&path) {
+            Ok(config) => {
+                terminal_manager.configure_dependency_cache(&path, &worktrees_root, config)
+            }
+            Err(e) => warn!(
+                "Failed to load dependency cache config for {}: {e}",
+                path.display()
+            ),
+        }
+        match load_project_custom_agents(&path) {
+            Ok(agents) => register_custom_agents(CustomAgentSource::Project(path.clone()), agents),
+            Err(e) => warn!("Failed to load custom agents for {}: {e}", path.display()),
+        }
+        let schaltwerk_core = Arc::new(RwLock::new(core));
 
         Ok(Self {
             path,
             terminal_manager,
             schaltwerk_core,
+            background_tasks: Mutex::new(Vec::new()),
         })
     }
 
+    /// Keep a task working on this project; it is aborted when the project is dropped
+    pub fn track_background_task(&self, task: JoinHandle<()>) {
+        if let Ok(mut tasks) = self.background_tasks.lock() {
+            tasks.push(task.abort_handle());
+        }
+    }
+
     /// Get the database path for a project in the global app data directory
     fn get_project_db_path(project_path: &PathBuf) -> Result<PathBuf> {
         // Get the app data directory (same location as settings)
@@ -54,16 +130,8 @@ impl Project {
         // Create a unique folder name for this project using a hash
         // This ensures uniqueness even for projects with the same name in different locations
         let canonical_path = std::fs::canonicalize(project_path)?;
-        let path_str = canonical_path.to_string_lossy();
-
-        // Create a hash of the full path
-        let mut hasher = Sha256::new();
-        hasher.update(path_str.as_bytes());
-        let hash_result = hasher.finalize();
-        let hash_hex = format!("{hash_result:x}");
 
-        // Take first 16 characters of hash for a shorter but still unique identifier
-        let hash_short = &hash_hex[..16];
+        let hash_short = project_hash(&canonical_path);
 
         // Get the project name for readability
         let project_name = canonical_path
@@ -106,14 +174,26 @@ impl Project {
             path,
             terminal_manager,
             schaltwerk_core,
+            background_tasks: Mutex::new(Vec::new()),
         })
     }
 }
 
+impl Drop for Project {
+    fn drop(&mut self) {
+        if let Ok(tasks) = self.background_tasks.get_mut() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+    }
+}
+
 /// Manages multiple projects and their resources
 pub struct ProjectManager {
     projects: Arc<RwLock<HashMap<PathBuf, Arc<Project>>>>,
     current_project: Arc<RwLock<Option<PathBuf>>>,
+    init_hooks: Mutex<Vec<ProjectInitHook>>,
 }
 
 impl Default for ProjectManager {
@@ -127,6 +207,28 @@ impl ProjectManager {
         Self {
             projects: Arc::new(RwLock::new(HashMap::new())),
             current_project: Arc::new(RwLock::new(None)),
+            init_hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Run `hook` for every project opened from now on and for those already open
+    pub async fn on_project_initialized(&self, hook: ProjectInitHook) {
+        let projects = self.projects.read().await;
+        if let Ok(mut hooks) = self.init_hooks.lock() {
+            hooks.push(hook.clone());
+        }
+        for project in projects.values() {
+            hook(project.clone());
+        }
+    }
+
+    fn run_init_hooks(&self, project: &Arc<Project>) {
+        let hooks = match self.init_hooks.lock() {
+            Ok(hooks) => hooks.clone(),
+            Err(_) => return,
+        };
+        for hook in hooks {
+            hook(project.clone());
         }
     }
 
@@ -167,6 +269,7 @@ impl ProjectManager {
                 }
             };
             projects.insert(path.clone(), new_project.clone());
+            self.run_init_hooks(&new_project);
             new_project
         };
 
@@ -177,6 +280,8 @@ impl ProjectManager {
 
         // Update current project
         *self.current_project.write().await = Some(path.clone());
+        set_current_custom_agent_project(Some(&path));
+        crate::infrastructure::crash::note_open_project(&path);
         log::info!("✅ Current project set to: {}", path.display());
 
         Ok(project)
@@ -357,6 +462,10 @@ impl ProjectManager {
             }
 
             drop(project);
+            register_custom_agents(
+                CustomAgentSource::Project(canonical.clone()),
+                HashMap::new(),
+            );
             info!("✅ Removed project from manager: {}", canonical.display());
         } else {
             drop(projects);
@@ -366,11 +475,21 @@ impl ProjectManager {
         let mut current = self.current_project.write().await;
         if current.as_ref() == Some(&canonical) {
             *current = None;
+            set_current_custom_agent_project(None);
         }
 
         Ok(())
     }
 
+    /// Find the open project a project-scoped webhook route points at
+    pub async fn project_for_hash(&self, hash: &str) -> Option<Arc<Project>> {
+        let projects = self.projects.read().await;
+        projects
+            .iter()
+            .find(|(path, _)| project_hash(path) == hash)
+            .map(|(_, project)| project.clone())
+    }
+
     /// Get terminal manager for current project
     pub async fn current_terminal_manager(&self) -> Result<Arc<TerminalManager>> {
         let project = self.current_project().await?;
@@ -430,6 +549,7 @@ impl ProjectManager {
         // Store it in the projects map
         let mut projects_write = self.projects.write().await;
         projects_write.insert(canonical_path.clone(), arc_project.clone());
+        self.run_init_hooks(&arc_project);
         drop(projects_write);
 
         Ok(arc_project.schaltwerk_core.clone())
@@ -451,11 +571,13 @@ impl ProjectManager {
         } else {
             let new_project = Arc::new(Project::new_in_memory(path.clone())?);
             projects.insert(path.clone(), new_project.clone());
+            self.run_init_hooks(&new_project);
             new_project
         };
 
         // Update current project
         *self.current_project.write().await = Some(path.clone());
+        set_current_custom_agent_project(Some(&path));
 
         Ok(project)
     }
@@ -625,6 +747,36 @@ mod tests {
         assert!(projects.get(&path).is_none());
     }
 
+    #[tokio::test]
+    async fn test_project_for_hash_finds_each_open_project() {
+        let mgr = ProjectManager::new();
+        let tmp1 = TempDir::new().unwrap();
+        let tmp2 = TempDir::new().unwrap();
+
+        let p1 = mgr
+            .switch_to_project_in_memory(tmp1.path().to_path_buf())
+            .await
+            .unwrap();
+        let p2 = mgr
+            .switch_to_project_in_memory(tmp2.path().to_path_buf())
+            .await
+            .unwrap();
+
+        let hash1 = project_hash(&p1.path);
+        assert_eq!(hash1.len(), 16);
+        assert_ne!(hash1, project_hash(&p2.path));
+
+        // Not only the current project is reachable
+        let found = mgr.project_for_hash(&hash1).await.unwrap();
+        assert!(Arc::ptr_eq(&found, &p1));
+        let found = mgr.project_for_hash(&project_hash(&p2.path)).await.unwrap();
+        assert!(Arc::ptr_eq(&found, &p2));
+        assert!(mgr.project_for_hash("0000000000000000").await.is_none());
+
+        mgr.remove_project(&p1.path).await.unwrap();
+        assert!(mgr.project_for_hash(&hash1).await.is_none());
+    }
+
     #[tokio::test]
     async fn test_remove_project_allows_fresh_reinitialization() {
         let mgr = ProjectManager::new();
@@ -643,4 +795,50 @@ mod tests {
 
         let _ = p2.terminal_manager.cleanup_all().await;
     }
+
+    #[tokio::test]
+    async fn test_init_hooks_run_per_project_and_tasks_stop_with_it() {
+        let mgr = ProjectManager::new();
+        let tmp1 = TempDir::new().unwrap();
+        let tmp2 = TempDir::new().unwrap();
+
+        let p1 = mgr
+            .switch_to_project_in_memory(tmp1.path().to_path_buf())
+            .await
+            .unwrap();
+
+        let initialized = Arc::new(Mutex::new(Vec::new()));
+        let tasks = Arc::new(Mutex::new(Vec::new()));
+        let hook_initialized = initialized.clone();
+        let hook_tasks = tasks.clone();
+        mgr.on_project_initialized(Arc::new(move |project: Arc<Project>| {
+            hook_initialized.lock().unwrap().push(project.path.clone());
+            let task = tokio::spawn(std::future::pending::<()>());
+            hook_tasks.lock().unwrap().push(task.abort_handle());
+            project.track_background_task(task);
+        }))
+        .await;
+
+        let p2 = mgr
+            .switch_to_project_in_memory(tmp2.path().to_path_buf())
+            .await
+            .unwrap();
+        // Switching back to an open project does not initialize it again
+        mgr.switch_to_project_in_memory(p1.path.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            *initialized.lock().unwrap(),
+            vec![p1.path.clone(), p2.path.clone()]
+        );
+
+        let p2_path = p2.path.clone();
+        drop(p2);
+        mgr.remove_project(&p2_path).await.unwrap();
+        tokio::task::yield_now().await;
+
+        let tasks = tasks.lock().unwrap();
+        assert!(!tasks[0].is_finished());
+        assert!(tasks[1].is_finished());
+    }
 }
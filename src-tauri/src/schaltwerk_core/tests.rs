@@ -1388,7 +1388,9 @@ fn test_mark_reviewed_refreshes_git_stats() {
         .unwrap();
 
     // Immediately mark as reviewed (auto_commit = false)
-    manager.mark_session_as_reviewed(&session.name).unwrap();
+    manager
+        .mark_session_as_reviewed(&session.name, false, None)
+        .unwrap();
 
     // Fetch enriched sessions; git stats should be refreshed and clean
     let enriched = manager.list_enriched_sessions().unwrap();
@@ -1407,6 +1409,38 @@ fn test_mark_reviewed_refreshes_git_stats() {
     );
 }
 
+#[test]
+fn test_mark_session_as_reviewed_can_commit_pending_changes() {
+    let env = TestEnvironment::new().unwrap();
+    let manager = env.get_session_manager().unwrap();
+
+    let session = manager
+        .create_session("review-auto-commit", None, None)
+        .unwrap();
+    std::fs::write(session.worktree_path.join("done.txt"), "finished").unwrap();
+
+    manager
+        .mark_session_as_reviewed(&session.name, true, Some("feat: finish the task"))
+        .unwrap();
+
+    let reviewed = manager
+        .db_ref()
+        .get_session_by_name(&env.repo_path, &session.name)
+        .unwrap();
+    assert!(reviewed.ready_to_merge);
+    assert!(!crate::domains::git::has_uncommitted_changes(&session.worktree_path).unwrap());
+
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%s"])
+        .current_dir(&session.worktree_path)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "feat: finish the task"
+    );
+}
+
 #[test]
 fn test_follow_up_unmarks_reviewed_and_sets_running() {
     use crate::domains::sessions::entity::SessionState;
@@ -1420,7 +1454,9 @@ fn test_follow_up_unmarks_reviewed_and_sets_running() {
         .unwrap();
 
     // Mark as reviewed
-    manager.mark_session_as_reviewed(&session.name).unwrap();
+    manager
+        .mark_session_as_reviewed(&session.name, false, None)
+        .unwrap();
 
     // Sanity: is reviewed
     let s1 = manager
@@ -1,9 +1,18 @@
 use async_trait::async_trait;
+use serde::Serialize;
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, OnceCell};
 
+/// Crashes in a row after which the supervisor stops restarting the server
+pub const MCP_MAX_CONSECUTIVE_FAILURES: u32 = 5;
+/// A server that stayed up this long starts counting crashes from zero again
+const MCP_STABLE_UPTIME: Duration = Duration::from_secs(60);
+const MCP_RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+const MCP_RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+
 #[async_trait]
 pub trait McpBackend: Send + Sync {
     async fn start_server(&self, port: Option<u16>) -> Result<(), String>;
@@ -47,6 +56,101 @@ pub fn get_mcp_server_process() -> &'static OnceCell<Arc<Mutex<Option<Child>>>>
     &MCP_SERVER_PROCESS
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum McpServerState {
+    /// Never started by Schaltwerk; agents may still run their own MCP server
+    #[default]
+    Stopped,
+    Running,
+    /// Crashed and waiting for the next restart attempt
+    Restarting,
+    /// Crashed too often in a row; restarted only on request
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpHealth {
+    pub state: McpServerState,
+    pub pid: Option<u32>,
+    /// Restarts after a crash since the app started
+    pub restart_count: u32,
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    consecutive_failures: u32,
+    #[serde(skip)]
+    started_at: Option<Instant>,
+}
+
+impl McpHealth {
+    pub fn record_started(&mut self, pid: Option<u32>, now: Instant) {
+        match self.state {
+            McpServerState::Restarting => self.restart_count += 1,
+            McpServerState::Stopped | McpServerState::Failed => self.consecutive_failures = 0,
+            McpServerState::Running => {}
+        }
+        self.state = McpServerState::Running;
+        self.pid = pid;
+        self.started_at = Some(now);
+    }
+
+    /// Record that the server exited or could not be spawned. Returns the delay before
+    /// the next restart, or `None` once it failed too often in a row.
+    pub fn record_failure(&mut self, error: String, now: Instant) -> Option<Duration> {
+        let was_stable = self
+            .started_at
+            .is_some_and(|started| now.duration_since(started) >= MCP_STABLE_UPTIME);
+        if was_stable {
+            self.consecutive_failures = 0;
+        }
+        self.consecutive_failures += 1;
+        self.pid = None;
+        self.started_at = None;
+        self.last_error = Some(error);
+
+        if self.consecutive_failures > MCP_MAX_CONSECUTIVE_FAILURES {
+            self.state = McpServerState::Failed;
+            return None;
+        }
+        self.state = McpServerState::Restarting;
+        Some(restart_backoff(self.consecutive_failures - 1))
+    }
+}
+
+/// Delay before restart `attempt` (zero based): doubling from one second up to 30.
+pub fn restart_backoff(attempt: u32) -> Duration {
+    MCP_RESTART_BASE_DELAY
+        .saturating_mul(1 << attempt.min(16))
+        .min(MCP_RESTART_MAX_DELAY)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpProcessStatus {
+    NotStarted,
+    Running { pid: u32 },
+    Exited(String),
+}
+
+/// Whether the server process Schaltwerk spawned is still alive.
+pub async fn mcp_process_status() -> McpProcessStatus {
+    let Some(process_mutex) = MCP_SERVER_PROCESS.get() else {
+        return McpProcessStatus::NotStarted;
+    };
+    let mut guard = process_mutex.lock().await;
+    let Some(process) = guard.as_mut() else {
+        return McpProcessStatus::NotStarted;
+    };
+    match process.try_wait() {
+        Ok(None) => McpProcessStatus::Running { pid: process.id() },
+        Ok(Some(status)) => McpProcessStatus::Exited(format!("MCP server exited with {status}")),
+        Err(e) => {
+            log::warn!("Error checking MCP server status: {e}");
+            McpProcessStatus::Running { pid: process.id() }
+        }
+    }
+}
+
 pub struct ProcessMcpBackend;
 
 impl ProcessMcpBackend {
@@ -160,6 +264,50 @@ mod tests {
         assert_eq!(recorded[0], Some(9000));
     }
 
+    #[test]
+    fn restart_backoff_doubles_up_to_the_cap() {
+        assert_eq!(restart_backoff(0), Duration::from_secs(1));
+        assert_eq!(restart_backoff(3), Duration::from_secs(8));
+        assert_eq!(restart_backoff(10), Duration::from_secs(30));
+        assert_eq!(restart_backoff(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn health_gives_up_after_repeated_crashes() {
+        let start = Instant::now();
+        let mut health = McpHealth::default();
+        health.record_started(Some(10), start);
+        assert_eq!(health.state, McpServerState::Running);
+
+        for attempt in 0..MCP_MAX_CONSECUTIVE_FAILURES {
+            let delay = health.record_failure("exit status: 1".to_string(), start);
+            assert_eq!(delay, Some(restart_backoff(attempt)));
+            assert_eq!(health.state, McpServerState::Restarting);
+            assert_eq!(health.pid, None);
+            health.record_started(Some(11), start);
+        }
+        assert_eq!(health.restart_count, MCP_MAX_CONSECUTIVE_FAILURES);
+        assert_eq!(
+            health.record_failure("exit status: 1".to_string(), start),
+            None
+        );
+        assert_eq!(health.state, McpServerState::Failed);
+        assert_eq!(health.last_error.as_deref(), Some("exit status: 1"));
+
+        // A manual start clears the streak, and a long run resets it as well
+        health.record_started(Some(12), start);
+        assert_eq!(
+            health.record_failure("crash".to_string(), start),
+            Some(restart_backoff(0))
+        );
+        health.record_started(Some(13), start);
+        let later = start + MCP_STABLE_UPTIME;
+        assert_eq!(
+            health.record_failure("crash".to_string(), later),
+            Some(restart_backoff(0))
+        );
+    }
+
     #[tokio::test]
     async fn wraps_errors_with_context() {
         let service = McpServiceImpl::new(ErrorBackend);
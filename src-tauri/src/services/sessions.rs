@@ -126,12 +126,20 @@ mod tests {
                 session_type: SessionType::Worktree,
                 container_status: None,
                 original_agent_type: None,
+                agent_model: None,
                 current_task: None,
                 diff_stats: None,
                 ready_to_merge: false,
                 spec_content: None,
                 worktree_size_bytes: None,
                 session_state: SessionState::Running,
+                labels: Vec::new(),
+                depends_on: Vec::new(),
+                blocked_by: Vec::new(),
+                auto_nudge_disabled: false,
+                remote_status: None,
+                base_freshness: None,
+                usage: None,
             },
             status: None,
             terminals: vec![],
@@ -1,11 +1,16 @@
+use crate::domains::sessions::db_sessions::SessionMethods;
 use crate::domains::terminal::{
-    manager::CreateTerminalWithAppAndSizeParams, TerminalManager, TerminalSnapshot,
+    buffer_search::{BufferSearchQuery, BufferSearchResult},
+    manager::CreateTerminalWithAppAndSizeParams,
+    TerminalManager, TerminalSnapshot,
 };
+use crate::domains::workspace::dev_environment::wrap_in_environment;
 use crate::project_manager::ProjectManager;
-use crate::schaltwerk_core::db_project_config::ProjectConfigMethods;
+use crate::schaltwerk_core::db_project_config::{DevEnvironmentKind, ProjectConfigMethods};
 use async_trait::async_trait;
 use futures::future::join_all;
 use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
 use tauri::AppHandle;
 
@@ -60,6 +65,11 @@ pub trait TerminalsBackend: Send + Sync {
         id: String,
         from_seq: Option<u64>,
     ) -> Result<TerminalSnapshot, String>;
+    async fn search_terminal_buffer(
+        &self,
+        id: String,
+        query: BufferSearchQuery,
+    ) -> Result<BufferSearchResult, String>;
     async fn get_terminal_activity_status(&self, id: String) -> Result<(bool, u64), String>;
     async fn get_all_terminal_activity(&self) -> Result<Vec<(String, u64)>, String>;
     async fn register_session_terminals(
@@ -107,6 +117,11 @@ pub trait TerminalsService: Send + Sync {
         id: String,
         from_seq: Option<u64>,
     ) -> Result<TerminalSnapshot, String>;
+    async fn search_terminal_buffer(
+        &self,
+        id: String,
+        query: BufferSearchQuery,
+    ) -> Result<BufferSearchResult, String>;
     async fn get_terminal_activity_status(&self, id: String) -> Result<(bool, u64), String>;
     async fn get_all_terminal_activity(&self) -> Result<Vec<(String, u64)>, String>;
     async fn register_session_terminals(
@@ -231,6 +246,17 @@ impl<B: TerminalsBackend> TerminalsServiceImpl<B> {
             .map_err(|err| Self::map_err(&format!("Failed to read buffer for terminal {id}"), err))
     }
 
+    pub async fn search_terminal_buffer(
+        &self,
+        id: String,
+        query: BufferSearchQuery,
+    ) -> Result<BufferSearchResult, String> {
+        self.backend
+            .search_terminal_buffer(id.clone(), query)
+            .await
+            .map_err(|err| Self::map_err(&format!("Failed to search terminal {id}"), err))
+    }
+
     pub async fn get_terminal_activity_status(&self, id: String) -> Result<(bool, u64), String> {
         self.backend
             .get_terminal_activity_status(id.clone())
@@ -352,6 +378,14 @@ where
         TerminalsServiceImpl::get_terminal_buffer(self, id, from_seq).await
     }
 
+    async fn search_terminal_buffer(
+        &self,
+        id: String,
+        query: BufferSearchQuery,
+    ) -> Result<BufferSearchResult, String> {
+        TerminalsServiceImpl::search_terminal_buffer(self, id, query).await
+    }
+
     async fn get_terminal_activity_status(&self, id: String) -> Result<(bool, u64), String> {
         TerminalsServiceImpl::get_terminal_activity_status(self, id).await
     }
@@ -411,15 +445,31 @@ impl TerminalManagerBackend {
         manager.set_app_handle(self.app_handle.clone()).await;
     }
 
-    async fn project_environment(&self) -> Vec<(String, String)> {
-        if let Ok(project) = self.project_manager.current_project().await {
-            let core = project.schaltwerk_core.read().await;
-            let db = core.database();
-            if let Ok(vars) = db.get_project_environment_variables(&project.path) {
-                return vars.into_iter().collect();
-            }
-        }
-        Vec::new()
+    /// Project variables with those of the session whose worktree is `cwd` layered on top.
+    async fn project_environment(&self, cwd: &str) -> Vec<(String, String)> {
+        let Ok(project) = self.project_manager.current_project().await else {
+            return Vec::new();
+        };
+        let core = project.schaltwerk_core.read().await;
+        let db = core.database();
+        let mut env: Vec<(String, String)> = db
+            .get_project_environment_variables(&project.path)
+            .map(|vars| vars.into_iter().collect())
+            .unwrap_or_default();
+        match db.get_session_environment_variables_by_worktree(Path::new(cwd)) {
+            Ok(session_env) => env = self.merge_env(env, session_env.into_iter().collect()),
+            Err(e) => log::warn!("Failed to load session environment for {cwd}: {e}"),
+        }
+        env
+    }
+
+    async fn project_dev_environment(&self) -> Option<DevEnvironmentKind> {
+        let project = self.project_manager.current_project().await.ok()?;
+        let core = project.schaltwerk_core.read().await;
+        core.database()
+            .get_project_dev_environment(&project.path)
+            .ok()
+            .flatten()
     }
 
     fn merge_env(
@@ -444,7 +494,7 @@ impl TerminalsBackend for TerminalManagerBackend {
         let manager = self.terminal_manager().await?;
         self.ensure_app_handle(&manager).await;
 
-        let env = self.merge_env(self.project_environment().await, request.env);
+        let env = self.merge_env(self.project_environment(&request.cwd).await, request.env);
 
         if !env.is_empty() {
             log::info!(
@@ -472,12 +522,17 @@ impl TerminalsBackend for TerminalManagerBackend {
         self.ensure_app_handle(&manager).await;
 
         let env = self.merge_env(
-            self.project_environment().await,
+            self.project_environment(&request.cwd).await,
             request.env.unwrap_or_default(),
         );
 
-        let bash = "/bin/bash".to_string();
-        let args = vec!["-l".to_string()];
+        let shell = "/bin/bash".to_string();
+        let shell_args = vec!["-l".to_string()];
+        // Run commands see the same toolchain as the agent when a dev environment is configured
+        let (bash, args) = match self.project_dev_environment().await {
+            Some(kind) => wrap_in_environment(kind, Path::new(&request.cwd), shell, shell_args),
+            None => (shell, shell_args),
+        };
 
         if let (Some(cols), Some(rows)) = (request.cols, request.rows) {
             manager
@@ -514,7 +569,7 @@ impl TerminalsBackend for TerminalManagerBackend {
             request.rows
         );
 
-        let env = self.project_environment().await;
+        let env = self.project_environment(&request.cwd).await;
 
         if !env.is_empty() {
             log::info!(
@@ -596,6 +651,15 @@ impl TerminalsBackend for TerminalManagerBackend {
         manager.get_terminal_buffer(id, from_seq).await
     }
 
+    async fn search_terminal_buffer(
+        &self,
+        id: String,
+        query: BufferSearchQuery,
+    ) -> Result<BufferSearchResult, String> {
+        let manager = self.terminal_manager().await?;
+        manager.search_terminal_buffer(id, query).await
+    }
+
     async fn get_terminal_activity_status(&self, id: String) -> Result<(bool, u64), String> {
         let manager = self.terminal_manager().await?;
         manager.get_terminal_activity_status(id).await
@@ -716,6 +780,14 @@ mod tests {
             panic!("unused in test backend");
         }
 
+        async fn search_terminal_buffer(
+            &self,
+            _id: String,
+            _query: BufferSearchQuery,
+        ) -> Result<BufferSearchResult, String> {
+            panic!("unused in test backend");
+        }
+
         async fn get_terminal_activity_status(&self, _id: String) -> Result<(bool, u64), String> {
             panic!("unused in test backend");
         }
@@ -812,6 +884,14 @@ mod tests {
             panic!("unused in test backend");
         }
 
+        async fn search_terminal_buffer(
+            &self,
+            _id: String,
+            _query: BufferSearchQuery,
+        ) -> Result<BufferSearchResult, String> {
+            panic!("unused in test backend");
+        }
+
         async fn get_terminal_activity_status(&self, _id: String) -> Result<(bool, u64), String> {
             panic!("unused in test backend");
         }
@@ -911,6 +991,14 @@ mod tests {
             panic!("unused in test backend");
         }
 
+        async fn search_terminal_buffer(
+            &self,
+            _id: String,
+            _query: BufferSearchQuery,
+        ) -> Result<BufferSearchResult, String> {
+            panic!("unused in test backend");
+        }
+
         async fn get_terminal_activity_status(&self, _id: String) -> Result<(bool, u64), String> {
             panic!("unused in test backend");
         }
@@ -1007,6 +1095,14 @@ mod tests {
             panic!("unused in test backend");
         }
 
+        async fn search_terminal_buffer(
+            &self,
+            _id: String,
+            _query: BufferSearchQuery,
+        ) -> Result<BufferSearchResult, String> {
+            panic!("unused in test backend");
+        }
+
         async fn get_terminal_activity_status(&self, _id: String) -> Result<(bool, u64), String> {
             panic!("unused in test backend");
         }
@@ -48,6 +48,11 @@ pub fn terminal_id_for_session_bottom(name: &str) -> String {
     format!("{}-bottom", session_terminal_base(name))
 }
 
+/// Terminal that runs the project setup script before the session's agent starts
+pub fn terminal_id_for_session_setup(name: &str) -> String {
+    format!("{}-setup", session_terminal_base(name))
+}
+
 fn strip_numeric_suffix(id: &str) -> &str {
     if let Some((prefix, suffix)) = id.rsplit_once('-') {
         if suffix.chars().all(|c| c.is_ascii_digit()) {
@@ -112,6 +117,9 @@ mod tests {
         assert_eq!(format!("{base}-top"), top);
         let bottom = terminal_id_for_session_bottom("alpha beta");
         assert_eq!(format!("{base}-bottom"), bottom);
+        let setup = terminal_id_for_session_setup("alpha beta");
+        assert_eq!(format!("{base}-setup"), setup);
+        assert!(!is_session_top_terminal_id(&setup));
     }
 
     #[test]
@@ -91,7 +91,8 @@ pub fn check_binary(
     })
 }
 
-fn detect_version(path: &Path) -> Option<String> {
+/// First line the binary prints for `--version`, `-v` or `version`.
+pub fn detect_version(path: &Path) -> Option<String> {
     let version_flags = vec!["--version", "-v", "version"];
 
     for flag in version_flags {
@@ -54,7 +54,7 @@ import { startSessionTop, computeProjectOrchestratorId, AGENT_START_TIMEOUT_MESS
 import { createTerminalBackend } from './terminal/transport/backend'
 import { beginSplitDrag, endSplitDrag } from './utils/splitDragCoordinator'
 import { useOptionalToast } from './common/toast/ToastProvider'
-import { AppUpdateResultPayload } from './common/events'
+import { AgentVersionWarning, AppUpdateResultPayload } from './common/events'
 import { RawSession, EnrichedSession } from './types/session'
 import { stableSessionTerminalId } from './common/terminalIdentity'
 
@@ -274,6 +274,47 @@ function AppContent() {
     }
   }, [toast])
 
+  useEffect(() => {
+    if (!toast) return
+
+    let disposed = false
+    let unlisten: (() => void) | null = null
+
+    const subscribe = async () => {
+      try {
+        const stop = await listenEvent(SchaltEvent.AgentVersionWarning, (payload: AgentVersionWarning) => {
+          // Detection reruns whenever settings open; warn once per agent version and feature
+          const key = `${payload.agent_name}@${payload.version}:${payload.feature}`
+          if (shownVersionWarningsRef.current.has(key)) return
+          shownVersionWarningsRef.current.add(key)
+          toast.pushToast({
+            tone: 'warning',
+            title: `Outdated ${payload.agent_name} version`,
+            description: payload.message,
+            durationMs: 7000,
+          })
+        })
+
+        if (disposed) {
+          stop()
+        } else {
+          unlisten = stop
+        }
+      } catch (error) {
+        logger.error('[App] Failed to attach agent version warning listener', error)
+      }
+    }
+
+    subscribe()
+
+    return () => {
+      disposed = true
+      if (unlisten) {
+        unlisten()
+      }
+    }
+  }, [toast])
+
   // Get dynamic shortcut displays
   const shortcuts = useMultipleShortcutDisplays([
     KeyboardShortcutAction.NewSession,
@@ -303,6 +344,7 @@ function AppContent() {
   const projectSwitchTargetRef = useRef<string | null>(null)
   const previousFocusRef = useRef<Element | null>(null)
   const lastAutoUpdateVersionRef = useRef<string | null>(null)
+  const shownVersionWarningsRef = useRef<Set<string>>(new Set())
   const { config: keyboardShortcutConfig } = useKeyboardShortcutsConfig()
   const platform = useMemo(() => detectPlatformSafe(), [])
   const isMac = platform === 'mac'
@@ -1,19 +1,29 @@
+import type { AgentActivityState, BaseFreshness, EnvVarSet, MergeQueueSnapshot, TerminalTheme } from '../types/session'
+
 export enum SchaltEvent {
   SessionsRefreshed = 'schaltwerk:sessions-refreshed',
   SessionAdded = 'schaltwerk:session-added',
   SessionRemoved = 'schaltwerk:session-removed',
+  SessionRenamed = 'schaltwerk:session-renamed',
   ArchiveUpdated = 'schaltwerk:archive-updated',
   SessionCancelling = 'schaltwerk:session-cancelling',
+  SessionUnblocked = 'schaltwerk:session-unblocked',
   CancelError = 'schaltwerk:cancel-error',
   TerminalCreated = 'schaltwerk:terminal-created',
 
   SessionActivity = 'schaltwerk:session-activity',
   SessionGitStats = 'schaltwerk:session-git-stats',
   TerminalAttention = 'schaltwerk:terminal-attention',
+  SessionIdle = 'schaltwerk:session-idle',
+  SessionAgentState = 'schaltwerk:session-agent-state',
   TerminalClosed = 'schaltwerk:terminal-closed',
+  TerminalCleanupReport = 'schaltwerk:terminal-cleanup-report',
   TerminalAgentStarted = 'schaltwerk:terminal-agent-started',
+  TerminalStats = 'schaltwerk:terminal-stats',
+  TerminalPasteConfirmation = 'schaltwerk:terminal-paste-confirmation',
   TerminalForceScroll = 'schaltwerk:terminal-force-scroll',
   PtyData = 'schaltwerk:pty-data',
+  AgentVersionWarning = 'schaltwerk:agent-version-warning',
   ProjectReady = 'schaltwerk:project-ready',
   OpenDirectory = 'schaltwerk:open-directory',
   OpenHome = 'schaltwerk:open-home',
@@ -23,12 +33,27 @@ export enum SchaltEvent {
   GitOperationStarted = 'schaltwerk:git-operation-started',
   GitOperationCompleted = 'schaltwerk:git-operation-completed',
   GitOperationFailed = 'schaltwerk:git-operation-failed',
+  MergeQueueUpdated = 'schaltwerk:merge-queue-updated',
   ProjectFilesUpdated = 'schaltwerk:project-files-updated',
   GitHubStatusChanged = 'schaltwerk:github-status-changed',
+  McpStatusChanged = 'schaltwerk:mcp-status-changed',
+  SettingsChanged = 'schaltwerk:settings-changed',
+  AgentProgressUpdated = 'schaltwerk:agent-progress-updated',
+  RunFinished = 'schaltwerk:run-finished',
+  SetupStarted = 'schaltwerk:setup-started',
+  SetupFinished = 'schaltwerk:setup-finished',
+  SetupError = 'schaltwerk:setup-error',
+  LogLine = 'schaltwerk:log-line',
   AppUpdateResult = 'schaltwerk:app-update-result'
 }
 
 
+// Added as `_meta` to object payloads emitted while a backend operation runs
+export interface EventMetadata {
+  correlation_id: string | null
+  session_id: string | null
+}
+
 export interface SessionActivityUpdated {
   session_id: string
   session_name: string
@@ -38,6 +63,137 @@ export interface SessionActivityUpdated {
   is_blocked: boolean | null
 }
 
+export type AgentTodoStatus = 'pending' | 'in_progress' | 'completed'
+
+export interface AgentTodo {
+  content: string
+  status: AgentTodoStatus
+  active_form?: string
+}
+
+export interface AgentProgress {
+  agent_session_id: string
+  todos: AgentTodo[]
+  completed: number
+  total: number
+  updated_at: number
+}
+
+export interface SessionAgentProgressUpdated {
+  session_id: string
+  session_name: string
+  progress: AgentProgress
+}
+
+export interface RunFinishedPayload {
+  terminal_id: string
+  exit_code: number | null
+  duration_ms: number
+}
+
+export type SetupStatus = 'running' | 'succeeded' | 'failed' | 'cancelled'
+
+export interface SetupStatusPayload {
+  session_name: string
+  terminal_id: string
+  status: SetupStatus
+  exit_code: number | null
+  duration_ms: number | null
+  error: string | null
+}
+
+// A backend log record; `line` is 0 for streamed records
+export interface LogEntry {
+  line: number
+  timestamp: string
+  level: string
+  target: string
+  message: string
+  session_id: string | null
+  correlation_id: string | null
+}
+
+export interface LogFilter {
+  level?: string | null
+  target?: string | null
+  session_id?: string | null
+}
+
+export interface LogPage {
+  file: string | null
+  entries: LogEntry[]
+  total: number
+  offset: number
+  has_more: boolean
+}
+
+export interface LogTail {
+  file: string | null
+  entries: LogEntry[]
+  last_line: number
+}
+
+export interface DatabaseBackup {
+  path: string
+  created_at: string
+  size_bytes: number
+}
+
+export interface DatabaseIntegrityReport {
+  ok: boolean
+  problems: string[]
+}
+
+// A crash report written by the backend panic hook; `id` is passed to ExportCrashReport
+export interface CrashReportSummary {
+  id: string
+  created_at: string
+  message: string
+}
+
+export interface TerminalResourceUsage {
+  terminal_id: string
+  pid: number
+  cpu_percent: number
+  memory_bytes: number
+  process_count: number
+}
+
+export interface SessionIdleState {
+  session_id: string
+  terminal_id: string
+  idle: boolean
+  waiting_for_input: boolean
+}
+
+export interface SessionAgentState {
+  session_id: string
+  terminal_id: string
+  state: AgentActivityState
+}
+
+export interface AgentVersionWarning {
+  agent_name: string
+  version: string
+  feature: 'resume'
+  minimum_version: string
+  message: string
+}
+
+export interface TerminalCleanupReport {
+  terminal_id: string
+  signaled: number[]
+  escalated: number[]
+  orphaned: number[]
+}
+
+export interface PendingPasteInfo {
+  paste_id: string
+  terminal_id: string
+  line_count: number
+  preview: string
+}
+
 export interface SessionGitStatsUpdated {
   session_id: string
   session_name: string
@@ -51,6 +207,7 @@ export interface SessionGitStatsUpdated {
   merge_conflicting_paths?: string[]
   merge_is_up_to_date?: boolean
   worktree_size_bytes?: number
+  base_freshness?: BaseFreshness
 }
 
 export interface FollowUpMessagePayload {
@@ -59,6 +216,8 @@ export interface FollowUpMessagePayload {
   timestamp: number
   terminal_id: string
   message_type: 'system' | 'user'
+  /** Project the webhook was scoped to; absent for events from older bridges */
+  project_path?: string
 }
 
 export interface PtyDataPayload {
@@ -105,6 +264,32 @@ export interface GitHubStatusPayload {
   repository?: GitHubRepositoryPayload | null
 }
 
+export type McpServerState = 'stopped' | 'running' | 'restarting' | 'failed'
+
+export interface McpHealth {
+  state: McpServerState
+  pid: number | null
+  restartCount: number
+  lastError: string | null
+}
+
+// The settings section a save changed, with its new value
+export type SettingsChangedPayload =
+  | { kind: 'agent_env_vars'; agent_type: string; env_vars: Record<string, string> }
+  | { kind: 'agent_cli_args'; agent_type: string; cli_args: string }
+  | {
+      kind: 'terminal'
+      terminal: { shell: string | null; shellArgs: string[]; fontFamily?: string | null; webglEnabled?: boolean; theme?: string | null }
+      shell_changed: boolean
+    }
+  | { kind: 'diff_view_preferences'; preferences: Record<string, unknown> }
+  | { kind: 'session_preferences'; preferences: Record<string, unknown> }
+  | { kind: 'keyboard_shortcuts'; shortcuts: Record<string, string[]> }
+  | { kind: 'env_sets'; env_sets: EnvVarSet[] }
+  | { kind: 'terminal_themes'; themes: TerminalTheme[] }
+  | { kind: 'font_sizes'; terminal_font_size: number; ui_font_size: number }
+  | { kind: 'json_logs'; enabled: boolean }
+
 export interface GitHubPrPayload {
   branch: string
   url: string
@@ -142,20 +327,34 @@ export type EventPayloadMap = {
     parent_branch: string
     created_at: string
     last_modified?: string
+    project_path?: string
+  }
+  [SchaltEvent.SessionRemoved]: { session_name: string, project_path?: string }
+  [SchaltEvent.SessionRenamed]: {
+    old_session_name: string
+    session_name: string
+    branch: string
+    worktree_path: string
   }
-  [SchaltEvent.SessionRemoved]: { session_name: string }
   [SchaltEvent.ArchiveUpdated]: { repo: string, count: number }
   [SchaltEvent.SessionCancelling]: { session_name: string }
+  [SchaltEvent.SessionUnblocked]: { session_name: string, dependency: string }
   [SchaltEvent.CancelError]: { session_name: string, error: string }
   [SchaltEvent.TerminalCreated]: { terminal_id: string, cwd: string }
 
   [SchaltEvent.SessionActivity]: SessionActivityUpdated
   [SchaltEvent.SessionGitStats]: SessionGitStatsUpdated
   [SchaltEvent.TerminalAttention]: { session_id: string, terminal_id: string, needs_attention: boolean }
+  [SchaltEvent.SessionIdle]: SessionIdleState
+  [SchaltEvent.SessionAgentState]: SessionAgentState
   [SchaltEvent.TerminalClosed]: { terminal_id: string }
+  [SchaltEvent.TerminalCleanupReport]: TerminalCleanupReport
   [SchaltEvent.TerminalAgentStarted]: { terminal_id: string, session_name?: string }
+  [SchaltEvent.TerminalStats]: TerminalResourceUsage[]
+  [SchaltEvent.TerminalPasteConfirmation]: PendingPasteInfo
   [SchaltEvent.TerminalForceScroll]: { terminal_id: string }
   [SchaltEvent.PtyData]: PtyDataPayload
+  [SchaltEvent.AgentVersionWarning]: AgentVersionWarning
   [SchaltEvent.ProjectReady]: string
   [SchaltEvent.OpenDirectory]: string
   [SchaltEvent.OpenHome]: string
@@ -169,7 +368,16 @@ export type EventPayloadMap = {
   [SchaltEvent.GitOperationStarted]: GitOperationPayload
   [SchaltEvent.GitOperationCompleted]: GitOperationPayload
   [SchaltEvent.GitOperationFailed]: GitOperationFailedPayload
+  [SchaltEvent.MergeQueueUpdated]: MergeQueueSnapshot
   [SchaltEvent.ProjectFilesUpdated]: string[]
   [SchaltEvent.GitHubStatusChanged]: GitHubStatusPayload
+  [SchaltEvent.McpStatusChanged]: McpHealth
+  [SchaltEvent.SettingsChanged]: SettingsChangedPayload
+  [SchaltEvent.AgentProgressUpdated]: SessionAgentProgressUpdated
+  [SchaltEvent.RunFinished]: RunFinishedPayload
+  [SchaltEvent.SetupStarted]: SetupStatusPayload
+  [SchaltEvent.SetupFinished]: SetupStatusPayload
+  [SchaltEvent.SetupError]: SetupStatusPayload
+  [SchaltEvent.LogLine]: LogEntry
   [SchaltEvent.AppUpdateResult]: AppUpdateResultPayload
 }
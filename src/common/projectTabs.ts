@@ -20,3 +20,11 @@ export function determineNextActiveTab(tabs: ProjectTab[], closingPath: string):
 
   return null
 }
+
+const trimTrailingSlashes = (path: string) => path.replace(/[\\/]+$/, '')
+
+/** Whether a project-scoped backend event was meant for a project other than the open one */
+export function isForOtherProject(eventProjectPath: string | undefined, projectPath: string | null): boolean {
+  if (!eventProjectPath || !projectPath) return false
+  return trimTrailingSlashes(eventProjectPath) !== trimTrailingSlashes(projectPath)
+}
@@ -12,6 +12,8 @@ export const TauriCommands = {
   GitHubAuthenticate: 'github_authenticate',
   GitHubConnectProject: 'github_connect_project',
   GitHubCreateReviewedPr: 'github_create_reviewed_pr',
+  GitHubPushSessionBranch: 'github_push_session_branch',
+  GitHubCreatePullRequestForSession: 'github_create_pull_request_for_session',
   CreateNewProject: 'create_new_project',
   CreateRunTerminal: 'create_run_terminal',
   CreateTerminal: 'create_terminal',
@@ -40,6 +42,7 @@ export const TauriCommands = {
   GetFileDiffFromMain: 'get_file_diff_from_main',
   GetGitGraphHistory: 'get_git_graph_history',
   GetGitGraphCommitFiles: 'get_git_graph_commit_files',
+  GetMcpHealth: 'get_mcp_health',
   GetMcpStatus: 'get_mcp_status',
   GetAmpMcpServers: 'get_amp_mcp_servers',
   SetAmpMcpServers: 'set_amp_mcp_servers',
@@ -47,15 +50,51 @@ export const TauriCommands = {
   GetProjectActionButtons: 'get_project_action_buttons',
   GetProjectDefaultBaseBranch: 'get_project_default_base_branch',
   GetProjectDefaultBranch: 'get_project_default_branch',
+  GetAgentProgress: 'get_agent_progress',
   GetProjectRunScript: 'get_project_run_script',
+  GetProjectCodexSettings: 'get_project_codex_settings',
+  GetProjectAgentDefaults: 'get_project_agent_defaults',
+  GetProjectSetupScripts: 'get_project_setup_scripts',
   GetProjectSessionsSettings: 'get_project_sessions_settings',
   GetProjectSettings: 'get_project_settings',
   GetProjectMergePreferences: 'get_project_merge_preferences',
   GetRecentProjects: 'get_recent_projects',
   GetSessionPreferences: 'get_session_preferences',
   GetTerminalBuffer: 'get_terminal_buffer',
+  SearchTerminalBuffer: 'search_terminal_buffer',
+  GetTerminalResourceUsage: 'get_terminal_resource_usage',
+  ResumeTerminalOutput: 'resume_terminal_output',
+  GetTerminalCommandHistory: 'get_terminal_command_history',
+  GetSessionIdleStates: 'get_session_idle_states',
+  GetSessionAgentStates: 'get_session_agent_states',
+  GetSessionTimeReport: 'get_session_time_report',
+  GetSessionCostReport: 'get_session_cost_report',
+  ListPendingFollowUps: 'list_pending_follow_ups',
+  ListPromptTemplates: 'list_prompt_templates',
+  SavePromptTemplate: 'save_prompt_template',
+  DeletePromptTemplate: 'delete_prompt_template',
+  RenderPromptTemplate: 'render_prompt_template',
+  ApplyPromptTemplate: 'apply_prompt_template',
+  GetWorktreeDiskUsage: 'get_worktree_disk_usage',
+  PruneWorktrees: 'prune_worktrees',
   GetTerminalBacklog: 'get_terminal_backlog',
   GetTerminalSettings: 'get_terminal_settings',
+  GetJsonLogs: 'get_json_logs',
+  SetJsonLogs: 'set_json_logs',
+  QueryLogs: 'query_logs',
+  TailLogs: 'tail_logs',
+  StartLogStream: 'start_log_stream',
+  StopLogStream: 'stop_log_stream',
+  ListCrashReports: 'list_crash_reports',
+  ExportCrashReport: 'export_crash_report',
+  BackupDatabase: 'backup_database',
+  RestoreDatabase: 'restore_database',
+  CheckDatabaseIntegrity: 'check_database_integrity',
+  ListDatabaseBackups: 'list_database_backups',
+  GetTerminalThemes: 'get_terminal_themes',
+  SetTerminalTheme: 'set_terminal_theme',
+  DeleteTerminalTheme: 'delete_terminal_theme',
+  ValidateTerminalFont: 'validate_terminal_font',
   GetTerminalUiPreferences: 'get_terminal_ui_preferences',
   GetTutorialCompleted: 'get_tutorial_completed',
   InitializeProject: 'initialize_project',
@@ -63,7 +102,10 @@ export const TauriCommands = {
   ListAvailableOpenApps: 'list_available_open_apps',
   ListProjectBranches: 'list_project_branches',
   OpenInApp: 'open_in_app',
+  OpenInBrowser: 'open_in_browser',
   PasteAndSubmitTerminal: 'paste_and_submit_terminal',
+  ConfirmTerminalPaste: 'confirm_terminal_paste',
+  BroadcastToTerminals: 'broadcast_to_terminals',
   PathExists: 'path_exists',
   GetEnvironmentVariable: 'get_environment_variable',
   PtySpawn: 'pty_spawn',
@@ -72,8 +114,12 @@ export const TauriCommands = {
   PtyKill: 'pty_kill',
   PtyAck: 'pty_ack',
   PtySubscribe: 'pty_subscribe',
+  StartTerminalRecording: 'start_terminal_recording',
+  StopTerminalRecording: 'stop_terminal_recording',
+  ExportTerminalRecording: 'export_terminal_recording',
   RefreshAgentBinaryDetection: 'refresh_agent_binary_detection',
   RemoveMcpForProject: 'remove_mcp_for_project',
+  SyncMcpConfigs: 'sync_mcp_configs',
   RemoveRecentProject: 'remove_recent_project',
   RepositoryIsEmpty: 'repository_is_empty',
   OpenDocumentsPrivacySettings: 'open_documents_privacy_settings',
@@ -84,6 +130,9 @@ export const TauriCommands = {
   SchaltwerkCoreConvertSessionToDraft: 'schaltwerk_core_convert_session_to_draft',
   SchaltwerkCoreCreateAndStartSpecSession: 'schaltwerk_core_create_and_start_spec_session',
   SchaltwerkCoreCreateSession: 'schaltwerk_core_create_session',
+  SchaltwerkCoreDuplicateSession: 'schaltwerk_core_duplicate_session',
+  SchaltwerkCoreRelocateSessionWorktree: 'schaltwerk_core_relocate_session_worktree',
+  SchaltwerkCoreCancelSessionCreation: 'schaltwerk_core_cancel_session_creation',
   SchaltwerkCoreCreateSpecSession: 'schaltwerk_core_create_spec_session',
   SchaltwerkCoreDeleteArchivedSpec: 'schaltwerk_core_delete_archived_spec',
   SchaltwerkCoreGetAgentType: 'schaltwerk_core_get_agent_type',
@@ -95,7 +144,13 @@ export const TauriCommands = {
   SchaltwerkCoreGetSkipPermissions: 'schaltwerk_core_get_skip_permissions',
   SchaltwerkCoreGetOrchestratorSkipPermissions: 'schaltwerk_core_get_orchestrator_skip_permissions',
   SchaltwerkCoreGetMergePreview: 'schaltwerk_core_get_merge_preview',
+  SchaltwerkCoreMergeSessionDryRun: 'schaltwerk_core_merge_session_dry_run',
+  SchaltwerkCoreCherryPickSessionCommits: 'schaltwerk_core_cherry_pick_session_commits',
   SchaltwerkCoreHasUncommittedChanges: 'schaltwerk_core_has_uncommitted_changes',
+  SchaltwerkCoreStashSessionChanges: 'schaltwerk_core_stash_session_changes',
+  SchaltwerkCoreListSessionStashes: 'schaltwerk_core_list_session_stashes',
+  SchaltwerkCorePopSessionStash: 'schaltwerk_core_pop_session_stash',
+  SchaltwerkCoreRebaseSessionOnBase: 'schaltwerk_core_rebase_session_on_base',
   SchaltwerkCoreListArchivedSpecs: 'schaltwerk_core_list_archived_specs',
   SchaltwerkCoreListEnrichedSessions: 'schaltwerk_core_list_enriched_sessions',
   SchaltwerkCoreListProjectFiles: 'schaltwerk_core_list_project_files',
@@ -103,17 +158,43 @@ export const TauriCommands = {
   SchaltwerkCoreLogFrontendMessage: 'schaltwerk_core_log_frontend_message',
   SchaltwerkCoreMarkReady: 'schaltwerk_core_mark_ready',
   SchaltwerkCoreMarkSessionReady: 'schaltwerk_core_mark_session_ready',
+  GenerateCommitMessage: 'generate_commit_message',
+  SummarizeSpec: 'summarize_spec',
+  SwitchSessionAgent: 'switch_session_agent',
+  SchaltwerkCorePreviewStaleSessions: 'schaltwerk_core_preview_stale_sessions',
+  SchaltwerkCoreListAgentCrashes: 'schaltwerk_core_list_agent_crashes',
+  SchaltwerkCoreRenameSession: 'schaltwerk_core_rename_session',
   SchaltwerkCoreRenameVersionGroup: 'schaltwerk_core_rename_version_group',
   SchaltwerkCoreResetOrchestrator: 'schaltwerk_core_reset_orchestrator',
   SchaltwerkCoreResetSessionWorktree: 'schaltwerk_core_reset_session_worktree',
   // Discard changes for a single file (session or orchestrator)
   SchaltwerkCoreDiscardFileInSession: 'schaltwerk_core_discard_file_in_session',
+  SchaltwerkCoreListSessionFileHunks: 'schaltwerk_core_list_session_file_hunks',
+  SchaltwerkCoreDiscardHunksInSession: 'schaltwerk_core_discard_hunks_in_session',
   SchaltwerkCoreDiscardFileInOrchestrator: 'schaltwerk_core_discard_file_in_orchestrator',
   SchaltwerkCoreRestoreArchivedSpec: 'schaltwerk_core_restore_archived_spec',
   SchaltwerkCoreSetAgentType: 'schaltwerk_core_set_agent_type',
   SchaltwerkCoreSetArchiveMaxEntries: 'schaltwerk_core_set_archive_max_entries',
   SchaltwerkCoreSetFontSizes: 'schaltwerk_core_set_font_sizes',
   SchaltwerkCoreSetSessionAgentType: 'schaltwerk_core_set_session_agent_type',
+  SchaltwerkCoreSetSessionCodexProfile: 'schaltwerk_core_set_session_codex_profile',
+  SchaltwerkCoreGetSessionEnvironment: 'schaltwerk_core_get_session_environment',
+  SchaltwerkCoreSetSessionEnvironment: 'schaltwerk_core_set_session_environment',
+  SchaltwerkCoreSetSessionDependencies: 'schaltwerk_core_set_session_dependencies',
+  SchaltwerkCoreSetSessionAutoNudge: 'schaltwerk_core_set_session_auto_nudge',
+  SchaltwerkCoreGetSessionNotes: 'schaltwerk_core_get_session_notes',
+  SchaltwerkCoreSetSessionNotes: 'schaltwerk_core_set_session_notes',
+  AddReviewComment: 'add_review_comment',
+  ListReviewComments: 'list_review_comments',
+  ResolveReviewComment: 'resolve_review_comment',
+  RequestReviewChanges: 'request_review_changes',
+  SchaltwerkCoreListSessionLabels: 'schaltwerk_core_list_session_labels',
+  SchaltwerkCoreSetSessionLabels: 'schaltwerk_core_set_session_labels',
+  SchaltwerkCoreAddSessionLabel: 'schaltwerk_core_add_session_label',
+  SchaltwerkCoreRemoveSessionLabel: 'schaltwerk_core_remove_session_label',
+  SchaltwerkCoreRunSessionSetup: 'schaltwerk_core_run_session_setup',
+  SchaltwerkCoreCancelSessionSetup: 'schaltwerk_core_cancel_session_setup',
+  SchaltwerkCoreGetSessionSetupStatus: 'schaltwerk_core_get_session_setup_status',
   SchaltwerkCoreSetSkipPermissions: 'schaltwerk_core_set_skip_permissions',
   SchaltwerkCoreSetOrchestratorAgentType: 'schaltwerk_core_set_orchestrator_agent_type',
   SchaltwerkCoreSetOrchestratorSkipPermissions: 'schaltwerk_core_set_orchestrator_skip_permissions',
@@ -127,17 +208,47 @@ export const TauriCommands = {
   SchaltwerkCoreUnmarkSessionReady: 'schaltwerk_core_unmark_session_ready',
   SchaltwerkCoreUpdateSpecContent: 'schaltwerk_core_update_spec_content',
   SchaltwerkCoreMergeSessionToMain: 'schaltwerk_core_merge_session_to_main',
+  SchaltwerkCoreEnqueueMerge: 'schaltwerk_core_enqueue_merge',
+  SchaltwerkCoreRemoveFromMergeQueue: 'schaltwerk_core_remove_from_merge_queue',
+  SchaltwerkCoreGetMergeQueue: 'schaltwerk_core_get_merge_queue',
   SetAgentBinaryPath: 'set_agent_binary_path',
   SetAgentCliArgs: 'set_agent_cli_args',
   SetAgentEnvVars: 'set_agent_env_vars',
   SetDefaultOpenApp: 'set_default_open_app',
   SetDiffViewPreferences: 'set_diff_view_preferences',
   SetKeyboardShortcuts: 'set_keyboard_shortcuts',
+  ValidateKeyboardShortcuts: 'validate_keyboard_shortcuts',
   SetProjectActionButtons: 'set_project_action_buttons',
   ResetProjectActionButtonsToDefaults: 'reset_project_action_buttons_to_defaults',
   SetProjectDefaultBaseBranch: 'set_project_default_base_branch',
   SetProjectEnvironmentVariables: 'set_project_environment_variables',
+  GetProjectSettingsOverrides: 'get_project_settings_overrides',
+  SetProjectSettingsOverrides: 'set_project_settings_overrides',
+  GetSessionSettingsOverrides: 'get_session_settings_overrides',
+  SetSessionSettingsOverrides: 'set_session_settings_overrides',
+  GetEnvSets: 'get_env_sets',
+  SetEnvSet: 'set_env_set',
+  DeleteEnvSet: 'delete_env_set',
+  GetProjectEnvSets: 'get_project_env_sets',
+  SetProjectEnvSets: 'set_project_env_sets',
+  GetEffectiveSettings: 'get_effective_settings',
   SetProjectRunScript: 'set_project_run_script',
+  SetProjectCodexSettings: 'set_project_codex_settings',
+  SetProjectAgentDefaults: 'set_project_agent_defaults',
+  SetProjectSetupScripts: 'set_project_setup_scripts',
+  GetProjectContainerConfig: 'get_project_container_config',
+  SetProjectContainerConfig: 'set_project_container_config',
+  GetProjectDependencyCacheConfig: 'get_project_dependency_cache_config',
+  SetProjectDependencyCacheConfig: 'set_project_dependency_cache_config',
+  GetProjectWorktreeRoot: 'get_project_worktree_root',
+  SetProjectWorktreeRoot: 'set_project_worktree_root',
+  GetProjectStaleSessionPolicy: 'get_project_stale_session_policy',
+  SetProjectStaleSessionPolicy: 'set_project_stale_session_policy',
+  GetProjectOutboundWebhookConfig: 'get_project_outbound_webhook_config',
+  SetProjectOutboundWebhookConfig: 'set_project_outbound_webhook_config',
+  DetectProjectDevEnvironments: 'detect_project_dev_environments',
+  GetProjectDevEnvironment: 'get_project_dev_environment',
+  SetProjectDevEnvironment: 'set_project_dev_environment',
   SetProjectSessionsSettings: 'set_project_sessions_settings',
   SetProjectSettings: 'set_project_settings',
   SetProjectMergePreferences: 'set_project_merge_preferences',
@@ -149,6 +260,13 @@ export const TauriCommands = {
   SetTutorialCompleted: 'set_tutorial_completed',
   StartFileWatcher: 'start_file_watcher',
   StopFileWatcher: 'stop_file_watcher',
+  StartRunWatch: 'start_run_watch',
+  StopRunWatch: 'stop_run_watch',
+  IsRunWatchActive: 'is_run_watch_active',
+  GetDetectedPorts: 'get_detected_ports',
+  StartPreviewProxy: 'start_preview_proxy',
+  StopPreviewProxy: 'stop_preview_proxy',
+  GetPreviewUrls: 'get_preview_urls',
   TerminalExists: 'terminal_exists',
   RegisterSessionTerminals: 'register_session_terminals',
   SuspendSessionTerminals: 'suspend_session_terminals',
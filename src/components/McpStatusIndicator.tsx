@@ -0,0 +1,35 @@
+import type { CSSProperties } from 'react'
+import { theme } from '../common/theme'
+import { useMcpHealth } from '../hooks/useMcpHealth'
+
+const containerStyle: CSSProperties = {
+  display: 'inline-flex',
+  alignItems: 'center',
+  gap: theme.spacing.xs,
+  marginRight: theme.spacing.sm,
+  fontSize: theme.fontSize.caption,
+  lineHeight: 1.2,
+}
+
+// Only shown while the MCP server Schaltwerk spawned is not running
+export function McpStatusIndicator() {
+  const health = useMcpHealth()
+  if (!health || (health.state !== 'restarting' && health.state !== 'failed')) {
+    return null
+  }
+
+  const failed = health.state === 'failed'
+  const color = failed ? theme.colors.accent.red.DEFAULT : theme.colors.accent.amber.DEFAULT
+  const label = failed ? 'MCP down' : 'MCP restarting'
+  const title = health.lastError ? `${label}: ${health.lastError}` : label
+
+  return (
+    <div style={{ ...containerStyle, color }} title={title} data-testid="mcp-status-indicator" data-no-drag>
+      <span
+        aria-hidden="true"
+        style={{ width: 8, height: 8, borderRadius: '50%', backgroundColor: color }}
+      />
+      <span>{label}</span>
+    </div>
+  )
+}
@@ -5,6 +5,7 @@ import { getCurrentWindow } from '@tauri-apps/api/window'
 import { useRef, useEffect, useCallback, useState } from 'react'
 import { OpenInSplitButton } from './OpenInSplitButton'
 import { BranchIndicator } from './BranchIndicator'
+import { McpStatusIndicator } from './McpStatusIndicator'
 import { logger } from '../utils/logger'
 import { theme } from '../common/theme'
 import { withOpacity } from '../common/colorUtils'
@@ -177,6 +178,9 @@ export function TopBar({
         {/* Branch indicator - only shows in development builds */}
         <BranchIndicator />
 
+        {/* MCP server health - only shows while the server is down */}
+        <McpStatusIndicator />
+
         {/* Open in IDE button - only show when a tab is active */}
         {activeTabPath && (
           <div className="mr-2" data-testid="topbar-open-button">
@@ -12,6 +12,8 @@ const MODEL_METADATA: Record<AgentType, { label: string; color: 'blue' | 'green'
     droid: { label: 'Droid', color: 'violet' },
     qwen: { label: 'Qwen', color: 'cyan' },
     amp: { label: 'Amp', color: 'yellow' },
+    aider: { label: 'Aider', color: 'green' },
+    goose: { label: 'Goose', color: 'cyan' },
     terminal: { label: 'Terminal Only', color: 'green' }
 }
 
@@ -113,4 +113,30 @@ describe('MarkReadyConfirmation', () => {
     })
   })
 
+  it('fills the commit message with one generated by the agent', async () => {
+    mockInvoke.mockImplementation(async (command: string) => {
+      if (command === TauriCommands.SchaltwerkCoreHasUncommittedChanges) return true
+      if (command === TauriCommands.GenerateCommitMessage) return 'feat(auth): add token refresh'
+      return true
+    })
+
+    render(<MarkReadyConfirmation {...baseProps} hasUncommittedChanges={true} />)
+
+    const generate = await screen.findByRole('button', { name: /Generate with agent/ })
+    fireEvent.click(generate)
+
+    await waitFor(() => {
+      expect(screen.getByDisplayValue('feat(auth): add token refresh')).toBeInTheDocument()
+    })
+    expect(mockInvoke).toHaveBeenCalledWith(TauriCommands.GenerateCommitMessage, { sessionName: 's1' })
+
+    fireEvent.click(screen.getByRole('button', { name: /Mark as Reviewed/ }))
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(TauriCommands.SchaltwerkCoreMarkSessionReady, {
+        name: 's1',
+        autoCommit: true,
+        commitMessage: 'feat(auth): add token refresh',
+      })
+    })
+  })
 })
@@ -22,12 +22,15 @@ export function MarkReadyConfirmation({
   const [autoCommit, setAutoCommit] = useState(true)
   const [customMessage, setCustomMessage] = useState('')
   const [loading, setLoading] = useState(false)
+  const [generating, setGenerating] = useState(false)
+  const [generateError, setGenerateError] = useState<string | null>(null)
   const [freshHasUncommitted, setFreshHasUncommitted] = useState<boolean | null>(null)
 
   useEffect(() => {
     if (!open) {
       setFreshHasUncommitted(null)
       setCustomMessage('')
+      setGenerateError(null)
       return
     }
 
@@ -54,6 +57,22 @@ export function MarkReadyConfirmation({
     return freshHasUncommitted ?? hasUncommittedChanges
   }, [freshHasUncommitted, hasUncommittedChanges])
   
+  const handleGenerate = useCallback(async () => {
+    if (generating) return
+
+    setGenerating(true)
+    setGenerateError(null)
+    try {
+      const message = await invoke<string>(TauriCommands.GenerateCommitMessage, { sessionName })
+      setCustomMessage(message)
+    } catch (error) {
+      logger.error('Failed to generate commit message:', error)
+      setGenerateError(String(error))
+    } finally {
+      setGenerating(false)
+    }
+  }, [generating, sessionName])
+
   const handleConfirm = useCallback(async () => {
     if (loading) return
     if (effectiveHasUncommitted && !autoCommit) return
@@ -105,11 +124,22 @@ export function MarkReadyConfirmation({
           </label>
           {autoCommit && (
             <div className="ml-6 mb-3">
-              <label className="block text-xs text-slate-400 mb-1">
-                Custom commit message (optional):
-              </label>
-              <input
-                type="text"
+              <div className="flex items-center justify-between mb-1">
+                <label className="block text-xs text-slate-400">
+                  Custom commit message (optional):
+                </label>
+                <button
+                  type="button"
+                  onClick={() => { void handleGenerate() }}
+                  disabled={generating || loading}
+                  className="text-xs text-cyan-400 hover:text-cyan-300 disabled:text-slate-500 disabled:cursor-not-allowed"
+                  title="Ask the session's agent to write a commit message for these changes"
+                >
+                  {generating ? 'Generating…' : 'Generate with agent'}
+                </button>
+              </div>
+              <textarea
+                rows={3}
                 value={customMessage}
                 onChange={(e) => setCustomMessage(e.target.value)}
                 onKeyDown={(e) => {
@@ -119,11 +149,14 @@ export function MarkReadyConfirmation({
                   }
                 }}
                 placeholder={`Complete development work for ${sessionName}`}
-                className="w-full bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700 placeholder-slate-500 text-sm focus:outline-none focus:border-cyan-400 transition-colors"
+                className="w-full bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700 placeholder-slate-500 text-sm focus:outline-none focus:border-cyan-400 transition-colors resize-y"
                 spellCheck={false}
               />
+              {generateError && (
+                <p className="text-xs text-red-400 mt-1">{generateError}</p>
+              )}
               <p className="text-xs text-slate-500 mt-1">
-                Leave empty to use the default message • Press Enter to submit
+                Leave empty to use the default message • Press Enter to submit, Shift+Enter for a new line
               </p>
             </div>
           )}
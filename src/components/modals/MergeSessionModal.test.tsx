@@ -8,10 +8,12 @@ const preview = {
   parentBranch: 'main',
   squashCommands: ['git reset --soft main', 'git commit -m "message"'],
   reapplyCommands: ['git rebase main'],
+  fastForwardCommands: ['git update-ref refs/heads/main refs/heads/feature/test-session'],
   defaultCommitMessage: 'Merge test session',
   hasConflicts: false,
   conflictingPaths: [],
   isUpToDate: false,
+  canFastForward: true,
 }
 
 function renderModal(
@@ -83,6 +85,15 @@ describe('MergeSessionModal', () => {
     expect(onConfirm).toHaveBeenCalledWith('reapply' as MergeModeOption)
   })
 
+  it('blocks fast-forward once the parent branch has moved', () => {
+    const { onConfirm } = renderModal({ preview: { ...preview, canFastForward: false } })
+    fireEvent.click(screen.getByRole('button', { name: 'Fast-forward only' }))
+    const confirm = findConfirmButton()
+    expect(confirm).toBeDisabled()
+    fireEvent.click(confirm)
+    expect(onConfirm).not.toHaveBeenCalled()
+  })
+
   it('renders auto-cancel toggle reflecting disabled state', () => {
     renderModal({ autoCancelEnabled: false })
     const toggle = screen.getByRole('checkbox', { name: 'Auto-cancel after merge' }) as HTMLInputElement
@@ -103,6 +114,46 @@ describe('MergeSessionModal', () => {
     expect(toggle.checked).toBe(true)
   })
 
+  it('queues the merge with the commit message and rebase choice', () => {
+    const onQueue = vi.fn()
+    const { onConfirm } = renderModal({ onQueue })
+    fireEvent.change(screen.getByLabelText('Commit message'), { target: { value: ' Ship it ' } })
+    fireEvent.click(screen.getByRole('checkbox', { name: 'Rebase before queued merge' }))
+    fireEvent.click(screen.getByRole('button', { name: 'Add to merge queue' }))
+    expect(onQueue).toHaveBeenCalledWith('squash' as MergeModeOption, 'Ship it', false)
+    expect(onConfirm).not.toHaveBeenCalled()
+  })
+
+  it('lists reviewed sessions that edit the same files', () => {
+    renderModal({
+      preview: {
+        ...preview,
+        sessionOverlaps: [
+          { sessionName: 'other-session', sessionBranch: 'schaltwerk/other-session', overlappingPaths: ['src/lib.rs'], conflictingPaths: ['src/lib.rs'] },
+          { sessionName: 'clean-session', sessionBranch: 'schaltwerk/clean-session', overlappingPaths: ['README.md'], conflictingPaths: [] },
+        ],
+      },
+    })
+    expect(screen.getByText('Other reviewed sessions edit the same files')).toBeInTheDocument()
+    expect(screen.getByText(/conflicts in src\/lib\.rs/)).toBeInTheDocument()
+    expect(screen.getByText(/also changes README\.md, but merges cleanly/)).toBeInTheDocument()
+  })
+
+  it('requires confirmation before merging changes to protected paths', () => {
+    const { onConfirm } = renderModal({
+      preview: { ...preview, protectedPaths: ['.github/workflows/ci.yml'] },
+    })
+    expect(screen.getByText('.github/workflows/ci.yml')).toBeInTheDocument()
+    fireEvent.click(screen.getByRole('button', { name: 'Reapply commits' }))
+    const confirm = findConfirmButton()
+    expect(confirm).toBeDisabled()
+
+    fireEvent.click(screen.getByRole('checkbox', { name: 'Confirm protected path changes' }))
+    expect(confirm).not.toBeDisabled()
+    fireEvent.click(confirm)
+    expect(onConfirm).toHaveBeenCalledWith('reapply' as MergeModeOption)
+  })
+
   it('surfaces keyboard hints for cancel and confirm actions', () => {
     renderModal()
     const cancel = screen.getAllByRole('button').find(button => button.textContent?.includes('Cancel'))
@@ -2,18 +2,23 @@ import { useCallback, useEffect, useLayoutEffect, useMemo, useRef, useState } fr
 import { theme } from '../../common/theme'
 import { useModal } from '../../contexts/ModalContext'
 import { LoadingSpinner } from '../common/LoadingSpinner'
+import type { SessionOverlap } from '../../types/session'
 
-export type MergeModeOption = 'squash' | 'reapply'
+export type MergeModeOption = 'squash' | 'reapply' | 'fast_forward'
 
 interface MergePreviewResponse {
   sessionBranch: string
   parentBranch: string
   squashCommands: string[]
   reapplyCommands: string[]
+  fastForwardCommands: string[]
   defaultCommitMessage: string
   hasConflicts: boolean
   conflictingPaths: string[]
   isUpToDate: boolean
+  canFastForward: boolean
+  sessionOverlaps?: SessionOverlap[]
+  protectedPaths?: string[]
 }
 
 interface MergeSessionModalProps {
@@ -24,6 +29,7 @@ interface MergeSessionModalProps {
   error?: string | null
   onClose: () => void
   onConfirm: (mode: MergeModeOption, commitMessage?: string) => void
+  onQueue?: (mode: MergeModeOption, commitMessage: string | undefined, autoRebase: boolean) => void
   autoCancelEnabled: boolean
   onToggleAutoCancel: (next: boolean) => void
 }
@@ -51,12 +57,15 @@ export function MergeSessionModal({
   error,
   onClose,
   onConfirm,
+  onQueue,
   autoCancelEnabled,
   onToggleAutoCancel,
 }: MergeSessionModalProps) {
   const { registerModal, unregisterModal } = useModal()
   const [mode, setMode] = useState<MergeModeOption>('squash')
   const [commitMessage, setCommitMessage] = useState('')
+  const [autoRebase, setAutoRebase] = useState(true)
+  const [protectedPathsConfirmed, setProtectedPathsConfirmed] = useState(false)
   const commitMessageInputRef = useRef<HTMLInputElement | null>(null)
 
   const focusCommitMessage = useCallback(() => {
@@ -83,6 +92,8 @@ export function MergeSessionModal({
     if (!open) {
       setMode('squash')
       setCommitMessage('')
+      setAutoRebase(true)
+      setProtectedPathsConfirmed(false)
       return
     }
 
@@ -101,6 +112,10 @@ export function MergeSessionModal({
   const hasConflicts = preview?.hasConflicts ?? false
   const conflictingPaths = preview?.conflictingPaths ?? []
   const isUpToDate = preview?.isUpToDate ?? false
+  const sessionOverlaps = preview?.sessionOverlaps ?? []
+  const protectedPaths = preview?.protectedPaths ?? []
+  const isProtectedPathsUnconfirmed = protectedPaths.length > 0 && !protectedPathsConfirmed
+  const isFastForwardBlocked = mode === 'fast_forward' && !(preview?.canFastForward ?? false)
 
   const isCommitMessageMissing = mode === 'squash' && commitMessage.trim().length === 0
 
@@ -110,7 +125,9 @@ export function MergeSessionModal({
     !preview ||
     hasConflicts ||
     isUpToDate ||
-    isCommitMessageMissing
+    isFastForwardBlocked ||
+    isCommitMessageMissing ||
+    isProtectedPathsUnconfirmed
 
   const confirmTitle = hasConflicts
     ? 'Resolve merge conflicts before merging.'
@@ -118,16 +135,25 @@ export function MergeSessionModal({
     ? 'Session has no commits to merge into the parent branch.'
     : status === 'running'
     ? 'Merging…'
+    : isFastForwardBlocked
+    ? `${parentBranch} has moved on; choose squash or reapply instead.`
     : isCommitMessageMissing
     ? 'Enter a commit message to enable merge.'
+    : isProtectedPathsUnconfirmed
+    ? 'Confirm the changes to protected paths to enable merge.'
     : 'Merge session (⌘↵)'
 
+  const handleQueue = useCallback(() => {
+    if (!onQueue || confirmDisabled) return
+    onQueue(mode, mode === 'squash' ? commitMessage.trim() : undefined, autoRebase)
+  }, [onQueue, confirmDisabled, mode, commitMessage, autoRebase])
+
   const handleToggleAutoCancel = useCallback(() => {
     onToggleAutoCancel(!autoCancelEnabled)
   }, [onToggleAutoCancel, autoCancelEnabled])
 
   const handleConfirm = useCallback(() => {
-    if (status === 'loading' || status === 'running' || hasConflicts || isUpToDate) return
+    if (status === 'loading' || status === 'running' || hasConflicts || isUpToDate || isFastForwardBlocked || isProtectedPathsUnconfirmed) return
       if (mode === 'squash') {
         const trimmed = commitMessage.trim()
         if (!trimmed) {
@@ -139,7 +165,7 @@ export function MergeSessionModal({
     } else {
       onConfirm(mode)
     }
-  }, [commitMessage, mode, onConfirm, status, hasConflicts, isUpToDate, focusCommitMessage])
+  }, [commitMessage, mode, onConfirm, status, hasConflicts, isUpToDate, isFastForwardBlocked, isProtectedPathsUnconfirmed, focusCommitMessage])
 
   useEffect(() => {
     if (!open) return
@@ -166,6 +192,7 @@ export function MergeSessionModal({
   const modeDescriptions: Record<MergeModeOption, string> = {
     squash: 'Create a single commit with your message, then fast-forward the parent branch.',
     reapply: 'Replay all session commits onto the latest parent branch, preserving history.',
+    fast_forward: 'Move the parent branch to the session head as-is. Only possible while the parent has not moved.',
   }
 
   return (
@@ -264,6 +291,19 @@ export function MergeSessionModal({
                   >
                     Reapply commits
                   </button>
+                  <button
+                    type="button"
+                    onClick={() => handleModeChange('fast_forward')}
+                    className="px-3 py-2 rounded text-sm"
+                    style={{
+                      backgroundColor:
+                        mode === 'fast_forward' ? theme.colors.accent.blue.bg : theme.colors.background.tertiary,
+                      border: `1px solid ${mode === 'fast_forward' ? theme.colors.accent.blue.border : theme.colors.border.subtle}`,
+                      color: theme.colors.text.primary,
+                    }}
+                  >
+                    Fast-forward only
+                  </button>
                 </div>
                 <p className="mt-2 text-sm" style={{ color: theme.colors.text.secondary }}>
                   {modeDescriptions[mode]}
@@ -289,6 +329,9 @@ export function MergeSessionModal({
                     }}
                     placeholder="Describe the changes that landed in this session"
                   />
+                  <p className="mt-1 text-xs" style={{ color: theme.colors.text.muted }}>
+                    {'{spec_title}'}, {'{spec_body}'}, {'{session}'}, {'{branch}'} and {'{parent}'} are filled in when merging.
+                  </p>
                 </div>
               )}
 
@@ -311,6 +354,53 @@ export function MergeSessionModal({
                 </div>
               )}
 
+              {sessionOverlaps.length > 0 && (
+                <div
+                  className="rounded-md px-3 py-2 text-sm"
+                  style={{
+                    backgroundColor: theme.colors.accent.amber.bg,
+                    border: `1px solid ${theme.colors.accent.amber.border}`,
+                    color: theme.colors.text.primary,
+                  }}
+                >
+                  <p className="font-medium">Other reviewed sessions edit the same files</p>
+                  <ul className="mt-1 space-y-1">
+                    {sessionOverlaps.map(overlap => (
+                      <li key={overlap.sessionName}>
+                        <span className="font-medium">{overlap.sessionName}</span>
+                        {overlap.conflictingPaths.length > 0
+                          ? ` conflicts in ${overlap.conflictingPaths.join(', ')}; whichever merges second needs a rebase.`
+                          : ` also changes ${overlap.overlappingPaths.join(', ')}, but merges cleanly.`}
+                      </li>
+                    ))}
+                  </ul>
+                </div>
+              )}
+
+              {protectedPaths.length > 0 && (
+                <div
+                  className="rounded-md px-3 py-2 text-sm"
+                  style={{
+                    backgroundColor: theme.colors.accent.amber.bg,
+                    border: `1px solid ${theme.colors.accent.amber.border}`,
+                    color: theme.colors.text.primary,
+                  }}
+                >
+                  <p className="font-medium">This merge changes protected paths</p>
+                  <p className="mt-1">{protectedPaths.join(', ')}</p>
+                  <label className="mt-2 flex items-center gap-2">
+                    <input
+                      type="checkbox"
+                      checked={protectedPathsConfirmed}
+                      onChange={(event) => setProtectedPathsConfirmed(event.target.checked)}
+                      className="w-4 h-4 rounded border-slate-600 bg-slate-800 text-cyan-400 focus:ring-cyan-400"
+                      aria-label="Confirm protected path changes"
+                    />
+                    <span>I reviewed these changes and want to merge them</span>
+                  </label>
+                </div>
+              )}
+
               {!hasConflicts && isUpToDate && (
                 <div
                   className="rounded-md px-3 py-2 text-sm"
@@ -361,6 +451,36 @@ export function MergeSessionModal({
               <span>Cancel</span>
               <span className="text-xs opacity-60 group-hover:opacity-100">Esc</span>
             </button>
+            {onQueue && (
+              <>
+                <label className="flex items-center gap-2 text-sm" style={{ color: theme.colors.text.secondary }}>
+                  <input
+                    type="checkbox"
+                    checked={autoRebase}
+                    onChange={(event) => setAutoRebase(event.target.checked)}
+                    className="w-4 h-4 rounded border-slate-600 bg-slate-800 text-cyan-400 focus:ring-cyan-400"
+                    aria-label="Rebase before queued merge"
+                  />
+                  <span>Rebase first</span>
+                </label>
+                <button
+                  type="button"
+                  onClick={handleQueue}
+                  disabled={confirmDisabled}
+                  className="px-4 py-2 text-sm rounded border"
+                  title={confirmDisabled ? confirmTitle : 'Merge after the sessions already queued, running the project test script first'}
+                  style={{
+                    backgroundColor: theme.colors.background.tertiary,
+                    borderColor: theme.colors.border.subtle,
+                    color: theme.colors.text.primary,
+                    cursor: confirmDisabled ? 'not-allowed' : 'pointer',
+                    opacity: confirmDisabled ? 0.6 : 1,
+                  }}
+                >
+                  Add to merge queue
+                </button>
+              </>
+            )}
               <button
                 type="button"
                 onClick={handleConfirm}
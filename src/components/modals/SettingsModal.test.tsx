@@ -25,6 +25,8 @@ const baseInvokeImplementation = async (command: string, _args?: unknown) => {
       }
     case TauriCommands.GetAppVersion:
       return '0.2.2'
+    case TauriCommands.GetJsonLogs:
+      return false
     case TauriCommands.GetAutoUpdateEnabled:
       return true
     case TauriCommands.SetAutoUpdateEnabled:
@@ -134,10 +136,10 @@ const createDefaultUseSettingsValue = () => ({
   saveAllSettings: vi.fn().mockResolvedValue({ success: true, savedSettings: [], failedSettings: [] }),
   loadEnvVars: vi.fn().mockResolvedValue(createEmptyEnvVars()),
   loadCliArgs: vi.fn().mockResolvedValue(createEmptyCliArgs()),
-  loadProjectSettings: vi.fn().mockResolvedValue({ setupScript: '', branchPrefix: 'schaltwerk', environmentVariables: [] }),
+  loadProjectSettings: vi.fn().mockResolvedValue({ setupScript: '', branchPrefix: 'schaltwerk', testScript: '', environmentVariables: [] }),
   loadTerminalSettings: vi.fn().mockResolvedValue({ shell: null, shellArgs: [], fontFamily: null }),
   loadSessionPreferences: vi.fn().mockResolvedValue({ auto_commit_on_review: false, skip_confirmation_modals: false }),
-  loadMergePreferences: vi.fn().mockResolvedValue({ autoCancelAfterMerge: true }),
+  loadMergePreferences: vi.fn().mockResolvedValue({ autoCancelAfterMerge: true, signCommits: true, runGitHooks: true, protectedPaths: [] }),
   loadKeyboardShortcuts: vi.fn().mockResolvedValue(defaultShortcutConfig),
   saveKeyboardShortcuts: vi.fn().mockResolvedValue(undefined),
   loadInstalledFonts: vi.fn().mockResolvedValue([]),
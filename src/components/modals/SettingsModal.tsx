@@ -10,6 +10,7 @@ import type { HeaderActionConfig } from '../ActionButton'
 import { SpecContentModal } from '../SpecContentModal'
 import { MCPConfigPanel } from '../settings/MCPConfigPanel'
 import { SettingsArchivesSection } from '../settings/SettingsArchivesSection'
+import { SettingsStorageSection } from '../settings/SettingsStorageSection'
 import { logger } from '../../utils/logger'
 import { FontPicker } from './FontPicker'
 import { GithubProjectIntegrationCard } from '../settings/GithubProjectIntegrationCard'
@@ -74,6 +75,7 @@ type SettingsCategory =
     | 'projectRun'
     | 'projectActions'
     | 'archives'
+    | 'storage'
     | 'appearance'
     | 'keyboard'
     | 'environment'
@@ -125,6 +127,16 @@ const CATEGORIES: CategoryConfig[] = [
             </svg>
         )
     },
+    {
+        id: 'storage',
+        label: 'Storage',
+        scope: 'project',
+        icon: (
+            <svg className="w-4 h-4" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M4 7v10c0 2.21 3.582 4 8 4s8-1.79 8-4V7M4 7c0 2.21 3.582 4 8 4s8-1.79 8-4M4 7c0-2.21 3.582-4 8-4s8 1.79 8 4m0 5c0 2.21-3.582 4-8 4s-8-1.79-8-4" />
+            </svg>
+        )
+    },
     {
         id: 'keyboard',
         label: 'Keyboard Shortcuts',
@@ -207,30 +219,79 @@ const CATEGORIES: CategoryConfig[] = [
     },
 ]
 
-const PROJECT_CATEGORY_ORDER: SettingsCategory[] = ['projectGeneral', 'projectRun', 'projectActions', 'archives']
+const PROJECT_CATEGORY_ORDER: SettingsCategory[] = ['projectGeneral', 'projectRun', 'projectActions', 'archives', 'storage']
 
 interface ProjectSettings {
     setupScript: string
     branchPrefix: string
+    testScript: string
+    testTimeoutMinutes?: number | null
     environmentVariables: Array<{key: string, value: string}>
 }
 
+type DevEnvironmentKind = 'devcontainer' | 'nix'
+
+interface DetectedDevEnvironment {
+    kind: DevEnvironmentKind
+    config_path: string
+}
+
+const DEV_ENVIRONMENT_LABELS: Record<DevEnvironmentKind, string> = {
+    devcontainer: 'Dev Container',
+    nix: 'Nix flake',
+}
+
 interface RunScript {
     command: string
     workingDirectory?: string
     environmentVariables: Record<string, string>
+    watch?: boolean
 }
 
 interface TerminalSettings {
     shell: string | null
     shellArgs: string[]
     fontFamily?: string | null
+    outputThrottleEnabled?: boolean
+    outputMaxBytesPerSecond?: number
+    persistence?: 'off' | 'tmux' | 'screen'
+    idleThresholdMs?: number
+    idlePromptPatterns?: Record<string, string[]>
+    scrollback?: {
+        agentBytes: number
+        shellBytes: number
+        runBytes: number
+    }
+    killGracePeriodMs?: number
     webglEnabled?: boolean
 }
 
+interface AutoNudgeConfig {
+    enabled: boolean
+    idle_minutes: number
+    message: string
+}
+
+interface AgentRestartConfig {
+    enabled: boolean
+    max_restarts: number
+    initial_backoff_secs: number
+    max_backoff_secs: number
+    reset_after_secs: number
+}
+
+interface AgentResourceLimits {
+    nice: number
+    max_memory_mb: number | null
+    max_cpu_seconds: number | null
+}
+
 interface SessionPreferences {
     auto_commit_on_review: boolean
     skip_confirmation_modals: boolean
+    auto_nudge?: AutoNudgeConfig
+    agent_restart?: AgentRestartConfig
+    agent_resource_limits?: AgentResourceLimits
 }
 
 export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
@@ -243,6 +304,7 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
     const [projectSettings, setProjectSettings] = useState<ProjectSettings>({
         setupScript: '',
         branchPrefix: 'schaltwerk',
+        testScript: '',
         environmentVariables: []
     })
     const [terminalSettings, setTerminalSettings] = useState<TerminalSettings>({
@@ -256,7 +318,10 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         skip_confirmation_modals: false
     })
     const [mergePreferences, setMergePreferences] = useState<ProjectMergePreferences>({
-        autoCancelAfterMerge: true
+        autoCancelAfterMerge: true,
+        signCommits: true,
+        runGitHooks: true,
+        protectedPaths: []
     })
     const platform = useMemo(() => detectPlatformSafe(), [])
 
@@ -324,6 +389,8 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         }
     }
     const [showFontPicker, setShowFontPicker] = useState(false)
+    const [devEnvironments, setDevEnvironments] = useState<DetectedDevEnvironment[]>([])
+    const [devEnvironment, setDevEnvironment] = useState<DevEnvironmentKind | null>(null)
     const [runScript, setRunScript] = useState<RunScript>({
         command: '',
         workingDirectory: '',
@@ -352,6 +419,7 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
     const toast = useOptionalToast()
     const [autoUpdateEnabled, setAutoUpdateEnabled] = useState<boolean>(true)
     const [loadingAutoUpdate, setLoadingAutoUpdate] = useState<boolean>(true)
+    const [jsonLogsEnabled, setJsonLogsEnabled] = useState<boolean>(false)
     const [checkingUpdate, setCheckingUpdate] = useState<boolean>(false)
 
     const [selectedSpec, setSelectedSpec] = useState<{ name: string; content: string } | null>(null)
@@ -388,6 +456,7 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
     const {
         autoCancelAfterMerge: contextAutoCancelAfterMerge,
         updateAutoCancelAfterMerge,
+        reloadSessions,
     } = useSessions()
     
     const [editableActionButtons, setEditableActionButtons] = useState<HeaderActionConfig[]>([])
@@ -445,6 +514,25 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         }
     }, [autoUpdateEnabled, toast])
 
+    const handleJsonLogsToggle = useCallback(async () => {
+        const previous = jsonLogsEnabled
+        const next = !previous
+        setJsonLogsEnabled(next)
+
+        try {
+            await invoke(TauriCommands.SetJsonLogs, { enabled: next })
+        } catch (error) {
+            logger.error('Failed to update log format preference:', error)
+            setJsonLogsEnabled(previous)
+            toast?.pushToast({
+                tone: 'error',
+                title: 'Could not update preference',
+                description: 'Try again or restart Schaltwerk.',
+                durationMs: 5000,
+            })
+        }
+    }, [jsonLogsEnabled, toast])
+
     const handleManualUpdateCheck = useCallback(async () => {
         setCheckingUpdate(true)
         try {
@@ -492,6 +580,15 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
                     setLoadingAutoUpdate(false)
                 }
             }
+
+            try {
+                const enabled = await invoke<boolean>(TauriCommands.GetJsonLogs)
+                if (!cancelled) {
+                    setJsonLogsEnabled(enabled)
+                }
+            } catch (error) {
+                logger.warn('Failed to load log format preference:', error)
+            }
         }
 
         setLoadingAutoUpdate(true)
@@ -558,10 +655,10 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         ])
         
         // Load project-specific settings (may fail if no project is open)
-        let loadedProjectSettings: ProjectSettings = { setupScript: '', branchPrefix: 'schaltwerk', environmentVariables: [] }
+        let loadedProjectSettings: ProjectSettings = { setupScript: '', branchPrefix: 'schaltwerk', testScript: '', environmentVariables: [] }
         let loadedTerminalSettings: TerminalSettings = { shell: null, shellArgs: [], fontFamily: null, webglEnabled: true }
         let loadedRunScript: RunScript = { command: '', workingDirectory: '', environmentVariables: {} }
-        let loadedMergePreferences: ProjectMergePreferences = { autoCancelAfterMerge: true }
+        let loadedMergePreferences: ProjectMergePreferences = { autoCancelAfterMerge: true, signCommits: true, runGitHooks: true, protectedPaths: [] }
         
         try {
             const results = await Promise.allSettled([
@@ -647,14 +744,49 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
     }, [open])
 
     useEffect(() => {
-        if (!projectAvailable && (activeCategory === 'projectGeneral' || activeCategory === 'projectRun' || activeCategory === 'projectActions' || activeCategory === 'archives')) {
+        if (!open || !projectAvailable) return
+        let cancelled = false
+
+        Promise.all([
+            invoke<DetectedDevEnvironment[]>(TauriCommands.DetectProjectDevEnvironments),
+            invoke<DevEnvironmentKind | null>(TauriCommands.GetProjectDevEnvironment),
+        ])
+            .then(([detected, selected]) => {
+                if (cancelled) return
+                setDevEnvironments(detected)
+                setDevEnvironment(selected ?? null)
+            })
+            .catch(error => {
+                if (cancelled) return
+                logger.info('Failed to load dev environments:', error)
+                setDevEnvironments([])
+                setDevEnvironment(null)
+            })
+
+        return () => {
+            cancelled = true
+        }
+    }, [open, projectAvailable])
+
+    const handleDevEnvironmentChange = async (environment: DevEnvironmentKind | null) => {
+        try {
+            await invoke(TauriCommands.SetProjectDevEnvironment, { environment })
+            setDevEnvironment(environment)
+        } catch (error) {
+            logger.error('Failed to update dev environment:', error)
+            showNotification(`Failed to update dev environment: ${error}`, 'error')
+        }
+    }
+
+    useEffect(() => {
+        if (!projectAvailable && (activeCategory === 'projectGeneral' || activeCategory === 'projectRun' || activeCategory === 'projectActions' || activeCategory === 'archives' || activeCategory === 'storage')) {
             setActiveCategory('appearance')
         }
     }, [projectAvailable, activeCategory])
 
     useEffect(() => {
         if (!hasUnsavedChanges) {
-            setMergePreferences({ autoCancelAfterMerge: contextAutoCancelAfterMerge })
+            setMergePreferences(prev => ({ ...prev, autoCancelAfterMerge: contextAutoCancelAfterMerge }))
         }
     }, [contextAutoCancelAfterMerge, hasUnsavedChanges])
 
@@ -855,11 +987,17 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         if (result.failedSettings.length > 0) {
             showNotification(`Failed to save: ${result.failedSettings.join(', ')}`, 'error')
         } else {
-            if (result.savedSettings.length > 0 || hasUnsavedChanges) {
+            const migration = result.branchPrefixMigration
+            if (migration && migration.skipped.length > 0) {
+                showNotification(`Settings saved; kept existing branches for: ${migration.skipped.join(', ')}`, 'info')
+            } else if (result.savedSettings.length > 0 || hasUnsavedChanges) {
                 showNotification(`Settings saved successfully`, 'success')
             }
             setHasUnsavedChanges(false)
             await updateAutoCancelAfterMerge(mergePreferences.autoCancelAfterMerge, false)
+            if (migration && migration.renamed.length > 0) {
+                await reloadSessions()
+            }
             onClose()
         }
     }
@@ -872,6 +1010,10 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         />
     )
 
+    const renderStorageSettings = () => (
+        <SettingsStorageSection onNotify={showNotification} />
+    )
+
     const renderProjectGeneral = () => (
         <div className="flex flex-col h-full">
             <div className="flex-1 overflow-y-auto p-6">
@@ -881,7 +1023,7 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
                     <div>
                         <h3 className="text-body font-medium text-slate-200 mb-2">Branch Prefix</h3>
                         <div className="text-body text-slate-400 mb-3">
-                            Configure the default Git branch prefix used when creating new Schaltwerk sessions. Use slashes to nest groups (for example <code className={theme.colors.accent.blue.DEFAULT}>team/frontend</code>). Spaces will be converted to hyphens automatically.
+                            Configure the default Git branch prefix used when creating new Schaltwerk sessions. Use slashes to nest groups (for example <code className={theme.colors.accent.blue.DEFAULT}>feat/agent</code>). Spaces will be converted to hyphens automatically. Changing the prefix also renames the branches of existing sessions that use the current one.
                         </div>
                         <input
                             type="text"
@@ -906,7 +1048,7 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
                                 type="checkbox"
                                 checked={mergePreferences.autoCancelAfterMerge}
                                 onChange={(event) => {
-                                    setMergePreferences({ autoCancelAfterMerge: event.target.checked })
+                                    setMergePreferences(prev => ({ ...prev, autoCancelAfterMerge: event.target.checked }))
                                     setHasUnsavedChanges(true)
                                 }}
                                 className="rounded border-slate-600 bg-slate-800 text-cyan-400 focus:ring-cyan-400"
@@ -917,6 +1059,96 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
                             You can also toggle this from the merge dialog&apos;s toolbar. The preference is stored per project.
                         </p>
                     </div>
+
+                    <div>
+                        <h3 className="text-body font-medium text-slate-200 mb-2">Commit Signing</h3>
+                        <div className="text-body text-slate-400 mb-3">
+                            Squash merges and auto-commits follow your Git signing configuration (<code className={theme.colors.accent.blue.DEFAULT}>commit.gpgsign</code>, GPG or SSH keys). Turn this off to create unsigned commits for this project, for example when no signing key is available.
+                        </div>
+                        <label className="flex items-center gap-3 text-sm text-slate-200">
+                            <input
+                                type="checkbox"
+                                checked={mergePreferences.signCommits}
+                                onChange={(event) => {
+                                    setMergePreferences(prev => ({ ...prev, signCommits: event.target.checked }))
+                                    setHasUnsavedChanges(true)
+                                }}
+                                className="rounded border-slate-600 bg-slate-800 text-cyan-400 focus:ring-cyan-400"
+                            />
+                            <span>Sign commits when Git is configured to</span>
+                        </label>
+                    </div>
+
+                    <div>
+                        <h3 className="text-body font-medium text-slate-200 mb-2">Git Hooks</h3>
+                        <div className="text-body text-slate-400 mb-3">
+                            Run the repository&apos;s <code className={theme.colors.accent.blue.DEFAULT}>pre-commit</code> and <code className={theme.colors.accent.blue.DEFAULT}>commit-msg</code> hooks for squash merges and auto-commits. When a hook rejects a commit, its output is printed in the session&apos;s run terminal.
+                        </div>
+                        <label className="flex items-center gap-3 text-sm text-slate-200">
+                            <input
+                                type="checkbox"
+                                checked={mergePreferences.runGitHooks}
+                                onChange={(event) => {
+                                    setMergePreferences(prev => ({ ...prev, runGitHooks: event.target.checked }))
+                                    setHasUnsavedChanges(true)
+                                }}
+                                className="rounded border-slate-600 bg-slate-800 text-cyan-400 focus:ring-cyan-400"
+                            />
+                            <span>Run Git hooks for Schaltwerk commits</span>
+                        </label>
+                    </div>
+
+                    <div>
+                        <h3 className="text-body font-medium text-slate-200 mb-2">Protected Paths</h3>
+                        <div className="text-body text-slate-400 mb-3">
+                            Files or directories, one per line and relative to the repository root, that a merge only changes after you confirm it in the merge dialog. For example <code className={theme.colors.accent.blue.DEFAULT}>.github/workflows</code> or <code className={theme.colors.accent.blue.DEFAULT}>infra/</code>.
+                        </div>
+                        <textarea
+                            value={mergePreferences.protectedPaths.join('\n')}
+                            onChange={(event) => {
+                                const protectedPaths = event.target.value.split('\n')
+                                setMergePreferences(prev => ({ ...prev, protectedPaths }))
+                                setHasUnsavedChanges(true)
+                            }}
+                            placeholder={'.github/workflows\ninfra/'}
+                            rows={3}
+                            className={`w-full bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700 placeholder-slate-500 font-mono text-body focus:outline-none focus:${theme.colors.border.focus} transition-colors`}
+                            spellCheck={false}
+                            aria-label="Protected paths"
+                        />
+                    </div>
+
+                    <div>
+                        <h3 className="text-body font-medium text-slate-200 mb-2">Merge Queue Test Script</h3>
+                        <div className="text-body text-slate-400 mb-3">
+                            Runs in the session worktree before the merge queue merges a reviewed session (after the optional rebase). A failing script keeps the session out of the base branch and its output is shown in the queue. Leave empty to merge without testing.
+                        </div>
+                        <input
+                            type="text"
+                            value={projectSettings.testScript}
+                            onChange={(e) => setProjectSettings(prev => ({ ...prev, testScript: e.target.value }))}
+                            placeholder="bun run test"
+                            className={`w-full bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700 placeholder-slate-500 font-mono text-body focus:outline-none focus:${theme.colors.border.focus} transition-colors`}
+                            spellCheck={false}
+                        />
+                        <div className="flex items-center gap-3 mt-3">
+                            <label htmlFor="merge-queue-test-timeout" className="text-body text-slate-400">
+                                Timeout (minutes)
+                            </label>
+                            <input
+                                id="merge-queue-test-timeout"
+                                type="number"
+                                min={1}
+                                value={projectSettings.testTimeoutMinutes ?? ''}
+                                onChange={(e) => {
+                                    const minutes = parseInt(e.target.value, 10)
+                                    setProjectSettings(prev => ({ ...prev, testTimeoutMinutes: minutes > 0 ? minutes : null }))
+                                }}
+                                placeholder="30"
+                                className={`w-24 bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700 placeholder-slate-500 text-body focus:outline-none focus:${theme.colors.border.focus} transition-colors`}
+                            />
+                        </div>
+                    </div>
                 </div>
             </div>
         </div>
@@ -926,6 +1158,27 @@ export function SettingsModal({ open, onClose, onOpenTutorial }: Props) {
         <div className="flex flex-col h-full">
             <div className="flex-1 overflow-y-auto p-6">
                 <div className="space-y-8">
+                    {devEnvironments.length > 0 && (
+                        <div>
+                            <h3 className="text-body font-medium text-slate-200 mb-2">Development Environment</h3>
+                            <div className="text-body text-slate-400 mb-4">
+                                This project declares a reproducible environment. When enabled, it is set up before the setup script of each new session, and agent and run terminals start inside it.
+                            </div>
+                            <select
+                                value={devEnvironment ?? ''}
+                                onChange={(e) => { void handleDevEnvironmentChange((e.target.value || null) as DevEnvironmentKind | null) }}
+                                className="w-full bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700"
+                            >
+                                <option value="">Don't use</option>
+                                {devEnvironments.map(env => (
+                                    <option key={env.kind} value={env.kind}>
+                                        {DEV_ENVIRONMENT_LABELS[env.kind]} ({env.config_path})
+                                    </option>
+                                ))}
+                            </select>
+                        </div>
+                    )}
+
                     <div>
                         <h3 className="text-body font-medium text-slate-200 mb-2">Worktree Setup Script</h3>
                         <div className="text-body text-slate-400 mb-4">
@@ -1004,6 +1257,15 @@ fi`}
                                     className={`w-full bg-slate-800 text-slate-100 rounded px-3 py-2 border border-slate-700 placeholder-slate-500 focus:outline-none focus:${theme.colors.border.focus} transition-colors`}
                                 />
                             </div>
+                            <label className="flex items-center gap-3 text-sm text-slate-200">
+                                <input
+                                    type="checkbox"
+                                    checked={Boolean(runScript.watch)}
+                                    onChange={(e) => setRunScript(prev => ({ ...prev, watch: e.target.checked }))}
+                                    className="rounded border-slate-600 bg-slate-800 text-cyan-400 focus:ring-cyan-400"
+                                />
+                                <span>Watch mode: restart the command when files in the session change</span>
+                            </label>
                             <div>
                                 <label className="block text-caption text-slate-400 mb-2">Environment Variables</label>
                                 <div className="space-y-2">
@@ -2098,6 +2360,27 @@ fi`}
                                     {checkingUpdate ? 'Checking...' : 'Check for updates'}
                                 </button>
                             </div>
+                            <div className="flex items-center justify-between py-3 px-4 bg-slate-800/50 rounded-lg">
+                                <div className="flex flex-col">
+                                    <span className="text-body font-medium text-slate-200">JSON logs</span>
+                                    <span className="text-caption text-slate-400 mt-1">
+                                        Write one JSON object per log line, with the session and correlation id, for log tooling. <code>SCHALTWERK_LOG_FORMAT</code> takes precedence.
+                                    </span>
+                                </div>
+                                <label className="flex items-center gap-3" htmlFor="json-logs-toggle">
+                                    <input
+                                        id="json-logs-toggle"
+                                        type="checkbox"
+                                        aria-label="Write logs as JSON"
+                                        className={`w-4 h-4 ${theme.colors.accent.cyan.dark} bg-slate-800 border-slate-600 rounded focus:ring-${theme.colors.accent.cyan.DEFAULT} focus:ring-2`}
+                                        checked={jsonLogsEnabled}
+                                        onChange={handleJsonLogsToggle}
+                                    />
+                                    <span className="text-caption text-slate-300">
+                                        {jsonLogsEnabled ? 'Enabled' : 'Disabled'}
+                                    </span>
+                                </label>
+                            </div>
                         </div>
                     </div>
                 </div>
@@ -2115,6 +2398,8 @@ fi`}
                 return projectAvailable ? renderProjectActions() : renderAppearanceSettings()
             case 'archives':
                 return projectAvailable ? renderArchivesSettings() : renderAppearanceSettings()
+            case 'storage':
+                return projectAvailable ? renderStorageSettings() : renderAppearanceSettings()
             case 'appearance':
                 return renderAppearanceSettings()
             case 'keyboard':
@@ -41,6 +41,11 @@ export function MCPConfigPanel({ projectPath, agent }: Props) {
     loadStatus()
   }, [projectPath, loadStatus])
 
+  useEffect(() => {
+    invoke<string[]>(TauriCommands.SyncMcpConfigs, { projectPath })
+      .catch(e => logger.warn('Failed to sync MCP configs:', e))
+  }, [projectPath])
+
   useEffect(() => {
     if (status?.is_configured) {
       setMcpEnabled(true)
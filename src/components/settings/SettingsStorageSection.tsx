@@ -0,0 +1,211 @@
+import { useCallback, useEffect, useRef, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { AnimatedText } from '../common/AnimatedText'
+import { logger } from '../../utils/logger'
+import { TauriCommands } from '../../common/tauriCommands'
+import { formatBytes } from '../../utils/storage'
+import type { PrunedPath, WorktreeDiskUsage, WorktreePruneReport } from '../../types/session'
+
+type NotificationType = 'success' | 'error' | 'info'
+
+interface Props {
+    onNotify: (message: string, type: NotificationType) => void
+}
+
+export function SettingsStorageSection({ onNotify }: Props) {
+    const [usage, setUsage] = useState<WorktreeDiskUsage[]>([])
+    const [usageLoading, setUsageLoading] = useState(true)
+    const [loadError, setLoadError] = useState<string | null>(null)
+    const [preview, setPreview] = useState<WorktreePruneReport | null>(null)
+    const [pruning, setPruning] = useState(false)
+
+    const isMountedRef = useRef(true)
+
+    useEffect(() => {
+        isMountedRef.current = true
+        return () => {
+            isMountedRef.current = false
+        }
+    }, [])
+
+    const fetchUsage = useCallback(async () => {
+        setUsageLoading(true)
+        try {
+            const list = await invoke<WorktreeDiskUsage[]>(TauriCommands.GetWorktreeDiskUsage)
+            if (isMountedRef.current) {
+                setUsage(list)
+                setLoadError(null)
+            }
+        } catch (error) {
+            logger.error('Failed to load worktree disk usage', error)
+            if (isMountedRef.current) {
+                setLoadError('Failed to measure worktree disk usage.')
+            }
+        } finally {
+            if (isMountedRef.current) {
+                setUsageLoading(false)
+            }
+        }
+    }, [])
+
+    useEffect(() => {
+        fetchUsage()
+    }, [fetchUsage])
+
+    const handlePreview = useCallback(async () => {
+        if (pruning) return
+
+        setPruning(true)
+        try {
+            const report = await invoke<WorktreePruneReport>(TauriCommands.PruneWorktrees, { dryRun: true })
+            if (!isMountedRef.current) return
+            if (report.orphaned_worktrees.length === 0 && report.build_artifacts.length === 0) {
+                setPreview(null)
+                onNotify('Nothing to prune', 'info')
+            } else {
+                setPreview(report)
+            }
+        } catch (error) {
+            logger.error('Failed to preview worktree prune', error)
+            onNotify('Failed to scan worktrees', 'error')
+        } finally {
+            if (isMountedRef.current) {
+                setPruning(false)
+            }
+        }
+    }, [onNotify, pruning])
+
+    const handlePrune = useCallback(async () => {
+        if (pruning) return
+
+        setPruning(true)
+        try {
+            const report = await invoke<WorktreePruneReport>(TauriCommands.PruneWorktrees, { dryRun: false })
+            onNotify(`Reclaimed ${formatBytes(report.reclaimed_bytes)}`, 'success')
+            if (isMountedRef.current) {
+                setPreview(null)
+            }
+            await fetchUsage()
+        } catch (error) {
+            logger.error('Failed to prune worktrees', error)
+            onNotify('Failed to prune worktrees', 'error')
+        } finally {
+            if (isMountedRef.current) {
+                setPruning(false)
+            }
+        }
+    }, [fetchUsage, onNotify, pruning])
+
+    const renderPrunedPaths = (title: string, paths: PrunedPath[]) => {
+        if (paths.length === 0) return null
+        return (
+            <div className="space-y-1">
+                <div className="text-caption text-slate-400">{title}</div>
+                {paths.map(item => (
+                    <div key={item.path} className="flex items-center justify-between gap-3 text-caption">
+                        <span className="text-slate-300 truncate" title={item.path}>
+                            {item.session_name ? `${item.session_name}: ` : ''}{item.path}
+                        </span>
+                        <span className="text-slate-400 flex-shrink-0">{formatBytes(item.bytes)}</span>
+                    </div>
+                ))}
+            </div>
+        )
+    }
+
+    const renderUsage = () => {
+        if (usageLoading) {
+            return (
+                <div className="py-6">
+                    <AnimatedText text="loading" size="sm" />
+                </div>
+            )
+        }
+
+        if (loadError) {
+            return <div className="text-body text-red-300">{loadError}</div>
+        }
+
+        if (usage.length === 0) {
+            return <div className="text-slate-400 text-body">No session worktrees on disk.</div>
+        }
+
+        return (
+            <div className="space-y-2 w-full">
+                {usage.map(item => (
+                    <div
+                        key={item.worktree_path}
+                        className="w-full border border-slate-800 rounded p-3 bg-slate-900/40 flex items-center justify-between gap-3 min-w-0"
+                    >
+                        <div className="flex-1 min-w-0">
+                            <div className="text-slate-200 text-body truncate">
+                                {item.session_name}
+                                {item.cancelled && <span className="ml-2 text-caption text-slate-500">cancelled</span>}
+                            </div>
+                            <div className="text-caption text-slate-500 truncate" title={item.worktree_path}>{item.worktree_path}</div>
+                        </div>
+                        <div className="text-right flex-shrink-0">
+                            <div className="text-body text-slate-200">{formatBytes(item.total_bytes)}</div>
+                            {item.artifact_bytes > 0 && (
+                                <div className="text-caption text-slate-500">{formatBytes(item.artifact_bytes)} build artifacts</div>
+                            )}
+                        </div>
+                    </div>
+                ))}
+            </div>
+        )
+    }
+
+    return (
+        <div className="flex flex-col h-full">
+            <div className="flex-1 overflow-y-auto p-6">
+                <div className="space-y-6">
+                    <div>
+                        <h3 className="text-body font-medium text-slate-200 mb-2">Prune Worktrees</h3>
+                        <div className="text-body text-slate-400 mb-4">
+                            Remove worktrees no session owns anymore and git-ignored dependency or build output directories (node_modules, target, …) left behind by cancelled sessions. Scan first to review what will be deleted.
+                        </div>
+                        {preview ? (
+                            <div className="space-y-3 border border-slate-800 rounded p-3 bg-slate-900/40">
+                                {renderPrunedPaths('Orphaned worktrees', preview.orphaned_worktrees)}
+                                {renderPrunedPaths('Build artifacts of cancelled sessions', preview.build_artifacts)}
+                                <div className="flex items-center justify-between gap-3">
+                                    <span className="text-body text-slate-200">Reclaims {formatBytes(preview.reclaimed_bytes)}</span>
+                                    <div className="flex items-center gap-2">
+                                        <button
+                                            onClick={() => setPreview(null)}
+                                            disabled={pruning}
+                                            className="px-2 py-1 border border-slate-700 rounded text-slate-200 text-caption bg-slate-800 hover:bg-slate-700 disabled:opacity-60 disabled:cursor-not-allowed"
+                                        >
+                                            Cancel
+                                        </button>
+                                        <button
+                                            onClick={handlePrune}
+                                            disabled={pruning}
+                                            className="px-2 py-1 border border-red-700 rounded text-red-200 text-caption bg-red-900/30 hover:bg-red-900/50 disabled:opacity-60 disabled:cursor-not-allowed"
+                                        >
+                                            Delete
+                                        </button>
+                                    </div>
+                                </div>
+                            </div>
+                        ) : (
+                            <button
+                                onClick={handlePreview}
+                                disabled={pruning}
+                                className="px-3 py-1 bg-slate-800 hover:bg-slate-700 border border-slate-700 rounded text-slate-200 text-body disabled:opacity-60 disabled:cursor-not-allowed"
+                            >
+                                Scan for reclaimable space
+                            </button>
+                        )}
+                    </div>
+                    <div>
+                        <h3 className="text-body font-medium text-slate-200 mb-2">Worktree Disk Usage</h3>
+                        <div className="text-body text-slate-400 mb-4">Space taken by each session worktree, largest first.</div>
+                        {renderUsage()}
+                    </div>
+                </div>
+            </div>
+        </div>
+    )
+}
@@ -25,6 +25,10 @@ export const displayNameForAgent = (agent: AgentType) => {
             return 'Qwen'
         case 'amp':
             return 'Amp'
+        case 'aider':
+            return 'Aider'
+        case 'goose':
+            return 'Goose'
         case 'terminal':
             return 'Terminal Only'
         default:
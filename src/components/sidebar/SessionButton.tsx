@@ -3,7 +3,7 @@ import { clsx } from 'clsx'
 import { formatLastActivity } from '../../utils/time'
 import { formatBytes } from '../../utils/storage'
 import { SessionActions } from '../session/SessionActions'
-import { SessionInfo, SessionMonitorStatus } from '../../types/session'
+import { AgentActivityState, SessionInfo, SessionMonitorStatus } from '../../types/session'
 import { UncommittedIndicator } from '../common/UncommittedIndicator'
 import { theme } from '../../common/theme'
 import type { MergeStatus } from '../../contexts/SessionsContext'
@@ -12,6 +12,13 @@ import { useMultipleShortcutDisplays } from '../../keyboardShortcuts/useShortcut
 import { KeyboardShortcutAction } from '../../keyboardShortcuts/config'
 import { detectPlatformSafe } from '../../keyboardShortcuts/helpers'
 
+const AGENT_STATE_LABELS: Record<AgentActivityState, string> = {
+    thinking: 'Thinking…',
+    editing_files: 'Editing files',
+    waiting_for_approval: 'Needs approval',
+    done: 'Done',
+}
+
 interface SessionButtonProps {
     session: {
         info: SessionInfo
@@ -137,11 +144,13 @@ export const SessionButton = memo<SessionButtonProps>(({
     const worktreeSizeLabel = typeof s.worktree_size_bytes === 'number'
         ? formatBytes(s.worktree_size_bytes)
         : null
+    const baseBehind = s.base_freshness?.behind ?? 0
     const isBlocked = s.is_blocked || false
     const isReadyToMerge = s.ready_to_merge || false
     const agentType = s.original_agent_type as (SessionInfo['original_agent_type'])
     const agentKey = (agentType || '').toLowerCase()
-    const agentLabel = agentKey
+    const agentLabel = s.agent_model ? `${agentKey}/${s.agent_model}` : agentKey
+    const agentStateLabel = s.agent_state ? AGENT_STATE_LABELS[s.agent_state] : null
 
     const getAgentColor = (agent: string): 'blue' | 'green' | 'orange' | 'violet' | 'red' => {
         switch (agent) {
@@ -281,6 +290,12 @@ export const SessionButton = memo<SessionButtonProps>(({
                                 ⏸ Idle
                             </span>
                         )}
+
+                        {agentStateLabel && !(s.attention_required && s.agent_state === 'done') && (
+                            <span className="ml-2 text-xs text-slate-400" title="What the agent is doing">
+                                {agentStateLabel}
+                            </span>
+                        )}
                     </div>
                 </div>
                 <div className="flex items-start gap-2 flex-shrink-0">
@@ -429,6 +444,25 @@ export const SessionButton = memo<SessionButtonProps>(({
                             {agentLabel}
                         </span>
                     )}
+                    {baseBehind > 0 && (
+                        <span
+                            className="inline-flex items-center gap-1 px-1.5 py-[1px] rounded text-[10px] border"
+                            style={{
+                                lineHeight: theme.lineHeight.badge,
+                                backgroundColor: theme.colors.background.elevated,
+                                color: theme.colors.text.muted,
+                                borderColor: theme.colors.border.subtle
+                            }}
+                            title={`${s.base_branch} moved ${baseBehind} commit${baseBehind === 1 ? '' : 's'} ahead`}
+                            aria-label={`${s.base_branch} moved ${baseBehind} commits ahead`}
+                        >
+                            <span
+                                className="w-1 h-1 rounded-full"
+                                style={{ backgroundColor: theme.colors.accent.amber.DEFAULT }}
+                            />
+                            {s.base_branch} +{baseBehind}
+                        </span>
+                    )}
                     {worktreeSizeLabel && (
                         <span
                             className="inline-flex items-center gap-1 px-1.5 py-[1px] rounded text-[10px] border"
@@ -265,7 +265,9 @@ export const SessionVersionGroup = memo<SessionVersionGroupProps>(({
                     const isSelected = (selection.kind === 'session' && selection.payload === version.session.info.session_id) ||
                                      (isInSpecMode === true && isSpec(version.session.info) && currentSpecId === version.session.info.session_id)
                     const versionAgentType = version.session.info.original_agent_type
-                    const displayName = versionAgentType ? `(v${version.versionNumber} • ${versionAgentType})` : `(v${version.versionNumber})`
+                    const versionModel = version.session.info.agent_model
+                    const versionAgent = versionAgentType && versionModel ? `${versionAgentType}/${versionModel}` : versionAgentType
+                    const displayName = versionAgent ? `(v${version.versionNumber} • ${versionAgent})` : `(v${version.versionNumber})`
                     const willBeDeleted = isPreviewingDeletion && hasSelectedVersion && !isSelected
 
                   return (
@@ -35,6 +35,7 @@ import { useModal } from '../../contexts/ModalContext'
 import { useProject } from '../../contexts/ProjectContext'
 import { getSessionDisplayName } from '../../utils/sessionDisplayName'
 import { theme } from '../../common/theme'
+import { isForOtherProject } from '../../common/projectTabs'
 import { useClaudeSession } from '../../hooks/useClaudeSession'
 
 // Normalize backend states to UI categories
@@ -81,6 +82,7 @@ export function Sidebar({ isDiffViewerOpen, openTabs = [], onSelectPrevProject,
         openMergeDialog,
         closeMergeDialog,
         confirmMerge,
+        enqueueMerge,
         isMergeInFlight,
         getMergeStatus,
         autoCancelAfterMerge,
@@ -933,6 +935,7 @@ export function Sidebar({ isDiffViewerOpen, openTabs = [], onSelectPrevProject,
         // Activity and git stats updates are handled by SessionsContext
 
         void register(SchaltEvent.SessionRemoved, (event) => {
+            if (isForOtherProject(event.project_path, projectPath)) return
             lastRemovedSessionRef.current = event.session_name
         })
 
@@ -943,6 +946,7 @@ export function Sidebar({ isDiffViewerOpen, openTabs = [], onSelectPrevProject,
         })
 
         void register(SchaltEvent.FollowUpMessage, (event) => {
+            if (isForOtherProject(event.project_path, projectPath)) return
             const { session_name, message, message_type } = event
 
             setSessionsWithNotifications(prev => new Set([...prev, session_name]))
@@ -978,7 +982,7 @@ export function Sidebar({ isDiffViewerOpen, openTabs = [], onSelectPrevProject,
                 }
             })
         }
-    }, [setCurrentFocus, setFocusForSession, setSelection])
+    }, [projectPath, setCurrentFocus, setFocusForSession, setSelection])
 
     useEffect(() => () => cancelMarkReadyCooldown(), [cancelMarkReadyCooldown])
 
@@ -1403,6 +1407,11 @@ export function Sidebar({ isDiffViewerOpen, openTabs = [], onSelectPrevProject,
                         void confirmMerge(mergeDialogState.sessionName, mode, commitMessage)
                     }
                 }}
+                onQueue={(mode, commitMessage, autoRebase) => {
+                    if (mergeDialogState.sessionName) {
+                        void enqueueMerge(mergeDialogState.sessionName, mode, commitMessage, autoRebase)
+                    }
+                }}
                 autoCancelEnabled={autoCancelAfterMerge}
                 onToggleAutoCancel={(next) => { void updateAutoCancelAfterMerge(next) }}
             />
@@ -19,6 +19,7 @@ interface RunScript {
   command: string
   workingDirectory?: string
   environmentVariables: Record<string, string>
+  watch?: boolean
 }
 
 const RUN_EXIT_SENTINEL_PREFIX = '__SCHALTWERK_RUN_EXIT__='
@@ -59,10 +60,17 @@ export const RunTerminal = forwardRef<RunTerminalHandle, RunTerminalProps>(({
   const [scrollRequestId, setScrollRequestId] = useState(0)
   const pendingScrollToBottomRef = useRef(false)
   const startPendingRef = useRef(false)
+  const watchActiveRef = useRef(false)
 
   const handleRunComplete = useCallback((exitCode: string) => {
     logger.info('[RunTerminal] Detected run command completion with exit code:', exitCode || 'unknown')
 
+    // In watch mode the backend restarts the command on changes; stay "running" until stopped
+    if (watchActiveRef.current) {
+      startPendingRef.current = false
+      return
+    }
+
     if (runningRef.current) {
       runningRef.current = false
       setIsRunning(false)
@@ -217,6 +225,10 @@ export const RunTerminal = forwardRef<RunTerminalHandle, RunTerminalProps>(({
 
       if (isRunning) {
         try {
+          if (watchActiveRef.current) {
+            watchActiveRef.current = false
+            await invoke(TauriCommands.StopRunWatch, { terminalId: runTerminalId })
+          }
           await writeTerminalBackend(runTerminalId, '\u0003')
           runningRef.current = false
           setIsRunning(false)
@@ -268,6 +280,10 @@ export const RunTerminal = forwardRef<RunTerminalHandle, RunTerminalProps>(({
           }
 
           await executeRunCommand(script.command)
+          if (script.watch) {
+            await invoke(TauriCommands.StartRunWatch, { terminalId: runTerminalId })
+            watchActiveRef.current = true
+          }
         } catch (err) {
           logger.error('[RunTerminal] Failed to start run process:', err)
         } finally {
@@ -20,6 +20,7 @@ import { logger } from '../../utils/logger'
 import { useModal } from '../../contexts/ModalContext'
 import { safeTerminalFocus, safeTerminalFocusImmediate } from '../../utils/safeFocus'
 import { buildTerminalFontFamily } from '../../utils/terminalFonts'
+import type { TerminalTheme } from '../../types/session'
 import { TerminalLoadingOverlay } from './TerminalLoadingOverlay'
 import { TerminalSearchPanel } from './TerminalSearchPanel'
 import { detectPlatformSafe } from '../../keyboardShortcuts/helpers'
@@ -152,6 +153,14 @@ const TerminalComponent = forwardRef<TerminalHandle, TerminalProps>(({ terminalI
     const rendererReadyRef = useRef<boolean>(false); // Canvas renderer readiness flag
     const [resolvedFontFamily, setResolvedFontFamily] = useState<string | null>(null);
     const [customFontFamily, setCustomFontFamily] = useState<string | null>(null);
+    const [terminalThemeName, setTerminalThemeName] = useState<string | null>(null);
+    const [terminalThemes, setTerminalThemes] = useState<TerminalTheme[]>([]);
+    const themeColors = useMemo(
+        () => terminalThemes.find(t => t.name === terminalThemeName)?.colors ?? {},
+        [terminalThemes, terminalThemeName]
+    );
+    const themeColorsRef = useRef(themeColors);
+    themeColorsRef.current = themeColors;
     const [fontsFullyLoaded, setFontsFullyLoaded] = useState(false);
     const fontsLoadedRef = useRef(false);
     // Agent conversation terminal detection reused across sizing logic and scrollback config
@@ -510,12 +519,17 @@ const TerminalComponent = forwardRef<TerminalHandle, TerminalProps>(({ terminalI
         let mounted = true
         const load = async () => {
             try {
-                const settings = await invoke<{ fontFamily?: string | null }>(TauriCommands.GetTerminalSettings)
+                const settings = await invoke<{ fontFamily?: string | null; theme?: string | null }>(TauriCommands.GetTerminalSettings)
                 const custom = settings?.fontFamily ?? null
                 const chain = buildTerminalFontFamily(custom)
                 if (mounted) {
                     setCustomFontFamily(custom)
                     setResolvedFontFamily(chain)
+                    setTerminalThemeName(settings?.theme ?? null)
+                }
+                if (settings?.theme) {
+                    const themes = await invoke<TerminalTheme[]>(TauriCommands.GetTerminalThemes)
+                    if (mounted) setTerminalThemes(themes)
                 }
             } catch (err) {
                 logger.warn('[Terminal] Failed to load terminal settings for font family', err)
@@ -540,6 +554,44 @@ const TerminalComponent = forwardRef<TerminalHandle, TerminalProps>(({ terminalI
         return cleanup
     }, [])
 
+    useEffect(() => {
+        let disposed = false
+        let unlisten: UnlistenFn | null = null
+        listenEvent(SchaltEvent.SettingsChanged, change => {
+            if (change.kind === 'terminal_themes') {
+                setTerminalThemes(change.themes)
+                return
+            }
+            if (change.kind !== 'terminal') return
+            const custom = change.terminal.fontFamily ?? null
+            setCustomFontFamily(custom)
+            setResolvedFontFamily(buildTerminalFontFamily(custom))
+            const themeName = change.terminal.theme ?? null
+            setTerminalThemeName(themeName)
+            if (themeName) {
+                invoke<TerminalTheme[]>(TauriCommands.GetTerminalThemes)
+                    .then(themes => { if (!disposed) setTerminalThemes(themes) })
+                    .catch(err => logger.warn('[Terminal] Failed to load terminal themes', err))
+            }
+        })
+            .then(stop => {
+                if (disposed) {
+                    stop()
+                } else {
+                    unlisten = stop
+                }
+            })
+            .catch(err => logger.warn('[Terminal] Failed to listen for settings changes', err))
+        return () => {
+            disposed = true
+            unlisten?.()
+        }
+    }, [])
+
+    useEffect(() => {
+        xtermWrapperRef.current?.applyConfig({ themeColors })
+    }, [themeColors])
+
      // Listen for unified agent-start events to prevent double-starting
      useEffect(() => {
          let unlistenAgentStarted: UnlistenFn | null = null;
@@ -815,6 +867,7 @@ const TerminalComponent = forwardRef<TerminalHandle, TerminalProps>(({ terminalI
                 fontFamily: resolvedFontFamily || 'Menlo, Monaco, ui-monospace, SFMono-Regular, monospace',
                 readOnly,
                 minimumContrastRatio: atlasContrast,
+                themeColors: themeColorsRef.current,
             },
         }));
         const instance = record.xterm;
@@ -825,6 +878,7 @@ const TerminalComponent = forwardRef<TerminalHandle, TerminalProps>(({ terminalI
                 fontFamily: resolvedFontFamily || 'Menlo, Monaco, ui-monospace, SFMono-Regular, monospace',
                 readOnly,
                 minimumContrastRatio: atlasContrast,
+                themeColors: themeColorsRef.current,
             });
         }
         xtermWrapperRef.current = instance;
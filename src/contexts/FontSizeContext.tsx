@@ -3,6 +3,7 @@ import { TauriCommands } from '../common/tauriCommands'
 import { invoke } from '@tauri-apps/api/core'
 import { logger } from '../utils/logger'
 import { emitUiEvent, UiEvent } from '../common/uiEvents'
+import { listenEvent, SchaltEvent } from '../common/eventSystem'
 
 interface FontSizeContextType {
   terminalFontSize: number
@@ -65,6 +66,37 @@ export function FontSizeProvider({ children }: { children: ReactNode }) {
       })
   }, [])
 
+  // Follow font sizes saved elsewhere, e.g. from another window or the API
+  useEffect(() => {
+    let disposed = false
+    let unlisten: (() => void) | null = null
+
+    listenEvent(SchaltEvent.SettingsChanged, (change) => {
+      if (change.kind !== 'font_sizes') return
+      const { terminal_font_size: terminal, ui_font_size: ui } = change
+      if (terminal < MIN_FONT_SIZE || terminal > MAX_FONT_SIZE || ui < MIN_FONT_SIZE || ui > MAX_FONT_SIZE) {
+        return
+      }
+      // Already persisted, so applying it must not trigger another save
+      lastSavedRef.current = { terminal, ui }
+      setTerminalFontSize(terminal)
+      setUiFontSize(ui)
+    })
+      .then(stop => {
+        if (disposed) {
+          stop()
+        } else {
+          unlisten = stop
+        }
+      })
+      .catch(err => logger.warn('Failed to listen for font size changes:', err))
+
+    return () => {
+      disposed = true
+      unlisten?.()
+    }
+  }, [])
+
   // Save font sizes to database when they change (debounced) and update CSS vars/events immediately
   useEffect(() => {
     if (!initialized) return
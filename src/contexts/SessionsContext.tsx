@@ -6,7 +6,7 @@ import { listenEvent, SchaltEvent } from '../common/eventSystem'
 import { useProject } from './ProjectContext'
 import { SortMode, FilterMode, getDefaultSortMode, getDefaultFilterMode, isValidSortMode, isValidFilterMode } from '../types/sessionFilters'
 import { mapSessionUiState, searchSessions as searchSessionsUtil } from '../utils/sessionFilters'
-import { EnrichedSession, SessionInfo, SessionState, RawSession } from '../types/session'
+import { EnrichedSession, SessionInfo, SessionState, RawSession, MergeQueueSnapshot, SessionOverlap } from '../types/session'
 import { logger } from '../utils/logger'
 import { useOptionalToast } from '../common/toast/ToastProvider'
 import { hasBackgroundStart, emitUiEvent, UiEvent } from '../common/uiEvents'
@@ -15,19 +15,24 @@ import { startSessionTop, computeProjectOrchestratorId } from '../common/agentSp
 import { EventPayloadMap, GitOperationFailedPayload, GitOperationPayload } from '../common/events'
 import { areSessionInfosEqual } from '../utils/sessionComparison'
 import { stableSessionTerminalId, isTopTerminalId } from '../common/terminalIdentity'
+import { isForOtherProject } from '../common/projectTabs'
 import { releaseSessionTerminals } from '../terminal/registry/terminalRegistry'
 
-type MergeModeOption = 'squash' | 'reapply'
+type MergeModeOption = 'squash' | 'reapply' | 'fast_forward'
 
 interface MergePreviewResponse {
     sessionBranch: string
     parentBranch: string
     squashCommands: string[]
     reapplyCommands: string[]
+    fastForwardCommands: string[]
     defaultCommitMessage: string
     hasConflicts: boolean
     conflictingPaths: string[]
     isUpToDate: boolean
+    canFastForward: boolean
+    sessionOverlaps?: SessionOverlap[]
+    protectedPaths?: string[]
 }
 
 type MergeDialogStatus = 'idle' | 'loading' | 'ready' | 'running'
@@ -158,6 +163,8 @@ interface SessionsContextValue {
     openMergeDialog: (sessionId: string) => Promise<void>
     closeMergeDialog: () => void
     confirmMerge: (sessionId: string, mode: MergeModeOption, commitMessage?: string) => Promise<void>
+    enqueueMerge: (sessionId: string, mode: MergeModeOption, commitMessage: string | undefined, autoRebase: boolean) => Promise<void>
+    mergeQueue: MergeQueueSnapshot | null
     isMergeInFlight: (sessionId: string) => boolean
     getMergeStatus: (sessionId: string) => MergeStatus
     autoCancelAfterMerge: boolean
@@ -199,6 +206,8 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
     const [sessionMutations, setSessionMutations] = useState<Map<string, Set<SessionMutationKind>>>(new Map())
     const mergeErrorCacheRef = useRef(new Map<string, string>())
     const [mergeStatuses, setMergeStatuses] = useState<Map<string, MergeStatus>>(new Map())
+    const [mergeQueue, setMergeQueue] = useState<MergeQueueSnapshot | null>(null)
+    const mergeQueueFailureKeyRef = useRef<string | null>(null)
     const mergePreviewCacheRef = useRef(new Map<string, MergePreviewResponse | null>())
     const pendingMergePreviewRef = useRef(new Set<string>())
     const pendingSessionsReloadRef = useRef(false)
@@ -414,10 +423,12 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
             updateMergeInFlight(sessionId, true)
 
             try {
+                // The dialog only allows merging protected path changes once they are confirmed
                 await invoke(TauriCommands.SchaltwerkCoreMergeSessionToMain, {
                     name: sessionId,
                     mode,
                     commitMessage: commitMessage ?? null,
+                    confirmProtectedPaths: (preview?.protectedPaths?.length ?? 0) > 0,
                 })
             } catch (error) {
                 const message = getErrorMessage(error)
@@ -438,6 +449,38 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
         [updateMergeInFlight, mergeDialogStateRef]
     )
 
+    const enqueueMerge = useCallback(
+        async (sessionId: string, mode: MergeModeOption, commitMessage: string | undefined, autoRebase: boolean) => {
+            try {
+                const preview = mergeDialogStateRef.current.preview
+                const snapshot = await invoke<MergeQueueSnapshot>(TauriCommands.SchaltwerkCoreEnqueueMerge, {
+                    name: sessionId,
+                    mode,
+                    commitMessage: commitMessage ?? null,
+                    autoRebase,
+                    confirmProtectedPaths: (preview?.protectedPaths?.length ?? 0) > 0,
+                })
+                setMergeQueue(snapshot)
+                closeMergeDialog()
+                pushToastRef.current({
+                    tone: 'info',
+                    title: `Queued ${sessionId} for merge`,
+                    description: `Position ${snapshot.entries.length} in the merge queue`,
+                })
+            } catch (error) {
+                logger.error('[SessionsContext] Failed to queue merge:', error)
+                const message = getErrorMessage(error)
+                setMergeDialogState(prev => {
+                    if (!prev.isOpen || prev.sessionName !== sessionId) {
+                        return prev
+                    }
+                    return { ...prev, status: 'ready', error: message }
+                })
+            }
+        },
+        [closeMergeDialog, pushToastRef, mergeDialogStateRef]
+    )
+
     const updateAutoCancelAfterMerge = useCallback(async (next: boolean, persist: boolean = true) => {
         const previous = autoCancelAfterMergeRef.current
         applyAutoCancelPreference(next)
@@ -445,8 +488,17 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
             return
         }
         try {
+            // Re-read so the commit signing, hook and protected path choices made in Settings are kept
+            const current = await invoke<{ sign_commits?: boolean, run_git_hooks?: boolean, protected_paths?: string[] } | null>(
+                TauriCommands.GetProjectMergePreferences
+            )
             await invoke(TauriCommands.SetProjectMergePreferences, {
-                preferences: { auto_cancel_after_merge: next }
+                preferences: {
+                    auto_cancel_after_merge: next,
+                    sign_commits: current?.sign_commits !== false,
+                    run_git_hooks: current?.run_git_hooks !== false,
+                    protected_paths: current?.protected_paths ?? [],
+                }
             })
         } catch (error) {
             logger.error('[SessionsContext] Failed to update merge preferences:', error)
@@ -1208,6 +1260,27 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
             })
         })
 
+        register(SchaltEvent.SessionAgentState, (event) => {
+            const { session_id, state } = event
+            setAllSessions(prev => {
+                const targetIndex = prev.findIndex(s => s.info.session_id === session_id)
+                if (targetIndex === -1) return prev
+
+                const target = prev[targetIndex]
+                if (target.info.agent_state === state) return prev
+
+                const updated = [...prev]
+                updated[targetIndex] = {
+                    ...target,
+                    info: {
+                        ...target.info,
+                        agent_state: state
+                    }
+                }
+                return updated
+            })
+        })
+
         register(SchaltEvent.SessionGitStats, (event) => {
             logger.debug('[SessionsContext] SessionGitStats event', event)
             const {
@@ -1222,6 +1295,7 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
                 merge_is_up_to_date,
                 merge_conflicting_paths,
                 worktree_size_bytes,
+                base_freshness,
             } = event
             setAllSessions(prev => prev.map(s => {
                 if (s.info.session_id !== session_name) return s
@@ -1244,6 +1318,7 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
                         merge_conflicting_paths: merge_conflicting_paths && merge_conflicting_paths.length ? merge_conflicting_paths : s.info.merge_conflicting_paths,
                         merge_is_up_to_date: typeof merge_is_up_to_date === 'boolean' ? merge_is_up_to_date : s.info.merge_is_up_to_date,
                         worktree_size_bytes: typeof worktree_size_bytes === 'number' ? worktree_size_bytes : s.info.worktree_size_bytes,
+                        base_freshness: base_freshness ?? s.info.base_freshness,
                     }
                 }
             }))
@@ -1284,6 +1359,7 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
         })
 
         register(SchaltEvent.SessionAdded, (event) => {
+            if (isForOtherProject(event.project_path, projectPath)) return
             const { session_name, branch, worktree_path, parent_branch } = event
             const nowIso = new Date().toISOString()
             const createdAt = event.created_at ?? nowIso
@@ -1360,6 +1436,7 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
         })
 
         register(SchaltEvent.SessionRemoved, (event) => {
+            if (isForOtherProject(event.project_path, projectPath)) return
             let removed = false
             setAllSessions(prev => {
                 const exists = prev.some(s => s.info.session_id === event.session_name)
@@ -1524,7 +1601,24 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
             })
         }
 
+        const handleQueueUpdated = (snapshot: MergeQueueSnapshot) => {
+            setMergeQueue(snapshot)
+            const failure = snapshot.lastFailure
+            const failureKey = failure ? `${snapshot.repoPath}:${failure.sessionName}:${failure.message}` : null
+            const previousKey = mergeQueueFailureKeyRef.current
+            mergeQueueFailureKeyRef.current = failureKey
+            // Failures while merging are already reported by GitOperationFailed
+            if (failure && failureKey !== previousKey && failure.stage !== 'merging') {
+                pushToastRef.current({
+                    tone: 'error',
+                    title: `Merge queue skipped ${failure.sessionName}`,
+                    description: failure.message,
+                })
+            }
+        }
+
         register(SchaltEvent.GitOperationStarted, handleStarted)
+        register(SchaltEvent.MergeQueueUpdated, handleQueueUpdated)
         register(SchaltEvent.GitOperationCompleted, handleCompleted)
         register(SchaltEvent.GitOperationFailed, handleFailed)
 
@@ -1564,6 +1658,8 @@ export function SessionsProvider({ children }: { children: ReactNode }) {
             openMergeDialog,
             closeMergeDialog,
             confirmMerge,
+            enqueueMerge,
+            mergeQueue,
             isMergeInFlight,
             getMergeStatus,
             autoCancelAfterMerge,
@@ -0,0 +1,57 @@
+import { renderHook, act, waitFor } from '@testing-library/react'
+import { describe, it, expect, beforeEach, vi, MockedFunction } from 'vitest'
+import { useMcpHealth } from '../useMcpHealth'
+import { TauriCommands } from '../../common/tauriCommands'
+import { McpHealth } from '../../common/events'
+import { invoke } from '@tauri-apps/api/core'
+import { SchaltEvent } from '../../common/eventSystem'
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn()
+}))
+
+const eventHandlers: Partial<Record<SchaltEvent, (payload: unknown) => void>> = {}
+
+vi.mock('../../common/eventSystem', async () => {
+  const actual = await vi.importActual<typeof import('../../common/eventSystem')>('../../common/eventSystem')
+  return {
+    ...actual,
+    listenEvent: vi.fn(async (event: SchaltEvent, handler: (payload: unknown) => void) => {
+      eventHandlers[event] = handler
+      return () => {
+        delete eventHandlers[event]
+      }
+    })
+  }
+})
+
+describe('useMcpHealth', () => {
+  const mockInvoke = invoke as MockedFunction<typeof invoke>
+
+  beforeEach(() => {
+    vi.clearAllMocks()
+    Object.keys(eventHandlers).forEach((key) => delete eventHandlers[key as SchaltEvent])
+  })
+
+  it('loads health and follows status change events', async () => {
+    const running: McpHealth = { state: 'running', pid: 42, restartCount: 0, lastError: null }
+    mockInvoke.mockResolvedValueOnce(running)
+
+    const { result } = renderHook(() => useMcpHealth())
+
+    await waitFor(() => {
+      expect(result.current).toEqual(running)
+    })
+    expect(mockInvoke).toHaveBeenCalledWith(TauriCommands.GetMcpHealth)
+
+    const failed: McpHealth = { state: 'failed', pid: null, restartCount: 5, lastError: 'MCP server exited with exit status: 1' }
+    await waitFor(() => {
+      expect(eventHandlers[SchaltEvent.McpStatusChanged]).toBeDefined()
+    })
+    act(() => {
+      eventHandlers[SchaltEvent.McpStatusChanged]?.(failed)
+    })
+
+    expect(result.current).toEqual(failed)
+  })
+})
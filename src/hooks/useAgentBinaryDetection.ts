@@ -17,9 +17,10 @@ export interface AgentBinaryConfig {
     custom_path: string | null
     auto_detect: boolean
     detected_binaries: DetectedBinary[]
+    detected_version?: string | null
 }
 
-export type AgentType = 'claude' | 'opencode' | 'gemini' | 'codex' | 'droid' | 'qwen' | 'amp' | 'terminal'
+export type AgentType = 'claude' | 'opencode' | 'gemini' | 'codex' | 'droid' | 'qwen' | 'amp' | 'aider' | 'goose' | 'terminal'
 
 // UI agent names to backend agent names mapping
 const AGENT_TO_BINARY_MAPPING: Record<string, AgentType> = {
@@ -29,7 +30,9 @@ const AGENT_TO_BINARY_MAPPING: Record<string, AgentType> = {
     'codex': 'codex',
     'droid': 'droid',
     'qwen': 'qwen',
-    'amp': 'amp'
+    'amp': 'amp',
+    'aider': 'aider',
+    'goose': 'goose'
 }
 
 export function mapAgentToBinary(agentName: string): AgentType {
@@ -48,7 +51,9 @@ const DEFAULT_CONFIGS: Record<string, AgentBinaryConfig> = {
     'codex': { agent_name: 'codex', custom_path: null, auto_detect: true, detected_binaries: [] },
     'droid': { agent_name: 'droid', custom_path: null, auto_detect: true, detected_binaries: [] },
     'qwen': { agent_name: 'qwen', custom_path: null, auto_detect: true, detected_binaries: [] },
-    'amp': { agent_name: 'amp', custom_path: null, auto_detect: true, detected_binaries: [] }
+    'amp': { agent_name: 'amp', custom_path: null, auto_detect: true, detected_binaries: [] },
+    'aider': { agent_name: 'aider', custom_path: null, auto_detect: true, detected_binaries: [] },
+    'goose': { agent_name: 'goose', custom_path: null, auto_detect: true, detected_binaries: [] }
 }
 
 // Cache for binary configs to avoid repeated backend calls
@@ -0,0 +1,44 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listenEvent, SchaltEvent } from '../common/eventSystem'
+import { TauriCommands } from '../common/tauriCommands'
+import { McpHealth } from '../common/events'
+import { logger } from '../utils/logger'
+
+export function useMcpHealth(): McpHealth | null {
+  const [health, setHealth] = useState<McpHealth | null>(null)
+
+  useEffect(() => {
+    let mounted = true
+    let unlisten: (() => void) | null = null
+
+    invoke<McpHealth>(TauriCommands.GetMcpHealth)
+      .then((result) => {
+        if (mounted) setHealth(result)
+      })
+      .catch((error) => {
+        logger.error('[useMcpHealth] Failed to fetch MCP health', error)
+      })
+
+    listenEvent(SchaltEvent.McpStatusChanged, (payload: McpHealth) => {
+      if (mounted) setHealth(payload)
+    })
+      .then((stop) => {
+        if (mounted) {
+          unlisten = stop
+        } else {
+          stop()
+        }
+      })
+      .catch((error) => {
+        logger.error('[useMcpHealth] Failed to register MCP status listener', error)
+      })
+
+    return () => {
+      mounted = false
+      unlisten?.()
+    }
+  }, [])
+
+  return health
+}
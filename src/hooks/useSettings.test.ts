@@ -2,6 +2,7 @@ import { describe, it, expect, vi, beforeEach, MockedFunction } from 'vitest'
 import { TauriCommands } from '../common/tauriCommands'
 import { renderHook, act } from '@testing-library/react'
 import { useSettings, AgentType } from './useSettings'
+import { createAgentRecord } from '../types/session'
 import { invoke, InvokeArgs } from '@tauri-apps/api/core'
 import { KeyboardShortcutAction, KeyboardShortcutConfig, defaultShortcutConfig } from '../keyboardShortcuts/config'
 
@@ -28,6 +29,8 @@ describe('useSettings', () => {
         droid: [{ key: 'WELCOME_PROMPT', value: 'ready' }],
         qwen: [{ key: 'PROJECT_ID', value: 'test-id' }],
         amp: [{ key: 'AMP_API_KEY', value: 'amp-key' }],
+        aider: [],
+        goose: [],
         terminal: []
       }
 
@@ -39,6 +42,8 @@ describe('useSettings', () => {
         droid: '--log-level debug',
         qwen: '--project test',
         amp: '--mode free',
+        aider: '',
+        goose: '',
         terminal: ''
       }
 
@@ -86,7 +91,7 @@ describe('useSettings', () => {
         agentType: 'amp',
         cliArgs: '--mode free'
       })
-      expect(mockInvoke).toHaveBeenCalledTimes(16)
+      expect(mockInvoke).toHaveBeenCalledTimes(20)
     })
 
     it('filters out empty environment variable keys', async () => {
@@ -104,6 +109,8 @@ describe('useSettings', () => {
         droid: [],
         qwen: [],
         amp: [],
+        aider: [],
+        goose: [],
         terminal: []
       }
 
@@ -115,6 +122,8 @@ describe('useSettings', () => {
         droid: '',
         qwen: '',
         amp: '',
+        aider: '',
+        goose: '',
         terminal: ''
       }
 
@@ -136,6 +145,7 @@ describe('useSettings', () => {
       const projectSettings = {
         setupScript: 'bun install && bun run build',
         branchPrefix: 'feature',
+        testScript: ' bun run test ',
         environmentVariables: [
           { key: 'NODE_ENV', value: 'production' },
           { key: 'PORT', value: '3000' }
@@ -147,7 +157,7 @@ describe('useSettings', () => {
       })
 
       expect(mockInvoke).toHaveBeenCalledWith(TauriCommands.SetProjectSettings, {
-        settings: { setupScript: 'bun install && bun run build', branchPrefix: 'feature' }
+        settings: { setupScript: 'bun install && bun run build', branchPrefix: 'feature', testScript: 'bun run test', testTimeoutMinutes: null }
       })
       expect(mockInvoke).toHaveBeenCalledWith(TauriCommands.SetProjectEnvironmentVariables, {
         envVars: {
@@ -163,6 +173,7 @@ describe('useSettings', () => {
       const projectSettings = {
         setupScript: '',
         branchPrefix: 'feature',
+        testScript: '',
         environmentVariables: [
           { key: 'VALID', value: 'yes' },
           { key: '', value: 'no-key' }
@@ -249,6 +260,8 @@ describe('useSettings', () => {
         droid: [],
         qwen: [],
         amp: [],
+        aider: [],
+        goose: [],
         terminal: []
       }
 
@@ -260,13 +273,16 @@ describe('useSettings', () => {
         droid: '',
         qwen: '',
         amp: '',
+        aider: '',
+        goose: '',
         terminal: ''
       }
       
       const projectSettings = {
         setupScript: '',
         environmentVariables: [],
-        branchPrefix: 'feature'
+        branchPrefix: 'feature',
+        testScript: ''
       }
       
       const terminalSettings = {
@@ -281,7 +297,10 @@ describe('useSettings', () => {
       }
 
       const mergePreferences = {
-        autoCancelAfterMerge: true
+        autoCancelAfterMerge: true,
+        signCommits: true,
+        runGitHooks: true,
+        protectedPaths: ['.github/workflows']
       }
 
       const saveResult = await act(async () => {
@@ -302,7 +321,12 @@ describe('useSettings', () => {
       })
       expect(result.current.saving).toBe(false)
       expect(mockInvoke).toHaveBeenCalledWith(TauriCommands.SetProjectMergePreferences, {
-        preferences: { auto_cancel_after_merge: true }
+        preferences: {
+          auto_cancel_after_merge: true,
+          sign_commits: true,
+          run_git_hooks: true,
+          protected_paths: ['.github/workflows']
+        }
       })
     })
 
@@ -324,6 +348,8 @@ describe('useSettings', () => {
         droid: [],
         qwen: [],
         amp: [],
+        aider: [],
+        goose: [],
         terminal: []
       }
 
@@ -335,13 +361,16 @@ describe('useSettings', () => {
         droid: '',
         qwen: '',
         amp: '',
+        aider: '',
+        goose: '',
         terminal: ''
       }
       
       const projectSettings = {
         setupScript: '',
         environmentVariables: [],
-        branchPrefix: 'feature'
+        branchPrefix: 'feature',
+        testScript: ''
       }
       
       const terminalSettings = {
@@ -355,7 +384,10 @@ describe('useSettings', () => {
       }
 
       const mergePreferences = {
-        autoCancelAfterMerge: false
+        autoCancelAfterMerge: false,
+        signCommits: true,
+        runGitHooks: true,
+        protectedPaths: ['.github/workflows']
       }
 
       const saveResult = await act(async () => {
@@ -394,6 +426,8 @@ describe('useSettings', () => {
         droid: [],
         qwen: [],
         amp: [],
+        aider: [],
+        goose: [],
         terminal: []
       }
 
@@ -405,13 +439,16 @@ describe('useSettings', () => {
         droid: '',
         qwen: '',
         amp: '',
+        aider: '',
+        goose: '',
         terminal: ''
       }
 
       const projectSettings = {
         setupScript: '',
         environmentVariables: [],
-        branchPrefix: 'feature'
+        branchPrefix: 'feature',
+        testScript: ''
       }
 
       const terminalSettings = {
@@ -425,7 +462,10 @@ describe('useSettings', () => {
       }
 
       const mergePreferences = {
-        autoCancelAfterMerge: false
+        autoCancelAfterMerge: false,
+        signCommits: true,
+        runGitHooks: true,
+        protectedPaths: ['.github/workflows']
       }
 
       const saveResult = await act(async () => {
@@ -445,13 +485,41 @@ describe('useSettings', () => {
         failedSettings: []
       })
     })
+
+    it('reports project settings as failed when the branch prefix is rejected', async () => {
+      const { result } = renderHook(() => useSettings())
+
+      mockInvoke.mockImplementation((command: string) => {
+        if (command === TauriCommands.SetProjectSettings) {
+          return Promise.reject(new Error("Failed to set project branch prefix: Invalid branch prefix 'feat..x'"))
+        }
+        return Promise.resolve()
+      })
+
+      const envVars = createAgentRecord<Array<{key: string, value: string}>>(() => [])
+      const cliArgs = createAgentRecord<string>(() => '')
+
+      const saveResult = await act(async () => {
+        return await result.current.saveAllSettings(
+          envVars,
+          cliArgs,
+          { setupScript: '', environmentVariables: [], branchPrefix: 'feat..x', testScript: '' },
+          { shell: null, shellArgs: [] },
+          { auto_commit_on_review: false, skip_confirmation_modals: false },
+          { autoCancelAfterMerge: false, signCommits: true, runGitHooks: true, protectedPaths: [] }
+        )
+      })
+
+      expect(saveResult.success).toBe(false)
+      expect(saveResult.failedSettings).toEqual(['project settings'])
+    })
   })
 
   describe('merge preferences', () => {
     it('loads merge preferences from backend', async () => {
       mockInvoke.mockImplementation(async (command: string) => {
         if (command === TauriCommands.GetProjectMergePreferences) {
-          return { auto_cancel_after_merge: true }
+          return { auto_cancel_after_merge: true, sign_commits: false, run_git_hooks: false, protected_paths: ['infra'] }
         }
         return null
       })
@@ -462,7 +530,7 @@ describe('useSettings', () => {
         return await result.current.loadMergePreferences()
       })
 
-      expect(prefs).toEqual({ autoCancelAfterMerge: true })
+      expect(prefs).toEqual({ autoCancelAfterMerge: true, signCommits: false, runGitHooks: false, protectedPaths: ['infra'] })
     })
 
     it('defaults auto-cancel to true when backend omits preference', async () => {
@@ -479,7 +547,7 @@ describe('useSettings', () => {
         return await result.current.loadMergePreferences()
       })
 
-      expect(prefs).toEqual({ autoCancelAfterMerge: true })
+      expect(prefs).toEqual({ autoCancelAfterMerge: true, signCommits: true, runGitHooks: true, protectedPaths: [] })
     })
   })
 
@@ -513,6 +581,8 @@ describe('useSettings', () => {
         droid: [],
         qwen: [],
         amp: [],
+        aider: [],
+        goose: [],
         terminal: []
       })
       expect(result.current.loading).toBe(false)
@@ -535,6 +605,8 @@ describe('useSettings', () => {
         droid: [],
         qwen: [],
         amp: [],
+        aider: [],
+        goose: [],
         terminal: []
       })
     })
@@ -570,6 +642,8 @@ describe('useSettings', () => {
         droid: '',
         qwen: '',
         amp: '',
+        aider: '',
+        goose: '',
         terminal: ''
       })
     })
@@ -591,6 +665,8 @@ describe('useSettings', () => {
         droid: '',
         qwen: '',
         amp: '',
+        aider: '',
+        goose: '',
         terminal: ''
       })
     })
@@ -600,7 +676,7 @@ describe('useSettings', () => {
     it('loads project settings and environment variables', async () => {
       mockInvoke.mockImplementation((command: string) => {
         if (command === TauriCommands.GetProjectSettings) {
-          return Promise.resolve({ setupScript: 'bun install', branchPrefix: 'team' })
+          return Promise.resolve({ setupScript: 'bun install', branchPrefix: 'team', testScript: 'bun run test', testTimeoutMinutes: 20 })
         }
         if (command === 'get_project_environment_variables') {
           return Promise.resolve({ NODE_ENV: 'test', DEBUG: 'true' })
@@ -617,6 +693,8 @@ describe('useSettings', () => {
       expect(settings).toEqual({
         setupScript: 'bun install',
         branchPrefix: 'team',
+        testScript: 'bun run test',
+        testTimeoutMinutes: 20,
         environmentVariables: [
           { key: 'NODE_ENV', value: 'test' },
           { key: 'DEBUG', value: 'true' }
@@ -636,6 +714,7 @@ describe('useSettings', () => {
       expect(settings).toEqual({
         setupScript: '',
         branchPrefix: 'schaltwerk',
+        testScript: '',
         environmentVariables: []
       })
     })
@@ -660,6 +739,8 @@ describe('useSettings', () => {
       expect(settings).toEqual({
         setupScript: '',
         branchPrefix: 'schaltwerk',
+        testScript: '',
+        testTimeoutMinutes: null,
         environmentVariables: []
       })
     })
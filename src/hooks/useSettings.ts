@@ -10,7 +10,7 @@ import {
     normalizeShortcutConfig,
     PartialKeyboardShortcutConfig,
 } from '../keyboardShortcuts/config'
-import { AgentType, AGENT_TYPES, createAgentRecord } from '../types/session'
+import { AgentType, AGENT_TYPES, BranchPrefixMigration, createAgentRecord } from '../types/session'
 
 export type { AgentType }
 type EnvVars = Record<string, string>
@@ -18,6 +18,8 @@ type EnvVars = Record<string, string>
 interface ProjectSettings {
     setupScript: string
     branchPrefix: string
+    testScript: string
+    testTimeoutMinutes?: number | null
     environmentVariables: Array<{key: string, value: string}>
 }
 
@@ -54,6 +56,7 @@ interface TerminalSettings {
     shellArgs: string[]
     fontFamily?: string | null
     webglEnabled?: boolean
+    theme?: string | null
 }
 
 interface SessionPreferences {
@@ -63,12 +66,16 @@ interface SessionPreferences {
 
 export interface ProjectMergePreferences {
     autoCancelAfterMerge: boolean
+    signCommits: boolean
+    runGitHooks: boolean
+    protectedPaths: string[]
 }
 
 export interface SettingsSaveResult {
     success: boolean
     savedSettings: string[]
     failedSettings: string[]
+    branchPrefixMigration?: BranchPrefixMigration
 }
 
 export const useSettings = () => {
@@ -93,13 +100,13 @@ export const useSettings = () => {
         }
     }, [])
     
-    const saveProjectSettings = useCallback(async (projectSettings: ProjectSettings): Promise<void> => {
+    const saveProjectSettings = useCallback(async (projectSettings: ProjectSettings): Promise<BranchPrefixMigration | undefined> => {
         const trimmed = projectSettings.branchPrefix.trim()
         const withoutWhitespace = trimmed.replace(/\s+/g, '-')
         const normalized = withoutWhitespace.replace(/^\/+|\/+$/g, '')
         const branchPrefix = normalized || DEFAULT_BRANCH_PREFIX
 
-        await invoke(TauriCommands.SetProjectSettings, { settings: { setupScript: projectSettings.setupScript, branchPrefix } })
+        const migration = await invoke<BranchPrefixMigration | undefined>(TauriCommands.SetProjectSettings, { settings: { setupScript: projectSettings.setupScript, branchPrefix, testScript: projectSettings.testScript.trim(), testTimeoutMinutes: projectSettings.testTimeoutMinutes ?? null } })
         
         const projectEnvVarsObject = projectSettings.environmentVariables.reduce((acc, { key, value }) => {
             if (key) acc[key] = value
@@ -107,6 +114,7 @@ export const useSettings = () => {
         }, {} as Record<string, string>)
         
         await invoke(TauriCommands.SetProjectEnvironmentVariables, { envVars: projectEnvVarsObject })
+        return migration
     }, [])
     
     const saveTerminalSettings = useCallback(async (terminalSettings: TerminalSettings): Promise<void> => {
@@ -131,7 +139,10 @@ export const useSettings = () => {
     const saveMergePreferences = useCallback(async (mergePreferences: ProjectMergePreferences): Promise<void> => {
         await invoke(TauriCommands.SetProjectMergePreferences, {
             preferences: {
-                auto_cancel_after_merge: mergePreferences.autoCancelAfterMerge
+                auto_cancel_after_merge: mergePreferences.autoCancelAfterMerge,
+                sign_commits: mergePreferences.signCommits,
+                run_git_hooks: mergePreferences.runGitHooks,
+                protected_paths: mergePreferences.protectedPaths
             }
         })
     }, [])
@@ -157,11 +168,17 @@ export const useSettings = () => {
             failedSettings.push('agent configurations')
         }
         
+        let branchPrefixMigration: BranchPrefixMigration | undefined
         try {
-            await saveProjectSettings(projectSettings)
+            branchPrefixMigration = await saveProjectSettings(projectSettings)
             savedSettings.push('project settings')
         } catch (error) {
-            logger.info('Project settings not saved - requires active project', error)
+            if (isProjectUnavailableError(error)) {
+                logger.info('Project settings not saved - requires active project', error)
+            } else {
+                logger.error('Failed to save project settings:', error)
+                failedSettings.push('project settings')
+            }
         }
         
         try {
@@ -198,7 +215,8 @@ export const useSettings = () => {
         return {
             success: failedSettings.length === 0,
             savedSettings,
-            failedSettings
+            failedSettings,
+            branchPrefixMigration
         }
     }, [saveAgentSettings, saveProjectSettings, saveTerminalSettings, saveSessionPreferences, saveMergePreferences])
     
@@ -239,18 +257,22 @@ export const useSettings = () => {
             return {
                 setupScript: settings?.setupScript || '',
                 branchPrefix: settings?.branchPrefix || DEFAULT_BRANCH_PREFIX,
+                testScript: settings?.testScript || '',
+                testTimeoutMinutes: settings?.testTimeoutMinutes ?? null,
                 environmentVariables: envVarArray
             }
         } catch (error) {
             logger.error('Failed to load project settings:', error)
-            return { setupScript: '', branchPrefix: DEFAULT_BRANCH_PREFIX, environmentVariables: [] }
+            return { setupScript: '', branchPrefix: DEFAULT_BRANCH_PREFIX, testScript: '', environmentVariables: [] }
         }
     }, [])
     
     const loadTerminalSettings = useCallback(async (): Promise<TerminalSettings> => {
         try {
             const settings = await invoke<TerminalSettings>(TauriCommands.GetTerminalSettings)
+            // Keep the fields this form does not edit so saving does not reset them
             return {
+                ...settings,
                 shell: settings?.shell || null,
                 shellArgs: settings?.shellArgs || [],
                 fontFamily: settings?.fontFamily ?? null,
@@ -274,15 +296,18 @@ export const useSettings = () => {
 
     const loadMergePreferences = useCallback(async (): Promise<ProjectMergePreferences> => {
         try {
-            const preferences = await invoke<{ auto_cancel_after_merge: boolean }>(
+            const preferences = await invoke<{ auto_cancel_after_merge: boolean, sign_commits?: boolean, run_git_hooks?: boolean, protected_paths?: string[] }>(
                 TauriCommands.GetProjectMergePreferences
             )
             return {
-                autoCancelAfterMerge: preferences?.auto_cancel_after_merge !== false
+                autoCancelAfterMerge: preferences?.auto_cancel_after_merge !== false,
+                signCommits: preferences?.sign_commits !== false,
+                runGitHooks: preferences?.run_git_hooks !== false,
+                protectedPaths: preferences?.protected_paths ?? []
             }
         } catch (error) {
             logger.error('Failed to load project merge preferences:', error)
-            return { autoCancelAfterMerge: true }
+            return { autoCancelAfterMerge: true, signCommits: true, runGitHooks: true, protectedPaths: [] }
         }
     }, [])
 
@@ -17,6 +17,7 @@ const createEvent = (key: string, options: Partial<KeyboardEvent> = {}) => {
 describe('keyboard shortcut matcher', () => {
   it('normalizes shortcut casing and whitespace', () => {
     expect(normalizeShortcut('  mod + Shift + d ')).toEqual('Mod+Shift+D')
+    expect(normalizeShortcut('mod+k  Mod + shift+s')).toEqual('Mod+K Mod+Shift+S')
   })
 
   it('matches primary modifier on mac using meta key', () => {
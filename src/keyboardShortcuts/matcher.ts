@@ -151,7 +151,19 @@ const buildModifierFlags = (set: Set<string>, platform: Platform) => {
   }
 }
 
-export const normalizeShortcut = (shortcut: string): string => {
+// Chords are combinations pressed one after another, separated by spaces ("Mod+K Mod+S").
+// Spaces around "+" stay part of a single combination.
+export const normalizeShortcut = (shortcut: string): string =>
+  (shortcut || '')
+    .split('+')
+    .map(token => token.trim())
+    .join('+')
+    .split(/\s+/)
+    .map(normalizeCombo)
+    .filter(Boolean)
+    .join(' ')
+
+const normalizeCombo = (shortcut: string): string => {
   const { key, required, optional } = parseShortcut(shortcut)
 
   if (!key && required.size === 0 && optional.size === 0) {
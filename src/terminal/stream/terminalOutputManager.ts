@@ -11,6 +11,8 @@ type TerminalStreamListener = (chunk: string) => void
 interface TerminalBufferResponse {
   seq: number
   startSeq: number
+  // Equals the requested from_seq when only new bytes were returned
+  fromSeq: number
   data: string
 }
 
@@ -16,8 +16,16 @@ interface SubscribeDelta {
   seq: number
 }
 
+interface SubscribeDeltaChunk {
+  term_id: string
+  from_seq: number
+  seq: number
+  base64: string
+}
+
 type SubscribeResponse =
   | { Snapshot: SubscribeSnapshot }
+  | { Delta: SubscribeDeltaChunk }
   | { DeltaReady: SubscribeDelta }
 
 function decodeBase64(base64: string): Uint8Array {
@@ -93,6 +101,13 @@ export class PluginTransport implements TerminalTransport {
       if (bytes.length > 0) {
         onData({ seq: snapshot.seq, bytes })
       }
+    } else if ('Delta' in response) {
+      const delta = response.Delta
+      const bytes = decodeBase64(delta.base64)
+      this.highestSeq.set(termId, delta.seq)
+      if (bytes.length > 0) {
+        onData({ seq: delta.seq, bytes })
+      }
     } else if ('DeltaReady' in response) {
       this.highestSeq.set(termId, response.DeltaReady.seq)
     }
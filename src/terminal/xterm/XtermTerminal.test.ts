@@ -156,5 +156,10 @@ describe('XtermTerminal wrapper', () => {
     wrapper.updateOptions({ disableStdin: false, scrollback: 8000 })
     expect(instance.options.disableStdin).toBe(false)
     expect(instance.options.scrollback).toBe(8000)
+
+    wrapper.applyConfig({ themeColors: { background: '#002b36' } })
+    const themed = instance.options.theme as Record<string, string>
+    expect(themed.background).toBe('#002b36')
+    expect(themed.brightWhite).toBeDefined()
   })
 })
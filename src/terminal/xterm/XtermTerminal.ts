@@ -13,6 +13,8 @@ export interface XtermTerminalConfig {
   fontFamily: string
   readOnly: boolean
   minimumContrastRatio: number
+  // Colors of the selected terminal theme, laid over the app palette
+  themeColors?: Record<string, string>
 }
 
 export interface XtermTerminalOptions {
@@ -22,7 +24,7 @@ export interface XtermTerminalOptions {
 
 type TerminalTheme = NonNullable<ITerminalOptions['theme']>
 
-function buildTheme(): TerminalTheme {
+function buildTheme(overrides: Record<string, string> = {}): TerminalTheme {
   return {
     background: theme.colors.background.secondary,
     foreground: theme.colors.text.primary,
@@ -44,12 +46,13 @@ function buildTheme(): TerminalTheme {
     brightMagenta: theme.colors.accent.purple.light,
     brightCyan: theme.colors.accent.cyan.light,
     brightWhite: theme.colors.text.primary,
+    ...overrides,
   }
 }
 
 function buildTerminalOptions(config: XtermTerminalConfig): ITerminalOptions {
   return {
-    theme: buildTheme(),
+    theme: buildTheme(config.themeColors),
     fontFamily: config.fontFamily,
     fontSize: config.fontSize,
     cursorBlink: true,
@@ -161,6 +164,10 @@ export class XtermTerminal {
     if (partial.minimumContrastRatio !== undefined) {
       this.raw.options.minimumContrastRatio = next.minimumContrastRatio
     }
+
+    if (partial.themeColors !== undefined) {
+      this.raw.options.theme = buildTheme(next.themeColors)
+    }
   }
 
   updateOptions(options: Partial<ITerminalOptions>): void {
@@ -27,3 +27,16 @@ export interface ReviewCommentThread {
   }
   comments: ReviewComment[]
 }
+
+// Review comment as stored for a session by the backend
+export interface StoredReviewComment {
+  id: string
+  file_path: string
+  line_start: number
+  line_end: number
+  side: 'old' | 'new'
+  selected_text: string
+  comment: string
+  created_at: string
+  resolved_at: string | null
+}
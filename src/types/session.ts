@@ -1,4 +1,4 @@
-export const AGENT_TYPES = ['claude', 'opencode', 'gemini', 'codex', 'droid', 'qwen', 'amp', 'terminal'] as const
+export const AGENT_TYPES = ['claude', 'opencode', 'gemini', 'codex', 'droid', 'qwen', 'amp', 'aider', 'goose', 'terminal'] as const
 export type AgentType = (typeof AGENT_TYPES)[number]
 
 export const AGENT_SUPPORTS_SKIP_PERMISSIONS: Record<AgentType, boolean> = {
@@ -9,6 +9,8 @@ export const AGENT_SUPPORTS_SKIP_PERMISSIONS: Record<AgentType, boolean> = {
     droid: true,
     qwen: true,
     amp: true,
+    aider: true,
+    goose: true,
     terminal: false
 }
 
@@ -53,10 +55,32 @@ export interface SessionInfo {
     ready_to_merge?: boolean
     spec_content?: string
     original_agent_type?: AgentType
+    agent_model?: string
     diff_stats?: DiffStats
     top_uncommitted_paths?: string[]
     attention_required?: boolean
+    agent_state?: AgentActivityState
     worktree_size_bytes?: number
+    labels?: string[]
+    depends_on?: string[]
+    blocked_by?: string[]
+    auto_nudge_disabled?: boolean
+    remote_status?: SessionRemoteStatus
+    base_freshness?: BaseFreshness
+    usage?: AgentUsage
+}
+
+export interface BaseFreshness {
+    ahead: number
+    behind: number
+    checked_at: string
+}
+
+export interface SessionRemoteStatus {
+    remote: string
+    pushed_commit: string
+    pushed_at: string
+    pull_request_url?: string | null
 }
 
 export interface DiffStats {
@@ -99,11 +123,13 @@ export interface RawSession {
     initial_prompt?: string
     ready_to_merge: boolean
     original_agent_type?: AgentType
+    agent_model?: string
     original_skip_permissions?: boolean
     pending_name_generation: boolean
     was_auto_generated: boolean
     spec_content?: string
     session_state: 'spec' | 'running' | 'reviewed'
+    labels?: string[]
     git_stats?: {
         files_changed: number
         additions: number
@@ -111,3 +137,219 @@ export interface RawSession {
         insertions: number
     }
 }
+
+export interface SessionTimeTotals {
+    session_name: string
+    active_seconds: number
+    wall_clock_seconds: number
+}
+
+export interface DailyTimeTotals {
+    day: string
+    active_seconds: number
+    sessions: Record<string, number>
+}
+
+export interface SessionTimeReport {
+    sessions: SessionTimeTotals[]
+    days: DailyTimeTotals[]
+}
+
+export type AgentActivityState = 'thinking' | 'editing_files' | 'waiting_for_approval' | 'done'
+
+export interface AgentUsage {
+    input_tokens: number
+    output_tokens: number
+    cache_read_tokens: number
+    cache_write_tokens: number
+    cost_usd: number
+}
+
+export interface SessionCostTotals extends AgentUsage {
+    session_name: string
+}
+
+export interface SessionCostReport {
+    sessions: SessionCostTotals[]
+    total: AgentUsage
+}
+
+export interface PromptTemplate {
+    id: string
+    name: string
+    content: string
+    repository_path: string | null
+    created_at: string
+    updated_at: string
+}
+
+export type PromptTemplateTarget = 'initial_prompt' | 'follow_up'
+
+export interface StaleSession {
+    session_name: string
+    last_activity: string
+    idle_days: number
+    has_unmerged_work: boolean
+}
+
+export type StaleSessionAction = 'label' | 'cancel'
+
+export interface ProjectStaleSessionPolicy {
+    enabled: boolean
+    idleDays: number
+    action: StaleSessionAction
+}
+
+export interface ProjectOutboundWebhookConfig {
+    urls: string[]
+    sessionReviewed: boolean
+    mergeCompleted: boolean
+    agentCrashed: boolean
+}
+
+export type SettingsLayer = 'global' | 'project' | 'session'
+
+export interface SettingsOverrides {
+    agent_cli_args?: Record<string, string>
+}
+
+export interface EffectiveValue<T> {
+    value: T
+    source: SettingsLayer
+}
+
+export interface EffectiveSettings {
+    agent_type: string
+    agent_cli_args: EffectiveValue<string>
+    env_vars: Record<string, EffectiveValue<string>>
+}
+
+export interface EnvVarEntry {
+    key: string
+    // Empty for secrets when read back; send a value only to replace the stored one
+    value: string
+    secret: boolean
+}
+
+export interface EnvVarSet {
+    name: string
+    vars: EnvVarEntry[]
+    agents: string[]
+}
+
+export interface TerminalTheme {
+    name: string
+    // xterm theme keys (`background`, `brightRed`, ...) to hex colors
+    colors: Record<string, string>
+    builtin: boolean
+}
+
+export interface FontValidation {
+    family: string | null
+    installed: boolean
+    monospace: boolean
+    ligatures: boolean
+    fallback: string | null
+}
+
+export interface ReviewChecklistItem {
+    text: string
+    checked: boolean
+}
+
+export interface SessionNotes {
+    notes: string
+    checklist: ReviewChecklistItem[]
+    updated_at?: string | null
+}
+
+export type SessionRebaseOutcome =
+    | { status: 'up_to_date'; base_commit: string }
+    | { status: 'rebased'; base_commit: string; head_commit: string }
+    | { status: 'conflicts'; base_commit: string; conflicting_paths: string[] }
+
+export interface CherryPickOutcome {
+    sessionBranch: string
+    parentBranch: string
+    newCommits: string[]
+}
+
+export interface WorktreeHunk {
+    id: string
+    header: string
+    old_start: number
+    old_lines: number
+    new_start: number
+    new_lines: number
+}
+
+export interface BranchPrefixMigration {
+    prefix: string
+    renamed: string[]
+    skipped: string[]
+}
+
+export interface WorktreeDiskUsage {
+    session_name: string
+    worktree_path: string
+    cancelled: boolean
+    total_bytes: number
+    artifact_bytes: number
+}
+
+export interface PrunedPath {
+    path: string
+    session_name: string | null
+    bytes: number
+}
+
+export interface WorktreePruneReport {
+    dry_run: boolean
+    orphaned_worktrees: PrunedPath[]
+    build_artifacts: PrunedPath[]
+    reclaimed_bytes: number
+}
+
+export interface MergeDryRun {
+    sessionBranch: string
+    parentBranch: string
+    hasConflicts: boolean
+    conflictingPaths: string[]
+    isUpToDate: boolean
+    canFastForward: boolean
+    filesChanged: number
+    insertions: number
+    deletions: number
+}
+
+export interface SessionOverlap {
+    sessionName: string
+    sessionBranch: string
+    overlappingPaths: string[]
+    conflictingPaths: string[]
+}
+
+export type MergeQueueStage = 'queued' | 'rebasing' | 'testing' | 'merging'
+
+export interface MergeQueueEntry {
+    sessionName: string
+    mode: 'squash' | 'reapply' | 'fast_forward'
+    commitMessage: string | null
+    autoRebase: boolean
+    confirmProtectedPaths: boolean
+    stage: MergeQueueStage
+    enqueuedAt: string
+}
+
+export interface MergeQueueFailure {
+    sessionName: string
+    stage: MergeQueueStage
+    message: string
+}
+
+export interface MergeQueueSnapshot {
+    repoPath: string
+    entries: MergeQueueEntry[]
+    running: boolean
+    lastFailure: MergeQueueFailure | null
+}
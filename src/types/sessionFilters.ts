@@ -22,6 +22,11 @@ export function isValidSortMode(value: unknown): value is SortMode {
     return typeof value === 'string' && SORT_MODES.includes(value as SortMode)
 }
 
+// Backend filter value selecting sessions that carry the given label
+export function labelFilterValue(label: string): string {
+    return `label:${label}`
+}
+
 export function getDefaultFilterMode(): FilterMode {
     return FilterMode.All
 }
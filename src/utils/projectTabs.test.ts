@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest'
-import { determineNextActiveTab, ProjectTab } from '../common/projectTabs'
+import { determineNextActiveTab, isForOtherProject, ProjectTab } from '../common/projectTabs'
 
 describe('determineNextActiveTab', () => {
   const tabs: ProjectTab[] = [
@@ -34,3 +34,12 @@ describe('determineNextActiveTab', () => {
     expect(result).toBeNull()
   })
 })
+
+describe('isForOtherProject', () => {
+  it('only flags events scoped to a different project', () => {
+    expect(isForOtherProject('/projects/alpha', '/projects/alpha/')).toBe(false)
+    expect(isForOtherProject('/projects/bravo', '/projects/alpha')).toBe(true)
+    expect(isForOtherProject(undefined, '/projects/alpha')).toBe(false)
+    expect(isForOtherProject('/projects/alpha', null)).toBe(false)
+  })
+})